@@ -0,0 +1,98 @@
+//! Hand ranking with and without `EvalCache`, on the work the equity
+//! features do: a street's equities worked out again after each action,
+//! as the feed does, and many hands' runouts ranked, as the luck report
+//! does. `cargo bench --bench eval_cache` prints the times and the cache's
+//! hit rate.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use transparent_poker::engine::{eval_cache_stats, rank_hand, rank_hand_cached};
+use transparent_poker::events::Card;
+
+/// Times a street is worked out again, once per action on it.
+const RECOUNTS: usize = 8;
+
+fn deck() -> Vec<Card> {
+	"shdc"
+		.chars()
+		.flat_map(|suit| "23456789TJQKA".chars().map(move |rank| Card::new(rank, suit)))
+		.collect()
+}
+
+/// Three hands and a flop, and the cards left to come.
+struct Spot {
+	hands: Vec<[Card; 2]>,
+	flop: Vec<Card>,
+	stub: Vec<Card>,
+}
+
+fn deal(rng: &mut StdRng) -> Spot {
+	let mut cards = deck();
+	cards.shuffle(rng);
+	Spot {
+		hands: (0..3).map(|i| [cards[2 * i], cards[2 * i + 1]]).collect(),
+		flop: cards[6..9].to_vec(),
+		stub: cards[9..].to_vec(),
+	}
+}
+
+/// Every turn and river for each hand, ranked with `rank`.
+fn run_out(spot: &Spot, rank: impl Fn(&[Card; 2], &[Card]) -> rs_poker::core::Rank) {
+	let mut board = spot.flop.clone();
+	for (i, &turn) in spot.stub.iter().enumerate() {
+		for &river in &spot.stub[i + 1..] {
+			board.truncate(3);
+			board.extend([turn, river]);
+			for hand in &spot.hands {
+				black_box(rank(hand, &board));
+			}
+		}
+	}
+}
+
+fn time(label: &str, work: impl Fn()) -> Duration {
+	let start = Instant::now();
+	work();
+	let took = start.elapsed();
+	println!("{:<40} {:>8.1} ms", label, took.as_secs_f64() * 1000.0);
+	took
+}
+
+fn compare(label: &str, spots: &[Spot], recounts: usize) {
+	println!("{}", label);
+	let plain = time("  rank_hand", || {
+		for spot in spots {
+			for _ in 0..recounts {
+				run_out(spot, rank_hand);
+			}
+		}
+	});
+	let before = eval_cache_stats();
+	let cached = time("  rank_hand_cached", || {
+		for spot in spots {
+			for _ in 0..recounts {
+				run_out(spot, rank_hand_cached);
+			}
+		}
+	});
+	let after = eval_cache_stats();
+	let hits = after.hits - before.hits;
+	let looked_up = hits + after.misses - before.misses;
+	println!(
+		"  {:.1}x the speed of rank_hand, {:.0}% hits, {} ranks held\n",
+		plain.as_secs_f64() / cached.as_secs_f64(),
+		100.0 * hits as f64 / looked_up.max(1) as f64,
+		after.len,
+	);
+}
+
+fn main() {
+	let mut rng = StdRng::seed_from_u64(7);
+	let spots = (0..20).map(|_| deal(&mut rng)).collect::<Vec<_>>();
+	compare(&format!("One flop's runouts, worked out {} times", RECOUNTS), &spots[..1], RECOUNTS);
+	compare("Twenty flops' runouts, once each", &spots, 1);
+}
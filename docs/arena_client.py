@@ -0,0 +1,60 @@
+#!/usr/bin/env python3
+"""Reference client for the bot arena (`poker arena`).
+
+    python3 arena_client.py --table piece-of-string --name PyBot
+
+Replace `decide` with your own logic. The same function works for a
+subprocess bot; only the registration handshake differs.
+"""
+
+import argparse
+import json
+import socket
+
+
+def decide(valid, state):
+    """Check when free, call small bets, fold the rest."""
+    if valid["can_check"]:
+        return "Check"
+    call = valid.get("call_amount")
+    if call is not None and call <= state["pot"] / 2:
+        return {"Call": {"amount": call}}
+    return "Fold"
+
+
+def play(host, port, name, table):
+    with socket.create_connection((host, port)) as sock:
+        reader = sock.makefile("r", encoding="utf-8")
+        writer = sock.makefile("w", encoding="utf-8")
+
+        def send(message):
+            writer.write(json.dumps(message) + "\n")
+            writer.flush()
+
+        send({"type": "register", "name": name, "table": table})
+
+        for line in reader:
+            msg = json.loads(line)
+            kind = msg["type"]
+            if kind == "error":
+                raise SystemExit(msg["message"])
+            elif kind == "registered":
+                print(f"Waiting at {msg['table']}: {msg['waiting']}/{msg['seats']}")
+            elif kind == "game_start":
+                print(f"Seat {msg['seat']} against {msg['players']}")
+            elif kind == "action_request":
+                send(decide(msg["valid_actions"], msg["state"]))
+            elif kind == "game_over":
+                for s in msg["standings"]:
+                    print(f"{s['finish_position']}. {s['name']} ${s['final_stack']:.0f}")
+            # "event" lines carry every GameEvent this seat may see
+
+
+if __name__ == "__main__":
+    parser = argparse.ArgumentParser()
+    parser.add_argument("--host", default="127.0.0.1")
+    parser.add_argument("--port", type=int, default=9998)
+    parser.add_argument("--name", default="PyBot")
+    parser.add_argument("--table", required=True)
+    args = parser.parse_args()
+    play(args.host, args.port, args.name, args.table)
@@ -0,0 +1,37 @@
+//! Arbitrary bytes off the wire, split into reads the way a socket might
+//! hand them over. Whatever comes in, the decoder has to keep making
+//! progress: every `Some` consumes bytes, and it only says `None` with
+//! less than a whole frame left.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use transparent_poker::net::protocol::{decode_frame, ClientMessage, ServerMessage, FRAME_HEADER_LEN};
+
+const MAX_LEN: usize = 64 * 1024;
+
+fn drain<T: serde::de::DeserializeOwned>(data: &[u8]) {
+	// The first byte sets the read size, so the fuzzer finds splits
+	let Some((&chunk, data)) = data.split_first() else {
+		return;
+	};
+	let mut buf = Vec::new();
+	for read in data.chunks(chunk.max(1) as usize) {
+		buf.extend_from_slice(read);
+		loop {
+			let before = buf.len();
+			match decode_frame::<T>(&mut buf, MAX_LEN) {
+				Some(_) => assert!(buf.len() < before, "decoded without consuming anything"),
+				None => break,
+			}
+		}
+	}
+	if buf.len() > FRAME_HEADER_LEN + MAX_LEN {
+		panic!("waiting on {} bytes, more than a frame", buf.len());
+	}
+}
+
+fuzz_target!(|data: &[u8]| {
+	drain::<ClientMessage>(data);
+	drain::<ServerMessage>(data);
+});
@@ -0,0 +1,19 @@
+//! A frame's payload decoded as a `ServerMessage`, without the frame and
+//! its checksum the fuzzer would otherwise have to guess. Whatever
+//! decodes has to come back out of a frame of its own.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use transparent_poker::net::protocol::{decode_frame, decode_payload, encode_message, ServerMessage, MAX_FRAME_LEN};
+
+fuzz_target!(|data: &[u8]| {
+	let Ok(msg) = decode_payload::<ServerMessage>(data) else {
+		return;
+	};
+	let mut buf = encode_message(&msg);
+	match decode_frame::<ServerMessage>(&mut buf, MAX_FRAME_LEN) {
+		Some(Ok(_)) => assert!(buf.is_empty()),
+		other => panic!("{:?} didn't come back out of a frame: {:?}", msg, other.map(|r| r.err())),
+	}
+});
@@ -0,0 +1,322 @@
+//! Achievements: milestones spotted in a player's own game events, kept
+//! in `achievements.toml` in their profile directory so they last from
+//! one session to the next. `Achievements::observe` follows the events
+//! from the hero's seat and says what each one unlocked; the game puts
+//! that in the chat, and the lobby's profile screen lists them all.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{profile_dir, profile_key};
+use crate::error::PokerError;
+use crate::events::{Card, GameEvent, PlayerAction, Seat};
+
+/// Hands won for `Achievement::HundredHands`.
+pub const HANDS_TO_WIN: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Achievement {
+	/// A royal flush with at least one of the hero's own cards in it.
+	RoyalFlush,
+	/// Hands won, all told.
+	HundredHands,
+	/// A sit & go won without going to a single showdown.
+	TournamentWithoutShowdown,
+}
+
+impl Achievement {
+	pub const ALL: [Achievement; 3] =
+		[Achievement::RoyalFlush, Achievement::HundredHands, Achievement::TournamentWithoutShowdown];
+
+	/// Its catalog key, under `[achievement]`; `<key>_about` says how it's
+	/// earned.
+	pub fn key(self) -> &'static str {
+		match self {
+			Achievement::RoyalFlush => "royal_flush",
+			Achievement::HundredHands => "hundred_hands",
+			Achievement::TournamentWithoutShowdown => "no_showdown",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct AchievementsFile {
+	/// Hands won so far, toward `HundredHands`.
+	hands_won: u32,
+	/// When each was unlocked, `YYYY-MM-DD`.
+	unlocked: BTreeMap<Achievement, String>,
+}
+
+/// The hand in play, from the hero's seat.
+#[derive(Debug, Clone, Default)]
+struct HandState {
+	cards: Option<[Card; 2]>,
+	board: Vec<Card>,
+	folded: bool,
+}
+
+/// One player's achievements, and the game they're playing.
+#[derive(Debug, Clone, Default)]
+pub struct Achievements {
+	file: AchievementsFile,
+	/// Where they're saved; unset keeps them for this session only.
+	path: Option<PathBuf>,
+	changed: bool,
+	hero: Option<Seat>,
+	tournament: bool,
+	hand: HandState,
+	/// The hero has been to a showdown in this game.
+	showed_down: bool,
+}
+
+impl Achievements {
+	/// `player`'s achievements from their profile directory, none yet if
+	/// there's no file. A name that can't be a profile keeps them for this
+	/// session only.
+	pub fn load(player: &str) -> Result<Self, PokerError> {
+		match profile_key(player).ok().and_then(|key| profile_dir(&key)) {
+			Some(dir) => Self::load_from(dir.join("achievements.toml")),
+			None => Ok(Self::default()),
+		}
+	}
+
+	pub fn load_from(path: impl Into<PathBuf>) -> Result<Self, PokerError> {
+		let path = path.into();
+		let file = match fs::read_to_string(&path) {
+			Ok(content) => toml::from_str(&content).map_err(|e| PokerError::parse("achievements", e))?,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => AchievementsFile::default(),
+			Err(e) => return Err(PokerError::io("read", &path, e)),
+		};
+		Ok(Self { file, path: Some(path), ..Self::default() })
+	}
+
+	/// Saves what's changed since the last save.
+	pub fn save(&mut self) -> Result<(), PokerError> {
+		let Some(path) = self.path.as_deref().filter(|_| self.changed) else {
+			return Ok(());
+		};
+		write(path, &self.file)?;
+		self.changed = false;
+		Ok(())
+	}
+
+	/// When `achievement` was unlocked, `YYYY-MM-DD`, if it has been.
+	pub fn unlocked_on(&self, achievement: Achievement) -> Option<&str> {
+		self.file.unlocked.get(&achievement).map(String::as_str)
+	}
+
+	/// How far along a locked achievement is, as done and needed, for
+	/// those that build up.
+	pub fn progress(&self, achievement: Achievement) -> Option<(u32, u32)> {
+		match achievement {
+			Achievement::HundredHands => Some((self.file.hands_won.min(HANDS_TO_WIN), HANDS_TO_WIN)),
+			_ => None,
+		}
+	}
+
+	/// Follows the game from `seat`, a sit & go if `tournament`.
+	pub fn sit(&mut self, seat: Seat, tournament: bool) {
+		self.hero = Some(seat);
+		self.tournament = tournament;
+	}
+
+	/// Takes in an event from the hero's game; what it unlocked, if
+	/// anything.
+	pub fn observe(&mut self, event: &GameEvent) -> Vec<Achievement> {
+		let Some(hero) = self.hero else {
+			return Vec::new();
+		};
+		let mut earned = Vec::new();
+		match event {
+			GameEvent::HandStarted { seats, .. } => {
+				self.hand = HandState {
+					folded: !seats.iter().any(|s| s.seat == hero && s.is_active),
+					..HandState::default()
+				};
+			}
+			GameEvent::HoleCardsDealt { seat, cards } if *seat == hero => self.hand.cards = Some(*cards),
+			GameEvent::StreetChanged { board, .. } => self.hand.board = board.clone(),
+			GameEvent::ActionTaken { seat, action: PlayerAction::Fold, .. } if *seat == hero => self.hand.folded = true,
+			// Only pots won at showdown have a hand description
+			GameEvent::PotAwarded { hand_description: Some(_), .. } if !self.hand.folded => self.showed_down = true,
+			GameEvent::HandEnded { results, .. } => {
+				let has_royal = self.hand.cards.is_some_and(|cards| !self.hand.folded && royal_flush(&cards, &self.hand.board));
+				if has_royal {
+					earned.push(Achievement::RoyalFlush);
+				}
+				if results.iter().any(|r| r.seat == hero && r.stack_change > 0.0) {
+					self.file.hands_won += 1;
+					self.changed = true;
+					if self.file.hands_won >= HANDS_TO_WIN {
+						earned.push(Achievement::HundredHands);
+					}
+				}
+			}
+			GameEvent::GameEnded { final_standings, .. } => {
+				let won = final_standings.len() > 1
+					&& final_standings.iter().any(|s| s.seat == hero && s.finish_position == 1);
+				if self.tournament && won && !self.showed_down {
+					earned.push(Achievement::TournamentWithoutShowdown);
+				}
+				self.showed_down = false;
+			}
+			_ => {}
+		}
+		earned.retain(|&achievement| self.unlock(achievement));
+		earned
+	}
+
+	/// True if `achievement` wasn't unlocked before.
+	fn unlock(&mut self, achievement: Achievement) -> bool {
+		if self.file.unlocked.contains_key(&achievement) {
+			return false;
+		}
+		let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+		self.file.unlocked.insert(achievement, today);
+		self.changed = true;
+		true
+	}
+}
+
+fn write(path: &Path, file: &AchievementsFile) -> Result<(), PokerError> {
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir).map_err(|e| PokerError::io("create", dir, e))?;
+	}
+	let content = toml::to_string_pretty(file)
+		.map_err(|e| PokerError::Config(format!("Failed to serialize achievements: {}", e)))?;
+	fs::write(path, content).map_err(|e| PokerError::io("write", path, e))
+}
+
+/// Ten to ace in one suit among the hole cards and the board, one of the
+/// hole cards in it.
+fn royal_flush(hole: &[Card; 2], board: &[Card]) -> bool {
+	hole.iter().any(|card| {
+		"TJQKA".contains(card.rank)
+			&& "TJQKA".chars().all(|rank| hole.iter().chain(board).any(|c| c.rank == rank && c.suit == card.suit))
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{cards, Blinds, GameEndReason, HandId, HandResult, Position, PotType, SeatInfo, Standing, Street};
+
+	/// A hand the hero plays to the river with `hole` on `board`, winning
+	/// `won` at showdown or, if `showdown` is false, when the others fold.
+	fn hand(hole: &str, board: &str, won: f32, showdown: bool) -> Vec<GameEvent> {
+		let seat = |i: usize| SeatInfo {
+			seat: Seat(i),
+			name: format!("P{}", i),
+			stack: 200.0,
+			position: Position::None,
+			is_active: true,
+			is_human: i == 0,
+			is_occupied: true,
+		};
+		let hole = cards(hole);
+		vec![
+			GameEvent::HandStarted {
+				hand_id: HandId(1),
+				hand_num: 1,
+				button: Seat(1),
+				blinds: Blinds { small: 5.0, big: 10.0, ante: None },
+				seats: vec![seat(0), seat(1)],
+				game_id: None,
+				at: None,
+			},
+			GameEvent::HoleCardsDealt { seat: Seat(0), cards: [hole[0], hole[1]] },
+			GameEvent::StreetChanged { street: Street::River, board: cards(board), pot: 20.0, effective_stack: 190.0, pots: Vec::new() },
+			GameEvent::PotAwarded {
+				seat: Seat(0),
+				amount: won,
+				hand_description: showdown.then(|| "a hand".to_string()),
+				best_five: Vec::new(),
+				pot_type: PotType::Main,
+			},
+			GameEvent::HandEnded {
+				hand_id: HandId(1),
+				results: vec![HandResult {
+					seat: Seat(0),
+					stack_change: won,
+					final_stack: 200.0 + won,
+					showed_cards: None,
+					hand_description: None,
+					tilt: None,
+				}],
+				at: None,
+			},
+		]
+	}
+
+	fn play(achievements: &mut Achievements, events: &[GameEvent]) -> Vec<Achievement> {
+		events.iter().flat_map(|e| achievements.observe(e)).collect()
+	}
+
+	fn game_won() -> GameEvent {
+		let standing = |seat: usize, finish_position: u8| Standing {
+			seat: Seat(seat),
+			name: format!("P{}", seat),
+			final_stack: 0.0,
+			finish_position,
+		};
+		GameEvent::GameEnded { reason: GameEndReason::Winner, final_standings: vec![standing(0, 1), standing(1, 2)], detail: None }
+	}
+
+	#[test]
+	fn test_royal_flush_needs_a_hole_card() {
+		assert!(royal_flush(&[Card::new('A', 's'), Card::new('2', 'd')], &cards("Ks Qs Js Ts 3c")));
+		assert!(!royal_flush(&[Card::new('2', 's'), Card::new('2', 'd')], &cards("As Ks Qs Js Ts")));
+		assert!(!royal_flush(&[Card::new('A', 's'), Card::new('K', 's')], &cards("Qs Js 9s")));
+
+		let mut achievements = Achievements::default();
+		achievements.sit(Seat(0), false);
+		assert_eq!(play(&mut achievements, &hand("As Ks", "Qs Js Ts 2c 3d", 10.0, true)), vec![Achievement::RoyalFlush]);
+		assert!(play(&mut achievements, &hand("As Ks", "Qs Js Ts 2c 3d", 10.0, true)).is_empty(), "only once");
+	}
+
+	#[test]
+	fn test_hundred_hands_build_up() {
+		let mut achievements = Achievements::default();
+		achievements.sit(Seat(0), false);
+		let won = hand("2c 7d", "Kh 9s 4d 3c Jh", 10.0, false);
+		let lost = hand("2c 7d", "Kh 9s 4d 3c Jh", -10.0, true);
+		for _ in 0..HANDS_TO_WIN - 1 {
+			assert!(play(&mut achievements, &won).is_empty());
+			play(&mut achievements, &lost);
+		}
+		assert_eq!(achievements.progress(Achievement::HundredHands), Some((99, 100)));
+		assert_eq!(play(&mut achievements, &won), vec![Achievement::HundredHands]);
+		assert!(achievements.unlocked_on(Achievement::HundredHands).is_some());
+	}
+
+	#[test]
+	fn test_tournament_without_showdown() {
+		let mut achievements = Achievements::default();
+		achievements.sit(Seat(0), true);
+		play(&mut achievements, &hand("2c 7d", "Kh 9s 4d 3c Jh", 10.0, true));
+		assert!(achievements.observe(&game_won()).is_empty(), "went to a showdown");
+
+		play(&mut achievements, &hand("2c 7d", "Kh 9s 4d 3c Jh", 10.0, false));
+		assert_eq!(achievements.observe(&game_won()), vec![Achievement::TournamentWithoutShowdown]);
+	}
+
+	#[test]
+	fn test_saved_and_loaded() {
+		let path = std::env::temp_dir().join(format!("poker-achievements-{}.toml", std::process::id()));
+		let _ = fs::remove_file(&path);
+		let mut achievements = Achievements::load_from(&path).unwrap();
+		achievements.sit(Seat(0), false);
+		play(&mut achievements, &hand("As Ks", "Qs Js Ts 2c 3d", 10.0, true));
+		achievements.save().unwrap();
+
+		let loaded = Achievements::load_from(&path).unwrap();
+		assert!(loaded.unlocked_on(Achievement::RoyalFlush).is_some());
+		assert_eq!(loaded.progress(Achievement::HundredHands), Some((1, 100)));
+		let _ = fs::remove_file(&path);
+	}
+}
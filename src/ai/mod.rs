@@ -1,3 +1,9 @@
 mod rules;
+mod table_talk;
+mod think_time;
+mod tilt;
 
-pub use rules::{try_rules, ActionFacing, RuleDecision, Situation};
+pub use rules::{try_rules, ActionFacing, MadeHand, RuleDecision, Situation};
+pub use table_talk::{remark, Moment};
+pub use think_time::think_time;
+pub use tilt::Tilt;
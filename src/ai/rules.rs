@@ -1,4 +1,7 @@
-use crate::strategy::{HandGroup, Position, Strategy};
+use rand::Rng;
+use rs_poker::core::Rank;
+
+use crate::strategy::{HandGroup, Position, Strategy, TableSize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ActionFacing {
@@ -25,6 +28,26 @@ impl ActionFacing {
 	}
 }
 
+/// What the hole cards have made with the board, by the best five.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MadeHand {
+	Nothing,
+	/// One pair: worth a showdown, not worth building a pot with.
+	OnePair,
+	/// Two pair or better.
+	Strong,
+}
+
+impl MadeHand {
+	pub fn from_rank(rank: &Rank) -> Self {
+		match rank {
+			Rank::HighCard(_) => MadeHand::Nothing,
+			Rank::OnePair(_) => MadeHand::OnePair,
+			_ => MadeHand::Strong,
+		}
+	}
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RuleDecision {
 	Fold,
@@ -36,12 +59,19 @@ pub enum RuleDecision {
 pub struct Situation {
 	pub hand_group: HandGroup,
 	pub position: Position,
+	/// The table's size by players dealt in.
+	pub table_size: TableSize,
 	pub pot: f32,
 	pub to_call: f32,
 	pub stack: f32,
 	pub big_blind: f32,
 	pub current_bet: f32,
 	pub is_preflop: bool,
+	pub is_river: bool,
+	/// `Nothing` preflop.
+	pub made_hand: MadeHand,
+	/// We checked earlier on this street, so a raise now is a check-raise.
+	pub checked_this_street: bool,
 	pub num_raises: u32,
 	pub raise_cap: u32,
 	pub we_are_preflop_aggressor: bool,
@@ -83,6 +113,11 @@ impl Situation {
 	pub fn three_bet_size(&self) -> f32 {
 		(self.current_bet * 3.0).min(self.stack)
 	}
+
+	/// A small river lead that sets the price of showdown.
+	pub fn block_size(&self, fraction: f32) -> f32 {
+		(self.pot * fraction).max(self.big_blind).min(self.stack)
+	}
 }
 
 pub fn try_preflop_rules(strategy: &Strategy, situation: &Situation) -> Option<RuleDecision> {
@@ -90,7 +125,7 @@ pub fn try_preflop_rules(strategy: &Strategy, situation: &Situation) -> Option<R
 
 	match action {
 		ActionFacing::Unopened => {
-			if strategy.should_open(situation.hand_group, situation.position) {
+			if strategy.should_open(situation.hand_group, situation.position, situation.table_size) {
 				if situation.can_raise() {
 					Some(RuleDecision::Raise(situation.standard_raise()))
 				} else {
@@ -101,7 +136,7 @@ pub fn try_preflop_rules(strategy: &Strategy, situation: &Situation) -> Option<R
 			}
 		}
 		ActionFacing::Limped => {
-			if strategy.should_open(situation.hand_group, situation.position) && situation.can_raise() {
+			if strategy.should_open(situation.hand_group, situation.position, situation.table_size) && situation.can_raise() {
 				Some(RuleDecision::Raise(situation.standard_raise()))
 			} else if situation.position == Position::Bb {
 				Some(RuleDecision::Check)
@@ -116,7 +151,7 @@ pub fn try_preflop_rules(strategy: &Strategy, situation: &Situation) -> Option<R
 				Some(RuleDecision::Raise(situation.three_bet_size()))
 			} else if strategy.should_cold_call(situation.hand_group) {
 				Some(RuleDecision::Call)
-			} else if situation.position == Position::Bb && strategy.should_defend_bb(situation.hand_group) {
+			} else if situation.position == Position::Bb && strategy.should_defend_bb(situation.hand_group, situation.table_size) {
 				Some(RuleDecision::Call)
 			} else {
 				Some(RuleDecision::Fold)
@@ -126,23 +161,54 @@ pub fn try_preflop_rules(strategy: &Strategy, situation: &Situation) -> Option<R
 	}
 }
 
-pub fn try_postflop_rules(strategy: &Strategy, situation: &Situation) -> Option<RuleDecision> {
+/// Postflop lines. Checked to, a preflop aggressor continuation-bets; on
+/// the river one pair may lead small for `river_block_size` of the pot, as
+/// often as the strategy raises, and nothing at all bets two thirds of the
+/// pot at `bluff_frequency`. Bet into after checking, two pair or better
+/// check-raises at `check_raise_frequency`.
+pub fn try_postflop_rules<R: Rng + ?Sized>(
+	strategy: &Strategy,
+	situation: &Situation,
+	rng: &mut R,
+) -> Option<RuleDecision> {
 	if situation.to_call <= 0.0 {
 		let should_cbet = situation.we_are_preflop_aggressor
-			&& rand::random::<f32>() < strategy.continuation_bet;
+			&& rng.random::<f32>() < strategy.continuation_bet;
+		let should_block = !should_cbet
+			&& situation.is_river
+			&& situation.made_hand == MadeHand::OnePair
+			&& strategy.river_block_size > 0.0
+			&& rng.random::<f32>() < strategy.aggression.raise_frequency();
+
+		let should_bluff = !should_cbet
+			&& situation.is_river
+			&& situation.made_hand == MadeHand::Nothing
+			&& rng.random::<f32>() < strategy.bluff_frequency.probability();
 
 		if should_cbet && situation.can_raise() {
 			let bet_size = (situation.pot * 0.5).max(situation.big_blind).min(situation.stack);
 			Some(RuleDecision::Raise(bet_size))
+		} else if should_block && situation.can_raise() {
+			Some(RuleDecision::Raise(situation.block_size(strategy.river_block_size)))
+		} else if should_bluff && situation.can_raise() {
+			Some(RuleDecision::Raise(situation.standard_raise()))
 		} else {
 			Some(RuleDecision::Check)
 		}
 	} else {
+		let should_check_raise = situation.checked_this_street
+			&& situation.made_hand == MadeHand::Strong
+			&& situation.can_raise()
+			&& rng.random::<f32>() < strategy.check_raise_frequency;
+		if should_check_raise {
+			return Some(RuleDecision::Raise(situation.three_bet_size()));
+		}
+
 		let pot_odds = situation.pot_odds();
 
 		if pot_odds < 0.2 {
 			Some(RuleDecision::Call)
-		} else if pot_odds > 0.4 && rand::random::<f32>() < strategy.fold_to_aggression.fold_frequency() {
+		} else if pot_odds > 0.4 && rng.random::<f32>() < strategy.fold_to_aggression.fold_frequency() {
 			Some(RuleDecision::Fold)
 		} else {
 			None
@@ -150,11 +216,11 @@ pub fn try_postflop_rules(strategy: &Strategy, situation: &Situation) -> Option<
 	}
 }
 
-pub fn try_rules(strategy: &Strategy, situation: &Situation) -> Option<RuleDecision> {
+pub fn try_rules<R: Rng + ?Sized>(strategy: &Strategy, situation: &Situation, rng: &mut R) -> Option<RuleDecision> {
 	if situation.is_preflop {
 		try_preflop_rules(strategy, situation)
 	} else {
-		try_postflop_rules(strategy, situation)
+		try_postflop_rules(strategy, situation, rng)
 	}
 }
 
@@ -162,6 +228,8 @@ pub fn try_rules(strategy: &Strategy, situation: &Situation) -> Option<RuleDecis
 mod tests {
 	use super::*;
 	use crate::strategy::Strategy;
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
 
 	fn tag_strategy() -> Strategy {
 		Strategy::default()
@@ -171,12 +239,16 @@ mod tests {
 		Situation {
 			hand_group: HandGroup::Strong,
 			position: Position::Btn,
+			table_size: TableSize::FullRing,
 			pot: 15.0,
 			to_call: 0.0,
 			stack: 500.0,
 			big_blind: 10.0,
 			current_bet: 10.0,
 			is_preflop: true,
+			is_river: false,
+			made_hand: MadeHand::Nothing,
+			checked_this_street: false,
 			num_raises: 0,
 			raise_cap: 4,
 			we_are_preflop_aggressor: false,
@@ -239,6 +311,16 @@ mod tests {
 		assert_eq!(decision, Some(RuleDecision::Fold));
 	}
 
+	#[test]
+	fn test_heads_up_opens_wider() {
+		let strategy = tag_strategy();
+		let mut sit = situation_unopened_btn();
+		sit.hand_group = HandGroup::Marginal;
+		assert_eq!(try_preflop_rules(&strategy, &sit), Some(RuleDecision::Fold));
+		sit.table_size = TableSize::HeadsUp;
+		assert!(matches!(try_preflop_rules(&strategy, &sit), Some(RuleDecision::Raise(_))));
+	}
+
 	#[test]
 	fn test_raise_cap_forces_call() {
 		let strategy = tag_strategy();
@@ -248,4 +330,82 @@ mod tests {
 		let decision = try_preflop_rules(&strategy, &sit);
 		assert_eq!(decision, Some(RuleDecision::Call));
 	}
+
+	fn situation_river(made_hand: MadeHand, to_call: f32, checked_this_street: bool) -> Situation {
+		Situation {
+			position: Position::Bb,
+			pot: 100.0,
+			to_call,
+			current_bet: to_call,
+			is_preflop: false,
+			is_river: true,
+			made_hand,
+			checked_this_street,
+			..situation_unopened_btn()
+		}
+	}
+
+	/// Share of `trials` decisions that were raises, under a fixed seed.
+	fn raise_rate(strategy: &Strategy, situation: &Situation, trials: usize) -> f32 {
+		let mut rng = StdRng::seed_from_u64(11);
+		let raises = (0..trials)
+			.filter(|_| matches!(try_postflop_rules(strategy, situation, &mut rng), Some(RuleDecision::Raise(_))))
+			.count();
+		raises as f32 / trials as f32
+	}
+
+	#[test]
+	fn test_check_raise_at_configured_frequency() {
+		let mut strategy = tag_strategy();
+		strategy.check_raise_frequency = 0.3;
+		let facing_bet = situation_river(MadeHand::Strong, 50.0, true);
+		assert!((raise_rate(&strategy, &facing_bet, 2000) - 0.3).abs() < 0.04);
+		let mut always = strategy.clone();
+		always.check_raise_frequency = 1.0;
+		let decision = try_postflop_rules(&always, &facing_bet, &mut StdRng::seed_from_u64(0));
+		assert_eq!(decision, Some(RuleDecision::Raise(150.0)));
+
+		// Only a hand that checked first, and only with two pair or better
+		let led_into = situation_river(MadeHand::Strong, 50.0, false);
+		assert_eq!(raise_rate(&strategy, &led_into, 500), 0.0);
+		let pair = situation_river(MadeHand::OnePair, 50.0, true);
+		assert_eq!(raise_rate(&strategy, &pair, 500), 0.0);
+	}
+
+	#[test]
+	fn test_river_block_bet_at_configured_size() {
+		let mut strategy = tag_strategy();
+		strategy.river_block_size = 0.25;
+		let checked_to = situation_river(MadeHand::OnePair, 0.0, false);
+		let expected = strategy.aggression.raise_frequency();
+		assert!((raise_rate(&strategy, &checked_to, 2000) - expected).abs() < 0.04);
+
+		let mut rng = StdRng::seed_from_u64(5);
+		let sizes = (0..200)
+			.filter_map(|_| match try_postflop_rules(&strategy, &checked_to, &mut rng) {
+				Some(RuleDecision::Raise(size)) => Some(size),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		assert!(!sizes.is_empty());
+		assert!(sizes.iter().all(|&size| size == 25.0));
+
+		// Not before the river, and not without the size set
+		let turn = Situation { is_river: false, ..situation_river(MadeHand::OnePair, 0.0, false) };
+		assert_eq!(raise_rate(&strategy, &turn, 500), 0.0);
+		strategy.river_block_size = 0.0;
+		assert_eq!(raise_rate(&strategy, &checked_to, 500), 0.0);
+	}
+
+	#[test]
+	fn test_tilt_bluffs_the_river_more() {
+		let mut strategy = tag_strategy();
+		strategy.tilt = Some(crate::strategy::TiltConfig { bluff: 2, ..Default::default() });
+		let air = situation_river(MadeHand::Nothing, 0.0, false);
+		let calm = raise_rate(&strategy, &air, 2000);
+		let tilted = raise_rate(&strategy.tilted(1.0), &air, 2000);
+		assert!((calm - strategy.bluff_frequency.probability()).abs() < 0.03);
+		assert!((tilted - strategy.tilted(1.0).bluff_frequency.probability()).abs() < 0.04);
+		assert!(tilted > calm * 2.0);
+	}
 }
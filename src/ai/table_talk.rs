@@ -0,0 +1,162 @@
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+use crate::events::Seat;
+use crate::players::HandRecap;
+use crate::strategy::ChatPersona;
+
+/// Net result, in big blinds, that makes a pot worth talking about.
+const BIG_POT_BB: f32 = 25.0;
+
+/// Why a seat might have something to say about the hand just played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Moment {
+	/// Lost the showdown as a big favourite on the turn.
+	BadBeat,
+	/// Won the showdown against a big favourite.
+	SuckedOut,
+	BigWin,
+	BigLoss,
+}
+
+impl Moment {
+	pub fn for_seat(recap: &HandRecap, seat: Seat) -> Option<Self> {
+		let change = recap.result(seat)?;
+		match recap.bad_beat {
+			Some(victim) if victim == seat => return Some(Moment::BadBeat),
+			Some(_) if change > 0.0 => return Some(Moment::SuckedOut),
+			_ => {}
+		}
+		let big_pot = BIG_POT_BB * recap.big_blind;
+		if change >= big_pot {
+			Some(Moment::BigWin)
+		} else if change <= -big_pot {
+			Some(Moment::BigLoss)
+		} else {
+			None
+		}
+	}
+}
+
+fn lines(persona: ChatPersona, moment: Moment) -> &'static [&'static str] {
+	match (persona, moment) {
+		(ChatPersona::Quiet, Moment::BadBeat) => &["...", "Of course."],
+		(ChatPersona::Quiet, Moment::SuckedOut) => &["Sorry.", "Lucky."],
+		(ChatPersona::Quiet, Moment::BigWin) => &["nh me", "Thanks."],
+		(ChatPersona::Quiet, Moment::BigLoss) => &["nh", "Hm."],
+		(ChatPersona::Friendly, Moment::BadBeat) => &[
+			"Ouch. Well played, I guess!",
+			"That river had my name on it... for you.",
+			"Can't win them all. That one hurt though.",
+		],
+		(ChatPersona::Friendly, Moment::SuckedOut) => &[
+			"Sorry! I know, I know.",
+			"I'll take it, but you played that better.",
+			"The poker gods smiled on me there, sorry!",
+		],
+		(ChatPersona::Friendly, Moment::BigWin) => &[
+			"What a hand! Thanks everyone.",
+			"Fun pot, gg.",
+			"That was a good one.",
+		],
+		(ChatPersona::Friendly, Moment::BigLoss) => &[
+			"Nice hand!",
+			"Well played, you got me.",
+			"gg, that was a fun pot.",
+		],
+		(ChatPersona::Needler, Moment::BadBeat) => &[
+			"You called THAT?",
+			"Enjoy it, it won't happen again.",
+			"Unbelievable. Do you even know the odds?",
+		],
+		(ChatPersona::Needler, Moment::SuckedOut) => &[
+			"Sometimes you have to let them think they're good.",
+			"Had you the whole way. Well, the last card anyway.",
+			"Math is overrated.",
+		],
+		(ChatPersona::Needler, Moment::BigWin) => &[
+			"Ship it.",
+			"Thanks for the donation.",
+			"Who's next?",
+		],
+		(ChatPersona::Needler, Moment::BigLoss) => &[
+			"Enjoy it while it lasts.",
+			"Lucky. I'll have those chips back soon.",
+			"Bet you can't do that twice.",
+		],
+	}
+}
+
+/// A line for `moment`, or `None` when the persona keeps quiet this time.
+/// `frequency` scales how often the persona speaks up: 1.0 is its natural
+/// chattiness, 0.0 silences it.
+pub fn remark<R: Rng + ?Sized>(
+	persona: ChatPersona,
+	moment: Moment,
+	frequency: f32,
+	rng: &mut R,
+) -> Option<&'static str> {
+	let chance = (persona.chattiness() * frequency).clamp(0.0, 1.0);
+	if rng.random::<f32>() >= chance {
+		return None;
+	}
+	lines(persona, moment).choose(rng).copied()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::SeedableRng;
+	use rand::rngs::StdRng;
+
+	fn recap(results: Vec<(Seat, f32)>, bad_beat: Option<Seat>) -> HandRecap {
+		HandRecap {
+			hand_num: 1,
+			big_blind: 10.0,
+			board: Vec::new(),
+			results,
+			showdown: true,
+			bad_beat,
+		}
+	}
+
+	#[test]
+	fn test_moment_for_seat() {
+		let beat = recap(vec![(Seat(0), 300.0), (Seat(1), -300.0), (Seat(2), -10.0)], Some(Seat(1)));
+		assert_eq!(Moment::for_seat(&beat, Seat(0)), Some(Moment::SuckedOut));
+		assert_eq!(Moment::for_seat(&beat, Seat(1)), Some(Moment::BadBeat));
+		assert_eq!(Moment::for_seat(&beat, Seat(2)), None);
+		assert_eq!(Moment::for_seat(&beat, Seat(5)), None);
+
+		let cooler = recap(vec![(Seat(0), 250.0), (Seat(1), -250.0)], None);
+		assert_eq!(Moment::for_seat(&cooler, Seat(0)), Some(Moment::BigWin));
+		assert_eq!(Moment::for_seat(&cooler, Seat(1)), Some(Moment::BigLoss));
+
+		let small = recap(vec![(Seat(0), 40.0), (Seat(1), -40.0)], None);
+		assert_eq!(Moment::for_seat(&small, Seat(0)), None);
+	}
+
+	#[test]
+	fn test_remark_frequency() {
+		let mut rng = StdRng::seed_from_u64(7);
+		for _ in 0..50 {
+			assert!(remark(ChatPersona::Needler, Moment::BigWin, 0.0, &mut rng).is_none());
+		}
+		let said = (0..200)
+			.filter_map(|_| remark(ChatPersona::Needler, Moment::BigWin, 10.0, &mut rng))
+			.collect::<Vec<_>>();
+		assert_eq!(said.len(), 200);
+		assert!(said.iter().all(|line| lines(ChatPersona::Needler, Moment::BigWin).contains(line)));
+	}
+
+	#[test]
+	fn test_quiet_talks_less_than_needler() {
+		let mut rng = StdRng::seed_from_u64(3);
+		let mut count = |persona| {
+			(0..1000)
+				.filter(|_| remark(persona, Moment::BigLoss, 1.0, &mut rng).is_some())
+				.count()
+		};
+		assert!(count(ChatPersona::Quiet) < count(ChatPersona::Needler));
+	}
+}
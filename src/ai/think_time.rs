@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::events::PlayerAction;
+use crate::strategy::Tempo;
+
+/// Pot, in big blinds, at which a decision takes as long as it ever will.
+const BIG_POT_BB: f32 = 40.0;
+
+/// How long an AI seems to think before `action`: a moment for a cheap
+/// fold, longer to call or raise, longest to shove into a big pot. `base`
+/// is the table's action delay; `pot_after` and `big_blind` size the
+/// decision. Jittered so the same spot doesn't always take the same time.
+pub fn think_time<R: Rng + ?Sized>(
+	tempo: Tempo,
+	base: Duration,
+	action: &PlayerAction,
+	pot_after: f32,
+	big_blind: f32,
+	rng: &mut R,
+) -> Duration {
+	let stakes = if big_blind > 0.0 {
+		(pot_after / big_blind / BIG_POT_BB).min(1.0)
+	} else {
+		0.0
+	};
+	let weight = match action {
+		PlayerAction::Fold => 0.4 + stakes,
+		PlayerAction::Check => 0.6,
+		PlayerAction::Call { .. } => 0.9 + stakes,
+		PlayerAction::Bet { .. } | PlayerAction::Raise { .. } => 1.1 + stakes,
+		PlayerAction::AllIn { .. } => 1.5 + 1.5 * stakes,
+		PlayerAction::Timeout | PlayerAction::Show | PlayerAction::Muck => return Duration::ZERO,
+	};
+	let jitter = rng.random_range(0.7..1.3);
+	base.mul_f32(weight * tempo.multiplier() * jitter)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::SeedableRng;
+	use rand::rngs::StdRng;
+
+	const BASE: Duration = Duration::from_millis(500);
+
+	#[test]
+	fn test_big_decisions_take_longer() {
+		let mut rng = StdRng::seed_from_u64(1);
+		for _ in 0..50 {
+			let fold = think_time(Tempo::Steady, BASE, &PlayerAction::Fold, 3.0, 2.0, &mut rng);
+			let shove = think_time(Tempo::Steady, BASE, &PlayerAction::AllIn { amount: 200.0 }, 300.0, 2.0, &mut rng);
+			assert!(fold < Duration::from_millis(300), "{:?}", fold);
+			assert!(shove > Duration::from_millis(1000), "{:?}", shove);
+		}
+	}
+
+	#[test]
+	fn test_tempo_scales_think_time() {
+		let call = PlayerAction::Call { amount: 10.0 };
+		let snappy = think_time(Tempo::Snappy, BASE, &call, 30.0, 2.0, &mut StdRng::seed_from_u64(9));
+		let deliberate = think_time(Tempo::Deliberate, BASE, &call, 30.0, 2.0, &mut StdRng::seed_from_u64(9));
+		assert!(snappy < deliberate);
+		assert_eq!(think_time(Tempo::Deliberate, Duration::ZERO, &call, 30.0, 2.0, &mut rand::rng()), Duration::ZERO);
+	}
+}
@@ -0,0 +1,60 @@
+use crate::strategy::TiltConfig;
+
+/// Below this the tilt has worn off.
+const CALM_BELOW: f32 = 0.1;
+
+/// An AI's mood over the hands, as its `TiltConfig` drives it: a big loss
+/// puts it on full tilt, and each hand after wears some of it off.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Tilt {
+	#[default]
+	Calm,
+	/// How far gone, 1.0 just after the loss.
+	Steaming(f32),
+}
+
+impl Tilt {
+	/// The mood after a hand the player finished `net` chips up or down.
+	pub fn after_hand(self, config: &TiltConfig, net: f32, big_blind: f32) -> Tilt {
+		if big_blind > 0.0 && -net >= config.big_pot * big_blind {
+			return Tilt::Steaming(1.0);
+		}
+		match self {
+			Tilt::Steaming(level) if level * config.decay >= CALM_BELOW => Tilt::Steaming(level * config.decay),
+			_ => Tilt::Calm,
+		}
+	}
+
+	/// 0.0 calm to 1.0 on full tilt.
+	pub fn level(&self) -> f32 {
+		match self {
+			Tilt::Calm => 0.0,
+			Tilt::Steaming(level) => *level,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_big_loss_tilts_and_wears_off() {
+		let config = TiltConfig { big_pot: 40.0, decay: 0.5, ..TiltConfig::default() };
+		let small_loss = Tilt::Calm.after_hand(&config, -300.0, 10.0);
+		assert_eq!(small_loss, Tilt::Calm);
+
+		let mut tilt = Tilt::Calm.after_hand(&config, -400.0, 10.0);
+		assert_eq!(tilt, Tilt::Steaming(1.0));
+		let levels = (0..4)
+			.map(|_| {
+				tilt = tilt.after_hand(&config, 50.0, 10.0);
+				tilt.level()
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(levels, vec![0.5, 0.25, 0.125, 0.0]);
+
+		// Another big loss sets it off again
+		assert_eq!(Tilt::Steaming(0.25).after_hand(&config, -500.0, 10.0), Tilt::Steaming(1.0));
+	}
+}
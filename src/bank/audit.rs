@@ -0,0 +1,334 @@
+//! The bank's audit trail: every change to a bankroll, one JSON object per
+//! line in `bank-audit.jsonl` beside `profiles.toml`. Each entry carries a
+//! checksum over itself and the one before it, so an edited, dropped or
+//! reordered line breaks the chain. `poker bank audit` walks the chain and
+//! checks the balances it ends on against the profiles.
+//!
+//! The checksum is FNV-1a: enough to catch accidents and hand edits, not
+//! someone set on forging a new chain from scratch.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::error::PokerError;
+use crate::events::{GameId, HandId};
+
+/// What the first entry's `prev` points at.
+pub(crate) const GENESIS: &str = "0000000000000000";
+
+/// Balances further apart than this don't match.
+const TOLERANCE: f32 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditKind {
+	/// A new profile, opening at `balance`.
+	Open,
+	Credit,
+	Debit,
+	Buyin,
+	Cashout,
+	Prize,
+	/// A buy-in handed back from escrow.
+	Refund,
+	/// A broke roster AI back at the default bankroll.
+	Reload,
+	TopUp,
+	/// Weekly rakeback.
+	Rakeback,
+	/// A promotion's payout, such as a high-hand bonus.
+	Promotion,
+}
+
+/// Where money moved: the table, game and hand, as far as they're known.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditRef {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub table: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub game: Option<GameId>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub hand: Option<HandId>,
+}
+
+impl AuditRef {
+	pub fn table(table_id: &str) -> Self {
+		Self { table: Some(table_id.to_string()), ..Self::default() }
+	}
+
+	pub fn game(mut self, game: GameId) -> Self {
+		self.game = Some(game);
+		self
+	}
+
+	pub fn hand(mut self, hand: Option<HandId>) -> Self {
+		self.hand = hand;
+		self
+	}
+}
+
+impl fmt::Display for AuditRef {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut parts = Vec::new();
+		if let Some(table) = &self.table {
+			parts.push(format!("table {}", table));
+		}
+		if let Some(game) = self.game {
+			parts.push(format!("game {:016x}", game.0));
+		}
+		if let Some(hand) = self.hand {
+			parts.push(format!("hand {}", hand.0));
+		}
+		write!(f, "{}", parts.join(", "))
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+	pub seq: u64,
+	/// Local time, RFC 3339.
+	pub at: String,
+	pub kind: AuditKind,
+	pub player: String,
+	/// Change to the bankroll; negative for money out.
+	pub amount: f32,
+	/// The bankroll afterwards.
+	pub balance: f32,
+	#[serde(flatten)]
+	pub reference: AuditRef,
+	/// The previous entry's `hash`.
+	pub prev: String,
+	pub hash: String,
+}
+
+impl AuditEntry {
+	/// The checksum over `prev` and everything else in the entry.
+	fn checksum(&self) -> String {
+		let unsigned = AuditEntry { hash: String::new(), ..self.clone() };
+		let json = serde_json::to_string(&unsigned).unwrap_or_default();
+		format!("{:016x}", fnv1a(json.as_bytes()))
+	}
+}
+
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+	bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// The end of the chain, ready for the next entry.
+pub struct AuditLog {
+	path: PathBuf,
+	seq: u64,
+	last: String,
+}
+
+impl AuditLog {
+	/// Picks up after the last entry in `path`, or starts a new chain.
+	pub fn open(path: impl Into<PathBuf>) -> Self {
+		let path = path.into();
+		let last = read_entries(&path).ok().and_then(|entries| entries.into_iter().last());
+		Self {
+			path,
+			seq: last.as_ref().map_or(0, |e| e.seq),
+			last: last.map_or_else(|| GENESIS.to_string(), |e| e.hash),
+		}
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	pub fn append(&mut self, kind: AuditKind, player: &str, amount: f32, balance: f32, reference: &AuditRef) -> Result<(), PokerError> {
+		let mut entry = AuditEntry {
+			seq: self.seq + 1,
+			at: Local::now().to_rfc3339(),
+			kind,
+			player: player.to_string(),
+			amount,
+			balance,
+			reference: reference.clone(),
+			prev: self.last.clone(),
+			hash: String::new(),
+		};
+		entry.hash = entry.checksum();
+		let line = serde_json::to_string(&entry)
+			.map_err(|e| PokerError::Config(format!("Failed to serialize audit entry: {}", e)))?;
+		if let Some(dir) = self.path.parent() {
+			fs::create_dir_all(dir)
+				.map_err(|e| PokerError::io("create", dir, e))?;
+		}
+		OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(&self.path)
+			.and_then(|mut file| writeln!(file, "{}", line))
+			.map_err(|e| PokerError::io("write", &self.path, e))?;
+		self.seq = entry.seq;
+		self.last = entry.hash;
+		Ok(())
+	}
+}
+
+/// Every entry in the log at `path`; none if there's no log yet.
+pub fn read_entries(path: &Path) -> Result<Vec<AuditEntry>, PokerError> {
+	if !path.exists() {
+		return Ok(Vec::new());
+	}
+	let content = fs::read_to_string(path)
+		.map_err(|e| PokerError::io("read", path, e))?;
+	content
+		.lines()
+		.filter(|line| !line.trim().is_empty())
+		.enumerate()
+		.map(|(i, line)| {
+			serde_json::from_str(line).map_err(|e| PokerError::parse(format!("{} line {}", path.display(), i + 1), e))
+		})
+		.collect()
+}
+
+/// What `verify` found.
+#[derive(Debug, Clone, Default)]
+pub struct AuditReport {
+	pub entries: usize,
+	/// Players with entries.
+	pub players: usize,
+	/// Breaks in the chain and balances that don't add up.
+	pub problems: Vec<String>,
+	/// Profiles the log has never seen, from before it was kept.
+	pub untracked: Vec<String>,
+}
+
+impl AuditReport {
+	pub fn is_clean(&self) -> bool {
+		self.problems.is_empty()
+	}
+}
+
+/// Walks the chain, replays each player's balance through it and checks
+/// where they end up against `balances`, the profiles as they stand.
+pub fn verify(entries: &[AuditEntry], balances: &HashMap<String, f32>) -> AuditReport {
+	let mut problems = Vec::new();
+	let mut prev = GENESIS;
+	let mut last_seq = 0;
+	let mut running: HashMap<&str, f32> = HashMap::new();
+
+	for entry in entries {
+		if entry.seq != last_seq + 1 {
+			problems.push(format!("entry {}: expected entry {} next", entry.seq, last_seq + 1));
+		}
+		if entry.prev != prev {
+			problems.push(format!("entry {}: doesn't follow on from the entry before", entry.seq));
+		}
+		if entry.hash != entry.checksum() {
+			problems.push(format!("entry {}: checksum doesn't match its contents", entry.seq));
+		}
+		if entry.kind != AuditKind::Open {
+			if let Some(&before) = running.get(entry.player.as_str()) {
+				if (before + entry.amount - entry.balance).abs() > TOLERANCE {
+					problems.push(format!(
+						"entry {}: {} had {:.2}, so {:+.2} should leave {:.2}, not {:.2}",
+						entry.seq,
+						entry.player,
+						before,
+						entry.amount,
+						before + entry.amount,
+						entry.balance
+					));
+				}
+			}
+		}
+		running.insert(&entry.player, entry.balance);
+		prev = &entry.hash;
+		last_seq = entry.seq;
+	}
+
+	let mut players = running.keys().copied().collect::<Vec<_>>();
+	players.sort();
+	for player in &players {
+		let ledger = running[player];
+		match balances.get(*player) {
+			Some(&profile) if (profile - ledger).abs() > TOLERANCE => problems.push(format!(
+				"{}: profile has {:.2}, the log says {:.2}",
+				player, profile, ledger
+			)),
+			None => problems.push(format!("{}: in the log but has no profile", player)),
+			_ => {}
+		}
+	}
+
+	let mut untracked = balances
+		.keys()
+		.filter(|id| !running.contains_key(id.as_str()))
+		.cloned()
+		.collect::<Vec<_>>();
+	untracked.sort();
+
+	AuditReport { entries: entries.len(), players: players.len(), problems, untracked }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn temp_log(name: &str) -> AuditLog {
+		let path = std::env::temp_dir().join(format!("poker-audit-{}-{}.jsonl", name, std::process::id()));
+		let _ = fs::remove_file(&path);
+		AuditLog::open(path)
+	}
+
+	fn sample(log: &mut AuditLog) {
+		let table = AuditRef::table("t").game(GameId(3));
+		log.append(AuditKind::Open, "alice", 1000.0, 1000.0, &AuditRef::default()).unwrap();
+		log.append(AuditKind::Buyin, "alice", -100.0, 900.0, &table).unwrap();
+		log.append(AuditKind::Cashout, "alice", 250.0, 1150.0, &table.clone().hand(Some(HandId(9)))).unwrap();
+	}
+
+	#[test]
+	fn test_chain_verifies_against_balances() {
+		let mut log = temp_log("clean");
+		sample(&mut log);
+		let entries = read_entries(log.path()).unwrap();
+		assert_eq!(entries.len(), 3);
+		assert_eq!(entries[2].reference.hand, Some(HandId(9)));
+
+		let balances = HashMap::from([("alice".to_string(), 1150.0), ("bob".to_string(), 500.0)]);
+		let report = verify(&entries, &balances);
+		assert!(report.is_clean(), "{:?}", report.problems);
+		assert_eq!(report.untracked, ["bob"]);
+
+		// Carries on the same chain after reopening
+		let mut reopened = AuditLog::open(log.path());
+		reopened.append(AuditKind::Debit, "alice", -50.0, 1100.0, &AuditRef::default()).unwrap();
+		let entries = read_entries(log.path()).unwrap();
+		let balances = HashMap::from([("alice".to_string(), 1100.0)]);
+		assert!(verify(&entries, &balances).is_clean());
+		let _ = fs::remove_file(log.path());
+	}
+
+	#[test]
+	fn test_tampering_is_caught() {
+		let mut log = temp_log("tampered");
+		sample(&mut log);
+		let entries = read_entries(log.path()).unwrap();
+		let _ = fs::remove_file(log.path());
+		let balances = HashMap::from([("alice".to_string(), 1150.0)]);
+
+		let mut edited = entries.clone();
+		edited[2].amount = 350.0;
+		let report = verify(&edited, &balances);
+		assert!(report.problems.iter().any(|p| p.contains("checksum")), "{:?}", report.problems);
+		assert!(report.problems.iter().any(|p| p.contains("should leave")), "{:?}", report.problems);
+
+		let dropped = [entries[0].clone(), entries[2].clone()];
+		let report = verify(&dropped, &balances);
+		assert!(report.problems.iter().any(|p| p.contains("doesn't follow")), "{:?}", report.problems);
+
+		let report = verify(&entries, &HashMap::from([("alice".to_string(), 5000.0)]));
+		assert_eq!(report.problems, ["alice: profile has 5000.00, the log says 1150.00"]);
+	}
+}
@@ -0,0 +1,926 @@
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::challenges::{ChallengeProgress, HandOutcome, Reward};
+use crate::error::PokerError;
+use crate::events::GameId;
+use crate::logging;
+use crate::money;
+
+pub mod audit;
+
+use audit::{AuditKind, AuditLog, AuditRef, AuditReport};
+
+/// The audit trail, beside `profiles.toml`.
+const AUDIT_FILE: &str = "bank-audit.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerProfile {
+	pub bankroll: f32,
+	/// Roster AIs waiting for payday: the day they went broke, `YYYY-MM-DD`.
+	#[serde(default)]
+	pub busted_on: Option<String>,
+	/// Rakeback earned and not yet paid; see `Bank::pay_rakeback`.
+	#[serde(default, skip_serializing_if = "is_zero")]
+	pub rakeback_owed: f32,
+	/// Toward the daily challenges; see `Bank::progress_challenges`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub challenges: Option<ChallengeProgress>,
+}
+
+fn is_zero(amount: &f32) -> bool {
+	*amount == 0.0
+}
+
+/// What a roster AI does when it can't cover a table's buy-in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BustPolicy {
+	/// Tops back up to the default bankroll straight away.
+	#[default]
+	Reload,
+	/// Sits out until the Friday after going broke, then reloads.
+	Payday,
+	/// Plays on at stakes it can still cover, and reloads only once it
+	/// can't cover any table.
+	MoveDown,
+}
+
+/// The day `Payday` players reload.
+const PAYDAY: Weekday = Weekday::Fri;
+
+/// Buy-ins taken for a game that hasn't finished yet. Saved with the
+/// profiles, so a game lost to a crash is refunded on the next start.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Escrow {
+	pub table_id: String,
+	/// Each player's buy-in, by bank id.
+	#[serde(default)]
+	pub held: HashMap<String, f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ProfilesFile {
+	#[serde(default = "default_bankroll")]
+	default_bankroll: f32,
+	#[serde(default)]
+	profiles: HashMap<String, PlayerProfile>,
+	/// Open escrows by game id.
+	#[serde(default)]
+	escrows: HashMap<String, Escrow>,
+	/// When rakeback was last paid, `YYYY-MM-DD`.
+	#[serde(default)]
+	rakeback_paid_on: Option<String>,
+	/// Bad-beat jackpot pools by name.
+	#[serde(default)]
+	jackpots: HashMap<String, f32>,
+}
+
+fn default_bankroll() -> f32 {
+	1000.0
+}
+
+impl Default for ProfilesFile {
+	fn default() -> Self {
+		Self {
+			default_bankroll: default_bankroll(),
+			profiles: HashMap::new(),
+			escrows: HashMap::new(),
+			rakeback_paid_on: None,
+			jackpots: HashMap::new(),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct InsufficientFunds {
+	pub player_id: String,
+	pub required: f32,
+	pub available: f32,
+}
+
+impl std::fmt::Display for InsufficientFunds {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{} has insufficient funds: needs {}, has {}",
+			self.player_id,
+			money::cents(self.required),
+			money::cents(self.available)
+		)
+	}
+}
+
+impl std::error::Error for InsufficientFunds {}
+
+pub struct Bank {
+	profiles: HashMap<String, PlayerProfile>,
+	escrows: HashMap<String, Escrow>,
+	/// `None` in tests, which keep nothing on disk.
+	audit: Option<AuditLog>,
+	rakeback_paid_on: Option<NaiveDate>,
+	jackpots: HashMap<String, f32>,
+	default_bankroll: f32,
+	path: PathBuf,
+}
+
+fn normalize_id(id: &str) -> String {
+	id.to_lowercase()
+}
+
+fn is_valid_amount(amount: f32) -> bool {
+	amount.is_finite() && amount >= 0.0
+}
+
+impl Bank {
+	pub fn load() -> Result<Self, PokerError> {
+		Self::load_from(Self::config_path()?)
+	}
+
+	/// Loads the profiles at `path`, with the audit log beside them.
+	pub fn load_from(path: impl Into<PathBuf>) -> Result<Self, PokerError> {
+		let path = path.into();
+		let file = if path.exists() {
+			let content = fs::read_to_string(&path)
+				.map_err(|e| PokerError::io("read", &path, e))?;
+			toml::from_str(&content)
+				.map_err(|e| PokerError::parse("profiles", e))?
+		} else {
+			ProfilesFile::default()
+		};
+
+		// Normalize all profile keys to lowercase
+		let profiles: HashMap<String, PlayerProfile> = file.profiles
+			.into_iter()
+			.map(|(k, v)| (normalize_id(&k), v))
+			.collect();
+
+		Ok(Self {
+			profiles,
+			escrows: file.escrows,
+			audit: Some(AuditLog::open(path.with_file_name(AUDIT_FILE))),
+			rakeback_paid_on: file.rakeback_paid_on.as_deref().and_then(parse_date),
+			jackpots: file.jackpots,
+			default_bankroll: file.default_bankroll,
+			path,
+		})
+	}
+
+	#[cfg(test)]
+	pub fn new_for_testing(profiles: HashMap<String, PlayerProfile>) -> Self {
+		Self {
+			profiles,
+			escrows: HashMap::new(),
+			audit: None,
+			rakeback_paid_on: None,
+			jackpots: HashMap::new(),
+			default_bankroll: 1000.0,
+			path: PathBuf::from("/tmp/test.toml"),
+		}
+	}
+
+	fn config_path() -> Result<PathBuf, PokerError> {
+		if let Some(config_dir) = dirs::config_dir() {
+			let dir = config_dir.join("transparent-poker");
+			fs::create_dir_all(&dir)
+				.map_err(|e| PokerError::io("create", &dir, e))?;
+			Ok(dir.join("profiles.toml"))
+		} else {
+			Ok(PathBuf::from("config/profiles.toml"))
+		}
+	}
+
+	pub fn get(&self, id: &str) -> PlayerProfile {
+		let id = normalize_id(id);
+		self.profiles.get(&id).cloned().unwrap_or(PlayerProfile {
+			bankroll: self.default_bankroll,
+			busted_on: None,
+			rakeback_owed: 0.0,
+			challenges: None,
+		})
+	}
+
+	pub fn get_bankroll(&self, id: &str) -> f32 {
+		let id = normalize_id(id);
+		self.profiles
+			.get(&id)
+			.map(|p| p.bankroll)
+			.unwrap_or(self.default_bankroll)
+	}
+
+	pub fn ensure_exists(&mut self, id: &str) {
+		let id = normalize_id(id);
+		if !self.profiles.contains_key(&id) {
+			self.profiles.insert(
+				id.clone(),
+				PlayerProfile {
+					bankroll: self.default_bankroll,
+					busted_on: None,
+					rakeback_owed: 0.0,
+					challenges: None,
+				},
+			);
+			self.record(AuditKind::Open, &id, self.default_bankroll, &AuditRef::default());
+		}
+	}
+
+	pub fn register(&mut self, id: &str, bankroll: f32) {
+		let id = normalize_id(id);
+		let bankroll = if is_valid_amount(bankroll) { bankroll } else { 0.0 };
+		self.profiles.insert(
+			id.clone(),
+			PlayerProfile { bankroll, busted_on: None, rakeback_owed: 0.0, challenges: None },
+		);
+		logging::log("Bank", "REGISTER", &format!("{}: ${:.2}", id, bankroll));
+		self.record(AuditKind::Open, &id, bankroll, &AuditRef::default());
+	}
+
+	pub fn debit(&mut self, id: &str, amount: f32) -> Result<(), InsufficientFunds> {
+		self.debit_for(id, amount, AuditKind::Debit, &AuditRef::default())
+	}
+
+	pub fn credit(&mut self, id: &str, amount: f32) {
+		self.credit_for(id, amount, AuditKind::Credit, &AuditRef::default());
+	}
+
+	fn debit_for(&mut self, id: &str, amount: f32, kind: AuditKind, at: &AuditRef) -> Result<(), InsufficientFunds> {
+		if !is_valid_amount(amount) || amount == 0.0 {
+			return Err(InsufficientFunds {
+				player_id: id.to_string(),
+				required: amount,
+				available: 0.0,
+			});
+		}
+		let id = normalize_id(id);
+		let profile = self.profiles.get_mut(&id).ok_or_else(|| InsufficientFunds {
+			player_id: id.clone(),
+			required: amount,
+			available: 0.0,
+		})?;
+
+		if profile.bankroll < amount {
+			return Err(InsufficientFunds {
+				player_id: id,
+				required: amount,
+				available: profile.bankroll,
+			});
+		}
+
+		profile.bankroll -= amount;
+		logging::log("Bank", "DEBIT", &format!("{}: -${:.2} (bal: ${:.2})", id, amount, profile.bankroll));
+		self.record(kind, &id, -amount, at);
+		Ok(())
+	}
+
+	fn credit_for(&mut self, id: &str, amount: f32, kind: AuditKind, at: &AuditRef) {
+		if !is_valid_amount(amount) {
+			logging::log("Bank", "CREDIT", &format!("rejected invalid amount: {}", amount));
+			return;
+		}
+		let id = normalize_id(id);
+		if let Some(profile) = self.profiles.get_mut(&id) {
+			profile.bankroll += amount;
+			logging::log("Bank", "CREDIT", &format!("{}: +${:.2} (bal: ${:.2})", id, amount, profile.bankroll));
+		} else {
+			// Create profile for new players (e.g. AI) receiving winnings
+			let bankroll = self.default_bankroll + amount;
+			self.profiles.insert(
+				id.clone(),
+				PlayerProfile { bankroll, busted_on: None, rakeback_owed: 0.0, challenges: None },
+			);
+			logging::log("Bank", "CREDIT", &format!("{}: +${:.2} (new profile, bal: ${:.2})", id, amount, bankroll));
+			let opening = self.default_bankroll;
+			if let Some(audit) = &mut self.audit {
+				if let Err(e) = audit.append(AuditKind::Open, &id, opening, opening, &AuditRef::default()) {
+					logging::log("Bank", "ERROR", &e.to_string());
+				}
+			}
+		}
+		self.record(kind, &id, amount, at);
+	}
+
+	/// Writes a change to `id`'s bankroll, now standing at its new balance,
+	/// to the audit trail.
+	fn record(&mut self, kind: AuditKind, id: &str, amount: f32, at: &AuditRef) {
+		let balance = self.get_bankroll(id);
+		if let Some(audit) = &mut self.audit {
+			if let Err(e) = audit.append(kind, id, amount, balance, at) {
+				logging::log("Bank", "ERROR", &e.to_string());
+			}
+		}
+	}
+
+	/// Checks the audit trail hangs together and ends where the profiles
+	/// stand.
+	pub fn verify_audit(&self) -> Result<AuditReport, PokerError> {
+		let path = self.path.with_file_name(AUDIT_FILE);
+		let entries = audit::read_entries(&path)?;
+		let balances = self.profiles.iter().map(|(id, p)| (id.clone(), p.bankroll)).collect();
+		Ok(audit::verify(&entries, &balances))
+	}
+
+	pub fn buyin(&mut self, id: &str, amount: f32, at: &AuditRef) -> Result<(), InsufficientFunds> {
+		let id = normalize_id(id);
+		self.debit_for(&id, amount, AuditKind::Buyin, at)?;
+		logging::log("Bank", "BUYIN", &format!("{}: ${:.2} for {}", id, amount, at));
+		Ok(())
+	}
+
+	pub fn cashout(&mut self, id: &str, amount: f32, at: &AuditRef) {
+		let id = normalize_id(id);
+		self.credit_for(&id, amount, AuditKind::Cashout, at);
+		logging::log("Bank", "CASHOUT", &format!("{}: ${:.2} from {}", id, amount, at));
+	}
+
+	pub fn award_prize(&mut self, id: &str, amount: f32, place: usize, at: &AuditRef) {
+		let id = normalize_id(id);
+		self.credit_for(&id, amount, AuditKind::Prize, at);
+		logging::log("Bank", "PRIZE", &format!("{}: ${:.2} ({})", id, amount, ordinal(place)));
+	}
+
+	/// A promotion's payout, such as a high-hand bonus.
+	pub fn award_promotion(&mut self, id: &str, amount: f32, what: &str, at: &AuditRef) {
+		let id = normalize_id(id);
+		self.credit_for(&id, amount, AuditKind::Promotion, at);
+		logging::log("Bank", "PROMO", &format!("{}: ${:.2} for {} at {}", id, amount, what, at));
+	}
+
+	/// Adds to what `id` is owed in rakeback, paid by `pay_rakeback`.
+	pub fn accrue_rakeback(&mut self, id: &str, amount: f32) {
+		if !is_valid_amount(amount) {
+			return;
+		}
+		self.ensure_exists(id);
+		if let Some(profile) = self.profiles.get_mut(&normalize_id(id)) {
+			profile.rakeback_owed += amount;
+		}
+	}
+
+	/// Counts a hand that went as `outcome` toward `id`'s daily
+	/// challenges, paying for what it earned.
+	pub fn progress_challenges(&mut self, id: &str, today: NaiveDate, outcome: &HandOutcome, at: &AuditRef) -> Vec<Reward> {
+		self.ensure_exists(id);
+		let Some(profile) = self.profiles.get_mut(&normalize_id(id)) else {
+			return Vec::new();
+		};
+		let rewards = profile.challenges.get_or_insert_default().observe(today, outcome);
+		for reward in &rewards {
+			self.award_promotion(id, reward.chips(), &format!("daily challenge, {}", reward.describe()), at);
+		}
+		rewards
+	}
+
+	/// What's in a bad-beat jackpot pool.
+	pub fn jackpot(&self, pool: &str) -> f32 {
+		self.jackpots.get(pool).copied().unwrap_or(0.0)
+	}
+
+	/// Adds a pot's drop to a jackpot pool.
+	pub fn add_to_jackpot(&mut self, pool: &str, amount: f32) {
+		if !is_valid_amount(amount) {
+			return;
+		}
+		*self.jackpots.entry(pool.to_string()).or_insert(0.0) += amount;
+	}
+
+	/// Empties a jackpot pool for paying out; what isn't paid goes back
+	/// with `add_to_jackpot`.
+	pub fn take_jackpot(&mut self, pool: &str) -> f32 {
+		let amount = self.jackpots.remove(pool).unwrap_or(0.0);
+		logging::log("Bank", "JACKPOT", &format!("{}: ${:.2} hit", pool, amount));
+		amount
+	}
+
+	/// Pays out all rakeback owed, once a week: the first call in a new
+	/// week (Monday to Sunday) pays, and the very first call only starts
+	/// the clock. Returns who was paid what.
+	pub fn pay_rakeback(&mut self, today: NaiveDate) -> Vec<(String, f32)> {
+		let due = match self.rakeback_paid_on {
+			Some(last) => today.iso_week() != last.iso_week() && today > last,
+			None => false,
+		};
+		if self.rakeback_paid_on.is_none() || due {
+			self.rakeback_paid_on = Some(today);
+		}
+		if !due {
+			return Vec::new();
+		}
+		let mut owed = self.profiles
+			.iter_mut()
+			.filter(|(_, p)| p.rakeback_owed >= 0.01)
+			.map(|(id, p)| (id.clone(), std::mem::take(&mut p.rakeback_owed)))
+			.collect::<Vec<_>>();
+		owed.sort_by(|a, b| a.0.cmp(&b.0));
+		for (id, amount) in &mut owed {
+			*amount = (*amount * 100.0).floor() / 100.0;
+			self.credit_for(id, *amount, AuditKind::Rakeback, &AuditRef::default());
+			logging::log("Bank", "RAKEBACK", &format!("{}: ${:.2}", id, amount));
+		}
+		owed
+	}
+
+	/// Buys `id` into `game`, holding the buy-in in the game's escrow
+	/// until it's settled or refunded.
+	pub fn escrow_buyin(&mut self, game: GameId, id: &str, amount: f32, table_id: &str) -> Result<(), InsufficientFunds> {
+		self.buyin(id, amount, &AuditRef::table(table_id).game(game))?;
+		let escrow = self.escrows.entry(escrow_key(game)).or_insert_with(|| Escrow {
+			table_id: table_id.to_string(),
+			held: HashMap::new(),
+		});
+		*escrow.held.entry(normalize_id(id)).or_insert(0.0) += amount;
+		Ok(())
+	}
+
+	pub fn escrow(&self, game: GameId) -> Option<&Escrow> {
+		self.escrows.get(&escrow_key(game))
+	}
+
+	/// Drops `id`'s buy-in from `game`'s escrow once their stack has been
+	/// paid out some other way, so a refund can't pay them twice.
+	pub fn release_escrow(&mut self, game: GameId, id: &str) {
+		if let Some(escrow) = self.escrows.get_mut(&escrow_key(game)) {
+			escrow.held.remove(&normalize_id(id));
+		}
+	}
+
+	/// Closes `game`'s escrow after its cashouts or prizes are paid.
+	pub fn settle_escrow(&mut self, game: GameId) {
+		if let Some(escrow) = self.escrows.remove(&escrow_key(game)) {
+			logging::log("Bank", "SETTLE", &format!("game {} at {}", escrow_key(game), escrow.table_id));
+		}
+	}
+
+	/// Hands back every buy-in still held for `game`, returning the total.
+	/// Nothing happens if it was already settled.
+	pub fn refund_escrow(&mut self, game: GameId) -> f32 {
+		match self.escrows.remove(&escrow_key(game)) {
+			Some(escrow) => self.refund(game, escrow),
+			None => 0.0,
+		}
+	}
+
+	/// Refunds every open escrow; run at startup, when no game can still
+	/// be going.
+	pub fn refund_all_escrows(&mut self) -> f32 {
+		let escrows = std::mem::take(&mut self.escrows);
+		escrows
+			.into_iter()
+			.map(|(key, escrow)| {
+				let game = GameId(u64::from_str_radix(&key, 16).unwrap_or_default());
+				self.refund(game, escrow)
+			})
+			.sum()
+	}
+
+	fn refund(&mut self, game: GameId, escrow: Escrow) -> f32 {
+		let at = AuditRef::table(&escrow.table_id).game(game);
+		let mut total = 0.0;
+		for (id, amount) in escrow.held {
+			self.credit_for(&id, amount, AuditKind::Refund, &at);
+			total += amount;
+			logging::log("Bank", "REFUND", &format!("{}: ${:.2} for {}", id, amount, at));
+		}
+		total
+	}
+
+	pub fn default_bankroll(&self) -> f32 {
+		self.default_bankroll
+	}
+
+	/// Brings `id` up to `to`, returning what was added; nothing if they
+	/// already have that much.
+	pub fn top_up(&mut self, id: &str, to: f32) -> f32 {
+		self.ensure_exists(id);
+		let id = normalize_id(id);
+		let profile = self.profiles.get_mut(&id).expect("ensured above");
+		if !is_valid_amount(to) || profile.bankroll >= to {
+			return 0.0;
+		}
+		let added = to - profile.bankroll;
+		profile.bankroll = to;
+		profile.busted_on = None;
+		logging::log("Bank", "TOPUP", &format!("{}: +${:.2} (bal: ${:.2})", id, added, to));
+		self.record(AuditKind::TopUp, &id, added, &AuditRef::default());
+		added
+	}
+
+	/// Whether roster AI `id` can sit down for `buy_in` on `today`, after
+	/// its bust policy has had its say: reloading, or waiting for payday.
+	/// `floor` is the smallest buy-in of any table, below which a player
+	/// moving down has nowhere left to go and reloads.
+	pub fn ready_ai(&mut self, id: &str, buy_in: f32, policy: BustPolicy, floor: f32, today: NaiveDate) -> bool {
+		self.ensure_exists(id);
+		let default_bankroll = self.default_bankroll;
+		let id = normalize_id(id);
+		let profile = self.profiles.get_mut(&id).expect("ensured above");
+		if profile.bankroll >= buy_in {
+			profile.busted_on = None;
+			return true;
+		}
+		let reload = match policy {
+			BustPolicy::Reload => true,
+			BustPolicy::MoveDown => profile.bankroll < floor,
+			BustPolicy::Payday => {
+				let busted_on = profile.busted_on.as_deref()
+					.and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+				match busted_on {
+					Some(busted_on) => today >= next_payday(busted_on),
+					None => {
+						profile.busted_on = Some(today.format("%Y-%m-%d").to_string());
+						logging::log("Bank", "BUSTED", &format!("{}: ${:.2}, out until payday", id, profile.bankroll));
+						false
+					}
+				}
+			}
+		};
+		if !reload || profile.bankroll >= default_bankroll {
+			return false;
+		}
+		logging::log("Bank", "RELOAD", &format!("{}: ${:.2} -> ${:.2}", id, profile.bankroll, default_bankroll));
+		let added = default_bankroll - profile.bankroll;
+		profile.bankroll = default_bankroll;
+		profile.busted_on = None;
+		self.record(AuditKind::Reload, &id, added, &AuditRef::default());
+		default_bankroll >= buy_in
+	}
+
+	pub fn profile_exists(&self, id: &str) -> bool {
+		let id = normalize_id(id);
+		self.profiles.contains_key(&id)
+	}
+
+	pub fn list_players(&self) -> Vec<(&str, &PlayerProfile)> {
+		self.profiles.iter().map(|(k, v)| (k.as_str(), v)).collect()
+	}
+
+	pub fn save(&self) -> Result<(), PokerError> {
+		let file = ProfilesFile {
+			default_bankroll: self.default_bankroll,
+			profiles: self.profiles.clone(),
+			escrows: self.escrows.clone(),
+			rakeback_paid_on: self.rakeback_paid_on.map(|d| d.format("%Y-%m-%d").to_string()),
+			jackpots: self.jackpots.clone(),
+		};
+
+		let content = toml::to_string_pretty(&file)
+			.map_err(|e| PokerError::Config(format!("Failed to serialize profiles: {}", e)))?;
+
+		fs::write(&self.path, content)
+			.map_err(|e| PokerError::io("write", &self.path, e))?;
+
+		Ok(())
+	}
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+	NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+fn escrow_key(game: GameId) -> String {
+	format!("{:016x}", game.0)
+}
+
+/// The first payday after `day`.
+fn next_payday(day: NaiveDate) -> NaiveDate {
+	let days = (7 + PAYDAY.num_days_from_monday() - day.weekday().num_days_from_monday()) % 7;
+	day + Days::new(if days == 0 { 7 } else { days as u64 })
+}
+
+fn ordinal(n: usize) -> String {
+	match n {
+		1 => "1st".to_string(),
+		2 => "2nd".to_string(),
+		3 => "3rd".to_string(),
+		_ => format!("{}th", n),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn test_bank() -> Bank {
+		Bank {
+			profiles: HashMap::new(),
+			escrows: HashMap::new(),
+			audit: None,
+			rakeback_paid_on: None,
+			jackpots: HashMap::new(),
+			default_bankroll: 1000.0,
+			path: PathBuf::from("/tmp/test.toml"),
+		}
+	}
+
+	#[test]
+	fn test_debit_credit() {
+		let mut bank = test_bank();
+
+		bank.credit("alice", 500.0);
+		assert_eq!(bank.get_bankroll("alice"), 1500.0);
+
+		bank.debit("alice", 200.0).unwrap();
+		assert_eq!(bank.get_bankroll("alice"), 1300.0);
+
+		let err = bank.debit("alice", 2000.0).unwrap_err();
+		assert_eq!(err.available, 1300.0);
+		assert_eq!(err.required, 2000.0);
+	}
+
+	#[test]
+	fn test_get_default_bankroll() {
+		let bank = test_bank();
+		assert_eq!(bank.get_bankroll("unknown_player"), 1000.0);
+	}
+
+	#[test]
+	fn test_get_profile_default() {
+		let bank = test_bank();
+		let profile = bank.get("unknown_player");
+		assert_eq!(profile.bankroll, 1000.0);
+	}
+
+	#[test]
+	fn test_ensure_exists_creates_profile() {
+		let mut bank = test_bank();
+		assert!(!bank.profiles.contains_key("bob"));
+		bank.ensure_exists("bob");
+		assert!(bank.profiles.contains_key("bob"));
+		assert_eq!(bank.get_bankroll("bob"), 1000.0);
+	}
+
+	#[test]
+	fn test_ensure_exists_preserves_existing() {
+		let mut bank = test_bank();
+		bank.credit("bob", 500.0);
+		bank.ensure_exists("bob");
+		assert_eq!(bank.get_bankroll("bob"), 1500.0);
+	}
+
+	#[test]
+	fn test_buyin_debits_correctly() {
+		let mut bank = test_bank();
+		bank.ensure_exists("alice");
+		bank.buyin("alice", 100.0, &AuditRef::table("table-1")).unwrap();
+		assert_eq!(bank.get_bankroll("alice"), 900.0);
+	}
+
+	#[test]
+	fn test_buyin_insufficient_funds() {
+		let mut bank = test_bank();
+		bank.ensure_exists("alice");
+		let result = bank.buyin("alice", 2000.0, &AuditRef::table("table-1"));
+		assert!(result.is_err());
+	}
+
+	#[test]
+	fn test_cashout_credits_correctly() {
+		let mut bank = test_bank();
+		bank.ensure_exists("alice");
+		bank.cashout("alice", 500.0, &AuditRef::table("table-1"));
+		assert_eq!(bank.get_bankroll("alice"), 1500.0);
+	}
+
+	#[test]
+	fn test_award_prize() {
+		let mut bank = test_bank();
+		bank.award_prize("winner", 1000.0, 1, &AuditRef::default());
+		assert_eq!(bank.get_bankroll("winner"), 2000.0);
+	}
+
+	#[test]
+	fn test_list_players_empty() {
+		let bank = test_bank();
+		assert!(bank.list_players().is_empty());
+	}
+
+	#[test]
+	fn test_list_players_with_profiles() {
+		let mut bank = test_bank();
+		bank.ensure_exists("alice");
+		bank.ensure_exists("bob");
+		let players = bank.list_players();
+		assert_eq!(players.len(), 2);
+	}
+
+	#[test]
+	fn test_bust_policies() {
+		// 2024-03-04 was a Monday
+		let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+		let mut bank = test_bank();
+		for id in ["reloader", "payday", "mover"] {
+			bank.register(id, 30.0);
+		}
+
+		assert!(bank.ready_ai("reloader", 40.0, BustPolicy::Reload, 40.0, monday));
+		assert_eq!(bank.get_bankroll("reloader"), 1000.0);
+
+		// Broke on Monday, back on Friday
+		assert!(!bank.ready_ai("payday", 40.0, BustPolicy::Payday, 40.0, monday));
+		assert_eq!(bank.get("payday").busted_on.as_deref(), Some("2024-03-04"));
+		assert!(!bank.ready_ai("payday", 40.0, BustPolicy::Payday, 40.0, monday + Days::new(3)));
+		assert!(bank.ready_ai("payday", 40.0, BustPolicy::Payday, 40.0, monday + Days::new(4)));
+		assert_eq!(bank.get_bankroll("payday"), 1000.0);
+		assert_eq!(bank.get("payday").busted_on, None);
+
+		// Still covers the smallest table, so no reload for the bigger one
+		assert!(!bank.ready_ai("mover", 100.0, BustPolicy::MoveDown, 20.0, monday));
+		assert!(bank.ready_ai("mover", 20.0, BustPolicy::MoveDown, 20.0, monday));
+		assert_eq!(bank.get_bankroll("mover"), 30.0);
+		bank.debit("mover", 15.0).unwrap();
+		assert!(bank.ready_ai("mover", 100.0, BustPolicy::MoveDown, 20.0, monday));
+		assert_eq!(bank.get_bankroll("mover"), 1000.0);
+	}
+
+	#[test]
+	fn test_escrow_refund_and_settle() {
+		let mut bank = test_bank();
+		bank.register("alice", 500.0);
+		bank.register("bob", 50.0);
+		let game = GameId(7);
+
+		// Bob can't pay, so Alice gets hers back
+		bank.escrow_buyin(game, "Alice", 100.0, "t").unwrap();
+		assert!(bank.escrow_buyin(game, "bob", 100.0, "t").is_err());
+		assert_eq!(bank.get_bankroll("alice"), 400.0);
+		assert_eq!(bank.refund_escrow(game), 100.0);
+		assert_eq!(bank.get_bankroll("alice"), 500.0);
+		assert!(bank.escrow(game).is_none());
+
+		// Settled games refund nothing
+		bank.escrow_buyin(game, "alice", 100.0, "t").unwrap();
+		bank.settle_escrow(game);
+		assert_eq!(bank.refund_escrow(game), 0.0);
+		assert_eq!(bank.get_bankroll("alice"), 400.0);
+
+		// A player already cashed out isn't refunded again
+		bank.escrow_buyin(GameId(8), "alice", 100.0, "t").unwrap();
+		bank.escrow_buyin(GameId(8), "bob", 50.0, "t").unwrap();
+		bank.release_escrow(GameId(8), "bob");
+		assert_eq!(bank.refund_all_escrows(), 100.0);
+		assert_eq!(bank.get_bankroll("alice"), 400.0);
+		assert_eq!(bank.get_bankroll("bob"), 0.0);
+	}
+
+	#[test]
+	fn test_jackpot_pools() {
+		let mut bank = test_bank();
+		bank.add_to_jackpot("main", 1.0);
+		bank.add_to_jackpot("main", 1.5);
+		bank.add_to_jackpot("high", 5.0);
+		bank.add_to_jackpot("main", -3.0);
+		assert_eq!(bank.jackpot("main"), 2.5);
+		assert_eq!(bank.take_jackpot("main"), 2.5);
+		assert_eq!(bank.jackpot("main"), 0.0);
+		assert_eq!(bank.jackpot("high"), 5.0);
+	}
+
+	#[test]
+	fn test_escrows_survive_a_restart() {
+		let mut bank = test_bank();
+		bank.register("alice", 500.0);
+		bank.escrow_buyin(GameId(9), "alice", 100.0, "t").unwrap();
+		let file = ProfilesFile {
+			default_bankroll: 1000.0,
+			profiles: bank.profiles.clone(),
+			escrows: bank.escrows.clone(),
+			rakeback_paid_on: None,
+			jackpots: HashMap::new(),
+		};
+		let reloaded: ProfilesFile = toml::from_str(&toml::to_string_pretty(&file).unwrap()).unwrap();
+		assert_eq!(reloaded.escrows[&escrow_key(GameId(9))].held["alice"], 100.0);
+	}
+
+	#[test]
+	fn test_every_change_is_audited() {
+		let dir = std::env::temp_dir().join(format!("poker-bank-audit-{}", std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		let mut bank = test_bank();
+		bank.path = dir.join("profiles.toml");
+		bank.audit = Some(AuditLog::open(dir.join(AUDIT_FILE)));
+
+		bank.register("alice", 500.0);
+		bank.escrow_buyin(GameId(1), "alice", 100.0, "t").unwrap();
+		bank.cashout("alice", 180.0, &AuditRef::table("t").game(GameId(1)));
+		bank.credit("newcomer", 50.0);
+		bank.register("broke", 10.0);
+		bank.ready_ai("broke", 40.0, BustPolicy::Reload, 40.0, NaiveDate::from_ymd_opt(2024, 3, 4).unwrap());
+		bank.top_up("alice", 2000.0);
+		bank.escrow_buyin(GameId(2), "broke", 40.0, "t").unwrap();
+		bank.refund_escrow(GameId(2));
+
+		let report = bank.verify_audit().unwrap();
+		assert!(report.is_clean(), "{:?}", report.problems);
+		assert_eq!((report.entries, report.players), (10, 3));
+
+		// Changing a balance behind the bank's back shows up
+		bank.profiles.get_mut("alice").unwrap().bankroll += 1.0;
+		assert_eq!(bank.verify_audit().unwrap().problems.len(), 1);
+		let _ = fs::remove_dir_all(&dir);
+	}
+
+	#[test]
+	fn test_rakeback_pays_weekly() {
+		// 2024-03-04 was a Monday
+		let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+		let mut bank = test_bank();
+		bank.register("alice", 100.0);
+		bank.accrue_rakeback("alice", 1.25);
+		bank.accrue_rakeback("alice", 2.0);
+		bank.accrue_rakeback("bob", 0.5);
+
+		// The first look only starts the clock, and a week isn't up on Sunday
+		assert!(bank.pay_rakeback(monday).is_empty());
+		assert!(bank.pay_rakeback(monday + Days::new(6)).is_empty());
+		let paid = bank.pay_rakeback(monday + Days::new(7));
+		assert_eq!(paid, [("alice".to_string(), 3.25), ("bob".to_string(), 0.5)]);
+		assert_eq!(bank.get_bankroll("alice"), 103.25);
+		assert_eq!(bank.get("alice").rakeback_owed, 0.0);
+		assert!(bank.pay_rakeback(monday + Days::new(14)).is_empty());
+	}
+
+	#[test]
+	fn test_daily_challenges_pay_and_persist() {
+		let today = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+		let mut bank = test_bank();
+		bank.register("alice", 100.0);
+		let hand = HandOutcome {
+			won: true,
+			on_button: true,
+			showdown: false,
+			betting: crate::table::BettingStructure::NoLimit,
+			session_hands: 1,
+		};
+		let mut paid = 0.0;
+		for _ in 0..60 {
+			let rewards = bank.progress_challenges("Alice", today, &hand, &AuditRef::default());
+			paid += rewards.iter().map(Reward::chips).sum::<f32>();
+		}
+		assert!(paid > 0.0, "some of the day's goals are met");
+		assert_eq!(bank.get_bankroll("alice"), 100.0 + paid);
+
+		let file = ProfilesFile {
+			default_bankroll: 1000.0,
+			profiles: bank.profiles.clone(),
+			escrows: HashMap::new(),
+			rakeback_paid_on: None,
+			jackpots: HashMap::new(),
+		};
+		let reloaded: ProfilesFile = toml::from_str(&toml::to_string_pretty(&file).unwrap()).unwrap();
+		assert_eq!(reloaded.profiles["alice"].challenges, bank.get("alice").challenges);
+	}
+
+	#[test]
+	fn test_top_up() {
+		let mut bank = test_bank();
+		bank.register("low", 200.0);
+		bank.register("high", 5000.0);
+		assert_eq!(bank.top_up("low", 1000.0), 800.0);
+		assert_eq!(bank.get_bankroll("low"), 1000.0);
+		assert_eq!(bank.top_up("high", 1000.0), 0.0);
+		assert_eq!(bank.get_bankroll("high"), 5000.0);
+		assert_eq!(bank.top_up("new", 1500.0), 500.0);
+	}
+
+	#[test]
+	fn test_next_payday() {
+		let friday = NaiveDate::from_ymd_opt(2024, 3, 8).unwrap();
+		assert_eq!(next_payday(friday - Days::new(1)), friday);
+		assert_eq!(next_payday(friday), friday + Days::new(7));
+	}
+
+	#[test]
+	fn test_ordinal() {
+		assert_eq!(ordinal(1), "1st");
+		assert_eq!(ordinal(2), "2nd");
+		assert_eq!(ordinal(3), "3rd");
+		assert_eq!(ordinal(4), "4th");
+		assert_eq!(ordinal(11), "11th");
+	}
+
+	#[test]
+	fn test_insufficient_funds_display() {
+		let err = InsufficientFunds {
+			player_id: "alice".to_string(),
+			required: 500.0,
+			available: 100.0,
+		};
+		let msg = format!("{}", err);
+		assert!(msg.contains("alice"));
+		assert!(msg.contains("500"));
+		assert!(msg.contains("100"));
+	}
+
+	#[test]
+	fn test_case_insensitive_ids() {
+		let mut bank = test_bank();
+		bank.credit("Alice", 500.0);
+		assert_eq!(bank.get_bankroll("alice"), 1500.0);
+		assert_eq!(bank.get_bankroll("ALICE"), 1500.0);
+		assert_eq!(bank.get_bankroll("Alice"), 1500.0);
+		assert!(bank.profile_exists("alice"));
+		assert!(bank.profile_exists("ALICE"));
+	}
+}
@@ -1,11 +1,13 @@
 use std::sync::Arc;
 
-use transparent_poker::config::load_strategies_auto;
+use transparent_poker::config::{load_preferences, load_strategies_auto};
 use transparent_poker::engine::{BettingStructure, GameRunner, RunnerConfig};
 use transparent_poker::events::Seat;
-use transparent_poker::players::{FoldingPlayer, RulesPlayer};
+use transparent_poker::money::{chips, set_format};
+use transparent_poker::players::{FoldingPlayer, PlayerPort, RulesPlayer, SubprocessPlayer};
 
 fn main() {
+	set_format(load_preferences().money);
 	let strategies = load_strategies_auto()
 		.unwrap_or_default();
 
@@ -15,13 +17,17 @@ fn main() {
 		starting_stack: 500.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(50),
 		seed: None,
+		game_id: None,
 		max_seats: None,
+		action_timeout: None,
 	};
 
 	let runtime = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
@@ -33,14 +39,12 @@ fn main() {
 		Seat(0),
 		"Lisa",
 		strategies.get_or_default("lag"),
-		10.0,
 	));
 
 	let lonny = Arc::new(RulesPlayer::new(
 		Seat(1),
 		"Lonny",
 		strategies.get_or_default("rock"),
-		10.0,
 	));
 
 	let foldy = Arc::new(FoldingPlayer::new(Seat(2), "FoldBot"));
@@ -49,6 +53,24 @@ fn main() {
 	runner.add_player(lonny);
 	runner.add_player(foldy);
 
+	// Any arguments are a bot command to seat alongside them:
+	//   ai-game python3 my_bot.py
+	let bot_command: Vec<String> = std::env::args().skip(1).collect();
+	let mut bot = None;
+	if let Some((program, args)) = bot_command.split_first() {
+		match SubprocessPlayer::spawn(Seat(3), "Bot", program, args) {
+			Ok(spawned) => {
+				let spawned = Arc::new(spawned);
+				runner.add_player(Arc::clone(&spawned) as Arc<dyn PlayerPort>);
+				bot = Some(spawned);
+			}
+			Err(e) => {
+				eprintln!("{}", e.user_message());
+				std::process::exit(1);
+			}
+		}
+	}
+
 	println!("Starting rules-based AI game");
 	println!("See logs/poker-*.log for details\n");
 
@@ -57,28 +79,29 @@ fn main() {
 	});
 
 	let mut hand_count = 0;
-	loop {
-		match game_handle.event_rx.recv() {
-			Ok(event) => {
-				match &event {
-					transparent_poker::events::GameEvent::HandStarted { hand_num, .. } => {
-						hand_count = *hand_num;
-						println!("\n=== Hand {} ===", hand_num);
-					}
-					transparent_poker::events::GameEvent::ActionTaken { seat, action, pot_after, .. } => {
-						println!("  Seat {}: {} (pot: ${:.0})", seat.0, action.description(), pot_after);
-					}
-					transparent_poker::events::GameEvent::PotAwarded { seat, amount, hand_description, .. } => {
-						println!("  → Seat {} wins ${:.0} {:?}", seat.0, amount, hand_description);
-					}
-					transparent_poker::events::GameEvent::GameEnded { .. } => {
-						println!("\nGame ended after {} hands", hand_count);
-						break;
-					}
-					_ => {}
+	while let Ok(event) = game_handle.event_rx.recv() {
+		if let Some(bot) = &bot {
+			bot.observe(&event);
+		}
+		match &event {
+			transparent_poker::events::GameEvent::HandStarted { hand_num, .. } => {
+				hand_count = *hand_num;
+				println!("\n=== Hand {} ===", hand_num);
+			}
+			transparent_poker::events::GameEvent::ActionTaken { seat, action, pot_after, .. } => {
+				println!("  Seat {}: {} (pot: {})", seat.0, action.description(), chips(*pot_after));
+			}
+			transparent_poker::events::GameEvent::PotAwarded { seat, amount, hand_description, .. } => {
+				println!("  → Seat {} wins {} {:?}", seat.0, chips(*amount), hand_description);
+			}
+			transparent_poker::events::GameEvent::GameEnded { detail, .. } => {
+				println!("\nGame ended after {} hands", hand_count);
+				if let Some(detail) = detail {
+					println!("{}", detail);
 				}
+				break;
 			}
-			Err(_) => break,
+			_ => {}
 		}
 	}
 }
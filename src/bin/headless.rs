@@ -1,12 +1,21 @@
+use std::io::BufRead;
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 
+use transparent_poker::config::load_preferences;
 use transparent_poker::engine::{BettingStructure, GameRunner, RunnerConfig};
 use transparent_poker::events::GameEvent;
 use transparent_poker::players::{CallingPlayer, FoldingPlayer, TestPlayer};
 use transparent_poker::events::{PlayerAction, Seat};
+use transparent_poker::money::{chips, set_format};
+
+/// Keys for `--step`, read a line at a time from stdin.
+const STEP_HELP: &str = "Enter: step   d: dump state   c: continue   p: pause   q: quit";
 
 fn main() {
+	set_format(load_preferences().money);
 	println!("=== Poker Engine Headless Test ===\n");
+	let stepping = std::env::args().any(|arg| arg == "--step");
 
 	let config = RunnerConfig {
 		small_blind: 5.0,
@@ -14,13 +23,17 @@ fn main() {
 		starting_stack: 100.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: None,
 		seed: None,
+		game_id: None,
 		max_seats: None,
+		action_timeout: None,
 	};
 
 	let runtime = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
@@ -46,6 +59,35 @@ fn main() {
 	runner.add_player(bob);
 	runner.add_player(carol);
 
+	if stepping {
+		// Held before the first hand; each step goes on to the next action
+		// request or hand
+		handle.pause.set(true);
+		println!("{}\n", STEP_HELP);
+		let pause = handle.pause.clone();
+		let quit_signal = Arc::clone(&handle.quit_signal);
+		std::thread::spawn(move || {
+			for line in std::io::stdin().lock().lines() {
+				let Ok(line) = line else { break };
+				match line.trim() {
+					"" => pause.step(),
+					"d" => println!("{}", pause.dump()),
+					"c" => {
+						pause.set(false);
+					}
+					"p" => {
+						pause.set(true);
+					}
+					"q" => {
+						quit_signal.store(true, Ordering::SeqCst);
+						pause.set(false);
+					}
+					_ => println!("{}", STEP_HELP),
+				}
+			}
+		});
+	}
+
 	std::thread::spawn(move || {
 		runner.run();
 	});
@@ -63,13 +105,16 @@ fn main() {
 			GameEvent::GameStarted { seats } => {
 				println!("[GAME] Started with {} players:", seats.len());
 				for s in seats {
-					println!("       Seat {}: {} (${:.0})", s.seat.0, s.name, s.stack);
+					println!("       Seat {}: {} ({})", s.seat.0, s.name, chips(s.stack));
 				}
 			}
+			GameEvent::GameChanged { name, .. } => {
+				println!("\n[GAME] Now playing {}", name);
+			}
 			GameEvent::HandStarted { hand_num, button, blinds, .. } => {
 				hand_count += 1;
-				println!("\n[HAND #{}] Button: Seat {}, Blinds: ${:.0}/${:.0}",
-					hand_num, button.0, blinds.small, blinds.big);
+				println!("\n[HAND #{}] Button: Seat {}, Blinds: {}/{}",
+					hand_num, button.0, chips(blinds.small), chips(blinds.big));
 			}
 			GameEvent::HoleCardsDealt { seat, cards } => {
 				println!("  [DEAL] Seat {} gets {}{} {}{}",
@@ -78,9 +123,9 @@ fn main() {
 					cards[1].rank, cards[1].suit);
 			}
 			GameEvent::BlindPosted { seat, blind_type, amount } => {
-				println!("  [BLIND] Seat {} posts {:?} ${:.0}", seat.0, blind_type, amount);
+				println!("  [BLIND] Seat {} posts {:?} {}", seat.0, blind_type, chips(*amount));
 			}
-			GameEvent::StreetChanged { street, board } => {
+			GameEvent::StreetChanged { street, board, .. } => {
 				let board_str: String = board
 					.iter()
 					.map(|c| format!("{}{}", c.rank, c.suit))
@@ -88,33 +133,42 @@ fn main() {
 					.join(" ");
 				println!("  [STREET] {:?} - Board: {}", street, if board_str.is_empty() { "-".to_string() } else { board_str });
 			}
-			GameEvent::ActionTaken { seat, action, stack_after, pot_after } => {
-				println!("  [ACTION] Seat {}: {} (stack: ${:.0}, pot: ${:.0})",
-					seat.0, action.description(), stack_after, pot_after);
+			GameEvent::ActionTaken { seat, action, stack_after, pot_after, .. } => {
+				println!("  [ACTION] Seat {}: {} (stack: {}, pot: {})",
+					seat.0, action.description(), chips(*stack_after), chips(*pot_after));
 			}
 			GameEvent::PotAwarded { seat, amount, hand_description, .. } => {
 				let desc = hand_description.as_deref().unwrap_or("no showdown");
-				println!("  [AWARD] Seat {} wins ${:.0} ({})", seat.0, amount, desc);
+				println!("  [AWARD] Seat {} wins {} ({})", seat.0, chips(*amount), desc);
 			}
 			GameEvent::HandEnded { results, .. } => {
 				println!("  [HAND END] Results:");
 				for r in results {
 					let change = if r.stack_change >= 0.0 {
-						format!("+${:.0}", r.stack_change)
+						format!("+{}", chips(r.stack_change))
 					} else {
-						format!("-${:.0}", -r.stack_change)
+						chips(r.stack_change)
 					};
-					println!("       Seat {}: {} (now ${:.0})", r.seat.0, change, r.final_stack);
+					println!("       Seat {}: {} (now {})", r.seat.0, change, chips(r.final_stack));
 				}
 			}
-			GameEvent::GameEnded { reason, final_standings } => {
+			GameEvent::GameEnded { reason, final_standings, detail } => {
 				println!("\n[GAME OVER] Reason: {:?}", reason);
+				if let Some(detail) = detail {
+					println!("{}", detail);
+				}
 				println!("Final standings:");
 				for s in final_standings {
-					println!("  {}. {} - ${:.0}", s.finish_position, s.name, s.final_stack);
+					println!("  {}. {} - {}", s.finish_position, s.name, chips(s.final_stack));
 				}
 				break;
 			}
+			GameEvent::GamePaused { .. } => {
+				println!("  [PAUSED]");
+			}
+			GameEvent::GameResumed { .. } => {
+				println!("  [RESUMED]");
+			}
 			GameEvent::ChatMessage { sender, text } => {
 				match sender {
 					transparent_poker::events::ChatSender::System => println!("  [SYS] {}", text),
@@ -1,20 +1,40 @@
-use std::io::{self, stdout};
+use std::io;
+use std::path::PathBuf;
 use std::time::Duration;
 
-use clap::{Parser, Subcommand};
-use crossterm::{
-	execute,
-	terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle},
-};
-use ratatui::{backend::CrosstermBackend, Terminal};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use crossterm::{execute, terminal::SetTitle};
 
 use transparent_poker::bank::Bank;
+use transparent_poker::challenges;
+use transparent_poker::daemon::{self, DaemonInfo};
+use transparent_poker::demo::{self, DemoConfig};
+use transparent_poker::config::{self, load_players_auto, load_preferences, load_strategies_auto};
 use transparent_poker::embedded_server::EmbeddedServer;
+use transparent_poker::error::PokerError;
+use transparent_poker::feed::{self, FeedConfig, ObserverFeed};
 use transparent_poker::game_loop;
-use transparent_poker::lobby::NetworkBackend;
-use transparent_poker::menu::{Menu, MenuResult};
-use transparent_poker::net::{GameClient, GameServer};
-use transparent_poker::theme::Theme;
+use transparent_poker::history::luck::LuckReport;
+use transparent_poker::history::pace::PaceReport;
+use transparent_poker::history::puzzle;
+use transparent_poker::history::solver::FlopSpot;
+use transparent_poker::history::{self, parse_hand_ref, HistoryLog};
+use transparent_poker::league::Leagues;
+use transparent_poker::lobby::{LobbyBackend, LobbyCommand, NetworkBackend};
+use transparent_poker::menu::{self, Menu, MenuResult};
+use transparent_poker::money::{big_blinds, money};
+use transparent_poker::logging::LogLevel;
+use transparent_poker::events::HandId;
+use transparent_poker::net::recording::Recordings;
+use transparent_poker::net::moderation::{self, Moderation, Target, STRIKE_LIMIT};
+use transparent_poker::net::soak::{self, SoakConfig};
+use transparent_poker::net::proxy::{self, Proxy, ReplayConfig};
+use transparent_poker::net::stress::{self, StressConfig};
+use transparent_poker::net::{relay, BotArena, GameClient, GameServer, Relay, ServerConfig};
+use transparent_poker::simulate::{simulate, SimulationConfig};
+use transparent_poker::theme::{Palette, Theme};
+use transparent_poker::tui::{TuiTerminal, WINDOW_TITLE};
+use transparent_poker::tutorial;
 
 #[derive(Parser)]
 #[command(name = "poker")]
@@ -23,6 +43,10 @@ use transparent_poker::theme::Theme;
 struct Cli {
 	#[command(subcommand)]
 	command: Commands,
+
+	#[arg(long, global = true, env = "POKER_USER")]
+	#[arg(help = "Profile whose preferences and hand histories to use")]
+	profile: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -37,16 +61,275 @@ enum Commands {
 		#[arg(help = "Color theme")]
 		theme: Option<String>,
 
-		#[arg(short, long)]
-		#[arg(help = "Connect to server (e.g., localhost:9999)")]
-		server: Option<String>,
+		#[command(flatten)]
+		connect: ConnectArgs,
+
+		#[arg(long)]
+		#[arg(help = "Draw without color (also set by NO_COLOR)")]
+		no_color: bool,
+
+		#[arg(long, env = "POKER_LINEAR")]
+		#[arg(help = "Screen-reader friendly output: plain lines of text instead of a drawn table")]
+		linear: bool,
+
+		#[arg(long, value_name = "NAMES", value_delimiter = ',', conflicts_with_all = ["server", "discover", "relay", "attach"])]
+		#[arg(help = "Others taking turns at this terminal, comma-separated (e.g., bob,carol)")]
+		hot_seat: Vec<String>,
+
+		#[arg(long, value_name = "PATH", conflicts_with = "hot_seat")]
+		#[arg(help = "Keep a one-line JSON status (table, stack, pot, your turn) in this file for tmux or screen")]
+		status_file: Option<PathBuf>,
+
+		#[command(flatten)]
+		feed: FeedArgs,
+	},
+
+	#[command(about = "Learn to play with a few guided hands")]
+	Tutorial {
+		#[arg(short, long, env = "POKER_THEME")]
+		#[arg(help = "Color theme")]
+		theme: Option<String>,
+
+		#[arg(long)]
+		#[arg(help = "Draw without color (also set by NO_COLOR)")]
+		no_color: bool,
+	},
+
+	#[command(about = "Watch a short game between AI players, for screenshots or a smoke test")]
+	Demo {
+		#[arg(long)]
+		#[arg(help = "Quit on its own when the game is over")]
+		auto: bool,
+
+		#[arg(long, value_name = "PATH")]
+		#[arg(help = "Write each hand's final screen to this file as text instead of drawing the table")]
+		capture: Option<PathBuf>,
+
+		#[arg(long, default_value = "10")]
+		#[arg(help = "Hands to play")]
+		hands: u32,
+
+		#[arg(long, default_value = "4")]
+		#[arg(help = "AI players at the table")]
+		players: usize,
+
+		#[arg(long, value_delimiter = ',', value_name = "STRATEGY,...")]
+		#[arg(help = "Seat one AI playing each of these strategies instead, to compare them")]
+		strategies: Vec<String>,
+
+		#[arg(long, default_value = "1")]
+		#[arg(help = "Play this many times the usual pace; + and - change it while watching")]
+		speed: f32,
+
+		#[arg(long)]
+		#[arg(help = "Have the pit boss comment on the big hands (needs the pit_boss model)")]
+		commentary: bool,
+
+		#[arg(long)]
+		#[arg(help = "Shuffle seed, for the same game every run")]
+		seed: Option<u64>,
+
+		#[arg(short, long, env = "POKER_THEME")]
+		#[arg(help = "Color theme")]
+		theme: Option<String>,
+
+		#[arg(long)]
+		#[arg(help = "Draw without color (also set by NO_COLOR)")]
+		no_color: bool,
+	},
+
+	#[command(about = "Watch a local game from another terminal (see --feed-socket)")]
+	Watch {
+		#[arg(long, value_name = "PATH")]
+		#[arg(help = "Socket the game feeds [default: the one --feed-socket uses]")]
+		socket: Option<PathBuf>,
+
+		#[arg(short, long, env = "POKER_THEME")]
+		#[arg(help = "Color theme")]
+		theme: Option<String>,
+
+		#[arg(long)]
+		#[arg(help = "Draw without color (also set by NO_COLOR)")]
+		no_color: bool,
+	},
+
+	#[command(about = "Keep a game server running in the background, for `poker play --attach`")]
+	Daemon {
+		#[command(subcommand)]
+		action: DaemonAction,
 	},
 
 	#[command(about = "Run a standalone poker server")]
 	Serve {
-		#[arg(short, long, default_value = "127.0.0.1:9999")]
+		#[command(flatten)]
+		serve: ServeArgs,
+
+		#[command(flatten)]
+		feed: FeedArgs,
+	},
+
+	#[command(about = "Run a relay, so home games need no port forwarding")]
+	Relay {
+		#[arg(short, long, default_value = "0.0.0.0:9900")]
+		#[arg(help = "Address to bind")]
+		bind: String,
+	},
+
+	#[command(about = "Sit between clients and a server, logging every message, or replay a logged client")]
+	Proxy {
+		#[arg(short, long, default_value = "127.0.0.1:9997")]
+		#[arg(help = "Address to take clients on")]
+		listen: String,
+
+		#[arg(short, long, value_name = "ADDR", default_value = "127.0.0.1:9999")]
+		#[arg(help = "Server to forward to, or to replay against")]
+		upstream: String,
+
+		#[arg(long, value_name = "PATH")]
+		#[arg(help = "File to log to [default: proxy.jsonl; stdout when replaying]")]
+		log: Option<PathBuf>,
+
+		#[arg(long, value_name = "PATH")]
+		#[arg(help = "Replay a client from this log instead of proxying")]
+		replay: Option<PathBuf>,
+
+		#[arg(long, value_name = "N", requires = "replay")]
+		#[arg(help = "Connection to replay [default: the first one logged]")]
+		connection: Option<u64>,
+
+		#[arg(long, requires = "replay")]
+		#[arg(help = "Send back to back, without the recorded pauses")]
+		no_wait: bool,
+	},
+
+	#[command(about = "Run a bot-only server for external bot clients")]
+	Arena {
+		#[arg(short, long, default_value = "127.0.0.1:9998")]
 		#[arg(help = "Address to bind")]
 		bind: String,
+
+		#[arg(long, default_value_t = transparent_poker::net::arena::DEFAULT_ARENA_HANDS)]
+		#[arg(help = "Hands per match")]
+		hands: u32,
+	},
+
+	#[command(about = "Play AI strategies against each other and compare results")]
+	Simulate {
+		#[arg(required = true, num_args = 2..)]
+		#[arg(help = "Strategies to seat, one per seat (e.g., rock maniac)")]
+		strategies: Vec<String>,
+
+		#[arg(short = 'n', long, default_value_t = 1000)]
+		#[arg(help = "Decks to deal")]
+		deals: u32,
+
+		#[arg(long)]
+		#[arg(help = "Deal each deck once per seating, so card luck cancels out")]
+		duplicate: bool,
+
+		#[arg(long, default_value_t = 0)]
+		#[arg(help = "RNG seed for the shuffles")]
+		seed: u64,
+	},
+
+	#[command(about = "Write JSON Schemas for events and protocol messages")]
+	Schema {
+		#[arg(short, long, default_value = "docs/schema")]
+		#[arg(help = "Directory to write into")]
+		out: String,
+	},
+
+	#[command(about = "Export a session's hands for spreadsheets or scripts")]
+	Export {
+		#[arg(short, long)]
+		#[arg(help = "Session to export; leave out to list recorded sessions")]
+		session: Option<String>,
+
+		#[arg(short, long, value_enum, default_value_t = ExportFormat::Csv)]
+		#[arg(help = "Output format")]
+		format: ExportFormat,
+
+		#[arg(long)]
+		#[arg(help = "Only hands with this tag; without --session, from every session")]
+		tag: Option<String>,
+	},
+
+	#[command(about = "Show a session's results against its all-in EV")]
+	Stats {
+		#[arg(short, long)]
+		#[arg(help = "Session to report on; leave out to list recorded sessions")]
+		session: Option<String>,
+
+		#[arg(long)]
+		#[arg(help = "Only hands with this tag; without --session, from every session")]
+		tag: Option<String>,
+	},
+
+	#[command(about = "Go back over a session's hands, with everyone's cards the table kept")]
+	Review {
+		#[arg(short, long)]
+		#[arg(help = "Session to review; leave out to list recorded sessions")]
+		session: Option<String>,
+
+		#[arg(long)]
+		#[arg(help = "Only hands with this tag; without --session, from every session")]
+		tag: Option<String>,
+	},
+
+	#[command(about = "Show how a session's decks came out: the seed, each deck's hash, and how evenly the cards fell")]
+	Shuffle {
+		#[arg(short, long)]
+		#[arg(help = "Session to report on; leave out to list recorded sessions")]
+		session: Option<String>,
+	},
+
+	#[command(about = "Tag a hand to find it again, or list tagged hands")]
+	Tag {
+		#[arg(help = "Hand reference from the table header, like 56789abc-3; leave out to list tagged hands")]
+		hand: Option<String>,
+
+		#[arg(help = "Tags to add [default: review later]")]
+		tags: Vec<String>,
+
+		#[arg(long)]
+		#[arg(help = "Take the tags off instead")]
+		remove: bool,
+	},
+
+	#[command(about = "Try the puzzle of the day: a bet faced in your own hands, to answer before seeing what happened")]
+	Puzzle {
+		#[arg(short, long)]
+		#[arg(help = "Only hands from this session [default: every session]")]
+		session: Option<String>,
+
+		#[arg(long)]
+		#[arg(help = "Only this player's decisions [default: any human whose cards were kept]")]
+		player: Option<String>,
+
+		#[arg(long)]
+		#[arg(help = "Use the bundled sample hands instead of your own")]
+		samples: bool,
+	},
+
+	#[command(about = "Set a hand's flop up for a solver, with pot, stacks and a bet tree")]
+	Study {
+		#[arg(help = "Hand reference from the table header, like 56789abc-3")]
+		hand: String,
+
+		#[arg(short, long, value_enum, default_value_t = StudyFormat::Texassolver)]
+		#[arg(help = "Output format")]
+		format: StudyFormat,
+
+		#[arg(short, long, value_name = "FILE")]
+		#[arg(help = "Write it here instead of to stdout")]
+		out: Option<PathBuf>,
+	},
+
+	#[command(about = "Import PokerStars or GGPoker hand histories")]
+	Import {
+		#[arg(required = true)]
+		#[arg(help = "Hand history text files")]
+		files: Vec<String>,
 	},
 
 	#[command(about = "List available color themes")]
@@ -73,6 +356,305 @@ enum Commands {
 		#[command(subcommand)]
 		action: BankrollAction,
 	},
+
+	#[command(about = "Check the bank's books")]
+	Bank {
+		#[command(subcommand)]
+		action: BankAction,
+	},
+
+	#[command(about = "Show today's challenges and how far along they are")]
+	Challenges {
+		#[arg(help = "Player name (defaults to your username)")]
+		name: Option<String>,
+	},
+
+	#[command(about = "See how the home game leagues stand")]
+	League {
+		#[command(subcommand)]
+		action: LeagueAction,
+	},
+
+	#[command(about = "Ban, mute and warn players, or pause tables, on a server")]
+	Admin {
+		#[arg(long, value_name = "PATH")]
+		#[arg(help = "Server config whose moderation file to use, instead of server.toml in the config directory")]
+		config: Option<PathBuf>,
+
+		#[command(subcommand)]
+		action: AdminAction,
+	},
+
+	#[command(about = "Top up every roster AI's bankroll")]
+	Topup {
+		#[arg(long)]
+		#[arg(help = "Bankroll to bring them up to (defaults to the default bankroll)")]
+		to: Option<f32>,
+	},
+
+	#[command(about = "Leave a server to scripted clients for hours, watching for stuck tables and leaks", hide = true)]
+	Soak {
+		#[arg(long, default_value_t = 24)]
+		#[arg(help = "Bots to connect")]
+		bots: usize,
+
+		#[arg(long, default_value_t = 4)]
+		#[arg(help = "Cash tables, besides one sit & go")]
+		tables: usize,
+
+		#[arg(long = "for", value_name = "DURATION", default_value = "1h", value_parser = moderation::parse_duration)]
+		#[arg(help = "How long to run (e.g., 30m, 6h)")]
+		duration: chrono::Duration,
+
+		#[arg(long, value_name = "SECONDS", default_value_t = 60)]
+		#[arg(help = "Seconds between samples of the server's memory and threads")]
+		sample_every: u64,
+
+		#[arg(long, default_value_t = 0)]
+		#[arg(help = "RNG seed for the bots")]
+		seed: u64,
+
+		#[arg(long, value_name = "PATH")]
+		#[arg(help = "Scratch directory for the server's config, bank and logs [default: a temporary one]")]
+		dir: Option<PathBuf>,
+	},
+
+	#[command(about = "Connect a crowd of scripted clients to a server and time its answers")]
+	Stress {
+		#[arg(short, long, value_name = "ADDR")]
+		#[arg(help = "Server to load [default: a scratch server of its own]")]
+		server: Option<String>,
+
+		#[arg(short = 'n', long, default_value_t = 24)]
+		#[arg(help = "Clients to connect")]
+		clients: usize,
+
+		#[arg(long, default_value_t = 4)]
+		#[arg(help = "Cash tables on the scratch server, besides one sit & go")]
+		tables: usize,
+
+		#[arg(long = "for", value_name = "DURATION", default_value = "1m", value_parser = moderation::parse_duration)]
+		#[arg(help = "How long to run (e.g., 30s, 5m)")]
+		duration: chrono::Duration,
+
+		#[arg(long, value_name = "MS", default_value_t = 100)]
+		#[arg(help = "Milliseconds each client thinks before acting, give or take half")]
+		think_ms: u64,
+
+		#[arg(long, value_name = "PERCENT", default_value_t = 1.0)]
+		#[arg(help = "Fail if more than this share of messages went wrong")]
+		max_failures: f64,
+
+		#[arg(long, default_value_t = 0)]
+		#[arg(help = "RNG seed for the clients")]
+		seed: u64,
+	},
+}
+
+#[derive(Args)]
+struct FeedArgs {
+	#[arg(long)]
+	#[arg(help = "Serve an observer feed for stream overlays (e.g., 127.0.0.1:8088)")]
+	feed: Option<String>,
+
+	#[arg(long)]
+	#[arg(help = "Write the observer feed to a file (.txt, .json, or .jsonl to record)")]
+	feed_file: Option<String>,
+
+	#[arg(long)]
+	#[arg(help = "Keep hole cards off the feed until showdown")]
+	feed_hide_cards: bool,
+
+	#[arg(long, value_name = "PATH")]
+	#[arg(help = "Let `poker watch` follow the game from another terminal, through a unix socket")]
+	feed_socket: Option<Option<PathBuf>>,
+}
+
+/// `poker serve`'s options; those left out come from server.toml.
+#[derive(Args)]
+struct ServeArgs {
+	#[arg(short, long)]
+	#[arg(help = "Address to bind [default: from server.toml, else 127.0.0.1:9999]")]
+	bind: Option<String>,
+
+	#[arg(long, value_name = "PATH")]
+	#[arg(help = "Server config to use instead of server.toml in the config directory")]
+	config: Option<PathBuf>,
+
+	#[arg(long, value_name = "LEVEL")]
+	#[arg(help = "Log level: error, warn, info or debug")]
+	log_level: Option<LogLevel>,
+
+	#[arg(long)]
+	#[arg(help = "Record hand histories for `poker export`")]
+	history: bool,
+
+	#[arg(long, value_name = "NAME")]
+	#[arg(help = "Advertise on the local network under this name, for `poker play --discover`")]
+	advertise: Option<String>,
+
+	#[arg(long, value_name = "ADDR", requires = "room")]
+	#[arg(help = "Open a room on a relay and take guests through it")]
+	relay: Option<String>,
+
+	#[arg(long, requires = "relay")]
+	#[arg(help = "Name of the room to open on the relay")]
+	room: Option<String>,
+}
+
+/// Where `poker play` finds its server; with none of these it starts
+/// its own.
+#[derive(Args)]
+struct ConnectArgs {
+	#[arg(short, long)]
+	#[arg(help = "Connect to server (e.g., localhost:9999)")]
+	server: Option<String>,
+
+	#[arg(long, conflicts_with = "server")]
+	#[arg(help = "Pick a server advertising on the local network")]
+	discover: bool,
+
+	#[arg(long, value_name = "ADDR", requires = "room", conflicts_with_all = ["server", "discover"])]
+	#[arg(help = "Join a game through a relay (e.g., relay.example.com:9900)")]
+	relay: Option<String>,
+
+	#[arg(long, requires = "relay")]
+	#[arg(help = "Room the host opened on the relay")]
+	room: Option<String>,
+
+	#[arg(long, conflicts_with_all = ["server", "discover", "relay"])]
+	#[arg(help = "Play at the running `poker daemon`, back at the table left behind")]
+	attach: bool,
+}
+
+enum Remote {
+	Server(String),
+	Relay { relay: String, room: String },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+	Csv,
+	Json,
+	/// Per-player, per-position stats as CSV, for poker trackers
+	Stats,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StudyFormat {
+	/// A TexasSolver console command file
+	Texassolver,
+	Json,
+}
+
+#[derive(Subcommand)]
+enum BankAction {
+	#[command(about = "Verify the audit log's checksums against current balances")]
+	Audit,
+}
+
+#[derive(Subcommand)]
+enum LeagueAction {
+	#[command(about = "Show the standings so far, from the games recorded")]
+	Standings {
+		#[arg(help = "League id from leagues.toml (defaults to every league)")]
+		league: Option<String>,
+	},
+}
+
+#[derive(Subcommand)]
+enum DaemonAction {
+	#[command(about = "Start the daemon, unless one is already running")]
+	Start {
+		#[arg(short, long, env = "POKER_USER")]
+		#[arg(help = "Player name")]
+		player: Option<String>,
+	},
+
+	#[command(about = "Stop the daemon, ending any game still going there")]
+	Stop,
+
+	#[command(about = "Show where the daemon listens and the table it holds")]
+	Status,
+
+	/// The daemon itself, as `start` runs it
+	#[command(hide = true)]
+	Run {
+		#[arg(long)]
+		player: String,
+	},
+}
+
+#[derive(Subcommand)]
+enum AdminAction {
+	#[command(about = "Keep a player or address off the server")]
+	Ban {
+		#[arg(help = "Username or IP address")]
+		target: String,
+
+		#[arg(long = "for", value_name = "DURATION", value_parser = moderation::parse_duration)]
+		#[arg(help = "How long, like 30m, 12h, 7d or 2w [default: for good]")]
+		duration: Option<chrono::Duration>,
+
+		#[arg(long, default_value = "")]
+		#[arg(help = "Shown to the player when they're turned away")]
+		reason: String,
+	},
+
+	#[command(about = "Lift a ban")]
+	Unban {
+		#[arg(help = "Username or IP address")]
+		target: String,
+	},
+
+	#[command(about = "Stop a player chatting")]
+	Mute {
+		username: String,
+
+		#[arg(long = "for", value_name = "DURATION", value_parser = moderation::parse_duration)]
+		#[arg(help = "How long, like 30m, 12h, 7d or 2w [default: for good]")]
+		duration: Option<chrono::Duration>,
+
+		#[arg(long, default_value = "")]
+		#[arg(help = "Shown to the player when they try to chat")]
+		reason: String,
+	},
+
+	#[command(about = "Let a muted player chat again")]
+	Unmute { username: String },
+
+	#[command(about = "Give a player a strike; enough of them earn a ban")]
+	Strike {
+		username: String,
+
+		#[arg(help = "What they did")]
+		reason: String,
+	},
+
+	#[command(about = "Hold the game at a table between actions, for a break")]
+	Pause {
+		#[arg(help = "Table id, as in tables.toml")]
+		table: String,
+	},
+
+	#[command(about = "Let a paused table's game go on")]
+	Resume {
+		#[arg(help = "Table id, as in tables.toml")]
+		table: String,
+	},
+
+	#[command(about = "Show bans, mutes, strikes and paused tables")]
+	List,
+
+	#[command(about = "Pull one hand out of the server's game recordings, with everyone's cards")]
+	ExportHand {
+		#[arg(help = "Hand reference from the table header, like 56789abc-3, or hand id as in hand histories")]
+		hand: String,
+
+		#[arg(short, long, value_name = "FILE")]
+		#[arg(help = "Write the entries here instead of to stdout")]
+		out: Option<PathBuf>,
+	},
 }
 
 #[derive(Subcommand)]
@@ -99,9 +681,24 @@ enum BankrollAction {
 	},
 }
 
+/// A library error as the player should read it.
+fn user_error(err: PokerError) -> io::Error {
+	io::Error::other(err.user_message())
+}
+
 fn main() -> io::Result<()> {
 	transparent_poker::defaults::ensure_config();
 	let cli = Cli::parse();
+	// With just the one profile there's no question whose hands are meant
+	let profiles = config::list_profiles();
+	let profile = cli.profile.clone().or_else(|| match profiles.as_slice() {
+		[only] => Some(only.clone()),
+		_ => None,
+	});
+	if let Some(profile) = &profile {
+		config::set_profile(profile).map_err(user_error)?;
+	}
+	apply_preferences();
 
 	match cli.command {
 		Commands::Themes => {
@@ -111,8 +708,60 @@ fn main() -> io::Result<()> {
 		Commands::Register { name, bankroll } => cmd_register(&name, bankroll),
 		Commands::Players => cmd_list_players(),
 		Commands::Bankroll { name, action } => cmd_bankroll(&name, action),
-		Commands::Serve { bind } => cmd_serve(&bind),
-		Commands::Play { player, theme, server } => cmd_play(player, theme, server),
+		Commands::Bank { action: BankAction::Audit } => cmd_bank_audit(),
+		Commands::Challenges { name } => cmd_challenges(name),
+		Commands::League { action: LeagueAction::Standings { league } } => cmd_league_standings(league),
+		Commands::Admin { config, action } => cmd_admin(config, action),
+		Commands::Topup { to } => cmd_topup(to),
+		Commands::Soak { bots, tables, duration, sample_every, seed, dir } => {
+			let config = SoakConfig {
+				bots,
+				tables,
+				duration: duration.to_std().unwrap_or_default(),
+				sample_every: Duration::from_secs(sample_every.max(1)),
+				seed,
+				..SoakConfig::default()
+			};
+			cmd_soak(&config, dir)
+		}
+		Commands::Stress { server, clients, tables, duration, think_ms, max_failures, seed } => {
+			let config = StressConfig {
+				clients,
+				duration: duration.to_std().unwrap_or_default(),
+				think: Duration::from_millis(think_ms),
+				seed,
+			};
+			cmd_stress(&config, server, tables, max_failures)
+		}
+		Commands::Daemon { action } => cmd_daemon(action),
+		Commands::Serve { serve, feed } => cmd_serve(serve, feed),
+		Commands::Relay { bind } => Relay::new().run(&bind),
+		Commands::Proxy { listen, upstream, log, replay: None, .. } => {
+			Proxy::new(&upstream, &log.unwrap_or_else(|| PathBuf::from("proxy.jsonl")))?.run(&listen)
+		}
+		Commands::Proxy { upstream, log, replay: Some(replay), connection, no_wait, .. } => {
+			cmd_replay(&replay, &upstream, log, connection, no_wait)
+		}
+		Commands::Arena { bind, hands } => cmd_arena(&bind, hands),
+		Commands::Schema { out } => cmd_schema(&out),
+		Commands::Simulate { strategies, deals, duplicate, seed } => cmd_simulate(strategies, deals, duplicate, seed),
+		Commands::Export { session, format, tag } => cmd_export(session, format, tag),
+		Commands::Stats { session, tag } => cmd_stats(session, tag),
+		Commands::Review { session, tag } => cmd_review(session, tag),
+		Commands::Shuffle { session } => cmd_shuffle(session),
+		Commands::Tag { hand, tags, remove } => cmd_tag(hand, tags, remove),
+		Commands::Puzzle { session, player, samples } => cmd_puzzle(session, player, samples),
+		Commands::Study { hand, format, out } => cmd_study(&hand, format, out),
+		Commands::Import { files } => cmd_import(&files),
+		Commands::Play { player, theme, connect, no_color, linear, hot_seat, status_file, feed } => {
+			cmd_play(player.or(cli.profile), theme, connect, no_color, linear, hot_seat, status_file, feed)
+		}
+		Commands::Tutorial { theme, no_color } => cmd_tutorial(theme, no_color),
+		Commands::Demo { auto, capture, hands, players, strategies, speed, commentary, seed, theme, no_color } => {
+			let config = DemoConfig { hands, players, seed, auto, strategies, speed, commentary };
+			cmd_demo(config, capture, theme, no_color)
+		}
+		Commands::Watch { socket, theme, no_color } => cmd_watch(socket, theme, no_color),
 	}
 }
 
@@ -127,7 +776,7 @@ fn cmd_themes() {
 }
 
 fn cmd_register(name: &str, bankroll: f32) -> io::Result<()> {
-	let mut bank = Bank::load().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+	let mut bank = Bank::load().map_err(user_error)?;
 
 	let normalized = name.to_lowercase();
 	if bank.profile_exists(&normalized) {
@@ -136,14 +785,15 @@ fn cmd_register(name: &str, bankroll: f32) -> io::Result<()> {
 	}
 
 	bank.register(&normalized, bankroll);
-	bank.save().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+	bank.save().map_err(user_error)?;
+	config::set_profile(&normalized).map_err(user_error)?;
 
-	println!("Registered '{}' with bankroll ${:.0}", normalized, bankroll);
+	println!("Registered '{}' with bankroll {}", normalized, money(bankroll));
 	Ok(())
 }
 
 fn cmd_list_players() -> io::Result<()> {
-	let bank = Bank::load().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+	let bank = Bank::load().map_err(user_error)?;
 
 	let players = bank.list_players();
 	if players.is_empty() {
@@ -155,14 +805,14 @@ fn cmd_list_players() -> io::Result<()> {
 	println!("{:<20} {:>12}", "Player", "Bankroll");
 	println!("{}", "-".repeat(34));
 	for (name, profile) in players {
-		println!("{:<20} ${:>11.0}", name, profile.bankroll);
+		println!("{:<20} {:>12}", name, money(profile.bankroll));
 	}
 
 	Ok(())
 }
 
 fn cmd_bankroll(name: &str, action: BankrollAction) -> io::Result<()> {
-	let mut bank = Bank::load().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+	let mut bank = Bank::load().map_err(user_error)?;
 
 	if !bank.profile_exists(name) {
 		eprintln!("Player '{}' not found. Use 'poker register {}' first.", name, name);
@@ -172,7 +822,7 @@ fn cmd_bankroll(name: &str, action: BankrollAction) -> io::Result<()> {
 	match action {
 		BankrollAction::Show => {
 			let balance = bank.get_bankroll(name);
-			println!("{}: ${:.0}", name, balance);
+			println!("{}: {}", name, money(balance));
 		}
 		BankrollAction::Set { amount } => {
 			let current = bank.get_bankroll(name);
@@ -180,99 +830,997 @@ fn cmd_bankroll(name: &str, action: BankrollAction) -> io::Result<()> {
 				bank.credit(name, amount - current);
 			} else {
 				bank.debit(name, current - amount)
-					.map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+					.map_err(|e| io::Error::other(e.to_string()))?;
 			}
-			bank.save().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-			println!("{}: ${:.0}", name, amount);
+			bank.save().map_err(user_error)?;
+			println!("{}: {}", name, money(amount));
 		}
 		BankrollAction::Add { amount } => {
 			bank.credit(name, amount);
-			bank.save().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+			bank.save().map_err(user_error)?;
 			let new_balance = bank.get_bankroll(name);
-			println!("{}: ${:.0} (+{:.0})", name, new_balance, amount);
+			println!("{}: {} (+{})", name, money(new_balance), money(amount));
 		}
 		BankrollAction::Sub { amount } => {
 			bank.debit(name, amount)
-				.map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
-			bank.save().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+				.map_err(|e| io::Error::other(e.to_string()))?;
+			bank.save().map_err(user_error)?;
 			let new_balance = bank.get_bankroll(name);
-			println!("{}: ${:.0} (-{:.0})", name, new_balance, amount);
+			println!("{}: {} (-{})", name, money(new_balance), money(amount));
 		}
 	}
 
 	Ok(())
 }
 
-fn cmd_serve(bind: &str) -> io::Result<()> {
+fn cmd_admin(config: Option<PathBuf>, action: AdminAction) -> io::Result<()> {
+	let server_config = match config {
+		Some(path) => ServerConfig::load_from(&path),
+		None => ServerConfig::load(),
+	}
+	.map_err(user_error)?;
+	let path = server_config.moderation_path().unwrap_or_else(Moderation::default_path);
+	let mut moderation = Moderation::open(&path).map_err(user_error)?;
+	let for_how_long = |duration: Option<chrono::Duration>| match duration {
+		Some(d) => format!("until {}", (chrono::Local::now() + d).format("%Y-%m-%d %H:%M")),
+		None => "for good".to_string(),
+	};
+
+	match action {
+		AdminAction::Ban { target, duration, reason } => {
+			let target = Target::parse(&target);
+			moderation.update(|file| file.ban(target.clone(), duration, &reason)).map_err(user_error)?;
+			println!("Banned {} {}", target, for_how_long(duration));
+		}
+		AdminAction::Unban { target } => {
+			let target = Target::parse(&target);
+			if moderation.update(|file| file.unban(&target)).map_err(user_error)? {
+				println!("Lifted the ban on {}", target);
+			} else {
+				println!("{} isn't banned", target);
+			}
+		}
+		AdminAction::Mute { username, duration, reason } => {
+			moderation.update(|file| file.mute(&username, duration, &reason)).map_err(user_error)?;
+			println!("Muted {} {}", username, for_how_long(duration));
+		}
+		AdminAction::Unmute { username } => {
+			if moderation.update(|file| file.unmute(&username)).map_err(user_error)? {
+				println!("Unmuted {}", username);
+			} else {
+				println!("{} isn't muted", username);
+			}
+		}
+		AdminAction::Strike { username, reason } => {
+			let count = moderation.update(|file| file.strike(&username, &reason)).map_err(user_error)?;
+			if count >= STRIKE_LIMIT {
+				println!("Strike {} for {}: banned for {} days", count, username, moderation::STRIKE_BAN.num_days());
+			} else {
+				println!("Strike {} of {} for {}", count, STRIKE_LIMIT, username);
+			}
+		}
+		AdminAction::Pause { table } => {
+			if moderation.update(|file| file.pause(&table)).map_err(user_error)? {
+				println!("Pausing {}; the server holds its game within a few seconds", table);
+			} else {
+				println!("{} is already paused", table);
+			}
+		}
+		AdminAction::Resume { table } => {
+			if moderation.update(|file| file.resume(&table)).map_err(user_error)? {
+				println!("Resuming {}", table);
+			} else {
+				println!("{} isn't paused", table);
+			}
+		}
+		AdminAction::List => {
+			let now = chrono::Local::now();
+			let file = moderation.current();
+			println!("{}", path.display());
+			for (title, sanctions) in [("Bans", &file.bans), ("Mutes", &file.mutes)] {
+				let active = sanctions.iter().filter(|s| s.is_active(now)).collect::<Vec<_>>();
+				println!("{}: {}", title, active.len());
+				for sanction in active {
+					println!("  {:<24} {}", sanction.target.to_string(), sanction.describe());
+				}
+			}
+			println!("Strikes: {}", file.strikes.len());
+			for strike in &file.strikes {
+				println!("  {:<24} {}  {}", strike.username, &strike.at[..10.min(strike.at.len())], strike.reason);
+			}
+			if !file.paused_tables.is_empty() {
+				println!("Paused tables: {}", file.paused_tables.join(", "));
+			}
+		}
+		AdminAction::ExportHand { hand, out } => {
+			let recordings = server_config.recordings_dir().map(Recordings::new).unwrap_or_default();
+			return cmd_export_hand(&recordings, &hand, out);
+		}
+	}
+	Ok(())
+}
+
+fn cmd_export_hand(recordings: &Recordings, hand: &str, out: Option<PathBuf>) -> io::Result<()> {
+	let record = match (parse_hand_ref(hand), hand.trim().parse()) {
+		(Some((session, hand_num)), _) => recordings.find_hand_ref(session, hand_num),
+		(None, Ok(id)) => recordings.find_hand(HandId(id)),
+		(None, Err(_)) => Err(PokerError::parse("hand reference", format!("'{}' isn't like 56789abc-3 or a hand id", hand))),
+	}
+	.map_err(user_error)?;
+	let mut lines = String::new();
+	for entry in &record.entries {
+		lines.push_str(&serde_json::to_string(entry).map_err(io::Error::other)?);
+		lines.push('\n');
+	}
+	match &out {
+		Some(path) => std::fs::write(path, lines)?,
+		None => print!("{}", lines),
+	}
+
+	eprintln!("Hand {}: {} entries from {}", hand.trim(), record.entries.len(), record.path.display());
+	if record.problems.is_empty() {
+		eprintln!("The game's recording is intact.");
+		return Ok(());
+	}
+	for problem in &record.problems {
+		eprintln!("  {}", problem);
+	}
+	eprintln!("The game's recording has been altered; don't rely on it");
+	std::process::exit(1);
+}
+
+fn cmd_bank_audit() -> io::Result<()> {
+	let bank = Bank::load().map_err(user_error)?;
+	let report = bank.verify_audit().map_err(user_error)?;
+
+	println!("{} entries for {} players", report.entries, report.players);
+	if !report.untracked.is_empty() {
+		println!("Not in the log (from before it was kept): {}", report.untracked.join(", "));
+	}
+	if report.is_clean() {
+		println!("Checksums and balances all agree.");
+		return Ok(());
+	}
+	for problem in &report.problems {
+		println!("  {}", problem);
+	}
+	eprintln!("{} problems in the bank's books", report.problems.len());
+	std::process::exit(1);
+}
+
+fn cmd_challenges(name: Option<String>) -> io::Result<()> {
+	let name = name.unwrap_or_else(default_username);
+	let bank = Bank::load().map_err(user_error)?;
+	let progress = bank.get(&name).challenges.unwrap_or_default();
+	let today = chrono::Local::now().date_naive();
+
+	println!("Daily challenges for {}, {}:", name, today);
+	let goals = challenges::daily(today);
+	for (goal, count) in goals.iter().zip(progress.counts_on(today)) {
+		let mark = if count >= goal.target() { "x" } else { " " };
+		let count = format!("{}/{}", count.min(goal.target()), goal.target());
+		println!("  [{}] {:<40} {:>6} {:>8}", mark, goal.describe(), count, money(goal.reward()));
+	}
+	let streak = progress.streak_on(today);
+	let cleared = progress.cleared_on.as_deref() == Some(today.to_string().as_str());
+	let next = if cleared { streak } else { streak + 1 };
+	let bonus = money(challenges::STREAK_BONUS * next.min(challenges::STREAK_BONUS_CAP) as f32);
+	let days = |n: u32| if n == 1 { "1 day".to_string() } else { format!("{} days", n) };
+	match (streak, cleared) {
+		(_, true) => println!("All done for today, {} running. Come back tomorrow.", days(streak)),
+		(0, false) => println!("Meet every goal today for a {} bonus.", bonus),
+		(_, false) => println!("Streak: {}. Meet every goal today to make it {}, for a {} bonus.", days(streak), next, bonus),
+	}
+	Ok(())
+}
+
+fn cmd_league_standings(league: Option<String>) -> io::Result<()> {
+	let leagues = Leagues::load().map_err(user_error)?;
+	if leagues.all().is_empty() {
+		println!("No leagues yet; define one in leagues.toml in the config directory.");
+		return Ok(());
+	}
+	let chosen = leagues.all().iter().filter(|l| league.as_ref().is_none_or(|id| &l.id == id)).collect::<Vec<_>>();
+	if chosen.is_empty() {
+		let ids = leagues.all().iter().map(|l| l.id.as_str()).collect::<Vec<_>>();
+		return Err(io::Error::other(format!("No league '{}'; leagues: {}", league.unwrap_or_default(), ids.join(", "))));
+	}
+
+	let today = chrono::Local::now().date_naive();
+	for (i, config) in chosen.iter().enumerate() {
+		if i > 0 {
+			println!();
+		}
+		let results = leagues.results(&config.id).map_err(user_error)?;
+		println!("{} ({}): {} of {} games played", config.name, config.id, results.len(), config.schedule.len());
+		println!("{:>4}  {:<20} {:>7} {:>7} {:>5}", "", "Player", "Points", "Played", "Won");
+		for (place, standing) in config.standings(&results).iter().enumerate() {
+			println!(
+				"{:>4}  {:<20} {:>7} {:>7} {:>5}",
+				place + 1, standing.member, standing.points, standing.played, standing.wins,
+			);
+		}
+		match config.next_game(&results, today) {
+			Some(game) => println!("Next game: {} on {}", game.table, game.date),
+			None => println!("The season is over."),
+		}
+	}
+	Ok(())
+}
+
+fn cmd_topup(to: Option<f32>) -> io::Result<()> {
+	let roster = load_players_auto().map_err(user_error)?;
+	let mut bank = Bank::load().map_err(user_error)?;
+	let to = to.unwrap_or(bank.default_bankroll());
+
+	let mut topped = 0;
+	for player in &roster {
+		let added = bank.top_up(&player.id, to);
+		if added > 0.0 {
+			println!("{:<20} {:>12} (+{})", player.id, money(to), money(added));
+			topped += 1;
+		}
+	}
+	bank.save().map_err(user_error)?;
+	println!("Topped up {} of {} roster players to {}", topped, roster.len(), money(to));
+	Ok(())
+}
+
+fn start_feed(args: FeedArgs) -> io::Result<Option<ObserverFeed>> {
+	if args.feed.is_none() && args.feed_file.is_none() && args.feed_socket.is_none() {
+		return Ok(None);
+	}
+	let socket = args.feed_socket.map(|path| path.unwrap_or_else(feed::default_socket_path));
+	let feed = ObserverFeed::start(FeedConfig {
+		addr: args.feed,
+		file: args.feed_file.map(Into::into),
+		hole_cards: !args.feed_hide_cards,
+		socket: socket.clone(),
+	})?;
+	if let Some(addr) = feed.local_addr() {
+		println!("Observer feed on http://{}/ (feed.txt, feed.json, events)", addr);
+	}
+	match socket {
+		Some(path) if path == feed::default_socket_path() => println!("Watch from another terminal with: poker watch"),
+		Some(path) => println!("Watch from another terminal with: poker watch --socket {}", path.display()),
+		None => {}
+	}
+	Ok(Some(feed))
+}
+
+fn cmd_serve(args: ServeArgs, feed: FeedArgs) -> io::Result<()> {
+	let mut config = match &args.config {
+		Some(path) => ServerConfig::load_from(path),
+		None => ServerConfig::load(),
+	}
+	.map_err(user_error)?;
+	if let Some(bind) = args.bind {
+		config.bind = bind;
+	}
+	if let Some(level) = args.log_level {
+		config.log_level = level;
+	}
+	let bind = config.bind.as_str();
+
 	println!("Starting poker server on {}...", bind);
-	let server = GameServer::new();
+	let mut server = GameServer::from_config(&config);
+	if args.history {
+		let log = config.history_dir().map(HistoryLog::new).unwrap_or_default();
+		println!("Recording hand histories in {}", log.dir().display());
+		server = server.with_history(log);
+	}
+	if let Some(feed) = start_feed(feed)? {
+		server = server.with_feed(feed);
+	}
+	if let Some(name) = args.advertise {
+		if bind.starts_with("127.") || bind.starts_with("localhost") {
+			println!("Note: bound to {}, so only this machine can connect; try --bind 0.0.0.0:9999", bind);
+		}
+		server = server.with_advertisement(&name);
+	}
+	if let Some((relay_addr, room)) = args.relay.zip(args.room) {
+		let local = local_addr(bind);
+		std::thread::spawn(move || {
+			if let Err(e) = relay::host(&relay_addr, &room, &local) {
+				eprintln!("Lost the relay: {}", e);
+			}
+		});
+	}
 	server.run(bind)
 }
 
-fn cmd_play(player: Option<String>, theme: Option<String>, server: Option<String>) -> io::Result<()> {
+/// Where this machine reaches a server bound to `bind`: a wildcard
+/// address is swapped for loopback.
+fn local_addr(bind: &str) -> String {
+	match bind.parse::<std::net::SocketAddr>() {
+		Ok(addr) if addr.ip().is_unspecified() => format!("127.0.0.1:{}", addr.port()),
+		_ => bind.to_string(),
+	}
+}
+
+fn cmd_arena(bind: &str, hands: u32) -> io::Result<()> {
+	println!("Starting bot arena on {}...", bind);
+	let arena = BotArena::from_config().with_max_hands(Some(hands));
+	arena.run(bind)
+}
+
+/// Starts `poker serve` on a scratch config as a process of its own, so
+/// its memory and threads can be watched apart from the bots', and soaks
+/// it.
+fn cmd_soak(config: &SoakConfig, dir: Option<PathBuf>) -> io::Result<()> {
+	let dir = dir.unwrap_or_else(|| std::env::temp_dir().join(format!("poker-soak-{}", std::process::id())));
+	let (mut server, addr) = start_scratch_server(&dir, config)?;
+	println!("Soaking {} with {} bots for {}s; scratch files in {}", addr, config.bots, config.duration.as_secs(), dir.display());
+
+	let report = soak::run(config, &addr, Some(server.id()), |sample| println!("{}", sample.line()));
+	let _ = server.kill();
+	let _ = server.wait();
+	println!();
+	for line in report.lines() {
+		println!("{}", line);
+	}
+	if report.problems().is_empty() {
+		Ok(())
+	} else {
+		Err(io::Error::other(format!("The soak found problems; the server's log is {}", dir.join("server.log").display())))
+	}
+}
+
+fn cmd_stress(config: &StressConfig, server: Option<String>, tables: usize, max_failures: f64) -> io::Result<()> {
+	let mut scratch = None;
+	let addr = match server {
+		Some(addr) => addr,
+		None => {
+			let dir = std::env::temp_dir().join(format!("poker-stress-{}", std::process::id()));
+			let soak = SoakConfig { bots: config.clients, tables, seed: config.seed, ..SoakConfig::default() };
+			let (server, addr) = start_scratch_server(&dir, &soak)?;
+			println!("Scratch server at {}; its files are in {}", addr, dir.display());
+			scratch = Some(server);
+			addr
+		}
+	};
+	println!("Stressing {} with {} clients for {}s", addr, config.clients, config.duration.as_secs());
+
+	let report = stress::run(config, &addr, |totals| {
+		print!("\r{:>4}s  {} sent  {} hands  {} failures", totals.elapsed.as_secs(), totals.sent, totals.hands, totals.failures());
+		let _ = io::Write::flush(&mut io::stdout());
+	});
+	if let Some(mut server) = scratch {
+		let _ = server.kill();
+		let _ = server.wait();
+	}
+	println!("\n");
+	for line in report.lines() {
+		println!("{}", line);
+	}
+	if report.failure_rate() > max_failures {
+		return Err(io::Error::other(format!("{:.2}% of messages went wrong, over {}%", report.failure_rate(), max_failures)));
+	}
+	Ok(())
+}
+
+fn cmd_replay(path: &std::path::Path, upstream: &str, log: Option<PathBuf>, connection: Option<u64>, no_wait: bool) -> io::Result<()> {
+	let entries = proxy::load(path)?;
+	let Some((connection, sent)) = proxy::client_session(&entries, connection) else {
+		return Err(io::Error::other(format!("No client messages to replay in {}", path.display())));
+	};
+	let out: Box<dyn io::Write + Send> = match &log {
+		Some(log) => Box::new(std::fs::File::create(log)?),
+		None => Box::new(io::stdout()),
+	};
+	eprintln!("Replaying connection {} ({} messages) against {}", connection, sent.len(), upstream);
+	let config = ReplayConfig { no_wait, ..ReplayConfig::default() };
+	let received = proxy::replay(&sent, upstream, &config, out)?;
+	eprintln!("Sent {}, received {}", sent.len(), received);
+	Ok(())
+}
+
+/// Starts `poker serve` on a free local port with the soak's tables and
+/// bots, its config and log in `dir`, and waits for it to take
+/// connections.
+fn start_scratch_server(dir: &std::path::Path, config: &SoakConfig) -> io::Result<(std::process::Child, String)> {
+	let addr = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?.to_string();
+	let server_config = soak::prepare(dir, config, &addr).map_err(user_error)?;
+	let log = std::fs::File::create(dir.join("server.log"))?;
+	let mut server = std::process::Command::new(std::env::current_exe()?)
+		.arg("serve")
+		.arg("--config")
+		.arg(&server_config)
+		.current_dir(dir)
+		.stdout(log.try_clone()?)
+		.stderr(log)
+		.spawn()?;
+
+	let up = (0..50).any(|_| {
+		std::thread::sleep(Duration::from_millis(100));
+		std::net::TcpStream::connect(&addr).is_ok()
+	});
+	if !up {
+		let _ = server.kill();
+		return Err(io::Error::other(format!("The server didn't start; see {}", dir.join("server.log").display())));
+	}
+	Ok((server, addr))
+}
+
+fn cmd_simulate(strategies: Vec<String>, deals: u32, duplicate: bool, seed: u64) -> io::Result<()> {
+	let store = load_strategies_auto().map_err(user_error)?;
+	let config = SimulationConfig { strategies, deals, duplicate, seed, ..SimulationConfig::default() };
+	let report = simulate(&config, &store).map_err(|e| {
+		let mut known = store.list();
+		known.sort();
+		io::Error::other(format!("{} (strategies: {})", e.user_message(), known.join(", ")))
+	})?;
+	for line in report.lines() {
+		println!("{}", line);
+	}
+	Ok(())
+}
+
+fn cmd_schema(out: &str) -> io::Result<()> {
+	for path in transparent_poker::schema::write_all(std::path::Path::new(out))? {
+		println!("Wrote {}", path);
+	}
+	Ok(())
+}
+
+fn cmd_export(session: Option<String>, format: ExportFormat, tag: Option<String>) -> io::Result<()> {
+	let log = HistoryLog::default();
+	let Some((_, hands)) = load_hands(&log, session, tag, "poker export --session <id> [--format csv|json|stats] [--tag <tag>]")? else {
+		return Ok(());
+	};
+
+	match format {
+		ExportFormat::Csv => print!("{}", history::to_csv(&hands)),
+		ExportFormat::Json => print!("{}", history::to_json(&hands)),
+		ExportFormat::Stats => print!("{}", history::tracker::to_stats_csv(&hands)),
+	}
+	Ok(())
+}
+
+fn cmd_stats(session: Option<String>, tag: Option<String>) -> io::Result<()> {
+	let log = HistoryLog::default();
+	let Some((title, hands)) = load_hands(&log, session, tag, "poker stats --session <id> [--tag <tag>]")? else {
+		return Ok(());
+	};
+
+	let report = LuckReport::from_hands(&hands);
+	println!("{}: {} hands, {} all-ins with cards to come\n", title, hands.len(), report.all_ins());
+	for line in report.lines() {
+		println!("{}", line);
+	}
+	let pace = PaceReport::from_hands(&hands);
+	if !pace.is_empty() {
+		println!();
+		for line in pace.lines() {
+			println!("{}", line);
+		}
+	}
+	Ok(())
+}
+
+fn cmd_review(session: Option<String>, tag: Option<String>) -> io::Result<()> {
+	let log = HistoryLog::default();
+	// Hands from every session are told apart by their references
+	let across_sessions = session.is_none();
+	let Some((title, hands)) = load_hands(&log, session, tag, "poker review --session <id> [--tag <tag>]")? else {
+		return Ok(());
+	};
+
+	println!("{}: {} hands\n", title, hands.len());
+	for hand in &hands {
+		if across_sessions {
+			println!("{} at {}", hand.reference(), hand.table);
+		}
+		for line in hand.review() {
+			println!("{}", line);
+		}
+		println!();
+	}
+	Ok(())
+}
+
+fn cmd_shuffle(session: Option<String>) -> io::Result<()> {
+	let log = HistoryLog::default();
+	let Some(session) = session else {
+		list_sessions(&log, "poker shuffle --session <id>");
+		return Ok(());
+	};
+	let report = log.load_shuffle(&session.to_lowercase()).map_err(user_error)?;
+	for line in report.lines() {
+		println!("{}", line);
+	}
+	Ok(())
+}
+
+/// The hands a history command asked for, with a title for them: a
+/// session's, maybe only those with a tag, or every session's hands with
+/// the tag. With neither, lists the sessions and returns `None`.
+fn load_hands(
+	log: &HistoryLog,
+	session: Option<String>,
+	tag: Option<String>,
+	usage: &str,
+) -> io::Result<Option<(String, Vec<history::HandHistory>)>> {
+	let (title, mut hands) = match (session, &tag) {
+		(Some(session), _) => (format!("Session {}", session), log.load(&session).map_err(user_error)?),
+		(None, Some(tag)) => {
+			let hands = log.tagged(tag).map_err(user_error)?;
+			return Ok(Some((format!("Tagged '{}'", history::tags::normalize(tag)), hands)));
+		}
+		(None, None) => {
+			list_sessions(log, usage);
+			return Ok(None);
+		}
+	};
+	if let Some(tag) = tag {
+		hands.retain(|hand| hand.has_tag(&tag));
+		return Ok(Some((format!("{}, tagged '{}'", title, history::tags::normalize(&tag)), hands)));
+	}
+	Ok(Some((title, hands)))
+}
+
+fn cmd_tag(hand: Option<String>, tags: Vec<String>, remove: bool) -> io::Result<()> {
+	let log = HistoryLog::default();
+	let Some(hand) = hand else {
+		let all = log.tags().map_err(user_error)?;
+		if all.is_empty() {
+			println!("No tagged hands in {}.", log.tags_path().display());
+		}
+		for (hand_ref, tags) in all {
+			println!("{:<14} {}", hand_ref, tags.join(", "));
+		}
+		println!("\nSee them with: poker review --tag <tag>");
+		return Ok(());
+	};
+
+	let tags = if tags.is_empty() { vec![history::tags::REVIEW_LATER.to_string()] } else { tags };
+	for tag in tags {
+		let tag = history::tags::normalize(&tag);
+		if remove {
+			if log.untag(&hand, &tag).map_err(user_error)? {
+				println!("Took '{}' off {}", tag, hand);
+			} else {
+				println!("{} isn't tagged '{}'", hand, tag);
+			}
+		} else if log.tag(&hand, &tag).map_err(user_error)? {
+			println!("Tagged {} '{}'", hand, tag);
+		} else {
+			println!("{} is already tagged '{}'", hand, tag);
+		}
+	}
+	Ok(())
+}
+
+fn cmd_puzzle(session: Option<String>, player: Option<String>, samples: bool) -> io::Result<()> {
+	let today = chrono::Local::now().date_naive();
+	let mut found = None;
+	if !samples {
+		let log = HistoryLog::default();
+		let sessions = match session {
+			Some(session) => vec![session],
+			None => log.sessions().into_iter().map(|s| s.id).collect(),
+		};
+		let mut hands = Vec::new();
+		for session in sessions {
+			hands.extend(log.load(&session).map_err(user_error)?);
+		}
+		found = puzzle::of_the_day(&hands, player.as_deref(), today);
+		if found.is_none() {
+			println!("No bets faced after the flop in your hands yet; here's one from the samples.\n");
+		}
+	}
+	let Some(puzzle) = found.or_else(|| puzzle::of_the_day(&puzzle::samples(), Some(puzzle::SAMPLE_HERO), today)) else {
+		return Err(io::Error::other("No puzzles in the sample hands"));
+	};
+
+	for line in puzzle.setup() {
+		println!("{}", line);
+	}
+	print!("What do you do? {}: ", puzzle.prompt());
+	io::Write::flush(&mut io::stdout())?;
+	let mut answer = String::new();
+	io::stdin().read_line(&mut answer)?;
+	println!();
+	let choice = puzzle::Choice::parse(&answer);
+	for line in puzzle.reveal() {
+		println!("{}", line);
+	}
+	let best = puzzle.evaluate().best();
+	match choice {
+		Some(choice) if choice == best => println!("\nYou picked the best option by the numbers."),
+		Some(_) => println!("\nBy the numbers there was a better option."),
+		None => {}
+	}
+	Ok(())
+}
+
+fn cmd_study(hand: &str, format: StudyFormat, out: Option<PathBuf>) -> io::Result<()> {
+	let hand = HistoryLog::default().hand(hand).map_err(user_error)?;
+	let spot = FlopSpot::from_hand(&hand).map_err(user_error)?;
+	let text = match format {
+		StudyFormat::Texassolver => spot.texassolver(),
+		StudyFormat::Json => spot.to_json(),
+	};
+	match &out {
+		Some(path) => std::fs::write(path, text)?,
+		None => print!("{}", text),
+	}
+
+	eprintln!(
+		"Hand {}: {} out of position, {} in position, pot {}, {} behind",
+		spot.hand_ref,
+		spot.oop.name,
+		spot.ip.name,
+		big_blinds(spot.pot, spot.big_blind),
+		big_blinds(spot.effective_stack, spot.big_blind)
+	);
+	for player in [&spot.oop, &spot.ip] {
+		if let Some(cards) = &player.hole_cards {
+			eprintln!("  {} held {}", player.name, cards);
+		}
+	}
+	eprintln!("Put each player's range in place of {} before solving.", history::solver::RANGE_PLACEHOLDER);
+	Ok(())
+}
+
+fn list_sessions(log: &HistoryLog, usage: &str) {
+	let sessions = log.sessions();
+	if sessions.is_empty() {
+		println!("No recorded sessions in {}.", log.dir().display());
+		return;
+	}
+	println!("{:<10} {:<20} {:<26} {:>6}", "Session", "Table", "Started", "Hands");
+	println!("{}", "-".repeat(65));
+	for s in sessions {
+		println!("{:<10} {:<20} {:<26} {:>6}", s.id, s.table, s.started_at, s.hands);
+	}
+	println!("\nUsage: {}", usage);
+}
+
+fn cmd_import(files: &[String]) -> io::Result<()> {
+	let log = HistoryLog::default();
+	for file in files {
+		let text = std::fs::read_to_string(file)?;
+		let import = history::import::import(&text).map_err(|e| io::Error::other(format!("{}: {}", file, e.user_message())))?;
+		let mut sessions = import.hands.iter().map(|h| h.session()).collect::<Vec<_>>();
+		sessions.sort();
+		sessions.dedup();
+		let read = import.hands.len();
+		let added = log.add(import.hands).map_err(user_error)?;
+		println!("{}: {} new of {} hands", file, added, read);
+		if !sessions.is_empty() {
+			println!("  sessions: {}", sessions.join(", "));
+		}
+		for skipped in import.skipped {
+			println!("  skipped #{}: {}", skipped.hand, skipped.reason);
+		}
+	}
+	println!("\nSee them with: poker export");
+	Ok(())
+}
+
+fn palette(no_color: bool) -> Palette {
+	if no_color {
+		Palette::Mono
+	} else {
+		Palette::detect()
+	}
+}
+
+fn cmd_tutorial(theme: Option<String>, no_color: bool) -> io::Result<()> {
 	let theme_name = theme
-		.clone()
-		.or_else(|| std::env::var("POKER_THEME").ok())
+		.or_else(|| load_preferences().theme)
 		.unwrap_or_else(|| "classic".to_string());
-	let theme = Theme::load_named(&theme_name).unwrap_or_default();
+	let theme = Theme::load_named(&theme_name).unwrap_or_default().with_palette(palette(no_color));
 
-	let username = player.unwrap_or_else(|| {
-		std::env::var("USER")
-			.or_else(|_| std::env::var("USERNAME"))
-			.unwrap_or_else(|_| "Player".to_string())
-	});
+	let mut terminal = TuiTerminal::enter("transparent-poker tutorial", false)?;
+	tutorial::run_tutorial(&mut terminal, theme, theme_name)
+}
+
+fn cmd_demo(config: DemoConfig, capture: Option<PathBuf>, theme: Option<String>, no_color: bool) -> io::Result<()> {
+	let theme_name = theme
+		.or_else(|| load_preferences().theme)
+		.unwrap_or_else(|| "classic".to_string());
+	let theme = Theme::load_named(&theme_name).unwrap_or_default().with_palette(palette(no_color));
+
+	if let Some(path) = capture {
+		let text = demo::capture(&config, theme, theme_name, 120, 40).map_err(user_error)?;
+		std::fs::write(&path, text)?;
+		println!("Played {} hands; the screens are in {}", config.hands, path.display());
+		return Ok(());
+	}
+
+	let mut terminal = TuiTerminal::enter("transparent-poker demo", false)?;
+	demo::run_demo(&mut terminal, &config, theme, theme_name)
+}
+
+fn cmd_watch(socket: Option<PathBuf>, theme: Option<String>, no_color: bool) -> io::Result<()> {
+	let path = socket.unwrap_or_else(feed::default_socket_path);
+	let events = feed::attach(&path).map_err(|e| {
+		io::Error::new(e.kind(), format!("No game to watch at {} ({}); start one with --feed-socket", path.display(), e))
+	})?;
+	let theme_name = theme
+		.or_else(|| load_preferences().theme)
+		.unwrap_or_else(|| "classic".to_string());
+	let theme = Theme::load_named(&theme_name).unwrap_or_default().with_palette(palette(no_color));
+
+	let mut terminal = TuiTerminal::enter("transparent-poker viewer", false)?;
+	game_loop::run_viewer(&mut terminal, events, theme, theme_name)
+}
+
+/// Runs the "Discover servers" screen on its own, before anything connects.
+fn discover_server(theme: &Theme, linear: bool) -> io::Result<Option<String>> {
+	let mut terminal = TuiTerminal::enter(WINDOW_TITLE, linear)?;
+	menu::pick_server(&mut terminal, theme, linear)
+}
+
+/// Takes up the language and money format of the profile in use.
+fn apply_preferences() {
+	let prefs = load_preferences();
+	transparent_poker::i18n::init(prefs.locale.as_deref());
+	transparent_poker::money::set_format(prefs.money);
+}
+
+/// Who's playing when no player is named: picked from the profiles played
+/// before, if there are any. `None` if the picker was quit.
+fn pick_profile(theme: &Theme, linear: bool) -> io::Result<Option<String>> {
+	let profiles = config::list_profiles();
+	if profiles.is_empty() {
+		return Ok(Some(default_username()));
+	}
+	let mut terminal = TuiTerminal::enter(WINDOW_TITLE, linear)?;
+	menu::pick_profile(&mut terminal, theme, linear, &profiles)
+}
+
+fn default_username() -> String {
+	std::env::var("USER")
+		.or_else(|_| std::env::var("USERNAME"))
+		.unwrap_or_else(|_| "Player".to_string())
+}
+
+fn cmd_daemon(action: DaemonAction) -> io::Result<()> {
+	let path = DaemonInfo::default_path();
+	let running = DaemonInfo::load_from(&path).map_err(user_error)?;
+	match action {
+		DaemonAction::Start { player } => {
+			if let Some(info) = &running {
+				if info.is_running() {
+					return Err(io::Error::other(format!("A daemon is already running on {}", info.addr)));
+				}
+				// One that was killed left its file behind
+				std::fs::remove_file(&path)?;
+			}
+			let username = player.unwrap_or_else(default_username);
+			let log_path = path.with_extension("log");
+			let log = std::fs::File::create(&log_path)?;
+			let mut command = std::process::Command::new(std::env::current_exe()?);
+			command
+				.args(["daemon", "run", "--player", &username])
+				.stdin(std::process::Stdio::null())
+				.stdout(log.try_clone()?)
+				.stderr(log);
+			// Its own process group, so closing this terminal doesn't take it along
+			#[cfg(unix)]
+			{
+				use std::os::unix::process::CommandExt;
+				command.process_group(0);
+			}
+			#[cfg(windows)]
+			{
+				use std::os::windows::process::CommandExt;
+				const DETACHED_PROCESS: u32 = 0x0000_0008;
+				const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+				command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+			}
+			command.spawn()?;
 
-	let (addr, _embedded) = match server {
-		Some(addr) => (addr, None),
+			let started = (0..50).find_map(|_| {
+				std::thread::sleep(Duration::from_millis(100));
+				DaemonInfo::load_from(&path).ok().flatten().filter(DaemonInfo::is_running)
+			});
+			let Some(info) = started else {
+				return Err(io::Error::other(format!("The daemon didn't start; see {}", log_path.display())));
+			};
+			println!("Poker daemon running on {} (pid {})", info.addr, info.pid);
+			println!("`poker play --attach` to play there; press d at the table to detach.");
+			Ok(())
+		}
+		DaemonAction::Stop => {
+			let Some(info) = running else {
+				println!("No daemon is running");
+				return Ok(());
+			};
+			std::fs::remove_file(&path)?;
+			let stopped = (0..20).any(|_| {
+				std::thread::sleep(Duration::from_millis(250));
+				!info.is_running()
+			});
+			if stopped {
+				println!("Stopped the daemon on {}; any game still going there has ended", info.addr);
+				Ok(())
+			} else {
+				Err(io::Error::other(format!("The daemon (pid {}) is still running", info.pid)))
+			}
+		}
+		DaemonAction::Status => {
+			match running {
+				Some(info) if info.is_running() => {
+					println!("Poker daemon running on {} (pid {})", info.addr, info.pid);
+					println!("  Started:  {} by {}", info.started_at, info.username);
+					println!("  Table:    {}", info.table.as_deref().unwrap_or("none"));
+				}
+				Some(info) => {
+					std::fs::remove_file(&path)?;
+					println!("The daemon on {} isn't answering; cleared its details", info.addr);
+				}
+				None => println!("No daemon is running"),
+			}
+			Ok(())
+		}
+		DaemonAction::Run { player } => {
+			config::set_profile(&player).map_err(user_error)?;
+			let game_server = GameServer::new().with_history(HistoryLog::default()).with_host(&player);
+			daemon::run(game_server, &path, &player)
+		}
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_play(
+	player: Option<String>,
+	theme: Option<String>,
+	connect: ConnectArgs,
+	no_color: bool,
+	linear: bool,
+	hot_seat: Vec<String>,
+	status_file: Option<PathBuf>,
+	feed: FeedArgs,
+) -> io::Result<()> {
+	let theme_choice = theme;
+	let load_theme = || {
+		let name = theme_choice
+			.clone()
+			.or_else(|| std::env::var("POKER_THEME").ok())
+			.or_else(|| load_preferences().theme)
+			.unwrap_or_else(|| "classic".to_string());
+		let theme = Theme::load_named(&name).unwrap_or_default().with_palette(palette(no_color));
+		(theme, name)
+	};
+
+	let username = match player {
+		Some(player) => player,
+		None => match pick_profile(&load_theme().0, linear)? {
+			Some(picked) => picked,
+			None => return Ok(()),
+		},
+	};
+	// Whoever plays brings their own settings and keeps their own hands
+	config::set_profile(&username).map_err(user_error)?;
+	apply_preferences();
+	let (mut theme, mut theme_name) = load_theme();
+	if !hot_seat.is_empty() {
+		if linear {
+			return Err(io::Error::other("Hot seat hides cards on the drawn table; leave out --linear"));
+		}
+		let mut names = vec![username.to_lowercase()];
+		for name in &hot_seat {
+			if name.is_empty() || names.contains(&name.to_lowercase()) {
+				return Err(io::Error::other(format!("Hot seat players need names of their own, not '{}'", name)));
+			}
+			names.push(name.to_lowercase());
+		}
+	}
+
+	let daemon_path = DaemonInfo::default_path();
+	let daemon = if connect.attach {
+		match DaemonInfo::load_from(&daemon_path).map_err(user_error)? {
+			Some(info) if info.is_running() => Some(info),
+			_ => return Err(io::Error::other("No daemon is running; start one with `poker daemon start`")),
+		}
+	} else {
+		None
+	};
+
+	let remote = if let Some(info) = &daemon {
+		Some(Remote::Server(info.addr.clone()))
+	} else if connect.discover {
+		match discover_server(&theme, linear)? {
+			Some(addr) => Some(Remote::Server(addr)),
+			None => return Ok(()),
+		}
+	} else if let (Some(relay), Some(room)) = (connect.relay, connect.room) {
+		Some(Remote::Relay { relay, room })
+	} else {
+		connect.server.map(Remote::Server)
+	};
+
+	let (remote, embedded) = match remote {
+		Some(remote) => {
+			if feed.feed.is_some() || feed.feed_file.is_some() || feed.feed_socket.is_some() {
+				return Err(io::Error::other("The observer feed runs on the server; pass --feed to `poker serve` instead"));
+			}
+			(remote, None)
+		}
 		None => {
-			let embedded = EmbeddedServer::start()?;
-			let addr = embedded.addr();
-			(addr, Some(embedded))
+			let mut game_server = GameServer::new().with_history(HistoryLog::default()).with_host(&username);
+			if let Some(feed) = start_feed(feed)? {
+				game_server = game_server.with_feed(feed);
+			}
+			let embedded = EmbeddedServer::start_with(game_server)?;
+			(Remote::Server(embedded.addr()), Some(embedded))
 		}
 	};
 
 	std::thread::sleep(Duration::from_millis(100));
 
-	let mut client = GameClient::connect(&addr)?;
+	let mut client = match remote {
+		Remote::Server(addr) => GameClient::connect(&addr)?,
+		Remote::Relay { relay, room } => GameClient::connect_relayed(&relay, &room)?,
+	};
 	client.login(&username)?;
+	// Hot seat is only offered without a server, so there's always one here
+	let mut guests = Vec::new();
+	if let Some(embedded) = &embedded {
+		for name in &hot_seat {
+			let mut guest = GameClient::connect(&embedded.addr())?;
+			guest.login(name)?;
+			guests.push(guest);
+		}
+	}
 	std::thread::sleep(Duration::from_millis(100));
 
-	enable_raw_mode()?;
-	let mut stdout = stdout();
-	execute!(stdout, EnterAlternateScreen, SetTitle("transparent-poker"))?;
-	let terminal_backend = CrosstermBackend::new(stdout);
-	let mut terminal = Terminal::new(terminal_backend)?;
+	// Linear output is printed as it comes, so it stays on the normal
+	// screen where the scrollback can be read back
+	let mut terminal = TuiTerminal::enter(WINDOW_TITLE, linear)?;
 
+	// Attached, straight back to the table the daemon is holding
+	let mut rejoin = daemon.as_ref().and_then(|info| info.table.clone());
 	loop {
-		let backend = NetworkBackend::new(client);
-		let mut menu = Menu::new(backend, username.clone(), theme.clone());
+		let mut backend = NetworkBackend::new(client).with_guests(guests);
+		if let Some(table) = rejoin.take() {
+			backend.send(LobbyCommand::JoinTable(table));
+		}
+		let mut menu = Menu::new(backend, username.clone(), theme.clone(), theme_name.clone()).with_linear(linear);
 
 		let result = menu.run(&mut terminal);
+		let (menu_theme, menu_theme_name) = menu.theme();
+		(theme, theme_name) = (menu_theme.clone(), menu_theme_name.to_string());
 
 		match result {
-			Ok(MenuResult::Quit) => {
-				disable_raw_mode()?;
-				execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-				return Ok(());
-			}
+			Ok(MenuResult::Quit) => return Ok(()),
 			Ok(MenuResult::NetworkGameStarted { seat: _, table_config, num_players }) => {
-				client = menu.into_backend().into_client();
-				match game_loop::run_game(&mut terminal, &mut client, &username, theme.clone(), theme_name.clone(), table_config, num_players)? {
+				(client, guests) = menu.into_backend().into_clients();
+				if daemon.is_some() {
+					DaemonInfo::set_table(&daemon_path, Some(&table_config.id)).map_err(user_error)?;
+				}
+				let result = if guests.is_empty() {
+					let detachable = daemon.is_some();
+					game_loop::run_game(&mut terminal, &mut client, &username, theme.clone(), theme_name.clone(), table_config, num_players, linear, detachable, status_file.as_deref())?
+				} else {
+					let seats = std::iter::once((&mut client, username.clone()))
+						.chain(guests.iter_mut().zip(hot_seat.iter().cloned()))
+						.collect();
+					game_loop::run_hot_seat(&mut terminal, seats, theme.clone(), theme_name.clone(), table_config, num_players)?
+				};
+				if daemon.is_some() && !matches!(result, game_loop::GameLoopResult::Detached) {
+					DaemonInfo::set_table(&daemon_path, None).map_err(user_error)?;
+				}
+				match result {
 					game_loop::GameLoopResult::ReturnToLobby => {
+						if !linear {
+							execute!(terminal.backend_mut(), SetTitle(WINDOW_TITLE))?;
+						}
 						continue;
 					}
-					game_loop::GameLoopResult::Quit => {
-						disable_raw_mode()?;
-						execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+					game_loop::GameLoopResult::Quit => return Ok(()),
+					game_loop::GameLoopResult::Detached => {
+						drop(terminal);
+						println!("Detached; the daemon holds your seat. `poker play --attach` picks the game back up.");
 						return Ok(());
 					}
 				}
 			}
-			Err(e) => {
-				disable_raw_mode()?;
-				execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-				return Err(e);
-			}
+			Err(e) => return Err(e),
 		}
 	}
 }
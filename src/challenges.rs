@@ -0,0 +1,288 @@
+//! Daily challenges, to give solo practice some structure: each day
+//! brings a few goals, the same for everyone, like winning three pots
+//! from the button or playing a session at Fixed-Limit. The server counts
+//! each finished hand toward the goals of the human players in it, and
+//! the bank pays a few play chips for each goal met. Meeting every goal
+//! on consecutive days builds a streak, which pays a bonus on top.
+//!
+//! Progress is kept with the player's bank profile. It counts toward
+//! the day's goals only, and starts over the next day.
+
+use chrono::{Datelike, Days, NaiveDate};
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::events::Seat;
+use crate::history::HandHistory;
+use crate::table::BettingStructure;
+
+/// Goals a day brings.
+pub const GOALS_PER_DAY: usize = 3;
+
+/// Paid for each day of a streak when the day's last goal is met.
+pub const STREAK_BONUS: f32 = 10.0;
+
+/// Days of a streak the bonus grows for.
+pub const STREAK_BONUS_CAP: u32 = 7;
+
+/// Something to do in a day's play.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Goal {
+	WinFromButton { pots: u32 },
+	WinAtShowdown { pots: u32 },
+	WinWithoutShowdown { pots: u32 },
+	PlayHands { hands: u32 },
+	/// This many hands in one game at a table with this betting.
+	PlaySession { betting: BettingStructure, hands: u32 },
+}
+
+impl Goal {
+	/// The count that meets it.
+	pub fn target(&self) -> u32 {
+		match *self {
+			Goal::WinFromButton { pots } | Goal::WinAtShowdown { pots } | Goal::WinWithoutShowdown { pots } => pots,
+			Goal::PlayHands { hands } | Goal::PlaySession { hands, .. } => hands,
+		}
+	}
+
+	/// Play chips for meeting it.
+	pub fn reward(&self) -> f32 {
+		match self {
+			Goal::WinFromButton { .. } | Goal::WinAtShowdown { .. } => 25.0,
+			Goal::WinWithoutShowdown { .. } | Goal::PlayHands { .. } => 20.0,
+			Goal::PlaySession { .. } => 30.0,
+		}
+	}
+
+	/// `count` after a hand that went as `outcome`.
+	fn advance(&self, count: u32, outcome: &HandOutcome) -> u32 {
+		let counts = match *self {
+			Goal::WinFromButton { .. } => outcome.won && outcome.on_button,
+			Goal::WinAtShowdown { .. } => outcome.won && outcome.showdown,
+			Goal::WinWithoutShowdown { .. } => outcome.won && !outcome.showdown,
+			Goal::PlayHands { .. } => true,
+			// The best session so far, not a running total
+			Goal::PlaySession { betting, .. } => {
+				return if outcome.betting == betting { count.max(outcome.session_hands) } else { count };
+			}
+		};
+		count + counts as u32
+	}
+
+	pub fn describe(&self) -> String {
+		match *self {
+			Goal::WinFromButton { pots } => format!("win {} pots from the button", pots),
+			Goal::WinAtShowdown { pots } => format!("win {} pots at showdown", pots),
+			Goal::WinWithoutShowdown { pots } => format!("win {} pots without a showdown", pots),
+			Goal::PlayHands { hands } => format!("play {} hands", hands),
+			Goal::PlaySession { betting, hands } => format!("play a session of {} hands at {}", hands, betting),
+		}
+	}
+}
+
+/// The goals for `day`, the same wherever they're asked for.
+pub fn daily(day: NaiveDate) -> Vec<Goal> {
+	let mut rng = StdRng::seed_from_u64(day.num_days_from_ce() as u64);
+	let betting = *[BettingStructure::NoLimit, BettingStructure::PotLimit, BettingStructure::FixedLimit]
+		.choose(&mut rng)
+		.unwrap_or(&BettingStructure::NoLimit);
+	let mut goals = vec![
+		Goal::WinFromButton { pots: 3 },
+		Goal::WinAtShowdown { pots: 3 },
+		Goal::WinWithoutShowdown { pots: 5 },
+		Goal::PlayHands { hands: 50 },
+		Goal::PlaySession { betting, hands: 20 },
+	];
+	goals.shuffle(&mut rng);
+	goals.truncate(GOALS_PER_DAY);
+	goals
+}
+
+/// How a hand went for one player, as far as the goals go.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandOutcome {
+	pub won: bool,
+	pub on_button: bool,
+	/// Whether anyone showed down.
+	pub showdown: bool,
+	pub betting: BettingStructure,
+	/// Hands they've played in this game, this one included.
+	pub session_hands: u32,
+}
+
+impl HandOutcome {
+	/// How `hand` went for `seat`, if they were dealt in.
+	pub fn of(hand: &HandHistory, seat: Seat, betting: BettingStructure, session_hands: u32) -> Option<Self> {
+		let player = hand.player(seat)?;
+		Some(Self {
+			won: player.net > 0.0,
+			on_button: hand.button == seat,
+			showdown: hand.players.iter().any(|p| p.made_hand.is_some()),
+			betting,
+			session_hands,
+		})
+	}
+}
+
+/// What a hand earned.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reward {
+	Goal { goal: Goal, chips: f32 },
+	/// Every goal met, `days` days running.
+	Streak { days: u32, chips: f32 },
+}
+
+impl Reward {
+	pub fn chips(&self) -> f32 {
+		match self {
+			Reward::Goal { chips, .. } | Reward::Streak { chips, .. } => *chips,
+		}
+	}
+
+	pub fn describe(&self) -> String {
+		match self {
+			Reward::Goal { goal, .. } => goal.describe(),
+			Reward::Streak { days: 1, .. } => "every goal met today".to_string(),
+			Reward::Streak { days, .. } => format!("every goal met, {} days running", days),
+		}
+	}
+}
+
+/// A player's progress, kept with their bank profile.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChallengeProgress {
+	/// The day `counts` are for, `YYYY-MM-DD`.
+	#[serde(default)]
+	pub day: String,
+	/// Toward each of the day's goals, in order.
+	#[serde(default)]
+	pub counts: Vec<u32>,
+	/// Days in a row every goal was met, up to `cleared_on`.
+	#[serde(default)]
+	pub streak: u32,
+	#[serde(default)]
+	pub cleared_on: Option<String>,
+}
+
+impl ChallengeProgress {
+	/// Counts a hand played on `today`, returning the goals it met and
+	/// the streak bonus if it met the day's last one.
+	pub fn observe(&mut self, today: NaiveDate, outcome: &HandOutcome) -> Vec<Reward> {
+		let goals = daily(today);
+		let day = today.to_string();
+		if self.day != day || self.counts.len() != goals.len() {
+			self.day = day.clone();
+			self.counts = vec![0; goals.len()];
+		}
+		let mut rewards = Vec::new();
+		for (goal, count) in goals.iter().zip(&mut self.counts) {
+			let was_met = *count >= goal.target();
+			*count = goal.advance(*count, outcome);
+			if !was_met && *count >= goal.target() {
+				rewards.push(Reward::Goal { goal: *goal, chips: goal.reward() });
+			}
+		}
+		let all_met = goals.iter().zip(&self.counts).all(|(goal, count)| *count >= goal.target());
+		if all_met && self.cleared_on.as_deref() != Some(day.as_str()) {
+			self.streak = self.streak_on(today) + 1;
+			self.cleared_on = Some(day);
+			let chips = STREAK_BONUS * self.streak.min(STREAK_BONUS_CAP) as f32;
+			rewards.push(Reward::Streak { days: self.streak, chips });
+		}
+		rewards
+	}
+
+	/// `today`'s count toward each of its goals.
+	pub fn counts_on(&self, today: NaiveDate) -> Vec<u32> {
+		if self.day == today.to_string() && self.counts.len() == GOALS_PER_DAY {
+			self.counts.clone()
+		} else {
+			vec![0; GOALS_PER_DAY]
+		}
+	}
+
+	/// The streak as of `today`: still going if the goals were last all
+	/// met today or yesterday.
+	pub fn streak_on(&self, today: NaiveDate) -> u32 {
+		let yesterday = today.checked_sub_days(Days::new(1));
+		let going = self.cleared_on.as_deref().is_some_and(|cleared| {
+			cleared == today.to_string() || yesterday.is_some_and(|y| cleared == y.to_string())
+		});
+		if going { self.streak } else { 0 }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn day(n: u32) -> NaiveDate {
+		NaiveDate::from_ymd_opt(2026, 3, n).unwrap()
+	}
+
+	/// A won hand from the button at the betting `today`'s session goal
+	/// wants, shown down every other time.
+	fn won(today: NaiveDate, session_hands: u32) -> HandOutcome {
+		let betting = daily(today)
+			.iter()
+			.find_map(|g| match g {
+				Goal::PlaySession { betting, .. } => Some(*betting),
+				_ => None,
+			})
+			.unwrap_or(BettingStructure::NoLimit);
+		HandOutcome { won: true, on_button: true, showdown: session_hands.is_multiple_of(2), betting, session_hands }
+	}
+
+	#[test]
+	fn test_daily_goals_are_the_same_all_day() {
+		assert_eq!(daily(day(1)), daily(day(1)));
+		assert_eq!(daily(day(1)).len(), GOALS_PER_DAY);
+		let days = (1..=20).map(|n| daily(day(n))).collect::<Vec<_>>();
+		assert!(days.iter().any(|goals| *goals != days[0]), "the goals change from day to day");
+	}
+
+	#[test]
+	fn test_goals_pay_once_each() {
+		let goal = Goal::WinFromButton { pots: 3 };
+		let outcome = HandOutcome {
+			won: true,
+			on_button: false,
+			showdown: false,
+			betting: BettingStructure::NoLimit,
+			session_hands: 1,
+		};
+		assert_eq!(goal.advance(0, &outcome), 0);
+		assert_eq!(goal.advance(2, &HandOutcome { on_button: true, ..outcome }), 3);
+
+		let session = Goal::PlaySession { betting: BettingStructure::FixedLimit, hands: 20 };
+		let limit = HandOutcome { betting: BettingStructure::FixedLimit, session_hands: 12, ..outcome };
+		assert_eq!(session.advance(15, &limit), 15, "the best session counts");
+		assert_eq!(session.advance(15, &HandOutcome { session_hands: 20, ..limit }), 20);
+		assert_eq!(session.advance(0, &HandOutcome { session_hands: 20, ..outcome }), 0);
+	}
+
+	#[test]
+	fn test_streak_builds_and_breaks() {
+		let mut progress = ChallengeProgress::default();
+		let play_out = |progress: &mut ChallengeProgress, today: NaiveDate| {
+			(1..=60).flat_map(|n| progress.observe(today, &won(today, n))).collect::<Vec<_>>()
+		};
+
+		let rewards = play_out(&mut progress, day(1));
+		assert_eq!(rewards.len(), GOALS_PER_DAY + 1);
+		assert_eq!(rewards.last(), Some(&Reward::Streak { days: 1, chips: STREAK_BONUS }));
+		assert!(play_out(&mut progress, day(1)).is_empty(), "nothing pays twice in a day");
+
+		let rewards = play_out(&mut progress, day(2));
+		assert_eq!(rewards.last(), Some(&Reward::Streak { days: 2, chips: 2.0 * STREAK_BONUS }));
+		assert_eq!(progress.streak_on(day(3)), 2);
+
+		// Missing a day starts it over
+		assert_eq!(progress.streak_on(day(4)), 0);
+		let rewards = play_out(&mut progress, day(4));
+		assert_eq!(rewards.last(), Some(&Reward::Streak { days: 1, chips: STREAK_BONUS }));
+		assert_eq!(progress.counts_on(day(5)), vec![0; GOALS_PER_DAY]);
+	}
+}
@@ -1,6 +1,12 @@
-use serde::Deserialize;
+use chrono::{Datelike, NaiveDateTime, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::bank::BustPolicy;
+use crate::error::PokerError;
+use crate::money::MoneyFormat;
 
 fn config_paths(filename: &str) -> Vec<PathBuf> {
 	let mut paths = Vec::new();
@@ -17,13 +23,13 @@ fn find_config(filename: &str) -> Option<PathBuf> {
 	config_paths(filename).into_iter().find(|p| p.exists())
 }
 
-pub fn resolve_config(filename: &str) -> Result<PathBuf, String> {
+pub fn resolve_config(filename: &str) -> Result<PathBuf, PokerError> {
 	find_config(filename).ok_or_else(|| {
 		let searched: Vec<_> = config_paths(filename)
 			.iter()
 			.map(|p| p.display().to_string())
 			.collect();
-		format!("Config file '{}' not found. Searched: {}", filename, searched.join(", "))
+		PokerError::MissingConfig { filename: filename.to_string(), searched: searched.join(", ") }
 	})
 }
 
@@ -39,6 +45,72 @@ pub struct PlayerConfig {
 	pub strategy: String,
 	#[serde(default)]
 	pub strategy_model: Option<String>,
+	/// Overrides the strategy's decision speed.
+	#[serde(default)]
+	pub tempo: Option<crate::strategy::Tempo>,
+	/// When the player is around to be seated; empty means any time.
+	#[serde(default)]
+	pub schedule: Vec<Schedule>,
+	/// What the player does on going broke.
+	#[serde(default)]
+	pub when_busted: BustPolicy,
+}
+
+/// A stretch of the week a roster player plays, in local time.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Schedule {
+	/// `mon` to `sun`; empty means every day.
+	#[serde(default)]
+	pub days: Vec<Day>,
+	/// First hour and the hour it ends, 0 to 24. An end before the start
+	/// runs past midnight, into the next day. Leave out for all day.
+	#[serde(default)]
+	pub hours: Option<(u32, u32)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Day {
+	Mon,
+	Tue,
+	Wed,
+	Thu,
+	Fri,
+	Sat,
+	Sun,
+}
+
+impl Day {
+	fn weekday(self) -> Weekday {
+		match self {
+			Day::Mon => Weekday::Mon,
+			Day::Tue => Weekday::Tue,
+			Day::Wed => Weekday::Wed,
+			Day::Thu => Weekday::Thu,
+			Day::Fri => Weekday::Fri,
+			Day::Sat => Weekday::Sat,
+			Day::Sun => Weekday::Sun,
+		}
+	}
+}
+
+impl Schedule {
+	pub fn covers(&self, at: NaiveDateTime) -> bool {
+		let today = at.weekday();
+		let hour = at.hour();
+		match self.hours {
+			None => self.plays_on(today),
+			Some((start, end)) if start <= end => self.plays_on(today) && (start..end).contains(&hour),
+			// Late sessions belong to the day they started
+			Some((start, end)) => {
+				(hour >= start && self.plays_on(today)) || (hour < end && self.plays_on(today.pred()))
+			}
+		}
+	}
+
+	fn plays_on(&self, day: Weekday) -> bool {
+		self.days.is_empty() || self.days.iter().any(|d| d.weekday() == day)
+	}
 }
 
 fn default_version() -> String {
@@ -59,6 +131,11 @@ impl PlayerConfig {
 			}
 		})
 	}
+
+	/// Whether the player's schedule has them playing at `at`.
+	pub fn is_online(&self, at: NaiveDateTime) -> bool {
+		self.schedule.is_empty() || self.schedule.iter().any(|s| s.covers(at))
+	}
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -67,12 +144,12 @@ pub struct PlayersFile {
 	pub players: Vec<PlayerConfig>,
 }
 
-pub fn load_players<P: AsRef<Path>>(path: P) -> Result<Vec<PlayerConfig>, String> {
+pub fn load_players<P: AsRef<Path>>(path: P) -> Result<Vec<PlayerConfig>, PokerError> {
 	let content = fs::read_to_string(&path)
-		.map_err(|e| format!("Failed to read {}: {}", path.as_ref().display(), e))?;
+		.map_err(|e| PokerError::io("read", path.as_ref(), e))?;
 
 	let file: PlayersFile = toml::from_str(&content)
-		.map_err(|e| format!("Failed to parse players config: {}", e))?;
+		.map_err(|e| PokerError::parse("players config", e))?;
 
 	Ok(file.players)
 }
@@ -168,14 +245,23 @@ impl ModelsConfig {
 				self.defaults.opponent_strategy
 			))
 	}
+
+	pub fn pit_boss_model(&self) -> Result<&ModelConfig, PokerError> {
+		self.get(&self.defaults.pit_boss).ok_or_else(|| {
+			PokerError::Config(format!(
+				"Invalid pit_boss model '{}' in config. Must be haiku, sonnet, or opus.",
+				self.defaults.pit_boss
+			))
+		})
+	}
 }
 
-pub fn load_models<P: AsRef<Path>>(path: P) -> Result<ModelsConfig, String> {
+pub fn load_models<P: AsRef<Path>>(path: P) -> Result<ModelsConfig, PokerError> {
 	let content = fs::read_to_string(&path)
-		.map_err(|e| format!("Failed to read {}: {}", path.as_ref().display(), e))?;
+		.map_err(|e| PokerError::io("read", path.as_ref(), e))?;
 
 	toml::from_str(&content)
-		.map_err(|e| format!("Failed to parse models config: {}", e))
+		.map_err(|e| PokerError::parse("models config", e))
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -210,33 +296,157 @@ pub struct GamesConfig {
 	pub stakes: Vec<StakesConfig>,
 }
 
-pub fn load_games<P: AsRef<Path>>(path: P) -> Result<GamesConfig, String> {
+pub fn load_games<P: AsRef<Path>>(path: P) -> Result<GamesConfig, PokerError> {
 	let content = fs::read_to_string(&path)
-		.map_err(|e| format!("Failed to read {}: {}", path.as_ref().display(), e))?;
+		.map_err(|e| PokerError::io("read", path.as_ref(), e))?;
 
 	toml::from_str(&content)
-		.map_err(|e| format!("Failed to parse games config: {}", e))
+		.map_err(|e| PokerError::parse("games config", e))
 }
 
-pub fn load_models_auto() -> Result<ModelsConfig, String> {
+pub fn load_models_auto() -> Result<ModelsConfig, PokerError> {
 	let path = resolve_config("models.toml")?;
 	load_models(&path)
 }
 
-pub fn load_strategies_auto() -> Result<crate::strategy::StrategyStore, String> {
+pub fn load_strategies_auto() -> Result<crate::strategy::StrategyStore, PokerError> {
 	match resolve_config("strategies.toml") {
 		Ok(path) => crate::strategy::StrategyStore::load(&path),
 		Err(_) => Ok(crate::strategy::StrategyStore::default()),
 	}
 }
 
-pub fn load_players_auto() -> Result<Vec<PlayerConfig>, String> {
+pub fn load_players_auto() -> Result<Vec<PlayerConfig>, PokerError> {
 	match resolve_config("players.toml") {
 		Ok(path) => load_players(&path),
 		Err(_) => Ok(Vec::new()),
 	}
 }
 
+/// How the TUI client plays hands out, set in-game or from the lobby's
+/// settings screen and kept in `preferences.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+	/// Don't show other players' cards at showdowns the hero isn't in.
+	pub skip_showdowns: bool,
+	/// Muck the hero's losing hands instead of showing them.
+	pub auto_muck: bool,
+	/// Skip AI pauses once the hero is out of the hand.
+	pub fast_forward_folded: bool,
+	/// Explain each decision in the action prompt and double-check
+	/// likely misclicks.
+	pub beginner_hints: bool,
+	/// A line in the chat after a hand where a fold or call after the flop
+	/// was clearly wrong for the price; see `tui::coach`.
+	pub coaching_tips: bool,
+	/// Theme to start with when none is given on the command line.
+	pub theme: Option<String>,
+	/// Pause after each action, in place of the table's.
+	pub action_delay_ms: Option<u64>,
+	/// Pause when the flop, turn and river come, in place of the table's.
+	pub street_delay_ms: Option<u64>,
+	/// Deal the hole cards round the table one at a time, this long
+	/// apart; unset deals them all at once.
+	pub deal_delay_ms: Option<u64>,
+	/// Language code for the UI, like "es"; unset follows the system's.
+	pub locale: Option<String>,
+	/// How amounts are written.
+	pub money: MoneyFormat,
+}
+
+/// The profile whose preferences and hand histories are in use; unset
+/// means the shared ones, as before there were profiles.
+static PROFILE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Switches to `name`'s profile, creating its directory if it's new.
+pub fn set_profile(name: &str) -> Result<(), PokerError> {
+	let key = profile_key(name)?;
+	if let Some(dir) = profile_dir(&key) {
+		fs::create_dir_all(&dir).map_err(|e| PokerError::io("create", &dir, e))?;
+	}
+	*PROFILE.write().unwrap_or_else(|e| e.into_inner()) = Some(key);
+	Ok(())
+}
+
+/// The profile in use, if one was set.
+pub fn profile() -> Option<String> {
+	PROFILE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// A player name as profiles are kept, lowercased like bank ids. It names
+/// a directory, so it can't climb out of `profiles/`.
+pub fn profile_key(name: &str) -> Result<String, PokerError> {
+	let key = name.trim().to_lowercase();
+	let fits = !key.is_empty()
+		&& !key.starts_with('.')
+		&& key.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ' '));
+	if fits {
+		Ok(key)
+	} else {
+		Err(PokerError::Config(format!("'{}' can't name a profile; use letters, digits, spaces, '-', '_' or '.'", name)))
+	}
+}
+
+fn profiles_dir() -> Option<PathBuf> {
+	dirs::config_dir().map(|dir| dir.join("transparent-poker").join("profiles"))
+}
+
+/// Where a profile's own settings are kept, `profiles/<name>/` in the
+/// config directory.
+pub fn profile_dir(key: &str) -> Option<PathBuf> {
+	profiles_dir().map(|dir| dir.join(key))
+}
+
+/// Every profile that's been played or registered, by name.
+pub fn list_profiles() -> Vec<String> {
+	let mut profiles = profiles_dir()
+		.and_then(|dir| fs::read_dir(dir).ok())
+		.into_iter()
+		.flatten()
+		.filter_map(Result::ok)
+		.filter(|entry| entry.path().is_dir())
+		.filter_map(|entry| entry.file_name().into_string().ok())
+		.collect::<Vec<_>>();
+	profiles.sort();
+	profiles
+}
+
+/// Where preferences are looked for, first match wins: the profile's own,
+/// then the shared ones, so a new profile starts from what was set before.
+fn preferences_paths(profile: Option<&str>) -> Vec<PathBuf> {
+	let shared = dirs::config_dir().map(|dir| dir.join("transparent-poker").join("preferences.toml"));
+	let own = profile.and_then(profile_dir).map(|dir| dir.join("preferences.toml"));
+	own.into_iter().chain(shared).collect()
+}
+
+pub fn parse_preferences(content: &str) -> Result<Preferences, PokerError> {
+	toml::from_str(content).map_err(|e| PokerError::parse("preferences", e))
+}
+
+/// The saved preferences, or the defaults if there are none yet.
+pub fn load_preferences() -> Preferences {
+	preferences_paths(profile().as_deref())
+		.into_iter()
+		.find_map(|path| fs::read_to_string(path).ok())
+		.and_then(|content| parse_preferences(&content).ok())
+		.unwrap_or_default()
+}
+
+/// Saves to the profile in use, or the shared preferences without one.
+pub fn save_preferences(prefs: &Preferences) -> Result<(), PokerError> {
+	let path = preferences_paths(profile().as_deref())
+		.into_iter()
+		.next()
+		.ok_or_else(|| PokerError::Config("No config directory".to_string()))?;
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir).map_err(|e| PokerError::io("create", dir, e))?;
+	}
+	let content = toml::to_string_pretty(prefs)
+		.map_err(|e| PokerError::Config(format!("Failed to serialize preferences: {}", e)))?;
+	fs::write(&path, content).map_err(|e| PokerError::io("write", &path, e))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -250,6 +460,9 @@ mod tests {
 			join_probability: 0.5,
 			strategy: "tag".to_string(),
 			strategy_model: None,
+			tempo: None,
+			schedule: Vec::new(),
+			when_busted: BustPolicy::default(),
 		};
 		assert_eq!(config.display_name(), "Test Player");
 	}
@@ -263,6 +476,9 @@ mod tests {
 			join_probability: 0.5,
 			strategy: "tag".to_string(),
 			strategy_model: None,
+			tempo: None,
+			schedule: Vec::new(),
+			when_busted: BustPolicy::default(),
 		};
 		assert_eq!(config.display_name(), "Alice");
 	}
@@ -276,10 +492,69 @@ mod tests {
 			join_probability: 0.5,
 			strategy: "tag".to_string(),
 			strategy_model: None,
+			tempo: None,
+			schedule: Vec::new(),
+			when_busted: BustPolicy::default(),
 		};
 		assert_eq!(config.display_name(), "McDonald");
 	}
 
+	#[test]
+	fn test_schedules_by_day_and_hour() {
+		let file: PlayersFile = toml::from_str(
+			r#"
+			[[players]]
+			id = "owl"
+			strategy = "lag"
+			schedule = [{ days = ["fri", "sat"], hours = [20, 3] }, { days = ["sun"] }]
+
+			[[players]]
+			id = "anytime"
+			strategy = "rock"
+			"#,
+		)
+		.unwrap();
+		let at = |day: u32, hour: u32| {
+			// 2024-03-04 was a Monday
+			chrono::NaiveDate::from_ymd_opt(2024, 3, 3 + day).unwrap().and_hms_opt(hour, 30, 0).unwrap()
+		};
+		let owl = &file.players[0];
+		assert!(!owl.is_online(at(5, 19)));
+		assert!(owl.is_online(at(5, 21)));
+		// Friday night runs into Saturday morning
+		assert!(owl.is_online(at(6, 2)));
+		assert!(!owl.is_online(at(6, 3)));
+		assert!(owl.is_online(at(7, 11)));
+		assert!(!owl.is_online(at(1, 2)));
+		assert!(!owl.is_online(at(4, 21)));
+		assert!((1..=7).all(|day| file.players[1].is_online(at(day, 4))));
+
+		let bundled: PlayersFile = toml::from_str(include_str!("../../config/players.toml")).unwrap();
+		assert!(bundled.players.iter().any(|p| !p.schedule.is_empty()));
+	}
+
+	#[test]
+	fn test_bust_policies_parse() {
+		let file: PlayersFile = toml::from_str(
+			r#"
+			[[players]]
+			id = "a"
+			strategy = "rock"
+			when_busted = "move-down"
+
+			[[players]]
+			id = "b"
+			strategy = "rock"
+			"#,
+		)
+		.unwrap();
+		assert_eq!(file.players[0].when_busted, BustPolicy::MoveDown);
+		assert_eq!(file.players[1].when_busted, BustPolicy::Reload);
+
+		let bundled: PlayersFile = toml::from_str(include_str!("../../config/players.toml")).unwrap();
+		assert!(bundled.players.iter().any(|p| p.when_busted == BustPolicy::Payday));
+	}
+
 	#[test]
 	fn test_model_config_calculate_cost() {
 		let model = ModelConfig {
@@ -352,6 +627,14 @@ mod tests {
 		assert!(config.get("sonnet").is_some());
 		assert!(config.get("opus").is_some());
 		assert!(config.get("gpt4").is_none());
+		assert_eq!(config.pit_boss_model().unwrap().id, "opus");
+	}
+
+	#[test]
+	fn test_bundled_models_config_parses() {
+		let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("config/models.toml");
+		let config = load_models(path).unwrap();
+		assert!(config.pit_boss_model().is_ok());
 	}
 
 	#[test]
@@ -376,4 +659,51 @@ mod tests {
 	fn test_default_max_tokens() {
 		assert_eq!(default_max_tokens(), 100);
 	}
+
+	#[test]
+	fn test_preferences_round_trip_and_default_missing_keys() {
+		let prefs = Preferences {
+			skip_showdowns: true,
+			auto_muck: false,
+			fast_forward_folded: true,
+			beginner_hints: true,
+			coaching_tips: true,
+			theme: Some("nord".to_string()),
+			action_delay_ms: Some(250),
+			street_delay_ms: None,
+			deal_delay_ms: Some(120),
+			locale: Some("es".to_string()),
+			money: MoneyFormat {
+				symbol: "€".to_string(),
+				symbol_after: true,
+				thousands: ".".to_string(),
+				chips_in_tournaments: true,
+			},
+		};
+		let content = toml::to_string_pretty(&prefs).unwrap();
+		assert_eq!(parse_preferences(&content).unwrap(), prefs);
+
+		let partial = parse_preferences("auto_muck = true\n").unwrap();
+		assert_eq!(partial, Preferences { auto_muck: true, ..Preferences::default() });
+	}
+
+	#[test]
+	fn test_profile_key_lowercases_and_stays_in_its_directory() {
+		assert_eq!(profile_key(" Alice ").unwrap(), "alice");
+		assert_eq!(profile_key("mary-jo smith").unwrap(), "mary-jo smith");
+		assert!(profile_key("").is_err());
+		assert!(profile_key("..").is_err());
+		assert!(profile_key("../bob").is_err());
+		assert!(profile_key("a/b").is_err());
+	}
+
+	#[test]
+	fn test_profile_preferences_come_before_shared() {
+		let paths = preferences_paths(Some("alice"));
+		if let [own, shared] = paths.as_slice() {
+			assert!(own.ends_with("profiles/alice/preferences.toml"));
+			assert!(shared.ends_with("transparent-poker/preferences.toml"));
+		}
+		assert!(preferences_paths(None).len() <= 1);
+	}
 }
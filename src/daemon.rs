@@ -0,0 +1,132 @@
+//! `poker daemon`: the game server on its own in the background, so a long
+//! tournament outlives the terminal it's played in. `poker play --attach`
+//! connects to it like to any server. The server holds the seat of a
+//! player whose terminal goes away and pauses a game with nobody left
+//! connected (`GameServer::detachable`); joining the table again picks
+//! the game back up.
+//!
+//! While the daemon runs, `transparent-poker-daemon.toml` in the runtime
+//! directory holds its address and the table its player last sat at.
+//! Removing the file is how it's told to stop.
+
+use std::fs;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::PokerError;
+use crate::net::GameServer;
+
+/// How often a running daemon checks whether it's been told to stop.
+const STOP_POLL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaemonInfo {
+	/// Where its server listens, always on this machine.
+	pub addr: String,
+	pub pid: u32,
+	/// Who started it; they may pause its games.
+	pub username: String,
+	pub started_at: String,
+	/// The table the player was last playing at, to go straight back to
+	/// when they attach.
+	#[serde(default)]
+	pub table: Option<String>,
+}
+
+impl DaemonInfo {
+	pub fn default_path() -> PathBuf {
+		dirs::runtime_dir().unwrap_or_else(std::env::temp_dir).join("transparent-poker-daemon.toml")
+	}
+
+	/// The running daemon's details, or none if there isn't one.
+	pub fn load_from(path: &Path) -> Result<Option<Self>, PokerError> {
+		if !path.exists() {
+			return Ok(None);
+		}
+		let content = fs::read_to_string(path).map_err(|e| PokerError::io("read", path, e))?;
+		toml::from_str(&content).map(Some).map_err(|e| PokerError::parse(path.display().to_string(), e))
+	}
+
+	pub fn save_to(&self, path: &Path) -> Result<(), PokerError> {
+		let content = toml::to_string(self).map_err(|e| PokerError::parse("daemon details", e))?;
+		fs::write(path, content).map_err(|e| PokerError::io("write", path, e))
+	}
+
+	/// Whether its server still answers; a daemon that was killed leaves
+	/// its file behind.
+	pub fn is_running(&self) -> bool {
+		self.addr
+			.parse()
+			.is_ok_and(|addr| TcpStream::connect_timeout(&addr, Duration::from_millis(500)).is_ok())
+	}
+
+	/// Notes the table the player sat down at, or that they left it, if a
+	/// daemon is running.
+	pub fn set_table(path: &Path, table: Option<&str>) -> Result<(), PokerError> {
+		match Self::load_from(path)? {
+			Some(mut info) => {
+				info.table = table.map(String::from);
+				info.save_to(path)
+			}
+			None => Ok(()),
+		}
+	}
+}
+
+/// Serves `server` on a free local port, noted at `path`, until that file
+/// is removed.
+pub fn run(server: GameServer, path: &Path, username: &str) -> io::Result<()> {
+	let listener = TcpListener::bind("127.0.0.1:0")?;
+	let info = DaemonInfo {
+		addr: listener.local_addr()?.to_string(),
+		pid: std::process::id(),
+		username: username.to_string(),
+		started_at: chrono::Local::now().to_rfc3339(),
+		table: None,
+	};
+	info.save_to(path).map_err(|e| io::Error::other(e.user_message()))?;
+	println!("Poker daemon listening on {}", info.addr);
+	thread::spawn(move || server.detachable().run_with_listener(listener));
+	while path.exists() {
+		thread::sleep(STOP_POLL);
+	}
+	println!("Poker daemon stopping");
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_daemon_info_notes_the_table_and_goes_stale() {
+		let path = std::env::temp_dir().join(format!("poker-daemon-{}.toml", std::process::id()));
+		let _ = fs::remove_file(&path);
+		assert_eq!(DaemonInfo::load_from(&path).unwrap(), None);
+		DaemonInfo::set_table(&path, Some("cash-1")).unwrap();
+		assert!(!path.exists(), "no daemon, nothing to note");
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let info = DaemonInfo {
+			addr: listener.local_addr().unwrap().to_string(),
+			pid: 42,
+			username: "ann".to_string(),
+			started_at: "2026-10-16T20:00:00+00:00".to_string(),
+			table: None,
+		};
+		info.save_to(&path).unwrap();
+		DaemonInfo::set_table(&path, Some("cash-1")).unwrap();
+		let loaded = DaemonInfo::load_from(&path).unwrap().unwrap();
+		assert_eq!(loaded, DaemonInfo { table: Some("cash-1".to_string()), ..info });
+		assert!(loaded.is_running());
+
+		drop(listener);
+		assert!(!loaded.is_running());
+		fs::remove_file(&path).unwrap();
+	}
+}
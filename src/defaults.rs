@@ -60,3 +60,40 @@ pub fn list_themes() -> Vec<String> {
 	themes.sort();
 	themes
 }
+
+/// A file from the built-in config directory, like `locales/en.toml`.
+pub fn embedded(path: &str) -> Option<&'static str> {
+	CONFIG_DIR.get_file(path).and_then(|file| file.contents_utf8())
+}
+
+pub fn list_locales() -> Vec<String> {
+	let mut locales = Vec::new();
+
+	if let Some(dir) = CONFIG_DIR.get_dir("locales") {
+		for file in dir.files() {
+			if let Some(code) = file.path().file_stem() {
+				locales.push(code.to_string_lossy().to_string());
+			}
+		}
+	}
+
+	if let Some(config_dir) = dirs::config_dir() {
+		let user_locales = config_dir.join("transparent-poker").join("locales");
+		if let Ok(entries) = fs::read_dir(user_locales) {
+			for entry in entries.flatten() {
+				let path = entry.path();
+				if path.extension().map(|e| e == "toml").unwrap_or(false) {
+					if let Some(stem) = path.file_stem() {
+						let code = stem.to_string_lossy().to_string();
+						if !locales.contains(&code) {
+							locales.push(code);
+						}
+					}
+				}
+			}
+		}
+	}
+
+	locales.sort();
+	locales
+}
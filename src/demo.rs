@@ -0,0 +1,302 @@
+//! `poker demo`: a short game between AI players, drawn on the table as it
+//! plays, for screenshots and as a smoke test of the engine and the view
+//! together. With a capture it draws to a `TestBackend` instead and keeps
+//! the screen as text at the end of each hand.
+//!
+//! Given strategies, it seats one AI playing each, to see them side by
+//! side. `+` and `-` speed the game up or slow it down, and the pit boss
+//! can comment on the big hands.
+
+use std::io;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::backend::{CrosstermBackend, TestBackend};
+use ratatui::Terminal;
+
+use crate::config::{load_players_auto, load_strategies_auto};
+use crate::error::PokerError;
+use crate::events::{ChatSender, GameEvent, Seat};
+use crate::game::{Game, RunningGame};
+use crate::i18n::tr_with;
+use crate::pit_boss::{self, PitBoss, PitBossConfig, PitBossHandle};
+use crate::players::RulesPlayer;
+use crate::strategy::Strategy;
+use crate::table::Pacing;
+use crate::theme::Theme;
+use crate::tui::snapshot::screen_text;
+use crate::tui::{GameUI, GameUIAction};
+
+/// A little quicker than a table's defaults; the pot being pushed holds
+/// each hand's result up long enough.
+const PACING: Pacing = Pacing { action_ms: 400, street_ms: 400, hand_end_ms: 0 };
+
+/// How long `--auto` leaves the final standings up before quitting.
+const FINAL_PAUSE: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone)]
+pub struct DemoConfig {
+	pub hands: u32,
+	pub players: usize,
+	pub seed: Option<u64>,
+	/// Quit once the game is over instead of waiting for a key.
+	pub auto: bool,
+	/// One AI for each of these strategies, in place of the roster.
+	pub strategies: Vec<String>,
+	/// How many times the usual pace to play at, to begin with.
+	pub speed: f32,
+	/// Have the pit boss comment on the big hands.
+	pub commentary: bool,
+}
+
+impl Default for DemoConfig {
+	fn default() -> Self {
+		Self {
+			hands: 10,
+			players: 4,
+			seed: None,
+			auto: false,
+			strategies: Vec::new(),
+			speed: 1.0,
+			commentary: false,
+		}
+	}
+}
+
+impl DemoConfig {
+	fn seats(&self) -> usize {
+		if self.strategies.is_empty() { self.players } else { self.strategies.len() }
+	}
+}
+
+/// Starts the game: an AI for each strategy asked for, or else the first
+/// regulars from the roster, each playing their strategy, with stand-ins
+/// if the roster is short.
+pub fn start(config: &DemoConfig) -> Result<RunningGame, PokerError> {
+	if !(2..=9).contains(&config.seats()) {
+		return Err(PokerError::Config(format!("A demo seats 2 to 9 players, not {}", config.seats())));
+	}
+	let strategies = load_strategies_auto()?;
+	let roster = load_players_auto()?;
+	let mut seated: Vec<(String, Strategy)> = Vec::new();
+	for id in &config.strategies {
+		let Some(strategy) = strategies.get(id) else {
+			let mut known = strategies.list();
+			known.sort();
+			return Err(PokerError::NotFound(format!("No strategy '{}'; there are {}", id, known.join(", "))));
+		};
+		// The same strategy twice is told apart by number
+		let twice = config.strategies.iter().filter(|s| *s == id).count() > 1;
+		let name = if twice {
+			let n = seated.iter().filter(|(_, s)| s.id == *id).count() + 1;
+			format!("{} {}", strategy.name, n)
+		} else {
+			strategy.name.clone()
+		};
+		seated.push((name, strategy.clone()));
+	}
+	if seated.is_empty() {
+		for i in 0..config.players {
+			seated.push(match roster.get(i) {
+				Some(player) => (player.display_name(), strategies.get_or_default(&player.strategy)),
+				None => (format!("Bot {}", i + 1), strategies.get_or_default("balanced")),
+			});
+		}
+	}
+	let mut builder = Game::builder()
+		.blinds(5.0, 10.0)
+		.starting_stack(500.0)
+		.max_hands(config.hands);
+	if let Some(seed) = config.seed {
+		builder = builder.seed(seed);
+	}
+	for (i, (name, strategy)) in seated.into_iter().enumerate() {
+		builder = builder.shared_player(Arc::new(RulesPlayer::new(Seat(i), &name, strategy)));
+	}
+	Ok(builder.build()?.spawn())
+}
+
+fn demo_ui(theme: Theme, theme_name: String, config: &DemoConfig) -> GameUI {
+	let mut ui = GameUI::new(None, theme, theme_name);
+	ui.show_all_cards = true;
+	let info = if config.strategies.is_empty() {
+		format!("{} hands, AI only", config.hands)
+	} else {
+		format!("{} hands, {}", config.hands, config.strategies.join(" vs "))
+	};
+	ui.set_table_info("Demo".to_string(), info, Vec::new());
+	ui
+}
+
+/// The pit boss, commenting into `say`. With no one to hurry along at a
+/// table of AIs, it has nothing to say without a model.
+fn commentator(say: mpsc::Sender<String>) -> Result<PitBossHandle, PokerError> {
+	let model = pit_boss::configured_model()?;
+	let config = PitBossConfig { narrate: true, ..PitBossConfig::default() };
+	Ok(PitBoss::new(config, Some(model)).spawn(move |text| {
+		let _ = say.send(text);
+	}))
+}
+
+type Term = Terminal<CrosstermBackend<io::Stdout>>;
+
+/// Plays the demo on the terminal. `q` quits at any point; without `auto`
+/// the final standings stay up until it's pressed.
+pub fn run_demo(terminal: &mut Term, config: &DemoConfig, theme: Theme, theme_name: String) -> io::Result<()> {
+	if config.speed <= 0.0 {
+		return Err(io::Error::other(format!("A speed of {} would never get anywhere", config.speed)));
+	}
+	let (say, said) = mpsc::channel();
+	let pit_boss = if config.commentary { Some(commentator(say).map_err(|e| io::Error::other(e.user_message()))?) } else { None };
+	let running = start(config).map_err(|e| io::Error::other(e.user_message()))?;
+	let mut ui = demo_ui(theme, theme_name, config);
+
+	let mut speed = config.speed;
+	let mut ended = false;
+	while let Ok(event) = running.events.recv() {
+		if let Some(pit_boss) = &pit_boss {
+			pit_boss.event(&event);
+		}
+		ui.apply_event(&event);
+		for text in said.try_iter() {
+			ui.apply_event(&GameEvent::ChatMessage { sender: ChatSender::Dealer, text });
+		}
+		ended |= matches!(event, GameEvent::GameEnded { .. });
+		let pause = PACING.delay(&event).div_f32(speed);
+		if pause.is_zero() {
+			continue;
+		}
+		terminal.draw(|f| ui.render(f, f.area()))?;
+		if quit_within(terminal, &mut ui, pause, &mut speed)? {
+			running.stop();
+			break;
+		}
+	}
+
+	ui.status_message = Some(if config.auto { "Demo over".to_string() } else { "Demo over. Press q to quit".to_string() });
+	terminal.draw(|f| ui.render(f, f.area()))?;
+	if ended && !config.auto {
+		while !quit_within(terminal, &mut ui, Duration::from_secs(60), &mut speed)? {}
+	} else if ended {
+		quit_within(terminal, &mut ui, FINAL_PAUSE, &mut speed)?;
+	}
+	running.join();
+	Ok(())
+}
+
+/// Waits out `pause`, true if `q` was pressed meanwhile. The watcher's
+/// keys work as in `poker watch`: `v` to see the table as one player, `?`
+/// and `$` as at the table, and `+` and `-` step `speed` up and down.
+fn quit_within(terminal: &mut Term, ui: &mut GameUI, pause: Duration, speed: &mut f32) -> io::Result<bool> {
+	let until = Instant::now() + pause;
+	loop {
+		let left = until.saturating_duration_since(Instant::now());
+		if left.is_zero() || !event::poll(left)? {
+			return Ok(false);
+		}
+		if let Event::Key(key) = event::read()? {
+			if key.kind != KeyEventKind::Press {
+				continue;
+			}
+			match key.code {
+				KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
+				KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(true),
+				KeyCode::Char('?' | '$') => {
+					ui.handle_key(key.code);
+				}
+				KeyCode::Char('v') => ui.cycle_perspective(),
+				KeyCode::Char('+' | '=' | '-') => {
+					if let GameUIAction::ChangeSpeed { faster } = ui.handle_key(key.code) {
+						*speed = Pacing::step_speed(*speed, faster);
+						ui.status_message = Some(tr_with("status.speed", &[("speed", &speed.to_string())]));
+					}
+				}
+				_ => continue,
+			}
+			terminal.draw(|f| ui.render(f, f.area()))?;
+		}
+	}
+}
+
+/// Plays the demo without a terminal, drawing each hand's end on a
+/// `width` by `height` screen, and returns the screens as text. Fails if
+/// the game stops short of its end.
+pub fn capture(config: &DemoConfig, theme: Theme, theme_name: String, width: u16, height: u16) -> Result<String, PokerError> {
+	let running = start(config)?;
+	let mut ui = demo_ui(theme, theme_name, config);
+	let mut terminal = Terminal::new(TestBackend::new(width, height)).map_err(|e| PokerError::Engine(e.to_string()))?;
+
+	let mut screens = Vec::new();
+	let mut hand_num = 0;
+	let mut ended = false;
+	while let Ok(event) = running.events.recv() {
+		ui.apply_event(&event);
+		let heading = match &event {
+			GameEvent::HandStarted { hand_num: num, .. } => {
+				hand_num = *num;
+				continue;
+			}
+			GameEvent::HandEnded { .. } => format!("== Hand {} ==", hand_num),
+			GameEvent::GameEnded { .. } => "== Game over ==".to_string(),
+			_ => continue,
+		};
+		terminal.draw(|f| ui.render(f, f.area())).map_err(|e| PokerError::Engine(e.to_string()))?;
+		screens.push(format!("{}\n{}", heading, screen_text(terminal.backend().buffer())));
+		ended = matches!(event, GameEvent::GameEnded { .. });
+	}
+	running.join();
+
+	if !ended {
+		return Err(PokerError::Engine("The demo game stopped before it ended".to_string()));
+	}
+	Ok(screens.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_capture_plays_to_the_end() {
+		let config = DemoConfig { hands: 3, players: 3, seed: Some(11), auto: true, ..DemoConfig::default() };
+		let text = capture(&config, Theme::default(), "default".into(), 120, 40).unwrap();
+		assert!(text.starts_with("== Hand 1 =="), "{}", &text[..80.min(text.len())]);
+		assert!(text.contains("== Game over =="));
+		assert!(text.lines().all(|line| line == line.trim_end()));
+	}
+
+	#[test]
+	fn test_rejects_a_table_nobody_could_sit_at() {
+		let config = DemoConfig { players: 1, ..DemoConfig::default() };
+		assert!(start(&config).is_err());
+	}
+
+	#[test]
+	fn test_seats_one_ai_per_strategy() {
+		let config = DemoConfig {
+			hands: 2,
+			seed: Some(3),
+			auto: true,
+			strategies: vec!["balanced".into(), "balanced".into()],
+			..DemoConfig::default()
+		};
+		let running = start(&config).unwrap();
+		let names = running
+			.events
+			.iter()
+			.find_map(|event| match event {
+				GameEvent::HandStarted { seats, .. } => Some(seats.iter().map(|s| s.name.clone()).collect::<Vec<_>>()),
+				_ => None,
+			})
+			.unwrap();
+		running.stop();
+		running.join();
+		assert_eq!(names, vec!["Balanced 1", "Balanced 2"]);
+
+		let config = DemoConfig { strategies: vec!["balanced".into(), "no such".into()], ..DemoConfig::default() };
+		let error = start(&config).err().unwrap();
+		assert!(error.to_string().contains("No strategy 'no such'"), "{}", error);
+	}
+}
@@ -11,13 +11,17 @@ pub struct EmbeddedServer {
 
 impl EmbeddedServer {
 	pub fn start() -> std::io::Result<Self> {
+		Self::start_with(GameServer::new())
+	}
+
+	/// Like `start`, for a server configured by the caller.
+	pub fn start_with(server: GameServer) -> std::io::Result<Self> {
 		let listener = TcpListener::bind("127.0.0.1:0")?;
 		let port = listener.local_addr()?.port();
 
 		let (ready_tx, ready_rx) = mpsc::channel();
 
 		let handle = thread::spawn(move || {
-			let server = GameServer::new();
 			ready_tx.send(()).ok();
 			server.run_with_listener(listener);
 		});
@@ -1,35 +1,29 @@
-use std::sync::{Arc, Mutex, MutexGuard, mpsc::Sender};
-use rs_poker::arena::{Agent, GameState, action::AgentAction};
-use rs_poker::arena::game_state::Round;
-use tokio::runtime::Handle;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 
-use crate::events::{
-	Card, GameEvent, PlayerAction, Position, RaiseOptions, Seat, Street, ValidActions,
-};
-use crate::players::{ActionRecord, GameSnapshot, PlayerPort, PlayerResponse, SeatSnapshot};
+use async_trait::async_trait;
+
+use crate::engine::hand::{Agent, HandState};
+use crate::engine::historian::EventSender;
+use crate::events::{GameEvent, PlayerAction, Position, Seat, ValidActions};
+use crate::players::{ActionRecord, GameSnapshot, PlayerPort, PlayerResponse, PotSnapshot, SeatSnapshot};
+
+/// How long a player who won uncalled has to decide whether to show.
+const SHOW_OR_MUCK_TIME: Duration = Duration::from_secs(5);
 
 fn lock_mutex<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
 	mutex.lock().unwrap_or_else(|e| e.into_inner())
 }
 
+/// Lets a `PlayerPort` sit in the dealer's seat list.
 pub struct PlayerAdapter {
 	port: Arc<dyn PlayerPort>,
 	seat: Seat,
 	player_idx: usize,
-	seat_map: Vec<Seat>,
-	hole_cards: Option<[Card; 2]>,
+	names: Arc<[String]>,
 	action_history: Arc<Mutex<Vec<ActionRecord>>>,
-	betting_structure: BettingStructure,
-	event_tx: Sender<GameEvent>,
-	max_raises_per_round: u32,
-	runtime_handle: Handle,
-}
-
-#[derive(Debug, Clone, Copy)]
-pub enum BettingStructure {
-	NoLimit,
-	PotLimit,
-	FixedLimit,
+	event_tx: EventSender,
+	time_limit: Option<Duration>,
 }
 
 impl PlayerAdapter {
@@ -37,312 +31,124 @@ impl PlayerAdapter {
 		port: Arc<dyn PlayerPort>,
 		seat: Seat,
 		player_idx: usize,
-		seat_map: Vec<Seat>,
-		betting_structure: BettingStructure,
+		names: Arc<[String]>,
 		action_history: Arc<Mutex<Vec<ActionRecord>>>,
-		event_tx: Sender<GameEvent>,
-		max_raises_per_round: u32,
-		runtime_handle: Handle,
+		event_tx: EventSender,
+		time_limit: Option<Duration>,
 	) -> Self {
 		Self {
 			port,
 			seat,
 			player_idx,
-			seat_map,
-			hole_cards: None,
+			names,
 			action_history,
-			betting_structure,
 			event_tx,
-			max_raises_per_round,
-			runtime_handle,
+			time_limit,
 		}
 	}
 
-	fn seat_at(&self, idx: usize) -> Seat {
-		self.seat_map.get(idx).copied().unwrap_or(Seat(idx))
-	}
-
-	fn build_snapshot(&self, game_state: &GameState) -> GameSnapshot {
-		let street = convert_round(game_state.round);
-		let board = game_state
-			.board
+	fn build_snapshot(&self, state: &HandState, valid_actions: &ValidActions) -> GameSnapshot {
+		let seats = state
+			.seats
 			.iter()
-			.map(convert_card)
+			.enumerate()
+			.map(|(i, s)| SeatSnapshot {
+				seat: Seat(i),
+				name: self.names.get(i).cloned().unwrap_or_default(),
+				stack: s.stack,
+				current_bet: s.street_bet,
+				total_bet: state.contribution(i),
+				is_active: s.dealt_in,
+				is_folded: s.dealt_in && s.folded,
+				is_all_in: s.in_hand() && s.all_in,
+				position: position_of(i, state),
+			})
 			.collect();
 
-		let seats = game_state
-			.stacks
-			.iter()
-			.enumerate()
-			.map(|(i, &stack)| {
-				let position = self.compute_position(i, game_state);
-				let is_active = game_state.player_active.get(i);
-				SeatSnapshot {
-					seat: self.seat_at(i),
-					name: String::new(),
-					stack,
-					current_bet: 0.0,
-					is_folded: !is_active,
-					is_all_in: stack <= 0.0 && is_active,
-					position,
-				}
+		let pots = state
+			.pots()
+			.into_iter()
+			.map(|p| PotSnapshot {
+				amount: p.amount,
+				eligible: p.eligible.into_iter().map(Seat).collect(),
 			})
 			.collect();
 
 		GameSnapshot {
-			hand_num: 0,
-			street,
-			board,
-			pot: game_state.total_pot,
+			hand_num: state.hand_num,
+			street: state.street,
+			board: state.board.clone(),
+			pot: state.pot(),
+			pots,
+			effective_stack: state.effective_stack(self.player_idx),
 			seats,
-			hero_cards: self.hole_cards,
+			hero_seat: self.seat,
+			hero_cards: state.seats.get(self.player_idx).and_then(|s| s.hole_cards),
+			button: Seat(state.positions.button),
+			blinds: state.blinds,
+			current_bet: state.current_bet,
+			min_raise: state.min_raise,
+			raises_this_street: state.raises_this_street,
+			max_raises: state.max_raises,
+			valid_actions: valid_actions.clone(),
 			action_history: lock_mutex(&self.action_history).clone(),
 		}
 	}
 
-	fn build_valid_actions(&self, game_state: &GameState) -> ValidActions {
-		let stack = game_state.stacks[self.player_idx];
-		let current_bet = game_state.current_round_bet();
-		let my_bet = game_state.current_round_current_player_bet();
-		let to_call = current_bet - my_bet;
-		let min_raise = game_state.current_round_min_raise();
-
-		let can_check = to_call <= 0.0;
-		let call_amount = if to_call > 0.0 {
-			Some(to_call.min(stack))
-		} else {
-			None
-		};
-
-		let current_street = convert_round(game_state.round);
-		let raises_this_round = self.count_raises_this_round(current_street);
-		let raise_cap_reached = raises_this_round >= self.max_raises_per_round;
-
-		let raise_options = if raise_cap_reached {
-			None
-		} else {
-			self.compute_raise_options(game_state, stack, current_bet, min_raise)
-		};
-
-		let all_in_amount = stack + my_bet;
-		let can_all_in = if stack <= 0.0 || raise_cap_reached {
-			false
-		} else {
-			match self.betting_structure {
-				BettingStructure::NoLimit => true,
-				BettingStructure::PotLimit => {
-					let pot = game_state.total_pot;
-					let pot_after_call = pot + to_call;
-					let max_bet_to = current_bet + pot_after_call;
-					all_in_amount <= max_bet_to
-				}
-				BettingStructure::FixedLimit => {
-					let bet_size = match game_state.round {
-						Round::Preflop | Round::Flop => game_state.big_blind,
-						_ => game_state.big_blind * 2.0,
-					};
-					let max_bet_to = current_bet + bet_size;
-					all_in_amount <= max_bet_to
-				}
-			}
-		};
-
-		ValidActions {
-			can_fold: to_call > 0.0,
-			can_check,
-			call_amount,
-			raise_options,
-			can_all_in,
-			all_in_amount,
-		}
-	}
-
-	fn count_raises_this_round(&self, current_street: Street) -> u32 {
-		lock_mutex(&self.action_history)
-			.iter()
-			.filter(|a| a.street == current_street)
-			.filter(|a| matches!(a.action, PlayerAction::Raise { .. } | PlayerAction::Bet { .. }))
-			.count() as u32
-	}
-
-	fn compute_raise_options(
-		&self,
-		game_state: &GameState,
-		stack: f32,
-		current_bet: f32,
-		min_raise: f32,
-	) -> Option<RaiseOptions> {
-		let my_bet = game_state.current_round_current_player_bet();
-		let can_afford_raise = stack > (current_bet - my_bet);
-
-		if !can_afford_raise {
-			return None;
-		}
-
-		match self.betting_structure {
-			BettingStructure::FixedLimit => {
-				let bet_size = match game_state.round {
-					Round::Preflop | Round::Flop => game_state.big_blind,
-					_ => game_state.big_blind * 2.0,
-				};
-				let raise_to = current_bet + bet_size;
-				if stack + my_bet >= raise_to {
-					Some(RaiseOptions::Fixed { amount: raise_to })
-				} else {
-					None
-				}
-			}
-			BettingStructure::PotLimit => {
-				let pot = game_state.total_pot;
-				let to_call = current_bet - my_bet;
-				let pot_after_call = pot + to_call;
-				let min_raise_to = current_bet + min_raise;
-				let max_raise_to = (current_bet + pot_after_call).min(stack + my_bet);
-
-				if max_raise_to >= min_raise_to {
-					Some(RaiseOptions::Variable {
-						min_raise: min_raise_to,
-						max_raise: max_raise_to,
-					})
-				} else {
-					None
-				}
-			}
-			BettingStructure::NoLimit => {
-				let min_raise_to = current_bet + min_raise;
-				let max_raise_to = stack + my_bet;
+	/// Announces the request to the table and waits for the port's answer.
+	async fn request(&self, state: &HandState, valid_actions: &ValidActions, time_limit: Option<Duration>) -> PlayerResponse {
+		let snapshot = self.build_snapshot(state, valid_actions);
 
-				if max_raise_to >= min_raise_to {
-					Some(RaiseOptions::Variable {
-						min_raise: min_raise_to,
-						max_raise: max_raise_to,
-					})
-				} else {
-					None
-				}
-			}
-		}
-	}
-
-	fn compute_position(&self, seat_idx: usize, game_state: &GameState) -> Position {
-		let btn = game_state.dealer_idx;
-		let n = game_state.stacks.len();
+		let _ = self.event_tx.send(GameEvent::ActionRequest {
+			seat: self.seat,
+			valid_actions: valid_actions.clone(),
+			time_limit: time_limit.map(|d| d.as_secs_f64().ceil() as u32),
+			at: None,
+		});
 
-		if seat_idx == btn {
-			Position::Button
-		} else if seat_idx == (btn + 1) % n {
-			Position::SmallBlind
-		} else if seat_idx == (btn + 2) % n {
-			Position::BigBlind
-		} else {
-			Position::None
-		}
+		self.port
+			.request_action_within(self.seat, valid_actions.clone(), &snapshot, time_limit)
+			.await
 	}
 
-	fn convert_response(&self, response: PlayerResponse, valid: &ValidActions) -> AgentAction {
+	fn convert_response(&self, response: PlayerResponse) -> PlayerAction {
 		match response {
-			PlayerResponse::Action(action) => self.convert_action(action, valid),
-			PlayerResponse::Admin(_) => {
-				if valid.can_check {
-					AgentAction::Call
-				} else {
-					AgentAction::Fold
-				}
-			}
-			PlayerResponse::Timeout => {
-				if valid.can_check {
-					AgentAction::Call
-				} else {
-					AgentAction::Fold
-				}
-			}
+			PlayerResponse::Action(action) => action,
+			PlayerResponse::Admin(_) | PlayerResponse::Timeout => PlayerAction::Timeout,
 		}
 	}
+}
 
-	fn convert_action(&self, action: PlayerAction, valid: &ValidActions) -> AgentAction {
-		match action {
-			PlayerAction::Fold => AgentAction::Fold,
-			PlayerAction::Check => AgentAction::Call,
-			PlayerAction::Call { .. } => AgentAction::Call,
-			PlayerAction::Bet { amount } | PlayerAction::Raise { amount } => {
-				AgentAction::Bet(amount)
-			}
-			PlayerAction::AllIn { .. } => AgentAction::AllIn,
-			PlayerAction::Timeout => {
-				if valid.can_check {
-					AgentAction::Call
-				} else {
-					AgentAction::Fold
-				}
-			}
-		}
+fn position_of(idx: usize, state: &HandState) -> Position {
+	let positions = &state.positions;
+	if idx == positions.button {
+		Position::Button
+	} else if Some(idx) == positions.small_blind_poster() {
+		Position::SmallBlind
+	} else if idx == positions.big_blind {
+		Position::BigBlind
+	} else {
+		Position::None
 	}
 }
 
+#[async_trait]
 impl Agent for PlayerAdapter {
-	fn act(&mut self, _id: u128, game_state: &GameState) -> AgentAction {
-		if self.hole_cards.is_none() {
-			if let Some(hand) = game_state.hands.get(self.player_idx) {
-				let cards: Vec<rs_poker::core::Card> = hand.iter().take(2).collect();
-				if cards.len() >= 2 {
-					self.hole_cards = Some([convert_card(&cards[0]), convert_card(&cards[1])]);
-				}
-			}
-		}
-
-		let snapshot = self.build_snapshot(game_state);
-		let valid_actions = self.build_valid_actions(game_state);
-
-		let _ = self.event_tx.send(GameEvent::ActionRequest {
-			seat: self.seat,
-			valid_actions: valid_actions.clone(),
-			time_limit: None,
-		});
-
-		let port = Arc::clone(&self.port);
-		let seat = self.seat;
-		let va = valid_actions.clone();
-
-		let response = self.runtime_handle.block_on(async move {
-			port.request_action(seat, va, &snapshot).await
-		});
-
-		self.convert_response(response, &valid_actions)
+	async fn act(&mut self, state: &HandState, valid_actions: &ValidActions) -> PlayerAction {
+		let response = self.request(state, valid_actions, self.time_limit).await;
+		self.convert_response(response)
 	}
-}
 
-fn convert_round(round: Round) -> Street {
-	match round {
-		Round::Preflop => Street::Preflop,
-		Round::Flop => Street::Flop,
-		Round::Turn => Street::Turn,
-		Round::River => Street::River,
-		Round::Showdown => Street::Showdown,
-		_ => Street::Preflop,
+	fn mucks_losers(&self) -> bool {
+		self.port.auto_muck()
 	}
-}
 
-pub fn convert_card(card: &rs_poker::core::Card) -> Card {
-	let rank = match card.value {
-		rs_poker::core::Value::Two => '2',
-		rs_poker::core::Value::Three => '3',
-		rs_poker::core::Value::Four => '4',
-		rs_poker::core::Value::Five => '5',
-		rs_poker::core::Value::Six => '6',
-		rs_poker::core::Value::Seven => '7',
-		rs_poker::core::Value::Eight => '8',
-		rs_poker::core::Value::Nine => '9',
-		rs_poker::core::Value::Ten => 'T',
-		rs_poker::core::Value::Jack => 'J',
-		rs_poker::core::Value::Queen => 'Q',
-		rs_poker::core::Value::King => 'K',
-		rs_poker::core::Value::Ace => 'A',
-	};
-	let suit = match card.suit {
-		rs_poker::core::Suit::Spade => 's',
-		rs_poker::core::Suit::Heart => 'h',
-		rs_poker::core::Suit::Diamond => 'd',
-		rs_poker::core::Suit::Club => 'c',
-	};
-	Card::new(rank, suit)
+	async fn shows_uncalled(&mut self, state: &HandState) -> bool {
+		if !self.port.asks_to_show() || self.port.auto_muck() {
+			return false;
+		}
+		let limit = self.time_limit.map_or(SHOW_OR_MUCK_TIME, |t| t.min(SHOW_OR_MUCK_TIME));
+		let response = self.request(state, &ValidActions::show_or_muck(), Some(limit)).await;
+		matches!(response, PlayerResponse::Action(PlayerAction::Show))
+	}
 }
@@ -0,0 +1,251 @@
+/// Button and blind seats for one hand, assigned with dead-button rules.
+///
+/// The big blind always moves forward exactly one active seat. The small
+/// blind goes to the seat that had the big blind last hand, and the button
+/// to the seat that had the small blind. Either may land on a seat whose
+/// player has busted or is sitting out, in which case it is "dead": a dead
+/// button still anchors postflop action and a dead small blind is simply
+/// not posted. Heads-up, the button always posts the small blind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlindPositions {
+	pub button: usize,
+	pub small_blind: usize,
+	pub big_blind: usize,
+	pub dead_button: bool,
+	pub dead_small_blind: bool,
+}
+
+impl BlindPositions {
+	/// Positions for the first hand, with the button on the first active
+	/// seat at or after `start`.
+	pub fn first_hand(start: usize, active: &[bool]) -> Option<Self> {
+		if active.iter().filter(|a| **a).count() < 2 {
+			return None;
+		}
+		let n = active.len();
+		let button = (0..n)
+			.map(|i| (start + i) % n)
+			.find(|&i| active[i])?;
+		Some(Self::live_from_button(button, active))
+	}
+
+	/// Positions for the hand after `self`, given who is dealt in now.
+	pub fn next_hand(&self, active: &[bool]) -> Option<Self> {
+		let active_count = active.iter().filter(|a| **a).count();
+		if active_count < 2 {
+			return None;
+		}
+
+		let big_blind = next_active_seat(active, self.big_blind)?;
+
+		if active_count == 2 {
+			// Heads-up: the other player takes the button and small blind.
+			let button = next_active_seat(active, big_blind)?;
+			return Some(Self {
+				button,
+				small_blind: button,
+				big_blind,
+				dead_button: false,
+				dead_small_blind: false,
+			});
+		}
+
+		let small_blind = self.big_blind;
+		let button = self.small_blind;
+
+		// Coming out of heads-up (or after players sit in between the old
+		// blinds) the carried-over seats can collide with the new big
+		// blind. Fall back to a fresh rotation from the old button.
+		if button == big_blind || button == small_blind {
+			let start = next_active_seat(active, self.button)?;
+			return Self::first_hand(start, active);
+		}
+
+		Some(Self {
+			button,
+			small_blind,
+			big_blind,
+			dead_button: !active[button],
+			dead_small_blind: !active[small_blind],
+		})
+	}
+
+	/// The seat that actually posts the small blind, if any.
+	pub fn small_blind_poster(&self) -> Option<usize> {
+		if self.dead_small_blind {
+			None
+		} else {
+			Some(self.small_blind)
+		}
+	}
+
+	fn live_from_button(button: usize, active: &[bool]) -> Self {
+		let active_count = active.iter().filter(|a| **a).count();
+		let (small_blind, big_blind) = if active_count == 2 {
+			let other = next_active_seat(active, button).unwrap_or(button);
+			(button, other)
+		} else {
+			let sb = next_active_seat(active, button).unwrap_or(button);
+			let bb = next_active_seat(active, sb).unwrap_or(sb);
+			(sb, bb)
+		};
+		Self {
+			button,
+			small_blind,
+			big_blind,
+			dead_button: false,
+			dead_small_blind: false,
+		}
+	}
+}
+
+/// First active seat strictly after `from`, wrapping around the table.
+pub fn next_active_seat(active: &[bool], from: usize) -> Option<usize> {
+	let n = active.len();
+	(1..=n)
+		.map(|i| (from + i) % n)
+		.find(|&i| active[i])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn seats(active: &[u8]) -> Vec<bool> {
+		active.iter().map(|&a| a == 1).collect()
+	}
+
+	#[test]
+	fn test_first_hand_three_handed() {
+		let pos = BlindPositions::first_hand(0, &seats(&[1, 1, 1])).unwrap();
+		assert_eq!((pos.button, pos.small_blind, pos.big_blind), (0, 1, 2));
+		assert!(!pos.dead_button && !pos.dead_small_blind);
+	}
+
+	#[test]
+	fn test_first_hand_skips_empty_seats() {
+		let pos = BlindPositions::first_hand(0, &seats(&[0, 1, 0, 1, 1])).unwrap();
+		assert_eq!((pos.button, pos.small_blind, pos.big_blind), (1, 3, 4));
+	}
+
+	#[test]
+	fn test_first_hand_heads_up_button_posts_small() {
+		let pos = BlindPositions::first_hand(0, &seats(&[1, 0, 1])).unwrap();
+		assert_eq!((pos.button, pos.small_blind, pos.big_blind), (0, 0, 2));
+		assert_eq!(pos.button, pos.small_blind);
+	}
+
+	#[test]
+	fn test_normal_rotation() {
+		let active = seats(&[1, 1, 1, 1]);
+		let pos = BlindPositions::first_hand(0, &active).unwrap();
+		let next = pos.next_hand(&active).unwrap();
+		assert_eq!((next.button, next.small_blind, next.big_blind), (1, 2, 3));
+	}
+
+	#[test]
+	fn test_big_blind_busts_gives_dead_small_blind() {
+		// Button 0, SB 1, BB 2; seat 2 busts.
+		let pos = BlindPositions::first_hand(0, &seats(&[1, 1, 1, 1])).unwrap();
+		let next = pos.next_hand(&seats(&[1, 1, 0, 1])).unwrap();
+		assert_eq!(next.button, 1);
+		assert_eq!(next.small_blind, 2);
+		assert!(next.dead_small_blind);
+		assert_eq!(next.small_blind_poster(), None);
+		assert_eq!(next.big_blind, 3);
+	}
+
+	#[test]
+	fn test_small_blind_busts_gives_dead_button() {
+		// Button 0, SB 1, BB 2; seat 1 busts.
+		let pos = BlindPositions::first_hand(0, &seats(&[1, 1, 1, 1])).unwrap();
+		let next = pos.next_hand(&seats(&[1, 0, 1, 1])).unwrap();
+		assert_eq!(next.button, 1);
+		assert!(next.dead_button);
+		assert_eq!(next.small_blind_poster(), Some(2));
+		assert_eq!(next.big_blind, 3);
+	}
+
+	#[test]
+	fn test_multi_elimination_in_one_hand() {
+		// Button 0, SB 1, BB 2 at a five-handed table; SB and BB both bust.
+		let pos = BlindPositions::first_hand(0, &seats(&[1, 1, 1, 1, 1])).unwrap();
+		let next = pos.next_hand(&seats(&[1, 0, 0, 1, 1])).unwrap();
+		assert_eq!(next.button, 1);
+		assert!(next.dead_button);
+		assert_eq!(next.small_blind, 2);
+		assert!(next.dead_small_blind);
+		assert_eq!(next.big_blind, 3);
+
+		// The following hand is back to normal.
+		let after = next.next_hand(&seats(&[1, 0, 0, 1, 1])).unwrap();
+		assert_eq!(after.button, 2);
+		assert!(after.dead_button);
+		assert_eq!(after.small_blind_poster(), Some(3));
+		assert_eq!(after.big_blind, 4);
+	}
+
+	#[test]
+	fn test_big_blind_never_skips_a_player() {
+		let mut active = seats(&[1, 1, 1, 1, 1, 1]);
+		let mut pos = BlindPositions::first_hand(0, &active).unwrap();
+		active[3] = false;
+		active[4] = false;
+		let mut big_blinds = vec![pos.big_blind];
+		for _ in 0..6 {
+			pos = pos.next_hand(&active).unwrap();
+			big_blinds.push(pos.big_blind);
+		}
+		assert_eq!(big_blinds, vec![2, 5, 0, 1, 2, 5, 0]);
+	}
+
+	#[test]
+	fn test_transition_to_heads_up() {
+		// Button 0, SB 1, BB 2; seat 0 busts leaving two players.
+		let pos = BlindPositions::first_hand(0, &seats(&[1, 1, 1])).unwrap();
+		let next = pos.next_hand(&seats(&[0, 1, 1])).unwrap();
+		// The old big blind must not post it again.
+		assert_eq!(next.big_blind, 1);
+		assert_eq!(next.button, 2);
+		assert_eq!(next.small_blind_poster(), Some(2));
+		assert_eq!(next.button, next.small_blind);
+	}
+
+	#[test]
+	fn test_transition_to_heads_up_when_big_blind_busts() {
+		// Button 0, SB 1, BB 2; seat 2 busts leaving two players.
+		let pos = BlindPositions::first_hand(0, &seats(&[1, 1, 1])).unwrap();
+		let next = pos.next_hand(&seats(&[1, 1, 0])).unwrap();
+		assert_eq!(next.big_blind, 0);
+		assert_eq!(next.button, 1);
+		assert_eq!(next.button, next.small_blind);
+	}
+
+	#[test]
+	fn test_heads_up_alternates() {
+		let active = seats(&[1, 1]);
+		let pos = BlindPositions::first_hand(0, &active).unwrap();
+		let next = pos.next_hand(&active).unwrap();
+		let after = next.next_hand(&active).unwrap();
+		assert_eq!((pos.button, pos.big_blind), (0, 1));
+		assert_eq!((next.button, next.big_blind), (1, 0));
+		assert_eq!((after.button, after.big_blind), (0, 1));
+	}
+
+	#[test]
+	fn test_back_from_heads_up() {
+		// Heads-up between 0 (button) and 2 (BB); a player sits in at seat 1.
+		let pos = BlindPositions::first_hand(0, &seats(&[1, 0, 1])).unwrap();
+		let next = pos.next_hand(&seats(&[1, 1, 1])).unwrap();
+		assert_ne!(next.button, next.big_blind);
+		assert_ne!(next.small_blind, next.big_blind);
+		assert_ne!(next.button, next.small_blind);
+	}
+
+	#[test]
+	fn test_single_player_has_no_positions() {
+		assert!(BlindPositions::first_hand(0, &seats(&[0, 1, 0])).is_none());
+		let pos = BlindPositions::first_hand(0, &seats(&[1, 1, 1])).unwrap();
+		assert!(pos.next_hand(&seats(&[0, 0, 1])).is_none());
+	}
+}
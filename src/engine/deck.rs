@@ -0,0 +1,124 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::bank::audit::fnv1a;
+use crate::events::Card;
+
+const RANKS: [char; 13] = ['2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A'];
+const SUITS: [char; 4] = ['s', 'h', 'd', 'c'];
+
+pub struct Deck {
+	cards: Vec<Card>,
+}
+
+impl Deck {
+	pub fn shuffled<R: Rng + ?Sized>(rng: &mut R) -> Self {
+		let mut cards: Vec<Card> = SUITS
+			.iter()
+			.flat_map(|&suit| RANKS.iter().map(move |&rank| Card::new(rank, suit)))
+			.collect();
+		cards.shuffle(rng);
+		Self { cards }
+	}
+
+	/// A deck that deals `top` first, in order, then the rest shuffled.
+	/// `None` leaves that card to chance, for burns and anything else the
+	/// caller doesn't care about.
+	pub fn stacked<R: Rng + ?Sized>(top: &[Option<Card>], rng: &mut R) -> Self {
+		let mut cards = Self::shuffled(rng).cards;
+		cards.retain(|card| !top.contains(&Some(*card)));
+		let mut first: Vec<Card> = top
+			.iter()
+			.map(|card| card.unwrap_or_else(|| cards.pop().expect("deck exhausted")))
+			.collect();
+		// Dealing pops from the end
+		first.reverse();
+		cards.extend(first);
+		Self { cards }
+	}
+
+	/// A deck that deals `cards` in order, as `order` gave them.
+	pub fn in_order(mut cards: Vec<Card>) -> Self {
+		cards.reverse();
+		Self { cards }
+	}
+
+	/// The cards still to come, in the order they'll be dealt.
+	pub fn order(&self) -> Vec<Card> {
+		self.cards.iter().rev().copied().collect()
+	}
+
+	/// FNV-1a over the cards still to come, in the order they'll be
+	/// dealt, as 16 hex digits. The table is shown it before the deal,
+	/// and the shuffle report keeps it.
+	pub fn hash(&self) -> String {
+		let order = self.order().iter().map(|c| format!("{}{}", c.rank, c.suit)).collect::<String>();
+		format!("{:016x}", fnv1a(order.as_bytes()))
+	}
+
+	pub fn deal(&mut self) -> Card {
+		self.cards.pop().expect("deck exhausted")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::SeedableRng;
+	use rand::rngs::StdRng;
+	use std::collections::HashSet;
+
+	#[test]
+	fn test_deck_has_52_unique_cards() {
+		let mut rng = StdRng::seed_from_u64(1);
+		let mut deck = Deck::shuffled(&mut rng);
+		let mut seen = HashSet::new();
+		for _ in 0..52 {
+			let card = deck.deal();
+			assert!(seen.insert((card.rank, card.suit)));
+		}
+		assert_eq!(seen.len(), 52);
+	}
+
+	#[test]
+	fn test_same_seed_same_order() {
+		let mut a = Deck::shuffled(&mut StdRng::seed_from_u64(7));
+		let mut b = Deck::shuffled(&mut StdRng::seed_from_u64(7));
+		for _ in 0..10 {
+			assert_eq!(a.deal(), b.deal());
+		}
+	}
+
+	#[test]
+	fn test_hash_follows_the_order() {
+		let deck = Deck::shuffled(&mut StdRng::seed_from_u64(7));
+		assert_eq!(deck.hash().len(), 16);
+		assert_eq!(deck.hash(), Deck::in_order(deck.order()).hash());
+		assert_ne!(deck.hash(), Deck::shuffled(&mut StdRng::seed_from_u64(8)).hash());
+	}
+
+	#[test]
+	fn test_order_replays_the_deck() {
+		let deck = Deck::shuffled(&mut StdRng::seed_from_u64(9));
+		let order = deck.order();
+		let mut replay = Deck::in_order(order.clone());
+		let mut original = deck;
+		for card in order {
+			assert_eq!(replay.deal(), card);
+			assert_eq!(original.deal(), card);
+		}
+	}
+
+	#[test]
+	fn test_stacked_deck_deals_top_cards_first() {
+		let ace = Card::new('A', 's');
+		let king = Card::new('K', 'h');
+		let mut deck = Deck::stacked(&[Some(ace), None, Some(king)], &mut StdRng::seed_from_u64(4));
+		assert_eq!(deck.deal(), ace);
+		let random = deck.deal();
+		assert!(random != ace && random != king);
+		assert_eq!(deck.deal(), king);
+		let rest: Vec<Card> = (0..49).map(|_| deck.deal()).collect();
+		assert!(!rest.contains(&ace) && !rest.contains(&king) && !rest.contains(&random));
+	}
+}
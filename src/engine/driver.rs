@@ -0,0 +1,322 @@
+//! What `GameRunner` and `Session` have in common: the seats and their
+//! stacks, and each hand from the button moving to the pot being awarded.
+//! `start_hand` sets a hand up and hands it back as a future; the runner
+//! blocks on it on its tokio runtime, the session polls it a decision at
+//! a time. Everything between hands (pauses, arrivals, cashing out, the
+//! blind clock) is left to them.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use crate::engine::adapter::PlayerAdapter;
+use crate::engine::blinds::BlindPositions;
+use crate::engine::deck::Deck;
+use crate::engine::hand::{Agent, Dealer, HandConfig, HandOutcome};
+use crate::engine::historian::{EventHistorian, EventSender};
+use crate::engine::pot::RakeConfig;
+use crate::engine::validator::{ActionValidator, BettingStructure};
+use crate::events::{
+	Blinds, ChatSender, GameEndReason, GameEvent, GameId, HandId, HandResult, Position, Seat, SeatInfo, Standing,
+};
+use crate::history::hand_ref;
+use crate::logging;
+use crate::players::{ActionRecord, PlayerPort};
+use crate::promotions::JackpotConfig;
+
+fn lock_mutex<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+	mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The rules every hand of a game is dealt under.
+pub struct HandRules {
+	pub max_raises_per_round: u32,
+	pub rake: RakeConfig,
+	pub jackpot: Option<JackpotConfig>,
+	pub action_timeout: Option<Duration>,
+}
+
+type HandFuture = Pin<Box<dyn Future<Output = HandOutcome>>>;
+
+/// A hand that's been set up, to be played out by awaiting `future`.
+pub struct HandInPlay {
+	pub hand_id: HandId,
+	pub future: HandFuture,
+	big_blind: f32,
+	stacks_before: Vec<f32>,
+}
+
+pub struct HandDriver {
+	/// Stamped on each hand and named in its chat line; a session has none.
+	game_id: Option<GameId>,
+	rules: HandRules,
+	/// By seat; fixed-seat games keep their empty seats as `None`.
+	pub players: Vec<Option<Arc<dyn PlayerPort>>>,
+	pub stacks: Vec<f32>,
+	pub sitting_out: Arc<Mutex<HashSet<Seat>>>,
+	pub hand_num: u32,
+	pub rng: StdRng,
+	pub event_tx: EventSender,
+	positions: Option<BlindPositions>,
+	action_history: Arc<Mutex<Vec<ActionRecord>>>,
+}
+
+impl HandDriver {
+	pub fn new(game_id: Option<GameId>, rules: HandRules, rng: StdRng, event_tx: EventSender) -> Self {
+		Self {
+			game_id,
+			rules,
+			players: Vec::new(),
+			stacks: Vec::new(),
+			sitting_out: Arc::default(),
+			hand_num: 0,
+			rng,
+			event_tx,
+			positions: None,
+			action_history: Arc::new(Mutex::new(Vec::new())),
+		}
+	}
+
+	/// `seats` empty seats, for a game that keeps players where they sit.
+	#[cfg(feature = "runtime")]
+	pub fn with_seats(mut self, seats: usize) -> Self {
+		self.players = vec![None; seats];
+		self.stacks = vec![0.0; seats];
+		self
+	}
+
+	#[cfg(feature = "runtime")]
+	pub fn with_sitting_out(mut self, sitting_out: Arc<Mutex<HashSet<Seat>>>) -> Self {
+		self.sitting_out = sitting_out;
+		self
+	}
+
+	pub fn emit(&self, event: GameEvent) {
+		let _ = self.event_tx.send(event);
+	}
+
+	/// Sits `player` down with `stack` in `slot`, or in a new seat at the
+	/// end if `slot` is past the last one.
+	pub fn sit(&mut self, slot: usize, player: Arc<dyn PlayerPort>, stack: f32) {
+		self.emit(GameEvent::PlayerJoined {
+			seat: player.seat(),
+			name: player.name().to_string(),
+			stack,
+			is_human: player.is_human(),
+		});
+		if slot < self.players.len() {
+			self.players[slot] = Some(player);
+			self.stacks[slot] = stack;
+		} else {
+			self.players.push(Some(player));
+			self.stacks.push(stack);
+		}
+	}
+
+	pub fn occupied(&self) -> usize {
+		self.players.iter().flatten().count()
+	}
+
+	pub fn start_game(&self) {
+		self.emit(GameEvent::GameStarted { seats: self.seat_infos(None) });
+	}
+
+	/// Seats that would be dealt in: occupied, with chips, not sitting out.
+	pub fn active_seats(&self) -> Vec<bool> {
+		let sitting_out = lock_mutex(&self.sitting_out);
+		self.players
+			.iter()
+			.enumerate()
+			.map(|(i, p)| p.is_some() && self.stacks[i] > 0.0 && !sitting_out.contains(&Seat(i)))
+			.collect()
+	}
+
+	/// Moves the button on and sets up the next hand at these blinds,
+	/// unless fewer than two seats can be dealt in. `wrap` gets each
+	/// seat's agent to wrap as it likes, and `deal` the deck.
+	pub fn start_hand(
+		&mut self,
+		(small_blind, big_blind): (f32, f32),
+		betting: BettingStructure,
+		wrap: impl Fn(Box<dyn Agent>, Seat, &Arc<[String]>, &EventSender) -> Box<dyn Agent>,
+		deal: impl FnOnce(&BlindPositions, &[bool], &mut StdRng) -> Deck,
+	) -> Option<HandInPlay> {
+		let active = self.active_seats();
+		if active.iter().filter(|a| **a).count() < 2 {
+			return None;
+		}
+		let pos = match self.positions {
+			None => BlindPositions::first_hand(0, &active),
+			Some(prev) => prev.next_hand(&active),
+		}?;
+		self.positions = Some(pos);
+		self.hand_num += 1;
+		lock_mutex(&self.action_history).clear();
+		let hand_id = HandId(self.rng.random());
+
+		let event = GameEvent::HandStarted {
+			hand_id,
+			hand_num: self.hand_num,
+			button: Seat(pos.button),
+			blinds: Blinds { small: small_blind, big: big_blind, ante: None },
+			seats: self.seat_infos(Some(&pos)),
+			at: None,
+			game_id: self.game_id,
+		};
+		self.emit(event.clone());
+		if let Some(game_id) = self.game_id {
+			self.emit(GameEvent::ChatMessage {
+				sender: ChatSender::Dealer,
+				text: format!("Hand {}", hand_ref(game_id, self.hand_num)),
+			});
+		}
+		logging::engine::hand_started(pos.button, self.players.len());
+		for player in self.players.iter().flatten() {
+			player.notify(&event);
+		}
+
+		let names: Arc<[String]> = self
+			.players
+			.iter()
+			.map(|p| p.as_ref().map(|p| p.name().to_string()).unwrap_or_default())
+			.collect();
+		let mut agents: Vec<Option<Box<dyn Agent>>> = self
+			.players
+			.iter()
+			.enumerate()
+			.map(|(i, p)| match p {
+				Some(p) if active[i] => {
+					let adapter = PlayerAdapter::new(
+						Arc::clone(p),
+						Seat(i),
+						i,
+						Arc::clone(&names),
+						Arc::clone(&self.action_history),
+						self.event_tx.clone(),
+						self.rules.action_timeout,
+					);
+					Some(wrap(Box::new(adapter), Seat(i), &names, &self.event_tx))
+				}
+				_ => None,
+			})
+			.collect();
+
+		let hand_config = HandConfig {
+			hand_num: self.hand_num,
+			small_blind,
+			big_blind,
+			validator: ActionValidator::new(betting, self.rules.max_raises_per_round),
+			rake: self.rules.rake.clone(),
+			jackpot: self.rules.jackpot.clone(),
+		};
+		let historian = EventHistorian::new(self.event_tx.clone(), Arc::clone(&self.action_history));
+		let deck = deal(&pos, &active, &mut self.rng);
+		self.emit(GameEvent::DeckShuffled { hand_id, hash: deck.hash() });
+
+		let stacks = self.stacks.clone();
+		let future: HandFuture = Box::pin(async move {
+			let dealer = Dealer::new(hand_config, pos, &stacks, &active, deck, &historian);
+			dealer.play(&mut agents).await
+		});
+		Some(HandInPlay { hand_id, future, big_blind, stacks_before: self.stacks.clone() })
+	}
+
+	/// Pays out a played hand: the new stacks, `HandEnded`, and each
+	/// player's look back at it.
+	pub fn finish_hand(&mut self, hand: &HandInPlay, outcome: HandOutcome) {
+		let recap = outcome.recap(self.hand_num, hand.big_blind, &hand.stacks_before);
+		self.stacks = outcome.stacks.clone();
+		let results = self
+			.players
+			.iter()
+			.enumerate()
+			.filter_map(|(i, p)| {
+				p.as_ref().map(|p| HandResult {
+					seat: Seat(i),
+					stack_change: self.stacks[i] - hand.stacks_before[i],
+					final_stack: self.stacks[i],
+					showed_cards: if outcome.folded[i] || outcome.mucked[i] { None } else { outcome.hole_cards[i] },
+					hand_description: outcome.made_hand(i),
+					tilt: p.tilt(),
+				})
+			})
+			.collect();
+		self.emit(GameEvent::HandEnded { hand_id: hand.hand_id, results, at: None });
+
+		for player in self.players.iter().flatten() {
+			player.hand_over(&recap);
+		}
+		for (i, player) in self.players.iter().enumerate() {
+			if let Some(text) = player.as_ref().and_then(|p| p.table_talk(&recap)) {
+				self.emit(GameEvent::ChatMessage { sender: ChatSender::Player(Seat(i)), text });
+			}
+		}
+	}
+
+	/// Ends the game, ranking everyone seated by their stack.
+	pub fn end_game(&self, reason: GameEndReason, detail: Option<String>) {
+		let mut standings: Vec<Standing> = self
+			.players
+			.iter()
+			.enumerate()
+			.filter_map(|(i, p)| {
+				p.as_ref().map(|p| Standing {
+					seat: Seat(i),
+					name: p.name().to_string(),
+					final_stack: self.stacks[i],
+					finish_position: 0,
+				})
+			})
+			.collect();
+		standings.sort_by(|a, b| b.final_stack.partial_cmp(&a.final_stack).unwrap_or(std::cmp::Ordering::Equal));
+		for (i, s) in standings.iter_mut().enumerate() {
+			s.finish_position = (i + 1) as u8;
+		}
+		self.emit(GameEvent::GameEnded { reason, final_standings: standings, detail });
+	}
+
+	fn seat_infos(&self, pos: Option<&BlindPositions>) -> Vec<SeatInfo> {
+		let sitting_out = lock_mutex(&self.sitting_out);
+		self.players
+			.iter()
+			.enumerate()
+			.map(|(i, p)| {
+				let seat = Seat(i);
+				match p {
+					Some(p) => {
+						let is_active = self.stacks[i] > 0.0 && !sitting_out.contains(&seat);
+						let position = match pos {
+							Some(pos) if is_active && i == pos.button => Position::Button,
+							Some(pos) if is_active && Some(i) == pos.small_blind_poster() => Position::SmallBlind,
+							Some(pos) if is_active && i == pos.big_blind => Position::BigBlind,
+							_ => Position::None,
+						};
+						SeatInfo {
+							seat,
+							name: p.name().to_string(),
+							stack: self.stacks[i],
+							position,
+							is_active,
+							is_human: p.is_human(),
+							is_occupied: true,
+						}
+					}
+					None => SeatInfo {
+						seat,
+						name: String::new(),
+						stack: 0.0,
+						position: Position::None,
+						is_active: false,
+						is_human: false,
+						is_occupied: false,
+					},
+				}
+			})
+			.collect()
+	}
+}
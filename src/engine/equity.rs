@@ -0,0 +1,305 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::index;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::eval_cache::rank_hand_cached;
+use crate::events::{Card, GameEvent, PlayerAction, Seat, Street};
+
+const RANKS: [char; 13] = ['2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A'];
+const SUITS: [char; 4] = ['s', 'h', 'd', 'c'];
+
+/// Runouts sampled when there are too many to enumerate (before the flop).
+const SAMPLES: usize = 3000;
+
+/// Each hand's share of the pot if the board were run out now, ties split.
+/// Exact from the flop on; sampled with a fixed seed before it, so the same
+/// spot always shows the same numbers.
+pub fn equities(hands: &[[Card; 2]], board: &[Card]) -> Vec<f32> {
+	let mut shares = vec![0.0; hands.len()];
+	if hands.is_empty() || board.len() > 5 {
+		return shares;
+	}
+
+	let dead: Vec<Card> = hands.iter().flatten().chain(board.iter()).copied().collect();
+	let stub: Vec<Card> = SUITS
+		.iter()
+		.flat_map(|&suit| RANKS.iter().map(move |&rank| Card::new(rank, suit)))
+		.filter(|c| !dead.contains(c))
+		.collect();
+
+	let missing = 5 - board.len();
+	let mut runouts = 0;
+	let mut score = |runout: &[Card]| {
+		let full: Vec<Card> = board.iter().chain(runout.iter()).copied().collect();
+		let ranks: Vec<_> = hands.iter().map(|h| rank_hand_cached(h, &full)).collect();
+		let best = ranks.iter().max().expect("at least one hand");
+		let winners: Vec<usize> = (0..hands.len()).filter(|&i| &ranks[i] == best).collect();
+		for &i in &winners {
+			shares[i] += 1.0 / winners.len() as f32;
+		}
+		runouts += 1;
+	};
+
+	match missing {
+		0 => score(&[]),
+		1 => {
+			for &a in &stub {
+				score(&[a]);
+			}
+		}
+		2 => {
+			for (i, &a) in stub.iter().enumerate() {
+				for &b in &stub[i + 1..] {
+					score(&[a, b]);
+				}
+			}
+		}
+		_ => {
+			let mut rng = StdRng::seed_from_u64(0);
+			for _ in 0..SAMPLES {
+				let runout: Vec<Card> = index::sample(&mut rng, stub.len(), missing)
+					.into_iter()
+					.map(|i| stub[i])
+					.collect();
+				score(&runout);
+			}
+		}
+	}
+
+	for share in &mut shares {
+		*share /= runouts as f32;
+	}
+	shares
+}
+
+/// A hand's share of the pot against `opponents` hands nobody has seen,
+/// each dealt at random, and the board run out. Sampled with a fixed seed,
+/// so it's an estimate, but the same spot always gives the same one.
+pub fn equity_against_random(hole: &[Card; 2], board: &[Card], opponents: usize) -> f32 {
+	let missing = 5usize.saturating_sub(board.len());
+	let dead: Vec<Card> = hole.iter().chain(board.iter()).copied().collect();
+	let stub: Vec<Card> = SUITS
+		.iter()
+		.flat_map(|&suit| RANKS.iter().map(move |&rank| Card::new(rank, suit)))
+		.filter(|c| !dead.contains(c))
+		.collect();
+	let drawn = 2 * opponents + missing;
+	if board.len() > 5 || drawn > stub.len() {
+		return 0.0;
+	}
+
+	let mut rng = StdRng::seed_from_u64(0);
+	let mut share = 0.0;
+	let mut full = board.to_vec();
+	for _ in 0..SAMPLES {
+		let picked: Vec<Card> = index::sample(&mut rng, stub.len(), drawn)
+			.into_iter()
+			.map(|i| stub[i])
+			.collect();
+		let (theirs, runout) = picked.split_at(2 * opponents);
+		full.truncate(board.len());
+		full.extend_from_slice(runout);
+		let ours = rank_hand_cached(hole, &full);
+		let mut tied = 1;
+		let beaten = theirs.chunks(2).any(|h| {
+			let rank = rank_hand_cached(&[h[0], h[1]], &full);
+			tied += (rank == ours) as usize;
+			rank > ours
+		});
+		if !beaten {
+			share += 1.0 / tied as f32;
+		}
+	}
+	share / SAMPLES as f32
+}
+
+/// How a finished hand's fortunes swung: each player's equity as each
+/// street was dealt, worked out afterwards from every hole card that came
+/// to light.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct EquityGraph {
+	/// Players whose cards are known, in seat order.
+	pub seats: Vec<Seat>,
+	pub streets: Vec<StreetEquity>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct StreetEquity {
+	pub street: Street,
+	/// One per entry in `EquityGraph::seats`; 0 once a player has folded.
+	pub equities: Vec<f32>,
+}
+
+/// Bar width in `EquityGraph::lines`.
+const BAR_WIDTH: usize = 10;
+
+impl EquityGraph {
+	/// The graph for one hand's events, from `HandStarted` on. Only hands
+	/// that reach a showdown have one. Players whose cards never showed
+	/// are left out.
+	pub fn from_hand(events: &[GameEvent]) -> Option<Self> {
+		let mut holes: Vec<(Seat, [Card; 2])> = Vec::new();
+		let mut folded: Vec<Seat> = Vec::new();
+		// Who was still in, and the board, as each street began.
+		let mut streets: Vec<(Street, Vec<Seat>, Vec<Card>)> = vec![(Street::Preflop, Vec::new(), Vec::new())];
+		let mut showdown = false;
+		// A winner who shows once the pot is pushed won it uncalled
+		let mut awarded = false;
+		for event in events {
+			match event {
+				GameEvent::HoleCardsDealt { seat, cards } => holes.push((*seat, *cards)),
+				GameEvent::PotAwarded { .. } => awarded = true,
+				GameEvent::ShowdownReveal { reveals } if !awarded => {
+					showdown = true;
+					for (seat, cards) in reveals {
+						if !holes.iter().any(|(s, _)| s == seat) {
+							holes.push((*seat, *cards));
+						}
+					}
+				}
+				GameEvent::ActionTaken { seat, action: PlayerAction::Fold, .. } => folded.push(*seat),
+				GameEvent::StreetChanged { street, board, .. } if *street != Street::Showdown => {
+					streets.push((*street, folded.clone(), board.clone()));
+				}
+				_ => {}
+			}
+		}
+		if !showdown {
+			return None;
+		}
+		holes.sort_by_key(|(seat, _)| seat.0);
+		let seats: Vec<Seat> = holes.iter().map(|(seat, _)| *seat).collect();
+		if seats.len() < 2 {
+			return None;
+		}
+
+		let streets = streets
+			.into_iter()
+			.map(|(street, out, board)| {
+				let live: Vec<usize> = (0..holes.len()).filter(|&i| !out.contains(&holes[i].0)).collect();
+				let hands: Vec<[Card; 2]> = live.iter().map(|&i| holes[i].1).collect();
+				let mut row = vec![0.0; holes.len()];
+				for (&i, equity) in live.iter().zip(equities(&hands, &board)) {
+					row[i] = equity;
+				}
+				StreetEquity { street, equities: row }
+			})
+			.collect();
+		Some(Self { seats, streets })
+	}
+
+	/// The graph as text, a row of bars per player:
+	/// `Alice  ███████░░░ 68%  ██░░░░░░░░ 21%`, under a header naming the
+	/// streets.
+	pub fn lines(&self, name: impl Fn(Seat) -> String) -> Vec<String> {
+		let names: Vec<String> = self.seats.iter().map(|&seat| name(seat)).collect();
+		let width = names.iter().map(|n| n.chars().count()).max().unwrap_or(0);
+		let column = BAR_WIDTH + 5;
+		let mut header = format!("{:width$}", "", width = width);
+		for street in &self.streets {
+			header.push_str(&format!("  {:<column$}", format!("{:?}", street.street), column = column));
+		}
+		let mut lines = vec![header.trim_end().to_string()];
+		for (i, name) in names.iter().enumerate() {
+			let mut line = format!("{:<width$}", name, width = width);
+			for street in &self.streets {
+				let equity = street.equities[i];
+				let filled = (equity * BAR_WIDTH as f32).round() as usize;
+				line.push_str(&format!(
+					"  {}{} {:>3.0}%",
+					"█".repeat(filled),
+					"░".repeat(BAR_WIDTH - filled),
+					equity * 100.0
+				));
+			}
+			lines.push(line);
+		}
+		lines
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::cards;
+
+	fn hole(s: &str) -> [Card; 2] {
+		let c = cards(s);
+		[c[0], c[1]]
+	}
+
+	#[test]
+	fn test_river_is_all_or_nothing() {
+		let board = cards("2h 7h 9h Td Jc");
+		let eq = equities(&[hole("Ah 3h"), hole("8s Qd")], &board);
+		assert_eq!(eq, vec![1.0, 0.0]);
+	}
+
+	#[test]
+	fn test_chopped_board_splits() {
+		let board = cards("As Ks Qs Js Ts");
+		let eq = equities(&[hole("2c 3d"), hole("4c 5d")], &board);
+		assert_eq!(eq, vec![0.5, 0.5]);
+	}
+
+	#[test]
+	fn test_turn_counts_outs() {
+		// Nine hearts left in 44 unseen cards make the flush
+		let board = cards("2h 7h Kc 9s");
+		let eq = equities(&[hole("8h 3h"), hole("Qs Qd")], &board);
+		assert!((eq[0] - 9.0 / 44.0).abs() < 1e-4, "{:?}", eq);
+		assert!((eq[0] + eq[1] - 1.0).abs() < 1e-4);
+	}
+
+	#[test]
+	fn test_graph_follows_the_hand_street_by_street() {
+		let events = vec![
+			GameEvent::HoleCardsDealt { seat: Seat(0), cards: hole("As Ah") },
+			GameEvent::HoleCardsDealt { seat: Seat(1), cards: hole("Kd Kc") },
+			GameEvent::HoleCardsDealt { seat: Seat(2), cards: hole("7c 2d") },
+			GameEvent::ActionTaken { seat: Seat(2), action: PlayerAction::Fold, stack_after: 100.0, pot_after: 15.0, at: None },
+			GameEvent::StreetChanged { street: Street::Flop, board: cards("Ks 8h 3c"), pot: 15.0, effective_stack: 90.0, pots: Vec::new() },
+			GameEvent::StreetChanged { street: Street::Turn, board: cards("Ks 8h 3c 9d"), pot: 15.0, effective_stack: 90.0, pots: Vec::new() },
+			GameEvent::StreetChanged { street: Street::River, board: cards("Ks 8h 3c 9d 2s"), pot: 15.0, effective_stack: 90.0, pots: Vec::new() },
+			GameEvent::StreetChanged { street: Street::Showdown, board: cards("Ks 8h 3c 9d 2s"), pot: 15.0, effective_stack: 90.0, pots: Vec::new() },
+			GameEvent::ShowdownReveal { reveals: vec![(Seat(0), hole("As Ah")), (Seat(1), hole("Kd Kc"))] },
+		];
+		let graph = EquityGraph::from_hand(&events).unwrap();
+		assert_eq!(graph.seats, vec![Seat(0), Seat(1), Seat(2)]);
+		let streets: Vec<Street> = graph.streets.iter().map(|s| s.street).collect();
+		assert_eq!(streets, vec![Street::Preflop, Street::Flop, Street::Turn, Street::River]);
+		assert!(graph.streets[0].equities[0] > graph.streets[0].equities[1]);
+		assert!(graph.streets[0].equities[2] > 0.0);
+		assert_eq!(graph.streets[1].equities[2], 0.0);
+		assert!(graph.streets[1].equities[1] > 0.9);
+		assert_eq!(graph.streets[3].equities, vec![0.0, 1.0, 0.0]);
+
+		let lines = graph.lines(|seat| ["Alice", "Bob", "Cy"][seat.0].to_string());
+		assert!(lines[0].starts_with("       Preflop"));
+		assert!(lines[2].starts_with("Bob  "));
+		assert!(lines[2].ends_with("██████████ 100%"));
+
+		assert_eq!(EquityGraph::from_hand(&events[..8]), None);
+	}
+
+	#[test]
+	fn test_equity_against_unseen_hands() {
+		// The nuts on the river can only tie
+		let nuts = equity_against_random(&hole("Ah 3h"), &cards("2h 7h 9h Td Jc"), 2);
+		assert_eq!(nuts, 1.0);
+		// A flush draw against one hand, and worse against three
+		let one = equity_against_random(&hole("8h 3h"), &cards("2h 7h Kc"), 1);
+		let three = equity_against_random(&hole("8h 3h"), &cards("2h 7h Kc"), 3);
+		assert!(one > 0.4 && one < 0.6, "{}", one);
+		assert!(three < one);
+	}
+
+	#[test]
+	fn test_preflop_overpair_is_a_big_favourite() {
+		let eq = equities(&[hole("As Ah"), hole("Kd Kc")], &[]);
+		assert!(eq[0] > 0.75 && eq[0] < 0.88, "{:?}", eq);
+		assert_eq!(eq, equities(&[hole("As Ah"), hole("Kd Kc")], &[]));
+	}
+}
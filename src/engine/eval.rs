@@ -0,0 +1,202 @@
+use rs_poker::core::{Hand, Rank, Rankable, Suit, Value};
+
+use crate::events::Card;
+use crate::promotions::HandCategory;
+
+const RANKS: &str = "23456789TJQKA";
+
+/// Best five-card rank from hole cards plus board.
+pub fn rank_hand(hole: &[Card; 2], board: &[Card]) -> Rank {
+	let cards: Vec<rs_poker::core::Card> = hole
+		.iter()
+		.chain(board.iter())
+		.filter_map(to_rs_card)
+		.collect();
+	Hand::new_with_cards(cards).rank()
+}
+
+/// Category name of a rank, e.g. "TwoPair" or "Flush".
+pub fn describe_rank(rank: &Rank) -> String {
+	let s = format!("{:?}", rank);
+	match s.find('(') {
+		Some(pos) => s[..pos].to_string(),
+		None => s,
+	}
+}
+
+/// The five cards that make the best hand, in the order they're read:
+/// the made part first, highest first, then the kickers. A wheel reads
+/// five down to ace. With fewer than five cards, all of them.
+pub fn best_five(hole: &[Card; 2], board: &[Card]) -> Vec<Card> {
+	let cards = hole.iter().chain(board.iter()).copied().collect::<Vec<_>>();
+	if cards.len() <= 5 {
+		return in_reading_order(&cards);
+	}
+	let mut best: Option<(Rank, Vec<Card>)> = None;
+	for mask in 0u32..(1 << cards.len()) {
+		if mask.count_ones() != 5 {
+			continue;
+		}
+		let five = (0..cards.len()).filter(|i| mask & (1 << i) != 0).map(|i| cards[i]).collect::<Vec<_>>();
+		let rank = Hand::new_with_cards(five.iter().filter_map(to_rs_card).collect()).rank();
+		if best.as_ref().is_none_or(|(top, _)| rank > *top) {
+			best = Some((rank, five));
+		}
+	}
+	best.map(|(_, five)| in_reading_order(&five)).unwrap_or_default()
+}
+
+/// A five-card hand in words, kickers and all: "pair of kings,
+/// ace-queen-ten kickers" or "full house, nines full of fours". Expects
+/// the cards as `best_five` orders them.
+pub fn describe_hand(five: &[Card]) -> String {
+	if five.len() < 5 {
+		return String::new();
+	}
+	let rank = Hand::new_with_cards(five.iter().filter_map(to_rs_card).collect()).rank();
+	let one = |i: usize| rank_name(five[i].rank, false);
+	let many = |i: usize| rank_name(five[i].rank, true);
+	let kickers = |from: usize| {
+		let names = five[from..].iter().map(|c| rank_name(c.rank, false)).collect::<Vec<_>>();
+		let noun = if names.len() == 1 { "kicker" } else { "kickers" };
+		format!("{} {}", names.join("-"), noun)
+	};
+	match HandCategory::of(&rank) {
+		HandCategory::HighCard => format!("{} high, {}", one(0), kickers(1)),
+		HandCategory::OnePair => format!("pair of {}, {}", many(0), kickers(2)),
+		HandCategory::TwoPair => format!("two pair, {} and {}, {}", many(0), many(2), kickers(4)),
+		HandCategory::ThreeOfAKind => format!("three {}, {}", many(0), kickers(3)),
+		HandCategory::Straight => format!("straight, {} to {}", one(4), one(0)),
+		HandCategory::Flush => {
+			let cards = five.iter().map(|c| rank_name(c.rank, false)).collect::<Vec<_>>();
+			format!("flush, {}", cards.join("-"))
+		}
+		HandCategory::FullHouse => format!("full house, {} full of {}", many(0), many(3)),
+		HandCategory::FourOfAKind => format!("four {}, {}", many(0), kickers(4)),
+		HandCategory::StraightFlush if five[0].rank == 'A' => "royal flush".to_string(),
+		HandCategory::StraightFlush => format!("straight flush, {} to {}", one(4), one(0)),
+	}
+}
+
+/// Bigger groups first, then higher ranks; a wheel's ace goes last.
+fn in_reading_order(cards: &[Card]) -> Vec<Card> {
+	let value = |c: &Card| RANKS.find(c.rank).unwrap_or(0);
+	let count = |c: &Card| cards.iter().filter(|o| o.rank == c.rank).count();
+	let mut ordered = cards.to_vec();
+	ordered.sort_by(|a, b| count(b).cmp(&count(a)).then(value(b).cmp(&value(a))));
+	let mut ranks = ordered.iter().map(|c| c.rank).collect::<String>();
+	ranks.retain(|r| r != 'A');
+	if ordered.len() == 5 && ordered[0].rank == 'A' && ranks == "5432" {
+		ordered.rotate_left(1);
+	}
+	ordered
+}
+
+fn rank_name(rank: char, plural: bool) -> String {
+	let name = match rank {
+		'2' => "two",
+		'3' => "three",
+		'4' => "four",
+		'5' => "five",
+		'6' => "six",
+		'7' => "seven",
+		'8' => "eight",
+		'9' => "nine",
+		'T' => "ten",
+		'J' => "jack",
+		'Q' => "queen",
+		'K' => "king",
+		'A' => "ace",
+		_ => "?",
+	};
+	match (plural, rank) {
+		(false, _) => name.to_string(),
+		(true, '6') => "sixes".to_string(),
+		(true, _) => format!("{}s", name),
+	}
+}
+
+fn to_rs_card(card: &Card) -> Option<rs_poker::core::Card> {
+	let value = match card.rank {
+		'2' => Value::Two,
+		'3' => Value::Three,
+		'4' => Value::Four,
+		'5' => Value::Five,
+		'6' => Value::Six,
+		'7' => Value::Seven,
+		'8' => Value::Eight,
+		'9' => Value::Nine,
+		'T' => Value::Ten,
+		'J' => Value::Jack,
+		'Q' => Value::Queen,
+		'K' => Value::King,
+		'A' => Value::Ace,
+		_ => return None,
+	};
+	let suit = match card.suit {
+		's' => Suit::Spade,
+		'h' => Suit::Heart,
+		'd' => Suit::Diamond,
+		'c' => Suit::Club,
+		_ => return None,
+	};
+	Some(rs_poker::core::Card { value, suit })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::cards;
+
+	fn hole(s: &str) -> [Card; 2] {
+		let c = cards(s);
+		[c[0], c[1]]
+	}
+
+	#[test]
+	fn test_flush_beats_straight() {
+		let board = cards("2h 7h 9h Td Jc");
+		let flush = rank_hand(&hole("Ah 3h"), &board);
+		let straight = rank_hand(&hole("8s Qd"), &board);
+		assert!(flush > straight);
+		assert_eq!(describe_rank(&flush), "Flush");
+		assert_eq!(describe_rank(&straight), "Straight");
+	}
+
+	#[test]
+	fn test_kicker_decides() {
+		let board = cards("Ks Kd 7c 4h 2s");
+		let ace = rank_hand(&hole("Ac 9d"), &board);
+		let queen = rank_hand(&hole("Qc 9h"), &board);
+		assert!(ace > queen);
+	}
+
+	#[test]
+	fn test_best_five_and_kickers() {
+		let describe = |h: &str, board: &str| describe_hand(&best_five(&hole(h), &cards(board)));
+		let five = best_five(&hole("Kh Qd"), &cards("Ks 2c Ac Td 7h"));
+		assert_eq!(five, cards("Kh Ks Ac Qd Td"));
+		assert_eq!(describe_hand(&five), "pair of kings, ace-queen-ten kickers");
+
+		assert_eq!(describe("Ah 3d", "Ks 9c 7d 4h 2s"), "ace high, king-nine-seven-four kickers");
+		assert_eq!(describe("9h 9d", "4s 4c Ad 7h 2s"), "two pair, nines and fours, ace kicker");
+		assert_eq!(describe("7h 7d", "7s Kc Ad 3h 2s"), "three sevens, ace-king kickers");
+		assert_eq!(describe("Ah 2d", "3s 4c 5d Kh Ks"), "straight, ace to five");
+		assert_eq!(describe("6h 6d", "6s 4c 4d Kh 2s"), "full house, sixes full of fours");
+		assert_eq!(describe("Qh Qd", "Qs Qc 9d Kh 2s"), "four queens, king kicker");
+		assert_eq!(describe("Ah Kh", "Qh Jh Th 2c 3d"), "royal flush");
+		assert_eq!(describe("Ah 8h", "Qh 9h 3h 2c 3d"), "flush, ace-queen-nine-eight-three");
+
+		// The wheel reads five down to ace
+		let wheel = best_five(&hole("Ah 2d"), &cards("3s 4c 5d Kh Ks"));
+		assert_eq!(wheel.iter().map(|c| c.rank).collect::<String>(), "5432A");
+	}
+
+	#[test]
+	fn test_board_plays_is_a_tie() {
+		let board = cards("As Ks Qs Js Ts");
+		let a = rank_hand(&hole("2c 3d"), &board);
+		let b = rank_hand(&hole("4c 5d"), &board);
+		assert_eq!(a, b);
+	}
+}
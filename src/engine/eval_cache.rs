@@ -0,0 +1,167 @@
+//! A cache of hand ranks for the equity and simulation work, which ranks
+//! the same cards over and over: the feed works a street's equities out
+//! again after every action, and every hand's luck goes through the same
+//! runouts. A hand is keyed by its cards with the suits put in a standard
+//! order, since swapping suits round never changes a rank, so the key for
+//! A♠K♠ on Q♠J♠T♥ is also the key for A♥K♥ on Q♥J♥T♠.
+//!
+//! The cache is a fixed table of sets of four, each a cache line: a key
+//! can only go in its own set, and a full set lets go of the one of its
+//! four used longest ago. A lookup reads one line, so a miss costs little
+//! more than the rank, where a map and a list over the whole cache would
+//! cost more than ranking the hand again.
+
+use std::cell::RefCell;
+
+use rs_poker::core::Rank;
+
+use super::eval::rank_hand;
+use crate::events::Card;
+
+/// Ranks each thread keeps: 256 KB.
+pub const EVAL_CACHE_SIZE: usize = 1 << 14;
+
+const WAYS: usize = 4;
+
+const EMPTY: u64 = u64::MAX;
+
+/// The key for a set of cards: each suit's ranks as 13 bits, the four
+/// sorted so that suits that could be swapped give the same key. `None`
+/// for a card that isn't one.
+pub fn canonical_key(cards: impl IntoIterator<Item = Card>) -> Option<u64> {
+	let mut suits = [0u64; 4];
+	for card in cards {
+		let rank = match card.rank {
+			'2'..='9' => card.rank as u32 - '2' as u32,
+			'T' => 8,
+			'J' => 9,
+			'Q' => 10,
+			'K' => 11,
+			'A' => 12,
+			_ => return None,
+		};
+		let suit = match card.suit {
+			's' => 0,
+			'h' => 1,
+			'd' => 2,
+			'c' => 3,
+			_ => return None,
+		};
+		suits[suit] |= 1 << rank;
+	}
+	suits.sort_unstable();
+	Some(suits.iter().fold(0, |key, &ranks| key << 13 | ranks))
+}
+
+/// How the cache has done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EvalCacheStats {
+	pub hits: u64,
+	pub misses: u64,
+	/// Ranks held now.
+	pub len: usize,
+}
+
+#[derive(Clone, Copy)]
+struct Slot {
+	key: u64,
+	rank: Rank,
+}
+
+const EMPTY_SLOT: Slot = Slot { key: EMPTY, rank: Rank::HighCard(0) };
+
+/// Ranks by `canonical_key`, about `capacity` of them, in sets of four
+/// kept most recently used first.
+pub struct EvalCache {
+	sets: Vec<[Slot; WAYS]>,
+	hits: u64,
+	misses: u64,
+	len: usize,
+}
+
+impl EvalCache {
+	pub fn new(capacity: usize) -> Self {
+		let sets = (capacity / WAYS).max(1).next_power_of_two();
+		Self { sets: vec![[EMPTY_SLOT; WAYS]; sets], hits: 0, misses: 0, len: 0 }
+	}
+
+	/// The rank for `key`, from `rank` the first time.
+	pub fn get_or_insert_with(&mut self, key: u64, rank: impl FnOnce() -> Rank) -> Rank {
+		let index = (key.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 32) as usize & (self.sets.len() - 1);
+		let set = &mut self.sets[index];
+		if let Some(way) = set.iter().position(|slot| slot.key == key) {
+			self.hits += 1;
+			let slot = set[way];
+			set.copy_within(0..way, 1);
+			set[0] = slot;
+			return slot.rank;
+		}
+		self.misses += 1;
+		let rank = rank();
+		if set[WAYS - 1].key == EMPTY {
+			self.len += 1;
+		}
+		set.copy_within(0..WAYS - 1, 1);
+		set[0] = Slot { key, rank };
+		rank
+	}
+
+	pub fn stats(&self) -> EvalCacheStats {
+		EvalCacheStats { hits: self.hits, misses: self.misses, len: self.len }
+	}
+}
+
+thread_local! {
+	static CACHE: RefCell<EvalCache> = RefCell::new(EvalCache::new(EVAL_CACHE_SIZE));
+}
+
+/// `rank_hand` through this thread's cache.
+pub fn rank_hand_cached(hole: &[Card; 2], board: &[Card]) -> Rank {
+	match canonical_key(hole.iter().chain(board).copied()) {
+		Some(key) => CACHE.with(|cache| cache.borrow_mut().get_or_insert_with(key, || rank_hand(hole, board))),
+		None => rank_hand(hole, board),
+	}
+}
+
+/// How this thread's cache has done.
+pub fn eval_cache_stats() -> EvalCacheStats {
+	CACHE.with(|cache| cache.borrow().stats())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::cards;
+
+	#[test]
+	fn test_swapped_suits_share_a_key() {
+		let key = |s: &str| canonical_key(cards(s)).unwrap();
+		assert_eq!(key("As Ks Qs Js Th 2c 3d"), key("Ah Kh Qh Jh Ts 2d 3c"));
+		assert_ne!(key("As Ks Qs Js Th 2c 3d"), key("As Ks Qs Js Ts 2c 3d"));
+		assert_eq!(canonical_key(cards("As ??")), None);
+	}
+
+	#[test]
+	fn test_least_recently_used_goes_first() {
+		// One set, so every key competes for the same four places
+		let mut cache = EvalCache::new(WAYS);
+		let rank = Rank::HighCard(1);
+		for key in 1..=4 {
+			cache.get_or_insert_with(key, || rank);
+		}
+		cache.get_or_insert_with(1, || unreachable!());
+		cache.get_or_insert_with(5, || rank);
+		// 2 went to make room; 1 was used more recently
+		cache.get_or_insert_with(1, || unreachable!());
+		cache.get_or_insert_with(2, || rank);
+		assert_eq!(cache.stats(), EvalCacheStats { hits: 2, misses: 6, len: 4 });
+	}
+
+	#[test]
+	fn test_cached_ranks_match() {
+		let hole = [Card::new('A', 'd'), Card::new('5', 'd')];
+		let board = cards("2d 3d 4c 9d Kh");
+		assert_eq!(rank_hand_cached(&hole, &board), rank_hand(&hole, &board));
+		assert_eq!(rank_hand_cached(&hole, &board), rank_hand(&hole, &board));
+	}
+}
@@ -0,0 +1,1062 @@
+use async_trait::async_trait;
+
+use crate::engine::blinds::BlindPositions;
+use crate::engine::deck::Deck;
+use crate::engine::equity::equities;
+use crate::engine::eval::{best_five, describe_hand, rank_hand};
+use crate::engine::historian::EventHistorian;
+use crate::engine::pot::{split_pot, Pot, PotManager, RakeConfig, CHIP_UNIT};
+use crate::engine::validator::{ActionValidator, BetContext, Resolved};
+use crate::events::{BlindType, Blinds, Card, PlayerAction, PotType, Seat, Street, ValidActions};
+use crate::players::HandRecap;
+use crate::promotions::{HandCategory, JackpotConfig};
+
+/// How far short of the minimum a raise can be and still count as full:
+/// a minimum worked out in f32 from fractional bets can land a hair under.
+const RAISE_TOLERANCE: f32 = 0.001;
+
+/// Something that can make decisions for a seat.
+#[async_trait]
+pub trait Agent: Send {
+	async fn act(&mut self, state: &HandState, valid: &ValidActions) -> PlayerAction;
+
+	/// Throw away a losing hand at showdown instead of showing it.
+	fn mucks_losers(&self) -> bool {
+		false
+	}
+
+	/// Turn over a hand that won without a call. Asked once the pot is
+	/// pushed; the hand is mucked unless this says otherwise.
+	async fn shows_uncalled(&mut self, _state: &HandState) -> bool {
+		false
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct SeatState {
+	pub stack: f32,
+	pub street_bet: f32,
+	pub dealt_in: bool,
+	pub folded: bool,
+	pub all_in: bool,
+	pub hole_cards: Option<[Card; 2]>,
+}
+
+impl SeatState {
+	pub fn in_hand(&self) -> bool {
+		self.dealt_in && !self.folded
+	}
+
+	pub fn can_act(&self) -> bool {
+		self.in_hand() && !self.all_in
+	}
+}
+
+/// Everything an agent may look at when deciding. Hole cards of other
+/// seats are present here; the adapter only passes on the acting seat's.
+#[derive(Debug, Clone)]
+pub struct HandState {
+	pub hand_num: u32,
+	pub street: Street,
+	pub board: Vec<Card>,
+	pub seats: Vec<SeatState>,
+	pub positions: BlindPositions,
+	pub blinds: Blinds,
+	pub current_bet: f32,
+	pub min_raise: f32,
+	pub raises_this_street: u32,
+	pub max_raises: u32,
+	pots: PotManager,
+}
+
+impl HandState {
+	pub fn pot(&self) -> f32 {
+		self.pots.total()
+	}
+
+	/// Main pot first, then side pots, as they stand right now.
+	pub fn pots(&self) -> Vec<Pot> {
+		self.pots.pots()
+	}
+
+	/// What a seat has put in over the whole hand.
+	pub fn contribution(&self, idx: usize) -> f32 {
+		self.pots.contribution(idx)
+	}
+
+	/// What a seat can still win or lose from here: its stack or the
+	/// biggest stack among the others still in, whichever is smaller.
+	pub fn effective_stack(&self, idx: usize) -> f32 {
+		let deepest_other = self
+			.seats
+			.iter()
+			.enumerate()
+			.filter(|(i, s)| *i != idx && s.in_hand())
+			.map(|(_, s)| s.stack)
+			.fold(0.0, f32::max);
+		self.seats.get(idx).map_or(0.0, |s| s.stack.min(deepest_other))
+	}
+
+	/// The most any two players still in can play for: the second-biggest
+	/// stack among them.
+	pub fn table_effective_stack(&self) -> f32 {
+		let mut stacks = self.seats.iter().filter(|s| s.in_hand()).map(|s| s.stack).collect::<Vec<_>>();
+		stacks.sort_by(|a, b| b.total_cmp(a));
+		stacks.get(1).copied().unwrap_or(0.0)
+	}
+}
+
+pub struct HandConfig {
+	pub hand_num: u32,
+	pub small_blind: f32,
+	pub big_blind: f32,
+	pub validator: ActionValidator,
+	pub rake: RakeConfig,
+	pub jackpot: Option<JackpotConfig>,
+}
+
+impl HandConfig {
+	/// What pots are split in: whole chips, unless the blinds, rake or
+	/// jackpot drop bring cents to the table.
+	pub fn chip_unit(&self) -> f32 {
+		let whole = |amount: f32| amount.fract() == 0.0;
+		if whole(self.small_blind) && whole(self.big_blind) && self.rake.percent <= 0.0 && self.jackpot.is_none() {
+			1.0
+		} else {
+			CHIP_UNIT
+		}
+	}
+}
+
+pub struct HandOutcome {
+	pub stacks: Vec<f32>,
+	pub hole_cards: Vec<Option<[Card; 2]>>,
+	pub folded: Vec<bool>,
+	/// Reached showdown but mucked a losing hand unseen, or won uncalled
+	/// and didn't show.
+	pub mucked: Vec<bool>,
+	pub board: Vec<Card>,
+}
+
+/// Turn equity at which losing the showdown counts as a bad beat.
+const BAD_BEAT_EQUITY: f32 = 0.8;
+
+impl HandOutcome {
+	/// What the table saw of this hand, given each seat's stack before it.
+	pub fn recap(&self, hand_num: u32, big_blind: f32, stacks_before: &[f32]) -> HandRecap {
+		let results = self
+			.hole_cards
+			.iter()
+			.enumerate()
+			.filter(|(_, cards)| cards.is_some())
+			.map(|(i, _)| (Seat(i), self.stacks[i] - stacks_before[i]))
+			.collect();
+		let shown: Vec<(usize, [Card; 2])> = self
+			.hole_cards
+			.iter()
+			.enumerate()
+			.filter_map(|(i, cards)| cards.filter(|_| !self.folded[i] && !self.mucked[i]).map(|c| (i, c)))
+			.collect();
+		let showdown = shown.len() >= 2;
+
+		let bad_beat = if showdown && self.board.len() == 5 {
+			let hands: Vec<[Card; 2]> = shown.iter().map(|(_, c)| *c).collect();
+			let turn = equities(&hands, &self.board[..4]);
+			shown
+				.iter()
+				.zip(turn)
+				.find(|((i, _), eq)| *eq >= BAD_BEAT_EQUITY && self.stacks[*i] < stacks_before[*i])
+				.map(|((i, _), _)| Seat(*i))
+		} else {
+			None
+		};
+
+		HandRecap {
+			hand_num,
+			big_blind,
+			board: self.board.clone(),
+			results,
+			showdown,
+			bad_beat,
+		}
+	}
+
+	/// The hand a seat showed down, kickers and all; `None` if the hand
+	/// didn't reach a showdown or the seat didn't show.
+	pub fn made_hand(&self, i: usize) -> Option<String> {
+		let shows = |i: usize| !self.folded[i] && !self.mucked[i] && self.hole_cards[i].is_some();
+		let showdown = (0..self.hole_cards.len()).filter(|&i| shows(i)).count() >= 2;
+		if !showdown || !shows(i) || self.board.len() < 3 {
+			return None;
+		}
+		Some(describe_hand(&best_five(&self.hole_cards[i]?, &self.board)))
+	}
+}
+
+/// Runs one hand of hold'em: blinds, dealing, betting rounds, showdown and
+/// pot distribution.
+pub struct Dealer<'a> {
+	config: HandConfig,
+	state: HandState,
+	deck: Deck,
+	historian: &'a EventHistorian,
+}
+
+impl<'a> Dealer<'a> {
+	pub fn new(
+		config: HandConfig,
+		positions: BlindPositions,
+		stacks: &[f32],
+		dealt_in: &[bool],
+		deck: Deck,
+		historian: &'a EventHistorian,
+	) -> Self {
+		let seats = stacks
+			.iter()
+			.zip(dealt_in)
+			.map(|(&stack, &dealt_in)| SeatState {
+				stack,
+				street_bet: 0.0,
+				dealt_in: dealt_in && stack > 0.0,
+				folded: false,
+				all_in: false,
+				hole_cards: None,
+			})
+			.collect();
+
+		let state = HandState {
+			hand_num: config.hand_num,
+			street: Street::Preflop,
+			board: Vec::new(),
+			seats,
+			positions,
+			blinds: Blinds {
+				small: config.small_blind,
+				big: config.big_blind,
+				ante: None,
+			},
+			current_bet: 0.0,
+			min_raise: config.big_blind,
+			raises_this_street: 0,
+			max_raises: config.validator.max_raises_per_round,
+			pots: PotManager::new(stacks.len()),
+		};
+
+		Self {
+			config,
+			state,
+			deck,
+			historian,
+		}
+	}
+
+	pub async fn play(mut self, agents: &mut [Option<Box<dyn Agent + '_>>]) -> HandOutcome {
+		self.post_blinds();
+		self.deal_hole_cards();
+		self.betting_round(agents).await;
+
+		for (street, cards) in [(Street::Flop, 3), (Street::Turn, 1), (Street::River, 1)] {
+			if self.in_hand_count() <= 1 {
+				break;
+			}
+			self.start_street(street, cards);
+			self.betting_round(agents).await;
+		}
+
+		let mut mucked = self.award_pots(agents);
+
+		// Won without a call: the winner may show, or muck unseen
+		if let [winner] = self.award_order()[..] {
+			let shows = match agents.get_mut(winner).and_then(|a| a.as_mut()) {
+				Some(agent) => agent.shows_uncalled(&self.state).await,
+				None => false,
+			};
+			match self.state.seats[winner].hole_cards {
+				Some(cards) if shows => self.historian.shows(Seat(winner), cards),
+				_ => mucked[winner] = true,
+			}
+		}
+
+		HandOutcome {
+			stacks: self.state.seats.iter().map(|s| s.stack).collect(),
+			hole_cards: self.state.seats.iter().map(|s| s.hole_cards).collect(),
+			folded: self.state.seats.iter().map(|s| s.folded).collect(),
+			mucked,
+			board: self.state.board.clone(),
+		}
+	}
+
+	fn in_hand_count(&self) -> usize {
+		self.state.seats.iter().filter(|s| s.in_hand()).count()
+	}
+
+	fn commit(&mut self, idx: usize, amount: f32) -> f32 {
+		let seat = &mut self.state.seats[idx];
+		let paid = amount.min(seat.stack);
+		seat.stack -= paid;
+		seat.street_bet += paid;
+		if seat.stack <= 0.0 {
+			seat.all_in = true;
+		}
+		self.state.pots.add(idx, paid);
+		paid
+	}
+
+	fn post_blinds(&mut self) {
+		let positions = self.state.positions;
+
+		if let Some(sb) = positions.small_blind_poster() {
+			if self.state.seats[sb].dealt_in {
+				let paid = self.commit(sb, self.config.small_blind);
+				self.historian.blind_posted(Seat(sb), BlindType::Small, paid);
+			}
+		}
+
+		let bb = positions.big_blind;
+		if self.state.seats[bb].dealt_in {
+			let paid = self.commit(bb, self.config.big_blind);
+			self.historian.blind_posted(Seat(bb), BlindType::Big, paid);
+		}
+
+		self.state.current_bet = self.config.big_blind;
+		self.state.min_raise = self.config.big_blind;
+	}
+
+	fn deal_hole_cards(&mut self) {
+		let n = self.state.seats.len();
+		let order: Vec<usize> = (1..=n)
+			.map(|i| (self.state.positions.button + i) % n)
+			.filter(|&i| self.state.seats[i].dealt_in)
+			.collect();
+
+		let first: Vec<Card> = order.iter().map(|_| self.deck.deal()).collect();
+		let second: Vec<Card> = order.iter().map(|_| self.deck.deal()).collect();
+
+		for (k, &idx) in order.iter().enumerate() {
+			let cards = [first[k], second[k]];
+			self.state.seats[idx].hole_cards = Some(cards);
+			self.historian.hole_cards_dealt(Seat(idx), cards);
+		}
+	}
+
+	fn start_street(&mut self, street: Street, cards: usize) {
+		let _burn = self.deck.deal();
+		for _ in 0..cards {
+			let card = self.deck.deal();
+			self.state.board.push(card);
+		}
+
+		self.state.street = street;
+		self.state.current_bet = 0.0;
+		self.state.min_raise = self.config.big_blind;
+		self.state.raises_this_street = 0;
+		for seat in &mut self.state.seats {
+			seat.street_bet = 0.0;
+		}
+
+		self.historian.street_changed(street, &self.state);
+	}
+
+	fn opponents_can_act(&self, idx: usize) -> bool {
+		self.state
+			.seats
+			.iter()
+			.enumerate()
+			.any(|(i, s)| i != idx && s.can_act())
+	}
+
+	fn needs_action(&self, idx: usize, acted: &[bool]) -> bool {
+		let seat = &self.state.seats[idx];
+		if !seat.can_act() {
+			return false;
+		}
+		if seat.street_bet < self.state.current_bet {
+			return true;
+		}
+		!acted[idx] && self.opponents_can_act(idx)
+	}
+
+	fn first_to_act(&self) -> usize {
+		let n = self.state.seats.len();
+		let anchor = match self.state.street {
+			Street::Preflop => self.state.positions.big_blind,
+			_ => self.state.positions.button,
+		};
+		(anchor + 1) % n
+	}
+
+	fn bet_context(&self, idx: usize) -> BetContext {
+		let seat = &self.state.seats[idx];
+		BetContext {
+			street: self.state.street,
+			stack: seat.stack,
+			street_bet: seat.street_bet,
+			current_bet: self.state.current_bet,
+			min_raise: self.state.min_raise,
+			pot: self.state.pot(),
+			big_blind: self.config.big_blind,
+			raises_this_street: self.state.raises_this_street,
+			opponents_can_act: self.opponents_can_act(idx),
+		}
+	}
+
+	async fn betting_round(&mut self, agents: &mut [Option<Box<dyn Agent + '_>>]) {
+		let n = self.state.seats.len();
+		let mut acted = vec![false; n];
+		let mut cursor = self.first_to_act();
+
+		loop {
+			if self.in_hand_count() <= 1 {
+				return;
+			}
+
+			let next = (0..n)
+				.map(|k| (cursor + k) % n)
+				.find(|&i| self.needs_action(i, &acted));
+			let Some(idx) = next else {
+				return;
+			};
+
+			let ctx = self.bet_context(idx);
+			let valid = self.config.validator.valid_actions(&ctx);
+			let requested = match agents.get_mut(idx).and_then(|a| a.as_mut()) {
+				Some(agent) => agent.act(&self.state, &valid).await,
+				None => PlayerAction::Timeout,
+			};
+			let resolved = self.config.validator.resolve(&requested, &valid);
+
+			let action = self.apply(idx, resolved, &mut acted);
+			acted[idx] = true;
+
+			let seat = &self.state.seats[idx];
+			self.historian.action_taken(
+				Seat(idx),
+				self.state.street,
+				action,
+				seat.stack,
+				self.state.pot(),
+			);
+
+			cursor = (idx + 1) % n;
+		}
+	}
+
+	fn apply(&mut self, idx: usize, resolved: Resolved, acted: &mut [bool]) -> PlayerAction {
+		let bet_before = self.state.current_bet;
+
+		match resolved {
+			Resolved::Fold => {
+				self.state.seats[idx].folded = true;
+				self.state.pots.fold(idx);
+				PlayerAction::Fold
+			}
+			Resolved::Check => PlayerAction::Check,
+			Resolved::Call => {
+				let to_call = bet_before - self.state.seats[idx].street_bet;
+				let paid = self.commit(idx, to_call);
+				PlayerAction::Call { amount: paid }
+			}
+			Resolved::RaiseTo(_) | Resolved::AllIn => {
+				let to = match resolved {
+					Resolved::RaiseTo(to) => to,
+					_ => self.state.seats[idx].stack + self.state.seats[idx].street_bet,
+				};
+				let needed = to - self.state.seats[idx].street_bet;
+				self.commit(idx, needed);
+				let total = self.state.seats[idx].street_bet;
+
+				if total > bet_before {
+					let raise_size = total - bet_before;
+					if raise_size >= self.state.min_raise - RAISE_TOLERANCE {
+						// A full raise reopens the action for everyone else.
+						self.state.min_raise = raise_size;
+						self.state.raises_this_street += 1;
+						for (i, a) in acted.iter_mut().enumerate() {
+							if i != idx {
+								*a = false;
+							}
+						}
+					}
+					self.state.current_bet = total;
+				}
+
+				if self.state.seats[idx].all_in {
+					PlayerAction::AllIn { amount: total }
+				} else if bet_before <= 0.0 {
+					PlayerAction::Bet { amount: total }
+				} else {
+					PlayerAction::Raise { amount: total }
+				}
+			}
+		}
+	}
+
+	/// Seats still in the hand, clockwise from the first seat after the
+	/// button. Odd chips go to the earliest winner in this order.
+	fn award_order(&self) -> Vec<usize> {
+		let n = self.state.seats.len();
+		(1..=n)
+			.map(|i| (self.state.positions.button + i) % n)
+			.filter(|&i| self.state.seats[i].in_hand())
+			.collect()
+	}
+
+	/// Settles every pot and returns which seats mucked at showdown.
+	fn award_pots(&mut self, agents: &[Option<Box<dyn Agent + '_>>]) -> Vec<bool> {
+		let contenders = self.award_order();
+		let showdown = contenders.len() >= 2;
+
+		let ranks: Vec<Option<rs_poker::core::Rank>> = self
+			.state
+			.seats
+			.iter()
+			.map(|s| match (showdown && s.in_hand(), s.hole_cards) {
+				(true, Some(hole)) => Some(rank_hand(&hole, &self.state.board)),
+				_ => None,
+			})
+			.collect();
+
+		// Winners are settled before anything is shown, so a losing hand
+		// can be mucked without being seen.
+		let mut pots: Vec<(Pot, Vec<usize>, Vec<usize>)> = self
+			.state
+			.pots
+			.pots()
+			.into_iter()
+			.map(|pot| {
+				let eligible: Vec<usize> = contenders
+					.iter()
+					.copied()
+					.filter(|i| pot.eligible.contains(i))
+					.collect();
+				let winners: Vec<usize> = if showdown && eligible.len() > 1 {
+					let best = eligible.iter().filter_map(|&i| ranks[i].as_ref()).max();
+					eligible.iter().copied().filter(|&i| ranks[i].as_ref() == best).collect()
+				} else {
+					eligible.clone()
+				};
+				(pot, eligible, winners)
+			})
+			.collect();
+
+		let mut mucked = vec![false; self.state.seats.len()];
+		if showdown {
+			for &i in &contenders {
+				let wins_something = pots.iter().any(|(_, _, winners)| winners.contains(&i));
+				let mucks = agents.get(i).and_then(|a| a.as_ref()).is_some_and(|a| a.mucks_losers());
+				mucked[i] = mucks && !wins_something;
+			}
+			let reveals: Vec<(Seat, [Card; 2])> = contenders
+				.iter()
+				.filter(|&&i| !mucked[i])
+				.filter_map(|&i| self.state.seats[i].hole_cards.map(|c| (Seat(i), c)))
+				.collect();
+			self.historian.showdown(&self.state, reveals);
+		}
+
+		let saw_flop = !self.state.board.is_empty();
+		let called = self.state.pots.total() - self.state.pots.uncalled();
+		let rake = self.config.rake.rake_for(called, saw_flop);
+		take_off_pots(&mut pots, rake);
+		if rake > 0.0 {
+			self.historian.rake_collected(rake);
+		}
+		let drop = self.config.jackpot.as_ref().map_or(0.0, |j| j.drop_for(called - rake, saw_flop));
+		take_off_pots(&mut pots, drop);
+		if drop > 0.0 {
+			self.historian.jackpot_drop(drop);
+		}
+
+		for (k, (pot, eligible, winners)) in pots.iter().enumerate() {
+			let pot_type = if k == 0 { PotType::Main } else { PotType::Side(k as u8) };
+			for (idx, amount) in split_pot(pot.amount, winners, self.config.chip_unit()) {
+				if amount <= 0.0 {
+					continue;
+				}
+				self.state.seats[idx].stack += amount;
+				let five = match (showdown && eligible.len() > 1, self.state.seats[idx].hole_cards) {
+					(true, Some(hole)) => best_five(&hole, &self.state.board),
+					_ => Vec::new(),
+				};
+				let description = (!five.is_empty()).then(|| describe_hand(&five));
+				self.historian.pot_awarded(Seat(idx), amount, description, five, pot_type);
+			}
+		}
+
+		// The best hand shown down that lost the main pot, if it's good
+		// enough for the jackpot
+		if let (Some(jackpot), Some((_, eligible, winners))) = (&self.config.jackpot, pots.first()) {
+			let beaten = eligible
+				.iter()
+				.filter(|i| !winners.contains(i) && !mucked[**i])
+				.filter_map(|&i| ranks[i].map(|rank| (i, rank)))
+				.max_by_key(|(_, rank)| *rank);
+			let winning = winners.first().and_then(|&i| ranks[i]);
+			if let (Some((loser, losing)), Some(winning)) = (beaten, winning) {
+				if showdown && jackpot.qualifies(&losing) {
+					self.historian.bad_beat_jackpot(
+						Seat(loser),
+						HandCategory::of(&losing).to_string(),
+						winners.iter().map(|&i| Seat(i)).collect(),
+						HandCategory::of(&winning).to_string(),
+					);
+				}
+			}
+		}
+
+		mucked
+	}
+}
+
+/// Takes `amount` off the pots, main pot first.
+fn take_off_pots(pots: &mut [(Pot, Vec<usize>, Vec<usize>)], amount: f32) {
+	let mut left = amount;
+	for (pot, _, _) in pots.iter_mut() {
+		let taken = left.min(pot.amount);
+		pot.amount -= taken;
+		left -= taken;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::engine::historian::EventSender;
+	use crate::engine::{event_queue, EventQueueConfig};
+	use crate::engine::validator::BettingStructure;
+	use crate::events::GameEvent;
+	use rand::SeedableRng;
+	use rand::rngs::StdRng;
+	use std::collections::VecDeque;
+	use std::sync::{Arc, Mutex};
+
+	struct Scripted {
+		actions: VecDeque<PlayerAction>,
+		default: PlayerAction,
+		mucks: bool,
+	}
+
+	#[async_trait]
+	impl Agent for Scripted {
+		async fn act(&mut self, _state: &HandState, _valid: &ValidActions) -> PlayerAction {
+			self.actions.pop_front().unwrap_or_else(|| self.default.clone())
+		}
+
+		fn mucks_losers(&self) -> bool {
+			self.mucks
+		}
+
+		async fn shows_uncalled(&mut self, _state: &HandState) -> bool {
+			let shows = matches!(self.actions.front(), Some(PlayerAction::Show));
+			if shows {
+				self.actions.pop_front();
+			}
+			shows
+		}
+	}
+
+	fn agent(actions: Vec<PlayerAction>, default: PlayerAction) -> Option<Box<dyn Agent>> {
+		Some(Box::new(Scripted { actions: actions.into(), default, mucks: false }))
+	}
+
+	fn play(
+		stacks: &[f32],
+		agents: &mut [Option<Box<dyn Agent>>],
+		seed: u64,
+	) -> (HandOutcome, Vec<GameEvent>) {
+		let (tx, rx) = event_queue(EventQueueConfig::unbounded());
+		let historian = EventHistorian::new(EventSender::new(tx), Arc::new(Mutex::new(Vec::new())));
+		let dealt_in: Vec<bool> = stacks.iter().map(|s| *s > 0.0).collect();
+		let positions = BlindPositions::first_hand(0, &dealt_in).unwrap();
+		let config = HandConfig {
+			hand_num: 1,
+			small_blind: 5.0,
+			big_blind: 10.0,
+			validator: ActionValidator::new(BettingStructure::NoLimit, 4),
+			rake: RakeConfig::default(),
+			jackpot: None,
+		};
+		let deck = Deck::shuffled(&mut StdRng::seed_from_u64(seed));
+		let dealer = Dealer::new(config, positions, stacks, &dealt_in, deck, &historian);
+		let outcome = block_on(dealer.play(agents));
+		drop(historian);
+		(outcome, rx.try_iter().collect())
+	}
+
+	fn block_on<F: std::future::Future>(future: F) -> F::Output {
+		tokio::runtime::Builder::new_current_thread()
+			.build()
+			.unwrap()
+			.block_on(future)
+	}
+
+	fn actors(events: &[GameEvent]) -> Vec<usize> {
+		events
+			.iter()
+			.filter_map(|e| match e {
+				GameEvent::ActionTaken { seat, .. } => Some(seat.0),
+				_ => None,
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_everyone_folds_to_big_blind() {
+		let mut agents = vec![
+			agent(vec![], PlayerAction::Fold),
+			agent(vec![], PlayerAction::Fold),
+			agent(vec![], PlayerAction::Fold),
+		];
+		let (outcome, events) = play(&[100.0, 100.0, 100.0], &mut agents, 1);
+		// Button 0, SB 1, BB 2: button then small blind fold.
+		assert_eq!(actors(&events), vec![0, 1]);
+		assert_eq!(outcome.stacks, vec![100.0, 95.0, 105.0]);
+	}
+
+	#[test]
+	fn test_uncalled_winner_chooses_whether_to_show() {
+		for shows in [false, true] {
+			let script = if shows { vec![PlayerAction::Show] } else { vec![] };
+			let mut agents = vec![
+				agent(vec![], PlayerAction::Fold),
+				agent(vec![], PlayerAction::Fold),
+				agent(script, PlayerAction::Check),
+			];
+			let (outcome, events) = play(&[100.0, 100.0, 100.0], &mut agents, 1);
+			let revealed: Vec<usize> = events
+				.iter()
+				.filter_map(|e| match e {
+					GameEvent::ShowdownReveal { reveals } => Some(reveals.iter().map(|(s, _)| s.0)),
+					_ => None,
+				})
+				.flatten()
+				.collect();
+			assert_eq!(revealed, if shows { vec![2] } else { vec![] });
+			assert_eq!(outcome.mucked[2], !shows, "an unshown winner's cards stay hidden");
+			assert!(!events.iter().any(|e| matches!(e, GameEvent::StreetChanged { street: Street::Showdown, .. })));
+		}
+	}
+
+	#[test]
+	fn test_heads_up_order() {
+		let mut agents = vec![
+			agent(vec![], PlayerAction::Call { amount: 0.0 }),
+			agent(vec![], PlayerAction::Check),
+		];
+		let (_outcome, events) = play(&[100.0, 100.0], &mut agents, 2);
+		let blinds: Vec<(usize, BlindType)> = events
+			.iter()
+			.filter_map(|e| match e {
+				GameEvent::BlindPosted { seat, blind_type, .. } => Some((seat.0, *blind_type)),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(blinds, vec![(0, BlindType::Small), (1, BlindType::Big)]);
+		// Button acts first preflop, last on every later street.
+		assert_eq!(actors(&events), vec![0, 1, 1, 0, 1, 0, 1, 0]);
+	}
+
+	#[test]
+	fn test_streets_carry_the_pot_and_effective_stack() {
+		let mut agents = vec![
+			agent(vec![], PlayerAction::Call { amount: 0.0 }),
+			agent(vec![], PlayerAction::Call { amount: 0.0 }),
+			agent(vec![], PlayerAction::Call { amount: 0.0 }),
+		];
+		let (_outcome, events) = play(&[100.0, 300.0, 60.0], &mut agents, 5);
+		let flop = events.iter().find_map(|e| match e {
+			GameEvent::StreetChanged { street: Street::Flop, pot, effective_stack, .. } => Some((*pot, *effective_stack)),
+			_ => None,
+		});
+		// Everyone limped for 10; the two deepest have 90 and 290 behind
+		assert_eq!(flop, Some((30.0, 90.0)));
+		assert_eq!(crate::events::stack_to_pot(90.0, 30.0), Some(3.0));
+		assert_eq!(crate::events::stack_to_pot(0.0, 30.0), None);
+	}
+
+	#[test]
+	fn test_streets_carry_side_pots_without_the_uncalled_bet() {
+		let mut agents = vec![
+			agent(vec![], PlayerAction::AllIn { amount: 0.0 }),
+			agent(vec![], PlayerAction::AllIn { amount: 0.0 }),
+			agent(vec![], PlayerAction::AllIn { amount: 0.0 }),
+		];
+		let (_outcome, events) = play(&[50.0, 100.0, 200.0], &mut agents, 6);
+		let pots = events.iter().find_map(|e| match e {
+			GameEvent::StreetChanged { street: Street::Flop, pots, .. } => Some(pots.clone()),
+			_ => None,
+		});
+		assert_eq!(pots, Some(vec![150.0, 100.0]));
+	}
+
+	#[test]
+	fn test_big_blind_gets_option() {
+		let mut agents = vec![
+			agent(vec![], PlayerAction::Call { amount: 0.0 }),
+			agent(vec![], PlayerAction::Call { amount: 0.0 }),
+			agent(vec![PlayerAction::Raise { amount: 30.0 }], PlayerAction::Check),
+		];
+		let (_outcome, events) = play(&[100.0, 100.0, 100.0], &mut agents, 3);
+		let preflop: Vec<(usize, String)> = events
+			.iter()
+			.take_while(|e| !matches!(e, GameEvent::StreetChanged { .. }))
+			.filter_map(|e| match e {
+				GameEvent::ActionTaken { seat, action, .. } => Some((seat.0, action.description())),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(preflop[2], (2, "raises to $30".to_string()));
+		// The raise reopens action for both callers.
+		assert_eq!(preflop.len(), 5);
+	}
+
+	#[test]
+	fn test_all_in_runs_out_board_and_conserves_chips() {
+		let mut agents = vec![
+			agent(vec![PlayerAction::AllIn { amount: 0.0 }], PlayerAction::Check),
+			agent(vec![], PlayerAction::Call { amount: 0.0 }),
+			agent(vec![], PlayerAction::Call { amount: 0.0 }),
+		];
+		let (outcome, events) = play(&[50.0, 200.0, 200.0], &mut agents, 4);
+		let total: f32 = outcome.stacks.iter().sum();
+		assert_eq!(total, 450.0);
+		assert!(events.iter().any(|e| matches!(e, GameEvent::ShowdownReveal { .. })));
+		let streets = events
+			.iter()
+			.filter(|e| matches!(e, GameEvent::StreetChanged { .. }))
+			.count();
+		assert_eq!(streets, 4, "flop, turn, river and showdown");
+	}
+
+	#[test]
+	fn test_losers_who_muck_are_not_shown() {
+		let mucker = || -> Option<Box<dyn Agent>> {
+			Some(Box::new(Scripted {
+				actions: VecDeque::new(),
+				default: PlayerAction::Call { amount: 0.0 },
+				mucks: true,
+			}))
+		};
+		let mut agents = vec![mucker(), mucker(), agent(vec![], PlayerAction::Call { amount: 0.0 })];
+		let (outcome, events) = play(&[100.0, 100.0, 100.0], &mut agents, 11);
+
+		let awarded: Vec<usize> = events
+			.iter()
+			.filter_map(|e| match e {
+				GameEvent::PotAwarded { seat, .. } => Some(seat.0),
+				_ => None,
+			})
+			.collect();
+		let shown: Vec<usize> = events
+			.iter()
+			.find_map(|e| match e {
+				GameEvent::ShowdownReveal { reveals } => Some(reveals.iter().map(|(s, _)| s.0).collect()),
+				_ => None,
+			})
+			.expect("checked down to showdown");
+
+		for seat in 0..2 {
+			let won = awarded.contains(&seat);
+			assert_eq!(shown.contains(&seat), won, "seat {} won: {}", seat, won);
+			assert_eq!(outcome.mucked[seat], !won);
+		}
+		assert!(outcome.mucked.contains(&true), "someone lost and mucked");
+		assert!(shown.contains(&2), "seat 2 always shows");
+		assert!(!outcome.mucked[2]);
+	}
+
+	#[test]
+	fn test_side_pot_is_awarded_separately() {
+		let mut agents = vec![
+			agent(vec![PlayerAction::AllIn { amount: 0.0 }], PlayerAction::Check),
+			agent(vec![PlayerAction::AllIn { amount: 0.0 }], PlayerAction::Check),
+			agent(vec![], PlayerAction::Call { amount: 0.0 }),
+		];
+		let (outcome, events) = play(&[30.0, 100.0, 200.0], &mut agents, 5);
+		let awards: Vec<(PotType, f32)> = events
+			.iter()
+			.filter_map(|e| match e {
+				GameEvent::PotAwarded { pot_type, amount, .. } => Some((*pot_type, *amount)),
+				_ => None,
+			})
+			.collect();
+		let main: f32 = awards.iter().filter(|(t, _)| *t == PotType::Main).map(|(_, a)| a).sum();
+		let side: f32 = awards.iter().filter(|(t, _)| *t == PotType::Side(1)).map(|(_, a)| a).sum();
+		assert_eq!(main, 90.0);
+		assert_eq!(side, 140.0);
+		assert_eq!(outcome.stacks.iter().sum::<f32>(), 330.0);
+	}
+
+	#[test]
+	fn test_uncalled_bet_returns_to_bettor() {
+		let mut agents = vec![
+			agent(vec![PlayerAction::Raise { amount: 100.0 }], PlayerAction::Check),
+			agent(vec![], PlayerAction::Fold),
+			agent(vec![], PlayerAction::Fold),
+		];
+		let (outcome, _events) = play(&[100.0, 100.0, 100.0], &mut agents, 6);
+		assert_eq!(outcome.stacks, vec![115.0, 95.0, 90.0]);
+	}
+
+	#[test]
+	fn test_min_raise_over_fractional_bets_counts_as_full() {
+		// 848 - 257.4135 is 590.5865 in f32, but 848 + 590.5865 rounds to
+		// 1438.5864: the min-raise comes out a hair short of the minimum
+		let mut agents = vec![
+			agent(vec![PlayerAction::Raise { amount: 257.4135 }, PlayerAction::Raise { amount: 2100.0 }], PlayerAction::Call { amount: 0.0 }),
+			agent(vec![PlayerAction::Raise { amount: 848.0 }, PlayerAction::Raise { amount: 2900.0 }], PlayerAction::Call { amount: 0.0 }),
+			agent(vec![PlayerAction::Raise { amount: 0.0 }], PlayerAction::Call { amount: 0.0 }),
+		];
+		let (_, events) = play(&[3000.0, 3000.0, 3000.0], &mut agents, 1);
+		let raises: Vec<(usize, f32)> = events
+			.iter()
+			.filter_map(|e| match e {
+				GameEvent::ActionTaken { seat, action: PlayerAction::Raise { amount }, .. } => Some((seat.0, *amount)),
+				_ => None,
+			})
+			.collect();
+		// The min-raise is the third of four, so the cap stops seat 1's fifth
+		assert_eq!(raises.len(), 4, "{:?}", raises);
+		assert_eq!(raises[3], (0, 2100.0));
+	}
+
+	#[test]
+	fn test_rake_comes_off_the_pot() {
+		let (tx, _rx) = event_queue(EventQueueConfig::unbounded());
+		let historian = EventHistorian::new(EventSender::new(tx), Arc::new(Mutex::new(Vec::new())));
+		let dealt_in = vec![true, true];
+		let positions = BlindPositions::first_hand(0, &dealt_in).unwrap();
+		let config = HandConfig {
+			hand_num: 1,
+			small_blind: 5.0,
+			big_blind: 10.0,
+			validator: ActionValidator::new(BettingStructure::NoLimit, 4),
+			rake: RakeConfig { percent: 0.1, cap: None, no_flop_no_drop: true },
+			jackpot: None,
+		};
+		let deck = Deck::shuffled(&mut StdRng::seed_from_u64(7));
+		let mut agents = vec![
+			agent(vec![], PlayerAction::Call { amount: 0.0 }),
+			agent(vec![], PlayerAction::Check),
+		];
+		let dealer = Dealer::new(config, positions, &[100.0, 100.0], &dealt_in, deck, &historian);
+		let outcome = block_on(dealer.play(&mut agents));
+		assert_eq!(outcome.stacks.iter().sum::<f32>(), 198.0);
+	}
+
+	#[test]
+	fn test_bad_beat_hits_the_jackpot() {
+		use crate::promotions::JackpotShares;
+		let c = |s: &str| Card::new(s.chars().next().unwrap(), s.chars().nth(1).unwrap());
+		let (tx, rx) = event_queue(EventQueueConfig::unbounded());
+		let historian = EventHistorian::new(EventSender::new(tx), Arc::new(Mutex::new(Vec::new())));
+		let dealt_in = vec![true, true];
+		let positions = BlindPositions::first_hand(0, &dealt_in).unwrap();
+		let jackpot = |at_least| HandConfig {
+			hand_num: 1,
+			small_blind: 5.0,
+			big_blind: 10.0,
+			validator: ActionValidator::new(BettingStructure::NoLimit, 4),
+			rake: RakeConfig::default(),
+			jackpot: Some(JackpotConfig {
+				drop: 1.0,
+				min_pot: 10.0,
+				at_least,
+				shares: JackpotShares::default(),
+				pool: "main".into(),
+			}),
+		};
+		// Seat 0 makes quads, seat 1 kings full
+		let hole = [[c("9s"), c("9h")], [c("Kd"), c("Kc")]];
+		let order = [(positions.button + 1) % 2, positions.button];
+		let mut top: Vec<Option<Card>> = (0..2).flat_map(|k| order.iter().map(move |&i| Some(hole[i][k]))).collect();
+		for street in [&["9c", "9d", "Ks"][..], &["4h"], &["2s"]] {
+			top.push(None);
+			top.extend(street.iter().map(|s| Some(c(s))));
+		}
+		let deck = || Deck::stacked(&top, &mut StdRng::seed_from_u64(9));
+		let agents = || vec![
+			agent(vec![], PlayerAction::Call { amount: 0.0 }),
+			agent(vec![], PlayerAction::Check),
+		];
+
+		let dealer = Dealer::new(jackpot(HandCategory::FullHouse), positions, &[100.0, 100.0], &dealt_in, deck(), &historian);
+		let outcome = block_on(dealer.play(&mut agents()));
+		assert_eq!(outcome.stacks.iter().sum::<f32>(), 199.0);
+		let events: Vec<GameEvent> = rx.try_iter().collect();
+		assert!(events.iter().any(|e| matches!(e, GameEvent::JackpotDrop { amount } if *amount == 1.0)));
+		let hit = events.iter().find_map(|e| match e {
+			GameEvent::BadBeatJackpot { loser, losing_hand, winners, .. } => Some((*loser, losing_hand.clone(), winners.clone())),
+			_ => None,
+		});
+		assert_eq!(hit, Some((Seat(1), "a full house".to_string(), vec![Seat(0)])));
+		// The winning hand goes out kickers and all
+		let won_with = events.iter().find_map(|e| match e {
+			GameEvent::PotAwarded { hand_description, best_five, .. } => Some((hand_description.clone(), best_five.len())),
+			_ => None,
+		});
+		assert_eq!(won_with, Some((Some("four nines, king kicker".to_string()), 5)));
+
+		// Kings full isn't enough when it takes quads
+		let dealer = Dealer::new(jackpot(HandCategory::FourOfAKind), positions, &[100.0, 100.0], &dealt_in, deck(), &historian);
+		block_on(dealer.play(&mut agents()));
+		drop(historian);
+		assert!(!rx.try_iter().any(|e| matches!(e, GameEvent::BadBeatJackpot { .. })));
+	}
+
+	#[test]
+	fn test_dead_small_blind_is_not_posted() {
+		let (tx, rx) = event_queue(EventQueueConfig::unbounded());
+		let historian = EventHistorian::new(EventSender::new(tx), Arc::new(Mutex::new(Vec::new())));
+		let first = BlindPositions::first_hand(0, &[true, true, true, true]).unwrap();
+		let dealt_in = vec![true, true, false, true];
+		let positions = first.next_hand(&dealt_in).unwrap();
+		assert!(positions.dead_small_blind);
+		let config = HandConfig {
+			hand_num: 2,
+			small_blind: 5.0,
+			big_blind: 10.0,
+			validator: ActionValidator::new(BettingStructure::NoLimit, 4),
+			rake: RakeConfig::default(),
+			jackpot: None,
+		};
+		let deck = Deck::shuffled(&mut StdRng::seed_from_u64(8));
+		let mut agents = vec![
+			agent(vec![], PlayerAction::Fold),
+			agent(vec![], PlayerAction::Fold),
+			None,
+			agent(vec![], PlayerAction::Check),
+		];
+		let dealer = Dealer::new(config, positions, &[100.0, 100.0, 0.0, 100.0], &dealt_in, deck, &historian);
+		let outcome = block_on(dealer.play(&mut agents));
+		drop(historian);
+		let blinds: Vec<(usize, BlindType)> = rx
+			.try_iter()
+			.filter_map(|e| match e {
+				GameEvent::BlindPosted { seat, blind_type, .. } => Some((seat.0, blind_type)),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(blinds, vec![(3, BlindType::Big)]);
+		assert_eq!(outcome.stacks, vec![100.0, 100.0, 0.0, 100.0]);
+	}
+
+	#[test]
+	fn test_recap_spots_a_bad_beat() {
+		let c = |s: &str| Card::new(s.chars().next().unwrap(), s.chars().nth(1).unwrap());
+		let outcome = HandOutcome {
+			stacks: vec![200.0, 0.0, 100.0],
+			hole_cards: vec![Some([c("8h"), c("8d")]), Some([c("Qs"), c("Qd")]), None],
+			folded: vec![false, false, false],
+			mucked: vec![false, false, false],
+			board: ["2h", "7c", "Kc", "9s", "8s"].into_iter().map(c).collect(),
+		};
+		let recap = outcome.recap(4, 10.0, &[100.0, 100.0, 100.0]);
+		assert!(recap.showdown);
+		assert_eq!(recap.bad_beat, Some(Seat(1)));
+		assert_eq!(recap.results, vec![(Seat(0), 100.0), (Seat(1), -100.0)]);
+
+		let fair = HandOutcome { stacks: vec![0.0, 200.0, 100.0], ..outcome };
+		assert_eq!(fair.recap(4, 10.0, &[100.0, 100.0, 100.0]).bad_beat, None);
+	}
+}
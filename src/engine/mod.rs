@@ -1,6 +1,28 @@
 mod adapter;
+mod blinds;
+mod deck;
+mod driver;
+mod equity;
+mod eval;
+mod eval_cache;
+mod hand;
 mod historian;
+#[cfg(feature = "runtime")]
+mod pause;
+mod pot;
+mod queue;
+#[cfg(feature = "runtime")]
 mod runner;
+mod session;
+mod validator;
 
-pub use adapter::BettingStructure;
-pub use runner::{GameRunner, RunnerConfig, GameHandle};
+pub use equity::{equities, equity_against_random, EquityGraph, StreetEquity};
+pub use eval::{best_five, describe_hand, describe_rank, rank_hand};
+pub use eval_cache::{canonical_key, eval_cache_stats, rank_hand_cached, EvalCache, EvalCacheStats, EVAL_CACHE_SIZE};
+pub use validator::BettingStructure;
+#[cfg(feature = "runtime")]
+pub use pause::Pause;
+#[cfg(feature = "runtime")]
+pub use runner::{Arrivals, GameRunner, RunnerConfig, GameHandle};
+pub use queue::{channel as event_queue, Backpressure, EventQueueConfig, EventReceiver, QueueMetrics, QueueSender, QueueStats};
+pub use session::{ExternalPlayer, Session, SessionConfig, Step};
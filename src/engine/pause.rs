@@ -0,0 +1,193 @@
+//! Pausing a running game, for a break at a home game or a look around
+//! while debugging. The runner waits out a pause before each action
+//! request and before dealing the next hand. A request already out is
+//! left to be answered; after that nobody is asked to act, so no action
+//! clock runs, until the game is resumed.
+//!
+//! A paused game can also be stepped, one hold at a time, for working on
+//! the engine: `Pause::step` lets it go on to the next action request or
+//! hand, and `Pause::dump` describes where it's held.
+
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use async_trait::async_trait;
+use tokio::sync::Notify;
+
+use crate::engine::hand::{Agent, HandState};
+use crate::engine::historian::EventSender;
+use crate::events::{GameEvent, PlayerAction, RaiseOptions, Seat, ValidActions};
+use crate::money::chips;
+
+/// The switch for one game. Clones share it.
+#[derive(Clone, Default)]
+pub struct Pause {
+	inner: Arc<PauseState>,
+}
+
+#[derive(Default)]
+struct PauseState {
+	paused: AtomicBool,
+	/// Holds to let through while paused, one per `step`
+	steps: AtomicU32,
+	changed: Notify,
+	/// Where the game was last held, for `dump`
+	held_at: Mutex<String>,
+}
+
+impl Pause {
+	/// Pauses or resumes the game; true if that changed anything.
+	pub fn set(&self, paused: bool) -> bool {
+		let changed = self.inner.paused.swap(paused, Ordering::SeqCst) != paused;
+		if changed {
+			// Steps left over from before aren't carried into the next pause
+			self.inner.steps.store(0, Ordering::SeqCst);
+			self.inner.changed.notify_waiters();
+		}
+		changed
+	}
+
+	pub fn is_paused(&self) -> bool {
+		self.inner.paused.load(Ordering::SeqCst)
+	}
+
+	/// Lets a paused game go on as far as its next hold, the next action
+	/// request or hand, and stop there again. Steps add up; while the game
+	/// isn't paused this does nothing.
+	pub fn step(&self) {
+		if self.is_paused() {
+			self.inner.steps.fetch_add(1, Ordering::SeqCst);
+			self.inner.changed.notify_waiters();
+		}
+	}
+
+	/// Where the game was last held while paused: the hand, pots, stacks
+	/// and who it's waiting on, or the stacks between hands. Empty if it
+	/// hasn't been held yet.
+	pub fn dump(&self) -> String {
+		self.inner.held_at.lock().unwrap_or_else(|e| e.into_inner()).clone()
+	}
+
+	/// Notes where the game is, for `dump`, if it's about to be held.
+	pub(crate) fn note(&self, describe: impl FnOnce() -> String) {
+		if self.is_paused() {
+			*self.inner.held_at.lock().unwrap_or_else(|e| e.into_inner()) = describe();
+		}
+	}
+
+	fn take_step(&self) -> bool {
+		self.inner.steps.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).is_ok()
+	}
+
+	/// Waits out a pause, if there is one, telling the table when the game
+	/// stops and when it starts again. A step goes straight through if it
+	/// came first, or ends the wait like a resume.
+	pub(crate) async fn hold(&self, events: &EventSender) {
+		if !self.is_paused() || self.take_step() {
+			return;
+		}
+		let _ = events.send(GameEvent::GamePaused { at: None });
+		loop {
+			// Made before the check, so a resume in between still wakes it
+			let changed = self.inner.changed.notified();
+			if !self.is_paused() || self.take_step() {
+				break;
+			}
+			changed.await;
+		}
+		let _ = events.send(GameEvent::GameResumed { at: None });
+	}
+}
+
+/// The hand as the dealer has it, for `Pause::dump`.
+fn describe_hand(state: &HandState, names: &[String], seat: Seat, waiting_for: &str) -> String {
+	let mut out = format!("Hand {}, {:?}", state.hand_num, state.street);
+	if !state.board.is_empty() {
+		let board = state.board.iter().map(|c| format!("{}{}", c.rank, c.suit)).collect::<Vec<_>>();
+		let _ = write!(out, ": {}", board.join(" "));
+	}
+	// Mid-street `pots()` splits at every bet size, so each seat's share is
+	// shown instead
+	let _ = write!(out, "\nPot {}, bet {}, min raise {}, raises {}/{}",
+		chips(state.pot()), chips(state.current_bet), chips(state.min_raise), state.raises_this_street, state.max_raises);
+	for (idx, s) in state.seats.iter().enumerate().filter(|(_, s)| s.dealt_in) {
+		let name = names.get(idx).map(String::as_str).unwrap_or("");
+		let status = if s.folded { " folded" } else if s.all_in { " all in" } else { "" };
+		let marker = if idx == seat.0 { "> " } else { "  " };
+		let _ = write!(out, "\n{}Seat {} {:<12} stack {:<8} bet {:<8} in {}{}",
+			marker, idx, name, chips(s.stack), chips(s.street_bet), chips(state.contribution(idx)), status);
+	}
+	let name = names.get(seat.0).map(String::as_str).unwrap_or("");
+	let _ = write!(out, "\nWaiting on seat {} ({}): {}", seat.0, name, waiting_for);
+	out
+}
+
+/// What a seat may do, like "fold, call 20, raise 40-500".
+fn describe_options(valid: &ValidActions) -> String {
+	let mut options = Vec::new();
+	if valid.can_fold {
+		options.push("fold".to_string());
+	}
+	if valid.can_check {
+		options.push("check".to_string());
+	}
+	if let Some(amount) = valid.call_amount {
+		options.push(format!("call {}", chips(amount)));
+	}
+	match valid.raise_options {
+		Some(RaiseOptions::Fixed { amount }) => options.push(format!("raise {}", chips(amount))),
+		Some(RaiseOptions::Variable { min_raise, max_raise }) => {
+			options.push(format!("raise {}-{}", chips(min_raise), chips(max_raise)));
+		}
+		None => {}
+	}
+	if valid.can_all_in {
+		options.push(format!("all in {}", chips(valid.all_in_amount)));
+	}
+	options.join(", ")
+}
+
+/// The stacks between hands, for `Pause::dump`.
+pub(crate) fn describe_table(hand_num: u32, seats: &[(usize, String, f32)]) -> String {
+	let mut out = format!("Before hand {}", hand_num);
+	for (idx, name, stack) in seats {
+		let _ = write!(out, "\n  Seat {} {:<12} stack {}", idx, name, chips(*stack));
+	}
+	out
+}
+
+/// Sits in front of a seat's agent and holds its requests while the game
+/// is paused.
+pub(crate) struct Pausable {
+	agent: Box<dyn Agent>,
+	seat: Seat,
+	names: Arc<[String]>,
+	pause: Pause,
+	events: EventSender,
+}
+
+impl Pausable {
+	pub(crate) fn new(agent: Box<dyn Agent>, seat: Seat, names: Arc<[String]>, pause: Pause, events: EventSender) -> Self {
+		Self { agent, seat, names, pause, events }
+	}
+}
+
+#[async_trait]
+impl Agent for Pausable {
+	async fn act(&mut self, state: &HandState, valid: &ValidActions) -> PlayerAction {
+		self.pause.note(|| describe_hand(state, &self.names, self.seat, &describe_options(valid)));
+		self.pause.hold(&self.events).await;
+		self.agent.act(state, valid).await
+	}
+
+	fn mucks_losers(&self) -> bool {
+		self.agent.mucks_losers()
+	}
+
+	async fn shows_uncalled(&mut self, state: &HandState) -> bool {
+		self.pause.note(|| describe_hand(state, &self.names, self.seat, "show or muck"));
+		self.pause.hold(&self.events).await;
+		self.agent.shows_uncalled(state).await
+	}
+}
@@ -0,0 +1,222 @@
+#[derive(Clone)]
+pub struct RakeConfig {
+	pub percent: f32,
+	pub cap: Option<f32>,
+	pub no_flop_no_drop: bool,
+}
+
+impl Default for RakeConfig {
+	fn default() -> Self {
+		Self {
+			percent: 0.0,
+			cap: None,
+			no_flop_no_drop: false,
+		}
+	}
+}
+
+impl RakeConfig {
+	/// Rake on `amount` of called chips, rounded to the cent.
+	pub fn rake_for(&self, amount: f32, saw_flop: bool) -> f32 {
+		if self.percent <= 0.0 || (self.no_flop_no_drop && !saw_flop) {
+			return 0.0;
+		}
+		let mut rake = amount * self.percent;
+		if let Some(cap) = self.cap {
+			rake = rake.min(cap);
+		}
+		(rake * 100.0).round() / 100.0
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pot {
+	pub amount: f32,
+	pub eligible: Vec<usize>,
+}
+
+/// Tracks what each seat has put in over the whole hand and splits it into
+/// a main pot and side pots.
+#[derive(Debug, Clone)]
+pub struct PotManager {
+	contributions: Vec<f32>,
+	folded: Vec<bool>,
+}
+
+impl PotManager {
+	pub fn new(num_seats: usize) -> Self {
+		Self {
+			contributions: vec![0.0; num_seats],
+			folded: vec![false; num_seats],
+		}
+	}
+
+	pub fn add(&mut self, idx: usize, amount: f32) {
+		self.contributions[idx] += amount;
+	}
+
+	pub fn fold(&mut self, idx: usize) {
+		self.folded[idx] = true;
+	}
+
+	/// What one seat has put in this hand.
+	pub fn contribution(&self, idx: usize) -> f32 {
+		self.contributions[idx]
+	}
+
+	pub fn total(&self) -> f32 {
+		self.contributions.iter().sum()
+	}
+
+	/// The part of the largest contribution nobody matched.
+	pub fn uncalled(&self) -> f32 {
+		let mut sorted = self.contributions.clone();
+		sorted.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+		match sorted.as_slice() {
+			[first, second, ..] => first - second,
+			[first] => *first,
+			[] => 0.0,
+		}
+	}
+
+	/// Main pot first, then side pots in order. Each level is capped by an
+	/// all-in amount and only seats that put in that much are eligible.
+	/// Chips from folded seats above the highest live level go to the last pot.
+	pub fn pots(&self) -> Vec<Pot> {
+		let mut levels: Vec<f32> = self
+			.contributions
+			.iter()
+			.enumerate()
+			.filter(|(i, c)| !self.folded[*i] && **c > 0.0)
+			.map(|(_, c)| *c)
+			.collect();
+		levels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+		levels.dedup();
+
+		let mut pots: Vec<Pot> = Vec::new();
+		let mut prev = 0.0;
+		for level in levels {
+			let amount: f32 = self
+				.contributions
+				.iter()
+				.map(|c| (c.min(level) - prev).max(0.0))
+				.sum();
+			let eligible: Vec<usize> = (0..self.contributions.len())
+				.filter(|&i| !self.folded[i] && self.contributions[i] >= level)
+				.collect();
+			if amount > 0.0 {
+				pots.push(Pot { amount, eligible });
+			}
+			prev = level;
+		}
+
+		let leftover: f32 = self.contributions.iter().map(|c| (c - prev).max(0.0)).sum();
+		if leftover > 0.0 {
+			match pots.last_mut() {
+				Some(pot) => pot.amount += leftover,
+				None => pots.push(Pot {
+					amount: leftover,
+					eligible: Vec::new(),
+				}),
+			}
+		}
+
+		pots
+	}
+}
+
+/// The smallest amount a table deals in once rake or cents come into it:
+/// rake is rounded to the cent and money is shown to it.
+pub const CHIP_UNIT: f32 = 0.01;
+
+/// Splits `amount` between `winners`, in order, in whole units of `unit`.
+/// Only what doesn't divide evenly goes to the first winner.
+pub fn split_pot(amount: f32, winners: &[usize], unit: f32) -> Vec<(usize, f32)> {
+	if winners.is_empty() {
+		return Vec::new();
+	}
+	let n = winners.len() as f32;
+	// The nudge keeps a share like 0.3 / 0.01 from flooring to 29 units
+	let share = (amount / n / unit + 1e-3).floor() * unit;
+	let first = amount - share * (n - 1.0);
+	winners
+		.iter()
+		.enumerate()
+		.map(|(i, &w)| (w, if i == 0 { first } else { share }))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_single_pot() {
+		let mut pm = PotManager::new(3);
+		pm.add(0, 10.0);
+		pm.add(1, 10.0);
+		pm.add(2, 10.0);
+		let pots = pm.pots();
+		assert_eq!(pots, vec![Pot { amount: 30.0, eligible: vec![0, 1, 2] }]);
+	}
+
+	#[test]
+	fn test_side_pot_for_short_all_in() {
+		let mut pm = PotManager::new(3);
+		pm.add(0, 50.0);
+		pm.add(1, 100.0);
+		pm.add(2, 100.0);
+		let pots = pm.pots();
+		assert_eq!(pots.len(), 2);
+		assert_eq!(pots[0], Pot { amount: 150.0, eligible: vec![0, 1, 2] });
+		assert_eq!(pots[1], Pot { amount: 100.0, eligible: vec![1, 2] });
+	}
+
+	#[test]
+	fn test_folded_chips_stay_in_pot() {
+		let mut pm = PotManager::new(3);
+		pm.add(0, 20.0);
+		pm.add(1, 60.0);
+		pm.add(2, 100.0);
+		pm.fold(1);
+		let pots = pm.pots();
+		assert_eq!(pots.len(), 2);
+		assert_eq!(pots[0], Pot { amount: 60.0, eligible: vec![0, 2] });
+		assert_eq!(pots[1], Pot { amount: 120.0, eligible: vec![2] });
+		let total: f32 = pots.iter().map(|p| p.amount).sum();
+		assert_eq!(total, pm.total());
+	}
+
+	#[test]
+	fn test_uncalled_bet() {
+		let mut pm = PotManager::new(3);
+		pm.add(0, 10.0);
+		pm.add(1, 40.0);
+		pm.add(2, 10.0);
+		assert_eq!(pm.uncalled(), 30.0);
+	}
+
+	#[test]
+	fn test_split_pot_odd_chip_to_first() {
+		let shares = split_pot(15.0, &[2, 0], 1.0);
+		assert_eq!(shares, vec![(2, 8.0), (0, 7.0)]);
+	}
+
+	#[test]
+	fn test_split_fractional_pot_in_cents() {
+		let cents = |shares: Vec<(usize, f32)>| shares.into_iter().map(|(w, a)| (w, (a / CHIP_UNIT).round() as u32)).collect::<Vec<_>>();
+		assert_eq!(cents(split_pot(21.85, &[1, 3], CHIP_UNIT)), vec![(1, 1093), (3, 1092)]);
+		assert_eq!(cents(split_pot(10.0, &[0, 1, 2], CHIP_UNIT)), vec![(0, 334), (1, 333), (2, 333)]);
+		assert_eq!(cents(split_pot(0.6, &[0, 1], CHIP_UNIT)), vec![(0, 30), (1, 30)]);
+		let total: f32 = split_pot(21.85, &[1, 3], CHIP_UNIT).iter().map(|(_, a)| a).sum();
+		assert_eq!(total, 21.85);
+	}
+
+	#[test]
+	fn test_rake() {
+		let rake = RakeConfig { percent: 0.05, cap: Some(3.0), no_flop_no_drop: true };
+		assert_eq!(rake.rake_for(40.0, true), 2.0);
+		assert_eq!(rake.rake_for(200.0, true), 3.0);
+		assert_eq!(rake.rake_for(200.0, false), 0.0);
+	}
+}
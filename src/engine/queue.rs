@@ -0,0 +1,322 @@
+//! The engine's event channel. It holds up to `capacity` events before it
+//! pushes back on the engine, in one of two ways: holding the engine
+//! between hands until whoever reads the events has caught up, or
+//! dropping cosmetic events (dealer chat) while it's full. Either way a
+//! hand in play is never held up halfway, since a player may be waiting
+//! on an event still in the queue. `QueueMetrics` reports how full it
+//! runs.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::events::GameEvent;
+
+/// What the queue does once it's full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backpressure {
+	/// Holds the engine before the next hand until the queue is below
+	/// capacity again. Nothing is lost.
+	#[default]
+	BlockBetweenHands,
+	/// Never holds the engine; dealer chat is dropped while the queue is
+	/// full.
+	DropCosmetic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EventQueueConfig {
+	pub capacity: usize,
+	pub backpressure: Backpressure,
+}
+
+impl Default for EventQueueConfig {
+	fn default() -> Self {
+		Self { capacity: 4096, backpressure: Backpressure::BlockBetweenHands }
+	}
+}
+
+impl EventQueueConfig {
+	/// A queue that never pushes back, for a `Session`, which the same
+	/// thread drains between steps.
+	pub fn unbounded() -> Self {
+		Self { capacity: usize::MAX, backpressure: Backpressure::DropCosmetic }
+	}
+}
+
+/// How full the queue has run, as of now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueueStats {
+	pub capacity: usize,
+	/// Events waiting to be read.
+	pub depth: usize,
+	/// The most that have been waiting at once.
+	pub peak: usize,
+	/// Cosmetic events dropped while the queue was full.
+	pub dropped: usize,
+	/// Times the engine was held between hands.
+	pub stalls: usize,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+	mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+struct State {
+	events: VecDeque<GameEvent>,
+	senders: usize,
+	receiving: bool,
+}
+
+struct Shared {
+	config: EventQueueConfig,
+	state: Mutex<State>,
+	/// Signalled when an event arrives or the last sender goes.
+	arrived: Condvar,
+	/// Signalled when an event is read or the receiver goes.
+	taken: Condvar,
+	peak: AtomicUsize,
+	dropped: AtomicUsize,
+	stalls: AtomicUsize,
+}
+
+impl Shared {
+	fn take(&self, state: &mut State) -> Option<GameEvent> {
+		let event = state.events.pop_front();
+		if event.is_some() {
+			self.taken.notify_all();
+		}
+		event
+	}
+}
+
+/// A new queue's two ends.
+pub fn channel(config: EventQueueConfig) -> (QueueSender, EventReceiver) {
+	let shared = Arc::new(Shared {
+		config,
+		state: Mutex::new(State { events: VecDeque::new(), senders: 1, receiving: true }),
+		arrived: Condvar::new(),
+		taken: Condvar::new(),
+		peak: AtomicUsize::new(0),
+		dropped: AtomicUsize::new(0),
+		stalls: AtomicUsize::new(0),
+	});
+	(QueueSender { shared: Arc::clone(&shared) }, EventReceiver { shared })
+}
+
+/// The engine's end.
+pub struct QueueSender {
+	shared: Arc<Shared>,
+}
+
+impl QueueSender {
+	/// Queues `event`, unless the queue is full and drops cosmetic events.
+	/// Fails only once the receiver is gone.
+	pub fn send(&self, event: GameEvent) -> Result<(), SendError<GameEvent>> {
+		let shared = &self.shared;
+		let mut state = lock(&shared.state);
+		if !state.receiving {
+			return Err(SendError(event));
+		}
+		let full = state.events.len() >= shared.config.capacity;
+		if full && shared.config.backpressure == Backpressure::DropCosmetic && event.is_cosmetic() {
+			shared.dropped.fetch_add(1, Ordering::Relaxed);
+			return Ok(());
+		}
+		state.events.push_back(event);
+		shared.peak.fetch_max(state.events.len(), Ordering::Relaxed);
+		shared.arrived.notify_one();
+		Ok(())
+	}
+
+	/// Called between hands: with `BlockBetweenHands`, waits until the
+	/// queue is below capacity or nobody is reading any more.
+	pub fn wait_for_room(&self) {
+		let shared = &self.shared;
+		if shared.config.backpressure != Backpressure::BlockBetweenHands {
+			return;
+		}
+		let mut state = lock(&shared.state);
+		if state.events.len() < shared.config.capacity {
+			return;
+		}
+		shared.stalls.fetch_add(1, Ordering::Relaxed);
+		while state.receiving && state.events.len() >= shared.config.capacity {
+			state = shared.taken.wait(state).unwrap_or_else(|e| e.into_inner());
+		}
+	}
+
+	pub fn metrics(&self) -> QueueMetrics {
+		QueueMetrics { shared: Arc::clone(&self.shared) }
+	}
+}
+
+impl Clone for QueueSender {
+	fn clone(&self) -> Self {
+		lock(&self.shared.state).senders += 1;
+		Self { shared: Arc::clone(&self.shared) }
+	}
+}
+
+impl Drop for QueueSender {
+	fn drop(&mut self) {
+		lock(&self.shared.state).senders -= 1;
+		self.shared.arrived.notify_all();
+	}
+}
+
+/// The reading end, with the same calls as an `mpsc::Receiver`.
+pub struct EventReceiver {
+	shared: Arc<Shared>,
+}
+
+impl EventReceiver {
+	/// Waits for the next event; fails once the queue is empty and the
+	/// engine is gone.
+	pub fn recv(&self) -> Result<GameEvent, RecvError> {
+		let shared = &self.shared;
+		let mut state = lock(&shared.state);
+		loop {
+			if let Some(event) = shared.take(&mut state) {
+				return Ok(event);
+			}
+			if state.senders == 0 {
+				return Err(RecvError);
+			}
+			state = shared.arrived.wait(state).unwrap_or_else(|e| e.into_inner());
+		}
+	}
+
+	pub fn try_recv(&self) -> Result<GameEvent, TryRecvError> {
+		let shared = &self.shared;
+		let mut state = lock(&shared.state);
+		match shared.take(&mut state) {
+			Some(event) => Ok(event),
+			None if state.senders == 0 => Err(TryRecvError::Disconnected),
+			None => Err(TryRecvError::Empty),
+		}
+	}
+
+	pub fn recv_timeout(&self, timeout: Duration) -> Result<GameEvent, RecvTimeoutError> {
+		let shared = &self.shared;
+		let until = Instant::now() + timeout;
+		let mut state = lock(&shared.state);
+		loop {
+			if let Some(event) = shared.take(&mut state) {
+				return Ok(event);
+			}
+			if state.senders == 0 {
+				return Err(RecvTimeoutError::Disconnected);
+			}
+			let left = until.saturating_duration_since(Instant::now());
+			if left.is_zero() {
+				return Err(RecvTimeoutError::Timeout);
+			}
+			state = shared.arrived.wait_timeout(state, left).unwrap_or_else(|e| e.into_inner()).0;
+		}
+	}
+
+	/// Every event until the engine is gone.
+	pub fn iter(&self) -> impl Iterator<Item = GameEvent> + '_ {
+		std::iter::from_fn(|| self.recv().ok())
+	}
+
+	/// The events waiting now.
+	pub fn try_iter(&self) -> impl Iterator<Item = GameEvent> + '_ {
+		std::iter::from_fn(|| self.try_recv().ok())
+	}
+
+	pub fn metrics(&self) -> QueueMetrics {
+		QueueMetrics { shared: Arc::clone(&self.shared) }
+	}
+}
+
+impl Drop for EventReceiver {
+	fn drop(&mut self) {
+		lock(&self.shared.state).receiving = false;
+		self.shared.taken.notify_all();
+	}
+}
+
+/// A look at the queue from outside, for logs and status displays.
+#[derive(Clone)]
+pub struct QueueMetrics {
+	shared: Arc<Shared>,
+}
+
+impl QueueMetrics {
+	pub fn stats(&self) -> QueueStats {
+		let shared = &self.shared;
+		QueueStats {
+			capacity: shared.config.capacity,
+			depth: lock(&shared.state).events.len(),
+			peak: shared.peak.load(Ordering::Relaxed),
+			dropped: shared.dropped.load(Ordering::Relaxed),
+			stalls: shared.stalls.load(Ordering::Relaxed),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::ChatSender;
+	use std::thread;
+
+	fn chat(text: &str) -> GameEvent {
+		GameEvent::ChatMessage { sender: ChatSender::Dealer, text: text.to_string() }
+	}
+
+	fn paused() -> GameEvent {
+		GameEvent::GamePaused { at: None }
+	}
+
+	#[test]
+	fn test_full_queue_drops_only_cosmetic_events() {
+		let (tx, rx) = channel(EventQueueConfig { capacity: 2, backpressure: Backpressure::DropCosmetic });
+		tx.send(paused()).unwrap();
+		tx.send(chat("one")).unwrap();
+		tx.send(chat("two")).unwrap();
+		tx.send(paused()).unwrap();
+
+		let stats = rx.metrics().stats();
+		assert_eq!((stats.depth, stats.peak, stats.dropped), (3, 3, 1));
+		let events = rx.try_iter().collect::<Vec<_>>();
+		assert!(matches!(events[1], GameEvent::ChatMessage { ref text, .. } if text == "one"));
+		assert!(matches!(events[2], GameEvent::GamePaused { .. }));
+	}
+
+	#[test]
+	fn test_engine_waits_between_hands_until_the_reader_catches_up() {
+		let (tx, rx) = channel(EventQueueConfig { capacity: 2, backpressure: Backpressure::BlockBetweenHands });
+		for _ in 0..3 {
+			tx.send(paused()).unwrap();
+		}
+		let engine = thread::spawn(move || {
+			tx.wait_for_room();
+			tx.send(chat("next hand")).unwrap();
+		});
+		while rx.metrics().stats().stalls == 0 {
+			thread::yield_now();
+		}
+		let events = rx.iter().collect::<Vec<_>>();
+		engine.join().unwrap();
+		assert_eq!(events.len(), 4);
+		assert_eq!(rx.metrics().stats().dropped, 0);
+	}
+
+	#[test]
+	fn test_nobody_reading_frees_the_engine() {
+		let (tx, rx) = channel(EventQueueConfig { capacity: 1, backpressure: Backpressure::BlockBetweenHands });
+		tx.send(paused()).unwrap();
+		drop(rx);
+		tx.wait_for_room();
+		assert!(tx.send(paused()).is_err());
+	}
+}
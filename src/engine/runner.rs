@@ -1,21 +1,26 @@
-use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::collections::HashSet;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
-use rs_poker::arena::{Agent, GameState, HoldemSimulationBuilder};
 use tokio::runtime::Handle;
 
-use crate::events::{
-	Blinds, BettingStructure as EventBettingStructure, GameConfig, GameEndReason, GameEvent,
-	GameId, HandId, HandResult, Position, Seat, SeatInfo, Standing,
-};
+use crate::events::{BettingStructure as EventBettingStructure, GameConfig, GameEndReason, GameEvent, GameId, Seat};
 use crate::logging;
-use crate::players::{ActionRecord, PlayerPort};
-use crate::engine::adapter::{BettingStructure, PlayerAdapter};
-use std::collections::HashSet;
-
-use crate::engine::historian::{EventHistorian, RakeConfig};
-use crate::table::BlindClock;
+use crate::players::PlayerPort;
+use crate::promotions::JackpotConfig;
+use crate::engine::deck::Deck;
+use crate::engine::driver::{HandDriver, HandRules};
+use crate::engine::historian::EventSender;
+use crate::engine::pause::{describe_table, Pausable, Pause};
+use crate::engine::pot::RakeConfig;
+use crate::engine::queue::{self, EventQueueConfig, EventReceiver, QueueMetrics};
+use crate::engine::validator::BettingStructure;
+use crate::table::{
+	BettingStructure as TableBettingStructure, BlindClock, GameFormat, GameRotation, MixedGame, TableConfig,
+};
 
 fn lock_mutex<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
 	mutex.lock().unwrap_or_else(|e| e.into_inner())
@@ -24,29 +29,38 @@ fn lock_mutex<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
 pub struct GameRunner {
 	game_id: GameId,
 	config: RunnerConfig,
-	players: Vec<Option<Arc<dyn PlayerPort>>>,
-	event_tx: mpsc::Sender<GameEvent>,
-	action_history: Arc<Mutex<Vec<ActionRecord>>>,
+	driver: HandDriver,
 	blind_clock: Option<BlindClock>,
-	rng: StdRng,
+	game_rotation: Option<GameRotation>,
 	runtime_handle: Handle,
 	quit_signal: Arc<AtomicBool>,
-	sitting_out: Arc<Mutex<HashSet<Seat>>>,
+	leaving: Arc<Mutex<HashSet<Seat>>>,
+	arrivals: Arrivals,
+	pause: Pause,
 }
 
+/// Players waiting to sit down at the next hand, each with their stack.
+pub type Arrivals = Arc<Mutex<Vec<(Arc<dyn PlayerPort>, f32)>>>;
+
 pub struct RunnerConfig {
 	pub small_blind: f32,
 	pub big_blind: f32,
 	pub starting_stack: f32,
 	pub betting_structure: BettingStructure,
 	pub blind_clock: Option<BlindClock>,
+	pub game_rotation: Option<GameRotation>,
 	pub max_raises_per_round: u32,
 	pub rake_percent: f32,
 	pub rake_cap: Option<f32>,
 	pub no_flop_no_drop: bool,
+	pub jackpot: Option<JackpotConfig>,
 	pub max_hands: Option<u32>,
 	pub seed: Option<u64>,
+	/// Chosen ahead by a caller that needs it before the game starts;
+	/// `None` draws one.
+	pub game_id: Option<GameId>,
 	pub max_seats: Option<usize>,  // None = compact (tournaments), Some(n) = fixed (cash)
+	pub action_timeout: Option<Duration>,
 }
 
 impl Default for RunnerConfig {
@@ -57,88 +71,185 @@ impl Default for RunnerConfig {
 			starting_stack: 500.0,
 			betting_structure: BettingStructure::NoLimit,
 			blind_clock: None,
+			game_rotation: None,
 			max_raises_per_round: 4,
 			rake_percent: 0.0,
 			rake_cap: None,
 			no_flop_no_drop: false,
+			jackpot: None,
 			max_hands: None,
 			seed: None,
+			game_id: None,
 			max_seats: None,
+			action_timeout: None,
+		}
+	}
+}
+
+impl RunnerConfig {
+	/// Runner settings for a configured table. Cash tables keep fixed seats;
+	/// tournaments seat players compactly.
+	pub fn from_table(table: &TableConfig) -> Self {
+		let (small_blind, big_blind) = table.current_blinds();
+
+		let max_seats = match table.format {
+			GameFormat::Cash => Some(table.max_players),
+			GameFormat::SitNGo => None,
+		};
+
+		Self {
+			small_blind,
+			big_blind,
+			starting_stack: table.effective_starting_stack(),
+			betting_structure: BettingStructure::from(table.betting),
+			blind_clock: None,
+			game_rotation: GameRotation::from_table(table),
+			max_raises_per_round: table.max_raises_per_round,
+			rake_percent: table.rake_percent,
+			rake_cap: table.rake_cap,
+			no_flop_no_drop: table.no_flop_no_drop,
+			jackpot: table.promotions.as_ref().and_then(|p| p.jackpot.clone()),
+			max_hands: None,
+			seed: table.seed,
+			game_id: None,
+			max_seats,
+			action_timeout: table.action_timeout_seconds.map(|s| Duration::from_secs(s as u64)),
 		}
 	}
 }
 
 pub struct GameHandle {
-	pub event_rx: mpsc::Receiver<GameEvent>,
+	pub event_rx: EventReceiver,
+	/// How full the event queue runs
+	pub queue: QueueMetrics,
 	pub game_id: GameId,
 	pub quit_signal: Arc<AtomicBool>,
 	pub sitting_out: Arc<Mutex<HashSet<Seat>>>,
+	/// Seats to cash out once the current hand is over
+	pub leaving: Arc<Mutex<HashSet<Seat>>>,
+	/// Fixed-seat games only: players to seat in empty seats between hands
+	pub arrivals: Arrivals,
+	/// Holds the game before its next action or hand while set
+	pub pause: Pause,
 }
 
 impl GameRunner {
 	pub fn new(config: RunnerConfig, runtime_handle: Handle) -> (Self, GameHandle) {
-		let (event_tx, event_rx) = mpsc::channel();
+		Self::with_queue(config, runtime_handle, EventQueueConfig::default())
+	}
+
+	/// Like `new`, with the event queue's capacity and what it does when
+	/// it's full.
+	pub fn with_queue(config: RunnerConfig, runtime_handle: Handle, queue: EventQueueConfig) -> (Self, GameHandle) {
+		let (event_tx, event_rx) = queue::channel(queue);
+		let queue = event_rx.metrics();
 		let blind_clock = config.blind_clock.clone();
+		let game_rotation = config.game_rotation.clone();
 
 		let mut rng = match config.seed {
 			Some(s) => StdRng::seed_from_u64(s),
 			None => StdRng::from_os_rng(),
 		};
 
-		let game_id = GameId(rng.random());
+		let game_id = config.game_id.unwrap_or_else(|| GameId(rng.random()));
 		let quit_signal = Arc::new(AtomicBool::new(false));
 		let sitting_out = Arc::new(Mutex::new(HashSet::new()));
-
-		let players = match config.max_seats {
-			Some(n) => vec![None; n],
-			None => Vec::new(),
+		let leaving = Arc::new(Mutex::new(HashSet::new()));
+		let arrivals: Arrivals = Arc::default();
+		let pause = Pause::default();
+
+		let rules = HandRules {
+			max_raises_per_round: config.max_raises_per_round,
+			rake: RakeConfig {
+				percent: config.rake_percent,
+				cap: config.rake_cap,
+				no_flop_no_drop: config.no_flop_no_drop,
+			},
+			jackpot: config.jackpot.clone(),
+			action_timeout: config.action_timeout,
 		};
+		let driver = HandDriver::new(Some(game_id), rules, rng, EventSender::new(event_tx))
+			.with_seats(config.max_seats.unwrap_or(0))
+			.with_sitting_out(Arc::clone(&sitting_out));
 
 		let runner = Self {
 			game_id,
 			config,
-			players,
-			event_tx,
-			action_history: Arc::new(Mutex::new(Vec::new())),
+			driver,
 			blind_clock,
-			rng,
+			game_rotation,
 			runtime_handle,
 			quit_signal: Arc::clone(&quit_signal),
-			sitting_out: Arc::clone(&sitting_out),
+			leaving: Arc::clone(&leaving),
+			arrivals: Arc::clone(&arrivals),
+			pause: pause.clone(),
 		};
 
 		let handle = GameHandle {
 			event_rx,
+			queue,
 			game_id,
 			quit_signal,
 			sitting_out,
+			leaving,
+			arrivals,
+			pause,
 		};
 
 		(runner, handle)
 	}
 
 	pub fn add_player(&mut self, player: Arc<dyn PlayerPort>) {
-		let seat = player.seat();
+		let stack = self.config.starting_stack;
+		self.add_player_with_stack(player, stack);
+	}
 
-		self.emit(GameEvent::PlayerJoined {
-			seat,
-			name: player.name().to_string(),
-			stack: self.config.starting_stack,
-			is_human: player.is_human(),
-		});
+	/// Like `add_player`, for someone bringing more or less than the
+	/// starting stack.
+	pub fn add_player_with_stack(&mut self, player: Arc<dyn PlayerPort>, stack: f32) {
+		// Fixed seats keep the player's seat; compact seating appends
+		let slot = match self.config.max_seats {
+			Some(_) => player.seat().0,
+			None => self.driver.players.len(),
+		};
+		self.driver.sit(slot, player, stack);
+	}
 
-		if self.config.max_seats.is_some() {
-			// Fixed-seat mode: insert at seat position
-			self.players[seat.0] = Some(player);
-		} else {
-			// Compact mode: append
-			self.players.push(Some(player));
+	/// Sits a player down in an empty fixed seat with the chips they
+	/// brought from another table.
+	fn seat_arrival(&mut self, player: Arc<dyn PlayerPort>, stack: f32) {
+		let seat = player.seat();
+		let driver = &self.driver;
+		let taken = driver.players.get(seat.0)
+			.and_then(|p| p.as_ref())
+			.is_some_and(|_| driver.stacks[seat.0] > 0.0 && !lock_mutex(&driver.sitting_out).contains(&seat));
+		if self.config.max_seats.is_none() || seat.0 >= driver.players.len() || taken {
+			logging::log("Engine", "WARN", &format!("No seat {} for {}", seat.0, player.name()));
+			return;
 		}
+
+		lock_mutex(&self.driver.sitting_out).remove(&seat);
+		self.driver.sit(seat.0, player, stack);
 	}
 
+	/// Plays the game out. A panic in the engine ends it with
+	/// `GameEnded { reason: Error }` saying what happened, rather than
+	/// taking down the thread and leaving whoever is watching waiting.
 	pub fn run(&mut self) {
-		let num_slots = self.players.len();
-		let occupied_count = self.players.iter().filter(|p| p.is_some()).count();
+		if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| self.play())) {
+			let detail = format!("The engine failed: {}", logging::panic_message(payload.as_ref()));
+			logging::log("Engine", "ERROR", &detail);
+			self.emit(GameEvent::GameEnded {
+				reason: GameEndReason::Error,
+				final_standings: vec![],
+				detail: Some(detail),
+			});
+		}
+	}
+
+	fn play(&mut self) {
+		let num_slots = self.driver.players.len();
+		let occupied_count = self.driver.occupied();
 		
 		logging::log(
 			"Engine",
@@ -152,14 +263,14 @@ impl GameRunner {
 		);
 		
 		if occupied_count < 2 {
-			self.emit(GameEvent::GameEnded {
-				reason: GameEndReason::Error,
-				final_standings: vec![],
-			});
+			self.driver.end_game(
+				GameEndReason::Error,
+				Some(format!("A game needs at least two players; {} sat down", occupied_count)),
+			);
 			return;
 		}
 
-		self.emit(GameEvent::GameCreated {
+		let created = GameEvent::GameCreated {
 			game_id: self.game_id,
 			config: GameConfig {
 				betting_structure: self.convert_betting_structure(),
@@ -169,80 +280,50 @@ impl GameRunner {
 				max_players: num_slots,
 				time_bank: None,
 			},
-		});
+		};
+		self.emit(created.clone());
 
 		logging::set_game_id(self.game_id.0);
 
-		for player in self.players.iter().flatten() {
-			player.notify(&GameEvent::GameCreated {
-				game_id: self.game_id,
-				config: GameConfig {
-					betting_structure: self.convert_betting_structure(),
-					small_blind: self.config.small_blind,
-					big_blind: self.config.big_blind,
-					starting_stack: self.config.starting_stack,
-					max_players: num_slots,
-					time_bank: None,
-				},
-			});
+		for player in self.driver.players.iter().flatten() {
+			player.notify(&created);
 		}
 
-		let seat_infos: Vec<SeatInfo> = self
-			.players
-			.iter()
-			.enumerate()
-			.map(|(i, opt)| {
-				match opt {
-					Some(p) => SeatInfo {
-						seat: Seat(i),
-						name: p.name().to_string(),
-						stack: self.config.starting_stack,
-						position: Position::None,
-						is_active: true,
-						is_human: p.is_human(),
-						is_occupied: true,
-					},
-					None => SeatInfo {
-						seat: Seat(i),
-						name: String::new(),
-						stack: 0.0,
-						position: Position::None,
-						is_active: false,
-						is_human: false,
-						is_occupied: false,
-					},
-				}
-			})
-			.collect();
-
-		self.emit(GameEvent::GameStarted {
-			seats: seat_infos,
-		});
-
-		// Initialize stacks: occupied seats get starting_stack, empty get 0
-		let mut stacks: Vec<f32> = self.players.iter()
-			.map(|opt| if opt.is_some() { self.config.starting_stack } else { 0.0 })
-			.collect();
-		let mut dealer_idx = self.find_first_occupied(0);
-		let mut hand_num: u32 = 0;
+		self.driver.start_game();
+		let mut announce_game = true;
 
 		loop {
-			hand_num += 1;
+			// Whoever reads the events catches up before the next hand
+			self.driver.event_tx.wait_for_room();
+			let hand_num = self.driver.hand_num + 1;
 			logging::set_hand_num(hand_num);
 
 			if self.quit_signal.load(Ordering::SeqCst) {
 				logging::engine::game_ended("User quit");
 				break;
 			}
+			if self.pause.is_paused() {
+				self.pause.note(|| {
+					let seats = self.driver.players.iter().zip(&self.driver.stacks).enumerate()
+						.filter_map(|(idx, (p, &stack))| p.as_ref().map(|p| (idx, p.name().to_string(), stack)))
+						.collect::<Vec<_>>();
+					describe_table(hand_num, &seats)
+				});
+				self.runtime_handle.block_on(self.pause.hold(&self.driver.event_tx));
+				// Whoever resumed may have been ending the game
+				if self.quit_signal.load(Ordering::SeqCst) {
+					logging::engine::game_ended("User quit");
+					break;
+				}
+			}
 
-			let sitting_out = lock_mutex(&self.sitting_out);
-			let active_count = self.players.iter().enumerate()
-				.filter(|(i, opt)| {
-					opt.is_some() && stacks[*i] > 0.0 && !sitting_out.contains(&Seat(*i))
-				})
-				.count();
-			drop(sitting_out);
-			if active_count <= 1 {
+			let arrivals: Vec<_> = lock_mutex(&self.arrivals).drain(..).collect();
+			for (player, stack) in arrivals {
+				self.seat_arrival(player, stack);
+			}
+
+			let dealt_in = self.driver.active_seats().iter().filter(|a| **a).count();
+			if dealt_in <= 1 {
 				break;
 			}
 
@@ -252,199 +333,66 @@ impl GameRunner {
 				}
 			}
 
-			let (small_blind, big_blind) = if let Some(ref clock) = self.blind_clock {
-				clock.current()
-			} else {
-				(self.config.small_blind, self.config.big_blind)
+			let blinds = match self.blind_clock {
+				Some(ref clock) => clock.current(),
+				None => (self.config.small_blind, self.config.big_blind),
 			};
 
-			lock_mutex(&self.action_history).clear();
-
-			let hand_id = HandId(self.rng.random());
-
-			let seat_infos = self.build_seat_infos(&stacks, dealer_idx);
-			let button_seat = Seat(dealer_idx);
-
-			self.emit(GameEvent::HandStarted {
-				hand_id,
-				hand_num,
-				button: button_seat,
-				blinds: Blinds {
-					small: small_blind,
-					big: big_blind,
-					ante: None,
-				},
-				seats: seat_infos,
-			});
-
-			let num_slots = self.players.len();
-			logging::engine::hand_started(dealer_idx, num_slots);
-
-			for player in self.players.iter().flatten() {
-				player.notify(&GameEvent::HandStarted {
-					hand_id,
-					hand_num,
-					button: button_seat,
-					blinds: Blinds {
-						small: small_blind,
-						big: big_blind,
-						ante: None,
-					},
-					seats: self.build_seat_infos(&stacks, dealer_idx),
-				});
-			}
-
-			let sitting_out = lock_mutex(&self.sitting_out);
-
-			// Build seat_map: slot_idx -> table_seat (for fixed mode, slot_idx == seat)
-			let seat_map: Vec<Seat> = (0..self.players.len()).map(|i| Seat(i)).collect();
-
-			// Create stacks for game state - empty/sitting_out seats have 0
-			let game_stacks: Vec<f32> = self.players.iter().enumerate()
-				.map(|(i, opt)| {
-					match opt {
-						None => 0.0,
-						Some(p) => if sitting_out.contains(&Seat(i)) { 0.0 } else { stacks[i] }
+			let betting_structure = match self.game_rotation {
+				Some(ref rotation) => {
+					let game = rotation.current();
+					if announce_game {
+						self.announce_game(game);
+						announce_game = false;
 					}
-				})
-				.collect();
-
-			let game_state = GameState::new_starting(
-				game_stacks.clone(),
-				big_blind,
-				small_blind,
-				0.0,
-				dealer_idx,
-			);
-
-			let player_names: Vec<String> = self.players.iter()
-				.map(|opt| opt.as_ref().map(|p| p.name().to_string()).unwrap_or_default())
-				.collect();
-
-			let agents: Vec<Box<dyn Agent>> = self
-				.players
-				.iter()
-				.enumerate()
-				.map(|(slot_idx, opt)| {
-					let seat = Seat(slot_idx);
-					match opt {
-						None => Box::new(FoldingAgent) as Box<dyn Agent>,
-						Some(p) => {
-							if stacks[slot_idx] <= 0.0 || sitting_out.contains(&seat) {
-								Box::new(FoldingAgent) as Box<dyn Agent>
-							} else {
-								Box::new(PlayerAdapter::new(
-									Arc::clone(p),
-									seat,
-									slot_idx,
-									seat_map.clone(),
-									self.config.betting_structure,
-									Arc::clone(&self.action_history),
-									self.event_tx.clone(),
-									self.config.max_raises_per_round,
-									self.runtime_handle.clone(),
-								)) as Box<dyn Agent>
-							}
-						}
-					}
-				})
-				.collect();
-			drop(sitting_out);
-
-			let rake_config = RakeConfig {
-				percent: self.config.rake_percent,
-				cap: self.config.rake_cap,
-				no_flop_no_drop: self.config.no_flop_no_drop,
+					BettingStructure::from(game.betting)
+				}
+				None => self.config.betting_structure,
 			};
 
-			// Note: use game_stacks for historian so it's consistent with GameState
-			// (sitting_out players have 0 stack in both)
-			let historian = EventHistorian::with_rake(
-				self.event_tx.clone(),
-				player_names,
-				hand_id,
-				hand_num,
-				game_stacks,
-				rake_config,
-				Arc::clone(&self.action_history),
-				seat_map,
+			let pause = &self.pause;
+			let hand = self.driver.start_hand(
+				blinds,
+				betting_structure,
+				|agent, seat, names, events| {
+					Box::new(Pausable::new(agent, seat, Arc::clone(names), pause.clone(), events.clone()))
+				},
+				|_, _, rng| Deck::shuffled(rng),
 			);
+			let Some(mut hand) = hand else {
+				break;
+			};
+			let outcome = self.runtime_handle.block_on(hand.future.as_mut());
+			self.driver.finish_hand(&hand, outcome);
 
-			// Keep reference to hole cards and folded status for HandResult
-			let hole_cards_ref = historian.hole_cards();
-			let folded_ref = historian.folded();
-
-			let mut sim = HoldemSimulationBuilder::default()
-				.game_state(game_state)
-				.agents(agents)
-				.historians(vec![Box::new(historian)])
-				.build()
-				.expect("Failed to build holdem simulation");
-
-			sim.run(&mut self.rng);
-
-			let old_stacks = stacks.clone();
-			let new_stacks = sim.game_state.stacks.clone();
-
-			// Update stacks, but preserve sitting_out/empty players' stacks
-			let sitting_out = lock_mutex(&self.sitting_out);
-			for (i, new_stack) in new_stacks.iter().enumerate() {
-				if self.players[i].is_some() && !sitting_out.contains(&Seat(i)) {
-					stacks[i] = *new_stack;
-				}
-			}
-			drop(sitting_out);
-
-			let hole_cards = lock_mutex(&hole_cards_ref);
-			let folded = lock_mutex(&folded_ref);
-
-			let results: Vec<HandResult> = self
-				.players
-				.iter()
-				.enumerate()
-				.filter_map(|(i, opt)| {
-					opt.as_ref().map(|_p| {
-						let showed = if !folded[i] {
-							hole_cards[i].clone()
-						} else {
-							None
-						};
-						HandResult {
-							seat: Seat(i),
-							stack_change: stacks[i] - old_stacks[i],
-							final_stack: stacks[i],
-							showed_cards: showed,
-							hand_description: None,
-						}
-					})
-				})
-				.collect();
-
-			self.emit(GameEvent::HandEnded { hand_id, results });
+			// Players changing tables go now the hand is over, busted or not
+			let leaving: HashSet<Seat> = lock_mutex(&self.leaving).drain().collect();
+			let driver = &mut self.driver;
+			let mut sitting_out = lock_mutex(&driver.sitting_out);
+			sitting_out.extend(leaving.iter().copied());
 
 			// Cash out any players who left mid-game (sitting_out with stack > 0)
-			let sitting_out = lock_mutex(&self.sitting_out);
 			logging::log("Engine", "DEBUG", &format!(
 				"Checking cashout: sitting_out={:?}, stacks={:?}",
 				sitting_out.iter().map(|s| s.0).collect::<Vec<_>>(),
-				stacks
+				driver.stacks
 			));
-			for i in 0..self.players.len() {
-				if sitting_out.contains(&Seat(i)) && stacks[i] > 0.0 {
-					let name = self.players[i]
+			for (i, stack) in driver.stacks.iter_mut().enumerate() {
+				if sitting_out.contains(&Seat(i)) && (*stack > 0.0 || leaving.contains(&Seat(i))) {
+					let name = driver.players[i]
 						.as_ref()
 						.map(|p| p.name().to_string())
 						.unwrap_or_default();
 					logging::log("Engine", "CASHOUT", &format!(
 						"Emitting PlayerCashedOut: seat={}, name={}, amount={}",
-						i, name, stacks[i]
+						i, name, stack
 					));
-					self.emit(GameEvent::PlayerCashedOut {
+					let _ = driver.event_tx.send(GameEvent::PlayerCashedOut {
 						seat: Seat(i),
 						name,
-						amount: stacks[i],
+						amount: *stack,
 					});
-					stacks[i] = 0.0;
+					*stack = 0.0;
 				}
 			}
 			drop(sitting_out);
@@ -453,28 +401,9 @@ impl GameRunner {
 				clock.advance_hand();
 			}
 
-			dealer_idx = self.next_active(dealer_idx, &stacks);
-		}
-
-		let mut standings: Vec<Standing> = self
-			.players
-			.iter()
-			.enumerate()
-			.filter_map(|(i, opt)| {
-				opt.as_ref().map(|p| Standing {
-					seat: Seat(i),
-					name: p.name().to_string(),
-					final_stack: stacks[i],
-					finish_position: 0,
-				})
-			})
-			.collect();
-
-		standings.sort_by(|a, b| {
-			b.final_stack.partial_cmp(&a.final_stack).unwrap_or(std::cmp::Ordering::Equal)
-		});
-		for (i, s) in standings.iter_mut().enumerate() {
-			s.finish_position = (i + 1) as u8;
+			if let Some(ref mut rotation) = self.game_rotation {
+				announce_game = rotation.advance_hand(dealt_in);
+			}
 		}
 
 		let reason = if self.quit_signal.load(Ordering::SeqCst) {
@@ -482,106 +411,26 @@ impl GameRunner {
 		} else {
 			GameEndReason::Winner
 		};
-
-		self.emit(GameEvent::GameEnded {
-			reason,
-			final_standings: standings,
-		});
+		self.driver.end_game(reason, None);
 	}
 
 	fn emit(&self, event: GameEvent) {
-		let _ = self.event_tx.send(event);
-	}
-
-	fn build_seat_infos(&self, stacks: &[f32], dealer_idx: usize) -> Vec<SeatInfo> {
-		let sitting_out = lock_mutex(&self.sitting_out);
-		self.players
-			.iter()
-			.enumerate()
-			.map(|(i, opt)| {
-				let seat = Seat(i);
-				match opt {
-					Some(p) => {
-						let is_sitting_out = sitting_out.contains(&seat);
-						let is_active = stacks[i] > 0.0 && !is_sitting_out;
-						let position = if !is_active {
-							Position::None
-						} else if i == dealer_idx {
-							Position::Button
-						} else if i == self.next_occupied_from(dealer_idx, 1) {
-							Position::SmallBlind
-						} else if i == self.next_occupied_from(dealer_idx, 2) {
-							Position::BigBlind
-						} else {
-							Position::None
-						};
-
-						SeatInfo {
-							seat,
-							name: p.name().to_string(),
-							stack: stacks[i],
-							position,
-							is_active,
-							is_human: p.is_human(),
-							is_occupied: true,
-						}
-					}
-					None => SeatInfo {
-						seat,
-						name: String::new(),
-						stack: 0.0,
-						position: Position::None,
-						is_active: false,
-						is_human: false,
-						is_occupied: false,
-					},
-				}
-			})
-			.collect()
+		self.driver.emit(event);
 	}
 
-	fn next_active(&self, start: usize, stacks: &[f32]) -> usize {
-		let n = stacks.len();
-		let sitting_out = lock_mutex(&self.sitting_out);
-		let mut idx = start;
-		loop {
-			idx = (idx + 1) % n;
-			if self.players[idx].is_some() && stacks[idx] > 0.0 && !sitting_out.contains(&Seat(idx)) {
-				return idx;
-			}
-			if idx == start {
-				return start;
-			}
-		}
-	}
-
-	fn find_first_occupied(&self, from: usize) -> usize {
-		let n = self.players.len();
-		for i in 0..n {
-			let idx = (from + i) % n;
-			if self.players[idx].is_some() {
-				return idx;
-			}
-		}
-		from
-	}
-
-	fn next_occupied_from(&self, start: usize, steps: usize) -> usize {
-		let n = self.players.len();
-		let mut idx = start;
-		let mut found = 0;
-		loop {
-			idx = (idx + 1) % n;
-			if self.players[idx].is_some() {
-				found += 1;
-				if found == steps {
-					return idx;
-				}
-			}
-			if idx == start {
-				return start;
-			}
+	fn announce_game(&self, game: MixedGame) {
+		let event = GameEvent::GameChanged {
+			name: game.to_string(),
+			betting_structure: match game.betting {
+				TableBettingStructure::NoLimit => EventBettingStructure::NoLimit,
+				TableBettingStructure::PotLimit => EventBettingStructure::PotLimit,
+				TableBettingStructure::FixedLimit => EventBettingStructure::FixedLimit,
+			},
+		};
+		for player in self.driver.players.iter().flatten() {
+			player.notify(&event);
 		}
+		self.emit(event);
 	}
 
 	fn convert_betting_structure(&self) -> EventBettingStructure {
@@ -593,19 +442,6 @@ impl GameRunner {
 	}
 }
 
-struct FoldingAgent;
-
-impl Agent for FoldingAgent {
-	fn act(&mut self, _id: u128, game_state: &GameState) -> rs_poker::arena::action::AgentAction {
-		let to_call = game_state.current_round_bet() - game_state.current_round_current_player_bet();
-		if to_call <= 0.0 {
-			rs_poker::arena::action::AgentAction::Call
-		} else {
-			rs_poker::arena::action::AgentAction::Fold
-		}
-	}
-}
-
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -620,10 +456,14 @@ mod tests {
 			rake_percent: 0.0,
 			rake_cap: None,
 			no_flop_no_drop: false,
+			jackpot: None,
 			seed: Some(42),
+			game_id: None,
 			max_hands: Some(1),
 			blind_clock: None,
+			game_rotation: None,
 			max_seats: None,
+			action_timeout: None,
 		}
 	}
 
@@ -641,7 +481,7 @@ mod tests {
 		let config = make_test_config();
 		let (runner, handle) = GameRunner::new(config, runtime.handle().clone());
 		
-		assert_eq!(runner.players.len(), 0);
+		assert_eq!(runner.driver.players.len(), 0);
 		assert!(!handle.quit_signal.load(Ordering::SeqCst));
 	}
 
@@ -671,4 +511,44 @@ mod tests {
 		assert!(sitting_out.contains(&Seat(0)));
 		assert!(!sitting_out.contains(&Seat(1)));
 	}
+	#[test]
+	fn test_players_leave_after_the_hand_and_arrive_before_the_next() {
+		use crate::events::SeatInfo;
+		use crate::players::RulesPlayer;
+		use crate::strategy::Strategy;
+
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		let config = RunnerConfig { max_seats: Some(3), max_hands: Some(2), ..make_test_config() };
+		let (mut runner, handle) = GameRunner::new(config, runtime.handle().clone());
+		for i in 0..2 {
+			runner.add_player(Arc::new(RulesPlayer::new(Seat(i), &format!("P{}", i), Strategy::default())));
+		}
+		handle.leaving.lock().unwrap().insert(Seat(0));
+		let newcomer = RulesPlayer::new(Seat(2), "New", Strategy::default());
+		handle.arrivals.lock().unwrap().push((Arc::new(newcomer), 250.0));
+		runner.run();
+
+		let events: Vec<GameEvent> = handle.event_rx.try_iter().collect();
+		assert!(events.iter().any(|e| matches!(e,
+			GameEvent::PlayerJoined { seat: Seat(2), stack, .. } if *stack == 250.0
+		)));
+		let cashed_out: Vec<Seat> = events.iter()
+			.filter_map(|e| match e {
+				GameEvent::PlayerCashedOut { seat, .. } => Some(*seat),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(cashed_out, vec![Seat(0)]);
+
+		// The second hand is dealt without the leaver
+		let hands: Vec<&Vec<SeatInfo>> = events.iter()
+			.filter_map(|e| match e {
+				GameEvent::HandStarted { seats, .. } => Some(seats),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(hands.len(), 2);
+		assert!(hands[0].iter().all(|s| s.is_active));
+		assert!(!hands[1][0].is_active);
+	}
 }
@@ -0,0 +1,468 @@
+use std::future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::deck::Deck;
+use crate::engine::driver::{HandDriver, HandInPlay, HandRules};
+use crate::engine::historian::EventSender;
+use crate::engine::pot::RakeConfig;
+use crate::engine::queue::{self, EventQueueConfig, EventReceiver};
+use crate::error::PokerError;
+use crate::events::{Card, GameEndReason, GameEvent, PlayerAction, Seat, ValidActions};
+use crate::players::{GameSnapshot, PlayerPort, PlayerResponse};
+use crate::table::BettingStructure;
+
+fn lock_mutex<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+	mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+	pub small_blind: f32,
+	pub big_blind: f32,
+	pub starting_stack: f32,
+	pub betting: BettingStructure,
+	pub max_raises_per_round: u32,
+	pub rake_percent: f32,
+	pub rake_cap: Option<f32>,
+	pub no_flop_no_drop: bool,
+	pub max_hands: Option<u32>,
+	pub seed: u64,
+}
+
+impl Default for SessionConfig {
+	fn default() -> Self {
+		Self {
+			small_blind: 5.0,
+			big_blind: 10.0,
+			starting_stack: 500.0,
+			betting: BettingStructure::NoLimit,
+			max_raises_per_round: 4,
+			rake_percent: 0.0,
+			rake_cap: None,
+			no_flop_no_drop: false,
+			max_hands: None,
+			seed: 0,
+		}
+	}
+}
+
+/// What `Session::step` stopped for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+	/// An external seat has to `submit_action` before play can go on.
+	WaitingForAction(Seat),
+	HandComplete,
+	GameOver,
+}
+
+/// A seat whose decisions are handed in through `Session::submit_action`.
+pub struct ExternalPlayer {
+	seat: Seat,
+	name: String,
+	waiting: AtomicBool,
+	action: Mutex<Option<PlayerAction>>,
+}
+
+impl ExternalPlayer {
+	pub fn new(seat: Seat, name: impl Into<String>) -> Self {
+		Self {
+			seat,
+			name: name.into(),
+			waiting: AtomicBool::new(false),
+			action: Mutex::new(None),
+		}
+	}
+
+	fn is_waiting(&self) -> bool {
+		self.waiting.load(Ordering::SeqCst)
+	}
+
+	fn submit(&self, action: PlayerAction) -> Result<(), PokerError> {
+		if !self.is_waiting() {
+			return Err(PokerError::Engine(format!("Seat {} is not waiting to act", self.seat.0)));
+		}
+		*lock_mutex(&self.action) = Some(action);
+		Ok(())
+	}
+}
+
+#[async_trait]
+impl PlayerPort for ExternalPlayer {
+	async fn request_action(
+		&self,
+		_seat: Seat,
+		_valid_actions: ValidActions,
+		_game_state: &GameSnapshot,
+	) -> PlayerResponse {
+		self.waiting.store(true, Ordering::SeqCst);
+		let action = future::poll_fn(|_| match lock_mutex(&self.action).take() {
+			Some(action) => Poll::Ready(action),
+			None => Poll::Pending,
+		})
+		.await;
+		self.waiting.store(false, Ordering::SeqCst);
+		PlayerResponse::Action(action)
+	}
+
+	fn notify(&self, _event: &GameEvent) {}
+
+	fn seat(&self) -> Seat {
+		self.seat
+	}
+
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	fn is_human(&self) -> bool {
+		true
+	}
+}
+
+/// Plays a game on the caller's thread, one decision at a time, without a
+/// tokio runtime. Seats are compact and players must answer without doing
+/// I/O; anything slower goes through an `ExternalPlayer`.
+pub struct Session {
+	config: SessionConfig,
+	driver: HandDriver,
+	external: Vec<Option<Arc<ExternalPlayer>>>,
+	event_rx: EventReceiver,
+	hand: Option<HandInPlay>,
+	/// Hole cards by seat and board for the next hand, instead of a shuffle
+	next_deal: Option<(Vec<[Card; 2]>, Vec<Card>)>,
+	/// A whole deck for the next hand, instead of a shuffle
+	next_deck: Option<Vec<Card>>,
+	/// The deck the last hand started with
+	last_deck: Option<Vec<Card>>,
+	started: bool,
+	over: bool,
+}
+
+impl Session {
+	pub fn new(config: SessionConfig) -> Self {
+		let (event_tx, event_rx) = queue::channel(EventQueueConfig::unbounded());
+		let rules = HandRules {
+			max_raises_per_round: config.max_raises_per_round,
+			rake: RakeConfig {
+				percent: config.rake_percent,
+				cap: config.rake_cap,
+				no_flop_no_drop: config.no_flop_no_drop,
+			},
+			jackpot: None,
+			action_timeout: None,
+		};
+		let driver = HandDriver::new(None, rules, StdRng::seed_from_u64(config.seed), EventSender::new(event_tx));
+		Self {
+			config,
+			driver,
+			external: Vec::new(),
+			event_rx,
+			hand: None,
+			next_deal: None,
+			next_deck: None,
+			last_deck: None,
+			started: false,
+			over: false,
+		}
+	}
+
+	pub fn config(&self) -> &SessionConfig {
+		&self.config
+	}
+
+	/// Seat number the next player will get.
+	pub fn next_seat(&self) -> Seat {
+		Seat(self.driver.players.len())
+	}
+
+	pub fn add_player(&mut self, player: Arc<dyn PlayerPort>) -> Result<Seat, PokerError> {
+		self.seat_player(player, None)
+	}
+
+	pub fn add_external_player(&mut self, name: &str) -> Result<Seat, PokerError> {
+		let player = Arc::new(ExternalPlayer::new(self.next_seat(), name));
+		self.seat_player(player.clone(), Some(player))
+	}
+
+	fn seat_player(
+		&mut self,
+		player: Arc<dyn PlayerPort>,
+		external: Option<Arc<ExternalPlayer>>,
+	) -> Result<Seat, PokerError> {
+		if self.started {
+			return Err(PokerError::Engine("Players can't join once the game has started".to_string()));
+		}
+		let seat = self.next_seat();
+		if player.seat() != seat {
+			return Err(PokerError::Engine(format!("Expected a player for seat {}, got seat {}", seat.0, player.seat().0)));
+		}
+
+		self.driver.sit(seat.0, player, self.config.starting_stack);
+		self.external.push(external);
+		Ok(seat)
+	}
+
+	pub fn submit_action(&mut self, seat: Seat, action: PlayerAction) -> Result<(), PokerError> {
+		match self.external.get(seat.0) {
+			Some(Some(player)) => player.submit(action),
+			_ => Err(PokerError::Engine(format!("Seat {} is not an external player", seat.0))),
+		}
+	}
+
+	/// Deals the next hand from these cards: hole cards by seat, then the
+	/// board in order. Anything left out is dealt at random.
+	pub fn deal_next(&mut self, hole_cards: Vec<[Card; 2]>, board: Vec<Card>) {
+		self.next_deal = Some((hole_cards, board));
+	}
+
+	/// Deals the next hand from this deck, top card first, as
+	/// `last_deck` returned it. Seated the same way, the same deck deals
+	/// every seat the same cards.
+	pub fn deal_deck(&mut self, cards: Vec<Card>) -> Result<(), PokerError> {
+		let mut unique = cards.clone();
+		unique.sort_by_key(|c| (c.rank, c.suit));
+		unique.dedup();
+		if cards.len() != 52 || unique.len() != 52 {
+			return Err(PokerError::Engine(format!("A deck needs 52 different cards, got {}", unique.len())));
+		}
+		self.next_deck = Some(cards);
+		Ok(())
+	}
+
+	/// The deck the last hand was dealt from, top card first.
+	pub fn last_deck(&self) -> Option<&[Card]> {
+		self.last_deck.as_deref()
+	}
+
+	pub fn stacks(&self) -> &[f32] {
+		&self.driver.stacks
+	}
+
+	pub fn is_over(&self) -> bool {
+		self.over
+	}
+
+	/// Everything emitted since the last call.
+	pub fn drain_events(&mut self) -> Vec<GameEvent> {
+		self.event_rx.try_iter().collect()
+	}
+
+	/// Plays until an external seat has to act, the hand ends, or the game
+	/// ends.
+	pub fn step(&mut self) -> Step {
+		if self.over {
+			return Step::GameOver;
+		}
+		if !self.started {
+			self.started = true;
+			let seated = self.driver.occupied();
+			if seated < 2 {
+				self.end_game(GameEndReason::Error, Some(format!("A game needs at least two players; {} sat down", seated)));
+				return Step::GameOver;
+			}
+			self.driver.start_game();
+		}
+		if self.hand.is_none() {
+			self.hand = self.start_hand();
+			if self.hand.is_none() {
+				self.end_game(GameEndReason::Winner, None);
+				return Step::GameOver;
+			}
+		}
+
+		let mut cx = Context::from_waker(Waker::noop());
+		let Some(hand) = self.hand.as_mut() else {
+			return Step::GameOver;
+		};
+		match hand.future.as_mut().poll(&mut cx) {
+			Poll::Ready(outcome) => {
+				let hand = self.hand.take().expect("hand in play");
+				self.driver.finish_hand(&hand, outcome);
+				Step::HandComplete
+			}
+			Poll::Pending => match self.waiting_seat() {
+				Some(seat) => Step::WaitingForAction(seat),
+				None => {
+					// A player awaited something other than an external action;
+					// there is nothing here that could ever wake it.
+					self.hand = None;
+					self.end_game(GameEndReason::Error, Some("A player waited on something other than an action".to_string()));
+					Step::GameOver
+				}
+			},
+		}
+	}
+
+	/// Steps until the game ends or an external seat has to act.
+	pub fn run_until_blocked(&mut self) -> Step {
+		loop {
+			match self.step() {
+				Step::HandComplete => continue,
+				other => return other,
+			}
+		}
+	}
+
+	fn waiting_seat(&self) -> Option<Seat> {
+		self.external
+			.iter()
+			.flatten()
+			.find(|p| p.is_waiting())
+			.map(|p| p.seat)
+	}
+
+	fn start_hand(&mut self) -> Option<HandInPlay> {
+		if self.config.max_hands.is_some_and(|max| self.driver.hand_num >= max) {
+			return None;
+		}
+		let (next_deck, next_deal) = (self.next_deck.take(), self.next_deal.take());
+		let last_deck = &mut self.last_deck;
+		let blinds = (self.config.small_blind, self.config.big_blind);
+		self.driver.start_hand(blinds, self.config.betting.into(), |agent, _, _, _| agent, |pos, active, rng| {
+			let deck = match (next_deck, next_deal) {
+				(Some(cards), _) => Deck::in_order(cards),
+				(None, Some((hole_cards, board))) => {
+					// Same order the dealer deals in: round twice from the button, then a
+					// burn before each street
+					let n = active.len();
+					let order: Vec<usize> = (1..=n).map(|i| (pos.button + i) % n).filter(|&i| active[i]).collect();
+					let mut top: Vec<Option<Card>> = Vec::new();
+					for k in 0..2 {
+						top.extend(order.iter().map(|&i| hole_cards.get(i).map(|cards| cards[k])));
+					}
+					for street in [0..3, 3..4, 4..5] {
+						top.push(None);
+						top.extend(street.map(|j| board.get(j).copied()));
+					}
+					Deck::stacked(&top, rng)
+				}
+				(None, None) => Deck::shuffled(rng),
+			};
+			*last_deck = Some(deck.order());
+			deck
+		})
+	}
+
+	fn end_game(&mut self, reason: GameEndReason, detail: Option<String>) {
+		self.over = true;
+		self.driver.end_game(reason, detail);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::cards;
+	use crate::players::{CallingPlayer, FoldingPlayer};
+
+	fn config() -> SessionConfig {
+		SessionConfig {
+			starting_stack: 100.0,
+			seed: 3,
+			..SessionConfig::default()
+		}
+	}
+
+	#[test]
+	fn test_plays_to_completion_without_runtime() {
+		let mut session = Session::new(SessionConfig { max_hands: Some(20), ..config() });
+		session.add_player(Arc::new(CallingPlayer::new(Seat(0), "Alice"))).unwrap();
+		session.add_player(Arc::new(FoldingPlayer::new(Seat(1), "Bob"))).unwrap();
+
+		assert_eq!(session.run_until_blocked(), Step::GameOver);
+		assert_eq!(session.stacks().iter().sum::<f32>(), 200.0);
+
+		let events = session.drain_events();
+		assert!(matches!(events.last(), Some(GameEvent::GameEnded { .. })));
+		assert!(events.iter().any(|e| matches!(e, GameEvent::HandEnded { .. })));
+
+		// Hands and decisions are stamped, in order
+		let times = events
+			.iter()
+			.filter(|e| matches!(e, GameEvent::HandStarted { .. } | GameEvent::ActionTaken { .. } | GameEvent::HandEnded { .. }))
+			.map(|e| e.time().unwrap().game_ms)
+			.collect::<Vec<_>>();
+		assert!(times.windows(2).all(|w| w[0] <= w[1]));
+	}
+
+	#[test]
+	fn test_waits_for_external_seat() {
+		let mut session = Session::new(SessionConfig { max_hands: Some(1), ..config() });
+		let hero = session.add_external_player("Hero").unwrap();
+		session.add_player(Arc::new(CallingPlayer::new(Seat(1), "Bob"))).unwrap();
+
+		// Heads-up the button (seat 0) acts first preflop.
+		assert_eq!(session.step(), Step::WaitingForAction(hero));
+		assert_eq!(session.step(), Step::WaitingForAction(hero));
+		assert!(session.submit_action(Seat(1), PlayerAction::Fold).is_err());
+
+		session.submit_action(hero, PlayerAction::Fold).unwrap();
+		assert_eq!(session.step(), Step::HandComplete);
+		assert_eq!(session.stacks(), &[95.0, 105.0]);
+		assert!(session.submit_action(hero, PlayerAction::Fold).is_err());
+		assert_eq!(session.step(), Step::GameOver);
+	}
+
+	#[test]
+	fn test_deal_next_stacks_the_deck() {
+		let mut session = Session::new(SessionConfig { max_hands: Some(1), ..config() });
+		session.add_player(Arc::new(CallingPlayer::new(Seat(0), "Alice"))).unwrap();
+		session.add_player(Arc::new(CallingPlayer::new(Seat(1), "Bob"))).unwrap();
+		session.add_player(Arc::new(CallingPlayer::new(Seat(2), "Cat"))).unwrap();
+		let hole = |s: &str| {
+			let c = cards(s);
+			[c[0], c[1]]
+		};
+		let board = cards("2c 7d Jh Qs 3h");
+		session.deal_next(vec![hole("As Ad"), hole("Ks Kd"), hole("9c 8c")], board.clone());
+		session.run_until_blocked();
+
+		let events = session.drain_events();
+		let dealt = |seat: usize| events.iter().find_map(|e| match e {
+			GameEvent::HoleCardsDealt { seat: s, cards } if s.0 == seat => Some(*cards),
+			_ => None,
+		});
+		assert_eq!(dealt(0), Some(hole("As Ad")));
+		assert_eq!(dealt(2), Some(hole("9c 8c")));
+		let final_board = events.iter().rev().find_map(|e| match e {
+			GameEvent::StreetChanged { board, .. } => Some(board.clone()),
+			_ => None,
+		});
+		assert_eq!(final_board, Some(board));
+	}
+
+	#[test]
+	fn test_captured_deck_deals_the_same_hand_again() {
+		let play = |deck: Option<Vec<Card>>| {
+			let mut session = Session::new(SessionConfig { max_hands: Some(1), seed: rand::random(), ..config() });
+			session.add_player(Arc::new(CallingPlayer::new(Seat(0), "Alice"))).unwrap();
+			session.add_player(Arc::new(CallingPlayer::new(Seat(1), "Bob"))).unwrap();
+			if let Some(deck) = deck {
+				session.deal_deck(deck).unwrap();
+			}
+			session.run_until_blocked();
+			(session.last_deck().unwrap().to_vec(), session.stacks().to_vec())
+		};
+		let (deck, stacks) = play(None);
+		assert_eq!(play(Some(deck.clone())), (deck.clone(), stacks));
+
+		let mut session = Session::new(config());
+		assert!(session.deal_deck(deck[..51].to_vec()).is_err());
+		assert!(session.deal_deck([&deck[..51], &deck[..1]].concat()).is_err());
+	}
+
+	#[test]
+	fn test_seats_are_assigned_in_order() {
+		let mut session = Session::new(config());
+		assert!(session.add_player(Arc::new(CallingPlayer::new(Seat(1), "Bob"))).is_err());
+		assert_eq!(session.add_external_player("Hero").unwrap(), Seat(0));
+	}
+}
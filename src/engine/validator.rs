@@ -0,0 +1,273 @@
+use crate::events::{PlayerAction, RaiseOptions, Street, ValidActions};
+
+#[derive(Debug, Clone, Copy)]
+pub enum BettingStructure {
+	NoLimit,
+	PotLimit,
+	FixedLimit,
+}
+
+impl From<crate::table::BettingStructure> for BettingStructure {
+	fn from(betting: crate::table::BettingStructure) -> Self {
+		match betting {
+			crate::table::BettingStructure::NoLimit => BettingStructure::NoLimit,
+			crate::table::BettingStructure::PotLimit => BettingStructure::PotLimit,
+			crate::table::BettingStructure::FixedLimit => BettingStructure::FixedLimit,
+		}
+	}
+}
+
+/// What the acting player is facing. Bets are street totals.
+#[derive(Debug, Clone)]
+pub struct BetContext {
+	pub street: Street,
+	pub stack: f32,
+	pub street_bet: f32,
+	pub current_bet: f32,
+	pub min_raise: f32,
+	pub pot: f32,
+	pub big_blind: f32,
+	pub raises_this_street: u32,
+	pub opponents_can_act: bool,
+}
+
+impl BetContext {
+	pub fn to_call(&self) -> f32 {
+		(self.current_bet - self.street_bet).max(0.0)
+	}
+}
+
+/// A player's action after validation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolved {
+	Fold,
+	Check,
+	Call,
+	RaiseTo(f32),
+	AllIn,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ActionValidator {
+	pub structure: BettingStructure,
+	pub max_raises_per_round: u32,
+}
+
+impl ActionValidator {
+	pub fn new(structure: BettingStructure, max_raises_per_round: u32) -> Self {
+		Self {
+			structure,
+			max_raises_per_round,
+		}
+	}
+
+	pub fn valid_actions(&self, ctx: &BetContext) -> ValidActions {
+		let to_call = ctx.to_call();
+		let all_in_amount = ctx.stack + ctx.street_bet;
+		let raise_cap_reached = ctx.raises_this_street >= self.max_raises_per_round;
+		let can_raise = !raise_cap_reached && ctx.opponents_can_act && ctx.stack > to_call;
+
+		let raise_options = if can_raise {
+			self.raise_options(ctx, all_in_amount)
+		} else {
+			None
+		};
+
+		let can_all_in = can_raise && all_in_amount <= self.max_raise_to(ctx);
+
+		ValidActions {
+			can_fold: to_call > 0.0,
+			can_check: to_call <= 0.0,
+			call_amount: if to_call > 0.0 { Some(to_call.min(ctx.stack)) } else { None },
+			raise_options,
+			can_all_in,
+			all_in_amount,
+			can_show: false,
+		}
+	}
+
+	/// Maps whatever the player asked for onto the nearest legal action.
+	pub fn resolve(&self, action: &PlayerAction, valid: &ValidActions) -> Resolved {
+		let passive = if valid.can_check { Resolved::Check } else { Resolved::Call };
+
+		match action {
+			PlayerAction::Fold => {
+				if valid.can_fold {
+					Resolved::Fold
+				} else {
+					Resolved::Check
+				}
+			}
+			PlayerAction::Check | PlayerAction::Call { .. } => passive,
+			PlayerAction::Bet { amount } | PlayerAction::Raise { amount } => {
+				match valid.raise_options {
+					Some(RaiseOptions::Fixed { amount: to }) => Resolved::RaiseTo(to),
+					Some(RaiseOptions::Variable { min_raise, max_raise }) => {
+						let to = amount.max(min_raise).min(max_raise);
+						if to >= valid.all_in_amount {
+							Resolved::AllIn
+						} else {
+							Resolved::RaiseTo(to)
+						}
+					}
+					None if valid.can_all_in && *amount >= valid.all_in_amount => Resolved::AllIn,
+					None => passive,
+				}
+			}
+			PlayerAction::AllIn { .. } => {
+				if valid.can_all_in {
+					Resolved::AllIn
+				} else {
+					passive
+				}
+			}
+			// Show and Muck only answer the offer to an uncalled winner
+			PlayerAction::Timeout | PlayerAction::Show | PlayerAction::Muck => {
+				if valid.can_check {
+					Resolved::Check
+				} else {
+					Resolved::Fold
+				}
+			}
+		}
+	}
+
+	fn fixed_bet_size(&self, ctx: &BetContext) -> f32 {
+		match ctx.street {
+			Street::Preflop | Street::Flop => ctx.big_blind,
+			_ => ctx.big_blind * 2.0,
+		}
+	}
+
+	/// Largest street total the structure allows, ignoring stack size.
+	fn max_raise_to(&self, ctx: &BetContext) -> f32 {
+		match self.structure {
+			BettingStructure::NoLimit => f32::INFINITY,
+			BettingStructure::PotLimit => ctx.current_bet + ctx.pot + ctx.to_call(),
+			BettingStructure::FixedLimit => ctx.current_bet + self.fixed_bet_size(ctx),
+		}
+	}
+
+	fn raise_options(&self, ctx: &BetContext, all_in_amount: f32) -> Option<RaiseOptions> {
+		match self.structure {
+			BettingStructure::FixedLimit => {
+				let raise_to = self.max_raise_to(ctx);
+				if all_in_amount >= raise_to {
+					Some(RaiseOptions::Fixed { amount: raise_to })
+				} else {
+					None
+				}
+			}
+			BettingStructure::PotLimit | BettingStructure::NoLimit => {
+				let min_raise_to = ctx.current_bet + ctx.min_raise;
+				let max_raise_to = self.max_raise_to(ctx).min(all_in_amount);
+				if max_raise_to >= min_raise_to {
+					Some(RaiseOptions::Variable {
+						min_raise: min_raise_to,
+						max_raise: max_raise_to,
+					})
+				} else {
+					None
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn preflop(stack: f32) -> BetContext {
+		BetContext {
+			street: Street::Preflop,
+			stack,
+			street_bet: 0.0,
+			current_bet: 10.0,
+			min_raise: 10.0,
+			pot: 15.0,
+			big_blind: 10.0,
+			raises_this_street: 0,
+			opponents_can_act: true,
+		}
+	}
+
+	#[test]
+	fn test_no_limit_facing_big_blind() {
+		let v = ActionValidator::new(BettingStructure::NoLimit, 4);
+		let valid = v.valid_actions(&preflop(500.0));
+		assert!(valid.can_fold);
+		assert!(!valid.can_check);
+		assert_eq!(valid.call_amount, Some(10.0));
+		assert!(matches!(
+			valid.raise_options,
+			Some(RaiseOptions::Variable { min_raise, max_raise }) if min_raise == 20.0 && max_raise == 500.0
+		));
+		assert!(valid.can_all_in);
+	}
+
+	#[test]
+	fn test_pot_limit_max_raise() {
+		let v = ActionValidator::new(BettingStructure::PotLimit, 4);
+		let valid = v.valid_actions(&preflop(500.0));
+		// Call 10 makes the pot 25, so the most is 10 + 25.
+		assert!(matches!(
+			valid.raise_options,
+			Some(RaiseOptions::Variable { max_raise, .. }) if max_raise == 35.0
+		));
+		assert!(!valid.can_all_in);
+	}
+
+	#[test]
+	fn test_fixed_limit_sizes() {
+		let v = ActionValidator::new(BettingStructure::FixedLimit, 4);
+		let valid = v.valid_actions(&preflop(500.0));
+		assert!(matches!(valid.raise_options, Some(RaiseOptions::Fixed { amount }) if amount == 20.0));
+
+		let turn = BetContext { street: Street::Turn, current_bet: 0.0, ..preflop(500.0) };
+		let valid = v.valid_actions(&turn);
+		assert!(matches!(valid.raise_options, Some(RaiseOptions::Fixed { amount }) if amount == 20.0));
+	}
+
+	#[test]
+	fn test_raise_cap() {
+		let v = ActionValidator::new(BettingStructure::NoLimit, 2);
+		let ctx = BetContext { raises_this_street: 2, ..preflop(500.0) };
+		let valid = v.valid_actions(&ctx);
+		assert!(valid.raise_options.is_none());
+		assert!(!valid.can_all_in);
+		assert_eq!(valid.call_amount, Some(10.0));
+	}
+
+	#[test]
+	fn test_short_stack_can_only_call_all_in() {
+		let v = ActionValidator::new(BettingStructure::NoLimit, 4);
+		let valid = v.valid_actions(&preflop(6.0));
+		assert_eq!(valid.call_amount, Some(6.0));
+		assert!(valid.raise_options.is_none());
+	}
+
+	#[test]
+	fn test_no_raising_when_everyone_else_is_all_in() {
+		let v = ActionValidator::new(BettingStructure::NoLimit, 4);
+		let ctx = BetContext { opponents_can_act: false, ..preflop(500.0) };
+		let valid = v.valid_actions(&ctx);
+		assert!(valid.raise_options.is_none());
+		assert!(!valid.can_all_in);
+	}
+
+	#[test]
+	fn test_resolve_clamps_and_falls_back() {
+		let v = ActionValidator::new(BettingStructure::NoLimit, 4);
+		let valid = v.valid_actions(&preflop(100.0));
+		assert_eq!(v.resolve(&PlayerAction::Raise { amount: 15.0 }, &valid), Resolved::RaiseTo(20.0));
+		assert_eq!(v.resolve(&PlayerAction::Raise { amount: 1000.0 }, &valid), Resolved::AllIn);
+		assert_eq!(v.resolve(&PlayerAction::Check, &valid), Resolved::Call);
+		assert_eq!(v.resolve(&PlayerAction::Timeout, &valid), Resolved::Fold);
+
+		let checked_to = BetContext { current_bet: 0.0, ..preflop(100.0) };
+		let valid = v.valid_actions(&checked_to);
+		assert_eq!(v.resolve(&PlayerAction::Fold, &valid), Resolved::Check);
+		assert_eq!(v.resolve(&PlayerAction::Timeout, &valid), Resolved::Check);
+	}
+}
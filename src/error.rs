@@ -0,0 +1,112 @@
+//! `PokerError`: what the library's public APIs fail with. Each variant
+//! says where things went wrong, so callers can tell a missing config file
+//! from a bankroll that's short; `user_message` is what the TUI and the
+//! command line show for it.
+
+use std::io;
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::bank::InsufficientFunds;
+
+#[derive(Debug, Error)]
+pub enum PokerError {
+	/// A file that couldn't be read, written or made a directory for.
+	#[error("Failed to {action} {}: {source}", path.display())]
+	Io {
+		action: &'static str,
+		path: PathBuf,
+		#[source]
+		source: io::Error,
+	},
+	/// A config or data file that isn't what it should be.
+	#[error("Failed to parse {what}: {message}")]
+	Parse { what: String, message: String },
+	/// A config file that isn't in any of the places it's looked for.
+	#[error("Config file '{filename}' not found. Searched: {searched}")]
+	MissingConfig { filename: String, searched: String },
+	/// A setting that can't be used as it stands.
+	#[error("{0}")]
+	Config(String),
+	/// Something asked for by name that isn't there: a session, a hand,
+	/// a recording.
+	#[error("{0}")]
+	NotFound(String),
+	/// A bankroll that can't cover what's asked of it.
+	#[error(transparent)]
+	Bank(#[from] InsufficientFunds),
+	/// What the server reported, or what went wrong on the way to it.
+	#[error("{0}")]
+	Net(String),
+	/// A game that can't be set up or played as asked.
+	#[error("{0}")]
+	Engine(String),
+	/// A golden snapshot that no longer matches what's replayed.
+	#[error("{0}")]
+	Mismatch(String),
+}
+
+impl PokerError {
+	pub fn io(action: &'static str, path: impl Into<PathBuf>, source: io::Error) -> Self {
+		PokerError::Io { action, path: path.into(), source }
+	}
+
+	pub fn parse(what: impl Into<String>, err: impl std::fmt::Display) -> Self {
+		PokerError::Parse { what: what.into(), message: err.to_string() }
+	}
+
+	/// True for a config file that isn't there, which most loaders take
+	/// as "use the defaults".
+	pub fn is_missing(&self) -> bool {
+		match self {
+			PokerError::MissingConfig { .. } => true,
+			PokerError::Io { source, .. } => source.kind() == io::ErrorKind::NotFound,
+			_ => false,
+		}
+	}
+
+	/// The error as a player should see it: what to do about it where
+	/// there's something to do, otherwise what went wrong.
+	pub fn user_message(&self) -> String {
+		match self {
+			PokerError::Io { path, source, .. } if source.kind() == io::ErrorKind::PermissionDenied => {
+				format!("Not allowed to use {}; check its permissions", path.display())
+			}
+			PokerError::MissingConfig { filename, searched } => {
+				format!("No {} found; put one in {}", filename, searched)
+			}
+			PokerError::Parse { what, message } => {
+				format!("The {} has a mistake in it: {}", what, message)
+			}
+			_ => self.to_string(),
+		}
+	}
+}
+
+/// For code that still reports errors as text.
+impl From<PokerError> for String {
+	fn from(err: PokerError) -> Self {
+		err.to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_user_message_says_what_to_do() {
+		let denied = PokerError::io("write", "/etc/profiles.toml", io::Error::from(io::ErrorKind::PermissionDenied));
+		assert!(denied.to_string().starts_with("Failed to write /etc/profiles.toml"));
+		assert_eq!(denied.user_message(), "Not allowed to use /etc/profiles.toml; check its permissions");
+
+		let missing = PokerError::MissingConfig { filename: "models.toml".into(), searched: "/cfg/models.toml".into() };
+		assert!(missing.is_missing());
+		assert_eq!(missing.user_message(), "No models.toml found; put one in /cfg/models.toml");
+
+		let short = PokerError::from(InsufficientFunds { player_id: "alice".into(), required: 50.0, available: 20.0 });
+		assert!(matches!(short, PokerError::Bank(_)));
+		assert_eq!(short.user_message(), "alice has insufficient funds: needs $50.00, has $20.00");
+	}
+}
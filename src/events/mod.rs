@@ -1,5 +1,6 @@
 mod types;
 mod transformer;
+pub mod redaction;
 
 pub use types::*;
 pub use transformer::ViewUpdater;
@@ -0,0 +1,158 @@
+//! Who may see what of an event. Every event type has a `Visibility`, and
+//! `redact` cuts an event down to what an `Audience` may see of it. The
+//! server sends each player what `Audience::Player` allows, the observer
+//! feed and `poker watch` without hole cards see what a spectator does,
+//! and the recordings keep what an admin sees. `visibility` matches every
+//! event by name, so a new event doesn't build until it has a policy.
+
+use crate::events::{Card, GameEvent, HandResult, Seat};
+
+/// Who an event is going to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Audience {
+	/// The player at a seat: their own cards, and that the others were
+	/// dealt theirs.
+	Player(Seat),
+	/// Someone watching the table: only cards turned over at showdown.
+	Spectator,
+	/// The server's operator, and its recordings: everything.
+	Admin,
+	/// A finished game gone back over, as the hand histories keep it:
+	/// everything, since there's no hand left in play to give away.
+	Replay,
+}
+
+/// Who an event type is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+	/// Everyone at or watching the table.
+	Public,
+	/// Only the player at this seat while the game is on; admins and
+	/// replays see it too.
+	Seat(Seat),
+	/// Everyone, less what only admins and replays see: the AIs' tilt on
+	/// a hand's results.
+	Redacted,
+}
+
+/// The card a player sees in place of someone else's.
+pub const HIDDEN_CARD: Card = Card { rank: '?', suit: '?' };
+
+pub fn visibility(event: &GameEvent) -> Visibility {
+	match event {
+		GameEvent::HoleCardsDealt { seat, .. } => Visibility::Seat(*seat),
+		GameEvent::HandEnded { .. } => Visibility::Redacted,
+		GameEvent::GameCreated { .. }
+		| GameEvent::PlayerJoined { .. }
+		| GameEvent::PlayerLeft { .. }
+		| GameEvent::PlayerCashedOut { .. }
+		| GameEvent::GameStarted { .. }
+		| GameEvent::GameChanged { .. }
+		| GameEvent::HandStarted { .. }
+		| GameEvent::DeckShuffled { .. }
+		| GameEvent::BlindPosted { .. }
+		| GameEvent::StreetChanged { .. }
+		| GameEvent::ActionRequest { .. }
+		| GameEvent::ActionTaken { .. }
+		| GameEvent::PotAwarded { .. }
+		| GameEvent::ShowdownReveal { .. }
+		| GameEvent::JackpotDrop { .. }
+		| GameEvent::BadBeatJackpot { .. }
+		| GameEvent::GameEnded { .. }
+		| GameEvent::GamePaused { .. }
+		| GameEvent::GameResumed { .. }
+		| GameEvent::ChatMessage { .. }
+		| GameEvent::AdminAction { .. }
+		| GameEvent::PromotionAwarded { .. } => Visibility::Public,
+	}
+}
+
+/// What `audience` may see of `event`, or nothing. Another player's seat
+/// event is masked, so the table still shows cards being dealt; a
+/// spectator doesn't get it at all. A redacted event reaches everyone
+/// with the admins' part taken out.
+pub fn redact(event: &GameEvent, audience: Audience) -> Option<GameEvent> {
+	let full = matches!(audience, Audience::Admin | Audience::Replay);
+	match visibility(event) {
+		Visibility::Public => Some(event.clone()),
+		Visibility::Redacted if full => Some(event.clone()),
+		Visibility::Redacted => Some(masked(event)),
+		Visibility::Seat(_) if full => Some(event.clone()),
+		Visibility::Seat(owner) => match audience {
+			Audience::Player(seat) if seat == owner => Some(event.clone()),
+			Audience::Player(_) => Some(masked(event)),
+			_ => None,
+		},
+	}
+}
+
+fn masked(event: &GameEvent) -> GameEvent {
+	match event {
+		GameEvent::HoleCardsDealt { seat, .. } => GameEvent::HoleCardsDealt { seat: *seat, cards: [HIDDEN_CARD; 2] },
+		GameEvent::HandEnded { hand_id, results, at } => GameEvent::HandEnded {
+			hand_id: *hand_id,
+			results: results.iter().map(|r| HandResult { tilt: None, ..r.clone() }).collect(),
+			at: *at,
+		},
+		_ => event.clone(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn dealt(seat: usize) -> GameEvent {
+		GameEvent::HoleCardsDealt { seat: Seat(seat), cards: [Card::new('A', 'h'), Card::new('K', 'h')] }
+	}
+
+	fn cards(event: Option<GameEvent>) -> Option<[Card; 2]> {
+		match event? {
+			GameEvent::HoleCardsDealt { cards, .. } => Some(cards),
+			_ => None,
+		}
+	}
+
+	#[test]
+	fn test_hole_cards_by_audience() {
+		let shown = Some([Card::new('A', 'h'), Card::new('K', 'h')]);
+		assert_eq!(cards(redact(&dealt(0), Audience::Player(Seat(0)))), shown);
+		assert_eq!(cards(redact(&dealt(0), Audience::Player(Seat(1)))), Some([HIDDEN_CARD; 2]));
+		assert!(redact(&dealt(0), Audience::Spectator).is_none());
+		assert_eq!(cards(redact(&dealt(0), Audience::Admin)), shown);
+		assert_eq!(cards(redact(&dealt(0), Audience::Replay)), shown);
+	}
+
+	#[test]
+	fn test_public_events_reach_everyone_unchanged() {
+		let reveal = GameEvent::ShowdownReveal { reveals: vec![(Seat(2), [Card::new('Q', 's'), Card::new('Q', 'd')])] };
+		let json = serde_json::to_string(&reveal).unwrap();
+		for audience in [Audience::Player(Seat(0)), Audience::Spectator, Audience::Admin, Audience::Replay] {
+			assert_eq!(serde_json::to_string(&redact(&reveal, audience).unwrap()).unwrap(), json);
+		}
+	}
+
+	#[test]
+	fn test_tilt_only_for_admins_and_replays() {
+		let ended = GameEvent::HandEnded {
+			hand_id: crate::events::HandId(1),
+			results: vec![HandResult {
+				seat: Seat(1),
+				stack_change: -400.0,
+				final_stack: 100.0,
+				showed_cards: None,
+				hand_description: None,
+				tilt: Some(1.0),
+			}],
+			at: None,
+		};
+		let tilt = |audience| match redact(&ended, audience) {
+			Some(GameEvent::HandEnded { results, .. }) => results[0].tilt,
+			_ => panic!("HandEnded goes to everyone"),
+		};
+		assert_eq!(tilt(Audience::Player(Seat(1))), None);
+		assert_eq!(tilt(Audience::Spectator), None);
+		assert_eq!(tilt(Audience::Admin), Some(1.0));
+		assert_eq!(tilt(Audience::Replay), Some(1.0));
+	}
+}
@@ -1,4 +1,7 @@
 use crate::events::types::*;
+use crate::history::session_id;
+use crate::i18n::{tr, tr_with};
+use crate::money::chips;
 use crate::view::{
 	ActionPrompt, Card as ViewCard, ChatMessage, PlayerStatus, PlayerView,
 	Position as ViewPosition, Street as ViewStreet, TableView,
@@ -20,12 +23,25 @@ impl ViewUpdater {
 				button,
 				blinds,
 				seats,
+				game_id,
 				..
 			} => {
+				if view.info.blind_level == 0 || view.blinds != (blinds.small, blinds.big) {
+					view.info.blind_level += 1;
+				}
+				view.info.players_remaining = seats.iter().filter(|s| s.is_active).count();
+				view.info.hand_pot = 0.0;
+
 				view.hand_num = *hand_num;
+				if let Some(game_id) = game_id {
+					view.game_id = Some(session_id(*game_id));
+				}
 				view.street = ViewStreet::Preflop;
 				view.board.clear();
 				view.pot = 0.0;
+				view.spr = None;
+				view.pots.clear();
+				view.antes = 0.0;
 				view.blinds = (blinds.small, blinds.big);
 
 				view.players = seats
@@ -48,6 +64,7 @@ impl ViewUpdater {
 						is_actor: false,
 						last_action: None,
 						action_fresh: false,
+						dealing: None,
 					})
 					.collect();
 
@@ -56,7 +73,7 @@ impl ViewUpdater {
 				let active_count = seats.iter().filter(|s| s.is_active).count();
 				view.chat_messages.push(ChatMessage {
 					sender: String::new(),
-					text: format!("Preflop ({} players)", active_count),
+					text: tr_with("log.preflop", &[("players", &active_count.to_string())]),
 					is_system: true,
 				});
 			}
@@ -70,16 +87,27 @@ impl ViewUpdater {
 				}
 			}
 
-			GameEvent::BlindPosted { seat, amount, .. } => {
+			GameEvent::BlindPosted { seat, blind_type, amount } => {
 				if let Some(player) = view.players.iter_mut().find(|p| p.seat == seat.0) {
-					player.current_bet = *amount;
+					// An ante is dead money, not a bet to match
+					if *blind_type != BlindType::Ante {
+						player.current_bet = *amount;
+					}
 					player.stack -= amount;
 				}
+				if *blind_type == BlindType::Ante {
+					view.antes += amount;
+				}
 				view.pot += amount;
 			}
 
-			GameEvent::StreetChanged { street, board } => {
+			GameEvent::StreetChanged { street, board, pot, effective_stack, pots } => {
 				view.street = self.convert_street(street);
+				view.pots = pots.clone();
+				view.spr = match street {
+					Street::Showdown => None,
+					_ => stack_to_pot(*effective_stack, *pot),
+				};
 				view.board = board
 					.iter()
 					.map(|c| ViewCard::new(c.rank, c.suit))
@@ -94,17 +122,18 @@ impl ViewUpdater {
 					.filter(|p| matches!(p.status, PlayerStatus::Active | PlayerStatus::AllIn))
 					.count();
 
+				let players = active_count.to_string();
 				let msg = match street {
 					Street::Flop => {
 						let cards: Vec<String> = board.iter()
 							.map(|c| ViewCard::new(c.rank, c.suit).display())
 							.collect();
-						format!("Flop ({} players): {}", active_count, cards.join(" "))
+						tr_with("log.flop", &[("players", &players), ("cards", &cards.join(" "))])
 					}
 					Street::Turn => {
 						if let Some(card) = board.last() {
 							let card_str = ViewCard::new(card.rank, card.suit).display();
-							format!("Turn ({} players): {}", active_count, card_str)
+							tr_with("log.turn", &[("players", &players), ("card", &card_str)])
 						} else {
 							return;
 						}
@@ -112,7 +141,7 @@ impl ViewUpdater {
 					Street::River => {
 						if let Some(card) = board.last() {
 							let card_str = ViewCard::new(card.rank, card.suit).display();
-							format!("River ({} players): {}", active_count, card_str)
+							tr_with("log.river", &[("players", &players), ("card", &card_str)])
 						} else {
 							return;
 						}
@@ -144,6 +173,7 @@ impl ViewUpdater {
 				action,
 				stack_after,
 				pot_after,
+				..
 			} => {
 				for player in &mut view.players {
 					if player.seat == seat.0 {
@@ -157,7 +187,7 @@ impl ViewUpdater {
 								player.status = PlayerStatus::Folded;
 								player.current_bet = 0.0;
 							}
-							PlayerAction::Check => {}
+							PlayerAction::Check | PlayerAction::Show | PlayerAction::Muck => {}
 							PlayerAction::Call { amount } | PlayerAction::Bet { amount } | PlayerAction::Raise { amount } => {
 								player.current_bet = *amount;
 							}
@@ -181,17 +211,25 @@ impl ViewUpdater {
 				seat,
 				amount,
 				hand_description,
+				best_five,
 				..
 			} => {
+				let name = self.player_name(view, *seat);
+				let won = chips(*amount);
 				let msg = if let Some(desc) = hand_description {
-					format!(
-						"{} wins ${:.0} with {}",
-						self.player_name(view, *seat),
-						amount,
-						desc
-					)
+					// The five cards after the words, so the kickers can be checked
+					let hand = if best_five.is_empty() {
+						desc.clone()
+					} else {
+						let cards = best_five
+							.iter()
+							.map(|c| ViewCard::new(c.rank, c.suit).display())
+							.collect::<Vec<_>>();
+						format!("{} ({})", desc, cards.join(" "))
+					};
+					tr_with("log.wins_with", &[("name", &name), ("amount", &won), ("hand", &hand)])
 				} else {
-					format!("{} wins ${:.0}", self.player_name(view, *seat), amount)
+					tr_with("log.wins", &[("name", &name), ("amount", &won)])
 				};
 
 				view.chat_messages.push(ChatMessage {
@@ -203,9 +241,13 @@ impl ViewUpdater {
 				if let Some(player) = view.players.iter_mut().find(|p| p.seat == seat.0) {
 					player.stack += amount;
 				}
+				view.info.hand_pot += amount;
 			}
 
 			GameEvent::HandEnded { results, .. } => {
+				view.info.hands_played += 1;
+				view.info.total_pots += view.info.hand_pot;
+				view.info.biggest_pot = view.info.biggest_pot.max(view.info.hand_pot);
 				view.action_prompt = None;
 				for player in &mut view.players {
 					player.is_actor = false;
@@ -236,6 +278,15 @@ impl ViewUpdater {
 				}
 			}
 
+			GameEvent::GameChanged { name, .. } => {
+				view.current_game = Some(name.clone());
+				view.chat_messages.push(ChatMessage {
+					sender: "Dealer".to_string(),
+					text: tr_with("log.now_playing", &[("game", name)]),
+					is_system: true,
+				});
+			}
+
 			GameEvent::ChatMessage { sender, text } => {
 				let (sender_str, is_system) = match sender {
 					ChatSender::System => (String::new(), true),
@@ -255,13 +306,16 @@ impl ViewUpdater {
 				if let Some(player) = view.players.iter_mut().find(|p| p.seat == seat.0) {
 					let name = player.name.clone();
 					match reason {
-						LeaveReason::Eliminated => player.status = PlayerStatus::Eliminated,
+						LeaveReason::Eliminated => {
+							player.status = PlayerStatus::Eliminated;
+							view.info.players_remaining = view.info.players_remaining.saturating_sub(1);
+						}
 						LeaveReason::Spectating => player.status = PlayerStatus::SittingOut,
 						LeaveReason::Disconnected => {
 							player.status = PlayerStatus::SittingOut;
 							view.chat_messages.push(ChatMessage {
 								sender: String::new(),
-								text: format!("{} disconnected", name),
+								text: tr_with("log.disconnected", &[("name", &name)]),
 								is_system: true,
 							});
 						}
@@ -270,18 +324,21 @@ impl ViewUpdater {
 				}
 			}
 
-			GameEvent::GameEnded { reason, final_standings } => {
+			GameEvent::GameEnded { reason, final_standings, detail } => {
 				let msg = match reason {
 					GameEndReason::Winner => {
 						if let Some(winner) = final_standings.first() {
-							format!("{} wins the game!", winner.name)
+							tr_with("log.game_won", &[("name", &winner.name)])
 						} else {
-							"Game over".to_string()
+							tr("log.game_over")
 						}
 					}
-					GameEndReason::AllPlayersLeft => "All players left".to_string(),
-					GameEndReason::HostTerminated => "Host ended the game".to_string(),
-					GameEndReason::Error => "Game ended due to error".to_string(),
+					GameEndReason::AllPlayersLeft => tr("log.all_left"),
+					GameEndReason::HostTerminated => tr("log.host_ended"),
+					GameEndReason::Error => match detail {
+						Some(detail) => tr_with("log.error_detail", &[("detail", detail)]),
+						None => tr("log.error_ended"),
+					},
 				};
 
 				view.chat_messages.push(ChatMessage {
@@ -291,6 +348,15 @@ impl ViewUpdater {
 				});
 			}
 
+			GameEvent::GamePaused { .. } | GameEvent::GameResumed { .. } => {
+				view.paused = matches!(event, GameEvent::GamePaused { .. });
+				view.chat_messages.push(ChatMessage {
+					sender: String::new(),
+					text: tr(if view.paused { "log.paused" } else { "log.resumed" }),
+					is_system: true,
+				});
+			}
+
 			_ => {}
 		}
 	}
@@ -1,15 +1,18 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+use crate::money::chips;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct GameId(pub u64);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct HandId(pub u64);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct Seat(pub usize);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct Card {
 	pub rank: char,
 	pub suit: char,
@@ -21,7 +24,18 @@ impl Card {
 	}
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Cards written out like "Ah Kd 2c", for tests.
+#[cfg(test)]
+pub(crate) fn cards(s: &str) -> Vec<Card> {
+	s.split_whitespace()
+		.map(|c| {
+			let mut chars = c.chars();
+			Card::new(chars.next().unwrap(), chars.next().unwrap())
+		})
+		.collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum Street {
 	Preflop,
 	Flop,
@@ -30,7 +44,7 @@ pub enum Street {
 	Showdown,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum Position {
 	Button,
 	SmallBlind,
@@ -38,7 +52,7 @@ pub enum Position {
 	None,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum GameEvent {
 	GameCreated {
 		game_id: GameId,
@@ -67,12 +81,31 @@ pub enum GameEvent {
 		seats: Vec<SeatInfo>,
 	},
 
+	GameChanged {
+		name: String,
+		betting_structure: BettingStructure,
+	},
+
 	HandStarted {
 		hand_id: HandId,
 		hand_num: u32,
 		button: Seat,
 		blinds: Blinds,
 		seats: Vec<SeatInfo>,
+		/// The game it's in, so the hand can be named without having seen
+		/// `GameCreated`; see `history::hand_ref`.
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		game_id: Option<GameId>,
+		/// When it happened; unset in the browser and in older recordings.
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		at: Option<EventTime>,
+	},
+
+	/// The deck the hand will be dealt from, by its hash, before a card
+	/// comes off it; see `Deck::hash`.
+	DeckShuffled {
+		hand_id: HandId,
+		hash: String,
 	},
 
 	HoleCardsDealt {
@@ -89,12 +122,27 @@ pub enum GameEvent {
 	StreetChanged {
 		street: Street,
 		board: Vec<Card>,
+		/// Everything in the middle as the street starts; unset in older
+		/// recordings.
+		#[serde(default)]
+		pot: f32,
+		/// The most any two players still in can play for from here: the
+		/// second-biggest stack among them.
+		#[serde(default)]
+		effective_stack: f32,
+		/// Main pot first, then side pots, when there's more than one
+		/// contested pot.
+		#[serde(default, skip_serializing_if = "Vec::is_empty")]
+		pots: Vec<f32>,
 	},
 
 	ActionRequest {
 		seat: Seat,
 		valid_actions: ValidActions,
 		time_limit: Option<u32>,
+		/// When it happened; unset in the browser and in older recordings.
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		at: Option<EventTime>,
 	},
 
 	ActionTaken {
@@ -102,12 +150,20 @@ pub enum GameEvent {
 		action: PlayerAction,
 		stack_after: f32,
 		pot_after: f32,
+		/// When it happened; unset in the browser and in older recordings.
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		at: Option<EventTime>,
 	},
 
 	PotAwarded {
 		seat: Seat,
 		amount: f32,
+		/// The winning hand with its kickers, e.g. "pair of kings,
+		/// ace-queen-ten kickers"; only for pots won at showdown.
 		hand_description: Option<String>,
+		/// The five cards the winning hand plays, in the order described.
+		#[serde(default, skip_serializing_if = "Vec::is_empty")]
+		best_five: Vec<Card>,
 		pot_type: PotType,
 	},
 
@@ -115,14 +171,47 @@ pub enum GameEvent {
 		reveals: Vec<(Seat, [Card; 2])>,
 	},
 
+	/// The bad-beat jackpot's drop, taken off the pot along with the rake.
+	JackpotDrop {
+		amount: f32,
+	},
+
+	/// A hand good enough for the bad-beat jackpot lost at showdown.
+	BadBeatJackpot {
+		loser: Seat,
+		losing_hand: String,
+		winners: Vec<Seat>,
+		winning_hand: String,
+	},
+
 	HandEnded {
 		hand_id: HandId,
 		results: Vec<HandResult>,
+		/// When it happened; unset in the browser and in older recordings.
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		at: Option<EventTime>,
 	},
 
 	GameEnded {
 		reason: GameEndReason,
 		final_standings: Vec<Standing>,
+		/// What went wrong, when the game ended with `Error`.
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		detail: Option<String>,
+	},
+
+	/// The game is held before its next action or hand: nobody is asked to
+	/// act, so no action clock runs, until `GameResumed`.
+	GamePaused {
+		/// When it happened; unset in the browser and in older recordings.
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		at: Option<EventTime>,
+	},
+
+	GameResumed {
+		/// When it happened; unset in the browser and in older recordings.
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		at: Option<EventTime>,
 	},
 
 	ChatMessage {
@@ -134,9 +223,103 @@ pub enum GameEvent {
 		seat: Option<Seat>,
 		action: AdminActionType,
 	},
+
+	/// Sent by the server for table promotions: a bonus paid, or rakeback
+	/// earned toward the weekly payout.
+	PromotionAwarded {
+		seat: Seat,
+		promotion: Promotion,
+		amount: f32,
+	},
+}
+
+impl GameEvent {
+	/// When the engine sent it, for the events that say: the start and
+	/// end of each hand, each decision asked for and made, and pauses.
+	pub fn time(&self) -> Option<EventTime> {
+		match self {
+			GameEvent::HandStarted { at, .. }
+			| GameEvent::ActionRequest { at, .. }
+			| GameEvent::ActionTaken { at, .. }
+			| GameEvent::HandEnded { at, .. }
+			| GameEvent::GamePaused { at }
+			| GameEvent::GameResumed { at } => *at,
+			_ => None,
+		}
+	}
+
+	/// Sets the time on the events that carry one.
+	pub fn stamp(&mut self, time: Option<EventTime>) {
+		if let GameEvent::HandStarted { at, .. }
+		| GameEvent::ActionRequest { at, .. }
+		| GameEvent::ActionTaken { at, .. }
+		| GameEvent::HandEnded { at, .. }
+		| GameEvent::GamePaused { at }
+		| GameEvent::GameResumed { at } = self
+		{
+			*at = time;
+		}
+	}
+
+	/// Events the table reads the same without, like the dealer's chat:
+	/// what a full event queue may drop.
+	pub fn is_cosmetic(&self) -> bool {
+		matches!(self, GameEvent::ChatMessage { .. })
+	}
+}
+
+/// Stack-to-pot ratio: how many pots deep the money behind is. `None`
+/// with nothing in the pot or nothing left to bet.
+pub fn stack_to_pot(effective_stack: f32, pot: f32) -> Option<f32> {
+	(pot > 0.0 && effective_stack > 0.0).then(|| effective_stack / pot)
+}
+
+/// When an event happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct EventTime {
+	/// Wall clock, milliseconds since the Unix epoch.
+	pub unix_ms: u64,
+	/// Milliseconds since the game started, on a clock that only goes
+	/// forward: durations between events come from these.
+	pub game_ms: u64,
+}
+
+impl EventTime {
+	/// Milliseconds from `earlier` to this.
+	pub fn since(&self, earlier: EventTime) -> u64 {
+		self.game_ms.saturating_sub(earlier.game_ms)
+	}
+}
+
+/// Reads `EventTime`s for one game. Copies share the start.
+#[derive(Debug, Clone, Copy)]
+pub struct EventClock {
+	#[cfg(not(target_arch = "wasm32"))]
+	started: std::time::Instant,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl EventClock {
+	pub fn start() -> Self {
+		Self {
+			#[cfg(not(target_arch = "wasm32"))]
+			started: std::time::Instant::now(),
+		}
+	}
+
+	/// The time now; none in the browser, which has no clock to read
+	/// without JS.
+	pub fn now(&self) -> Option<EventTime> {
+		#[cfg(not(target_arch = "wasm32"))]
+		{
+			let unix = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+			Some(EventTime { unix_ms: unix.as_millis() as u64, game_ms: self.started.elapsed().as_millis() as u64 })
+		}
+		#[cfg(target_arch = "wasm32")]
+		None
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GameConfig {
 	pub betting_structure: BettingStructure,
 	pub small_blind: f32,
@@ -146,14 +329,14 @@ pub struct GameConfig {
 	pub time_bank: Option<u32>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum BettingStructure {
 	NoLimit,
 	PotLimit,
 	FixedLimit,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SeatInfo {
 	pub seat: Seat,
 	pub name: String,
@@ -164,14 +347,14 @@ pub struct SeatInfo {
 	pub is_occupied: bool,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
 pub struct Blinds {
 	pub small: f32,
 	pub big: f32,
 	pub ante: Option<f32>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum BlindType {
 	Small,
 	Big,
@@ -179,7 +362,7 @@ pub enum BlindType {
 	Straddle,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ValidActions {
 	pub can_fold: bool,
 	pub can_check: bool,
@@ -187,9 +370,27 @@ pub struct ValidActions {
 	pub raise_options: Option<RaiseOptions>,
 	pub can_all_in: bool,
 	pub all_in_amount: f32,
+	/// Won without a call: the only choice is `Show` or `Muck`.
+	#[serde(default)]
+	pub can_show: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ValidActions {
+	/// The choice offered to a player who won the pot uncalled.
+	pub fn show_or_muck() -> Self {
+		Self {
+			can_fold: false,
+			can_check: false,
+			call_amount: None,
+			raise_options: None,
+			can_all_in: false,
+			all_in_amount: 0.0,
+			can_show: true,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum RaiseOptions {
 	Fixed {
 		amount: f32,
@@ -200,7 +401,7 @@ pub enum RaiseOptions {
 	},
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum PlayerAction {
 	Fold,
 	Check,
@@ -209,6 +410,9 @@ pub enum PlayerAction {
 	Raise { amount: f32 },
 	AllIn { amount: f32 },
 	Timeout,
+	/// Turn over a hand that won uncalled.
+	Show,
+	Muck,
 }
 
 impl PlayerAction {
@@ -216,31 +420,37 @@ impl PlayerAction {
 		match self {
 			PlayerAction::Fold => "folds".to_string(),
 			PlayerAction::Check => "checks".to_string(),
-			PlayerAction::Call { amount } => format!("calls ${:.0}", amount),
-			PlayerAction::Bet { amount } => format!("bets ${:.0}", amount),
-			PlayerAction::Raise { amount } => format!("raises to ${:.0}", amount),
-			PlayerAction::AllIn { amount } => format!("all-in ${:.0}", amount),
+			PlayerAction::Call { amount } => format!("calls {}", chips(*amount)),
+			PlayerAction::Bet { amount } => format!("bets {}", chips(*amount)),
+			PlayerAction::Raise { amount } => format!("raises to {}", chips(*amount)),
+			PlayerAction::AllIn { amount } => format!("all-in {}", chips(*amount)),
 			PlayerAction::Timeout => "timed out".to_string(),
+			PlayerAction::Show => "shows".to_string(),
+			PlayerAction::Muck => "mucks".to_string(),
 		}
 	}
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum PotType {
 	Main,
 	Side(u8),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct HandResult {
 	pub seat: Seat,
 	pub stack_change: f32,
 	pub final_stack: f32,
 	pub showed_cards: Option<[Card; 2]>,
 	pub hand_description: Option<String>,
+	/// The AI's tilt over the hand, for players that can tilt. Only
+	/// admins and replays see it.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub tilt: Option<f32>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum LeaveReason {
 	Quit,
 	Disconnected,
@@ -249,7 +459,7 @@ pub enum LeaveReason {
 	Kicked,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum GameEndReason {
 	Winner,
 	AllPlayersLeft,
@@ -257,7 +467,7 @@ pub enum GameEndReason {
 	Error,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Standing {
 	pub seat: Seat,
 	pub name: String,
@@ -265,7 +475,7 @@ pub struct Standing {
 	pub finish_position: u8,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum ChatSender {
 	System,
 	Dealer,
@@ -273,7 +483,18 @@ pub enum ChatSender {
 	Spectator(String),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Promotion {
+	Rakeback,
+	/// The hand that won it, e.g. "four of a kind".
+	HighHand { hand: String },
+	/// A share of the bad-beat jackpot.
+	Jackpot,
+	/// A daily challenge met, or a streak of them: what for.
+	Challenge { goal: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum AdminActionType {
 	Spectate,
 	LeaveGame,
@@ -344,6 +565,7 @@ mod tests {
 			}),
 			can_all_in: true,
 			all_in_amount: 100.0,
+			can_show: false,
 		};
 		assert!(valid.can_fold);
 		assert!(!valid.can_check);
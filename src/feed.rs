@@ -0,0 +1,799 @@
+//! Observer feed for streaming a game: the table as a commentator sees it
+//! (board, stacks, pot, recent actions and, by default, every hand with its
+//! equity), published as plain text and JSON every time it changes.
+//!
+//! Outputs, any combination:
+//! - a local HTTP endpoint serving `/feed.txt`, `/feed.json` and `/events`
+//!   (server-sent events, one JSON state per message)
+//! - a file, rewritten on every change: plain text, or JSON for a `.json`
+//!   path; a `.jsonl` path gets one line per change instead, which makes a
+//!   replayable recording
+//! - a unix socket for `poker watch`: the game's events as JSON lines,
+//!   starting from the current hand, so a second terminal on the same
+//!   machine can follow the table read-only
+//!
+//! OBS can show the text file with a Text source, or point a Browser
+//! source at a page that listens on `/events`.
+
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::engine::{equities, EquityGraph};
+use crate::events::redaction::{redact, Audience};
+use crate::events::{Blinds, Card, GameEvent, GameId, PlayerAction, Seat, Street};
+use crate::money::chips;
+
+/// Actions kept in `FeedState::last_actions`.
+pub const FEED_ACTIONS: usize = 6;
+
+#[derive(Debug, Clone)]
+pub struct FeedConfig {
+	/// Address for the HTTP endpoint, e.g. `127.0.0.1:8088`.
+	pub addr: Option<String>,
+	pub file: Option<PathBuf>,
+	/// Show hole cards and equities as soon as they're dealt. Off, only
+	/// cards turned over at showdown appear.
+	pub hole_cards: bool,
+	/// Unix socket for viewers; see `attach`.
+	pub socket: Option<PathBuf>,
+}
+
+impl Default for FeedConfig {
+	fn default() -> Self {
+		Self {
+			addr: None,
+			file: None,
+			hole_cards: true,
+			socket: None,
+		}
+	}
+}
+
+/// The viewer socket when none is given: in the runtime directory, else the
+/// temp directory.
+pub fn default_socket_path() -> PathBuf {
+	dirs::runtime_dir().unwrap_or_else(std::env::temp_dir).join("transparent-poker.sock")
+}
+
+/// What the overlay shows. Cards are two-character strings like `"Ah"`.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct FeedState {
+	pub game_id: Option<GameId>,
+	pub hand_num: u32,
+	pub street: Option<Street>,
+	pub board: Vec<String>,
+	pub pot: f32,
+	pub blinds: Option<Blinds>,
+	pub button: Option<Seat>,
+	pub to_act: Option<Seat>,
+	pub players: Vec<FeedPlayer>,
+	/// Most recent last, at most `FEED_ACTIONS`.
+	pub last_actions: VecDeque<String>,
+	/// Pots won this hand, or the winner once the game is over.
+	pub results: Vec<String>,
+	/// How the last hand's equities swung, once it's over; only for hands
+	/// that went to showdown.
+	pub equity_graph: Option<EquityGraph>,
+	#[serde(skip)]
+	hole_cards: bool,
+	/// This hand's events so far, for `equity_graph`.
+	#[serde(skip)]
+	hand_events: Vec<GameEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FeedPlayer {
+	pub seat: Seat,
+	pub name: String,
+	pub stack: f32,
+	/// Chips put in on the current street.
+	pub bet: f32,
+	pub folded: bool,
+	pub all_in: bool,
+	pub cards: Option<[String; 2]>,
+	/// Share of the pot if the board ran out now; only while every player
+	/// still in the hand has known cards.
+	pub equity: Option<f32>,
+	#[serde(skip)]
+	hole: Option<[Card; 2]>,
+}
+
+fn card_str(card: &Card) -> String {
+	format!("{}{}", card.rank, card.suit)
+}
+
+impl FeedState {
+	pub fn new(hole_cards: bool) -> Self {
+		Self {
+			hole_cards,
+			..Self::default()
+		}
+	}
+
+	/// Who the feed shows the game to: a spectator, unless it's showing
+	/// hole cards as they're dealt.
+	fn audience(&self) -> Audience {
+		if self.hole_cards { Audience::Admin } else { Audience::Spectator }
+	}
+
+	fn player_mut(&mut self, seat: Seat) -> Option<&mut FeedPlayer> {
+		self.players.iter_mut().find(|p| p.seat == seat)
+	}
+
+	fn name(&self, seat: Seat) -> String {
+		self.players
+			.iter()
+			.find(|p| p.seat == seat)
+			.map(|p| p.name.clone())
+			.unwrap_or_else(|| format!("Seat {}", seat.0))
+	}
+
+	fn show(&mut self, seat: Seat, cards: [Card; 2]) {
+		if let Some(p) = self.player_mut(seat) {
+			p.hole = Some(cards);
+			p.cards = Some([card_str(&cards[0]), card_str(&cards[1])]);
+		}
+	}
+
+	fn push_action(&mut self, line: String) {
+		if self.last_actions.len() == FEED_ACTIONS {
+			self.last_actions.pop_front();
+		}
+		self.last_actions.push_back(line);
+	}
+
+	/// Folds one event into the state. Returns false for events the overlay
+	/// doesn't show.
+	pub fn apply(&mut self, event: &GameEvent) -> bool {
+		if let Some(seen) = redact(event, self.audience()) {
+			self.hand_events.push(seen);
+		}
+		match event {
+			GameEvent::GameCreated { game_id, .. } => {
+				*self = Self {
+					game_id: Some(*game_id),
+					..Self::new(self.hole_cards)
+				};
+			}
+			GameEvent::HandStarted { hand_num, button, blinds, seats, .. } => {
+				self.hand_num = *hand_num;
+				self.street = Some(Street::Preflop);
+				self.board.clear();
+				self.pot = 0.0;
+				self.blinds = Some(*blinds);
+				self.button = Some(*button);
+				self.to_act = None;
+				self.last_actions.clear();
+				self.results.clear();
+				self.equity_graph = None;
+				self.hand_events = vec![event.clone()];
+				self.players = seats
+					.iter()
+					.filter(|s| s.is_occupied)
+					.map(|s| FeedPlayer {
+						seat: s.seat,
+						name: s.name.clone(),
+						stack: s.stack,
+						bet: 0.0,
+						folded: !s.is_active,
+						all_in: false,
+						cards: None,
+						equity: None,
+						hole: None,
+					})
+					.collect();
+			}
+			GameEvent::HoleCardsDealt { seat, cards } => {
+				if !self.hole_cards {
+					return false;
+				}
+				self.show(*seat, *cards);
+				self.update_equities();
+			}
+			GameEvent::BlindPosted { seat, amount, .. } => {
+				if let Some(p) = self.player_mut(*seat) {
+					p.bet += amount;
+					p.stack -= amount;
+					p.all_in = p.stack <= 0.0;
+				}
+				self.pot += amount;
+			}
+			GameEvent::StreetChanged { street, board, .. } => {
+				self.street = Some(*street);
+				self.board = board.iter().map(card_str).collect();
+				self.to_act = None;
+				for p in &mut self.players {
+					p.bet = 0.0;
+				}
+				self.update_equities();
+			}
+			GameEvent::ActionRequest { seat, .. } => {
+				self.to_act = Some(*seat);
+			}
+			GameEvent::ActionTaken { seat, action, stack_after, pot_after, .. } => {
+				let line = format!("{} {}", self.name(*seat), action.description());
+				if let Some(p) = self.player_mut(*seat) {
+					p.bet += p.stack - stack_after;
+					p.stack = *stack_after;
+					p.folded |= matches!(action, PlayerAction::Fold);
+					p.all_in = !p.folded && *stack_after <= 0.0;
+				}
+				self.pot = *pot_after;
+				self.to_act = None;
+				self.push_action(line);
+				if matches!(action, PlayerAction::Fold) {
+					self.update_equities();
+				}
+			}
+			GameEvent::ShowdownReveal { reveals } => {
+				for (seat, cards) in reveals {
+					self.show(*seat, *cards);
+				}
+				self.update_equities();
+			}
+			GameEvent::PotAwarded { seat, amount, hand_description, .. } => {
+				let mut line = format!("{} wins {}", self.name(*seat), chips(*amount));
+				if let Some(desc) = hand_description {
+					line.push_str(&format!(" with {}", desc));
+				}
+				if let Some(p) = self.player_mut(*seat) {
+					p.stack += amount;
+				}
+				self.pot = (self.pot - amount).max(0.0);
+				self.results.push(line);
+			}
+			GameEvent::HandEnded { results, .. } => {
+				for r in results {
+					if let Some(p) = self.player_mut(r.seat) {
+						p.stack = r.final_stack;
+					}
+				}
+				self.equity_graph = EquityGraph::from_hand(&self.hand_events);
+			}
+			GameEvent::GameEnded { final_standings, .. } => {
+				self.to_act = None;
+				self.results = final_standings
+					.iter()
+					.find(|s| s.finish_position == 1)
+					.map(|s| vec![format!("{} wins the game with {}", s.name, chips(s.final_stack))])
+					.unwrap_or_default();
+			}
+			_ => return false,
+		}
+		true
+	}
+
+	fn update_equities(&mut self) {
+		for p in &mut self.players {
+			p.equity = None;
+		}
+		let live: Vec<usize> = (0..self.players.len()).filter(|&i| !self.players[i].folded).collect();
+		let hands: Vec<[Card; 2]> = live.iter().filter_map(|&i| self.players[i].hole).collect();
+		if live.len() < 2 || hands.len() < live.len() {
+			return;
+		}
+		let board: Vec<Card> = self
+			.board
+			.iter()
+			.filter_map(|c| {
+				let mut chars = c.chars();
+				Some(Card::new(chars.next()?, chars.next()?))
+			})
+			.collect();
+		for (&i, equity) in live.iter().zip(equities(&hands, &board)) {
+			self.players[i].equity = Some(equity);
+		}
+	}
+
+	/// Plain-text rendering for a text overlay.
+	pub fn to_text(&self) -> String {
+		let mut out = String::new();
+		let Some(street) = self.street else {
+			out.push_str("Waiting for the first hand\n");
+			return out;
+		};
+
+		out.push_str(&format!("Hand #{}  {:?}", self.hand_num, street));
+		if let Some(blinds) = self.blinds {
+			out.push_str(&format!("  Blinds {}/{}", chips(blinds.small), chips(blinds.big)));
+		}
+		out.push('\n');
+		let board = if self.board.is_empty() { "-".to_string() } else { self.board.join(" ") };
+		out.push_str(&format!("Board: {}   Pot: {}\n\n", board, chips(self.pot)));
+
+		let width = self.players.iter().map(|p| p.name.chars().count()).max().unwrap_or(0);
+		for p in &self.players {
+			let marker = if self.to_act == Some(p.seat) {
+				">"
+			} else if self.button == Some(p.seat) {
+				"D"
+			} else {
+				" "
+			};
+			let cards = p.cards.as_ref().map(|c| c.join(" ")).unwrap_or_else(|| "-- --".to_string());
+			out.push_str(&format!("{} {:<width$}  {:>7}  {}", marker, p.name, chips(p.stack), cards, width = width));
+			if let Some(equity) = p.equity {
+				out.push_str(&format!("  {:>3.0}%", equity * 100.0));
+			}
+			if p.folded {
+				out.push_str("  folded");
+			} else if p.all_in {
+				out.push_str("  ALL IN");
+			} else if p.bet > 0.0 {
+				out.push_str(&format!("  bet {}", chips(p.bet)));
+			}
+			out.push('\n');
+		}
+
+		if !self.last_actions.is_empty() || !self.results.is_empty() {
+			out.push('\n');
+		}
+		for line in self.last_actions.iter().chain(self.results.iter()) {
+			out.push_str(line);
+			out.push('\n');
+		}
+		if let Some(graph) = &self.equity_graph {
+			out.push('\n');
+			for line in graph.lines(|seat| self.name(seat)) {
+				out.push_str(&line);
+				out.push('\n');
+			}
+		}
+		out
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string(self).expect("Feed state always serializes")
+	}
+}
+
+enum FeedInput {
+	Event(GameEvent),
+	GameEvent(GameId, GameEvent),
+}
+
+#[derive(Default)]
+struct Published {
+	text: String,
+	json: String,
+	subscribers: Vec<Sender<String>>,
+	/// The followed game's events since its current hand began, as JSON
+	/// lines, for viewers attaching mid-hand.
+	hand_events: Vec<String>,
+	viewers: Vec<Sender<String>>,
+}
+
+/// Handle to a running feed. Clones share it; the feed stops once every
+/// clone is dropped.
+#[derive(Clone)]
+pub struct ObserverFeed {
+	tx: Sender<FeedInput>,
+	addr: Option<SocketAddr>,
+}
+
+impl ObserverFeed {
+	pub fn start(config: FeedConfig) -> io::Result<Self> {
+		let state = FeedState::new(config.hole_cards);
+		let published = Arc::new(Mutex::new(Published {
+			text: state.to_text(),
+			json: state.to_json(),
+			..Published::default()
+		}));
+
+		let addr = match &config.addr {
+			Some(addr) => {
+				let listener = TcpListener::bind(addr)?;
+				let local = listener.local_addr()?;
+				let published = Arc::clone(&published);
+				thread::spawn(move || serve(listener, published));
+				Some(local)
+			}
+			None => None,
+		};
+
+		if let Some(path) = &config.socket {
+			let listener = bind_socket(path)?;
+			let published = Arc::clone(&published);
+			thread::spawn(move || serve_viewers(listener, published));
+		}
+
+		let (tx, rx) = mpsc::channel();
+		thread::spawn(move || run_feed(rx, state, config.file, published));
+		Ok(Self { tx, addr })
+	}
+
+	/// Where the HTTP endpoint is listening, if there is one.
+	pub fn local_addr(&self) -> Option<SocketAddr> {
+		self.addr
+	}
+
+	/// Feeds one event from a single game, e.g. from `GameBuilder::on_event`.
+	pub fn observe(&self, event: &GameEvent) {
+		let _ = self.tx.send(FeedInput::Event(event.clone()));
+	}
+
+	/// For a feed shared by several games: it follows whichever game was
+	/// created most recently and ignores the rest.
+	pub fn observe_game(&self, game_id: GameId, event: &GameEvent) {
+		let _ = self.tx.send(FeedInput::GameEvent(game_id, event.clone()));
+	}
+}
+
+fn run_feed(rx: Receiver<FeedInput>, mut state: FeedState, file: Option<PathBuf>, published: Arc<Mutex<Published>>) {
+	for input in rx {
+		let event = match input {
+			FeedInput::Event(event) => event,
+			FeedInput::GameEvent(game_id, event) => {
+				let newer_game = matches!(event, GameEvent::GameCreated { .. });
+				if !newer_game && state.game_id != Some(game_id) {
+					continue;
+				}
+				event
+			}
+		};
+		if let Some(seen) = redact(&event, state.audience()) {
+			if let Ok(line) = serde_json::to_string(&seen) {
+				let mut published = published.lock().unwrap_or_else(|e| e.into_inner());
+				if matches!(event, GameEvent::GameCreated { .. } | GameEvent::HandStarted { .. }) {
+					published.hand_events.clear();
+				}
+				published.hand_events.push(line.clone());
+				published.viewers.retain(|v| v.send(line.clone()).is_ok());
+			}
+		}
+		if !state.apply(&event) {
+			continue;
+		}
+
+		let text = state.to_text();
+		let json = state.to_json();
+		if let Some(path) = &file {
+			if let Err(e) = write_file(path, &text, &json) {
+				eprintln!("Observer feed: failed to write {}: {}", path.display(), e);
+			}
+		}
+
+		let mut published = published.lock().unwrap_or_else(|e| e.into_inner());
+		published.subscribers.retain(|s| s.send(json.clone()).is_ok());
+		published.text = text;
+		published.json = json;
+	}
+	published.lock().unwrap_or_else(|e| e.into_inner()).viewers.clear();
+}
+
+fn write_file(path: &Path, text: &str, json: &str) -> io::Result<()> {
+	match path.extension().and_then(|e| e.to_str()) {
+		Some("jsonl") => {
+			let mut f = OpenOptions::new().create(true).append(true).open(path)?;
+			writeln!(f, "{}", json)
+		}
+		ext => {
+			let contents = if ext == Some("json") { json } else { text };
+			// Write then rename, so the overlay never reads a half-written file
+			let tmp = path.with_extension("feed-tmp");
+			fs::write(&tmp, contents)?;
+			fs::rename(&tmp, path)
+		}
+	}
+}
+
+fn serve(listener: TcpListener, published: Arc<Mutex<Published>>) {
+	for stream in listener.incoming().flatten() {
+		let published = Arc::clone(&published);
+		thread::spawn(move || {
+			let _ = handle_request(stream, &published);
+		});
+	}
+}
+
+fn handle_request(mut stream: TcpStream, published: &Mutex<Published>) -> io::Result<()> {
+	let mut reader = BufReader::new(stream.try_clone()?);
+	let mut request_line = String::new();
+	reader.read_line(&mut request_line)?;
+	let mut header = String::new();
+	while reader.read_line(&mut header)? > 2 {
+		header.clear();
+	}
+
+	let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+	let lock = || published.lock().unwrap_or_else(|e| e.into_inner());
+	match path {
+		"/" | "/feed.txt" => {
+			let body = lock().text.clone();
+			respond(&mut stream, "200 OK", "text/plain; charset=utf-8", &body)
+		}
+		"/feed.json" => {
+			let body = lock().json.clone();
+			respond(&mut stream, "200 OK", "application/json", &body)
+		}
+		"/events" => {
+			let (tx, rx) = mpsc::channel();
+			let current = {
+				let mut published = lock();
+				published.subscribers.push(tx);
+				published.json.clone()
+			};
+			write!(
+				stream,
+				"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nAccess-Control-Allow-Origin: *\r\n\r\n"
+			)?;
+			write!(stream, "data: {}\n\n", current)?;
+			stream.flush()?;
+			for json in rx {
+				write!(stream, "data: {}\n\n", json)?;
+				stream.flush()?;
+			}
+			Ok(())
+		}
+		_ => respond(&mut stream, "404 Not Found", "text/plain", "Not found\n"),
+	}
+}
+
+/// Binds the viewer socket, clearing one left behind by a game that has
+/// ended but not one another game is still feeding.
+#[cfg(unix)]
+fn bind_socket(path: &Path) -> io::Result<UnixListener> {
+	if path.exists() {
+		if UnixStream::connect(path).is_ok() {
+			let message = format!("{} is in use by another game", path.display());
+			return Err(io::Error::new(io::ErrorKind::AddrInUse, message));
+		}
+		fs::remove_file(path)?;
+	}
+	UnixListener::bind(path)
+}
+
+#[cfg(not(unix))]
+fn bind_socket(_path: &Path) -> io::Result<std::convert::Infallible> {
+	Err(io::Error::new(io::ErrorKind::Unsupported, "Viewer sockets need a Unix-like system"))
+}
+
+#[cfg(unix)]
+fn serve_viewers(listener: UnixListener, published: Arc<Mutex<Published>>) {
+	for mut stream in listener.incoming().flatten() {
+		let (tx, rx) = mpsc::channel();
+		let backlog = {
+			let mut published = published.lock().unwrap_or_else(|e| e.into_inner());
+			published.viewers.push(tx);
+			published.hand_events.clone()
+		};
+		thread::spawn(move || {
+			for line in backlog.into_iter().chain(rx) {
+				if writeln!(stream, "{}", line).is_err() {
+					return;
+				}
+			}
+		});
+	}
+}
+
+#[cfg(not(unix))]
+fn serve_viewers(listener: std::convert::Infallible, _published: Arc<Mutex<Published>>) {
+	match listener {}
+}
+
+/// Follows a game another terminal is feeding to `path`: the events of its
+/// current hand, then each one as it happens. The channel closes when the
+/// feed stops.
+#[cfg(unix)]
+pub fn attach(path: &Path) -> io::Result<Receiver<GameEvent>> {
+	let stream = UnixStream::connect(path)?;
+	let (tx, rx) = mpsc::channel();
+	thread::spawn(move || {
+		for line in BufReader::new(stream).lines() {
+			let Ok(line) = line else {
+				return;
+			};
+			if let Ok(event) = serde_json::from_str(&line) {
+				if tx.send(event).is_err() {
+					return;
+				}
+			}
+		}
+	});
+	Ok(rx)
+}
+
+#[cfg(not(unix))]
+pub fn attach(_path: &Path) -> io::Result<Receiver<GameEvent>> {
+	Err(io::Error::new(io::ErrorKind::Unsupported, "Viewer sockets need a Unix-like system"))
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+	write!(
+		stream,
+		"HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+		status,
+		content_type,
+		body.len(),
+		body
+	)?;
+	stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{cards, HandId, Position, SeatInfo};
+	use std::io::Read;
+	use std::time::{Duration, Instant};
+
+	fn seat(n: usize, name: &str, stack: f32) -> SeatInfo {
+		SeatInfo {
+			seat: Seat(n),
+			name: name.to_string(),
+			stack,
+			position: Position::None,
+			is_active: true,
+			is_human: false,
+			is_occupied: true,
+		}
+	}
+
+	fn hand_events() -> Vec<GameEvent> {
+		vec![
+			GameEvent::GameCreated {
+				game_id: GameId(7),
+				config: crate::events::GameConfig {
+					betting_structure: crate::events::BettingStructure::NoLimit,
+					small_blind: 5.0,
+					big_blind: 10.0,
+					starting_stack: 100.0,
+					max_players: 2,
+					time_bank: None,
+				},
+			},
+			GameEvent::HandStarted {
+				hand_id: HandId(1),
+				hand_num: 1,
+				button: Seat(0),
+				blinds: Blinds { small: 5.0, big: 10.0, ante: None },
+				seats: vec![seat(0, "Alice", 100.0), seat(1, "Bob", 100.0)],
+				at: None,
+				game_id: None,
+			},
+			GameEvent::BlindPosted { seat: Seat(0), blind_type: crate::events::BlindType::Small, amount: 5.0 },
+			GameEvent::BlindPosted { seat: Seat(1), blind_type: crate::events::BlindType::Big, amount: 10.0 },
+			GameEvent::HoleCardsDealt { seat: Seat(0), cards: [Card::new('A', 's'), Card::new('A', 'h')] },
+			GameEvent::HoleCardsDealt { seat: Seat(1), cards: [Card::new('K', 'd'), Card::new('K', 'c')] },
+			GameEvent::ActionTaken {
+				seat: Seat(0),
+				action: PlayerAction::Raise { amount: 30.0 },
+				stack_after: 70.0,
+				pot_after: 40.0,
+				at: None,
+			},
+		]
+	}
+
+	#[test]
+	fn test_tracks_bets_and_equities() {
+		let mut state = FeedState::new(true);
+		for event in hand_events() {
+			state.apply(&event);
+		}
+		assert_eq!(state.game_id, Some(GameId(7)));
+		assert_eq!(state.pot, 40.0);
+		let alice = &state.players[0];
+		assert_eq!(alice.bet, 30.0);
+		assert_eq!(alice.cards, Some(["As".to_string(), "Ah".to_string()]));
+		assert!(alice.equity.unwrap() > 0.75);
+		assert_eq!(state.last_actions.back().unwrap(), "Alice raises to $30");
+		assert!(state.to_text().contains("Board: -   Pot: $40"));
+	}
+
+	#[test]
+	fn test_showdown_leaves_an_equity_graph() {
+		let mut state = FeedState::new(true);
+		let rest = [
+			GameEvent::ActionTaken { seat: Seat(1), action: PlayerAction::Call { amount: 30.0 }, stack_after: 70.0, pot_after: 60.0, at: None },
+			GameEvent::StreetChanged { street: Street::Flop, board: cards("Ks 8h 3c"), pot: 60.0, effective_stack: 70.0, pots: Vec::new() },
+			GameEvent::StreetChanged { street: Street::Turn, board: cards("Ks 8h 3c 9d"), pot: 60.0, effective_stack: 70.0, pots: Vec::new() },
+			GameEvent::StreetChanged { street: Street::River, board: cards("Ks 8h 3c 9d 2s"), pot: 60.0, effective_stack: 70.0, pots: Vec::new() },
+			GameEvent::ShowdownReveal { reveals: vec![(Seat(0), [Card::new('A', 's'), Card::new('A', 'h')]), (Seat(1), [Card::new('K', 'd'), Card::new('K', 'c')])] },
+			GameEvent::HandEnded { hand_id: HandId(1), results: Vec::new(), at: None },
+		];
+		for event in hand_events().iter().chain(rest.iter()) {
+			state.apply(event);
+		}
+		let graph = state.equity_graph.as_ref().unwrap();
+		assert_eq!(graph.streets.len(), 4);
+		let text = state.to_text();
+		assert!(text.contains("      Preflop"), "{}", text);
+		assert!(text.lines().any(|l| l.starts_with("Bob") && l.ends_with("██████████ 100%")), "{}", text);
+
+		state.apply(&hand_events()[1]);
+		assert!(state.equity_graph.is_none());
+	}
+
+	#[test]
+	fn test_hidden_hole_cards_stay_hidden() {
+		let mut state = FeedState::new(false);
+		for event in hand_events() {
+			state.apply(&event);
+		}
+		assert!(state.players.iter().all(|p| p.cards.is_none() && p.equity.is_none()));
+		assert!(!state.to_json().contains("\"As\""));
+	}
+
+	#[test]
+	fn test_http_endpoint_serves_latest_state() {
+		let feed = ObserverFeed::start(FeedConfig {
+			addr: Some("127.0.0.1:0".to_string()),
+			..FeedConfig::default()
+		})
+		.unwrap();
+		for event in hand_events() {
+			feed.observe(&event);
+		}
+
+		let addr = feed.local_addr().unwrap();
+		let deadline = Instant::now() + Duration::from_secs(5);
+		loop {
+			let mut stream = TcpStream::connect(addr).unwrap();
+			write!(stream, "GET /feed.json HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+			let mut response = String::new();
+			stream.read_to_string(&mut response).unwrap();
+			assert!(response.starts_with("HTTP/1.1 200 OK"));
+			if response.contains("Alice raises to $30") {
+				break;
+			}
+			assert!(Instant::now() < deadline, "feed never caught up: {}", response);
+			thread::sleep(Duration::from_millis(20));
+		}
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_viewer_catches_up_on_the_hand_then_follows_it() {
+		let path = std::env::temp_dir().join(format!("poker-viewer-{}.sock", std::process::id()));
+		let feed = ObserverFeed::start(FeedConfig {
+			socket: Some(path.clone()),
+			hole_cards: false,
+			..FeedConfig::default()
+		})
+		.unwrap();
+		let events = hand_events();
+		for event in &events[..3] {
+			feed.observe_game(GameId(7), event);
+		}
+		// Another game's events aren't followed
+		feed.observe_game(GameId(8), &events[6]);
+
+		let timeout = Duration::from_secs(5);
+		let deadline = Instant::now() + timeout;
+		let viewer = loop {
+			let viewer = attach(&path).unwrap();
+			match viewer.recv_timeout(timeout).unwrap() {
+				GameEvent::HandStarted { .. } => break viewer,
+				// Attached before the feed caught up
+				_ => assert!(Instant::now() < deadline, "never got the hand"),
+			}
+			thread::sleep(Duration::from_millis(20));
+		};
+		assert!(matches!(viewer.recv_timeout(timeout).unwrap(), GameEvent::BlindPosted { seat: Seat(0), .. }));
+
+		for event in &events[3..] {
+			feed.observe_game(GameId(7), event);
+		}
+		assert!(matches!(viewer.recv_timeout(timeout).unwrap(), GameEvent::BlindPosted { seat: Seat(1), .. }));
+		// Hole cards kept off the feed
+		assert!(matches!(viewer.recv_timeout(timeout).unwrap(), GameEvent::ActionTaken { seat: Seat(0), .. }));
+		assert!(ObserverFeed::start(FeedConfig { socket: Some(path.clone()), ..FeedConfig::default() }).is_err());
+
+		drop(feed);
+		assert!(viewer.recv_timeout(timeout).is_err());
+		let _ = fs::remove_file(&path);
+	}
+}
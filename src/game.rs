@@ -0,0 +1,486 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use tokio::runtime::Runtime;
+
+use crate::engine::{event_queue, EventQueueConfig, EventReceiver, GameRunner, Pause, QueueStats, RunnerConfig};
+use crate::error::PokerError;
+use crate::events::{GameEvent, GameId, Standing};
+use crate::players::PlayerPort;
+use crate::table::{BettingStructure, TableConfig};
+
+type Observer = Box<dyn FnMut(&GameEvent) + Send>;
+
+/// A configured game, ready to play. Built with `Game::builder()`.
+///
+/// ```no_run
+/// use transparent_poker::prelude::*;
+///
+/// let standings = Game::builder()
+///     .blinds(1.0, 2.0)
+///     .starting_stack(200.0)
+///     .max_hands(100)
+///     .player(CallingPlayer::new(Seat(0), "Alice"))
+///     .player(FoldingPlayer::new(Seat(1), "Bob"))
+///     .on_event(|event| println!("{:?}", event))
+///     .build()?
+///     .run();
+/// # Ok::<(), PokerError>(())
+/// ```
+pub struct Game {
+	runner: GameRunner,
+	game_id: GameId,
+	event_rx: EventReceiver,
+	queue: EventQueueConfig,
+	quit_signal: Arc<AtomicBool>,
+	pause: Pause,
+	runtime: Runtime,
+	observers: Vec<Observer>,
+}
+
+#[derive(Default)]
+pub struct GameBuilder {
+	config: RunnerConfig,
+	players: Vec<Arc<dyn PlayerPort>>,
+	observers: Vec<Observer>,
+	stepping: bool,
+	queue: EventQueueConfig,
+}
+
+impl GameBuilder {
+	/// Takes blinds, stacks, betting, rake and seating from a table config.
+	/// Settings made before this call are replaced; later ones override it.
+	pub fn table(mut self, table: &TableConfig) -> Self {
+		self.config = RunnerConfig::from_table(table);
+		self
+	}
+
+	pub fn blinds(mut self, small: f32, big: f32) -> Self {
+		self.config.small_blind = small;
+		self.config.big_blind = big;
+		self
+	}
+
+	pub fn starting_stack(mut self, stack: f32) -> Self {
+		self.config.starting_stack = stack;
+		self
+	}
+
+	pub fn betting(mut self, betting: BettingStructure) -> Self {
+		self.config.betting_structure = betting.into();
+		self.config.game_rotation = None;
+		self
+	}
+
+	pub fn max_raises_per_round(mut self, max: u32) -> Self {
+		self.config.max_raises_per_round = max;
+		self
+	}
+
+	pub fn max_hands(mut self, max: u32) -> Self {
+		self.config.max_hands = Some(max);
+		self
+	}
+
+	pub fn seed(mut self, seed: u64) -> Self {
+		self.config.seed = Some(seed);
+		self
+	}
+
+	pub fn action_timeout(mut self, limit: Duration) -> Self {
+		self.config.action_timeout = Some(limit);
+		self
+	}
+
+	/// How many events may wait to be read before the game is held
+	/// between hands, or cosmetic events dropped; see `EventQueueConfig`.
+	pub fn event_queue(mut self, queue: EventQueueConfig) -> Self {
+		self.queue = queue;
+		self
+	}
+
+	/// Starts the game paused, to be taken through with
+	/// `RunningGame::step`.
+	pub fn stepping(mut self) -> Self {
+		self.stepping = true;
+		self
+	}
+
+	/// Seats a player at `player.seat()`.
+	pub fn player<P: PlayerPort + 'static>(self, player: P) -> Self {
+		self.shared_player(Arc::new(player))
+	}
+
+	/// Like `player`, for a player the caller keeps a handle to.
+	pub fn shared_player(mut self, player: Arc<dyn PlayerPort>) -> Self {
+		self.players.push(player);
+		self
+	}
+
+	/// Called with every event, in order, on the thread that drives the game.
+	pub fn on_event<F: FnMut(&GameEvent) + Send + 'static>(mut self, observer: F) -> Self {
+		self.observers.push(Box::new(observer));
+		self
+	}
+
+	pub fn build(mut self) -> Result<Game, PokerError> {
+		if self.players.len() < 2 {
+			return Err(PokerError::Engine("A game needs at least two players".to_string()));
+		}
+		if self.config.small_blind <= 0.0 || self.config.big_blind < self.config.small_blind {
+			return Err(PokerError::Engine(format!(
+				"Invalid blinds ${}/${}",
+				self.config.small_blind, self.config.big_blind
+			)));
+		}
+		if self.config.starting_stack <= 0.0 {
+			return Err(PokerError::Engine("Starting stack must be positive".to_string()));
+		}
+
+		let mut seats = HashSet::new();
+		for player in &self.players {
+			let seat = player.seat();
+			if !seats.insert(seat) {
+				return Err(PokerError::Engine(format!("Seat {} is taken twice", seat.0)));
+			}
+		}
+
+		match self.config.max_seats {
+			Some(max) => {
+				if let Some(p) = self.players.iter().find(|p| p.seat().0 >= max) {
+					return Err(PokerError::Engine(format!("Seat {} is outside a {}-seat table", p.seat().0, max)));
+				}
+				if self.players.len() > max {
+					return Err(PokerError::Engine(format!("{} players don't fit at a {}-seat table", self.players.len(), max)));
+				}
+			}
+			None => {
+				// Compact seating places players in order, so seat numbers
+				// have to match that order.
+				self.players.sort_by_key(|p| p.seat().0);
+				if let Some((i, p)) = self.players.iter().enumerate().find(|(i, p)| p.seat().0 != *i) {
+					return Err(PokerError::Engine(format!(
+						"Seat {} is not contiguous (expected seat {})",
+						p.seat().0,
+						i
+					)));
+				}
+			}
+		}
+
+		let runtime = tokio::runtime::Builder::new_multi_thread()
+			.enable_all()
+			.build()
+			.map_err(|e| PokerError::Engine(format!("Failed to create runtime: {}", e)))?;
+
+		let (mut runner, handle) = GameRunner::with_queue(self.config, runtime.handle().clone(), self.queue);
+		for player in self.players {
+			runner.add_player(player);
+		}
+		handle.pause.set(self.stepping);
+
+		Ok(Game {
+			runner,
+			game_id: handle.game_id,
+			event_rx: handle.event_rx,
+			queue: self.queue,
+			quit_signal: handle.quit_signal,
+			pause: handle.pause,
+			runtime,
+			observers: self.observers,
+		})
+	}
+}
+
+impl Game {
+	pub fn builder() -> GameBuilder {
+		GameBuilder::default()
+	}
+
+	pub fn id(&self) -> GameId {
+		self.game_id
+	}
+
+	/// Plays until the game ends and returns the final standings.
+	pub fn run(self) -> Vec<Standing> {
+		let running = self.spawn();
+		let mut standings = Vec::new();
+		while let Ok(event) = running.events.recv() {
+			if let GameEvent::GameEnded { final_standings, .. } = event {
+				standings = final_standings;
+			}
+		}
+		running.join();
+		standings
+	}
+
+	/// Plays on background threads. Events arrive on `RunningGame::events`
+	/// after any `on_event` observers have seen them.
+	pub fn spawn(self) -> RunningGame {
+		let Game {
+			mut runner,
+			game_id,
+			event_rx,
+			queue,
+			quit_signal,
+			pause,
+			runtime,
+			mut observers,
+		} = self;
+
+		let runner_thread = thread::spawn(move || runner.run());
+
+		// The caller's queue pushes back the same way the engine's does: the
+		// relay waits for room after each hand, so the engine's queue fills
+		// and holds the engine before the next one
+		let (tx, rx) = event_queue(queue);
+		let relay_thread = thread::spawn(move || {
+			while let Ok(event) = event_rx.recv() {
+				for observer in observers.iter_mut() {
+					observer(&event);
+				}
+				let game_over = matches!(event, GameEvent::GameEnded { .. });
+				let hand_over = matches!(event, GameEvent::HandEnded { .. });
+				// The caller may have stopped listening; keep draining anyway
+				let _ = tx.send(event);
+				if game_over {
+					break;
+				}
+				if hand_over {
+					tx.wait_for_room();
+				}
+			}
+		});
+
+		RunningGame {
+			game_id,
+			events: rx,
+			quit_signal,
+			pause,
+			threads: vec![runner_thread, relay_thread],
+			runtime,
+		}
+	}
+}
+
+/// A game playing on background threads.
+pub struct RunningGame {
+	game_id: GameId,
+	pub events: EventReceiver,
+	quit_signal: Arc<AtomicBool>,
+	pause: Pause,
+	threads: Vec<JoinHandle<()>>,
+	runtime: Runtime,
+}
+
+impl RunningGame {
+	pub fn id(&self) -> GameId {
+		self.game_id
+	}
+
+	/// How full `events` has run; see `QueueStats`.
+	pub fn queue_stats(&self) -> QueueStats {
+		self.events.metrics().stats()
+	}
+
+	/// Ends the game after the current hand.
+	pub fn stop(&self) {
+		self.quit_signal.store(true, Ordering::SeqCst);
+		self.pause.set(false);
+	}
+
+	/// Holds the game before its next action; see `Pause`.
+	pub fn pause(&self) {
+		self.pause.set(true);
+	}
+
+	pub fn resume(&self) {
+		self.pause.set(false);
+	}
+
+	/// While paused, lets the game go on to its next action request or
+	/// hand; see `Pause::step`.
+	pub fn step(&self) {
+		self.pause.step();
+	}
+
+	/// Where the paused game is held; see `Pause::dump`.
+	pub fn dump(&self) -> String {
+		self.pause.dump()
+	}
+
+	/// Waits for the game to finish.
+	pub fn join(self) {
+		for handle in self.threads {
+			let _ = handle.join();
+		}
+		drop(self.runtime);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::Seat;
+	use crate::players::{CallingPlayer, FoldingPlayer};
+
+	#[test]
+	fn test_needs_two_players() {
+		let result = Game::builder()
+			.player(CallingPlayer::new(Seat(0), "Alice"))
+			.build();
+		assert!(result.is_err());
+	}
+
+	#[test]
+	fn test_rejects_duplicate_seats() {
+		let result = Game::builder()
+			.player(CallingPlayer::new(Seat(0), "Alice"))
+			.player(FoldingPlayer::new(Seat(0), "Bob"))
+			.build();
+		assert_eq!(result.err().map(|e| e.to_string()), Some("Seat 0 is taken twice".to_string()));
+	}
+
+	#[test]
+	fn test_rejects_gaps_in_compact_seating() {
+		let result = Game::builder()
+			.player(CallingPlayer::new(Seat(0), "Alice"))
+			.player(FoldingPlayer::new(Seat(2), "Bob"))
+			.build();
+		assert!(result.is_err());
+	}
+
+	#[test]
+	fn test_run_returns_standings() {
+		let standings = Game::builder()
+			.seed(7)
+			.max_hands(5)
+			.player(FoldingPlayer::new(Seat(1), "Bob"))
+			.player(CallingPlayer::new(Seat(0), "Alice"))
+			.build()
+			.unwrap()
+			.run();
+		assert_eq!(standings.len(), 2);
+		let total: f32 = standings.iter().map(|s| s.final_stack).sum();
+		assert_eq!(total, 1000.0);
+	}
+
+	/// Folds, and falls over when the first hand is dealt.
+	struct BrokenPlayer(FoldingPlayer);
+
+	#[async_trait::async_trait]
+	impl PlayerPort for BrokenPlayer {
+		async fn request_action(
+			&self,
+			seat: Seat,
+			valid_actions: crate::events::ValidActions,
+			game_state: &crate::players::GameSnapshot,
+		) -> crate::players::PlayerResponse {
+			self.0.request_action(seat, valid_actions, game_state).await
+		}
+
+		fn notify(&self, event: &GameEvent) {
+			if matches!(event, GameEvent::HandStarted { .. }) {
+				panic!("dealt a card it couldn't read");
+			}
+		}
+
+		fn seat(&self) -> Seat {
+			self.0.seat()
+		}
+
+		fn name(&self) -> &str {
+			self.0.name()
+		}
+
+		fn is_human(&self) -> bool {
+			false
+		}
+	}
+
+	#[test]
+	fn test_engine_panic_ends_the_game_with_an_error() {
+		let running = Game::builder()
+			.player(CallingPlayer::new(Seat(0), "Alice"))
+			.player(BrokenPlayer(FoldingPlayer::new(Seat(1), "Bob")))
+			.build()
+			.unwrap()
+			.spawn();
+		let events = running.events.iter().collect::<Vec<_>>();
+		running.join();
+		match events.last() {
+			Some(GameEvent::GameEnded { reason: crate::events::GameEndReason::Error, detail: Some(detail), .. }) => {
+				assert_eq!(detail, "The engine failed: dealt a card it couldn't read");
+			}
+			other => panic!("expected an error ending, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn test_paused_game_asks_nobody_until_resumed() {
+		let running = Game::builder()
+			.seed(3)
+			.max_hands(50)
+			.player(CallingPlayer::new(Seat(0), "Alice"))
+			.player(CallingPlayer::new(Seat(1), "Bob"))
+			.build()
+			.unwrap()
+			.spawn();
+		// Calling stations only ever see blinds go in, so 50 hands is plenty
+		// of time to pause in
+		while !matches!(running.events.recv().unwrap(), GameEvent::HandStarted { .. }) {}
+		running.pause();
+		while !matches!(running.events.recv().unwrap(), GameEvent::GamePaused { .. }) {}
+		assert!(running.events.recv_timeout(Duration::from_millis(300)).is_err(), "nothing happens while paused");
+
+		running.resume();
+		assert!(matches!(running.events.recv().unwrap(), GameEvent::GameResumed { .. }));
+		let events = running.events.iter().collect::<Vec<_>>();
+		assert!(matches!(events.last(), Some(GameEvent::GameEnded { .. })));
+		assert!(events.iter().any(|e| matches!(e, GameEvent::ActionTaken { .. })));
+		running.join();
+	}
+
+	#[test]
+	fn test_stepping_goes_one_action_at_a_time() {
+		let running = Game::builder()
+			.seed(3)
+			.max_hands(1)
+			.stepping()
+			.player(CallingPlayer::new(Seat(0), "Alice"))
+			.player(FoldingPlayer::new(Seat(1), "Bob"))
+			.build()
+			.unwrap()
+			.spawn();
+		let next_hold = || {
+			let mut seen = Vec::new();
+			loop {
+				match running.events.recv().unwrap() {
+					GameEvent::GamePaused { .. } => return seen,
+					event => seen.push(event),
+				}
+			}
+		};
+
+		next_hold();
+		assert!(running.dump().starts_with("Before hand 1"));
+		running.step();
+		let dealt = next_hold();
+		assert!(dealt.iter().any(|e| matches!(e, GameEvent::HandStarted { .. })));
+		assert!(!dealt.iter().any(|e| matches!(e, GameEvent::ActionTaken { .. })));
+		let dump = running.dump();
+		assert!(dump.contains("Hand 1, Preflop") && dump.contains("Waiting on seat"), "{}", dump);
+
+		running.step();
+		let acted = next_hold();
+		assert_eq!(acted.iter().filter(|e| matches!(e, GameEvent::ActionTaken { .. })).count(), 1);
+
+		running.resume();
+		let rest = running.events.iter().collect::<Vec<_>>();
+		assert!(matches!(rest.last(), Some(GameEvent::GameEnded { .. })));
+		running.join();
+	}
+}
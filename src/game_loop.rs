@@ -1,21 +1,88 @@
-use std::io;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, stdout};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, TryRecvError};
 use std::time::Duration;
 
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::SetTitle;
 use ratatui::{backend::CrosstermBackend, Terminal};
 
-use crate::events::{GameEvent, Seat};
-use crate::net::{GameClient, ServerMessage};
-use crate::players::PlayerResponse;
-use crate::table::{build_info_lines, TableConfig};
+use crate::events::{GameEndReason, GameEvent};
+pub use crate::game_session::GameLoopResult;
+use crate::game_session::{leave, GameSession};
+use crate::net::GameClient;
+use crate::table::TableConfig;
 use crate::theme::Theme;
-use crate::tui::{GameUI, GameUIAction};
+use crate::tui::linear::{help_lines, speak};
+use crate::tui::{GameUI, Narrator};
 
-pub enum GameLoopResult {
-	ReturnToLobby,
-	Quit,
+/// Keeps the terminal window's title in step with the game, setting it
+/// only when it changes.
+#[derive(Default)]
+struct WindowTitle(String);
+
+impl WindowTitle {
+	fn update(&mut self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, game_ui: &GameUI) -> io::Result<()> {
+		let title = game_ui.window_title();
+		if title != self.0 {
+			execute!(terminal.backend_mut(), SetTitle(&title))?;
+			self.0 = title;
+		}
+		Ok(())
+	}
+}
+
+/// `--status-file`: the hero's `StatusLine` as one line of JSON, rewritten
+/// whenever it changes and removed when the game is left, so a status
+/// bar never shows a table that's gone.
+struct StatusFile {
+	path: PathBuf,
+	last: String,
+}
+
+impl StatusFile {
+	fn new(path: &Path) -> Self {
+		Self { path: path.to_path_buf(), last: String::new() }
+	}
+
+	fn update(&mut self, game_ui: &GameUI) -> io::Result<()> {
+		let line = serde_json::to_string(&game_ui.status_line()).map_err(io::Error::other)? + "\n";
+		if line != self.last {
+			// Written beside it and renamed over, so a reader never sees half a line
+			let partial = self.path.with_extension("partial");
+			fs::write(&partial, &line)?;
+			fs::rename(&partial, &self.path)?;
+			self.last = line;
+		}
+		Ok(())
+	}
+}
+
+impl Drop for StatusFile {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+/// A game the engine couldn't finish. Whatever failed may have written
+/// over the screen on its way down, so the next draw starts from a clear
+/// one.
+fn engine_failed(event: &GameEvent) -> bool {
+	matches!(event, GameEvent::GameEnded { reason: GameEndReason::Error, .. })
+}
+
+/// Throws away keys pressed before the table came up.
+fn flush_keys() -> io::Result<()> {
+	while event::poll(Duration::from_millis(0))? {
+		let _ = event::read();
+	}
+	Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn run_game(
 	terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
 	client: &mut GameClient,
@@ -24,50 +91,132 @@ pub fn run_game(
 	theme_name: String,
 	table_config: TableConfig,
 	num_players: usize,
+	linear: bool,
+	detachable: bool,
+	status_file: Option<&Path>,
 ) -> io::Result<GameLoopResult> {
-	// Flush any stale keyboard input
-	while event::poll(Duration::from_millis(0))? {
-		let _ = event::read();
-	}
-	let table_info_str = format!("{} {}", table_config.betting, table_config.format);
-	let info_lines = build_info_lines(&table_config, num_players, table_config.seed);
-	let table_name = table_config.name.clone();
-
-	let mut game_ui = GameUI::new(None, theme.clone(), theme_name.clone());
-	game_ui.set_table_info(table_name.clone(), table_info_str.clone(), info_lines.clone());
-	let mut game_seat: Option<Seat> = None;
+	flush_keys()?;
+	let mut player = GameSession::new(client, username, &table_config, num_players, theme, theme_name);
+	player.game_ui.detachable = detachable;
+	let mut narrator = Narrator::new();
+	let mut showing_help = false;
+	let mut title = WindowTitle::default();
+	let mut status = status_file.map(StatusFile::new);
 
 	loop {
-		while let Some(msg) = client.try_recv() {
-			match msg {
-				ServerMessage::GameEvent(event) => {
-					if let GameEvent::HandStarted { seats, .. } = &event {
-						if game_seat.is_none() {
-							let found_seat = seats.iter()
-								.find(|s| s.name.eq_ignore_ascii_case(username))
-								.map(|s| s.seat);
-
-							if let Some(seat) = found_seat {
-								game_seat = Some(seat);
-								game_ui = GameUI::new(Some(seat), theme.clone(), theme_name.clone());
-								game_ui.set_table_info(table_name.clone(), table_info_str.clone(), info_lines.clone());
-							}
-						}
-					}
-					game_ui.apply_event(&event);
+		let mut repaint = false;
+		player.pump(|event, game_ui| {
+			repaint |= engine_failed(event);
+			if linear {
+				speak(&mut stdout(), &narrator.event(event, &game_ui.table_view))?;
+			}
+			Ok(())
+		})?;
+		player.sync();
+		if let Some(status) = status.as_mut() {
+			status.update(&player.game_ui)?;
+		}
+
+		if linear {
+			let mut lines = Vec::new();
+			if player.game_ui.show_help && !showing_help {
+				lines = help_lines(&player.game_ui.rules_lines);
+			}
+			lines.extend(narrator.status(&player.game_ui));
+			speak(&mut stdout(), &lines)?;
+		} else {
+			if repaint {
+				terminal.clear()?;
+			}
+			title.update(terminal, &player.game_ui)?;
+			terminal.draw(|f| {
+				player.game_ui.render(f, f.area());
+			})?;
+		}
+		showing_help = player.game_ui.show_help;
+
+		if event::poll(Duration::from_millis(50))? {
+			if let Event::Key(key) = event::read()? {
+				if key.kind != KeyEventKind::Press {
+					continue;
 				}
-				ServerMessage::ActionRequest { valid_actions, .. } => {
-					game_ui.enter_action_mode(valid_actions);
+
+				if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+					player.leave_table();
+					return Ok(GameLoopResult::Quit);
 				}
-				ServerMessage::Error { message } => {
-					game_ui.status_message = Some(format!("Error: {}", message));
+
+				match player.press(key.code) {
+					Some(result @ (GameLoopResult::Quit | GameLoopResult::Detached)) => return Ok(result),
+					Some(result) => return Ok(leave(std::slice::from_mut(&mut player), result)),
+					None => {}
 				}
-				_ => {}
 			}
 		}
+	}
+}
 
+/// Several humans taking turns at one terminal. Each plays through their
+/// own connection, seated like any other player; the screen shows one of
+/// them at a time. Between turns their cards are face down, and when it's
+/// someone's turn the screen asks for it to be handed over and waits for
+/// a key before showing their cards.
+pub fn run_hot_seat(
+	terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+	seats: Vec<(&mut GameClient, String)>,
+	theme: Theme,
+	theme_name: String,
+	table_config: TableConfig,
+	num_players: usize,
+) -> io::Result<GameLoopResult> {
+	flush_keys()?;
+	let mut players: Vec<GameSession> = seats
+		.into_iter()
+		.map(|(client, username)| {
+			let mut player = GameSession::new(client, &username, &table_config, num_players, theme.clone(), theme_name.clone());
+			player.game_ui.private = true;
+			player
+		})
+		.collect();
+	// Whose view is on the screen; they have the keys while their cards show
+	let mut shown = 0;
+	// Players waiting for their turn at the screen
+	let mut turns: VecDeque<usize> = VecDeque::new();
+	let mut title = WindowTitle::default();
+
+	loop {
+		let mut repaint = false;
+		for (i, player) in players.iter_mut().enumerate() {
+			let asked = player.pump(|event, _| {
+				repaint |= engine_failed(event);
+				Ok(())
+			})?;
+			player.sync();
+			if asked && !turns.contains(&i) && !(i == shown && !player.game_ui.private) {
+				turns.push_back(i);
+			}
+		}
+		// A turn can lapse unplayed, like the offer to show a winning hand
+		turns.retain(|&i| players[i].game_ui.is_deciding());
+
+		let current = &mut players[shown];
+		if !current.game_ui.is_deciding() {
+			current.game_ui.private = true;
+			current.game_ui.pass_to = None;
+		}
+		if current.game_ui.private && current.game_ui.pass_to.is_none() {
+			if let Some(&next) = turns.front() {
+				shown = next;
+				players[shown].game_ui.pass_to = Some(players[shown].username().to_string());
+			}
+		}
+
+		if repaint {
+			terminal.clear()?;
+		}
+		title.update(terminal, &players[shown].game_ui)?;
 		terminal.draw(|f| {
-			game_ui.render(f, f.area());
+			players[shown].game_ui.render(f, f.area());
 		})?;
 
 		if event::poll(Duration::from_millis(50))? {
@@ -77,20 +226,78 @@ pub fn run_game(
 				}
 
 				if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
-					let _ = client.leave_table();
-					return Ok(GameLoopResult::Quit);
+					return Ok(leave(&mut players, GameLoopResult::Quit));
 				}
 
-				match game_ui.handle_key(key.code) {
-					GameUIAction::Respond(PlayerResponse::Action(action)) => {
-						let _ = client.action(action);
+				let current = &mut players[shown];
+				if current.game_ui.pass_to.take().is_some() {
+					current.game_ui.private = false;
+					turns.retain(|&i| i != shown);
+					continue;
+				}
+				// Nobody's turn: only the keys that give nothing away
+				if current.game_ui.private && !matches!(key.code, KeyCode::Char('q' | '?')) {
+					continue;
+				}
+				match current.press(key.code) {
+					Some(GameLoopResult::Quit) => return Ok(GameLoopResult::Quit),
+					Some(result) => return Ok(leave(&mut players, result)),
+					None => {}
+				}
+			}
+		}
+	}
+}
+
+/// `poker watch`: a read-only table following the events another terminal
+/// feeds it, for someone railing a local game. Nothing here can act; the
+/// keys only change what's shown, `v` stepping through each player's view
+/// of the table.
+pub fn run_viewer(
+	terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+	events: Receiver<GameEvent>,
+	theme: Theme,
+	theme_name: String,
+) -> io::Result<()> {
+	flush_keys()?;
+	let mut game_ui = GameUI::new(None, theme, theme_name);
+	game_ui.show_all_cards = true;
+	game_ui.set_table_info("Watching".to_string(), String::new(), Vec::new());
+	let mut ended = false;
+
+	loop {
+		while !ended {
+			match events.try_recv() {
+				Ok(event) => {
+					if engine_failed(&event) {
+						terminal.clear()?;
 					}
-					GameUIAction::Quit => {
-						let _ = client.leave_table();
-						std::thread::sleep(Duration::from_millis(100));
-						client.drain();
-						return Ok(GameLoopResult::ReturnToLobby);
+					game_ui.apply_event(&event);
+				}
+				Err(TryRecvError::Empty) => break,
+				Err(TryRecvError::Disconnected) => {
+					ended = true;
+					game_ui.status_message = Some("The game has closed its feed. Press q to quit".to_string());
+				}
+			}
+		}
+
+		terminal.draw(|f| {
+			game_ui.render(f, f.area());
+		})?;
+
+		if event::poll(Duration::from_millis(50))? {
+			if let Event::Key(key) = event::read()? {
+				if key.kind != KeyEventKind::Press {
+					continue;
+				}
+				match key.code {
+					KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+					KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+					KeyCode::Char('?' | '$') => {
+						game_ui.handle_key(key.code);
 					}
+					KeyCode::Char('v') => game_ui.cycle_perspective(),
 					_ => {}
 				}
 			}
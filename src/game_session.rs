@@ -0,0 +1,304 @@
+//! `GameSession`: one human's seat at a table the client is playing at.
+//! It owns their `GameUI`, pumps what the server sends into it, keeps the
+//! connection state and fast-forward in step, and carries out what their
+//! key presses ask for. The game loops in `game_loop` only draw and read
+//! keys around it, so the single-player table, the hot seat and anything
+//! built on them share one controller.
+
+use std::io;
+use std::time::Duration;
+
+use crossterm::event::KeyCode;
+
+use crate::achievements::Achievements;
+use crate::config::{load_preferences, save_preferences, Preferences};
+use crate::events::{GameEvent, Seat};
+use crate::history::{tags, HistoryLog};
+use crate::i18n::{tr, tr_with};
+use crate::money;
+use crate::net::{ConnectionState, GameClient, ServerMessage};
+use crate::players::PlayerResponse;
+use crate::table::{build_info_lines, build_rules_lines, GameFormat, Pacing, TableConfig};
+use crate::theme::Theme;
+use crate::tui::{GameUI, GameUIAction};
+use crate::view::ChatMessage;
+
+/// How a session at a table ended.
+pub enum GameLoopResult {
+	ReturnToLobby,
+	Quit,
+	/// Left the table running at the daemon, still seated.
+	Detached,
+}
+
+/// Tells the server the preferences it acts on, once the hero has a seat.
+fn send_seat_preferences(client: &mut GameClient, prefs: &Preferences) {
+	if prefs.auto_muck {
+		let _ = client.auto_muck(true);
+	}
+	if prefs.action_delay_ms.is_some() || prefs.street_delay_ms.is_some() {
+		let _ = client.pacing(prefs.action_delay_ms, prefs.street_delay_ms);
+	}
+}
+
+/// The table's name and rules, for the info panel and the help overlay.
+struct TableText {
+	name: String,
+	info: String,
+	info_lines: Vec<String>,
+	rules_lines: Vec<String>,
+	tournament: bool,
+	pacing: Pacing,
+}
+
+impl TableText {
+	fn new(table_config: &TableConfig, num_players: usize) -> Self {
+		let tournament = matches!(table_config.format, GameFormat::SitNGo);
+		money::set_tournament(tournament);
+		Self {
+			name: table_config.name.clone(),
+			info: format!("{} {}", table_config.betting, table_config.format),
+			info_lines: build_info_lines(table_config, num_players, table_config.seed),
+			rules_lines: build_rules_lines(table_config),
+			tournament,
+			pacing: Pacing::from_table(table_config),
+		}
+	}
+
+	/// A fresh `GameUI` for `seat` at this table, keeping the settings the
+	/// player had in `previous`.
+	fn seat_ui(&self, previous: &GameUI, seat: Option<Seat>, theme: &Theme, theme_name: &str) -> GameUI {
+		let mut game_ui = GameUI::new(seat, theme.clone(), theme_name.to_string());
+		game_ui.fast_forward = previous.fast_forward;
+		game_ui.show_help = previous.show_help;
+		game_ui.big_blinds = previous.big_blinds;
+		game_ui.private = previous.private;
+		game_ui.prefs = previous.prefs.clone();
+		game_ui.tournament = self.tournament;
+		game_ui.rules_lines = self.rules_lines.clone();
+		game_ui.set_table_info(self.name.clone(), self.info.clone(), self.info_lines.clone());
+		game_ui
+	}
+}
+
+/// One human at the table: their connection and their view of the game.
+/// A hot seat has several sharing the terminal.
+pub struct GameSession<'a> {
+	client: &'a mut GameClient,
+	username: String,
+	table: TableText,
+	theme: Theme,
+	theme_name: String,
+	pub game_ui: GameUI,
+	seat: Option<Seat>,
+	fast_forwarding: bool,
+	/// The pace they asked for with `+` and `-`, as a multiple of their
+	/// usual one.
+	speed: f32,
+	/// The player's achievements, watching for new ones.
+	achievements: Achievements,
+}
+
+impl<'a> GameSession<'a> {
+	pub fn new(
+		client: &'a mut GameClient,
+		username: &str,
+		table_config: &TableConfig,
+		num_players: usize,
+		theme: Theme,
+		theme_name: String,
+	) -> Self {
+		let table = TableText::new(table_config, num_players);
+		let mut game_ui = GameUI::new(None, theme.clone(), theme_name.clone());
+		game_ui.prefs = load_preferences();
+		let mut game_ui = table.seat_ui(&game_ui, None, &theme, &theme_name);
+		let achievements = Achievements::load(username).unwrap_or_else(|e| {
+			game_ui.status_message = Some(format!("Error: {}", e.user_message()));
+			Achievements::default()
+		});
+		Self {
+			client,
+			username: username.to_string(),
+			table,
+			theme,
+			theme_name,
+			game_ui,
+			seat: None,
+			fast_forwarding: false,
+			speed: 1.0,
+			achievements,
+		}
+	}
+
+	pub fn username(&self) -> &str {
+		&self.username
+	}
+
+	/// Seats the hero in a fresh `GameUI`.
+	fn sit(&mut self, seat: Seat) {
+		self.seat = Some(seat);
+		self.game_ui = self.table.seat_ui(&self.game_ui, Some(seat), &self.theme, &self.theme_name);
+		self.achievements.sit(seat, self.table.tournament);
+		send_seat_preferences(self.client, &self.game_ui.prefs);
+	}
+
+	/// Takes in what the server sent, passing each game event on to
+	/// `on_event` once it's applied. True if the hero was asked to act.
+	pub fn pump(&mut self, mut on_event: impl FnMut(&GameEvent, &GameUI) -> io::Result<()>) -> io::Result<bool> {
+		let mut asked = false;
+		while let Some(msg) = self.client.try_recv() {
+			match msg {
+				ServerMessage::GameEvent(event) => {
+					if let GameEvent::HandStarted { seats, .. } = &event {
+						if self.seat.is_none() {
+							let found_seat = seats.iter()
+								.find(|s| s.name.eq_ignore_ascii_case(&self.username))
+								.map(|s| s.seat);
+
+							if let Some(seat) = found_seat {
+								self.sit(seat);
+							}
+						}
+					}
+					self.game_ui.apply_event(&event);
+					self.track_achievements(&event);
+					on_event(&event, &self.game_ui)?;
+				}
+				ServerMessage::ActionRequest { valid_actions, time_limit } => {
+					self.game_ui.action_deadline = time_limit.map(|limit| self.client.action_deadline(limit));
+					match self.game_ui.enter_action_mode(valid_actions) {
+						Some(action) => {
+							let _ = self.client.action(action);
+						}
+						None => asked = true,
+					}
+				}
+				ServerMessage::TableChanged { seat, table_config, .. } => {
+					self.table = TableText::new(&table_config, table_config.max_players);
+					self.sit(seat);
+					self.game_ui.status_message = Some(format!("Moved to {}; you're in from the next hand", self.table.name));
+				}
+				ServerMessage::Error { message } => {
+					self.game_ui.status_message = Some(format!("Error: {}", message));
+				}
+				ServerMessage::ProtocolError { reason } => {
+					self.game_ui.status_message = Some(format!("Lost a message on the way to the server: {}", reason));
+				}
+				_ => {}
+			}
+		}
+		Ok(asked)
+	}
+
+	/// Announces in the chat anything `event` unlocked, and saves the
+	/// player's progress at the end of each hand and game.
+	fn track_achievements(&mut self, event: &GameEvent) {
+		for achievement in self.achievements.observe(event) {
+			let name = tr(&format!("achievement.{}", achievement.key()));
+			self.game_ui.table_view.chat_messages.push(ChatMessage {
+				sender: String::new(),
+				text: tr_with("achievement.unlocked", &[("name", &name)]),
+				is_system: true,
+			});
+		}
+		if matches!(event, GameEvent::HandEnded { .. } | GameEvent::GameEnded { .. }) {
+			if let Err(e) = self.achievements.save() {
+				self.game_ui.status_message = Some(format!("Error: {}", e.user_message()));
+			}
+		}
+	}
+
+	/// Keeps the connection state, latency and fast-forward in step with
+	/// the server.
+	pub fn sync(&mut self) {
+		self.game_ui.connection = self.client.connection_state();
+		let _ = self.client.ping_if_due();
+		self.game_ui.latency = self.client.latency();
+
+		let wants_fast_forward = self.game_ui.wants_fast_forward();
+		if wants_fast_forward != self.fast_forwarding {
+			self.fast_forwarding = wants_fast_forward;
+			let _ = self.client.fast_forward(self.fast_forwarding);
+		}
+	}
+
+	/// A key pressed at this player's view: `GameUI` works out what it
+	/// means and `act` carries it out.
+	pub fn press(&mut self, key: KeyCode) -> Option<GameLoopResult> {
+		let action = self.game_ui.handle_key(key);
+		self.act(action)
+	}
+
+	/// Carries out what a key press asked for. Quitting is left to the
+	/// caller, which may have other players to take away from the table.
+	pub fn act(&mut self, action: GameUIAction) -> Option<GameLoopResult> {
+		match action {
+			GameUIAction::Respond(PlayerResponse::Action(action)) => {
+				let _ = self.client.action(action);
+			}
+			GameUIAction::Chat(text) => {
+				let _ = self.client.chat(&text);
+			}
+			GameUIAction::PreferencesChanged => {
+				if let Err(e) = save_preferences(&self.game_ui.prefs) {
+					self.game_ui.status_message = Some(format!("Error: {}", e.user_message()));
+				}
+				let _ = self.client.auto_muck(self.game_ui.prefs.auto_muck);
+			}
+			GameUIAction::ChangeTable => {
+				let _ = self.client.change_table();
+			}
+			GameUIAction::TogglePause => {
+				let _ = self.client.pause(!self.game_ui.table_view.paused);
+			}
+			GameUIAction::ChangeSpeed { faster } => {
+				self.speed = Pacing::step_speed(self.speed, faster);
+				let prefs = &self.game_ui.prefs;
+				let usual = Pacing {
+					action_ms: prefs.action_delay_ms.unwrap_or(self.table.pacing.action_ms),
+					street_ms: prefs.street_delay_ms.unwrap_or(self.table.pacing.street_ms),
+					..self.table.pacing
+				};
+				let pacing = usual.at_speed(self.speed);
+				let _ = self.client.pacing(Some(pacing.action_ms), Some(pacing.street_ms));
+				self.game_ui.status_message = Some(tr_with("status.speed", &[("speed", &self.speed.to_string())]));
+			}
+			GameUIAction::TagHand(tag) => {
+				self.game_ui.status_message = Some(match self.game_ui.table_view.hand_ref() {
+					Some(hand_ref) => match HistoryLog::default().tag(&hand_ref, &tag) {
+						Ok(_) => format!("Tagged {} '{}'", hand_ref, tags::normalize(&tag)),
+						Err(e) => format!("Error: {}", e),
+					},
+					None => "No hand to tag yet".to_string(),
+				});
+			}
+			// With no server there's no lobby to go back to
+			GameUIAction::Quit if self.game_ui.connection == ConnectionState::Lost => {
+				return Some(GameLoopResult::Quit);
+			}
+			GameUIAction::Quit => return Some(GameLoopResult::ReturnToLobby),
+			GameUIAction::Detach => return Some(GameLoopResult::Detached),
+			_ => {}
+		}
+		None
+	}
+
+	/// Gets up from the table.
+	pub fn leave_table(&mut self) {
+		let _ = self.client.leave_table();
+	}
+}
+
+/// Takes everyone away from the table.
+pub fn leave(sessions: &mut [GameSession], result: GameLoopResult) -> GameLoopResult {
+	for session in sessions.iter_mut() {
+		session.leave_table();
+	}
+	if let GameLoopResult::ReturnToLobby = result {
+		std::thread::sleep(Duration::from_millis(100));
+		for session in sessions.iter() {
+			session.client.drain();
+		}
+	}
+	result
+}
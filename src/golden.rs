@@ -0,0 +1,271 @@
+//! Golden tests for the table view. A fixture is a stretch of play as one
+//! client saw it, one `GameEvent` per line in the wire's JSON; `replay`
+//! runs it through `ViewUpdater`, and `snapshot` writes out the
+//! `TableView` it leaves as plain text for `check` to compare with the
+//! `.snap` file beside the fixture. A change to the transformer then shows
+//! up as a diff of what the player would see. The repo's fixtures are in
+//! `tests/golden/`; UI authors can keep their own and `check_dir` them.
+//!
+//! Lines starting with `#` are comments, except `# hero: <seat>`, which
+//! replays from that seat's chair instead of a spectator's.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::PokerError;
+use crate::events::{GameEvent, Seat, ViewUpdater};
+use crate::view::{Card, PlayerStatus, TableView};
+
+/// Set it to write the snapshots instead of checking them.
+pub const UPDATE_VAR: &str = "UPDATE_GOLDEN";
+
+#[derive(Debug, Clone, Default)]
+pub struct Fixture {
+	pub hero: Option<Seat>,
+	pub events: Vec<GameEvent>,
+}
+
+impl Fixture {
+	pub fn parse(text: &str) -> Result<Self, PokerError> {
+		let mut fixture = Self::default();
+		for (i, line) in text.lines().enumerate() {
+			let line = line.trim();
+			if let Some(comment) = line.strip_prefix('#') {
+				if let Some(seat) = comment.trim().strip_prefix("hero:") {
+					let seat = seat.trim().parse().map_err(|_| PokerError::parse(format!("fixture line {}", i + 1), format!("bad hero seat '{}'", seat.trim())))?;
+					fixture.hero = Some(Seat(seat));
+				}
+				continue;
+			}
+			if line.is_empty() {
+				continue;
+			}
+			let event = serde_json::from_str(line).map_err(|e| PokerError::parse(format!("fixture line {}", i + 1), e))?;
+			fixture.events.push(event);
+		}
+		Ok(fixture)
+	}
+
+	pub fn load(path: &Path) -> Result<Self, PokerError> {
+		let content = fs::read_to_string(path).map_err(|e| PokerError::io("read", path, e))?;
+		Self::parse(&content).map_err(|e| match e {
+			PokerError::Parse { what, message } => PokerError::Parse { what: format!("{} {}", path.display(), what), message },
+			e => e,
+		})
+	}
+
+	/// A fresh view with every event applied.
+	pub fn replay(&self) -> TableView {
+		let updater = ViewUpdater::new(self.hero);
+		let mut view = TableView::new();
+		for event in &self.events {
+			updater.apply(&mut view, event);
+		}
+		view
+	}
+}
+
+/// The view as text: the header, board and pot, a line per seat, the
+/// action prompt, the info panel's numbers and the table log. Amounts are
+/// bare numbers so the snapshot doesn't depend on the money format.
+pub fn snapshot(view: &TableView) -> String {
+	let mut lines = vec![
+		format!("{} - {}", view.hand_label(), view.street.name()),
+		format!("Board: {}", cards(&view.board)),
+		format!("Pot: {}  Blinds: {}/{}", view.pot, view.blinds.0, view.blinds.1),
+	];
+	if let Some(game) = &view.current_game {
+		lines.push(format!("Game: {}", game));
+	}
+	if view.paused {
+		lines.push("Paused".to_string());
+	}
+	if let Some(spr) = view.spr {
+		lines.push(format!("SPR: {:.1}", spr));
+	}
+	if !view.pots.is_empty() {
+		lines.push(format!("Pots: {}", view.pots.iter().map(f32::to_string).collect::<Vec<_>>().join(" ")));
+	}
+	if view.antes > 0.0 {
+		lines.push(format!("Antes: {}", view.antes));
+	}
+
+	lines.push(String::new());
+	for p in &view.players {
+		let mut marks = vec![p.position.label()];
+		if p.is_hero {
+			marks.push("hero");
+		}
+		if p.is_actor {
+			marks.push("to act");
+		}
+		let hole = p.hole_cards.as_ref().map(|c| cards(c)).unwrap_or_else(|| "-".to_string());
+		let mut line = format!(
+			"Seat {} {:<12} stack {:<6} bet {:<6} {:<11} {:<5} {}",
+			p.seat,
+			p.name,
+			p.stack,
+			p.current_bet,
+			status(p.status),
+			hole,
+			marks.iter().filter(|m| !m.is_empty()).copied().collect::<Vec<_>>().join(", ")
+		);
+		if let Some(action) = &p.last_action {
+			line = format!("{} ({})", line.trim_end(), action);
+		}
+		lines.push(line.trim_end().to_string());
+	}
+
+	lines.push(String::new());
+	lines.push(match &view.action_prompt {
+		Some(prompt) if prompt.can_raise => format!(
+			"Prompt: {} to call, raise {} to {}",
+			prompt.to_call, prompt.min_raise, prompt.max_bet
+		),
+		Some(prompt) => format!("Prompt: {} to call, all-in {}", prompt.to_call, prompt.max_bet),
+		None => "Prompt: none".to_string(),
+	});
+	let info = &view.info;
+	lines.push(format!(
+		"Info: {} hands, pots {} (biggest {}), this hand {}, {} players left, level {}",
+		info.hands_played, info.total_pots, info.biggest_pot, info.hand_pot, info.players_remaining, info.blind_level
+	));
+
+	lines.push(String::new());
+	lines.push("Log:".to_string());
+	for message in &view.chat_messages {
+		if message.sender.is_empty() {
+			lines.push(format!("  {}", message.text));
+		} else {
+			lines.push(format!("  {}: {}", message.sender, message.text));
+		}
+	}
+	lines.push(String::new());
+	lines.join("\n")
+}
+
+fn cards(cards: &[Card]) -> String {
+	if cards.is_empty() {
+		return "-".to_string();
+	}
+	cards.iter().map(|c| format!("{}{}", c.rank, c.suit)).collect::<Vec<_>>().join(" ")
+}
+
+fn status(status: PlayerStatus) -> &'static str {
+	match status {
+		PlayerStatus::Active => "active",
+		PlayerStatus::Folded => "folded",
+		PlayerStatus::AllIn => "all-in",
+		PlayerStatus::SittingOut => "sitting out",
+		PlayerStatus::Eliminated => "eliminated",
+		PlayerStatus::Empty => "empty",
+	}
+}
+
+/// `<fixture>.snap`.
+pub fn snapshot_path(fixture: &Path) -> PathBuf {
+	fixture.with_extension("snap")
+}
+
+/// Replays the fixture and compares the view with its snapshot, or with
+/// `UPDATE_GOLDEN` set, writes the snapshot.
+pub fn check(fixture: &Path) -> Result<(), PokerError> {
+	let actual = snapshot(&Fixture::load(fixture)?.replay());
+	compare(&snapshot_path(fixture), &actual)
+}
+
+/// Compares `actual` with the snapshot at `path`, or with `UPDATE_GOLDEN`
+/// set, writes it there.
+pub fn compare(path: &Path, actual: &str) -> Result<(), PokerError> {
+	if std::env::var_os(UPDATE_VAR).is_some() {
+		return fs::write(path, actual).map_err(|e| PokerError::io("write", path, e));
+	}
+	let expected = fs::read_to_string(path).map_err(|e| match e.kind() {
+		io::ErrorKind::NotFound => PokerError::NotFound(format!("No snapshot at {} (set {} to write it)", path.display(), UPDATE_VAR)),
+		_ => PokerError::io("read", path, e),
+	})?;
+	match diff(&expected, actual) {
+		None => Ok(()),
+		Some(diff) => Err(PokerError::Mismatch(format!(
+			"{} no longer matches (set {} to accept the new version):\n{}",
+			path.display(),
+			UPDATE_VAR,
+			diff
+		))),
+	}
+}
+
+/// The `.jsonl` fixtures in `dir`, in name order.
+pub fn fixtures(dir: &Path) -> Result<Vec<PathBuf>, PokerError> {
+	let entries = fs::read_dir(dir).map_err(|e| PokerError::io("read", dir, e))?;
+	let mut fixtures = entries
+		.flatten()
+		.map(|entry| entry.path())
+		.filter(|path| path.extension().is_some_and(|e| e == "jsonl"))
+		.collect::<Vec<_>>();
+	fixtures.sort();
+	Ok(fixtures)
+}
+
+/// Runs `check` over every fixture in `dir` and says how many there were.
+pub fn check_all(dir: &Path, check: impl Fn(&Path) -> Result<(), PokerError>) -> Result<usize, PokerError> {
+	let fixtures = fixtures(dir)?;
+	let mut failures = fixtures.iter().filter_map(|fixture| check(fixture).err()).collect::<Vec<_>>();
+	match failures.len() {
+		0 => Ok(fixtures.len()),
+		1 => Err(failures.remove(0)),
+		_ => Err(PokerError::Mismatch(failures.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n\n"))),
+	}
+}
+
+/// Checks every `.jsonl` fixture in `dir` and says how many there were.
+pub fn check_dir(dir: &Path) -> Result<usize, PokerError> {
+	check_all(dir, check)
+}
+
+/// The lines that differ, `-` as expected and `+` as they are now.
+fn diff(expected: &str, actual: &str) -> Option<String> {
+	let expected = expected.lines().collect::<Vec<_>>();
+	let actual = actual.lines().collect::<Vec<_>>();
+	if expected == actual {
+		return None;
+	}
+	let mut lines = Vec::new();
+	for i in 0..expected.len().max(actual.len()) {
+		let (was, now) = (expected.get(i), actual.get(i));
+		if was != now {
+			if let Some(was) = was {
+				lines.push(format!("-{:>4} {}", i + 1, was));
+			}
+			if let Some(now) = now {
+				lines.push(format!("+{:>4} {}", i + 1, now));
+			}
+		}
+	}
+	Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_fixture_reads_the_hero_and_skips_comments() {
+		let text = "# A hand\n# hero: 2\n\n{\"ChatMessage\":{\"sender\":\"Dealer\",\"text\":\"Shuffle up\"}}\n";
+		let fixture = Fixture::parse(text).unwrap();
+		assert_eq!(fixture.hero, Some(Seat(2)));
+		assert_eq!(fixture.events.len(), 1);
+		let log = snapshot(&fixture.replay());
+		assert!(log.ends_with("Log:\n  Dealer: Shuffle up\n"), "{}", log);
+
+		assert_eq!(Fixture::parse("# hero: two").unwrap_err().to_string(), "Failed to parse fixture line 1: bad hero seat 'two'");
+		assert!(Fixture::parse("{\"Nope\":{}}").unwrap_err().to_string().starts_with("Failed to parse fixture line 1: "));
+	}
+
+	#[test]
+	fn test_diff_shows_the_changed_lines() {
+		assert_eq!(diff("a\nb\nc\n", "a\nb\nc"), None);
+		assert_eq!(diff("a\nb\n", "a\nB\nc\n").unwrap(), "-   2 b\n+   2 B\n+   3 c");
+	}
+}
@@ -0,0 +1,490 @@
+//! Reads hand histories saved by PokerStars and GGPoker, so hands played
+//! there can be exported and studied like our own. Only Hold'em with a
+//! single board is understood; other hands are skipped with the reason.
+
+use chrono::NaiveDateTime;
+
+use crate::error::PokerError;
+use crate::events::{Blinds, Card, GameId, HandId, PlayerAction, Seat, Street};
+use crate::strategy::Position;
+
+use super::{HandAction, HandHistory, HandPlayer};
+
+/// What came out of a file.
+#[derive(Debug, Default)]
+pub struct Import {
+	pub hands: Vec<HandHistory>,
+	pub skipped: Vec<Skipped>,
+}
+
+/// A hand left out, by the site's hand number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skipped {
+	pub hand: String,
+	pub reason: String,
+}
+
+const HEADERS: [&str; 4] = ["PokerStars Hand #", "PokerStars Zoom Hand #", "PokerStars Game #", "Poker Hand #"];
+
+/// Every hand in `text`, which may hold many, one after another. Hand
+/// numbers count from 1 in the order read; `HistoryLog::add` renumbers
+/// them to follow what a session already has.
+pub fn import(text: &str) -> Result<Import, PokerError> {
+	let text = text.trim_start_matches('\u{feff}');
+	let mut blocks: Vec<Vec<&str>> = Vec::new();
+	for line in text.lines().map(|l| l.trim_end_matches('\r')) {
+		if HEADERS.iter().any(|h| line.starts_with(h)) {
+			blocks.push(Vec::new());
+		}
+		if let Some(block) = blocks.last_mut() {
+			block.push(line);
+		}
+	}
+	if blocks.is_empty() {
+		return Err(PokerError::parse("hand history", "no PokerStars or GGPoker hands found"));
+	}
+
+	let mut import = Import::default();
+	for block in blocks {
+		let id = site_hand_id(block[0]);
+		match parse_hand(&block) {
+			Ok(mut hand) => {
+				hand.hand_num = import.hands.len() as u32 + 1;
+				import.hands.push(hand);
+			}
+			Err(reason) => import.skipped.push(Skipped { hand: id, reason }),
+		}
+	}
+	Ok(import)
+}
+
+fn site_hand_id(header: &str) -> String {
+	header
+		.split_once('#')
+		.map(|(_, rest)| rest.split(':').next().unwrap_or("").trim().to_string())
+		.unwrap_or_default()
+}
+
+/// Chips put in on the current street and in all, per player.
+struct Ledger {
+	street: Vec<f32>,
+	total: Vec<f32>,
+	back: Vec<f32>,
+}
+
+impl Ledger {
+	fn new(players: usize) -> Self {
+		Self { street: vec![0.0; players], total: vec![0.0; players], back: vec![0.0; players] }
+	}
+
+	fn put_in(&mut self, i: usize, amount: f32) {
+		self.street[i] += amount;
+		self.total[i] += amount;
+	}
+
+	fn raise_to(&mut self, i: usize, to: f32) {
+		self.total[i] += to - self.street[i];
+		self.street[i] = to;
+	}
+
+	fn new_street(&mut self) {
+		self.street.iter_mut().for_each(|s| *s = 0.0);
+	}
+}
+
+fn parse_hand(lines: &[&str]) -> Result<HandHistory, String> {
+	let header = lines[0];
+	let game = header.split_once(": ").map(|(_, g)| g).unwrap_or("");
+	if let Some(other) = ["Omaha", "Stud", "Razz", "Draw", "Badugi", "Courchevel", "6+ Hold'em", "Short Deck", "HORSE", "Mixed"]
+		.iter()
+		.find(|g| game.contains(**g))
+	{
+		return Err(format!("{} isn't supported", other));
+	}
+	if !game.contains("Hold'em") {
+		return Err("Not a Hold'em hand".to_string());
+	}
+	if lines.iter().any(|l| l.contains("*** FIRST") || l.contains("*** SECOND")) {
+		return Err("Run it twice isn't supported".to_string());
+	}
+	if lines.iter().any(|l| l.contains(" cashed out the hand")) {
+		return Err("All-in cash out isn't supported".to_string());
+	}
+
+	let site_id = site_hand_id(header);
+	let (small, big) = stakes(game).ok_or("No stakes in the header")?;
+	let started_at = game
+		.rsplit(" - ")
+		.next()
+		.and_then(|date| {
+			let mut parts = date.split_whitespace();
+			let stamp = format!("{} {}", parts.next()?, parts.next()?);
+			NaiveDateTime::parse_from_str(&stamp, "%Y/%m/%d %H:%M:%S").ok()
+		})
+		.map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string())
+		.unwrap_or_default();
+
+	let table_line = lines.iter().find(|l| l.starts_with("Table '")).ok_or("No table line")?;
+	let table = table_line["Table '".len()..].split('\'').next().unwrap_or("").to_string();
+	let button_number = table_line
+		.split("Seat #")
+		.nth(1)
+		.and_then(|rest| rest.split_whitespace().next())
+		.and_then(|n| n.parse::<usize>().ok())
+		.ok_or("No button in the table line")?;
+
+	// Seats, up to the first *** line: "Seat 3: name ($2.10 in chips)"
+	let mut players: Vec<HandPlayer> = Vec::new();
+	for line in lines.iter().skip(1).take_while(|l| !l.starts_with("***")) {
+		let Some(rest) = line.strip_prefix("Seat ") else {
+			continue;
+		};
+		let Some((number, rest)) = rest.split_once(": ") else {
+			continue;
+		};
+		let Some(open) = rest.rfind(" (") else {
+			continue;
+		};
+		let (name, chips) = (&rest[..open], &rest[open + 2..]);
+		if !chips.contains(" in chips") || line.ends_with("is sitting out") {
+			continue;
+		}
+		let number = number.parse::<usize>().map_err(|_| format!("Bad seat line: {}", line))?;
+		let stack = chips.split_whitespace().next().and_then(amount).ok_or_else(|| format!("Bad seat line: {}", line))?;
+		players.push(HandPlayer {
+			seat: Seat(number.saturating_sub(1)),
+			name: name.to_string(),
+			position: String::new(),
+			is_human: true,
+			stack,
+			hole_cards: None,
+			net: 0.0,
+			all_in: None,
+			made_hand: None,
+			best_five: Vec::new(),
+			mucked_cards: None,
+			tilt: None,
+		});
+	}
+	if players.len() < 2 {
+		return Err("Fewer than two players".to_string());
+	}
+	players.sort_by_key(|p| p.seat.0);
+	let button = Seat(button_number.saturating_sub(1));
+	// A dead button sits on an empty seat; count from the player before it
+	let button_index = players
+		.iter()
+		.rposition(|p| p.seat.0 <= button.0)
+		.unwrap_or(players.len() - 1);
+	let count = players.len();
+	for (i, player) in players.iter_mut().enumerate() {
+		player.position = Position::from_seat(i, button_index, count).name().to_string();
+	}
+
+	let mut ledger = Ledger::new(players.len());
+	let mut actions = Vec::new();
+	let mut street = Street::Preflop;
+	let mut board = Vec::new();
+	let mut ante = None;
+
+	for line in &lines[1..] {
+		if let Some(marker) = line.strip_prefix("*** ") {
+			let next = if marker.starts_with("FLOP") {
+				Some(Street::Flop)
+			} else if marker.starts_with("TURN") {
+				Some(Street::Turn)
+			} else if marker.starts_with("RIVER") {
+				Some(Street::River)
+			} else {
+				None
+			};
+			if let Some(next) = next {
+				street = next;
+				ledger.new_street();
+			}
+			if marker.starts_with("SUMMARY") {
+				break;
+			}
+			continue;
+		}
+		if let Some(cards) = line.strip_prefix("Uncalled bet (") {
+			let (returned, name) = cards.split_once(") returned to ").ok_or_else(|| format!("Bad line: {}", line))?;
+			let i = player_index(&players, name).ok_or_else(|| format!("Unknown player in: {}", line))?;
+			ledger.back[i] += amount(returned).ok_or_else(|| format!("Bad line: {}", line))?;
+			continue;
+		}
+		if let Some(rest) = line.strip_prefix("Dealt to ") {
+			if let Some((i, cards)) = named(&players, rest, " ").and_then(|(i, rest)| Some((i, hole_cards(rest)?))) {
+				players[i].hole_cards = Some(cards);
+			}
+			continue;
+		}
+		if let Some((i, rest)) = named(&players, line, " collected ") {
+			let won = rest.split_whitespace().next().and_then(amount).ok_or_else(|| format!("Bad line: {}", line))?;
+			ledger.back[i] += won;
+			continue;
+		}
+		let Some((i, verb)) = named(&players, line, ": ") else {
+			continue;
+		};
+		let all_in = verb.ends_with(" and is all-in");
+		let verb = verb.trim_end_matches(" and is all-in");
+		let bad = || format!("Bad action: {}", line);
+		let last_amount = || verb.split_whitespace().last().and_then(amount).ok_or_else(bad);
+		let seat = players[i].seat;
+		let action = if verb == "folds" {
+			PlayerAction::Fold
+		} else if verb == "checks" {
+			PlayerAction::Check
+		} else if verb.starts_with("calls ") {
+			let paid = last_amount()?;
+			ledger.put_in(i, paid);
+			PlayerAction::Call { amount: paid }
+		} else if verb.starts_with("bets ") {
+			let bet = last_amount()?;
+			ledger.put_in(i, bet);
+			if all_in { PlayerAction::AllIn { amount: ledger.street[i] } } else { PlayerAction::Bet { amount: bet } }
+		} else if verb.starts_with("raises ") {
+			let to = last_amount()?;
+			ledger.raise_to(i, to);
+			if all_in { PlayerAction::AllIn { amount: to } } else { PlayerAction::Raise { amount: to } }
+		} else if verb.starts_with("posts the ante ") {
+			let paid = last_amount()?;
+			ledger.total[i] += paid;
+			ante = Some(paid);
+			continue;
+		} else if verb.starts_with("posts small & big blinds ") {
+			// The small blind part is dead money
+			let paid = last_amount()?;
+			ledger.put_in(i, big);
+			ledger.total[i] += paid - big;
+			continue;
+		} else if verb.starts_with("posts ") {
+			ledger.put_in(i, last_amount()?);
+			continue;
+		} else if let Some(shown) = verb.strip_prefix("shows ").or_else(|| verb.strip_prefix("mucks ")) {
+			if let Some(cards) = hole_cards(shown) {
+				players[i].hole_cards = Some(cards);
+			}
+			continue;
+		} else {
+			continue;
+		};
+		actions.push(HandAction { street, seat, action, think_ms: None });
+	}
+
+	// Cards turned over show up again in the summary
+	let summary = lines.iter().skip_while(|l| !l.starts_with("*** SUMMARY"));
+	for line in summary {
+		if let Some(cards) = line.strip_prefix("Board ") {
+			board = parse_cards(cards).ok_or_else(|| format!("Bad board: {}", line))?;
+		} else if let Some(rest) = line.strip_prefix("Seat ").and_then(|r| r.split_once(": ")).map(|(_, r)| r) {
+			let at = rest.find(" showed [").or_else(|| rest.find(" mucked ["));
+			if let (Some(at), Some(i)) = (at, at.and_then(|at| player_index(&players, strip_role(&rest[..at])))) {
+				if let Some(cards) = hole_cards(&rest[at + " showed ".len()..]) {
+					players[i].hole_cards = Some(cards);
+				}
+			}
+		}
+	}
+
+	for (i, player) in players.iter_mut().enumerate() {
+		player.net = ((ledger.back[i] - ledger.total[i]) * 100.0).round() / 100.0;
+	}
+
+	let mut hand = HandHistory {
+		game_id: GameId(stable_hash(&format!("{} {}", site_name(header), table))),
+		table,
+		hand_num: 0,
+		hand_id: HandId(site_id.parse().unwrap_or_else(|_| stable_hash(&site_id))),
+		started_at,
+		button,
+		blinds: Blinds { small, big, ante },
+		players,
+		actions,
+		board,
+		duration_ms: None,
+		tags: Vec::new(),
+	};
+	super::luck::settle(&mut hand, &ledger.total);
+	Ok(hand)
+}
+
+fn site_name(header: &str) -> &'static str {
+	if header.starts_with("PokerStars") { "PokerStars" } else { "GGPoker" }
+}
+
+/// `name` as it starts `line`, followed by `separator`; the longest name
+/// wins, for names that start with another player's.
+fn named<'a>(players: &[HandPlayer], line: &'a str, separator: &str) -> Option<(usize, &'a str)> {
+	players
+		.iter()
+		.enumerate()
+		.filter(|(_, p)| line.starts_with(&p.name) && line[p.name.len()..].starts_with(separator))
+		.max_by_key(|(_, p)| p.name.len())
+		.map(|(i, p)| (i, &line[p.name.len() + separator.len()..]))
+}
+
+fn player_index(players: &[HandPlayer], name: &str) -> Option<usize> {
+	players.iter().position(|p| p.name == name)
+}
+
+/// "name (big blind)" to "name".
+fn strip_role(name: &str) -> &str {
+	match name.rfind(" (") {
+		Some(at) if name.ends_with(')') => &name[..at],
+		_ => name,
+	}
+}
+
+/// The smaller and bigger blind in "($0.01/$0.02 USD)" or "Level I (10/20)".
+fn stakes(game: &str) -> Option<(f32, f32)> {
+	game.split('(').skip(1).find_map(|part| {
+		let inside = part.split(')').next()?;
+		let (small, big) = inside.split_once('/')?;
+		Some((amount(small)?, amount(big.split_whitespace().next()?)?))
+	})
+}
+
+/// "$1,250.50", "€2" or "1500" as a number.
+fn amount(text: &str) -> Option<f32> {
+	text.trim()
+		.trim_start_matches(['$', '€', '£', '¥', '(', '₹'])
+		.trim_end_matches(')')
+		.replace(',', "")
+		.parse()
+		.ok()
+}
+
+/// The cards in the first "[..]" of `text`.
+fn parse_cards(text: &str) -> Option<Vec<Card>> {
+	let inside = text.split('[').nth(1)?.split(']').next()?;
+	inside
+		.split_whitespace()
+		.map(|c| {
+			let mut chars = c.chars();
+			let (rank, suit) = (chars.next()?, chars.next()?);
+			(chars.next().is_none() && "23456789TJQKA".contains(rank) && "shdc".contains(suit)).then(|| Card::new(rank, suit))
+		})
+		.collect()
+}
+
+fn hole_cards(text: &str) -> Option<[Card; 2]> {
+	match parse_cards(text)?.as_slice() {
+		[a, b] => Some([*a, *b]),
+		_ => None,
+	}
+}
+
+/// FNV-1a: the same ids for the same table on every run.
+fn stable_hash(text: &str) -> u64 {
+	text.bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const STARS: &str = "\u{feff}PokerStars Hand #243567890123: Hold'em No Limit ($0.01/$0.02 USD) - 2023/01/15 20:30:00 ET
+Table 'Alcyone II' 6-max Seat #3 is the button
+Seat 1: villain one ($2.00 in chips)
+Seat 2: Bob ($1.85 in chips)
+Seat 3: hero ($2.10 in chips)
+Seat 5: nap ($3 in chips) is sitting out
+villain one: posts small blind $0.01
+Bob: posts big blind $0.02
+*** HOLE CARDS ***
+Dealt to hero [Ah Kd]
+hero: raises $0.04 to $0.06
+villain one: folds
+Bob: calls $0.04
+*** FLOP *** [Kh 8c 3s]
+Bob: checks
+hero: bets $0.08
+Bob: raises $0.20 to $0.28
+hero: raises $1.76 to $2.04 and is all-in
+Bob: calls $1.51 and is all-in
+Uncalled bet ($0.25) returned to hero
+*** TURN *** [Kh 8c 3s] [9d]
+*** RIVER *** [Kh 8c 3s 9d] [2s]
+*** SHOW DOWN ***
+Bob: shows [8d 8s] (three of a kind, Eights)
+hero: shows [Ah Kd] (a pair of Kings)
+Bob collected $3.62 from pot
+*** SUMMARY ***
+Total pot $3.71 | Rake $0.09
+Board [Kh 8c 3s 9d 2s]
+Seat 1: villain one (small blind) folded before Flop
+Seat 2: Bob (big blind) showed [8d 8s] and won ($3.62) with three of a kind, Eights
+Seat 3: hero (button) showed [Ah Kd] and lost with a pair of Kings
+
+
+PokerStars Hand #243567890124: Omaha Pot Limit ($0.01/$0.02 USD) - 2023/01/15 20:31:00 ET
+Table 'Alcyone II' 6-max Seat #1 is the button
+";
+
+	const GG: &str = "Poker Hand #HD1234567: Hold'em No Limit ($0.05/$0.1) - 2024/03/02 11:04:10
+Table 'NLHGold12' 6-max Seat #1 is the button
+Seat 1: Hero ($10 in chips)
+Seat 2: 5f3a2b ($12.40 in chips)
+Hero: posts small blind $0.05
+5f3a2b: posts big blind $0.1
+*** HOLE CARDS ***
+Dealt to Hero [Qs Qh]
+Dealt to 5f3a2b
+Hero: raises $0.2 to $0.3
+5f3a2b: folds
+Uncalled bet ($0.2) returned to Hero
+*** SHOWDOWN ***
+Hero collected $0.2 from pot
+*** SUMMARY ***
+Total pot $0.2 | Rake $0
+";
+
+	#[test]
+	fn test_pokerstars_hand_with_showdown() {
+		let import = import(STARS).unwrap();
+		assert_eq!(import.hands.len(), 1);
+		assert_eq!(import.skipped, vec![Skipped { hand: "243567890124".to_string(), reason: "Omaha isn't supported".to_string() }]);
+
+		let hand = &import.hands[0];
+		assert_eq!(hand.table, "Alcyone II");
+		assert_eq!(hand.hand_id, HandId(243567890123));
+		assert_eq!(hand.started_at, "2023-01-15T20:30:00");
+		assert_eq!(hand.blinds.big, 0.02);
+		assert_eq!(hand.board.len(), 5);
+
+		// The sitting-out seat isn't dealt in
+		let names = hand.players.iter().map(|p| (p.name.as_str(), p.position.as_str())).collect::<Vec<_>>();
+		assert_eq!(names, [("villain one", "SB"), ("Bob", "BB"), ("hero", "BTN")]);
+
+		let hero = hand.player(Seat(2)).unwrap();
+		assert_eq!(hero.hole_cards, Some([Card::new('A', 'h'), Card::new('K', 'd')]));
+		assert_eq!(hero.net, -1.85);
+		assert_eq!(hand.player(Seat(1)).unwrap().net, 1.77);
+		assert_eq!(hand.player(Seat(0)).unwrap().net, -0.01);
+		assert!(hand.player(Seat(1)).unwrap().hole_cards.is_some());
+
+		assert_eq!(hand.actions.len(), 8);
+		assert!(matches!(hand.actions[6].action, PlayerAction::AllIn { amount } if amount == 2.04));
+		assert!(matches!(hand.actions[7].action, PlayerAction::Call { amount } if amount == 1.51));
+		assert_eq!(hand.actions[3].street, Street::Flop);
+	}
+
+	#[test]
+	fn test_ggpoker_hand() {
+		let import = import(GG).unwrap();
+		assert!(import.skipped.is_empty());
+		let hand = &import.hands[0];
+		assert_eq!(hand.players[0].position, "BTN");
+		assert_eq!(hand.players[0].hole_cards, Some([Card::new('Q', 's'), Card::new('Q', 'h')]));
+		assert_eq!(hand.players[1].hole_cards, None);
+		assert_eq!(hand.players[0].net, 0.1);
+		assert_eq!(hand.players[1].net, -0.1);
+	}
+
+	#[test]
+	fn test_not_a_hand_history() {
+		assert!(import("hello").is_err());
+	}
+}
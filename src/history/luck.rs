@@ -0,0 +1,286 @@
+//! All-in luck: what players were due when their chips went in with cards
+//! still to come, against what the board then gave them. Summed over a
+//! session it tells running hot or cold apart from playing well.
+
+use std::collections::HashSet;
+
+use super::{AllInEv, HandHistory};
+use crate::engine::equities;
+use crate::events::{PlayerAction, Street};
+
+/// Fills in `all_in` for everyone left in, if the betting was over with
+/// cards still to come and every live hand was turned over. `invested` is
+/// what each player put in, in `players` order.
+pub(crate) fn settle(hand: &mut HandHistory, invested: &[f32]) {
+	let folded = hand
+		.actions
+		.iter()
+		.filter(|a| matches!(a.action, PlayerAction::Fold))
+		.map(|a| a.seat)
+		.collect::<HashSet<_>>();
+	let live = (0..hand.players.len())
+		.filter(|&i| !folded.contains(&hand.players[i].seat))
+		.collect::<Vec<_>>();
+	let dealt = match hand.actions.last().map_or(Street::Preflop, |a| a.street) {
+		Street::Preflop => 0,
+		Street::Flop => 3,
+		Street::Turn => 4,
+		_ => 5,
+	};
+	if live.len() < 2 || dealt >= hand.board.len() || invested.len() != hand.players.len() {
+		return;
+	}
+	let Some(holes) = live.iter().map(|&i| hand.players[i].hole_cards).collect::<Option<Vec<_>>>() else {
+		return;
+	};
+	let board = &hand.board[..dealt];
+
+	// Rake comes out of every pot alike
+	let pot: f32 = invested.iter().sum();
+	let paid_out: f32 = hand.players.iter().zip(invested).map(|(p, put_in)| p.net + put_in).sum();
+	let kept = if pot > 0.0 { paid_out / pot } else { 1.0 };
+
+	// Main pot, then a side pot for each deeper stack; the last takes
+	// back anything nobody called
+	let mut levels = live.iter().map(|&i| invested[i]).collect::<Vec<_>>();
+	levels.sort_by(f32::total_cmp);
+	levels.dedup();
+	let mut due = vec![0.0; live.len()];
+	let mut below = 0.0;
+	for (n, &level) in levels.iter().enumerate() {
+		let top = if n + 1 == levels.len() { f32::INFINITY } else { level };
+		let amount = invested.iter().map(|&c| c.min(top) - c.min(below)).sum::<f32>() * kept;
+		let contesting = (0..live.len()).filter(|&j| invested[live[j]] >= level).collect::<Vec<_>>();
+		let hands = contesting.iter().map(|&j| holes[j]).collect::<Vec<_>>();
+		for (&j, share) in contesting.iter().zip(equities(&hands, board)) {
+			due[j] += share * amount;
+		}
+		below = level;
+	}
+
+	let main = equities(&holes, board);
+	for (j, &i) in live.iter().enumerate() {
+		hand.players[i].all_in = Some(AllInEv {
+			equity: main[j],
+			ev_net: ((due[j] - invested[i]) * 100.0).round() / 100.0,
+		});
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct PlayerLuck {
+	pub name: String,
+	pub hands: u32,
+	/// Hands all-in with cards to come.
+	pub all_ins: u32,
+	pub net: f32,
+	/// `net` with every all-in settled by equity instead of the board.
+	pub ev_net: f32,
+	/// Running `luck()` after each hand the player was dealt into.
+	pub curve: Vec<f32>,
+}
+
+impl PlayerLuck {
+	/// Won above what the all-ins were worth; negative when running cold.
+	pub fn luck(&self) -> f32 {
+		self.net - self.ev_net
+	}
+}
+
+/// Everyone's all-in luck over a set of hands, usually one session.
+#[derive(Debug, Clone, Default)]
+pub struct LuckReport {
+	/// Luckiest first.
+	pub players: Vec<PlayerLuck>,
+}
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Graph width in `LuckReport::lines`.
+const GRAPH_WIDTH: usize = 40;
+
+impl LuckReport {
+	pub fn from_hands(hands: &[HandHistory]) -> Self {
+		let mut players: Vec<PlayerLuck> = Vec::new();
+		for hand in hands {
+			for player in &hand.players {
+				let index = match players.iter().position(|p| p.name == player.name) {
+					Some(index) => index,
+					None => {
+						players.push(PlayerLuck {
+							name: player.name.clone(),
+							hands: 0,
+							all_ins: 0,
+							net: 0.0,
+							ev_net: 0.0,
+							curve: Vec::new(),
+						});
+						players.len() - 1
+					}
+				};
+				let luck = &mut players[index];
+				luck.hands += 1;
+				luck.net += player.net;
+				luck.ev_net += player.all_in.map_or(player.net, |a| a.ev_net);
+				luck.all_ins += player.all_in.is_some() as u32;
+				let so_far = luck.luck();
+				luck.curve.push(so_far);
+			}
+		}
+		players.sort_by(|a, b| b.luck().total_cmp(&a.luck()));
+		Self { players }
+	}
+
+	pub fn all_ins(&self) -> u32 {
+		self.players.iter().map(|p| p.all_ins).sum()
+	}
+
+	/// Cents only when the hands were played for them.
+	fn decimals(&self) -> usize {
+		if self.players.iter().any(|p| p.net.fract().abs() > 0.001) { 2 } else { 0 }
+	}
+
+	/// A row per player: hands, all-ins, net, all-in EV and luck.
+	pub fn table_lines(&self) -> Vec<String> {
+		let d = self.decimals();
+		let mut lines = vec![format!(
+			"{:<16} {:>6} {:>8} {:>10} {:>10} {:>10}",
+			"Player", "Hands", "All-ins", "Net", "All-in EV", "Luck"
+		)];
+		lines.push("-".repeat(65));
+		for p in &self.players {
+			lines.push(format!(
+				"{:<16} {:>6} {:>8} {:>+10.d$} {:>+10.d$} {:>+10.d$}",
+				p.name,
+				p.hands,
+				p.all_ins,
+				p.net,
+				p.ev_net,
+				p.luck(),
+				d = d
+			));
+		}
+		lines
+	}
+
+	/// Each player's running luck as a sparkline at most `width` wide,
+	/// all on one scale: `Alice  ▄▄▄▆▇▇▅ +120`.
+	pub fn graph_lines(&self, width: usize) -> Vec<String> {
+		let d = self.decimals();
+		let points = self.players.iter().flat_map(|p| p.curve.iter().copied()).chain([0.0]);
+		let (low, high) = points.fold((f32::MAX, f32::MIN), |(low, high), v| (low.min(v), high.max(v)));
+		let name_width = self.players.iter().map(|p| p.name.chars().count()).max().unwrap_or(0);
+		self.players
+			.iter()
+			.map(|p| {
+				let len = p.curve.len();
+				let samples = len.min(width.max(1));
+				let spark = (0..samples)
+					.map(|k| {
+						let v = p.curve[(k + 1) * len / samples - 1];
+						let level = if high > low { ((v - low) / (high - low) * 7.0).round() as usize } else { 0 };
+						SPARKS[level.min(7)]
+					})
+					.collect::<String>();
+				format!("{:<name_width$}  {} {:+.d$}", p.name, spark, p.luck(), name_width = name_width, d = d)
+			})
+			.collect()
+	}
+
+	pub fn lines(&self) -> Vec<String> {
+		let mut lines = self.table_lines();
+		lines.push(String::new());
+		lines.push("Luck over the session:".to_string());
+		lines.extend(self.graph_lines(GRAPH_WIDTH));
+		lines
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::cards;
+	use crate::history::{test_action, test_hand, test_player, HandPlayer};
+
+	fn player(seat: usize, name: &str, hole: &str, net: f32) -> HandPlayer {
+		HandPlayer { hole_cards: cards(hole).try_into().ok(), net, ..test_player(seat, name) }
+	}
+
+	/// Aces against kings, all in before the flop; the kings hit.
+	fn cooler(board: &str, aces_net: f32) -> HandHistory {
+		HandHistory {
+			players: vec![player(0, "Aces", "As Ah", aces_net), player(1, "Kings", "Ks Kh", -aces_net)],
+			actions: vec![
+				test_action(Street::Preflop, 0, PlayerAction::AllIn { amount: 100.0 }),
+				test_action(Street::Preflop, 1, PlayerAction::Call { amount: 90.0 }),
+			],
+			board: cards(board),
+			..test_hand()
+		}
+	}
+
+	#[test]
+	fn test_all_in_before_the_flop_is_settled_by_equity() {
+		let mut hand = cooler("Kd 7c 2h 9s 3d", -100.0);
+		settle(&mut hand, &[100.0, 100.0]);
+		let aces = hand.players[0].all_in.unwrap();
+		let kings = hand.players[1].all_in.unwrap();
+		assert!((aces.equity - 0.82).abs() < 0.03, "{:?}", aces);
+		assert!((aces.equity + kings.equity - 1.0).abs() < 1e-4);
+		assert!((aces.ev_net - (200.0 * aces.equity - 100.0)).abs() < 0.01);
+		assert!((aces.ev_net + kings.ev_net).abs() < 0.02);
+	}
+
+	#[test]
+	fn test_side_pot_and_uncalled_chips() {
+		let mut hand = cooler("Kd 7c 2h 9s 3d", -40.0);
+		hand.players.push(player(2, "Folder", "2c 3c", -10.0));
+		hand.actions.push(test_action(Street::Preflop, 2, PlayerAction::Fold));
+		// Aces only had 40; the kings' other 60 came back
+		hand.players[1].net = 50.0;
+		settle(&mut hand, &[40.0, 100.0, 10.0]);
+		let aces = hand.players[0].all_in.unwrap();
+		let kings = hand.players[1].all_in.unwrap();
+		assert!((aces.ev_net - (90.0 * aces.equity - 40.0)).abs() < 0.01);
+		assert!((kings.ev_net - (90.0 * kings.equity - 40.0)).abs() < 0.01);
+		assert!(hand.players[2].all_in.is_none());
+	}
+
+	#[test]
+	fn test_river_action_and_hidden_cards_are_not_all_ins() {
+		let mut hand = cooler("Kd 7c 2h 9s 3d", -100.0);
+		for action in &mut hand.actions {
+			action.street = Street::River;
+		}
+		settle(&mut hand, &[100.0, 100.0]);
+		assert!(hand.players.iter().all(|p| p.all_in.is_none()));
+
+		let mut hand = cooler("Kd 7c 2h 9s 3d", -100.0);
+		hand.players[1].hole_cards = None;
+		settle(&mut hand, &[100.0, 100.0]);
+		assert!(hand.players.iter().all(|p| p.all_in.is_none()));
+	}
+
+	#[test]
+	fn test_report_sums_luck_per_player() {
+		let mut unlucky = cooler("Kd 7c 2h 9s 3d", -100.0);
+		settle(&mut unlucky, &[100.0, 100.0]);
+		let mut plain = cooler("Kd 7c 2h 9s 3d", 10.0);
+		plain.actions.clear();
+		plain.board.clear();
+		let report = LuckReport::from_hands(&[unlucky, plain]);
+		assert_eq!(report.all_ins(), 2);
+		assert_eq!(report.players[0].name, "Kings");
+		let aces = &report.players[1];
+		assert_eq!((aces.hands, aces.all_ins), (2, 1));
+		assert_eq!(aces.net, -90.0);
+		assert!(aces.luck() < -100.0, "{}", aces.luck());
+		assert_eq!(aces.curve.len(), 2);
+
+		let lines = report.lines();
+		assert!(lines[0].starts_with("Player"));
+		let graph = report.graph_lines(10);
+		assert!(graph[0].starts_with("Kings  █"), "{:?}", graph);
+		assert!(graph[1].starts_with("Aces   ▁"), "{:?}", graph);
+	}
+}
@@ -0,0 +1,933 @@
+//! Hand histories: every finished hand written down, one JSON object per
+//! line, in a file per session (one game at one table). The server keeps
+//! them when it's given a `HistoryLog`; `poker export` reads them back
+//! as CSV or JSON.
+//!
+//! Hole cards are recorded once they're shown, at showdown or by choice.
+//! Hands folded or mucked stay unknown, a human's as much as an AI's,
+//! unless the table has `transparency` on: then every hand that was never
+//! shown is kept too, as `mucked_cards`, for reviewing once the game is
+//! over. They're only ever written here, never sent to anyone at the table.
+//!
+//! Hands that were all-in with cards to come also keep what each player
+//! was due by their equity at that moment; `luck` adds those up.
+//!
+//! Hands played since events carried times also keep how long the hand
+//! took and how long each decision did; `pace` adds those up.
+//!
+//! Players can tag hands to come back to; `tags` keeps them. `solver`
+//! sets a hand's flop up for studying in an outside solver, and `puzzle`
+//! turns the bets players faced into puzzles.
+//!
+//! Beside each session, `shuffle` keeps how its decks came out.
+//! `tracker` adds hands up per player and position for poker trackers.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::events::{Blinds, Card, EventTime, GameEvent, GameId, HandId, PlayerAction, Seat, Street};
+use crate::engine::best_five;
+use crate::error::PokerError;
+use crate::strategy::Position;
+
+pub mod import;
+pub mod luck;
+pub mod pace;
+pub mod puzzle;
+pub mod shuffle;
+pub mod solver;
+pub mod tags;
+pub mod tracker;
+
+/// One hand from the deal to the payout.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HandHistory {
+	pub game_id: GameId,
+	pub table: String,
+	pub hand_num: u32,
+	pub hand_id: HandId,
+	/// Local time of the deal, RFC 3339.
+	pub started_at: String,
+	pub button: Seat,
+	pub blinds: Blinds,
+	/// Everyone dealt in, in seat order.
+	pub players: Vec<HandPlayer>,
+	/// Every action after the blinds, in order.
+	pub actions: Vec<HandAction>,
+	pub board: Vec<Card>,
+	/// From the deal to the payout, in milliseconds, not counting time
+	/// the game was paused.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub duration_ms: Option<u64>,
+	/// What the player tagged it with, like `review later` or `bluff`.
+	/// Filled in from `tags.toml` on loading, never written to the session.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct HandPlayer {
+	pub seat: Seat,
+	pub name: String,
+	/// `BTN`, `SB`, `BB`, `UTG`, `MP` or `CO`.
+	pub position: String,
+	pub is_human: bool,
+	/// Stack before the blinds.
+	pub stack: f32,
+	pub hole_cards: Option<[Card; 2]>,
+	/// Won or lost over the hand, blinds included.
+	pub net: f32,
+	/// Set for everyone still in when the money went in with cards to come.
+	#[serde(default)]
+	pub all_in: Option<AllInEv>,
+	/// What they showed down, in words: "pair of kings, ace-queen-ten
+	/// kickers".
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub made_hand: Option<String>,
+	/// The five cards that made it, in the order described.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub best_five: Vec<Card>,
+	/// A hand folded or mucked without being shown; only kept at tables
+	/// with `transparency` on.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub mucked_cards: Option<[Card; 2]>,
+	/// An AI's tilt over the hand, 0.0 to 1.0, for those that can tilt;
+	/// only kept at tables with `transparency` on.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub tilt: Option<f32>,
+}
+
+/// A player's share of an all-in, by the odds rather than the board.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AllInEv {
+	/// Chance of winning the main pot when the last chips went in, ties split.
+	pub equity: f32,
+	/// `net` had every pot been shared out by equity.
+	pub ev_net: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HandAction {
+	pub street: Street,
+	pub seat: Seat,
+	pub action: PlayerAction,
+	/// From being asked to acting, in milliseconds.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub think_ms: Option<u64>,
+}
+
+impl HandHistory {
+	/// The session this hand belongs to, as `poker export` names it.
+	pub fn session(&self) -> String {
+		session_id(self.game_id)
+	}
+
+	pub fn reference(&self) -> String {
+		hand_ref(self.game_id, self.hand_num)
+	}
+
+	pub fn player(&self, seat: Seat) -> Option<&HandPlayer> {
+		self.players.iter().find(|p| p.seat == seat)
+	}
+
+	fn player_mut(&mut self, seat: Seat) -> Option<&mut HandPlayer> {
+		self.players.iter_mut().find(|p| p.seat == seat)
+	}
+
+	/// The hand with everyone's cards, as far as they were kept: a header
+	/// with the board and how long the hand took, then a line per player.
+	/// Unshown hands are marked folded or mucked; unknown ones are `?? ??`.
+	pub fn review(&self) -> Vec<String> {
+		let mut header = format!("Hand {}: {}", self.hand_num, cards_text(&self.board));
+		if let Some(ms) = self.duration_ms {
+			header.push_str(&format!(" ({})", pace::seconds(ms)));
+		}
+		if !self.tags.is_empty() {
+			header.push_str(&format!(" [{}]", self.tags.join(", ")));
+		}
+		let mut lines = vec![header];
+		let name_width = self.players.iter().map(|p| p.name.chars().count()).max().unwrap_or(0);
+		for player in &self.players {
+			let folded = self.actions.iter().any(|a| a.seat == player.seat && matches!(a.action, PlayerAction::Fold));
+			let cards = match (player.hole_cards, player.mucked_cards) {
+				(Some(cards), _) => cards_text(&cards),
+				(None, Some(cards)) if folded => format!("{} (folded)", cards_text(&cards)),
+				(None, Some(cards)) => format!("{} (mucked)", cards_text(&cards)),
+				(None, None) => "?? ??".to_string(),
+			};
+			let mut line = format!(
+				"  {:<w$}  {:<3}  {:<16} {:>+8}",
+				player.name,
+				player.position,
+				cards,
+				player.net,
+				w = name_width
+			);
+			if let Some(tilt) = player.tilt.filter(|&t| t > 0.0) {
+				line.push_str(&format!("  tilted {:.0}%", tilt * 100.0));
+			}
+			lines.push(line);
+		}
+		lines
+	}
+}
+
+/// A game's session name: the short game id the logs use.
+pub fn session_id(game_id: GameId) -> String {
+	format!("{:08x}", game_id.0 & 0xFFFFFFFF)
+}
+
+/// How players cite a hand: its session and its number there, like
+/// `56789abc-3`. The table header shows it and the dealer says it as each
+/// hand starts.
+pub fn hand_ref(game_id: GameId, hand_num: u32) -> String {
+	format!("{}-{}", session_id(game_id), hand_num)
+}
+
+/// The session and hand number in a `hand_ref`.
+pub fn parse_hand_ref(text: &str) -> Option<(&str, u32)> {
+	let (session, hand_num) = text.trim().split_once('-')?;
+	let valid = session.len() == 8 && session.chars().all(|c| c.is_ascii_hexdigit());
+	valid.then_some(())?;
+	Some((session, hand_num.parse().ok()?))
+}
+
+/// A hand for tests to fill in: hand 1 of game 1 at "Test", blinds 5/10
+/// with the button on seat 0, and nobody dealt in yet.
+#[cfg(test)]
+pub(crate) fn test_hand() -> HandHistory {
+	HandHistory {
+		game_id: GameId(1),
+		table: "Test".to_string(),
+		hand_num: 1,
+		hand_id: HandId(1),
+		started_at: String::new(),
+		button: Seat(0),
+		blinds: Blinds { small: 5.0, big: 10.0, ante: None },
+		players: Vec::new(),
+		actions: Vec::new(),
+		board: Vec::new(),
+		duration_ms: None,
+		tags: Vec::new(),
+	}
+}
+
+/// An AI in `seat` for `test_hand`, 100 behind, its cards unknown.
+#[cfg(test)]
+pub(crate) fn test_player(seat: usize, name: &str) -> HandPlayer {
+	HandPlayer {
+		seat: Seat(seat),
+		name: name.to_string(),
+		position: String::new(),
+		is_human: false,
+		stack: 100.0,
+		hole_cards: None,
+		net: 0.0,
+		all_in: None,
+		made_hand: None,
+		best_five: Vec::new(),
+		mucked_cards: None,
+		tilt: None,
+	}
+}
+
+#[cfg(test)]
+pub(crate) fn test_action(street: Street, seat: usize, action: PlayerAction) -> HandAction {
+	HandAction { street, seat: Seat(seat), action, think_ms: None }
+}
+
+/// Builds `HandHistory`s out of a game's events.
+pub struct HandRecorder {
+	game_id: GameId,
+	table: String,
+	hand: Option<HandHistory>,
+	street: Street,
+	/// Chips each player has put in this hand.
+	invested: HashMap<Seat, f32>,
+	/// Keep the hands nobody saw as `mucked_cards`.
+	transparency: bool,
+	dealt: HashMap<Seat, [Card; 2]>,
+	/// When the hand was dealt.
+	started: Option<EventTime>,
+	/// The seat last asked to act, and when.
+	asked: Option<(Seat, EventTime)>,
+	/// When the game was paused, if it is.
+	paused: Option<EventTime>,
+	/// Time spent paused this hand, in milliseconds.
+	paused_ms: u64,
+}
+
+impl HandRecorder {
+	pub fn new(game_id: GameId, table: impl Into<String>) -> Self {
+		Self {
+			game_id,
+			table: table.into(),
+			hand: None,
+			street: Street::Preflop,
+			invested: HashMap::new(),
+			transparency: false,
+			dealt: HashMap::new(),
+			started: None,
+			asked: None,
+			paused: None,
+			paused_ms: 0,
+		}
+	}
+
+	/// Records every hand dealt, shown or not; see the module docs.
+	pub fn with_transparency(mut self, transparency: bool) -> Self {
+		self.transparency = transparency;
+		self
+	}
+
+	/// Follows the game, unfiltered; returns each hand as it ends.
+	pub fn record(&mut self, event: &GameEvent) -> Option<HandHistory> {
+		match event {
+			GameEvent::HandStarted { hand_id, hand_num, button, blinds, seats, at, .. } => {
+				let dealt_in = seats.iter().filter(|s| s.is_active).collect::<Vec<_>>();
+				let button_index = dealt_in.iter().position(|s| s.seat == *button).unwrap_or(0);
+				let players = dealt_in
+					.iter()
+					.enumerate()
+					.map(|(i, s)| HandPlayer {
+						seat: s.seat,
+						name: s.name.clone(),
+						position: Position::from_seat(i, button_index, dealt_in.len()).name().to_string(),
+						is_human: s.is_human,
+						stack: s.stack,
+						hole_cards: None,
+						net: 0.0,
+						all_in: None,
+						made_hand: None,
+						best_five: Vec::new(),
+						mucked_cards: None,
+						tilt: None,
+					})
+					.collect();
+				self.dealt.clear();
+				self.street = Street::Preflop;
+				self.invested.clear();
+				self.started = *at;
+				self.asked = None;
+				self.paused_ms = 0;
+				let dealt_at = at
+					.and_then(|at| chrono::DateTime::from_timestamp_millis(at.unix_ms as i64))
+					.map(|t| t.with_timezone(&Local))
+					.unwrap_or_else(Local::now);
+				self.hand = Some(HandHistory {
+					game_id: self.game_id,
+					table: self.table.clone(),
+					hand_num: *hand_num,
+					hand_id: *hand_id,
+					started_at: dealt_at.to_rfc3339(),
+					button: *button,
+					blinds: *blinds,
+					players,
+					actions: Vec::new(),
+					board: Vec::new(),
+					duration_ms: None,
+					tags: Vec::new(),
+				});
+			}
+			GameEvent::HoleCardsDealt { seat, cards } => {
+				if self.transparency {
+					self.dealt.insert(*seat, *cards);
+				}
+			}
+			GameEvent::BlindPosted { seat, amount, .. } => {
+				*self.invested.entry(*seat).or_default() += amount;
+			}
+			GameEvent::StreetChanged { street, board, .. } => {
+				self.street = *street;
+				if let Some(hand) = &mut self.hand {
+					hand.board = board.clone();
+				}
+			}
+			GameEvent::ActionRequest { seat, at: Some(at), .. } => {
+				self.asked = Some((*seat, *at));
+			}
+			GameEvent::GamePaused { at } => {
+				self.paused = *at;
+			}
+			GameEvent::GameResumed { at: Some(at) } => {
+				if let Some(paused) = self.paused.take() {
+					self.paused_ms += at.since(paused);
+				}
+			}
+			GameEvent::ActionTaken { seat, action, stack_after, at, .. } => {
+				let think_ms = match (self.asked.take(), at) {
+					(Some((asked, asked_at)), Some(at)) if asked == *seat => Some(at.since(asked_at)),
+					_ => None,
+				};
+				if let Some(hand) = &mut self.hand {
+					if let Some(player) = hand.player(*seat) {
+						self.invested.insert(*seat, player.stack - stack_after);
+					}
+					hand.actions.push(HandAction { street: self.street, seat: *seat, action: action.clone(), think_ms });
+				}
+			}
+			GameEvent::ShowdownReveal { reveals } => {
+				if let Some(hand) = &mut self.hand {
+					for (seat, cards) in reveals {
+						if let Some(player) = hand.player_mut(*seat) {
+							player.hole_cards = Some(*cards);
+						}
+					}
+				}
+			}
+			GameEvent::HandEnded { results, at, .. } => {
+				let mut hand = self.hand.take()?;
+				hand.duration_ms = self.started.zip(*at).map(|(started, ended)| ended.since(started).saturating_sub(self.paused_ms));
+				let board = hand.board.clone();
+				for result in results {
+					if let Some(player) = hand.player_mut(result.seat) {
+						player.net = result.stack_change;
+						if self.transparency {
+							player.tilt = result.tilt;
+						}
+						if let Some(cards) = result.showed_cards {
+							player.hole_cards = Some(cards);
+						}
+						if let (Some(made_hand), Some(cards)) = (&result.hand_description, result.showed_cards) {
+							player.made_hand = Some(made_hand.clone());
+							player.best_five = best_five(&cards, &board);
+						}
+					}
+				}
+				for player in &mut hand.players {
+					if player.hole_cards.is_none() {
+						player.mucked_cards = self.dealt.get(&player.seat).copied();
+					}
+				}
+				let invested = hand
+					.players
+					.iter()
+					.map(|p| self.invested.get(&p.seat).copied().unwrap_or(0.0))
+					.collect::<Vec<_>>();
+				luck::settle(&mut hand, &invested);
+				return Some(hand);
+			}
+			_ => {}
+		}
+		None
+	}
+}
+
+/// A recorded session, as listed by `poker export`.
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+	pub id: String,
+	pub table: String,
+	pub started_at: String,
+	pub hands: usize,
+}
+
+/// The line `HistoryLog::end_session` writes after a session's last hand.
+#[derive(Serialize, Deserialize)]
+struct SessionEnded {
+	session_ended: String,
+}
+
+/// Where hand histories are kept: a directory of `<session>.jsonl` files.
+#[derive(Debug, Clone)]
+pub struct HistoryLog {
+	dir: PathBuf,
+}
+
+impl Default for HistoryLog {
+	/// `history/` in the data directory, beside `logs/`, or under
+	/// `profiles/<name>/` there once a profile is in use.
+	fn default() -> Self {
+		let dir = match crate::config::profile() {
+			Some(profile) => PathBuf::from("profiles").join(profile).join("history"),
+			None => PathBuf::from("history"),
+		};
+		match dirs::data_dir() {
+			Some(data_dir) => Self::new(data_dir.join("transparent-poker").join(dir)),
+			None => Self::new(dir),
+		}
+	}
+}
+
+impl HistoryLog {
+	pub fn new(dir: impl Into<PathBuf>) -> Self {
+		Self { dir: dir.into() }
+	}
+
+	pub fn dir(&self) -> &Path {
+		&self.dir
+	}
+
+	pub fn path(&self, session: &str) -> PathBuf {
+		self.dir.join(format!("{}.jsonl", session))
+	}
+
+	pub fn append(&self, hand: &HandHistory) -> Result<(), PokerError> {
+		let untagged = HandHistory { tags: Vec::new(), ..hand.clone() };
+		let line = serde_json::to_string(&untagged).map_err(|e| PokerError::parse(format!("hand {}", hand.hand_num), e))?;
+		self.append_line(&hand.session(), &line)
+	}
+
+	/// Marks a session over, after its last hand; until then `load` keeps
+	/// its `mucked_cards` back.
+	pub fn end_session(&self, game_id: GameId) -> Result<(), PokerError> {
+		let ended = SessionEnded { session_ended: Local::now().to_rfc3339() };
+		let line = serde_json::to_string(&ended).map_err(|e| PokerError::parse("session end", e))?;
+		self.append_line(&session_id(game_id), &line)
+	}
+
+	fn append_line(&self, session: &str, line: &str) -> Result<(), PokerError> {
+		fs::create_dir_all(&self.dir).map_err(|e| PokerError::io("create", &self.dir, e))?;
+		let path = self.path(session);
+		OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(&path)
+			.and_then(|mut file| writeln!(file, "{}", line))
+			.map_err(|e| PokerError::io("write", path, e))
+	}
+
+	pub fn load(&self, session: &str) -> Result<Vec<HandHistory>, PokerError> {
+		let path = self.path(session);
+		let content = fs::read_to_string(&path).map_err(|e| match e.kind() {
+			io::ErrorKind::NotFound => PokerError::NotFound(format!("No session '{}' in {}", session, self.dir.display())),
+			_ => PokerError::io("read", &path, e),
+		})?;
+		let mut ended = false;
+		let mut hands = Vec::new();
+		for (i, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+			if serde_json::from_str::<SessionEnded>(line).is_ok() {
+				ended = true;
+				continue;
+			}
+			let hand: HandHistory =
+				serde_json::from_str(line).map_err(|e| PokerError::parse(format!("{} line {}", path.display(), i + 1), e))?;
+			hands.push(hand);
+		}
+		let mut tags = self.tags()?;
+		for hand in &mut hands {
+			hand.tags = tags.remove(&hand.reference()).unwrap_or_default();
+			// Nobody sees what was folded while the game is still on
+			if !ended {
+				for player in &mut hand.players {
+					player.mucked_cards = None;
+				}
+			}
+		}
+		Ok(hands)
+	}
+
+	/// One hand, by its reference, like `56789abc-3`.
+	pub fn hand(&self, hand_ref: &str) -> Result<HandHistory, PokerError> {
+		let Some((session, hand_num)) = parse_hand_ref(hand_ref) else {
+			return Err(PokerError::parse("hand reference", format!("'{}' isn't like 56789abc-3", hand_ref)));
+		};
+		self.load(&session.to_lowercase())?
+			.into_iter()
+			.find(|hand| hand.hand_num == hand_num)
+			.ok_or_else(|| PokerError::NotFound(format!("Session {} has no hand {}", session, hand_num)))
+	}
+
+	/// Saves hands from elsewhere, each into its session and numbered on
+	/// from the hands already there. Hands a session already has are left
+	/// out, so a file can be imported twice. Returns how many were new.
+	pub fn add(&self, hands: Vec<HandHistory>) -> Result<usize, PokerError> {
+		let mut known: HashMap<String, (HashSet<HandId>, u32)> = HashMap::new();
+		let mut added = 0;
+		for mut hand in hands {
+			let session = hand.session();
+			if !known.contains_key(&session) {
+				let existing = if self.path(&session).exists() { self.load(&session)? } else { Vec::new() };
+				let ids = existing.iter().map(|h| h.hand_id).collect();
+				known.insert(session.clone(), (ids, existing.len() as u32));
+			}
+			let (ids, count) = known.get_mut(&session).expect("inserted above");
+			if !ids.insert(hand.hand_id) {
+				continue;
+			}
+			*count += 1;
+			hand.hand_num = *count;
+			self.append(&hand)?;
+			added += 1;
+		}
+		Ok(added)
+	}
+
+	/// Every recorded session, oldest first.
+	pub fn sessions(&self) -> Vec<SessionSummary> {
+		let Ok(entries) = fs::read_dir(&self.dir) else {
+			return Vec::new();
+		};
+		let mut sessions = entries
+			.flatten()
+			.filter_map(|entry| {
+				let path = entry.path();
+				if path.extension()? != "jsonl" {
+					return None;
+				}
+				let id = path.file_stem()?.to_str()?.to_string();
+				let hands = self.load(&id).ok()?;
+				let first = hands.first()?;
+				Some(SessionSummary {
+					table: first.table.clone(),
+					started_at: first.started_at.clone(),
+					hands: hands.len(),
+					id,
+				})
+			})
+			.collect::<Vec<_>>();
+		sessions.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+		sessions
+	}
+}
+
+/// Header of `to_csv`, one row per player per hand.
+pub const CSV_COLUMNS: [&str; 15] = [
+	"session",
+	"hand",
+	"hand_id",
+	"started_at",
+	"table",
+	"seat",
+	"player",
+	"position",
+	"stack",
+	"hole_cards",
+	"board",
+	"actions",
+	"net",
+	"mucked_cards",
+	"tags",
+];
+
+pub fn to_csv(hands: &[HandHistory]) -> String {
+	let mut out = CSV_COLUMNS.join(",");
+	out.push('\n');
+	for hand in hands {
+		for player in &hand.players {
+			let row = [
+				hand.session(),
+				hand.hand_num.to_string(),
+				hand.hand_id.0.to_string(),
+				hand.started_at.clone(),
+				hand.table.clone(),
+				player.seat.0.to_string(),
+				player.name.clone(),
+				player.position.clone(),
+				player.stack.to_string(),
+				player.hole_cards.map(|cards| cards_text(&cards)).unwrap_or_default(),
+				cards_text(&hand.board),
+				actions_text(hand, player.seat),
+				player.net.to_string(),
+				player.mucked_cards.map(|cards| cards_text(&cards)).unwrap_or_default(),
+				hand.tags.join("; "),
+			];
+			out.push_str(&row.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(","));
+			out.push('\n');
+		}
+	}
+	out
+}
+
+pub fn to_json(hands: &[HandHistory]) -> String {
+	let mut json = serde_json::to_string_pretty(hands).expect("Hand histories always serialize");
+	json.push('\n');
+	json
+}
+
+fn csv_field(field: &str) -> String {
+	if field.contains([',', '"', '\n']) {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_string()
+	}
+}
+
+fn cards_text(cards: &[Card]) -> String {
+	cards.iter().map(|c| format!("{}{}", c.rank, c.suit)).collect::<Vec<_>>().join(" ")
+}
+
+/// One player's actions, streets split by ` / ` and trailing empty
+/// streets left off: `R60 C180 / X / B40 F`.
+fn actions_text(hand: &HandHistory, seat: Seat) -> String {
+	let mut streets = [Street::Preflop, Street::Flop, Street::Turn, Street::River]
+		.iter()
+		.map(|street| {
+			hand.actions
+				.iter()
+				.filter(|a| a.seat == seat && a.street == *street)
+				.map(|a| action_code(&a.action))
+				.collect::<Vec<_>>()
+				.join(" ")
+		})
+		.collect::<Vec<_>>();
+	while streets.last().is_some_and(|s| s.is_empty()) {
+		streets.pop();
+	}
+	streets.join(" / ")
+}
+
+fn action_code(action: &PlayerAction) -> String {
+	match action {
+		PlayerAction::Fold => "F".to_string(),
+		PlayerAction::Check => "X".to_string(),
+		PlayerAction::Call { amount } => format!("C{}", amount),
+		PlayerAction::Bet { amount } => format!("B{}", amount),
+		PlayerAction::Raise { amount } => format!("R{}", amount),
+		PlayerAction::AllIn { amount } => format!("A{}", amount),
+		PlayerAction::Timeout => "T".to_string(),
+		PlayerAction::Show => "S".to_string(),
+		PlayerAction::Muck => "M".to_string(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{HandResult, Position as SeatPosition, SeatInfo, ValidActions};
+
+	fn seat(seat: usize, name: &str, is_human: bool) -> SeatInfo {
+		SeatInfo {
+			seat: Seat(seat),
+			name: name.to_string(),
+			stack: 100.0,
+			position: SeatPosition::None,
+			is_active: true,
+			is_human,
+			is_occupied: true,
+		}
+	}
+
+	fn played_hand() -> Vec<GameEvent> {
+		vec![
+			GameEvent::HandStarted {
+				hand_id: HandId(42),
+				hand_num: 3,
+				button: Seat(0),
+				blinds: Blinds { small: 5.0, big: 10.0, ante: None },
+				seats: vec![seat(0, "Alice", true), seat(1, "Bob", false), seat(2, "Carol, Jr.", false)],
+				at: None,
+				game_id: None,
+			},
+			GameEvent::HoleCardsDealt { seat: Seat(0), cards: [Card::new('A', 's'), Card::new('K', 's')] },
+			GameEvent::HoleCardsDealt { seat: Seat(1), cards: [Card::new('7', 'd'), Card::new('2', 'c')] },
+			GameEvent::HoleCardsDealt { seat: Seat(2), cards: [Card::new('Q', 'h'), Card::new('Q', 'd')] },
+			GameEvent::ActionTaken { seat: Seat(0), action: PlayerAction::Raise { amount: 30.0 }, stack_after: 70.0, pot_after: 45.0, at: None },
+			GameEvent::ActionTaken { seat: Seat(1), action: PlayerAction::Fold, stack_after: 95.0, pot_after: 45.0, at: None },
+			GameEvent::ActionTaken { seat: Seat(2), action: PlayerAction::Call { amount: 20.0 }, stack_after: 70.0, pot_after: 65.0, at: None },
+			GameEvent::StreetChanged { street: Street::Flop, board: vec![Card::new('K', 'h'), Card::new('8', 'c'), Card::new('3', 's')], pot: 65.0, effective_stack: 70.0, pots: Vec::new() },
+			GameEvent::ActionTaken { seat: Seat(2), action: PlayerAction::Check, stack_after: 70.0, pot_after: 65.0, at: None },
+			GameEvent::ActionTaken { seat: Seat(0), action: PlayerAction::Bet { amount: 40.0 }, stack_after: 30.0, pot_after: 105.0, at: None },
+			GameEvent::ActionTaken { seat: Seat(2), action: PlayerAction::Fold, stack_after: 70.0, pot_after: 105.0, at: None },
+			GameEvent::HandEnded {
+				hand_id: HandId(42),
+				results: [(0, 65.0), (1, -5.0), (2, -30.0)]
+					.into_iter()
+					.map(|(s, change)| HandResult {
+						seat: Seat(s),
+						stack_change: change,
+						final_stack: 100.0 + change,
+						// Alice shows her uncalled bet
+						showed_cards: (s == 0).then(|| [Card::new('A', 's'), Card::new('K', 's')]),
+						hand_description: None,
+						tilt: (s == 1).then_some(0.6),
+					})
+					.collect(),
+					at: None,
+			},
+		]
+	}
+
+	fn recorded() -> HandHistory {
+		let mut recorder = HandRecorder::new(GameId(0x1234_5678_9abc), "Home game");
+		let mut hands = played_hand().iter().filter_map(|e| recorder.record(e)).collect::<Vec<_>>();
+		assert_eq!(hands.len(), 1);
+		hands.remove(0)
+	}
+
+	#[test]
+	fn test_recorder_keeps_positions_actions_and_net() {
+		let hand = recorded();
+		assert_eq!(hand.session(), "56789abc");
+		assert_eq!(hand.hand_num, 3);
+		assert_eq!(hand.board.len(), 3);
+		let positions = hand.players.iter().map(|p| p.position.as_str()).collect::<Vec<_>>();
+		assert_eq!(positions, ["BTN", "SB", "BB"]);
+		assert_eq!(hand.player(Seat(0)).unwrap().net, 65.0);
+		assert_eq!(hand.actions.len(), 6);
+		assert_eq!(hand.actions[3].street, Street::Flop);
+
+		// Only the cards shown; the folded hands stay unknown
+		assert!(hand.player(Seat(0)).unwrap().hole_cards.is_some());
+		assert!(hand.player(Seat(1)).unwrap().hole_cards.is_none());
+		assert!(hand.player(Seat(2)).unwrap().hole_cards.is_none());
+	}
+
+	#[test]
+	fn test_recorder_keeps_a_folded_human_hand_unknown() {
+		let mut events = played_hand();
+		let Some(GameEvent::HandStarted { seats, .. }) = events.first_mut() else {
+			unreachable!()
+		};
+		seats[1].is_human = true;
+		let mut recorder = HandRecorder::new(GameId(1), "Home game");
+		let hand = events.iter().find_map(|e| recorder.record(e)).unwrap();
+		let bob = hand.player(Seat(1)).unwrap();
+		assert!(bob.is_human);
+		assert_eq!(bob.hole_cards, None);
+		assert_eq!(bob.mucked_cards, None);
+
+		let mut recorder = HandRecorder::new(GameId(1), "Home game").with_transparency(true);
+		let hand = events.iter().find_map(|e| recorder.record(e)).unwrap();
+		assert_eq!(hand.player(Seat(1)).unwrap().mucked_cards, Some([Card::new('7', 'd'), Card::new('2', 'c')]));
+	}
+
+	#[test]
+	fn test_recorder_keeps_the_hand_shown_down() {
+		let mut events = played_hand();
+		let Some(GameEvent::HandEnded { results, .. }) = events.last_mut() else {
+			unreachable!()
+		};
+		results[2].showed_cards = Some([Card::new('Q', 'h'), Card::new('Q', 'd')]);
+		results[2].hand_description = Some("pair of queens, king-eight-three kickers".to_string());
+		let mut recorder = HandRecorder::new(GameId(1), "Home game");
+		let hand = events.iter().find_map(|e| recorder.record(e)).unwrap();
+
+		let carol = hand.player(Seat(2)).unwrap();
+		assert_eq!(carol.made_hand.as_deref(), Some("pair of queens, king-eight-three kickers"));
+		assert_eq!(carol.best_five.iter().map(|c| c.rank).collect::<String>(), "QQK83");
+		assert!(hand.player(Seat(0)).unwrap().made_hand.is_none());
+	}
+
+	#[test]
+	fn test_transparency_keeps_the_hands_nobody_saw() {
+		let mut recorder = HandRecorder::new(GameId(1), "Home game").with_transparency(true);
+		let hand = played_hand().iter().find_map(|e| recorder.record(e)).unwrap();
+		assert_eq!(hand.player(Seat(0)).unwrap().mucked_cards, None, "shown cards aren't mucked");
+		assert_eq!(hand.player(Seat(1)).unwrap().mucked_cards, Some([Card::new('7', 'd'), Card::new('2', 'c')]));
+		assert!(hand.player(Seat(2)).unwrap().hole_cards.is_none());
+
+		let review = hand.review();
+		assert_eq!(review[0], "Hand 3: Kh 8c 3s");
+		assert!(review[1].contains("As Ks") && review[1].ends_with("+65"), "{}", review[1]);
+		assert!(review[2].contains("7d 2c (folded)"), "{}", review[2]);
+		assert!(review[2].ends_with("-5  tilted 60%"), "{}", review[2]);
+		assert!(recorded().review()[2].contains("?? ??"));
+		assert_eq!(recorded().player(Seat(1)).unwrap().tilt, None, "tilt is kept with transparency only");
+		assert!(to_csv(&[hand]).lines().nth(3).unwrap().ends_with(",-30,Qh Qd,"));
+	}
+
+	#[test]
+	fn test_hand_reference_names_the_session_and_number() {
+		assert_eq!(recorded().reference(), "56789abc-3");
+		assert_eq!(parse_hand_ref(" 56789abc-3 "), Some(("56789abc", 3)));
+		assert_eq!(parse_hand_ref("56789abc"), None);
+		assert_eq!(parse_hand_ref("56789abc-"), None);
+		assert_eq!(parse_hand_ref("5678-3"), None);
+		assert_eq!(parse_hand_ref("1234"), None);
+	}
+
+	#[test]
+	fn test_csv_rows_per_player() {
+		let csv = to_csv(&[recorded()]);
+		let lines = csv.lines().collect::<Vec<_>>();
+		assert_eq!(lines[0], CSV_COLUMNS.join(","));
+		assert_eq!(lines.len(), 4);
+		assert!(lines[1].ends_with(",0,Alice,BTN,100,As Ks,Kh 8c 3s,R30 / B40,65,,"), "{}", lines[1]);
+		assert!(lines[2].ends_with(",Bob,SB,100,,Kh 8c 3s,F,-5,,"), "{}", lines[2]);
+		assert!(lines[3].contains(",\"Carol, Jr.\",BB,"), "{}", lines[3]);
+		assert!(lines[3].ends_with(",C20 / X F,-30,,"), "{}", lines[3]);
+	}
+
+	#[test]
+	fn test_recorder_times_the_hand_and_each_decision() {
+		let time = |game_ms: u64| Some(EventTime { unix_ms: 1_700_000_000_000 + game_ms, game_ms });
+		let ask = |seat: usize, game_ms: u64| {
+			let mut request = GameEvent::ActionRequest {
+				seat: Seat(seat),
+				valid_actions: ValidActions {
+					can_fold: true,
+					can_check: false,
+					call_amount: Some(10.0),
+					raise_options: None,
+					can_all_in: true,
+					all_in_amount: 100.0,
+					can_show: false,
+				},
+				time_limit: None,
+				at: None,
+			};
+			request.stamp(time(game_ms));
+			request
+		};
+		let mut events = played_hand();
+		events[0].stamp(time(1_000));
+		events.insert(4, ask(0, 2_000));
+		events[5].stamp(time(6_500));
+		events.insert(6, ask(1, 7_000));
+		events[7].stamp(time(7_250));
+		events.last_mut().unwrap().stamp(time(155_000));
+		// A minute's break late in the hand doesn't count
+		events.insert(events.len() - 1, GameEvent::GamePaused { at: time(60_000) });
+		events.insert(events.len() - 1, GameEvent::GameResumed { at: time(120_000) });
+
+		let mut recorder = HandRecorder::new(GameId(1), "Home game");
+		let hand = events.iter().find_map(|e| recorder.record(e)).unwrap();
+		assert_eq!(hand.duration_ms, Some(94_000));
+		let thinks = hand.actions.iter().map(|a| a.think_ms).collect::<Vec<_>>();
+		assert_eq!(thinks[..3], [Some(4_500), Some(250), None]);
+		assert_eq!(hand.review()[0], "Hand 3: Kh 8c 3s (1m 34s)");
+
+		let pace = pace::PaceReport::from_hands(&[hand, recorded()]);
+		assert_eq!(pace.hands, 1);
+		assert_eq!(pace.players[0].name, "Alice");
+		assert_eq!(pace.lines()[0], "Hands took 1m 34s on average, the longest 1m 34s");
+		assert!(pace.lines()[5].starts_with("Bob") && pace.lines()[5].ends_with("0.2s"), "{:?}", pace.lines());
+		assert!(pace::PaceReport::from_hands(&[recorded()]).is_empty());
+	}
+
+	#[test]
+	fn test_log_round_trip() {
+		let dir = std::env::temp_dir().join(format!("poker-history-test-{}", std::process::id()));
+		let log = HistoryLog::new(&dir);
+		let hand = recorded();
+		log.append(&hand).unwrap();
+		log.append(&hand).unwrap();
+
+		assert_eq!(to_json(&log.load("56789abc").unwrap()), to_json(&[hand.clone(), hand]));
+		let sessions = log.sessions();
+		assert_eq!(sessions.len(), 1);
+		assert_eq!(sessions[0].hands, 2);
+		assert_eq!(sessions[0].table, "Home game");
+		assert!(log.load("nope").is_err());
+
+		// Adding the same hand again changes nothing; a new one is numbered on
+		let mut other = recorded();
+		other.hand_id = HandId(43);
+		assert_eq!(log.add(vec![recorded(), other]).unwrap(), 1);
+		assert_eq!(log.load("56789abc").unwrap().last().unwrap().hand_num, 3);
+		let _ = fs::remove_dir_all(&dir);
+	}
+
+	#[test]
+	fn test_mucked_cards_wait_for_the_session_to_end() {
+		let dir = std::env::temp_dir().join(format!("poker-history-ended-test-{}", std::process::id()));
+		let log = HistoryLog::new(&dir);
+		let mut recorder = HandRecorder::new(GameId(1), "Home game").with_transparency(true);
+		let hand = played_hand().iter().find_map(|e| recorder.record(e)).unwrap();
+		log.append(&hand).unwrap();
+
+		let bob = |log: &HistoryLog| log.load(&hand.session()).unwrap()[0].player(Seat(1)).unwrap().mucked_cards;
+		assert_eq!(bob(&log), None, "still being written");
+		log.end_session(GameId(1)).unwrap();
+		assert_eq!(bob(&log), Some([Card::new('7', 'd'), Card::new('2', 'c')]));
+		assert_eq!(log.sessions()[0].hands, 1);
+		let _ = fs::remove_dir_all(&dir);
+	}
+}
@@ -0,0 +1,110 @@
+//! The pace of a session: how long hands took from the deal to the
+//! payout, and how long each player took over their decisions. Only
+//! hands recorded from timed events count; older histories and imported
+//! ones have no times and are left out.
+
+use super::HandHistory;
+
+#[derive(Debug, Clone)]
+pub struct PlayerPace {
+	pub name: String,
+	/// Decisions with a think time.
+	pub decisions: u32,
+	pub total_ms: u64,
+	pub longest_ms: u64,
+}
+
+impl PlayerPace {
+	pub fn average_ms(&self) -> u64 {
+		self.total_ms / self.decisions.max(1) as u64
+	}
+}
+
+/// Hand lengths and think times over a set of hands, usually one session.
+#[derive(Debug, Clone, Default)]
+pub struct PaceReport {
+	/// Hands with a duration.
+	pub hands: u32,
+	pub total_ms: u64,
+	pub longest_ms: u64,
+	/// Slowest on average first.
+	pub players: Vec<PlayerPace>,
+}
+
+impl PaceReport {
+	pub fn from_hands(hands: &[HandHistory]) -> Self {
+		let mut report = Self::default();
+		for hand in hands {
+			if let Some(ms) = hand.duration_ms {
+				report.hands += 1;
+				report.total_ms += ms;
+				report.longest_ms = report.longest_ms.max(ms);
+			}
+			for action in &hand.actions {
+				let (Some(ms), Some(player)) = (action.think_ms, hand.player(action.seat)) else {
+					continue;
+				};
+				let index = match report.players.iter().position(|p| p.name == player.name) {
+					Some(index) => index,
+					None => {
+						report.players.push(PlayerPace {
+							name: player.name.clone(),
+							decisions: 0,
+							total_ms: 0,
+							longest_ms: 0,
+						});
+						report.players.len() - 1
+					}
+				};
+				let pace = &mut report.players[index];
+				pace.decisions += 1;
+				pace.total_ms += ms;
+				pace.longest_ms = pace.longest_ms.max(ms);
+			}
+		}
+		report.players.sort_by_key(|p| std::cmp::Reverse(p.average_ms()));
+		report
+	}
+
+	/// Whether any hand had times to go on.
+	pub fn is_empty(&self) -> bool {
+		self.hands == 0 && self.players.is_empty()
+	}
+
+	/// The average and longest hand, then a row per player: decisions,
+	/// average and longest think.
+	pub fn lines(&self) -> Vec<String> {
+		let mut lines = Vec::new();
+		if self.hands > 0 {
+			lines.push(format!(
+				"Hands took {} on average, the longest {}",
+				seconds(self.total_ms / self.hands as u64),
+				seconds(self.longest_ms)
+			));
+			lines.push(String::new());
+		}
+		if !self.players.is_empty() {
+			lines.push(format!("{:<16} {:>9} {:>9} {:>9}", "Player", "Decisions", "Average", "Longest"));
+			lines.push("-".repeat(46));
+			for p in &self.players {
+				lines.push(format!(
+					"{:<16} {:>9} {:>9} {:>9}",
+					p.name,
+					p.decisions,
+					seconds(p.average_ms()),
+					seconds(p.longest_ms)
+				));
+			}
+		}
+		lines
+	}
+}
+
+/// `4.2s`, or `2m 05s` from a minute up.
+pub fn seconds(ms: u64) -> String {
+	if ms < 60_000 {
+		format!("{:.1}s", ms as f64 / 1000.0)
+	} else {
+		format!("{}m {:02}s", ms / 60_000, ms % 60_000 / 1000)
+	}
+}
@@ -0,0 +1,413 @@
+//! Puzzles from recorded hands: a bet a player faced after the flop, set
+//! out as it stood, for answering before seeing what they did and how it
+//! came out. Each option is then judged by the player's equity at that
+//! moment: against the hands still in when the history kept them all,
+//! against random hands when it didn't.
+//!
+//! `of_the_day` picks one of the biggest decisions for the day, the same
+//! one all day. With no hands of their own yet, players get `samples`.
+
+use chrono::{Datelike, NaiveDate};
+
+use super::{cards_text, import, HandHistory};
+use crate::engine::{equities, equity_against_random};
+use crate::events::{Card, PlayerAction, Street};
+use crate::money::chips;
+
+/// Hands bundled for trying puzzles out, in PokerStars' format.
+const SAMPLES: &str = include_str!("../../config/puzzles.txt");
+
+/// Who the bundled hands' puzzles are for.
+pub const SAMPLE_HERO: &str = "Hero";
+
+/// The biggest decisions the day's puzzle is picked from.
+const DAILY_POOL: usize = 20;
+
+/// What a player facing a bet can do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+	Fold,
+	Call,
+	/// A pot-sized raise, or all in if that's less.
+	Raise,
+}
+
+impl Choice {
+	/// From what a player typed: `f`, `c`, `r` or the whole word.
+	pub fn parse(text: &str) -> Option<Self> {
+		match text.trim().to_lowercase().as_str() {
+			"f" | "fold" => Some(Choice::Fold),
+			"c" | "call" => Some(Choice::Call),
+			"r" | "raise" => Some(Choice::Raise),
+			_ => None,
+		}
+	}
+}
+
+/// An opponent still in at the decision.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Opponent {
+	pub name: String,
+	/// What they held, if the history kept it.
+	pub cards: Option<[Card; 2]>,
+}
+
+/// A decision, as it stood when it was made, and what became of it.
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+	pub hand_ref: String,
+	pub table: String,
+	pub hero: String,
+	pub position: String,
+	pub hole_cards: [Card; 2],
+	pub street: Street,
+	pub board: Vec<Card>,
+	/// Everything in the middle, the bet faced included.
+	pub pot: f32,
+	pub to_call: f32,
+	/// Behind before calling.
+	pub stack: f32,
+	/// Chips the hero already has in on this street.
+	pub street_in: f32,
+	/// What a raise would be to, on this street; none when calling
+	/// takes everything.
+	pub raise_to: Option<f32>,
+	pub big_blind: f32,
+	/// The hand up to the decision, a line per street.
+	pub story: Vec<String>,
+	pub opponents: Vec<Opponent>,
+	/// What the hero did.
+	pub played: PlayerAction,
+	pub final_board: Vec<Card>,
+	pub net: f32,
+}
+
+/// Each option by its expected chips, against how the hand stood.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+	pub equity: f32,
+	/// Against the cards held, rather than random hands.
+	pub against_known: bool,
+	/// The equity a call needs to break even.
+	pub needed: f32,
+	/// Expected chips of each option, folding being 0; a raise is
+	/// counted as if called.
+	pub options: Vec<(Choice, f32)>,
+}
+
+impl Evaluation {
+	pub fn best(&self) -> Choice {
+		self.options
+			.iter()
+			.max_by(|a, b| a.1.total_cmp(&b.1))
+			.map_or(Choice::Fold, |(choice, _)| *choice)
+	}
+}
+
+/// Every bet faced after the flop in `hand` by a human whose cards are
+/// known, or only by `hero` when one's named.
+pub fn puzzles(hand: &HandHistory, hero: Option<&str>) -> Vec<Puzzle> {
+	let count = hand.players.len();
+	let mut street_in = vec![0.0f32; count];
+	let mut total = vec![0.0f32; count];
+	let mut folded = vec![false; count];
+	for (i, player) in hand.players.iter().enumerate() {
+		let ante = hand.blinds.ante.map_or(0.0, |ante| ante.min(player.stack));
+		// Heads-up, the button posts the small blind
+		let blind = match player.position.as_str() {
+			"SB" => hand.blinds.small,
+			"BTN" if count == 2 => hand.blinds.small,
+			"BB" => hand.blinds.big,
+			_ => 0.0,
+		};
+		street_in[i] = blind.min(player.stack - ante);
+		total[i] = ante + street_in[i];
+	}
+
+	let mut found = Vec::new();
+	let mut street = Street::Preflop;
+	let mut story: Vec<String> = Vec::new();
+	let mut line = "Preflop:".to_string();
+	for action in &hand.actions {
+		if action.street != street {
+			street = action.street;
+			street_in.iter_mut().for_each(|s| *s = 0.0);
+			story.push(std::mem::take(&mut line));
+			line = format!("{} ({}):", street_name(street), cards_text(&hand.board[..board_len(street).min(hand.board.len())]));
+		}
+		let Some(i) = hand.players.iter().position(|p| p.seat == action.seat) else {
+			continue;
+		};
+		let player = &hand.players[i];
+		let bet = street_in.iter().copied().fold(0.0, f32::max);
+		let behind = player.stack - total[i];
+		let decides = street != Street::Preflop
+			&& bet > street_in[i]
+			&& behind > 0.0
+			&& hand.board.len() >= board_len(street)
+			&& hero.is_none_or(|hero| player.name == hero)
+			&& matches!(
+				action.action,
+				PlayerAction::Fold | PlayerAction::Call { .. } | PlayerAction::Raise { .. } | PlayerAction::AllIn { .. }
+			);
+		if let (true, true, Some(hole_cards)) = (decides, player.is_human, player.hole_cards) {
+			let to_call = (bet - street_in[i]).min(behind);
+			let pot = total.iter().sum::<f32>();
+			let raise = bet + pot + to_call;
+			let opponents = hand.players
+				.iter()
+				.enumerate()
+				.filter(|(j, _)| *j != i && !folded[*j])
+				.map(|(_, p)| Opponent { name: p.name.clone(), cards: p.hole_cards.or(p.mucked_cards) })
+				.collect();
+			let mut story = story.clone();
+			story.push(line.clone());
+			found.push(Puzzle {
+				hand_ref: hand.reference(),
+				table: hand.table.clone(),
+				hero: player.name.clone(),
+				position: player.position.clone(),
+				hole_cards,
+				street,
+				board: hand.board[..board_len(street)].to_vec(),
+				pot,
+				to_call,
+				stack: behind,
+				street_in: street_in[i],
+				raise_to: (behind > to_call).then(|| raise.min(street_in[i] + behind)),
+				big_blind: hand.blinds.big,
+				story,
+				opponents,
+				played: action.action.clone(),
+				final_board: hand.board.clone(),
+				net: player.net,
+			});
+		}
+
+		let put_in = match action.action {
+			PlayerAction::Fold | PlayerAction::Timeout => {
+				folded[i] = true;
+				0.0
+			}
+			PlayerAction::Call { amount } => amount,
+			PlayerAction::Bet { amount } | PlayerAction::Raise { amount } | PlayerAction::AllIn { amount } => {
+				amount - street_in[i]
+			}
+			_ => 0.0,
+		};
+		street_in[i] += put_in;
+		total[i] += put_in;
+		line.push_str(&format!(" {} {},", player.name, action.action.description()));
+	}
+	found
+}
+
+/// The day's puzzle among `hands`: one of the biggest pots a decision
+/// was made in, the same one all day.
+pub fn of_the_day(hands: &[HandHistory], hero: Option<&str>, day: NaiveDate) -> Option<Puzzle> {
+	let mut all = hands.iter().flat_map(|hand| puzzles(hand, hero)).collect::<Vec<_>>();
+	all.sort_by(|a, b| b.size().total_cmp(&a.size()).then_with(|| a.hand_ref.cmp(&b.hand_ref)));
+	all.truncate(DAILY_POOL);
+	if all.is_empty() {
+		return None;
+	}
+	let pick = day.num_days_from_ce().unsigned_abs() as usize % all.len();
+	Some(all.swap_remove(pick))
+}
+
+/// The bundled hands.
+pub fn samples() -> Vec<HandHistory> {
+	import::import(SAMPLES).map(|import| import.hands).unwrap_or_default()
+}
+
+impl Puzzle {
+	/// The pot in big blinds.
+	pub fn size(&self) -> f32 {
+		if self.big_blind > 0.0 { self.pot / self.big_blind } else { self.pot }
+	}
+
+	/// What the hero did, as one of the choices.
+	pub fn played_choice(&self) -> Choice {
+		match self.played {
+			PlayerAction::Fold | PlayerAction::Timeout => Choice::Fold,
+			PlayerAction::Raise { .. } => Choice::Raise,
+			// All in for no more than the bet is a call
+			PlayerAction::AllIn { amount } if amount > self.street_in + self.to_call => Choice::Raise,
+			_ => Choice::Call,
+		}
+	}
+
+	/// The hand up to the decision, and the question.
+	pub fn setup(&self) -> Vec<String> {
+		let mut lines = vec![format!("Hand {} at {}", self.hand_ref, self.table)];
+		let others = self.opponents.iter().map(|o| o.name.as_str()).collect::<Vec<_>>().join(", ");
+		lines.push(format!(
+			"{} in the {} with {}, against {}",
+			self.hero, self.position, cards_text(&self.hole_cards), others
+		));
+		lines.extend(self.story.iter().map(|line| format!("  {}", line.trim_end_matches(','))));
+		lines.push(format!(
+			"Pot {}, {} to call, {} behind.",
+			chips(self.pot), chips(self.to_call), chips(self.stack)
+		));
+		lines
+	}
+
+	/// The choices on offer, like `[f]old, [c]all $40, [r]aise to $160`.
+	pub fn prompt(&self) -> String {
+		let mut prompt = if self.to_call >= self.stack {
+			format!("[f]old, [c]all all in for {}", chips(self.to_call))
+		} else {
+			format!("[f]old, [c]all {}", chips(self.to_call))
+		};
+		if let Some(to) = self.raise_to {
+			prompt.push_str(&format!(", [r]aise to {}", chips(to)));
+		}
+		prompt
+	}
+
+	/// The hero's equity at the decision, and what each option was worth.
+	pub fn evaluate(&self) -> Evaluation {
+		let known = self.opponents.iter().map(|o| o.cards).collect::<Option<Vec<_>>>();
+		let (equity, against_known) = match known {
+			Some(cards) if !cards.is_empty() => {
+				let hands = std::iter::once(self.hole_cards).chain(cards).collect::<Vec<_>>();
+				(equities(&hands, &self.board)[0], true)
+			}
+			_ => (equity_against_random(&self.hole_cards, &self.board, self.opponents.len().max(1)), false),
+		};
+		let needed = self.to_call / (self.pot + self.to_call);
+		let mut options = vec![
+			(Choice::Fold, 0.0),
+			(Choice::Call, equity * (self.pot + self.to_call) - self.to_call),
+		];
+		if let Some(to) = self.raise_to {
+			// What the raise costs, and what the bettor adds to call it
+			let cost = to - self.street_in;
+			let called = cost - self.to_call;
+			options.push((Choice::Raise, equity * (self.pot + cost + called) - cost));
+		}
+		Evaluation { equity, against_known, needed, options }
+	}
+
+	/// What the hero did, how the hand came out, and each option judged.
+	pub fn reveal(&self) -> Vec<String> {
+		let mut lines = vec![format!("{} {}.", self.hero, self.played.description())];
+		let shown = self.opponents
+			.iter()
+			.filter_map(|o| o.cards.map(|cards| format!("{} held {}", o.name, cards_text(&cards))))
+			.collect::<Vec<_>>();
+		if !shown.is_empty() {
+			lines.push(format!("{}.", shown.join("; ")));
+		}
+		if self.final_board.len() > self.board.len() {
+			lines.push(format!("The board ran out {}.", cards_text(&self.final_board)));
+		}
+		let result = if self.net >= 0.0 { "won" } else { "lost" };
+		lines.push(format!("{} {} {} on the hand.", self.hero, result, chips(self.net.abs())));
+
+		let evaluation = self.evaluate();
+		let against = if evaluation.against_known { "the hands held" } else { "random hands" };
+		lines.push(String::new());
+		lines.push(format!(
+			"Equity {:.0}% against {}; calling needed {:.0}%.",
+			evaluation.equity * 100.0, against, evaluation.needed * 100.0
+		));
+		let best = evaluation.best();
+		let played = self.played_choice();
+		for (choice, ev) in &evaluation.options {
+			let name = match choice {
+				Choice::Fold => "Fold".to_string(),
+				Choice::Call => format!("Call {}", chips(self.to_call)),
+				Choice::Raise => format!("Raise to {}", chips(self.raise_to.unwrap_or_default())),
+			};
+			let mut line = format!("  {:<20} {:>+7.1} bb", name, ev / self.big_blind.max(f32::EPSILON));
+			if *choice == Choice::Raise {
+				line.push_str(" if called");
+			}
+			if *choice == best {
+				line.push_str("  best");
+			}
+			if *choice == played {
+				line.push_str("  (played)");
+			}
+			lines.push(line);
+		}
+		lines
+	}
+}
+
+fn street_name(street: Street) -> &'static str {
+	match street {
+		Street::Preflop => "Preflop",
+		Street::Flop => "Flop",
+		Street::Turn => "Turn",
+		_ => "River",
+	}
+}
+
+/// Board cards out on `street`.
+fn board_len(street: Street) -> usize {
+	match street {
+		Street::Preflop => 0,
+		Street::Flop => 3,
+		Street::Turn => 4,
+		_ => 5,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sample_puzzles() -> Vec<Puzzle> {
+		samples().iter().flat_map(|hand| puzzles(hand, Some(SAMPLE_HERO))).collect()
+	}
+
+	#[test]
+	fn test_samples_set_out_the_bets_faced() {
+		assert_eq!(samples().len(), 3);
+		let all = sample_puzzles();
+		let faced = all.iter().map(|p| (p.street, p.pot, p.to_call, p.played_choice())).collect::<Vec<_>>();
+		assert_eq!(faced, [
+			(Street::Flop, 105.0, 40.0, Choice::Call),
+			(Street::Turn, 235.0, 90.0, Choice::Call),
+			(Street::Flop, 85.0, 30.0, Choice::Call),
+			(Street::River, 235.0, 120.0, Choice::Fold),
+			(Street::Flop, 1065.0, 935.0, Choice::Call),
+		]);
+
+		let river = &all[3];
+		assert_eq!(river.board.len(), 5);
+		assert_eq!(river.raise_to, Some(120.0 + 235.0 + 120.0));
+		assert_eq!(river.story.len(), 4);
+		assert!(river.story[3].starts_with("River (Td 6c 2s Qh As): Hero checks, Marlowe bets"), "{}", river.story[3]);
+		assert_eq!(river.opponents, [Opponent { name: "Marlowe".to_string(), cards: None }]);
+	}
+
+	#[test]
+	fn test_facing_a_shove_is_judged_against_the_hand_held() {
+		let shove = sample_puzzles().pop().unwrap();
+		assert_eq!(shove.raise_to, None, "calling takes everything");
+		assert_eq!(shove.stack, 935.0);
+		let evaluation = shove.evaluate();
+		assert!(evaluation.against_known);
+		assert!((evaluation.needed - 935.0 / 2000.0).abs() < 1e-6);
+		assert_eq!(evaluation.options.len(), 2);
+		let call = evaluation.options[1].1;
+		assert!((call - (evaluation.equity * 2000.0 - 935.0)).abs() < 0.01);
+		assert!(shove.reveal().iter().any(|line| line == "Marlowe held Td Ts."));
+	}
+
+	#[test]
+	fn test_puzzle_of_the_day_holds_all_day() {
+		let hands = samples();
+		let day = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+		let first = of_the_day(&hands, Some(SAMPLE_HERO), day).unwrap();
+		let again = of_the_day(&hands, Some(SAMPLE_HERO), day).unwrap();
+		assert_eq!((first.hand_ref.as_str(), first.street), (again.hand_ref.as_str(), again.street));
+		assert!(of_the_day(&hands, Some("Nobody"), day).is_none());
+		assert_eq!(Choice::parse(" R "), Some(Choice::Raise));
+		assert_eq!(Choice::parse("check"), None);
+	}
+}
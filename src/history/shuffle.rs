@@ -0,0 +1,328 @@
+//! The shuffle report: how a game's decks came out, so players can see
+//! the deals were fair. Every deck's hash is shown at the table before
+//! its hand is dealt (`GameEvent::DeckShuffled`); the report keeps them
+//! with the table's seed, if it set one, and how often each card was
+//! dealt. The server saves it beside the session as
+//! `<session>.shuffle.json` when the game ends; `poker shuffle` shows it.
+//!
+//! The counts are held to what a fair deck gives by a chi-square test at
+//! the 1% level. A fair deck fails it one session in a hundred, so one
+//! failure is a curiosity rather than proof.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::{session_id, HistoryLog};
+use crate::error::PokerError;
+use crate::events::{Card, GameEvent, GameId, HandId};
+
+const RANKS: [char; 13] = ['2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A'];
+const SUITS: [char; 4] = ['s', 'h', 'd', 'c'];
+
+/// Fewest times each outcome must be due before its counts are tested.
+const MIN_EXPECTED: f64 = 5.0;
+
+/// One hand's deck, as the table was shown it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeckHash {
+	pub hand_num: u32,
+	pub hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShuffleReport {
+	pub session: String,
+	pub table: String,
+	/// The table's `seed`; without one the decks came from the system's
+	/// randomness.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub seed: Option<u64>,
+	/// In the order the hands were dealt.
+	pub decks: Vec<DeckHash>,
+	/// Times each card was dealt, hole cards and board alike, by name
+	/// like `As`.
+	pub dealt: BTreeMap<String, u32>,
+}
+
+/// A chi-square test of some counts against even odds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fit {
+	pub chi_square: f64,
+	pub degrees: usize,
+	/// The 1% critical value for `degrees`.
+	pub critical: f64,
+}
+
+impl Fit {
+	/// `None` with too few counts for the test to mean anything.
+	fn test(counts: &[u32]) -> Option<Self> {
+		let total = counts.iter().sum::<u32>() as f64;
+		let expected = total / counts.len() as f64;
+		if expected < MIN_EXPECTED {
+			return None;
+		}
+		let chi_square = counts.iter().map(|&n| (n as f64 - expected).powi(2) / expected).sum();
+		let degrees = counts.len() - 1;
+		let critical = match degrees {
+			3 => 11.345,
+			12 => 26.217,
+			51 => 77.386,
+			_ => return None,
+		};
+		Some(Self { chi_square, degrees, critical })
+	}
+
+	pub fn is_even(&self) -> bool {
+		self.chi_square <= self.critical
+	}
+}
+
+impl ShuffleReport {
+	pub fn cards_dealt(&self) -> u32 {
+		self.dealt.values().sum()
+	}
+
+	fn count(&self, rank: char, suit: char) -> u32 {
+		self.dealt.get(&format!("{}{}", rank, suit)).copied().unwrap_or(0)
+	}
+
+	pub fn ranks(&self) -> Vec<u32> {
+		RANKS.iter().map(|&r| SUITS.iter().map(|&s| self.count(r, s)).sum()).collect()
+	}
+
+	pub fn suits(&self) -> Vec<u32> {
+		SUITS.iter().map(|&s| RANKS.iter().map(|&r| self.count(r, s)).sum()).collect()
+	}
+
+	pub fn cards(&self) -> Vec<u32> {
+		RANKS.iter().flat_map(|&r| SUITS.iter().map(move |&s| (r, s))).map(|(r, s)| self.count(r, s)).collect()
+	}
+
+	/// The report for `poker shuffle`: the seed, the counts by rank and
+	/// suit, how each holds up, then every deck's hash.
+	pub fn lines(&self) -> Vec<String> {
+		let mut lines = vec![
+			format!("Session {} at {}: {} hands, {} cards dealt", self.session, self.table, self.decks.len(), self.cards_dealt()),
+			match self.seed {
+				Some(seed) => format!("Seed {}: every game at this table deals the same decks", seed),
+				None => "No seed: shuffled from the system's randomness".to_string(),
+			},
+			String::new(),
+		];
+		let row = |label: &str, heads: Vec<String>| {
+			format!("{:<6}{}", label, heads.iter().map(|h| format!("{:>5}", h)).collect::<String>())
+		};
+		lines.push(row("Rank", RANKS.iter().map(|r| r.to_string()).collect()));
+		lines.push(row("", self.ranks().iter().map(|n| n.to_string()).collect()));
+		lines.push(row("Suit", SUITS.iter().map(|s| s.to_string()).collect()));
+		lines.push(row("", self.suits().iter().map(|n| n.to_string()).collect()));
+		lines.push(String::new());
+		for (name, counts) in [("Cards", self.cards()), ("Ranks", self.ranks()), ("Suits", self.suits())] {
+			lines.push(match Fit::test(&counts) {
+				Some(fit) => format!(
+					"{:<6}chi-square {:.1} on {} degrees of freedom: {}",
+					name,
+					fit.chi_square,
+					fit.degrees,
+					if fit.is_even() {
+						"as even as a fair deck deals".to_string()
+					} else {
+						format!("more uneven than a fair deck deals 99 times in 100 (over {:.1})", fit.critical)
+					}
+				),
+				None => format!("{:<6}too few dealt to tell", name),
+			});
+		}
+		if !self.decks.is_empty() {
+			lines.push(String::new());
+			lines.push("Deck hashes, as the table was shown them:".to_string());
+			for deck in &self.decks {
+				lines.push(format!("  Hand {:<5} {}", deck.hand_num, deck.hash));
+			}
+		}
+		lines
+	}
+}
+
+/// Builds a `ShuffleReport` out of a game's events, unfiltered.
+pub struct ShuffleTally {
+	report: ShuffleReport,
+	/// The hand being dealt, and its board so far.
+	hand: Option<(HandId, u32)>,
+	board: Vec<Card>,
+}
+
+impl ShuffleTally {
+	pub fn new(game_id: GameId, table: impl Into<String>, seed: Option<u64>) -> Self {
+		Self {
+			report: ShuffleReport {
+				session: session_id(game_id),
+				table: table.into(),
+				seed,
+				decks: Vec::new(),
+				dealt: BTreeMap::new(),
+			},
+			hand: None,
+			board: Vec::new(),
+		}
+	}
+
+	pub fn record(&mut self, event: &GameEvent) {
+		match event {
+			GameEvent::HandStarted { hand_id, hand_num, .. } => {
+				self.hand = Some((*hand_id, *hand_num));
+				self.board.clear();
+			}
+			GameEvent::DeckShuffled { hand_id, hash } => {
+				if let Some((_, hand_num)) = self.hand.filter(|(id, _)| id == hand_id) {
+					self.report.decks.push(DeckHash { hand_num, hash: hash.clone() });
+				}
+			}
+			GameEvent::HoleCardsDealt { cards, .. } => self.deal(cards),
+			GameEvent::StreetChanged { board, .. } => {
+				let new = board.get(self.board.len()..).unwrap_or_default().to_vec();
+				self.deal(&new);
+				self.board = board.clone();
+			}
+			_ => {}
+		}
+	}
+
+	fn deal(&mut self, cards: &[Card]) {
+		// Hidden cards, should a redacted event get here
+		for card in cards.iter().filter(|c| RANKS.contains(&c.rank) && SUITS.contains(&c.suit)) {
+			*self.report.dealt.entry(format!("{}{}", card.rank, card.suit)).or_default() += 1;
+		}
+	}
+
+	pub fn report(&self) -> &ShuffleReport {
+		&self.report
+	}
+}
+
+impl HistoryLog {
+	pub fn shuffle_path(&self, session: &str) -> PathBuf {
+		self.dir.join(format!("{}.shuffle.json", session))
+	}
+
+	pub fn save_shuffle(&self, report: &ShuffleReport) -> Result<(), PokerError> {
+		fs::create_dir_all(&self.dir).map_err(|e| PokerError::io("create", &self.dir, e))?;
+		let path = self.shuffle_path(&report.session);
+		let json = serde_json::to_string_pretty(report).expect("Shuffle reports always serialize");
+		fs::write(&path, json).map_err(|e| PokerError::io("write", path, e))
+	}
+
+	pub fn load_shuffle(&self, session: &str) -> Result<ShuffleReport, PokerError> {
+		let path = self.shuffle_path(session);
+		let content = fs::read_to_string(&path).map_err(|e| match e.kind() {
+			io::ErrorKind::NotFound => {
+				PokerError::NotFound(format!("No shuffle report for session '{}' in {}", session, self.dir.display()))
+			}
+			_ => PokerError::io("read", &path, e),
+		})?;
+		serde_json::from_str(&content).map_err(|e| PokerError::parse(path.display().to_string(), e))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{Blinds, Seat, Street};
+	use rand::SeedableRng;
+	use rand::rngs::StdRng;
+	use rand::seq::SliceRandom;
+
+	/// A full deck in a random order, dealt from the end.
+	fn shuffled(rng: &mut StdRng) -> Vec<Card> {
+		let mut deck = RANKS.iter().flat_map(|&r| SUITS.iter().map(move |&s| Card::new(r, s))).collect::<Vec<_>>();
+		deck.shuffle(rng);
+		deck
+	}
+
+	fn hand(hand_num: u32, deck: &mut Vec<Card>) -> Vec<GameEvent> {
+		let hand_id = HandId(hand_num as u64);
+		let mut events = vec![
+			GameEvent::HandStarted {
+				hand_id,
+				hand_num,
+				button: Seat(0),
+				blinds: Blinds { small: 5.0, big: 10.0, ante: None },
+				seats: Vec::new(),
+				at: None,
+				game_id: None,
+			},
+			GameEvent::DeckShuffled { hand_id, hash: format!("{:016x}", hand_num) },
+		];
+		for seat in 0..4 {
+			events.push(GameEvent::HoleCardsDealt { seat: Seat(seat), cards: [deck.pop().unwrap(), deck.pop().unwrap()] });
+		}
+		let mut board = Vec::new();
+		for (street, n) in [(Street::Flop, 3), (Street::Turn, 1), (Street::River, 1)] {
+			board.extend((0..n).map(|_| deck.pop().unwrap()));
+			events.push(GameEvent::StreetChanged { street, board: board.clone(), pot: 0.0, effective_stack: 0.0, pots: Vec::new() });
+		}
+		events
+	}
+
+	#[test]
+	fn test_tally_counts_every_card_dealt_once() {
+		let mut rng = StdRng::seed_from_u64(3);
+		let mut tally = ShuffleTally::new(GameId(0x1234_5678_9abc), "Home game", Some(3));
+		for hand_num in 1..=200 {
+			let mut deck = shuffled(&mut rng);
+			for event in hand(hand_num, &mut deck) {
+				tally.record(&event);
+			}
+		}
+		let report = tally.report();
+		assert_eq!(report.session, "56789abc");
+		assert_eq!(report.cards_dealt(), 200 * 13);
+		assert_eq!(report.decks.len(), 200);
+		assert_eq!(report.decks[1], DeckHash { hand_num: 2, hash: "0000000000000002".to_string() });
+
+		let lines = report.lines();
+		assert_eq!(lines[0], "Session 56789abc at Home game: 200 hands, 2600 cards dealt");
+		assert!(lines[8].starts_with("Cards chi-square"), "{:?}", lines);
+		assert!(lines[8..11].iter().all(|line| line.ends_with("as even as a fair deck deals")), "{:?}", lines);
+		assert!(lines.last().unwrap().starts_with("  Hand 200"));
+	}
+
+	#[test]
+	fn test_uneven_counts_fail_and_few_are_not_tested() {
+		let mut tally = ShuffleTally::new(GameId(1), "Home game", None);
+		let aces = |seat| GameEvent::HoleCardsDealt { seat: Seat(seat), cards: [Card::new('A', 's'), Card::new('A', 'h')] };
+		tally.record(&aces(0));
+		assert!(tally.report().lines()[8].ends_with("too few dealt to tell"));
+		assert_eq!(tally.report().lines()[1], "No seed: shuffled from the system's randomness");
+
+		// Aces and nothing else, many times over
+		for _ in 0..100 {
+			tally.record(&aces(1));
+			tally.record(&GameEvent::HoleCardsDealt { seat: Seat(2), cards: [Card::new('A', 'd'), Card::new('A', 'c')] });
+		}
+		assert!(!Fit::test(&tally.report().ranks()).unwrap().is_even());
+		assert!(Fit::test(&tally.report().suits()).unwrap().is_even());
+		assert!(tally.report().lines()[9].contains("more uneven than a fair deck deals 99 times in 100"));
+	}
+
+	#[test]
+	fn test_report_round_trip() {
+		let dir = std::env::temp_dir().join(format!("poker-shuffle-test-{}", std::process::id()));
+		let log = HistoryLog::new(&dir);
+		let mut tally = ShuffleTally::new(GameId(1), "Home game", Some(9));
+		for event in hand(1, &mut shuffled(&mut StdRng::seed_from_u64(9))) {
+			tally.record(&event);
+		}
+		log.save_shuffle(tally.report()).unwrap();
+		let loaded = log.load_shuffle("00000001").unwrap();
+		assert_eq!(loaded.seed, Some(9));
+		assert_eq!(loaded.dealt, tally.report().dealt);
+		assert!(log.sessions().is_empty(), "reports aren't sessions");
+		assert!(log.load_shuffle("nope").is_err());
+		let _ = fs::remove_dir_all(&dir);
+	}
+}
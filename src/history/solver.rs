@@ -0,0 +1,263 @@
+//! A recorded hand's flop, set up for an outside solver: the pot and
+//! stacks the two players took to the flop, the flop itself, who's in and
+//! out of position, and a bet tree to start from. Ranges are left as
+//! placeholders; the history only knows the cards that were held, and a
+//! solver wants what each player could have held.
+//!
+//! `texassolver` writes a TexasSolver console command file; `to_json` a
+//! plain description of the spot for other tools or scripts.
+
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use super::HandHistory;
+use crate::error::PokerError;
+use crate::events::{Card, PlayerAction, Street};
+
+/// Put in each range's place until someone fills it in.
+pub const RANGE_PLACEHOLDER: &str = "RANGE";
+
+/// Bet sizes, in percent of the pot, for every street of the tree.
+const BET_SIZES: [&str; 3] = ["33,75", "50,100", "50,100"];
+/// Raise sizes, in percent of the pot.
+const RAISE_SIZES: &str = "60";
+
+/// The hand as it stood when the flop came down.
+#[derive(Debug, Clone, Serialize)]
+pub struct FlopSpot {
+	pub hand_ref: String,
+	pub table: String,
+	pub big_blind: f32,
+	/// Like `["Qs", "Jh", "2h"]`.
+	pub board: Vec<String>,
+	/// Everything in the middle, folded players' chips and antes too.
+	pub pot: f32,
+	/// The smaller of the two stacks behind.
+	pub effective_stack: f32,
+	/// First to act after the flop.
+	pub oop: SpotPlayer,
+	pub ip: SpotPlayer,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpotPlayer {
+	pub name: String,
+	pub position: String,
+	/// Behind at the flop.
+	pub stack: f32,
+	/// What they held, if the history kept it, like `AsKd`.
+	pub hole_cards: Option<String>,
+	pub range: String,
+}
+
+impl FlopSpot {
+	/// The flop of `hand`, if exactly two players saw it with chips left
+	/// to play for.
+	pub fn from_hand(hand: &HandHistory) -> Result<Self, PokerError> {
+		let hand_ref = hand.reference();
+		if hand.board.len() < 3 {
+			return Err(PokerError::Engine(format!("Hand {} ended before the flop", hand_ref)));
+		}
+
+		// What each player put in before the flop, blinds and antes first
+		let count = hand.players.len();
+		let mut street = vec![0.0f32; count];
+		let mut dead = vec![0.0f32; count];
+		for (i, player) in hand.players.iter().enumerate() {
+			if let Some(ante) = hand.blinds.ante {
+				dead[i] = ante.min(player.stack);
+			}
+			let left = player.stack - dead[i];
+			// Heads-up, the button posts the small blind
+			street[i] = match player.position.as_str() {
+				"SB" => hand.blinds.small.min(left),
+				"BTN" if count == 2 => hand.blinds.small.min(left),
+				"BB" => hand.blinds.big.min(left),
+				_ => 0.0,
+			};
+		}
+		let mut folded = vec![false; count];
+		for action in hand.actions.iter().filter(|a| a.street == Street::Preflop) {
+			let Some(i) = hand.players.iter().position(|p| p.seat == action.seat) else {
+				continue;
+			};
+			match action.action {
+				PlayerAction::Fold | PlayerAction::Timeout => folded[i] = true,
+				PlayerAction::Call { amount } => street[i] += amount,
+				PlayerAction::Bet { amount } | PlayerAction::Raise { amount } | PlayerAction::AllIn { amount } => {
+					street[i] = amount;
+				}
+				_ => {}
+			}
+		}
+
+		let in_hand = (0..count).filter(|i| !folded[*i]).collect::<Vec<_>>();
+		let &[first, second] = in_hand.as_slice() else {
+			return Err(PokerError::Engine(format!("{} players saw the flop in hand {}; solvers take two", in_hand.len(), hand_ref)));
+		};
+		let behind = |i: usize| cents(hand.players[i].stack - dead[i] - street[i]);
+		let effective_stack = behind(first).min(behind(second));
+		if effective_stack <= 0.0 {
+			return Err(PokerError::Engine(format!("Hand {} was all in before the flop; there's nothing left to decide", hand_ref)));
+		}
+
+		// After the flop the seat after the button acts first, the button last
+		let button = hand.players.iter().position(|p| p.seat == hand.button).unwrap_or(0);
+		let order = |i: usize| (i + count - button - 1) % count;
+		let (oop, ip) = if order(first) < order(second) { (first, second) } else { (second, first) };
+		let spot_player = |i: usize| {
+			let player = &hand.players[i];
+			SpotPlayer {
+				name: player.name.clone(),
+				position: player.position.clone(),
+				stack: behind(i),
+				hole_cards: player.hole_cards.or(player.mucked_cards).map(|cards| card_names(&cards).concat()),
+				range: RANGE_PLACEHOLDER.to_string(),
+			}
+		};
+
+		Ok(Self {
+			hand_ref,
+			table: hand.table.clone(),
+			big_blind: hand.blinds.big,
+			board: card_names(&hand.board[..3]),
+			pot: cents(street.iter().chain(&dead).sum()),
+			effective_stack,
+			oop: spot_player(oop),
+			ip: spot_player(ip),
+		})
+	}
+
+	/// A TexasSolver command file: the spot, placeholder ranges, a bet tree
+	/// and settings to solve with. Its reader takes no comments, so the
+	/// hand it came from isn't named.
+	pub fn texassolver(&self) -> String {
+		let mut out = String::new();
+		let _ = writeln!(out, "set_pot {}", number(self.pot));
+		let _ = writeln!(out, "set_effective_stack {}", number(self.effective_stack));
+		let _ = writeln!(out, "set_board {}", self.board.join(","));
+		let _ = writeln!(out, "set_range_oop {}", self.oop.range);
+		let _ = writeln!(out, "set_range_ip {}", self.ip.range);
+		for side in ["oop", "ip"] {
+			for (street, bets) in ["flop", "turn", "river"].iter().zip(BET_SIZES) {
+				let _ = writeln!(out, "set_bet_sizes {},{},bet,{}", side, street, bets);
+				let _ = writeln!(out, "set_bet_sizes {},{},raise,{}", side, street, RAISE_SIZES);
+				let _ = writeln!(out, "set_bet_sizes {},{},allin", side, street);
+			}
+		}
+		for line in [
+			"set_allin_threshold 0.67",
+			"build_tree",
+			"set_thread_num 4",
+			"set_accuracy 0.5",
+			"set_max_iteration 200",
+			"set_print_interval 10",
+			"set_use_isomorphism 1",
+			"start_solve",
+			"set_dump_rounds 2",
+			"dump_result output_result.json",
+		] {
+			let _ = writeln!(out, "{}", line);
+		}
+		out
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).unwrap_or_default() + "\n"
+	}
+}
+
+fn card_names(cards: &[Card]) -> Vec<String> {
+	cards.iter().map(|c| format!("{}{}", c.rank, c.suit)).collect()
+}
+
+/// Sums of cents come out a hair off in `f32`.
+fn cents(amount: f32) -> f32 {
+	(amount * 100.0).round() / 100.0
+}
+
+/// Amounts as solvers read them: no currency, no trailing zeros.
+fn number(amount: f32) -> String {
+	let text = format!("{:.2}", amount);
+	text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{cards, Blinds, GameId, HandId};
+	use crate::history::{test_action as action, test_hand, test_player, HandAction, HandPlayer};
+
+	fn player(seat: usize, name: &str, position: &str, stack: f32) -> HandPlayer {
+		HandPlayer {
+			position: position.to_string(),
+			is_human: seat == 0,
+			stack,
+			hole_cards: (seat == 0).then(|| [Card::new('A', 's'), Card::new('K', 's')]),
+			..test_player(seat, name)
+		}
+	}
+
+	fn hand(actions: Vec<HandAction>) -> HandHistory {
+		HandHistory {
+			game_id: GameId(0x5678_9abc),
+			hand_num: 3,
+			hand_id: HandId(3),
+			started_at: "2026-10-16T20:00:00+00:00".to_string(),
+			blinds: Blinds { small: 5.0, big: 10.0, ante: Some(1.0) },
+			players: vec![player(0, "Alice", "BTN", 200.0), player(1, "Bob", "SB", 100.0), player(2, "Carol", "BB", 150.0)],
+			actions,
+			board: cards("Kh 8c 3s 2d"),
+			..test_hand()
+		}
+	}
+
+	#[test]
+	fn test_flop_spot_from_a_raised_pot() {
+		let hand = hand(vec![
+			action(Street::Preflop, 0, PlayerAction::Raise { amount: 30.0 }),
+			action(Street::Preflop, 1, PlayerAction::Fold),
+			action(Street::Preflop, 2, PlayerAction::Call { amount: 20.0 }),
+			action(Street::Flop, 2, PlayerAction::Check),
+		]);
+		let spot = FlopSpot::from_hand(&hand).unwrap();
+		assert_eq!(spot.hand_ref, "56789abc-3");
+		assert_eq!(spot.board, ["Kh", "8c", "3s"]);
+		// Three antes, the small blind, and 30 each from the two left
+		assert_eq!(spot.pot, 68.0);
+		assert_eq!((spot.oop.name.as_str(), spot.ip.name.as_str()), ("Carol", "Alice"));
+		assert_eq!((spot.oop.stack, spot.ip.stack), (119.0, 169.0));
+		assert_eq!(spot.effective_stack, 119.0);
+		assert_eq!(spot.ip.hole_cards.as_deref(), Some("AsKs"));
+		assert_eq!(spot.oop.hole_cards, None);
+
+		let commands = spot.texassolver();
+		let lines = commands.lines().collect::<Vec<_>>();
+		assert_eq!(lines[..5], ["set_pot 68", "set_effective_stack 119", "set_board Kh,8c,3s", "set_range_oop RANGE", "set_range_ip RANGE"]);
+		assert!(lines.contains(&"set_bet_sizes ip,river,allin"));
+		assert_eq!(lines.last(), Some(&"dump_result output_result.json"));
+		assert!(spot.to_json().contains("\"effective_stack\": 119.0"));
+	}
+
+	#[test]
+	fn test_flop_spot_needs_two_players_with_chips_behind() {
+		let limped = hand(vec![
+			action(Street::Preflop, 0, PlayerAction::Call { amount: 10.0 }),
+			action(Street::Preflop, 1, PlayerAction::Call { amount: 5.0 }),
+			action(Street::Preflop, 2, PlayerAction::Check),
+		]);
+		assert_eq!(FlopSpot::from_hand(&limped).unwrap_err().to_string(), "3 players saw the flop in hand 56789abc-3; solvers take two");
+
+		let shoved = hand(vec![
+			action(Street::Preflop, 0, PlayerAction::AllIn { amount: 199.0 }),
+			action(Street::Preflop, 1, PlayerAction::AllIn { amount: 99.0 }),
+			action(Street::Preflop, 2, PlayerAction::Fold),
+		]);
+		assert!(FlopSpot::from_hand(&shoved).unwrap_err().to_string().contains("all in before the flop"));
+
+		let mut walked = hand(vec![action(Street::Preflop, 0, PlayerAction::Fold), action(Street::Preflop, 1, PlayerAction::Fold)]);
+		walked.board.clear();
+		assert_eq!(FlopSpot::from_hand(&walked).unwrap_err().to_string(), "Hand 56789abc-3 ended before the flop");
+	}
+}
@@ -0,0 +1,164 @@
+//! Tags players put on hands, like "bluff" or "cooler", to find them again.
+//! They're kept in `tags.toml` beside the sessions, keyed by hand
+//! reference, rather than in the session files: a hand can be tagged
+//! while it's still being played, before the server has written it down,
+//! and the session files stay append-only. `HistoryLog::load` puts them
+//! on the hands.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::{parse_hand_ref, HandHistory, HistoryLog};
+use crate::error::PokerError;
+
+/// What a hand is tagged with when no tag is given.
+pub const REVIEW_LATER: &str = "review later";
+
+/// Tags by hand reference, like `56789abc-3`.
+pub type Tags = BTreeMap<String, Vec<String>>;
+
+/// How tags are compared and stored: trimmed and lowercased.
+pub fn normalize(tag: &str) -> String {
+	tag.trim().to_lowercase()
+}
+
+impl HandHistory {
+	pub fn has_tag(&self, tag: &str) -> bool {
+		let tag = normalize(tag);
+		self.tags.contains(&tag)
+	}
+}
+
+impl HistoryLog {
+	pub fn tags_path(&self) -> PathBuf {
+		self.dir.join("tags.toml")
+	}
+
+	/// Every tagged hand; none if nothing has been tagged yet.
+	pub fn tags(&self) -> Result<Tags, PokerError> {
+		let path = self.tags_path();
+		if !path.exists() {
+			return Ok(Tags::new());
+		}
+		let content = fs::read_to_string(&path).map_err(|e| PokerError::io("read", &path, e))?;
+		toml::from_str(&content).map_err(|e| PokerError::parse(path.display().to_string(), e))
+	}
+
+	/// Tags the hand `hand_ref`; false if it already had that tag.
+	pub fn tag(&self, hand_ref: &str, tag: &str) -> Result<bool, PokerError> {
+		let tag = normalize(tag);
+		if tag.is_empty() {
+			return Err(PokerError::Config("A tag needs some text".to_string()));
+		}
+		self.update_tags(hand_ref, |tags| {
+			let added = !tags.contains(&tag);
+			if added {
+				tags.push(tag);
+			}
+			added
+		})
+	}
+
+	/// Takes a tag off; false if the hand didn't have it.
+	pub fn untag(&self, hand_ref: &str, tag: &str) -> Result<bool, PokerError> {
+		let tag = normalize(tag);
+		self.update_tags(hand_ref, |tags| {
+			let before = tags.len();
+			tags.retain(|t| *t != tag);
+			tags.len() != before
+		})
+	}
+
+	fn update_tags(&self, hand_ref: &str, change: impl FnOnce(&mut Vec<String>) -> bool) -> Result<bool, PokerError> {
+		let Some((session, hand_num)) = parse_hand_ref(hand_ref) else {
+			return Err(PokerError::parse("hand reference", format!("'{}' isn't like 56789abc-3", hand_ref)));
+		};
+		let key = format!("{}-{}", session.to_lowercase(), hand_num);
+		let mut all = self.tags()?;
+		let tags = all.entry(key.clone()).or_default();
+		let changed = change(tags);
+		if tags.is_empty() {
+			all.remove(&key);
+		}
+		if changed {
+			fs::create_dir_all(&self.dir).map_err(|e| PokerError::io("create", &self.dir, e))?;
+			let content = toml::to_string(&all).map_err(|e| PokerError::parse("tags", e))?;
+			let path = self.tags_path();
+			fs::write(&path, content).map_err(|e| PokerError::io("write", &path, e))?;
+		}
+		Ok(changed)
+	}
+
+	/// Every recorded hand with `tag`, from all sessions, in the order
+	/// they were played.
+	pub fn tagged(&self, tag: &str) -> Result<Vec<HandHistory>, PokerError> {
+		let tag = normalize(tag);
+		let mut sessions = self
+			.tags()?
+			.into_iter()
+			.filter(|(_, tags)| tags.contains(&tag))
+			.filter_map(|(hand_ref, _)| parse_hand_ref(&hand_ref).map(|(session, _)| session.to_string()))
+			.collect::<Vec<_>>();
+		sessions.sort();
+		sessions.dedup();
+
+		let mut hands = Vec::new();
+		for session in sessions {
+			// A hand tagged at someone else's server isn't in this history
+			if self.path(&session).exists() {
+				hands.extend(self.load(&session)?.into_iter().filter(|hand| hand.tags.contains(&tag)));
+			}
+		}
+		hands.sort_by(|a, b| a.started_at.cmp(&b.started_at).then(a.hand_num.cmp(&b.hand_num)));
+		Ok(hands)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{GameId, HandId};
+	use crate::history::test_hand;
+
+	fn hand(hand_num: u32) -> HandHistory {
+		HandHistory {
+			game_id: GameId(0x5678_9abc),
+			hand_num,
+			hand_id: HandId(hand_num as u64),
+			started_at: format!("2026-10-16T20:0{}:00+00:00", hand_num),
+			..test_hand()
+		}
+	}
+
+	#[test]
+	fn test_tags_reach_loaded_hands_and_filter_them() {
+		let dir = std::env::temp_dir().join(format!("poker-tags-{}", std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		let log = HistoryLog::new(&dir);
+		// Tagged while it's being played, before the server writes it down
+		assert!(log.tag("56789ABC-2", "Bluff ").unwrap());
+		assert!(!log.tag("56789abc-2", "bluff").unwrap());
+		for hand_num in 1..=3 {
+			log.append(&hand(hand_num)).unwrap();
+		}
+		log.tag("56789abc-3", REVIEW_LATER).unwrap();
+		log.tag("56789abc-3", "bluff").unwrap();
+		log.tag("00000001-1", "bluff").unwrap();
+		assert!(log.tag("last hand", "bluff").is_err());
+
+		let hands = log.load("56789abc").unwrap();
+		assert!(hands[0].tags.is_empty());
+		assert_eq!(hands[1].tags, vec!["bluff"]);
+		assert!(hands[2].has_tag("Review Later"));
+
+		let bluffs = log.tagged("bluff").unwrap();
+		assert_eq!(bluffs.iter().map(|h| h.hand_num).collect::<Vec<_>>(), vec![2, 3]);
+
+		assert!(log.untag("56789abc-2", "bluff").unwrap());
+		assert!(!log.untag("56789abc-2", "bluff").unwrap());
+		assert!(!log.tags().unwrap().contains_key("56789abc-2"));
+		assert_eq!(log.tagged("bluff").unwrap().len(), 1);
+		let _ = fs::remove_dir_all(&dir);
+	}
+}
@@ -0,0 +1,298 @@
+//! Per-player, per-position stats for merging home games into a poker
+//! tracker's database: `poker export --format stats`. The counts are kept
+//! alongside the percentages so rows can be added up, across sessions or
+//! positions, without going back to the hands.
+
+use std::collections::HashMap;
+
+use super::{csv_field, HandHistory};
+use crate::events::{PlayerAction, Seat, Street};
+
+/// Seating order, earliest to act preflop first.
+const POSITIONS: [&str; 6] = ["UTG", "MP", "CO", "BTN", "SB", "BB"];
+
+/// Header of `to_stats_csv`, one row per player per position.
+pub const STATS_COLUMNS: [&str; 20] = [
+	"player",
+	"position",
+	"hands",
+	"vpip",
+	"pfr",
+	"three_bet",
+	"three_bet_chances",
+	"saw_flop",
+	"showdowns",
+	"won_at_showdown",
+	"postflop_aggressive",
+	"postflop_calls",
+	"net",
+	"net_bb",
+	"vpip_pct",
+	"pfr_pct",
+	"three_bet_pct",
+	"wtsd_pct",
+	"wsd_pct",
+	"bb_per_100",
+];
+
+/// One player's hands from one position.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PositionStats {
+	pub player: String,
+	pub position: String,
+	pub hands: u32,
+	/// Hands they put money in before the flop by choice: blinds don't count.
+	pub vpip: u32,
+	/// Hands they bet or raised before the flop.
+	pub pfr: u32,
+	/// Hands they re-raised a single raise before the flop.
+	pub three_bet: u32,
+	/// Hands they had the chance to, facing a single raise.
+	pub three_bet_chances: u32,
+	pub saw_flop: u32,
+	/// Hands they went to showdown, all-ins with cards to come included.
+	pub showdowns: u32,
+	pub won_at_showdown: u32,
+	/// Bets and raises after the flop.
+	pub postflop_aggressive: u32,
+	pub postflop_calls: u32,
+	pub net: f32,
+	/// `net` in the big blinds of each hand.
+	pub net_bb: f32,
+}
+
+impl PositionStats {
+	pub fn vpip_pct(&self) -> f32 {
+		percent(self.vpip, self.hands)
+	}
+
+	pub fn pfr_pct(&self) -> f32 {
+		percent(self.pfr, self.hands)
+	}
+
+	pub fn three_bet_pct(&self) -> f32 {
+		percent(self.three_bet, self.three_bet_chances)
+	}
+
+	/// Went to showdown, of the hands they saw a flop.
+	pub fn wtsd_pct(&self) -> f32 {
+		percent(self.showdowns, self.saw_flop)
+	}
+
+	/// Won, of the hands they went to showdown.
+	pub fn wsd_pct(&self) -> f32 {
+		percent(self.won_at_showdown, self.showdowns)
+	}
+
+	pub fn bb_per_100(&self) -> f32 {
+		if self.hands == 0 { 0.0 } else { self.net_bb * 100.0 / self.hands as f32 }
+	}
+}
+
+fn percent(count: u32, of: u32) -> f32 {
+	if of == 0 { 0.0 } else { count as f32 * 100.0 / of as f32 }
+}
+
+/// What one player did in one hand.
+#[derive(Default)]
+struct Played {
+	vpip: bool,
+	pfr: bool,
+	three_bet: bool,
+	three_bet_chance: bool,
+	folded: bool,
+	folded_preflop: bool,
+	aggressive: u32,
+	calls: u32,
+}
+
+/// Everyone's stats by position, players by name and positions in
+/// seating order.
+pub fn position_stats(hands: &[HandHistory]) -> Vec<PositionStats> {
+	let mut stats: HashMap<(String, String), PositionStats> = HashMap::new();
+	for hand in hands {
+		let mut played: HashMap<Seat, Played> = HashMap::new();
+		let mut street = Street::Preflop;
+		let mut level = hand.blinds.big;
+		let mut raises = 0;
+		for action in &hand.actions {
+			if action.street != street {
+				street = action.street;
+				level = 0.0;
+			}
+			let preflop = street == Street::Preflop;
+			let p = played.entry(action.seat).or_default();
+			if preflop && raises == 1 {
+				p.three_bet_chance = true;
+			}
+			let raised_to = match action.action {
+				PlayerAction::Bet { amount } | PlayerAction::Raise { amount } | PlayerAction::AllIn { amount } if amount > level => Some(amount),
+				_ => None,
+			};
+			match (&action.action, raised_to) {
+				(PlayerAction::Fold | PlayerAction::Timeout, _) => {
+					p.folded = true;
+					p.folded_preflop |= preflop;
+				}
+				(_, Some(amount)) => {
+					if preflop {
+						p.vpip = true;
+						p.pfr = true;
+						p.three_bet |= raises == 1;
+						raises += 1;
+					} else {
+						p.aggressive += 1;
+					}
+					level = amount;
+				}
+				(PlayerAction::Call { .. } | PlayerAction::AllIn { .. }, None) => {
+					if preflop {
+						p.vpip = true;
+					} else {
+						p.calls += 1;
+					}
+				}
+				_ => {}
+			}
+		}
+
+		let contested = hand.players.iter().filter(|player| !played.get(&player.seat).is_some_and(|p| p.folded)).count() > 1;
+		for player in &hand.players {
+			let p = played.remove(&player.seat).unwrap_or_default();
+			let row = stats.entry((player.name.clone(), player.position.clone())).or_insert_with(|| PositionStats {
+				player: player.name.clone(),
+				position: player.position.clone(),
+				..PositionStats::default()
+			});
+			let saw_flop = hand.board.len() >= 3 && !p.folded_preflop;
+			let showdown = saw_flop && contested && !p.folded;
+			row.hands += 1;
+			row.vpip += p.vpip as u32;
+			row.pfr += p.pfr as u32;
+			row.three_bet += p.three_bet as u32;
+			row.three_bet_chances += p.three_bet_chance as u32;
+			row.saw_flop += saw_flop as u32;
+			row.showdowns += showdown as u32;
+			row.won_at_showdown += (showdown && player.net > 0.0) as u32;
+			row.postflop_aggressive += p.aggressive;
+			row.postflop_calls += p.calls;
+			row.net += player.net;
+			if hand.blinds.big > 0.0 {
+				row.net_bb += player.net / hand.blinds.big;
+			}
+		}
+	}
+
+	let mut rows = stats.into_values().collect::<Vec<_>>();
+	let order = |position: &str| POSITIONS.iter().position(|p| *p == position).unwrap_or(POSITIONS.len());
+	rows.sort_by(|a, b| a.player.cmp(&b.player).then(order(&a.position).cmp(&order(&b.position))));
+	rows
+}
+
+pub fn to_stats_csv(hands: &[HandHistory]) -> String {
+	let mut out = STATS_COLUMNS.join(",");
+	out.push('\n');
+	for row in position_stats(hands) {
+		let fields = [
+			csv_field(&row.player),
+			row.position.clone(),
+			row.hands.to_string(),
+			row.vpip.to_string(),
+			row.pfr.to_string(),
+			row.three_bet.to_string(),
+			row.three_bet_chances.to_string(),
+			row.saw_flop.to_string(),
+			row.showdowns.to_string(),
+			row.won_at_showdown.to_string(),
+			row.postflop_aggressive.to_string(),
+			row.postflop_calls.to_string(),
+			format!("{:.2}", row.net),
+			format!("{:.2}", row.net_bb),
+			format!("{:.1}", row.vpip_pct()),
+			format!("{:.1}", row.pfr_pct()),
+			format!("{:.1}", row.three_bet_pct()),
+			format!("{:.1}", row.wtsd_pct()),
+			format!("{:.1}", row.wsd_pct()),
+			format!("{:.1}", row.bb_per_100()),
+		];
+		out.push_str(&fields.join(","));
+		out.push('\n');
+	}
+	out
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::Card;
+	use crate::history::{test_action as action, test_hand, test_player, HandAction, HandPlayer};
+
+	fn player(seat: usize, name: &str, position: &str, net: f32) -> HandPlayer {
+		HandPlayer { position: position.to_string(), stack: 1000.0, net, ..test_player(seat, name) }
+	}
+
+	fn hand(players: Vec<HandPlayer>, actions: Vec<HandAction>, board: usize) -> HandHistory {
+		HandHistory { players, actions, board: vec![Card::new('2', 'c'); board], ..test_hand() }
+	}
+
+	#[test]
+	fn test_three_bet_pot_to_showdown() {
+		use PlayerAction::*;
+		let played = hand(
+			vec![player(0, "Alice", "BTN", 215.0), player(1, "Bob", "SB", -5.0), player(2, "Carol", "BB", -210.0)],
+			vec![
+				action(Street::Preflop, 0, Raise { amount: 30.0 }),
+				action(Street::Preflop, 1, Fold),
+				action(Street::Preflop, 2, Raise { amount: 90.0 }),
+				action(Street::Preflop, 0, Call { amount: 60.0 }),
+				action(Street::Flop, 2, Bet { amount: 60.0 }),
+				action(Street::Flop, 0, Raise { amount: 120.0 }),
+				action(Street::Flop, 2, AllIn { amount: 120.0 }),
+			],
+			5,
+		);
+		let rows = position_stats(&[played]);
+		let row = |name: &str| rows.iter().find(|r| r.player == name).unwrap().clone();
+
+		let alice = row("Alice");
+		assert_eq!((alice.vpip, alice.pfr, alice.three_bet, alice.three_bet_chances), (1, 1, 0, 0));
+		assert_eq!((alice.saw_flop, alice.showdowns, alice.won_at_showdown), (1, 1, 1));
+		assert_eq!((alice.postflop_aggressive, alice.postflop_calls), (1, 0));
+		assert_eq!(alice.bb_per_100(), 2150.0);
+
+		let bob = row("Bob");
+		assert_eq!((bob.vpip, bob.three_bet_chances, bob.saw_flop, bob.showdowns), (0, 1, 0, 0));
+
+		let carol = row("Carol");
+		assert_eq!((carol.vpip, carol.pfr, carol.three_bet, carol.three_bet_chances), (1, 1, 1, 1));
+		assert_eq!((carol.postflop_aggressive, carol.postflop_calls, carol.won_at_showdown), (1, 1, 0));
+		assert_eq!(carol.three_bet_pct(), 100.0);
+	}
+
+	#[test]
+	fn test_rows_add_up_per_player_and_position() {
+		use PlayerAction::*;
+		let walk = || hand(
+			vec![player(0, "Alice, Jr.", "SB", -5.0), player(1, "Bob", "BB", 5.0)],
+			vec![action(Street::Preflop, 0, Fold)],
+			0,
+		);
+		let limped = hand(
+			vec![player(0, "Alice, Jr.", "SB", 10.0), player(1, "Bob", "BB", -10.0)],
+			vec![
+				action(Street::Preflop, 0, Call { amount: 5.0 }),
+				action(Street::Preflop, 1, Check),
+				action(Street::Flop, 1, Check),
+				action(Street::Flop, 0, Bet { amount: 10.0 }),
+				action(Street::Flop, 1, Fold),
+			],
+			3,
+		);
+		let csv = to_stats_csv(&[walk(), walk(), limped]);
+		let lines = csv.lines().collect::<Vec<_>>();
+		assert_eq!(lines[0], STATS_COLUMNS.join(","));
+		assert_eq!(lines.len(), 3);
+		assert_eq!(lines[1], "\"Alice, Jr.\",SB,3,1,0,0,0,1,0,0,1,0,0.00,0.00,33.3,0.0,0.0,0.0,0.0,0.0");
+		assert_eq!(lines[2], "Bob,BB,3,0,0,0,0,1,0,0,0,0,0.00,0.00,0.0,0.0,0.0,0.0,0.0,0.0");
+	}
+}
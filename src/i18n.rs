@@ -0,0 +1,190 @@
+//! Translated UI strings. Each language is a catalog in
+//! `config/locales/<code>.toml`; a user copy in the config directory
+//! overrides the built-in one, and new files there add languages.
+//! Anything a catalog leaves out falls back to English.
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{LazyLock, RwLock};
+
+use crate::config::resolve_config;
+use crate::defaults;
+use crate::error::PokerError;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Messages by dotted key, like `prompt.check`.
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+	messages: HashMap<String, String>,
+}
+
+impl Catalog {
+	pub fn parse(content: &str) -> Result<Self, PokerError> {
+		let table: toml::Table = toml::from_str(content)
+			.map_err(|e| PokerError::parse("locale", e))?;
+		let mut messages = HashMap::new();
+		flatten("", &table, &mut messages);
+		Ok(Self { messages })
+	}
+
+	/// The catalog for `code`: the user's copy if there is one, else the
+	/// built-in one.
+	pub fn load(code: &str) -> Result<Self, PokerError> {
+		let path = format!("locales/{}.toml", code);
+		match resolve_config(&path) {
+			Ok(file) => {
+				let content = fs::read_to_string(&file)
+					.map_err(|e| PokerError::io("read", &file, e))?;
+				Self::parse(&content)
+			}
+			Err(_) => defaults::embedded(&path)
+				.ok_or_else(|| PokerError::Config(format!("No language '{}'", code)))
+				.and_then(Self::parse),
+		}
+	}
+
+	pub fn get(&self, key: &str) -> Option<&str> {
+		self.messages.get(key).map(String::as_str)
+	}
+
+	pub fn keys(&self) -> impl Iterator<Item = &str> {
+		self.messages.keys().map(String::as_str)
+	}
+}
+
+fn flatten(prefix: &str, table: &toml::Table, out: &mut HashMap<String, String>) {
+	for (key, value) in table {
+		let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+		match value {
+			toml::Value::Table(inner) => flatten(&key, inner, out),
+			toml::Value::String(text) => {
+				out.insert(key, text.clone());
+			}
+			other => {
+				out.insert(key, other.to_string());
+			}
+		}
+	}
+}
+
+static ENGLISH: LazyLock<Catalog> = LazyLock::new(|| {
+	defaults::embedded("locales/en.toml")
+		.and_then(|content| Catalog::parse(content).ok())
+		.unwrap_or_default()
+});
+
+static ACTIVE: RwLock<Option<(String, Catalog)>> = RwLock::new(None);
+
+/// Switches the UI to `code`; English stays on if it can't be loaded.
+pub fn set_locale(code: &str) -> Result<(), PokerError> {
+	let catalog = Catalog::load(code)?;
+	*ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = Some((code.to_string(), catalog));
+	Ok(())
+}
+
+/// The language in use.
+pub fn locale() -> String {
+	ACTIVE
+		.read()
+		.unwrap_or_else(|e| e.into_inner())
+		.as_ref()
+		.map(|(code, _)| code.clone())
+		.unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// Languages to choose from: the built-in ones and any in the config
+/// directory.
+pub fn available() -> Vec<String> {
+	defaults::list_locales()
+}
+
+/// A language's own name for itself, like "Español", or its code if its
+/// catalog doesn't say.
+pub fn language_name(code: &str) -> String {
+	Catalog::load(code)
+		.ok()
+		.and_then(|catalog| catalog.get("language.name").map(str::to_string))
+		.unwrap_or_else(|| code.to_string())
+}
+
+/// Picks the language at startup: the saved one, else the system's (from
+/// `LC_ALL`, `LC_MESSAGES` or `LANG`, like `es_ES.UTF-8`) if there's a
+/// catalog for it.
+pub fn init(saved: Option<&str>) {
+	let from_env = ["LC_ALL", "LC_MESSAGES", "LANG"]
+		.iter()
+		.filter_map(|var| std::env::var(var).ok())
+		.find(|value| !value.is_empty())
+		.and_then(|value| language_of(&value));
+	for code in saved.map(str::to_string).into_iter().chain(from_env) {
+		if set_locale(&code).is_ok() {
+			return;
+		}
+	}
+}
+
+/// The language part of a POSIX locale: "pt_BR.UTF-8" is "pt".
+fn language_of(posix: &str) -> Option<String> {
+	let code = posix.split(['_', '.', '@']).next()?.to_lowercase();
+	(!code.is_empty() && code != "c" && code != "posix").then_some(code)
+}
+
+/// The message for `key` in the current language.
+pub fn tr(key: &str) -> String {
+	if let Some((_, catalog)) = ACTIVE.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+		if let Some(text) = catalog.get(key) {
+			return text.to_string();
+		}
+	}
+	ENGLISH.get(key).unwrap_or(key).to_string()
+}
+
+/// `tr` with `{name}` placeholders filled in.
+pub fn tr_with(key: &str, args: &[(&str, &str)]) -> String {
+	fill(&tr(key), args)
+}
+
+fn fill(template: &str, args: &[(&str, &str)]) -> String {
+	args.iter().fold(template.to_string(), |text, (name, value)| {
+		text.replace(&format!("{{{}}}", name), value)
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn placeholders(text: &str) -> Vec<&str> {
+		let mut names: Vec<&str> = text
+			.split('{')
+			.skip(1)
+			.filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+			.collect();
+		names.sort();
+		names
+	}
+
+	#[test]
+	fn test_every_catalog_matches_english() {
+		for code in ["es"] {
+			let catalog = Catalog::parse(defaults::embedded(&format!("locales/{}.toml", code)).unwrap()).unwrap();
+			for key in ENGLISH.keys() {
+				let text = catalog.get(key).unwrap_or_else(|| panic!("{} is missing {}", code, key));
+				assert_eq!(placeholders(text), placeholders(ENGLISH.get(key).unwrap()), "{} {}", code, key);
+			}
+			for key in catalog.keys() {
+				assert!(ENGLISH.get(key).is_some(), "{} has unknown key {}", code, key);
+			}
+		}
+	}
+
+	#[test]
+	fn test_lookup_falls_back_and_fills_placeholders() {
+		assert_eq!(tr_with("prompt.call", &[("amount", "$20")]), "[c]all $20");
+		assert_eq!(tr("no.such.key"), "no.such.key");
+		assert_eq!(fill("{name} wins {amount}", &[("name", "Ana"), ("amount", "$5")]), "Ana wins $5");
+		assert_eq!(language_of("pt_BR.UTF-8").as_deref(), Some("pt"));
+		assert_eq!(language_of("C"), None);
+	}
+}
@@ -0,0 +1,340 @@
+//! Home game leagues: the same friends playing a season of sit & gos,
+//! scored by where they finish. `leagues.toml` in the config directory
+//! defines each league: its members, the games on its schedule (a sit &
+//! go table and the day it's played there), and the points for each
+//! finish. The server records the finishes of each scheduled game in
+//! `leagues/<id>.jsonl` in the data directory, and the standings are
+//! added up from those, so changing the points rescores the season.
+//!
+//! A finish is a member's place among the members who played; AIs and
+//! guests filling seats score nothing and don't push members down.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::error::PokerError;
+use crate::events::GameId;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LeagueConfig {
+	pub id: String,
+	pub name: String,
+	/// Players by bank id, as they sign in.
+	pub members: Vec<String>,
+	pub schedule: Vec<LeagueGame>,
+	/// Points for first, second and so on; finishes past the end score
+	/// nothing.
+	pub points: Vec<u32>,
+}
+
+/// A game on a league's schedule.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LeagueGame {
+	/// The sit & go table it's played at.
+	pub table: String,
+	/// The day, `YYYY-MM-DD`.
+	pub date: String,
+}
+
+impl LeagueGame {
+	fn is(&self, table: &str, date: NaiveDate) -> bool {
+		self.table == table && parse_date(&self.date) == Some(date)
+	}
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LeaguesFile {
+	#[serde(default)]
+	leagues: Vec<LeagueConfig>,
+}
+
+/// One scheduled game's result, a line of a league's results file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeagueResult {
+	pub game_id: GameId,
+	pub table: String,
+	pub date: String,
+	/// The members who played, winner first.
+	pub finishes: Vec<String>,
+}
+
+/// A member's season so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeagueStanding {
+	pub member: String,
+	pub points: u32,
+	pub played: u32,
+	pub wins: u32,
+}
+
+impl LeagueConfig {
+	fn is_member(&self, id: &str) -> bool {
+		self.members.iter().any(|m| m.eq_ignore_ascii_case(id))
+	}
+
+	/// The game on the schedule at `table` on `date`, if there is one.
+	pub fn scheduled(&self, table: &str, date: NaiveDate) -> Option<&LeagueGame> {
+		self.schedule.iter().find(|game| game.is(table, date))
+	}
+
+	/// The first game on the schedule from `today` on that hasn't been
+	/// played.
+	pub fn next_game(&self, results: &[LeagueResult], today: NaiveDate) -> Option<&LeagueGame> {
+		self.schedule.iter().find(|game| {
+			parse_date(&game.date).is_some_and(|date| date >= today)
+				&& !results.iter().any(|r| r.table == game.table && r.date == game.date)
+		})
+	}
+
+	/// Points for finishing `place`, 1 for first.
+	pub fn points_for(&self, place: usize) -> u32 {
+		place.checked_sub(1).and_then(|i| self.points.get(i)).copied().unwrap_or(0)
+	}
+
+	/// Every member, most points first; wins, then name, break ties.
+	pub fn standings(&self, results: &[LeagueResult]) -> Vec<LeagueStanding> {
+		let mut standings = self
+			.members
+			.iter()
+			.map(|member| LeagueStanding { member: member.clone(), points: 0, played: 0, wins: 0 })
+			.collect::<Vec<_>>();
+		for result in results {
+			for (i, id) in result.finishes.iter().enumerate() {
+				if let Some(standing) = standings.iter_mut().find(|s| s.member.eq_ignore_ascii_case(id)) {
+					standing.points += self.points_for(i + 1);
+					standing.played += 1;
+					if i == 0 {
+						standing.wins += 1;
+					}
+				}
+			}
+		}
+		standings.sort_by(|a, b| {
+			b.points.cmp(&a.points).then(b.wins.cmp(&a.wins)).then_with(|| a.member.cmp(&b.member))
+		});
+		standings
+	}
+}
+
+/// The leagues defined, and where their results are kept.
+#[derive(Debug, Clone, Default)]
+pub struct Leagues {
+	leagues: Vec<LeagueConfig>,
+	dir: PathBuf,
+}
+
+impl Leagues {
+	/// `leagues.toml` from the config directory, none if there isn't one,
+	/// with results in `leagues/` in the data directory.
+	pub fn load() -> Result<Self, PokerError> {
+		let dir = match dirs::data_dir() {
+			Some(data_dir) => data_dir.join("transparent-poker").join("leagues"),
+			None => PathBuf::from("leagues"),
+		};
+		match dirs::config_dir() {
+			Some(config_dir) => Self::load_from(&config_dir.join("transparent-poker").join("leagues.toml"), dir),
+			None => Ok(Self::new(Vec::new(), dir)),
+		}
+	}
+
+	/// The leagues in `path`, none if there's no such file, with results
+	/// in `dir`.
+	pub fn load_from(path: &Path, dir: impl Into<PathBuf>) -> Result<Self, PokerError> {
+		if !path.exists() {
+			return Ok(Self::new(Vec::new(), dir));
+		}
+		let content = fs::read_to_string(path).map_err(|e| PokerError::io("read", path, e))?;
+		Ok(Self::new(Self::parse(&content)?, dir))
+	}
+
+	pub fn parse(content: &str) -> Result<Vec<LeagueConfig>, PokerError> {
+		let file: LeaguesFile = toml::from_str(content).map_err(|e| PokerError::parse("leagues config", e))?;
+		for league in &file.leagues {
+			if let Some(game) = league.schedule.iter().find(|game| parse_date(&game.date).is_none()) {
+				return Err(PokerError::Config(format!(
+					"League '{}' has a game on '{}', which isn't a YYYY-MM-DD date",
+					league.id, game.date
+				)));
+			}
+		}
+		Ok(file.leagues)
+	}
+
+	pub fn new(leagues: Vec<LeagueConfig>, dir: impl Into<PathBuf>) -> Self {
+		Self { leagues, dir: dir.into() }
+	}
+
+	pub fn all(&self) -> &[LeagueConfig] {
+		&self.leagues
+	}
+
+	pub fn get(&self, id: &str) -> Option<&LeagueConfig> {
+		self.leagues.iter().find(|league| league.id == id)
+	}
+
+	fn path(&self, id: &str) -> PathBuf {
+		self.dir.join(format!("{}.jsonl", id))
+	}
+
+	/// The results recorded for league `id` so far, oldest first.
+	pub fn results(&self, id: &str) -> Result<Vec<LeagueResult>, PokerError> {
+		let path = self.path(id);
+		let content = match fs::read_to_string(&path) {
+			Ok(content) => content,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+			Err(e) => return Err(PokerError::io("read", &path, e)),
+		};
+		content
+			.lines()
+			.filter(|line| !line.trim().is_empty())
+			.map(|line| serde_json::from_str(line).map_err(|e| PokerError::parse(path.display().to_string(), e)))
+			.collect()
+	}
+
+	/// League `id`'s standings from the results so far.
+	pub fn standings(&self, id: &str) -> Result<Vec<LeagueStanding>, PokerError> {
+		let league = self.get(id).ok_or_else(|| PokerError::Config(format!("No league '{}' in leagues.toml", id)))?;
+		Ok(league.standings(&self.results(id)?))
+	}
+
+	/// Records a game at `table` on `date` for each league that has it on
+	/// the schedule and hasn't had it recorded already. `finished` is the
+	/// players by bank id and finish, 1 for the winner. Returns the
+	/// leagues it counted for.
+	pub fn record(
+		&self,
+		table: &str,
+		date: NaiveDate,
+		game_id: GameId,
+		finished: &[(String, u8)],
+	) -> Result<Vec<String>, PokerError> {
+		let mut order = finished.to_vec();
+		order.sort_by_key(|(_, place)| *place);
+		let mut counted = Vec::new();
+		for league in &self.leagues {
+			let Some(game) = league.scheduled(table, date) else {
+				continue;
+			};
+			let results = self.results(&league.id)?;
+			if results.iter().any(|r| r.table == game.table && r.date == game.date) {
+				continue;
+			}
+			let finishes = order
+				.iter()
+				.filter(|(id, _)| league.is_member(id))
+				.map(|(id, _)| id.to_lowercase())
+				.collect::<Vec<_>>();
+			if finishes.is_empty() {
+				continue;
+			}
+			let result = LeagueResult { game_id, table: game.table.clone(), date: game.date.clone(), finishes };
+			self.append(&league.id, &result)?;
+			counted.push(league.id.clone());
+		}
+		Ok(counted)
+	}
+
+	fn append(&self, id: &str, result: &LeagueResult) -> Result<(), PokerError> {
+		fs::create_dir_all(&self.dir).map_err(|e| PokerError::io("create", &self.dir, e))?;
+		let path = self.path(id);
+		let line = serde_json::to_string(result).map_err(|e| PokerError::parse("league result", e))?;
+		OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(&path)
+			.and_then(|mut file| writeln!(file, "{}", line))
+			.map_err(|e| PokerError::io("write", &path, e))
+	}
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+	NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const LEAGUES: &str = r#"
+[[leagues]]
+id = "friday"
+name = "Friday Night"
+members = ["alice", "bob", "carol"]
+points = [10, 6, 3]
+schedule = [
+    { table = "olive-field", date = "2026-10-02" },
+    { table = "olive-field", date = "2026-10-09" },
+]
+"#;
+
+	fn date(s: &str) -> NaiveDate {
+		parse_date(s).unwrap()
+	}
+
+	fn finished(players: &[&str]) -> Vec<(String, u8)> {
+		players.iter().enumerate().map(|(i, p)| (p.to_string(), i as u8 + 1)).collect()
+	}
+
+	#[test]
+	fn test_scheduled_games_are_recorded_once() {
+		let dir = std::env::temp_dir().join(format!("poker-league-{}", std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		let leagues = Leagues::new(Leagues::parse(LEAGUES).unwrap(), &dir);
+
+		// An AI winning doesn't push the members down
+		let counted = leagues.record("olive-field", date("2026-10-02"), GameId(1), &finished(&["tabby", "Bob", "alice"])).unwrap();
+		assert_eq!(counted, vec!["friday"]);
+		let again = leagues.record("olive-field", date("2026-10-02"), GameId(2), &finished(&["alice", "bob"]));
+		assert!(again.unwrap().is_empty());
+		let unscheduled = leagues.record("olive-field", date("2026-10-03"), GameId(3), &finished(&["alice", "bob"]));
+		assert!(unscheduled.unwrap().is_empty());
+
+		let results = leagues.results("friday").unwrap();
+		assert_eq!(results.len(), 1);
+		assert_eq!(results[0].finishes, vec!["bob", "alice"]);
+		let _ = fs::remove_dir_all(&dir);
+	}
+
+	#[test]
+	fn test_standings() {
+		let league = &Leagues::parse(LEAGUES).unwrap()[0];
+		let result = |finishes: &[&str]| LeagueResult {
+			game_id: GameId(1),
+			table: "olive-field".to_string(),
+			date: "2026-10-02".to_string(),
+			finishes: finishes.iter().map(|s| s.to_string()).collect(),
+		};
+		let standings = league.standings(&[result(&["bob", "alice", "carol"]), result(&["alice", "bob"])]);
+		let table = standings.iter().map(|s| (s.member.as_str(), s.points, s.played, s.wins)).collect::<Vec<_>>();
+		// Level on points and wins, so by name
+		assert_eq!(table, vec![("alice", 16, 2, 1), ("bob", 16, 2, 1), ("carol", 3, 1, 0)]);
+		assert_eq!(league.points_for(4), 0);
+
+		let next = league.next_game(&[result(&["bob"])], date("2026-10-01")).unwrap();
+		assert_eq!(next.date, "2026-10-09");
+		assert!(league.next_game(&[], date("2026-10-10")).is_none());
+	}
+
+	#[test]
+	fn test_example_league_parses() {
+		// Uncommented from the first league on
+		let example = include_str!("../config/leagues.toml")
+			.lines()
+			.skip_while(|line| *line != "# [[leagues]]")
+			.map(|line| line.trim_start_matches('#').trim_start())
+			.collect::<Vec<_>>()
+			.join("\n");
+		let leagues = Leagues::parse(&example).unwrap();
+		assert_eq!(leagues[0].schedule.len(), 4);
+	}
+
+	#[test]
+	fn test_bad_dates_are_refused() {
+		let bad = LEAGUES.replace("2026-10-09", "next friday");
+		assert!(matches!(Leagues::parse(&bad), Err(PokerError::Config(_))));
+	}
+}
@@ -3,22 +3,64 @@
 #![allow(clippy::if_same_then_else)]
 #![allow(clippy::expect_fun_call)]
 
+pub mod achievements;
 pub mod ai;
 pub mod bank;
+pub mod challenges;
 pub mod config;
+#[cfg(feature = "net")]
+pub mod daemon;
+#[cfg(feature = "tui")]
+pub mod demo;
+#[cfg(feature = "net")]
 pub mod embedded_server;
 pub mod engine;
+pub mod error;
 pub mod events;
+#[cfg(feature = "net")]
+pub mod feed;
+#[cfg(feature = "runtime")]
+pub mod game;
+#[cfg(feature = "tui")]
 pub mod game_loop;
+#[cfg(feature = "tui")]
+pub mod game_session;
+pub mod golden;
+pub mod history;
+pub mod i18n;
+pub mod league;
+pub mod lineup;
+#[cfg(feature = "net")]
 pub mod lobby;
+#[cfg(feature = "net")]
+pub mod llm;
 pub mod logging;
+#[cfg(feature = "tui")]
 pub mod menu;
+pub mod money;
+#[cfg(feature = "net")]
 pub mod net;
+#[cfg(feature = "net")]
+pub mod pit_boss;
 pub mod players;
+pub mod prelude;
+pub mod promotions;
 pub mod scenario;
+#[cfg(feature = "net")]
+pub mod schema;
+pub mod simulate;
 pub mod strategy;
 pub mod table;
+#[cfg(feature = "tui")]
 pub mod theme;
+#[cfg(feature = "tui")]
 pub mod tui;
+#[cfg(feature = "tui")]
+pub mod tutorial;
 pub mod view;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 pub mod defaults;
+
+#[cfg(feature = "runtime")]
+pub use game::{Game, GameBuilder, RunningGame};
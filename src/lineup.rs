@@ -0,0 +1,276 @@
+//! Which roster AIs fill a table's empty seats. A table with a `lineup`
+//! asks for a mix of strategies and, at cash tables, a spread of stack
+//! depths; the next AI to sit is drawn from whichever strategy the table
+//! is furthest short of. Without one, anyone on the roster may sit, each
+//! as keen as their `join_probability`. AIs whose bankrolls suit the
+//! stakes are asked first.
+
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::PlayerConfig;
+use crate::table::{GameFormat, TableConfig};
+
+/// Ready-made strategy mixes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Difficulty {
+	/// Calling stations and maniacs: loose, and easy to value bet.
+	Soft,
+	/// A bit of everything.
+	Mixed,
+	/// Balanced and aggressive regulars.
+	Tough,
+}
+
+impl Difficulty {
+	/// Strategy ids and their weights.
+	pub fn strategies(self) -> &'static [(&'static str, f32)] {
+		match self {
+			Difficulty::Soft => &[("calling_station", 3.0), ("maniac", 2.0), ("lag", 1.0), ("rock", 1.0)],
+			Difficulty::Mixed => &[
+				("balanced", 1.0),
+				("lag", 1.0),
+				("rock", 1.0),
+				("nit", 1.0),
+				("calling_station", 1.0),
+				("maniac", 1.0),
+			],
+			Difficulty::Tough => &[("balanced", 3.0), ("lag", 2.0), ("rock", 1.0), ("nit", 1.0)],
+		}
+	}
+}
+
+/// A table's wanted lineup, `[tables.lineup]` in `tables.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LineupConfig {
+	#[serde(default)]
+	pub difficulty: Option<Difficulty>,
+	/// Strategy ids and their relative weights, in place of the
+	/// difficulty's mix.
+	#[serde(default)]
+	pub strategies: BTreeMap<String, f32>,
+	/// Cash tables: AI stacks drawn between these many big blinds, kept
+	/// inside the table's buy-in range.
+	#[serde(default)]
+	pub stack_bb: Option<(f32, f32)>,
+}
+
+impl LineupConfig {
+	/// The strategy mix to aim for; empty when any will do.
+	pub fn weights(&self) -> Vec<(String, f32)> {
+		if !self.strategies.is_empty() {
+			return self.strategies.iter().map(|(id, w)| (id.clone(), *w)).collect();
+		}
+		self.difficulty
+			.map(|d| d.strategies().iter().map(|(id, w)| (id.to_string(), *w)).collect())
+			.unwrap_or_default()
+	}
+
+	/// A stack for the next AI to sit at `table`, or `None` to bring the
+	/// usual buy-in.
+	pub fn stack<R: Rng + ?Sized>(&self, table: &TableConfig, rng: &mut R) -> Option<f32> {
+		let (low, high) = self.stack_bb?;
+		if table.format != GameFormat::Cash {
+			return None;
+		}
+		let big = table.current_blinds().1;
+		let depth = rng.random_range(low.min(high)..=high.max(low)).round();
+		let least = table.effective_buy_in();
+		let most = table.max_buy_in.unwrap_or(f32::MAX).max(least);
+		Some((depth * big).clamp(least, most))
+	}
+}
+
+/// Bankrolls, in buy-ins, that suit a table's stakes: enough not to go
+/// broke on a bad night, not so much the player belongs at a bigger game.
+pub const BANKROLL_BUY_INS: RangeInclusive<f32> = 5.0..=50.0;
+
+/// Narrows `available` to the players whose bankroll suits `buy_in`,
+/// unless none do.
+pub fn prefer_stakes(available: &mut Vec<&PlayerConfig>, bankroll: impl Fn(&str) -> f32, buy_in: f32) {
+	let suits = |p: &&PlayerConfig| buy_in <= 0.0 || BANKROLL_BUY_INS.contains(&(bankroll(&p.id) / buy_in));
+	if available.iter().any(suits) {
+		available.retain(suits);
+	}
+}
+
+/// The next AI to sit, from the roster players not yet at the table.
+/// `seated` holds the strategies of the AIs already there.
+pub fn pick<'a, R: Rng + ?Sized>(
+	lineup: Option<&LineupConfig>,
+	available: &[&'a PlayerConfig],
+	seated: &[String],
+	rng: &mut R,
+) -> Option<&'a PlayerConfig> {
+	let weights = lineup
+		.map(LineupConfig::weights)
+		.unwrap_or_default()
+		.into_iter()
+		.filter(|(id, w)| *w > 0.0 && available.iter().any(|p| &p.strategy == id))
+		.collect::<Vec<_>>();
+	if weights.is_empty() {
+		return by_join_probability(available, rng);
+	}
+
+	// How far short of its share each strategy falls once one more sits
+	let total: f32 = weights.iter().map(|(_, w)| w).sum();
+	let after = seated.len() as f32 + 1.0;
+	let mut wanted = weights
+		.iter()
+		.map(|(id, w)| {
+			let have = seated.iter().filter(|s| *s == id).count() as f32;
+			(id, (w / total * after - have).max(0.0))
+		})
+		.collect::<Vec<_>>();
+	if wanted.iter().all(|(_, short)| *short == 0.0) {
+		wanted = weights.iter().map(|(id, w)| (id, *w)).collect();
+	}
+
+	let strategy = weighted(&wanted, rng)?;
+	let candidates = available.iter().filter(|p| &p.strategy == strategy).copied().collect::<Vec<_>>();
+	by_join_probability(&candidates, rng)
+}
+
+fn weighted<'a, R: Rng + ?Sized>(choices: &[(&'a String, f32)], rng: &mut R) -> Option<&'a String> {
+	let total: f32 = choices.iter().map(|(_, w)| w).sum();
+	let mut roll = rng.random::<f32>() * total;
+	for (choice, weight) in choices {
+		if roll < *weight {
+			return Some(choice);
+		}
+		roll -= weight;
+	}
+	choices.last().map(|(choice, _)| *choice)
+}
+
+/// Anyone, each as likely to accept as their `join_probability`; the
+/// first asked sits if nobody does.
+fn by_join_probability<'a, R: Rng + ?Sized>(available: &[&'a PlayerConfig], rng: &mut R) -> Option<&'a PlayerConfig> {
+	let mut shuffled = available.to_vec();
+	shuffled.shuffle(rng);
+	shuffled
+		.iter()
+		.find(|p| rng.random::<f32>() < p.join_probability)
+		.or_else(|| shuffled.first())
+		.copied()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::bank::BustPolicy;
+	use rand::SeedableRng;
+	use rand::rngs::StdRng;
+
+	fn roster() -> Vec<PlayerConfig> {
+		["calling_station", "calling_station", "maniac", "balanced", "balanced", "nit", "rock"]
+			.iter()
+			.enumerate()
+			.map(|(i, strategy)| PlayerConfig {
+				id: format!("ai{}", i),
+				name: None,
+				version: "0.1".to_string(),
+				join_probability: 0.5,
+				strategy: strategy.to_string(),
+				strategy_model: None,
+				tempo: None,
+				schedule: Vec::new(),
+				when_busted: BustPolicy::default(),
+			})
+			.collect()
+	}
+
+	/// Fills `seats` one at a time, as the server does.
+	fn fill(lineup: &LineupConfig, seats: usize, seed: u64) -> Vec<String> {
+		let roster = roster();
+		let mut rng = StdRng::seed_from_u64(seed);
+		let mut seated: Vec<&PlayerConfig> = Vec::new();
+		for _ in 0..seats {
+			let available = roster.iter().filter(|p| !seated.iter().any(|s| s.id == p.id)).collect::<Vec<_>>();
+			let strategies = seated.iter().map(|p| p.strategy.clone()).collect::<Vec<_>>();
+			seated.push(pick(Some(lineup), &available, &strategies, &mut rng).unwrap());
+		}
+		seated.into_iter().map(|p| p.strategy.clone()).collect()
+	}
+
+	#[test]
+	fn test_soft_table_seats_the_loose_players_first() {
+		let lineup = LineupConfig { difficulty: Some(Difficulty::Soft), ..LineupConfig::default() };
+		for seed in 0..20 {
+			let mut strategies = fill(&lineup, 4, seed);
+			strategies.sort();
+			assert_eq!(strategies, ["calling_station", "calling_station", "maniac", "rock"]);
+		}
+	}
+
+	#[test]
+	fn test_explicit_mix_is_followed_then_falls_back() {
+		let lineup = LineupConfig {
+			difficulty: Some(Difficulty::Soft),
+			strategies: BTreeMap::from([("balanced".to_string(), 1.0), ("nit".to_string(), 1.0)]),
+			..LineupConfig::default()
+		};
+		let mut strategies = fill(&lineup, 3, 7);
+		strategies.sort();
+		assert_eq!(strategies, ["balanced", "balanced", "nit"]);
+		// With the mix used up, anyone left may sit
+		assert_eq!(fill(&lineup, 7, 7).len(), 7);
+	}
+
+	#[test]
+	fn test_no_lineup_draws_from_everyone() {
+		let roster = roster();
+		let available = roster.iter().collect::<Vec<_>>();
+		let mut rng = StdRng::seed_from_u64(3);
+		assert!(pick(None, &available, &[], &mut rng).is_some());
+		assert!(pick(None, &[], &[], &mut rng).is_none());
+	}
+
+	#[test]
+	fn test_bankrolls_that_suit_the_stakes_go_first() {
+		let roster = roster();
+		let bankroll = |id: &str| match id {
+			"ai0" => 100.0,
+			"ai1" => 1000.0,
+			_ => 20000.0,
+		};
+		let mut available = roster.iter().collect::<Vec<_>>();
+		prefer_stakes(&mut available, bankroll, 40.0);
+		assert_eq!(available.iter().map(|p| p.id.as_str()).collect::<Vec<_>>(), ["ai1"]);
+
+		let mut available = roster.iter().collect::<Vec<_>>();
+		prefer_stakes(&mut available, bankroll, 10000.0);
+		assert_eq!(available.len(), roster.len());
+	}
+
+	#[test]
+	fn test_stacks_stay_inside_the_buy_in_range() {
+		let table: TableConfig = toml::from_str(
+			r#"
+			id = "t"
+			name = "T"
+			format = "cash"
+			betting = "no-limit"
+			small_blind = 1.0
+			big_blind = 2.0
+			min_buy_in = 40.0
+			max_buy_in = 200.0
+			"#,
+		)
+		.unwrap();
+		let lineup = LineupConfig { stack_bb: Some((10.0, 300.0)), ..LineupConfig::default() };
+		let mut rng = StdRng::seed_from_u64(5);
+		for _ in 0..50 {
+			let stack = lineup.stack(&table, &mut rng).unwrap();
+			assert!((40.0..=200.0).contains(&stack), "{}", stack);
+			assert_eq!(stack % 2.0, 0.0);
+		}
+		assert!(LineupConfig::default().stack(&table, &mut rng).is_none());
+	}
+}
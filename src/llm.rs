@@ -0,0 +1,176 @@
+//! Calls to a hosted language model for the table characters that talk
+//! (the pit boss). The API is reached through the `curl` binary so the
+//! crate doesn't carry an HTTP/TLS stack for one optional feature.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde::Deserialize;
+
+use crate::config::ModelConfig;
+use crate::error::PokerError;
+use crate::logging;
+
+const API_URL: &str = "https://api.anthropic.com/v1/messages";
+const API_VERSION: &str = "2023-06-01";
+const REQUEST_TIMEOUT_SECS: u32 = 20;
+
+#[derive(Debug, Clone)]
+pub struct Completion {
+	pub text: String,
+	pub input_tokens: u32,
+	pub output_tokens: u32,
+}
+
+pub trait LanguageModel: Send {
+	/// Model id, for logs and cost reports.
+	fn id(&self) -> &str;
+	fn complete(&self, system: &str, prompt: &str) -> Result<Completion, PokerError>;
+	/// Dollar cost of one call.
+	fn cost(&self, completion: &Completion) -> f64;
+}
+
+/// Running totals for one character's model use.
+#[derive(Debug, Clone, Default)]
+pub struct Usage {
+	pub calls: u32,
+	pub input_tokens: u64,
+	pub output_tokens: u64,
+	pub cost: f64,
+}
+
+impl Usage {
+	/// Adds a call to the totals and logs it under `who`.
+	pub fn record(&mut self, who: &str, model: &dyn LanguageModel, completion: &Completion) {
+		self.calls += 1;
+		self.input_tokens += completion.input_tokens as u64;
+		self.output_tokens += completion.output_tokens as u64;
+		self.cost += model.cost(completion);
+		logging::ai::cost(who, model.id(), completion.input_tokens, completion.output_tokens);
+	}
+}
+
+/// The Messages API, authenticated with `ANTHROPIC_API_KEY`.
+pub struct AnthropicModel {
+	config: ModelConfig,
+	api_key: String,
+}
+
+impl AnthropicModel {
+	pub fn new(config: ModelConfig, api_key: impl Into<String>) -> Self {
+		Self {
+			config,
+			api_key: api_key.into(),
+		}
+	}
+
+	pub fn from_env(config: ModelConfig) -> Result<Self, PokerError> {
+		let key = std::env::var("ANTHROPIC_API_KEY")
+			.map_err(|_| PokerError::Config("ANTHROPIC_API_KEY is not set".to_string()))?;
+		Ok(Self::new(config, key))
+	}
+}
+
+#[derive(Deserialize)]
+struct ApiResponse {
+	#[serde(default)]
+	content: Vec<ContentBlock>,
+	usage: Option<ApiUsage>,
+	error: Option<ApiError>,
+}
+
+#[derive(Deserialize)]
+struct ContentBlock {
+	#[serde(default)]
+	text: String,
+}
+
+#[derive(Deserialize)]
+struct ApiUsage {
+	input_tokens: u32,
+	output_tokens: u32,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+	message: String,
+}
+
+fn parse_response(body: &str) -> Result<Completion, PokerError> {
+	let response: ApiResponse = serde_json::from_str(body)
+		.map_err(|e| PokerError::parse("model response", e))?;
+	if let Some(error) = response.error {
+		return Err(PokerError::Net(format!("Model error: {}", error.message)));
+	}
+	let usage = response.usage.ok_or_else(|| PokerError::parse("model response", "no usage"))?;
+	let text: String = response.content.into_iter().map(|b| b.text).collect();
+	Ok(Completion {
+		text: text.trim().to_string(),
+		input_tokens: usage.input_tokens,
+		output_tokens: usage.output_tokens,
+	})
+}
+
+impl LanguageModel for AnthropicModel {
+	fn id(&self) -> &str {
+		&self.config.id
+	}
+
+	fn complete(&self, system: &str, prompt: &str) -> Result<Completion, PokerError> {
+		let body = serde_json::json!({
+			"model": self.config.id,
+			"max_tokens": self.config.max_tokens,
+			"system": system,
+			"messages": [{"role": "user", "content": prompt}],
+		});
+
+		// The key goes in on stdin so it never shows up in the process list
+		let mut child = Command::new("curl")
+			.args(["-sS", "--max-time", &REQUEST_TIMEOUT_SECS.to_string(), API_URL])
+			.args(["-H", "@-"])
+			.args(["-H", &format!("anthropic-version: {}", API_VERSION)])
+			.args(["-H", "content-type: application/json"])
+			.args(["--data-binary", &body.to_string()])
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.spawn()
+			.map_err(|e| PokerError::Net(format!("Failed to run curl: {}", e)))?;
+
+		if let Some(mut stdin) = child.stdin.take() {
+			writeln!(stdin, "x-api-key: {}", self.api_key)
+				.map_err(|e| PokerError::Net(format!("Failed to pass API key to curl: {}", e)))?;
+		}
+		let output = child
+			.wait_with_output()
+			.map_err(|e| PokerError::Net(format!("curl failed: {}", e)))?;
+		if !output.status.success() {
+			return Err(PokerError::Net(format!("curl failed: {}", String::from_utf8_lossy(&output.stderr).trim())));
+		}
+		parse_response(&String::from_utf8_lossy(&output.stdout))
+	}
+
+	fn cost(&self, completion: &Completion) -> f64 {
+		self.config.calculate_cost(completion.input_tokens, completion.output_tokens)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_response_joins_text_blocks() {
+		let body = r#"{"content":[{"type":"text","text":" Big pot! "}],"usage":{"input_tokens":120,"output_tokens":8}}"#;
+		let completion = parse_response(body).unwrap();
+		assert_eq!(completion.text, "Big pot!");
+		assert_eq!(completion.input_tokens, 120);
+		assert_eq!(completion.output_tokens, 8);
+	}
+
+	#[test]
+	fn test_parse_response_surfaces_api_errors() {
+		let body = r#"{"type":"error","error":{"type":"authentication_error","message":"invalid x-api-key"}}"#;
+		assert_eq!(parse_response(body).unwrap_err().to_string(), "Model error: invalid x-api-key");
+	}
+}
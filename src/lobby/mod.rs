@@ -1,4 +1,6 @@
+use crate::error::PokerError;
 use crate::events::Seat;
+use crate::money::money;
 use crate::net::client::GameClient;
 use crate::net::protocol::{PlayerInfo, ServerMessage, TableInfo, TableStatus};
 use crate::table::TableConfig;
@@ -13,7 +15,7 @@ pub enum LobbyCommand {
 	Ready,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub enum LobbyEvent {
 	TablesListed(Vec<TableSummary>),
 	TableJoined {
@@ -28,6 +30,7 @@ pub enum LobbyEvent {
 		seat: Seat,
 		username: String,
 		is_ai: bool,
+		bankroll: Option<f32>,
 	},
 	PlayerLeft {
 		seat: Seat,
@@ -35,13 +38,17 @@ pub enum LobbyEvent {
 	PlayerReady {
 		seat: Seat,
 	},
+	/// The sit & go starts, with AIs in any empty seats, in `seconds`.
+	RegistrationOpen {
+		seconds: u32,
+	},
 	GameStarting,
 	NetworkGameStarted {
 		seat: Seat,
 		table_config: TableConfig,
 		num_players: usize,
 	},
-	Error(String),
+	Error(PokerError),
 	LeftTable,
 }
 
@@ -78,10 +85,6 @@ impl From<TableInfo> for TableSummary {
 
 impl From<&TableConfig> for TableSummary {
 	fn from(config: &TableConfig) -> Self {
-		let blinds = match (config.small_blind, config.big_blind) {
-			(Some(sb), Some(bb)) => format!("${:.0}/${:.0}", sb, bb),
-			_ => "N/A".to_string(),
-		};
 		let buy_in = config.effective_buy_in();
 		let status = TableStatus::Waiting;
 		let players = 0;
@@ -91,8 +94,8 @@ impl From<&TableConfig> for TableSummary {
 			name: config.name.clone(),
 			format: config.format.to_string(),
 			betting: config.betting.to_string(),
-			blinds,
-			buy_in: format!("${:.0}", buy_in),
+			blinds: config.blinds_label(),
+			buy_in: money(buy_in),
 			players,
 			max_players: config.max_players,
 			status,
@@ -123,7 +126,7 @@ impl From<PlayerInfo> for LobbyPlayer {
 			is_human: !info.is_ai,
 			is_ready: info.ready,
 			strategy: None,
-			bankroll: None,
+			bankroll: info.bankroll,
 		}
 	}
 }
@@ -137,6 +140,10 @@ pub trait LobbyBackend {
 
 pub struct NetworkBackend {
 	client: GameClient,
+	/// Hot seat: the other players at this terminal, who follow the main
+	/// client to its table and ready up with it. Their messages wait
+	/// unread for the game loop.
+	guests: Vec<GameClient>,
 	pending_events: Vec<LobbyEvent>,
 	my_seat: Option<Seat>,
 	game_started: bool,
@@ -150,6 +157,7 @@ impl NetworkBackend {
 	pub fn new(client: GameClient) -> Self {
 		Self {
 			client,
+			guests: Vec::new(),
 			pending_events: Vec::new(),
 			my_seat: None,
 			game_started: false,
@@ -164,10 +172,20 @@ impl NetworkBackend {
 		self.username.as_deref()
 	}
 
+	pub fn with_guests(mut self, guests: Vec<GameClient>) -> Self {
+		self.guests = guests;
+		self
+	}
+
 	pub fn into_client(self) -> GameClient {
 		self.client
 	}
 
+	/// The main client and the hot-seat guests.
+	pub fn into_clients(self) -> (GameClient, Vec<GameClient>) {
+		(self.client, self.guests)
+	}
+
 	pub fn client_mut(&mut self) -> &mut GameClient {
 		&mut self.client
 	}
@@ -218,6 +236,7 @@ impl NetworkBackend {
 						seat,
 						username,
 						is_ai: false,
+						bankroll: None,
 					});
 				}
 
@@ -230,7 +249,11 @@ impl NetworkBackend {
 					self.emit(LobbyEvent::PlayerReady { seat });
 				}
 
-				ServerMessage::AIAdded { seat, name } => {
+				ServerMessage::RegistrationOpen { seconds } => {
+					self.emit(LobbyEvent::RegistrationOpen { seconds });
+				}
+
+				ServerMessage::AIAdded { seat, name, bankroll } => {
 					self.lobby_players.push(LobbyPlayer {
 						seat: Some(seat),
 						id: name.to_lowercase(),
@@ -239,12 +262,13 @@ impl NetworkBackend {
 						is_human: false,
 						is_ready: true,
 						strategy: None,
-						bankroll: None,
+						bankroll,
 					});
 					self.emit(LobbyEvent::PlayerJoined {
 						seat,
 						username: name,
 						is_ai: true,
+						bankroll,
 					});
 				}
 
@@ -275,7 +299,11 @@ impl NetworkBackend {
 				}
 
 				ServerMessage::Error { message } => {
-					self.emit(LobbyEvent::Error(message));
+					self.emit(LobbyEvent::Error(PokerError::Net(message)));
+				}
+
+				ServerMessage::ProtocolError { reason } => {
+					self.emit(LobbyEvent::Error(PokerError::Net(format!("Lost a message on the way to the server: {}", reason))));
 				}
 
 				ServerMessage::Welcome { username, bankroll, .. } => {
@@ -296,9 +324,17 @@ impl LobbyBackend for NetworkBackend {
 				self.client.list_tables()
 			}
 			LobbyCommand::JoinTable(table_id) => {
-				self.client.join_table(&table_id)
+				let joined = self.client.join_table(&table_id);
+				for guest in &mut self.guests {
+					guest.drain();
+					let _ = guest.join_table(&table_id);
+				}
+				joined
 			}
 			LobbyCommand::LeaveTable => {
+				for guest in &mut self.guests {
+					let _ = guest.leave_table();
+				}
 				self.client.leave_table()
 			}
 			LobbyCommand::AddAI => {
@@ -308,6 +344,9 @@ impl LobbyBackend for NetworkBackend {
 				self.client.remove_ai(seat)
 			}
 			LobbyCommand::Ready => {
+				for guest in &mut self.guests {
+					let _ = guest.ready();
+				}
 				self.client.ready()
 			}
 		};
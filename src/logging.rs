@@ -1,14 +1,58 @@
 use std::fs::{self, OpenOptions};
 use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Mutex;
 
 use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+/// How much goes in the log. Each line's type maps to a level: `ERROR`,
+/// `WARN` and the other trouble types, `DEBUG`, and everything else is
+/// `info`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+	Error,
+	Warn,
+	Info,
+	#[default]
+	Debug,
+}
+
+impl LogLevel {
+	fn of(log_type: &str) -> Self {
+		match log_type {
+			"ERROR" => LogLevel::Error,
+			"WARN" | "TIMEOUT" | "PROTOCOL" | "STDERR" => LogLevel::Warn,
+			"DEBUG" => LogLevel::Debug,
+			_ => LogLevel::Info,
+		}
+	}
+}
+
+impl FromStr for LogLevel {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_lowercase().as_str() {
+			"error" => Ok(LogLevel::Error),
+			"warn" => Ok(LogLevel::Warn),
+			"info" => Ok(LogLevel::Info),
+			"debug" => Ok(LogLevel::Debug),
+			other => Err(format!("Unknown log level '{}' (error, warn, info or debug)", other)),
+		}
+	}
+}
 
 struct LogState {
 	file: Option<std::fs::File>,
 	current_date: String,
 	game_id: String,
 	hand_num: u32,
+	level: LogLevel,
+	/// In place of `logs/` in the data directory.
+	dir: Option<PathBuf>,
 }
 
 static LOG_STATE: Mutex<LogState> = Mutex::new(LogState {
@@ -16,6 +60,8 @@ static LOG_STATE: Mutex<LogState> = Mutex::new(LogState {
 	current_date: String::new(),
 	game_id: String::new(),
 	hand_num: 0,
+	level: LogLevel::Debug,
+	dir: None,
 });
 
 fn today() -> String {
@@ -26,18 +72,18 @@ fn timestamp() -> String {
 	Local::now().format("%H:%M:%S%.3f").to_string()
 }
 
-fn log_dir() -> std::path::PathBuf {
+fn default_log_dir() -> PathBuf {
 	if let Some(data_dir) = dirs::data_dir() {
 		data_dir.join("transparent-poker").join("logs")
 	} else {
-		std::path::PathBuf::from("logs")
+		PathBuf::from("logs")
 	}
 }
 
 fn ensure_log_file(state: &mut LogState) {
 	let date = today();
 	if state.current_date != date || state.file.is_none() {
-		let log_dir = log_dir();
+		let log_dir = state.dir.clone().unwrap_or_else(default_log_dir);
 		let _ = fs::create_dir_all(&log_dir);
 		let path = log_dir.join(format!("poker-{}.log", date));
 		if let Ok(file) = OpenOptions::new()
@@ -51,6 +97,21 @@ fn ensure_log_file(state: &mut LogState) {
 	}
 }
 
+/// Leaves out lines below `level`.
+pub fn set_level(level: LogLevel) {
+	if let Ok(mut state) = LOG_STATE.lock() {
+		state.level = level;
+	}
+}
+
+/// Writes the logs to `dir` from now on.
+pub fn set_dir(dir: impl Into<PathBuf>) {
+	if let Ok(mut state) = LOG_STATE.lock() {
+		state.dir = Some(dir.into());
+		state.file = None;
+	}
+}
+
 pub fn set_game_id(game_id: u64) {
 	if let Ok(mut state) = LOG_STATE.lock() {
 		state.game_id = format!("{:08x}", game_id & 0xFFFFFFFF);
@@ -65,6 +126,9 @@ pub fn set_hand_num(hand_num: u32) {
 
 pub fn log(module: &str, log_type: &str, message: &str) {
 	if let Ok(mut state) = LOG_STATE.lock() {
+		if LogLevel::of(log_type) > state.level {
+			return;
+		}
 		ensure_log_file(&mut state);
 
 		let game_id = if state.game_id.is_empty() { "--------" } else { &state.game_id };
@@ -85,6 +149,49 @@ pub fn log(module: &str, log_type: &str, message: &str) {
 	}
 }
 
+/// The message a panic was raised with, if it had one.
+pub fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+	payload
+		.downcast_ref::<&str>()
+		.map(|s| s.to_string())
+		.or_else(|| payload.downcast_ref::<String>().cloned())
+		.unwrap_or_else(|| "unknown panic".to_string())
+}
+
+fn crash_report(message: &str, location: &str, thread: &str, game_id: &str, hand_num: u32, backtrace: &str) -> String {
+	let game_id = if game_id.is_empty() { "none" } else { game_id };
+	format!(
+		"transparent-poker {} crashed at {}\n\nPanic: {}\nAt: {}\nThread: {}\nGame: {}\nHand: {}\n\n{}\n",
+		env!("CARGO_PKG_VERSION"),
+		Local::now().to_rfc3339(),
+		message,
+		location,
+		thread,
+		game_id,
+		hand_num,
+		backtrace
+	)
+}
+
+/// Writes what's known about a panic, with the game and hand in play, to
+/// `crash-<time>.txt` beside the logs, and notes it in the log.
+pub fn write_crash_report(message: &str, location: &str, thread: &str) -> std::io::Result<PathBuf> {
+	let (dir, game_id, hand_num) = match LOG_STATE.lock() {
+		Ok(state) => (state.dir.clone(), state.game_id.clone(), state.hand_num),
+		Err(e) => {
+			let state = e.into_inner();
+			(state.dir.clone(), state.game_id.clone(), state.hand_num)
+		}
+	};
+	let dir = dir.unwrap_or_else(default_log_dir);
+	fs::create_dir_all(&dir)?;
+	let path = dir.join(format!("crash-{}.txt", Local::now().format("%Y%m%d-%H%M%S")));
+	let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+	fs::write(&path, crash_report(message, location, thread, &game_id, hand_num, &backtrace))?;
+	log("Crash", "ERROR", &format!("{} at {}; report in {}", message, location, path.display()));
+	Ok(path)
+}
+
 pub fn log_verbatim(module: &str, log_type: &str, label: &str, content: &str) {
 	let single_line = content.replace('\n', " ").replace('\r', "");
 	log(module, log_type, &format!("{}: <<<{}>>>", label, single_line));
@@ -92,13 +199,14 @@ pub fn log_verbatim(module: &str, log_type: &str, label: &str, content: &str) {
 
 pub mod engine {
 	use super::log;
+	use crate::money::chips;
 
 	pub fn hand_started(button: usize, num_players: usize) {
 		log("Engine", "HAND", &format!("started button={} players={}", button, num_players));
 	}
 
 	pub fn action(player: &str, action_desc: &str, pot: f32) {
-		log("Engine", "ACTION", &format!("{}: {} (pot: ${:.0})", player, action_desc, pot));
+		log("Engine", "ACTION", &format!("{}: {} (pot: {})", player, action_desc, chips(pot)));
 	}
 
 	pub fn street(street: &str, board: &str) {
@@ -111,8 +219,8 @@ pub mod engine {
 
 	pub fn pot_awarded(player: &str, amount: f32, hand_desc: Option<&str>) {
 		match hand_desc {
-			Some(desc) => log("Engine", "POT", &format!("{} wins ${:.0} ({})", player, amount, desc)),
-			None => log("Engine", "POT", &format!("{} wins ${:.0}", player, amount)),
+			Some(desc) => log("Engine", "POT", &format!("{} wins {} ({})", player, chips(amount), desc)),
+			None => log("Engine", "POT", &format!("{} wins {}", player, chips(amount))),
 		}
 	}
 
@@ -168,3 +276,16 @@ pub mod tui {
 		log("TUI", "EVENT", msg);
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_crash_report_names_the_game_and_hand() {
+		let report = crash_report("index out of bounds", "src/engine/hand.rs:12:5", "main", "1a2b3c4d", 7, "");
+		assert!(report.contains("Panic: index out of bounds\nAt: src/engine/hand.rs:12:5\nThread: main"));
+		assert!(report.contains("Game: 1a2b3c4d\nHand: 7"));
+		assert!(crash_report("boom", "x", "main", "", 0, "").contains("Game: none"));
+	}
+}
@@ -1,4 +1,5 @@
 use std::io;
+use std::time::{Duration, Instant};
 
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use ratatui::{
@@ -10,11 +11,20 @@ use ratatui::{
 	Frame, Terminal,
 };
 
+use crate::achievements::{Achievement, Achievements};
+use crate::config::{load_preferences, save_preferences, Preferences};
+use crate::error::PokerError;
 use crate::events::Seat;
+use crate::i18n::{self, tr, tr_with};
+use crate::league::{LeagueStanding, Leagues};
+use crate::money::{self, money};
 use crate::lobby::{LobbyBackend, LobbyCommand, LobbyEvent, LobbyPlayer, TableSummary};
+use crate::net::discovery::{self, DiscoveredServer};
 use crate::net::protocol::TableStatus;
 use crate::table::TableConfig;
 use crate::theme::Theme;
+use crate::tui::linear::speak;
+use crate::tui::snapshot;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum SortMode {
@@ -50,18 +60,110 @@ impl SortMode {
 		}
 	}
 
-	fn label(self) -> &'static str {
-		match self {
-			SortMode::Manual => "Manual",
-			SortMode::Alpha => "A-Z",
-			SortMode::Format => "Format (cash game or tournament)",
-			SortMode::Betting => "Betting structure (no limit, pot limit, fixed)",
-			SortMode::StakesAsc => "Minimum buy-in (ascending)",
-			SortMode::StakesDesc => "Minimum buy-in (descending)",
-		}
+	fn label(self) -> String {
+		tr(match self {
+			SortMode::Manual => "sort.manual",
+			SortMode::Alpha => "sort.alpha",
+			SortMode::Format => "sort.format",
+			SortMode::Betting => "sort.betting",
+			SortMode::StakesAsc => "sort.stakes_asc",
+			SortMode::StakesDesc => "sort.stakes_desc",
+		})
+	}
+}
+
+/// A row of the settings screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Setting {
+	Theme,
+	Language,
+	Currency,
+	Thousands,
+	TournamentChips,
+	ActionDelay,
+	StreetDelay,
+	DealDelay,
+	SkipShowdowns,
+	AutoMuck,
+	FastForwardFolded,
+	BeginnerHints,
+	CoachingTips,
+}
+
+const SETTINGS: [Setting; 13] = [
+	Setting::Theme,
+	Setting::Language,
+	Setting::Currency,
+	Setting::Thousands,
+	Setting::TournamentChips,
+	Setting::ActionDelay,
+	Setting::StreetDelay,
+	Setting::DealDelay,
+	Setting::SkipShowdowns,
+	Setting::AutoMuck,
+	Setting::FastForwardFolded,
+	Setting::BeginnerHints,
+	Setting::CoachingTips,
+];
+
+/// Currency symbols to choose between, and whether each goes after the
+/// amount.
+const CURRENCIES: [(&str, bool); 6] = [("$", false), ("€", false), ("€", true), ("£", false), ("¥", false), ("", false)];
+
+/// Thousands separators to choose between.
+const SEPARATORS: [&str; 5] = ["", ",", ".", " ", "'"];
+
+/// Delays to choose between; `None` keeps the table's own.
+const DELAY_CHOICES: [Option<u64>; 6] = [None, Some(0), Some(250), Some(500), Some(1000), Some(2000)];
+
+/// Time between cards when dealing; `None` deals them all at once.
+const DEAL_CHOICES: [Option<u64>; 4] = [None, Some(60), Some(120), Some(250)];
+
+impl Setting {
+	fn label(self) -> String {
+		tr(match self {
+			Setting::Theme => "setting.theme",
+			Setting::Language => "setting.language",
+			Setting::Currency => "setting.currency",
+			Setting::Thousands => "setting.thousands",
+			Setting::TournamentChips => "setting.tournament_chips",
+			Setting::ActionDelay => "setting.action_delay",
+			Setting::StreetDelay => "setting.street_delay",
+			Setting::DealDelay => "setting.deal_delay",
+			Setting::SkipShowdowns => "setting.skip_showdowns",
+			Setting::AutoMuck => "setting.auto_muck",
+			Setting::FastForwardFolded => "setting.fast_forward_folded",
+			Setting::BeginnerHints => "setting.beginner_hints",
+			Setting::CoachingTips => "setting.coaching_tips",
+		})
+	}
+}
+
+fn delay_text(delay: Option<u64>) -> String {
+	match delay {
+		None => tr("setting.table_default"),
+		Some(0) => tr("setting.no_delay"),
+		Some(ms) => tr_with("setting.delay_ms", &[("ms", &ms.to_string())]),
 	}
 }
 
+/// The entry next to `current` in `choices`, going `direction` and
+/// wrapping round.
+fn cycle<T: PartialEq + Copy>(choices: &[T], current: T, direction: i32) -> T {
+	let len = choices.len() as i32;
+	let index = choices.iter().position(|&c| c == current).unwrap_or(0) as i32;
+	choices[(index + direction).rem_euclid(len) as usize]
+}
+
+/// The next delay choice in `direction`, wrapping round.
+fn cycle_delay(delay: Option<u64>, direction: i32) -> Option<u64> {
+	cycle(&DELAY_CHOICES, delay, direction)
+}
+
+fn on_off(on: bool) -> String {
+	tr(if on { "prompt.on" } else { "prompt.off" })
+}
+
 pub enum MenuResult {
 	NetworkGameStarted {
 		seat: Seat,
@@ -74,6 +176,40 @@ pub enum MenuResult {
 enum MenuState {
 	TableSelect,
 	Lobby,
+	Settings,
+	League,
+	Profile,
+}
+
+/// A league's standings as the league screen shows them.
+struct LeagueView {
+	name: String,
+	played: usize,
+	scheduled: usize,
+	/// The next game's table and day.
+	next: Option<(String, String)>,
+	standings: Vec<LeagueStanding>,
+}
+
+impl LeagueView {
+	/// Every league's standings so far.
+	fn load(leagues: &Leagues) -> Result<Vec<LeagueView>, PokerError> {
+		let today = chrono::Local::now().date_naive();
+		leagues
+			.all()
+			.iter()
+			.map(|league| {
+				let results = leagues.results(&league.id)?;
+				Ok(LeagueView {
+					name: league.name.clone(),
+					played: results.len(),
+					scheduled: league.schedule.len(),
+					next: league.next_game(&results, today).map(|game| (game.table.clone(), game.date.clone())),
+					standings: league.standings(&results),
+				})
+			})
+			.collect()
+	}
 }
 
 pub struct Menu<B: LobbyBackend> {
@@ -92,14 +228,29 @@ pub struct Menu<B: LobbyBackend> {
 	max_players: usize,
 	players: Vec<LobbyPlayer>,
 	lobby_cursor: usize,
+	registration_closes: Option<Instant>,
 
 	theme: Theme,
+	theme_name: String,
+	prefs: Preferences,
+	settings_cursor: usize,
+	/// Where league results are read from; the usual places unless set.
+	leagues: Option<Leagues>,
+	league_views: Vec<LeagueView>,
+	league_cursor: usize,
+	/// Whose achievements the profile screen shows; the host's saved ones
+	/// unless set.
+	achievements: Option<Achievements>,
+	profile: Achievements,
 	show_info: bool,
 	error_message: Option<String>,
+	/// Say the screen as text lines instead of drawing it.
+	linear: bool,
+	last_spoken: Vec<String>,
 }
 
 impl<B: LobbyBackend> Menu<B> {
-	pub fn new(backend: B, host_id: String, theme: Theme) -> Self {
+	pub fn new(backend: B, host_id: String, theme: Theme, theme_name: String) -> Self {
 		let mut table_list_state = ListState::default();
 		table_list_state.select(Some(1)); // Start at 1 to skip header row
 
@@ -117,12 +268,60 @@ impl<B: LobbyBackend> Menu<B> {
 			max_players: 6,
 			players: Vec::new(),
 			lobby_cursor: 0,
+			registration_closes: None,
 			theme,
+			theme_name,
+			prefs: load_preferences(),
+			settings_cursor: 0,
+			leagues: None,
+			league_views: Vec::new(),
+			league_cursor: 0,
+			achievements: None,
+			profile: Achievements::default(),
 			show_info: false,
 			error_message: None,
+			linear: false,
+			last_spoken: Vec::new(),
 		}
 	}
 
+	/// Screen-reader output: see `tui::linear`.
+	pub fn with_linear(mut self, linear: bool) -> Self {
+		self.linear = linear;
+		self
+	}
+
+	/// Starts from these preferences instead of the saved ones.
+	pub fn with_preferences(mut self, prefs: Preferences) -> Self {
+		self.prefs = prefs;
+		self
+	}
+
+	/// Shows these leagues on the league screen instead of the saved ones.
+	pub fn with_leagues(mut self, leagues: Leagues) -> Self {
+		self.leagues = Some(leagues);
+		self
+	}
+
+	/// Shows these achievements on the profile screen instead of the
+	/// host's saved ones.
+	pub fn with_achievements(mut self, achievements: Achievements) -> Self {
+		self.achievements = Some(achievements);
+		self
+	}
+
+	/// Takes in whatever the backend has sent and draws the screen as
+	/// text; see `tui::snapshot`.
+	pub fn screen(&mut self, width: u16, height: u16) -> String {
+		let _ = self.process_events();
+		snapshot::render(width, height, |frame| self.draw(frame))
+	}
+
+	/// The theme in use, which may have been changed in the settings.
+	pub fn theme(&self) -> (&Theme, &str) {
+		(&self.theme, &self.theme_name)
+	}
+
 	pub fn into_backend(self) -> B {
 		self.backend
 	}
@@ -142,9 +341,10 @@ impl<B: LobbyBackend> Menu<B> {
 					self.min_players = min_players;
 					self.max_players = max_players;
 					self.lobby_cursor = self.players.len();
+					self.registration_closes = None;
 					self.state = MenuState::Lobby;
 				}
-				LobbyEvent::PlayerJoined { seat, username, is_ai } => {
+				LobbyEvent::PlayerJoined { seat, username, is_ai, bankroll } => {
 					self.players.push(LobbyPlayer {
 						seat: Some(seat),
 						id: username.to_lowercase(),
@@ -153,7 +353,7 @@ impl<B: LobbyBackend> Menu<B> {
 						is_human: !is_ai,
 						is_ready: is_ai,
 						strategy: None,
-						bankroll: None,
+						bankroll,
 					});
 				}
 				LobbyEvent::PlayerLeft { seat } => {
@@ -164,17 +364,21 @@ impl<B: LobbyBackend> Menu<B> {
 						p.is_ready = true;
 					}
 				}
+				LobbyEvent::RegistrationOpen { seconds } => {
+					self.registration_closes = Some(Instant::now() + Duration::from_secs(seconds as u64));
+				}
 				LobbyEvent::GameStarting => {}
 				LobbyEvent::NetworkGameStarted { seat, table_config, num_players } => {
 					return Some(MenuResult::NetworkGameStarted { seat, table_config, num_players });
 				}
-				LobbyEvent::Error(msg) => {
-					self.error_message = Some(msg);
+				LobbyEvent::Error(err) => {
+					self.error_message = Some(err.user_message());
 				}
 				LobbyEvent::LeftTable => {
 					self.current_table_id = None;
 					self.current_table_name.clear();
 					self.players.clear();
+					self.registration_closes = None;
 					self.state = MenuState::TableSelect;
 				}
 			}
@@ -283,91 +487,251 @@ impl<B: LobbyBackend> Menu<B> {
 				return Ok(result);
 			}
 
-			terminal.draw(|f| self.draw(f))?;
+			if self.linear {
+				let lines = self.describe();
+				if lines != self.last_spoken {
+					speak(&mut io::stdout(), &lines)?;
+					self.last_spoken = lines;
+				}
+			} else {
+				terminal.draw(|f| self.draw(f))?;
+			}
 
-			if event::poll(std::time::Duration::from_millis(50))? {
+			if event::poll(Duration::from_millis(50))? {
 				if let Event::Key(key) = event::read()? {
 					if key.kind == KeyEventKind::Press {
-						self.error_message = None;
-
-						if self.show_info {
-							self.show_info = false;
-							continue;
+						if let Some(result) = self.handle_key(key.code) {
+							return Ok(result);
 						}
+					}
+				}
+			}
+		}
+	}
 
-						match &self.state {
-							MenuState::TableSelect => {
-								match key.code {
-									KeyCode::Char('q') => {
-										return Ok(MenuResult::Quit);
-									}
-									KeyCode::Up => {
-										self.move_table_selection(-1);
-									}
-									KeyCode::Down => {
-										self.move_table_selection(1);
-									}
-									KeyCode::Left => {
-										self.cycle_sort_prev();
-									}
-									KeyCode::Right => {
-										self.cycle_sort_next();
-									}
-									KeyCode::Char('i') => {
-										self.show_info = true;
-									}
-									KeyCode::Enter => {
-										if let Some(idx) = self.selected_table_index() {
-											let table_id = self.tables[idx].id.clone();
-											self.backend.send(LobbyCommand::JoinTable(table_id));
-										}
-									}
-									_ => {}
-								}
-							}
-							MenuState::Lobby => {
-								match key.code {
-									KeyCode::Esc => {
-										self.backend.send(LobbyCommand::LeaveTable);
-									}
-									KeyCode::Char('q') => {
-										return Ok(MenuResult::Quit);
-									}
-									KeyCode::Up => {
-										if self.lobby_cursor > 0 {
-											self.lobby_cursor -= 1;
-										}
-									}
-									KeyCode::Down => {
-										let max = self.players.len();
-										if self.lobby_cursor < max {
-											self.lobby_cursor += 1;
-										}
-									}
-									KeyCode::Char(' ') | KeyCode::Char('a') => {
-										self.backend.send(LobbyCommand::AddAI);
-									}
-									KeyCode::Char('d') | KeyCode::Delete | KeyCode::Backspace => {
-										if let Some(player) = self.players.get(self.lobby_cursor) {
-											if !player.is_host && !player.is_human {
-												if let Some(seat) = player.seat {
-													self.backend.send(LobbyCommand::RemoveAI(seat));
-												}
-											}
-										}
-									}
-									KeyCode::Enter => {
-										if self.can_start() {
-											self.backend.send(LobbyCommand::Ready);
-										}
-									}
-									_ => {}
+	/// Acts on a key press as the screen showing takes it; `Some` once the
+	/// player has quit.
+	pub fn handle_key(&mut self, key: KeyCode) -> Option<MenuResult> {
+		self.error_message = None;
+
+		if self.show_info {
+			self.show_info = false;
+			return None;
+		}
+
+		match &self.state {
+			MenuState::TableSelect => {
+				match key {
+					KeyCode::Char('q') => {
+						return Some(MenuResult::Quit);
+					}
+					KeyCode::Up => {
+						self.move_table_selection(-1);
+					}
+					KeyCode::Down => {
+						self.move_table_selection(1);
+					}
+					KeyCode::Left => {
+						self.cycle_sort_prev();
+					}
+					KeyCode::Right => {
+						self.cycle_sort_next();
+					}
+					KeyCode::Char('i') => {
+						self.show_info = true;
+					}
+					KeyCode::Char('s') => {
+						self.settings_cursor = 0;
+						self.state = MenuState::Settings;
+					}
+					KeyCode::Char('l') => self.open_leagues(),
+					KeyCode::Char('p') => self.open_profile(),
+					KeyCode::Enter => {
+						if let Some(idx) = self.selected_table_index() {
+							let table_id = self.tables[idx].id.clone();
+							self.backend.send(LobbyCommand::JoinTable(table_id));
+						}
+					}
+					_ => {}
+				}
+			}
+			MenuState::Lobby => {
+				match key {
+					KeyCode::Esc => {
+						self.backend.send(LobbyCommand::LeaveTable);
+					}
+					KeyCode::Char('q') => {
+						return Some(MenuResult::Quit);
+					}
+					KeyCode::Up => {
+						if self.lobby_cursor > 0 {
+							self.lobby_cursor -= 1;
+						}
+					}
+					KeyCode::Down => {
+						let max = self.players.len();
+						if self.lobby_cursor < max {
+							self.lobby_cursor += 1;
+						}
+					}
+					KeyCode::Char(' ') | KeyCode::Char('a') => {
+						self.backend.send(LobbyCommand::AddAI);
+					}
+					KeyCode::Char('d') | KeyCode::Delete | KeyCode::Backspace => {
+						if let Some(player) = self.players.get(self.lobby_cursor) {
+							if !player.is_host && !player.is_human {
+								if let Some(seat) = player.seat {
+									self.backend.send(LobbyCommand::RemoveAI(seat));
 								}
 							}
 						}
 					}
+					KeyCode::Enter if self.can_start() => {
+						self.backend.send(LobbyCommand::Ready);
+					}
+					_ => {}
+				}
+			}
+			MenuState::Settings => {
+				match key {
+					KeyCode::Esc | KeyCode::Char('s') | KeyCode::Char('q') => {
+						if let Err(e) = save_preferences(&self.prefs) {
+							self.error_message = Some(e.user_message());
+						}
+						self.state = MenuState::TableSelect;
+					}
+					KeyCode::Up => {
+						self.settings_cursor = self.settings_cursor.saturating_sub(1);
+					}
+					KeyCode::Down => {
+						self.settings_cursor = (self.settings_cursor + 1).min(SETTINGS.len() - 1);
+					}
+					KeyCode::Left => self.change_setting(-1),
+					KeyCode::Right | KeyCode::Enter | KeyCode::Char(' ') => self.change_setting(1),
+					_ => {}
 				}
 			}
+			MenuState::League => {
+				let count = self.league_views.len().max(1);
+				match key {
+					KeyCode::Esc | KeyCode::Char('l') | KeyCode::Char('q') => self.state = MenuState::TableSelect,
+					KeyCode::Left => self.league_cursor = (self.league_cursor + count - 1) % count,
+					KeyCode::Right => self.league_cursor = (self.league_cursor + 1) % count,
+					_ => {}
+				}
+			}
+			MenuState::Profile => {
+				if matches!(key, KeyCode::Esc | KeyCode::Char('p') | KeyCode::Char('q')) {
+					self.state = MenuState::TableSelect;
+				}
+			}
+		}
+		None
+	}
+
+	/// Reads the host's achievements afresh and shows them.
+	fn open_profile(&mut self) {
+		self.profile = match &self.achievements {
+			Some(achievements) => achievements.clone(),
+			None => Achievements::load(&self.host_id).unwrap_or_else(|e| {
+				self.error_message = Some(e.user_message());
+				Achievements::default()
+			}),
+		};
+		self.state = MenuState::Profile;
+	}
+
+	/// Reads the leagues' results afresh and shows the first league.
+	fn open_leagues(&mut self) {
+		let views = match &self.leagues {
+			Some(leagues) => LeagueView::load(leagues),
+			None => Leagues::load().and_then(|leagues| LeagueView::load(&leagues)),
+		};
+		match views {
+			Ok(views) => self.league_views = views,
+			Err(e) => {
+				self.league_views.clear();
+				self.error_message = Some(e.user_message());
+			}
+		}
+		self.league_cursor = 0;
+		self.state = MenuState::League;
+	}
+
+	/// Steps the selected setting; the theme is previewed straight away.
+	fn change_setting(&mut self, direction: i32) {
+		match SETTINGS[self.settings_cursor] {
+			Setting::Theme => {
+				let available = Theme::list_available();
+				if available.is_empty() {
+					return;
+				}
+				let current = available.iter().position(|name| name == &self.theme_name).unwrap_or(0) as i32;
+				let next = &available[(current + direction).rem_euclid(available.len() as i32) as usize];
+				match Theme::load_named(next) {
+					Ok(theme) => {
+						self.theme = theme.with_palette(self.theme.palette);
+						self.theme_name = next.clone();
+						self.prefs.theme = Some(next.clone());
+					}
+					Err(e) => self.error_message = Some(e.user_message()),
+				}
+			}
+			Setting::Language => {
+				let available = i18n::available();
+				if available.is_empty() {
+					return;
+				}
+				let current = available.iter().position(|code| *code == i18n::locale()).unwrap_or(0) as i32;
+				let next = &available[(current + direction).rem_euclid(available.len() as i32) as usize];
+				match i18n::set_locale(next) {
+					Ok(()) => self.prefs.locale = Some(next.clone()),
+					Err(e) => self.error_message = Some(e.user_message()),
+				}
+			}
+			Setting::Currency => {
+				let current = (self.prefs.money.symbol.as_str(), self.prefs.money.symbol_after);
+				let (symbol, after) = cycle(&CURRENCIES, current, direction);
+				self.prefs.money.symbol = symbol.to_string();
+				self.prefs.money.symbol_after = after;
+				money::set_format(self.prefs.money.clone());
+			}
+			Setting::Thousands => {
+				self.prefs.money.thousands = cycle(&SEPARATORS, self.prefs.money.thousands.as_str(), direction).to_string();
+				money::set_format(self.prefs.money.clone());
+			}
+			Setting::TournamentChips => {
+				self.prefs.money.chips_in_tournaments = !self.prefs.money.chips_in_tournaments;
+				money::set_format(self.prefs.money.clone());
+			}
+			Setting::ActionDelay => self.prefs.action_delay_ms = cycle_delay(self.prefs.action_delay_ms, direction),
+			Setting::StreetDelay => self.prefs.street_delay_ms = cycle_delay(self.prefs.street_delay_ms, direction),
+			Setting::DealDelay => self.prefs.deal_delay_ms = cycle(&DEAL_CHOICES, self.prefs.deal_delay_ms, direction),
+			Setting::SkipShowdowns => self.prefs.skip_showdowns = !self.prefs.skip_showdowns,
+			Setting::AutoMuck => self.prefs.auto_muck = !self.prefs.auto_muck,
+			Setting::FastForwardFolded => self.prefs.fast_forward_folded = !self.prefs.fast_forward_folded,
+			Setting::BeginnerHints => self.prefs.beginner_hints = !self.prefs.beginner_hints,
+			Setting::CoachingTips => self.prefs.coaching_tips = !self.prefs.coaching_tips,
+		}
+	}
+
+	fn setting_value(&self, setting: Setting) -> String {
+		match setting {
+			Setting::Theme => self.theme_name.clone(),
+			Setting::Language => i18n::language_name(&i18n::locale()),
+			Setting::Currency | Setting::Thousands => money(1234567.0),
+			Setting::TournamentChips => on_off(self.prefs.money.chips_in_tournaments),
+			Setting::ActionDelay => delay_text(self.prefs.action_delay_ms),
+			Setting::StreetDelay => delay_text(self.prefs.street_delay_ms),
+			Setting::DealDelay => match self.prefs.deal_delay_ms {
+				None => tr("prompt.off"),
+				Some(ms) => tr_with("setting.delay_ms", &[("ms", &ms.to_string())]),
+			},
+			Setting::SkipShowdowns => on_off(self.prefs.skip_showdowns),
+			Setting::AutoMuck => on_off(self.prefs.auto_muck),
+			Setting::FastForwardFolded => on_off(self.prefs.fast_forward_folded),
+			Setting::BeginnerHints => on_off(self.prefs.beginner_hints),
+			Setting::CoachingTips => on_off(self.prefs.coaching_tips),
 		}
 	}
 
@@ -386,6 +750,9 @@ impl<B: LobbyBackend> Menu<B> {
 		match &self.state {
 			MenuState::TableSelect => self.draw_table_select(frame),
 			MenuState::Lobby => self.draw_lobby(frame),
+			MenuState::Settings => self.draw_settings(frame),
+			MenuState::League => self.draw_league(frame),
+			MenuState::Profile => self.draw_profile(frame),
 		}
 
 		if self.show_info {
@@ -410,7 +777,7 @@ impl<B: LobbyBackend> Menu<B> {
 
 		let host_bankroll = self.backend.get_bankroll(&self.host_id);
 		let player_info = if host_bankroll > 0.0 {
-			format!("Player: {}  Bankroll: ${:.0}", self.host_id, host_bankroll)
+			tr_with("menu.player_bankroll", &[("name", &self.host_id), ("amount", &money(host_bankroll))])
 		} else {
 			self.host_id.clone()
 		};
@@ -424,31 +791,31 @@ impl<B: LobbyBackend> Menu<B> {
 		// Column header
 		let header_line = Line::from(vec![
 			Span::styled(
-				format!("{:<24}", "Table"),
+				format!("{:<24}", tr("menu.column_table")),
 				Style::default().fg(self.theme.menu_title()).add_modifier(Modifier::BOLD),
 			),
 			Span::styled(
-				format!("{:<11}", "Status"),
+				format!("{:<11}", tr("menu.column_status")),
 				Style::default().fg(self.theme.menu_title()).add_modifier(Modifier::BOLD),
 			),
 			Span::styled(
-				format!("{:<6}", "Type"),
+				format!("{:<6}", tr("menu.column_type")),
 				Style::default().fg(self.theme.menu_title()).add_modifier(Modifier::BOLD),
 			),
 			Span::styled(
-				format!("{:<7}", "Limit"),
+				format!("{:<7}", tr("menu.column_limit")),
 				Style::default().fg(self.theme.menu_title()).add_modifier(Modifier::BOLD),
 			),
 			Span::styled(
-				format!("{:>7}", "Stakes"),
+				format!("{:>7}", tr("menu.column_stakes")),
 				Style::default().fg(self.theme.menu_title()).add_modifier(Modifier::BOLD),
 			),
 			Span::styled(
-				format!("{:>6}", "Seats"),
+				format!("{:>6}", tr("menu.column_seats")),
 				Style::default().fg(self.theme.menu_title()).add_modifier(Modifier::BOLD),
 			),
 			Span::styled(
-				format!("{:>8}", "Joinable"),
+				format!("{:>8}", tr("menu.column_joinable")),
 				Style::default().fg(self.theme.menu_title()).add_modifier(Modifier::BOLD),
 			),
 		]);
@@ -461,9 +828,9 @@ impl<B: LobbyBackend> Menu<B> {
 			.map(|&idx| {
 				let t = &self.tables[idx];
 				let (status_text, status_color) = match t.status {
-					TableStatus::Waiting => ("Open", self.theme.stack()),
-					TableStatus::InProgress => ("In Progress", self.theme.bet()),
-					TableStatus::Finished => ("Finished", self.theme.menu_unselected()),
+					TableStatus::Waiting => (tr("menu.open"), self.theme.stack()),
+					TableStatus::InProgress => (tr("menu.in_progress"), self.theme.bet()),
+					TableStatus::Finished => (tr("menu.finished"), self.theme.menu_unselected()),
 				};
 				let format_abbrev = match t.format.as_str() {
 					"Sit & Go" => "SnG",
@@ -476,9 +843,9 @@ impl<B: LobbyBackend> Menu<B> {
 					other => other,
 				};
 				let (joinable_text, joinable_color) = if t.is_joinable {
-					("Yes", self.theme.stack())
+					(tr("menu.yes"), self.theme.stack())
 				} else {
-					("No", self.theme.menu_unselected())
+					(tr("menu.no"), self.theme.menu_unselected())
 				};
 				let line = Line::from(vec![
 					Span::styled(
@@ -513,7 +880,7 @@ impl<B: LobbyBackend> Menu<B> {
 				ListItem::new(line)
 			}));
 
-		let title = format!(" SELECT TABLE (sort: {}) ", self.sort_mode.label());
+		let title = tr_with("menu.select_table", &[("sort", &self.sort_mode.label())]);
 		let list = List::new(items)
 			.block(
 				Block::default()
@@ -531,7 +898,7 @@ impl<B: LobbyBackend> Menu<B> {
 
 		frame.render_stateful_widget(list, chunks[1], &mut self.table_list_state.clone());
 
-		let help = Paragraph::new("  [↑/↓] Select  [←/→] Sort  [Enter] Open Lobby  [i] Info  [q] Quit")
+		let help = Paragraph::new(tr("menu.table_help"))
 			.style(Style::default().fg(self.theme.menu_unselected()))
 			.block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(self.theme.menu_border())));
 		frame.render_widget(help, chunks[2]);
@@ -553,9 +920,9 @@ impl<B: LobbyBackend> Menu<B> {
 			.split(area);
 
 		let header_text = if let Some(ref err) = self.error_message {
-			format!("  TABLE: {} - {}", self.current_table_name, err)
+			tr_with("menu.lobby_table_error", &[("name", &self.current_table_name), ("error", err)])
 		} else {
-			format!("  TABLE: {}", self.current_table_name)
+			tr_with("menu.lobby_table", &[("name", &self.current_table_name)])
 		};
 		let header_color = if self.error_message.is_some() {
 			self.theme.status_quit()
@@ -571,24 +938,24 @@ impl<B: LobbyBackend> Menu<B> {
 		let player_list = Paragraph::new(player_lines)
 			.block(
 				Block::default()
-					.title(format!(
-						" PLAYERS ({}/{}) ",
-						self.players.len(),
-						self.max_players
-					))
+					.title(tr_with("menu.players", &[
+						("count", &self.players.len().to_string()),
+						("max", &self.max_players.to_string()),
+					]))
 					.borders(Borders::ALL)
 					.border_style(Style::default().fg(self.theme.menu_border())),
 			);
 		frame.render_widget(player_list, chunks[1]);
 
 		let can_start = self.can_start();
-		let help_text = if can_start {
-			"  [Enter] Start game  [a] Add AI player  [d] Remove player  [Esc] Back  [q] Quit"
+		let help_text = if let Some(closes) = self.registration_closes {
+			let seconds = closes.saturating_duration_since(Instant::now()).as_secs();
+			tr_with("menu.lobby_starting", &[("seconds", &seconds.to_string())])
+		} else if can_start {
+			tr("menu.lobby_ready")
 		} else {
-			Box::leak(format!(
-				"  Need {} more players  [a] Add AI  [Esc] Back  [q] Quit",
-				self.min_players.saturating_sub(self.players.len())
-			).into_boxed_str())
+			let count = self.min_players.saturating_sub(self.players.len());
+			tr_with("menu.lobby_waiting", &[("count", &count.to_string())])
 		};
 		let help = Paragraph::new(help_text)
 			.style(Style::default().fg(self.theme.menu_unselected()))
@@ -596,13 +963,139 @@ impl<B: LobbyBackend> Menu<B> {
 		frame.render_widget(help, chunks[2]);
 	}
 
-	fn draw_info_popup(&self, frame: &mut Frame) {
-		let Some(idx) = self.selected_table_index() else {
-			return;
-		};
-		let table = &self.tables[idx];
+	/// The current screen as lines of plain text, for linear output.
+	fn describe(&self) -> Vec<String> {
+		let mut lines = Vec::new();
+		if let Some(err) = &self.error_message {
+			lines.push(format!("Error: {}", err));
+		}
+		if self.show_info {
+			if let Some(idx) = self.selected_table_index() {
+				lines.push(format!("Settings for {}:", self.tables[idx].name));
+				lines.extend(self.info_text(idx).lines().filter(|l| !l.is_empty()).map(str::to_string));
+				lines.push("Press any key to close.".to_string());
+			}
+			return lines;
+		}
+		match &self.state {
+			MenuState::TableSelect => match self.selected_table_index() {
+				Some(idx) => {
+					let t = &self.tables[idx];
+					let position = self.sorted_indices.iter().position(|&i| i == idx).unwrap_or(0);
+					let status = match t.status {
+						TableStatus::Waiting => "open",
+						TableStatus::InProgress => "in progress",
+						TableStatus::Finished => "finished",
+					};
+					lines.push(format!(
+						"Table {} of {}: {}, {}, {} {}, blinds {}, buy-in {}, {} of {} seats taken{}.",
+						position + 1,
+						self.sorted_indices.len(),
+						t.name,
+						status,
+						t.betting,
+						t.format,
+						t.blinds,
+						t.buy_in,
+						t.players,
+						t.max_players,
+						if t.is_joinable { "" } else { ", can't join" }
+					));
+					lines.push(format!(
+						"Sorted by {}. Up and Down to browse, Left and Right to sort, Enter to open, i for info, s for settings, l for leagues, p for your profile, q to quit.",
+						self.sort_mode.label().to_lowercase()
+					));
+				}
+				None => lines.push("Loading tables...".to_string()),
+			},
+			MenuState::Settings => {
+				let setting = SETTINGS[self.settings_cursor];
+				lines.push(format!(
+					"Settings, {} of {}: {}, {}.",
+					self.settings_cursor + 1,
+					SETTINGS.len(),
+					setting.label(),
+					self.setting_value(setting)
+				));
+				lines.push("Up and Down to choose, Left and Right to change, Escape to save and go back.".to_string());
+			}
+			MenuState::League => match self.league_views.get(self.league_cursor) {
+				Some(view) => {
+					lines.push(format!(
+						"League {} of {}: {}, {} of {} games played.",
+						self.league_cursor + 1,
+						self.league_views.len(),
+						view.name,
+						view.played,
+						view.scheduled
+					));
+					for (i, s) in view.standings.iter().enumerate() {
+						lines.push(format!("{}. {}, {} points, {} played, {} won.", i + 1, s.member, s.points, s.played, s.wins));
+					}
+					if let Some((table, date)) = &view.next {
+						lines.push(format!("Next game at {} on {}.", table, date));
+					}
+					lines.push("Left and Right for other leagues, Escape to go back.".to_string());
+				}
+				None => lines.push("No leagues yet; define one in leagues.toml. Escape to go back.".to_string()),
+			},
+			MenuState::Profile => {
+				lines.push(format!("Profile for {}. Achievements:", self.host_id));
+				for achievement in Achievement::ALL {
+					let key = achievement.key();
+					lines.push(format!(
+						"{}: {}, {}.",
+						tr(&format!("achievement.{}", key)),
+						tr(&format!("achievement.{}_about", key)),
+						self.achievement_status(achievement)
+					));
+				}
+				lines.push("Escape to go back.".to_string());
+			}
+			MenuState::Lobby => {
+				let players = self
+					.players
+					.iter()
+					.map(|p| {
+						let mut name = p.name.clone();
+						if p.is_host {
+							name.push_str(" (host)");
+						} else if !p.is_human {
+							name.push_str(" (AI)");
+						}
+						name
+					})
+					.collect::<Vec<_>>();
+				lines.push(format!(
+					"Lobby for {}, {} of {} seats: {}.",
+					self.current_table_name,
+					self.players.len(),
+					self.max_players,
+					players.join(", ")
+				));
+				let selected = match self.players.get(self.lobby_cursor) {
+					Some(player) => player.name.clone(),
+					None => "add player".to_string(),
+				};
+				lines.push(format!("Selected: {}.", selected));
+				lines.push(if self.registration_closes.is_some() {
+					"Starting soon; empty seats fill with AI. a to add AI, d to remove, Escape to go back, q to quit.".to_string()
+				} else if self.can_start() {
+					"Enter to start, a to add AI, d to remove, Escape to go back, q to quit.".to_string()
+				} else {
+					format!(
+						"Need {} more players. a to add AI, Escape to go back, q to quit.",
+						self.min_players.saturating_sub(self.players.len())
+					)
+				});
+			}
+		}
+		lines
+	}
 
-		let info_str = if let Some(config) = self.backend.table_config(&table.id) {
+	fn info_text(&self, idx: usize) -> String {
+		let table = &self.tables[idx];
+		if let Some(config) = self.backend.table_config(&table.id) {
 			match toml::to_string_pretty(&config) {
 				Ok(s) => s,
 				Err(_) => "Failed to serialize table config".to_string(),
@@ -612,7 +1105,234 @@ impl<B: LobbyBackend> Menu<B> {
 				"ID: {}\nFormat: {}\nBetting: {}\nBlinds: {}\nBuy-in: {}\nPlayers: {}/{}",
 				table.id, table.format, table.betting, table.blinds, table.buy_in, table.players, table.max_players
 			)
+		}
+	}
+
+	fn draw_settings(&self, frame: &mut Frame) {
+		let area = frame.area();
+
+		let bg = Block::default().style(Style::default().bg(self.theme.background()));
+		frame.render_widget(bg, area);
+
+		let chunks = Layout::default()
+			.direction(Direction::Vertical)
+			.constraints([
+				Constraint::Length(3),
+				Constraint::Min(10),
+				Constraint::Length(3),
+			])
+			.split(area);
+
+		let header_text = match &self.error_message {
+			Some(err) => tr_with("menu.settings_error", &[("error", err)]),
+			None => tr("menu.settings"),
+		};
+		let header = Paragraph::new(header_text)
+			.style(Style::default().fg(self.theme.menu_title()).add_modifier(Modifier::BOLD))
+			.block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(self.theme.menu_border())));
+		frame.render_widget(header, chunks[0]);
+
+		let lines = SETTINGS
+			.iter()
+			.enumerate()
+			.map(|(i, &setting)| {
+				let selected = i == self.settings_cursor;
+				let (cursor, label_style) = if selected {
+					("> ", Style::default().fg(self.theme.menu_selected()).add_modifier(Modifier::BOLD))
+				} else {
+					("  ", Style::default().fg(self.theme.menu_text()))
+				};
+				Line::from(vec![
+					Span::styled(format!("{}{:<32}", cursor, setting.label()), label_style),
+					Span::styled(
+						format!("< {} >", self.setting_value(setting)),
+						Style::default().fg(self.theme.menu_highlight()),
+					),
+				])
+			})
+			.collect::<Vec<_>>();
+		let list = Paragraph::new(lines)
+			.block(
+				Block::default()
+					.title(tr("menu.settings_note"))
+					.borders(Borders::ALL)
+					.border_style(Style::default().fg(self.theme.menu_border())),
+			);
+		frame.render_widget(list, chunks[1]);
+
+		let help = Paragraph::new(tr("menu.settings_help"))
+			.style(Style::default().fg(self.theme.menu_unselected()))
+			.block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(self.theme.menu_border())));
+		frame.render_widget(help, chunks[2]);
+	}
+
+	fn draw_league(&self, frame: &mut Frame) {
+		let area = frame.area();
+
+		let bg = Block::default().style(Style::default().bg(self.theme.background()));
+		frame.render_widget(bg, area);
+
+		let chunks = Layout::default()
+			.direction(Direction::Vertical)
+			.constraints([
+				Constraint::Length(3),
+				Constraint::Min(10),
+				Constraint::Length(3),
+			])
+			.split(area);
+
+		let view = self.league_views.get(self.league_cursor);
+		let header_text = match (&self.error_message, view) {
+			(Some(err), _) => tr_with("menu.league_error", &[("error", err)]),
+			(None, Some(view)) => tr_with("menu.league_title", &[
+				("name", &view.name),
+				("played", &view.played.to_string()),
+				("scheduled", &view.scheduled.to_string()),
+			]),
+			(None, None) => tr("menu.league"),
+		};
+		let header = Paragraph::new(header_text)
+			.style(Style::default().fg(self.theme.menu_title()).add_modifier(Modifier::BOLD))
+			.block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(self.theme.menu_border())));
+		frame.render_widget(header, chunks[0]);
+
+		let heading = Style::default().fg(self.theme.menu_title()).add_modifier(Modifier::BOLD);
+		let mut lines = Vec::new();
+		match view {
+			None => lines.push(Line::from(Span::styled(tr("menu.league_none"), Style::default().fg(self.theme.menu_unselected())))),
+			Some(view) => {
+				lines.push(Line::from(vec![
+					Span::styled(format!("{:>4}  ", ""), heading),
+					Span::styled(format!("{:<20}", tr("menu.column_player")), heading),
+					Span::styled(format!("{:>8}", tr("menu.column_points")), heading),
+					Span::styled(format!("{:>8}", tr("menu.column_played")), heading),
+					Span::styled(format!("{:>8}", tr("menu.column_won")), heading),
+				]));
+				for (i, standing) in view.standings.iter().enumerate() {
+					let name_color = if standing.member.eq_ignore_ascii_case(&self.host_id) {
+						self.theme.menu_host_marker()
+					} else {
+						self.theme.menu_text()
+					};
+					lines.push(Line::from(vec![
+						Span::styled(format!("{:>4}  ", i + 1), Style::default().fg(self.theme.menu_unselected())),
+						Span::styled(format!("{:<20}", truncate_str(&standing.member, 19)), Style::default().fg(name_color)),
+						Span::styled(format!("{:>8}", standing.points), Style::default().fg(self.theme.menu_highlight())),
+						Span::styled(format!("{:>8}", standing.played), Style::default().fg(self.theme.menu_unselected())),
+						Span::styled(format!("{:>8}", standing.wins), Style::default().fg(self.theme.menu_unselected())),
+					]));
+				}
+				lines.push(Line::from(""));
+				let next = match &view.next {
+					Some((table, date)) => tr_with("menu.league_next", &[("table", table), ("date", date)]),
+					None => tr("menu.league_over"),
+				};
+				lines.push(Line::from(Span::styled(format!("  {}", next), Style::default().fg(self.theme.menu_unselected()))));
+			}
+		}
+		let title = tr_with("menu.league_standings", &[
+			("index", &(self.league_cursor + 1).to_string()),
+			("count", &self.league_views.len().to_string()),
+		]);
+		let list = Paragraph::new(lines)
+			.block(
+				Block::default()
+					.title(title)
+					.borders(Borders::ALL)
+					.border_style(Style::default().fg(self.theme.menu_border())),
+			);
+		frame.render_widget(list, chunks[1]);
+
+		let help = Paragraph::new(tr("menu.league_help"))
+			.style(Style::default().fg(self.theme.menu_unselected()))
+			.block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(self.theme.menu_border())));
+		frame.render_widget(help, chunks[2]);
+	}
+
+	/// What the profile screen says about `achievement`: when it was
+	/// unlocked, or how far along it is.
+	fn achievement_status(&self, achievement: Achievement) -> String {
+		match (self.profile.unlocked_on(achievement), self.profile.progress(achievement)) {
+			(Some(date), _) => tr_with("menu.achievement_unlocked_on", &[("date", date)]),
+			(None, Some((done, needed))) => tr_with("menu.achievement_progress", &[
+				("done", &done.to_string()),
+				("needed", &needed.to_string()),
+			]),
+			(None, None) => tr("menu.achievement_locked"),
+		}
+	}
+
+	fn draw_profile(&self, frame: &mut Frame) {
+		let area = frame.area();
+
+		let bg = Block::default().style(Style::default().bg(self.theme.background()));
+		frame.render_widget(bg, area);
+
+		let chunks = Layout::default()
+			.direction(Direction::Vertical)
+			.constraints([
+				Constraint::Length(3),
+				Constraint::Min(10),
+				Constraint::Length(3),
+			])
+			.split(area);
+
+		let header_text = match &self.error_message {
+			Some(err) => tr_with("menu.profile_error", &[("name", &self.host_id), ("error", err)]),
+			None => tr_with("menu.profile", &[("name", &self.host_id)]),
+		};
+		let header = Paragraph::new(header_text)
+			.style(Style::default().fg(self.theme.menu_title()).add_modifier(Modifier::BOLD))
+			.block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(self.theme.menu_border())));
+		frame.render_widget(header, chunks[0]);
+
+		let mut lines = Vec::new();
+		for achievement in Achievement::ALL {
+			let unlocked = self.profile.unlocked_on(achievement).is_some();
+			let (mark, name_color) = if unlocked {
+				("★ ", self.theme.menu_highlight())
+			} else {
+				("☆ ", self.theme.menu_unselected())
+			};
+			let key = achievement.key();
+			lines.push(Line::from(vec![
+				Span::styled(format!("  {}", mark), Style::default().fg(name_color)),
+				Span::styled(
+					format!("{:<24}", tr(&format!("achievement.{}", key))),
+					Style::default().fg(name_color).add_modifier(Modifier::BOLD),
+				),
+				Span::styled(
+					format!("{:<52}", tr(&format!("achievement.{}_about", key))),
+					Style::default().fg(self.theme.menu_text()),
+				),
+				Span::styled(self.achievement_status(achievement), Style::default().fg(self.theme.menu_unselected())),
+			]));
+		}
+		let unlocked = Achievement::ALL.iter().filter(|&&a| self.profile.unlocked_on(a).is_some()).count();
+		let list = Paragraph::new(lines)
+			.block(
+				Block::default()
+					.title(tr_with("menu.achievements", &[
+						("count", &unlocked.to_string()),
+						("total", &Achievement::ALL.len().to_string()),
+					]))
+					.borders(Borders::ALL)
+					.border_style(Style::default().fg(self.theme.menu_border())),
+			);
+		frame.render_widget(list, chunks[1]);
+
+		let help = Paragraph::new(tr("menu.profile_help"))
+			.style(Style::default().fg(self.theme.menu_unselected()))
+			.block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(self.theme.menu_border())));
+		frame.render_widget(help, chunks[2]);
+	}
+
+	fn draw_info_popup(&self, frame: &mut Frame) {
+		let Some(idx) = self.selected_table_index() else {
+			return;
 		};
+		let table = &self.tables[idx];
+		let info_str = self.info_text(idx);
 
 		let area = frame.area();
 		let popup_width = (area.width * 2 / 3).min(60);
@@ -645,7 +1365,7 @@ impl<B: LobbyBackend> Menu<B> {
 			let ready_tag = if player.is_ready { " ✓" } else { "" };
 
 			let bankroll_str = if let Some(br) = player.bankroll {
-				format!("${:.0}", br)
+				money(br)
 			} else {
 				String::new()
 			};
@@ -678,7 +1398,7 @@ impl<B: LobbyBackend> Menu<B> {
 				"  "
 			};
 			lines.push(Line::from(vec![Span::styled(
-				format!("{}+ Add player...", cursor),
+				format!("{}{}", cursor, tr("menu.add_player")),
 				Style::default().fg(self.theme.menu_unselected()),
 			)]));
 		}
@@ -687,8 +1407,306 @@ impl<B: LobbyBackend> Menu<B> {
 	}
 }
 
+/// How long each browse for servers listens before the list is refreshed.
+const BROWSE_TIME: Duration = Duration::from_secs(2);
+
+/// The "Discover servers" screen: lists the servers advertising on the
+/// local network as they answer, and returns the address of the one
+/// picked, or `None` on quit.
+pub fn pick_server<Back: Backend>(terminal: &mut Terminal<Back>, theme: &Theme, linear: bool) -> io::Result<Option<String>> {
+	flush_keyboard_buffer();
+
+	let (tx, rx) = std::sync::mpsc::channel();
+	std::thread::spawn(move || loop {
+		let found = discovery::discover(BROWSE_TIME).map_err(|e| e.user_message());
+		if tx.send(found).is_err() {
+			break;
+		}
+	});
+
+	let mut servers: Vec<DiscoveredServer> = Vec::new();
+	let mut searched = false;
+	let mut error = None;
+	let mut list_state = ListState::default();
+	let mut last_spoken = Vec::new();
+
+	loop {
+		while let Ok(found) = rx.try_recv() {
+			searched = true;
+			match found {
+				Ok(found) => {
+					let selected = list_state.selected().and_then(|i| servers.get(i)).map(|s| s.addr.clone());
+					servers = found;
+					error = None;
+					let index = selected
+						.and_then(|addr| servers.iter().position(|s| s.addr == addr))
+						.or(if servers.is_empty() { None } else { Some(0) });
+					list_state.select(index);
+				}
+				Err(e) => error = Some(e),
+			}
+		}
+
+		if linear {
+			let lines = describe_servers(&servers, list_state.selected(), searched, error.as_deref());
+			if lines != last_spoken {
+				speak(&mut io::stdout(), &lines)?;
+				last_spoken = lines;
+			}
+		} else {
+			terminal.draw(|f| draw_servers(f, theme, &servers, &mut list_state, searched, error.as_deref()))?;
+		}
+
+		if event::poll(Duration::from_millis(50))? {
+			if let Event::Key(key) = event::read()? {
+				if key.kind != KeyEventKind::Press {
+					continue;
+				}
+				let selected = list_state.selected().unwrap_or(0);
+				match key.code {
+					KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+					KeyCode::Up if selected > 0 => list_state.select(Some(selected - 1)),
+					KeyCode::Down if selected + 1 < servers.len() => list_state.select(Some(selected + 1)),
+					KeyCode::Enter => {
+						if let Some(server) = list_state.selected().and_then(|i| servers.get(i)) {
+							return Ok(Some(server.addr.clone()));
+						}
+					}
+					_ => {}
+				}
+			}
+		}
+	}
+}
+
+fn draw_servers(
+	frame: &mut Frame,
+	theme: &Theme,
+	servers: &[DiscoveredServer],
+	list_state: &mut ListState,
+	searched: bool,
+	error: Option<&str>,
+) {
+	let area = frame.area();
+	frame.render_widget(Block::default().style(Style::default().bg(theme.background())), area);
+
+	let chunks = Layout::default()
+		.direction(Direction::Vertical)
+		.constraints([
+			Constraint::Length(3),
+			Constraint::Min(6),
+			Constraint::Length(3),
+		])
+		.split(area);
+
+	let (header_text, header_color) = match error {
+		Some(e) => (format!("  {}", e), theme.status_quit()),
+		None if !searched => (tr("discover.searching"), theme.menu_title()),
+		None if servers.is_empty() => (tr("discover.none"), theme.menu_title()),
+		None => (tr_with("discover.found", &[("count", &servers.len().to_string())]), theme.menu_title()),
+	};
+	let header = Paragraph::new(header_text)
+		.style(Style::default().fg(header_color).add_modifier(Modifier::BOLD))
+		.block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.menu_border())));
+	frame.render_widget(header, chunks[0]);
+
+	let bold = Style::default().fg(theme.menu_title()).add_modifier(Modifier::BOLD);
+	let columns = Line::from(vec![
+		Span::styled(format!("  {:<28}", tr("discover.column_name")), bold),
+		Span::styled(format!("{:<24}", tr("discover.column_address")), bold),
+		Span::styled(format!("{:>8}", tr("discover.column_tables")), bold),
+		Span::styled(format!("{:>9}", tr("discover.column_players")), bold),
+	]);
+	let items = servers
+		.iter()
+		.map(|s| {
+			ListItem::new(Line::from(vec![
+				Span::styled(format!("{:<28}", truncate_str(&s.name, 27)), Style::default().fg(theme.menu_text())),
+				Span::styled(format!("{:<24}", s.addr), Style::default().fg(theme.menu_unselected())),
+				Span::styled(format!("{:>8}", s.tables), Style::default().fg(theme.menu_highlight())),
+				Span::styled(format!("{:>9}", s.players), Style::default().fg(theme.menu_highlight())),
+			]))
+		})
+		.collect::<Vec<_>>();
+
+	let block = Block::default()
+		.title(tr("discover.title"))
+		.borders(Borders::ALL)
+		.border_style(Style::default().fg(theme.menu_border()));
+	let inner = block.inner(chunks[1]);
+	frame.render_widget(block, chunks[1]);
+	let rows = Layout::default()
+		.direction(Direction::Vertical)
+		.constraints([Constraint::Length(1), Constraint::Min(1)])
+		.split(inner);
+	frame.render_widget(Paragraph::new(columns), rows[0]);
+	let list = List::new(items)
+		.highlight_style(
+			Style::default()
+				.fg(theme.menu_selected())
+				.bg(theme.menu_selected_bg())
+				.add_modifier(Modifier::BOLD),
+		)
+		.highlight_symbol("> ");
+	frame.render_stateful_widget(list, rows[1], list_state);
+
+	let help = Paragraph::new(tr("discover.help"))
+		.style(Style::default().fg(theme.menu_unselected()))
+		.block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.menu_border())));
+	frame.render_widget(help, chunks[2]);
+}
+
+/// The "Discover servers" screen as lines of plain text, for linear output.
+fn describe_servers(servers: &[DiscoveredServer], selected: Option<usize>, searched: bool, error: Option<&str>) -> Vec<String> {
+	if let Some(e) = error {
+		return vec![format!("Error: {}", e)];
+	}
+	if !searched {
+		return vec![tr("discover.searching").trim().to_string()];
+	}
+	let Some(s) = selected.and_then(|i| servers.get(i)) else {
+		return vec![tr("discover.none").trim().to_string()];
+	};
+	vec![
+		format!(
+			"Server {} of {}: {} at {}, {} tables, {} players online.",
+			selected.unwrap_or(0) + 1,
+			servers.len(),
+			s.name,
+			s.addr,
+			s.tables,
+			s.players
+		),
+		"Up and down to choose, Enter to connect, q to quit.".to_string(),
+	]
+}
+
+/// The profile picker `poker play` shows when no player is named: the
+/// profiles played before, and a new one typed in with `n`. Returns the
+/// name picked, or `None` on quit.
+pub fn pick_profile<Back: Backend>(terminal: &mut Terminal<Back>, theme: &Theme, linear: bool, profiles: &[String]) -> io::Result<Option<String>> {
+	flush_keyboard_buffer();
+
+	let mut list_state = ListState::default();
+	list_state.select(if profiles.is_empty() { None } else { Some(0) });
+	// A name being typed for a new profile
+	let mut typing: Option<String> = if profiles.is_empty() { Some(String::new()) } else { None };
+	let mut last_spoken = Vec::new();
+
+	loop {
+		if linear {
+			let lines = describe_profiles(profiles, list_state.selected(), typing.as_deref());
+			if lines != last_spoken {
+				speak(&mut io::stdout(), &lines)?;
+				last_spoken = lines;
+			}
+		} else {
+			terminal.draw(|f| draw_profiles(f, theme, profiles, &mut list_state, typing.as_deref()))?;
+		}
+
+		if event::poll(Duration::from_millis(50))? {
+			if let Event::Key(key) = event::read()? {
+				if key.kind != KeyEventKind::Press {
+					continue;
+				}
+				if let Some(name) = typing.as_mut() {
+					match key.code {
+						KeyCode::Esc if profiles.is_empty() => return Ok(None),
+						KeyCode::Esc => typing = None,
+						KeyCode::Enter if crate::config::profile_key(name).is_ok() => return Ok(Some(name.trim().to_string())),
+						KeyCode::Backspace => {
+							name.pop();
+						}
+						KeyCode::Char(c) if name.chars().count() < 24 => name.push(c),
+						_ => {}
+					}
+					continue;
+				}
+				let selected = list_state.selected().unwrap_or(0);
+				match key.code {
+					KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+					KeyCode::Char('n') => typing = Some(String::new()),
+					KeyCode::Up if selected > 0 => list_state.select(Some(selected - 1)),
+					KeyCode::Down if selected + 1 < profiles.len() => list_state.select(Some(selected + 1)),
+					KeyCode::Enter => {
+						if let Some(profile) = list_state.selected().and_then(|i| profiles.get(i)) {
+							return Ok(Some(profile.clone()));
+						}
+					}
+					_ => {}
+				}
+			}
+		}
+	}
+}
+
+fn draw_profiles(frame: &mut Frame, theme: &Theme, profiles: &[String], list_state: &mut ListState, typing: Option<&str>) {
+	let area = frame.area();
+	frame.render_widget(Block::default().style(Style::default().bg(theme.background())), area);
+
+	let chunks = Layout::default()
+		.direction(Direction::Vertical)
+		.constraints([
+			Constraint::Length(3),
+			Constraint::Min(6),
+			Constraint::Length(3),
+		])
+		.split(area);
+
+	let header_text = match typing {
+		Some(name) => format!("{}{}_", tr("profile.new_name"), name),
+		None => tr("profile.header"),
+	};
+	let header = Paragraph::new(header_text)
+		.style(Style::default().fg(theme.menu_title()).add_modifier(Modifier::BOLD))
+		.block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.menu_border())));
+	frame.render_widget(header, chunks[0]);
+
+	let items = profiles
+		.iter()
+		.map(|profile| ListItem::new(Span::styled(profile.clone(), Style::default().fg(theme.menu_text()))))
+		.collect::<Vec<_>>();
+	let list = List::new(items)
+		.block(
+			Block::default()
+				.title(tr("profile.title"))
+				.borders(Borders::ALL)
+				.border_style(Style::default().fg(theme.menu_border())),
+		)
+		.highlight_style(
+			Style::default()
+				.fg(theme.menu_selected())
+				.bg(theme.menu_selected_bg())
+				.add_modifier(Modifier::BOLD),
+		)
+		.highlight_symbol("> ");
+	frame.render_stateful_widget(list, chunks[1], list_state);
+
+	let help = Paragraph::new(if typing.is_some() { tr("profile.help_typing") } else { tr("profile.help") })
+		.style(Style::default().fg(theme.menu_unselected()))
+		.block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.menu_border())));
+	frame.render_widget(help, chunks[2]);
+}
+
+/// The profile picker as lines of plain text, for linear output.
+fn describe_profiles(profiles: &[String], selected: Option<usize>, typing: Option<&str>) -> Vec<String> {
+	if let Some(name) = typing {
+		return vec![
+			format!("New profile name: {}", name),
+			"Type a name, Enter to play as it, Escape to go back.".to_string(),
+		];
+	}
+	let Some(profile) = selected.and_then(|i| profiles.get(i)) else {
+		return vec!["No profiles yet.".to_string()];
+	};
+	vec![
+		format!("Profile {} of {}: {}.", selected.unwrap_or(0) + 1, profiles.len(), profile),
+		"Up and down to choose, Enter to play, n for a new profile, q to quit.".to_string(),
+	]
+}
+
 fn flush_keyboard_buffer() {
-	while event::poll(std::time::Duration::from_millis(0)).unwrap_or(false) {
+	while event::poll(Duration::from_millis(0)).unwrap_or(false) {
 		let _ = event::read();
 	}
 }
@@ -707,3 +1725,18 @@ fn truncate_str(s: &str, max_len: usize) -> String {
 		format!("{}…", &s[..end])
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_delay_choices_cycle_both_ways() {
+		assert_eq!(cycle_delay(None, 1), Some(0));
+		assert_eq!(cycle_delay(None, -1), Some(2000));
+		assert_eq!(cycle_delay(Some(2000), 1), None);
+		assert_eq!(cycle_delay(Some(333), 1), Some(0));
+		assert_eq!(delay_text(None), "table default");
+		assert_eq!(delay_text(Some(250)), "250 ms");
+	}
+}
@@ -0,0 +1,172 @@
+//! How amounts are written: the currency symbol and where it goes, the
+//! thousands separator, and whether tournament stacks show as chips
+//! rather than money. The format comes from `preferences.toml`; the
+//! defaults write `$1500`, as the game always has.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MoneyFormat {
+	/// Written before the amount, or after it with `symbol_after`.
+	pub symbol: String,
+	pub symbol_after: bool,
+	/// Between each group of three digits; empty for none.
+	pub thousands: String,
+	/// Show stacks, bets and pots in tournaments as plain chip counts.
+	/// Buy-ins and prizes are still money.
+	pub chips_in_tournaments: bool,
+}
+
+impl Default for MoneyFormat {
+	fn default() -> Self {
+		Self {
+			symbol: "$".to_string(),
+			symbol_after: false,
+			thousands: String::new(),
+			chips_in_tournaments: false,
+		}
+	}
+}
+
+impl MoneyFormat {
+	/// `amount` as money, rounded to a whole number.
+	pub fn money(&self, amount: f32) -> String {
+		let sign = if amount.round() < 0.0 { "-" } else { "" };
+		self.with_symbol(sign, &self.number(amount.abs()))
+	}
+
+	/// `amount` as money to the cent, for amounts too small to round,
+	/// like rakeback. The decimal point is a comma if the thousands
+	/// separator is a full stop.
+	pub fn cents(&self, amount: f32) -> String {
+		let cents = (amount.abs() * 100.0).round() as u64;
+		let sign = if amount < 0.0 && cents > 0 { "-" } else { "" };
+		let point = if self.thousands == "." { "," } else { "." };
+		let number = format!("{}{}{:02}", self.number((cents / 100) as f32), point, cents % 100);
+		self.with_symbol(sign, &number)
+	}
+
+	fn with_symbol(&self, sign: &str, number: &str) -> String {
+		if self.symbol_after {
+			format!("{}{} {}", sign, number, self.symbol).trim_end().to_string()
+		} else {
+			format!("{}{}{}", sign, self.symbol, number)
+		}
+	}
+
+	/// `amount` as it sits on the table: chips in a tournament if
+	/// `chips_in_tournaments` is on, money otherwise.
+	pub fn chips(&self, amount: f32, tournament: bool) -> String {
+		if tournament && self.chips_in_tournaments {
+			let sign = if amount.round() < 0.0 { "-" } else { "" };
+			format!("{}{}", sign, self.number(amount.abs()))
+		} else {
+			self.money(amount)
+		}
+	}
+
+	fn number(&self, amount: f32) -> String {
+		let digits = format!("{:.0}", amount);
+		if self.thousands.is_empty() {
+			return digits;
+		}
+		let mut grouped = String::new();
+		for (i, digit) in digits.chars().enumerate() {
+			if i > 0 && (digits.len() - i) % 3 == 0 {
+				grouped.push_str(&self.thousands);
+			}
+			grouped.push(digit);
+		}
+		grouped
+	}
+}
+
+static FORMAT: RwLock<Option<MoneyFormat>> = RwLock::new(None);
+static TOURNAMENT: AtomicBool = AtomicBool::new(false);
+
+/// Sets the format used from now on.
+pub fn set_format(format: MoneyFormat) {
+	*FORMAT.write().unwrap_or_else(|e| e.into_inner()) = Some(format);
+}
+
+/// The format in use.
+pub fn format() -> MoneyFormat {
+	FORMAT.read().unwrap_or_else(|e| e.into_inner()).clone().unwrap_or_default()
+}
+
+/// Tells `chips` whether the table being played or watched is a
+/// tournament.
+pub fn set_tournament(tournament: bool) {
+	TOURNAMENT.store(tournament, Ordering::Relaxed);
+}
+
+/// Bankrolls, buy-ins and prizes.
+pub fn money(amount: f32) -> String {
+	format().money(amount)
+}
+
+/// Money to the cent.
+pub fn cents(amount: f32) -> String {
+	format().cents(amount)
+}
+
+/// Stacks, bets and pots at the current table.
+pub fn chips(amount: f32) -> String {
+	format().chips(amount, TOURNAMENT.load(Ordering::Relaxed))
+}
+
+/// Stacks, bets and pots at a table other than the current one.
+pub fn chips_at(amount: f32, tournament: bool) -> String {
+	format().chips(amount, tournament)
+}
+
+/// `amount` as a count of big blinds, like "23.5 BB".
+pub fn big_blinds(amount: f32, big_blind: f32) -> String {
+	let count = format!("{:.1}", amount / big_blind);
+	format!("{} BB", count.strip_suffix(".0").unwrap_or(&count))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_default_format_is_unchanged() {
+		let format = MoneyFormat::default();
+		assert_eq!(format.money(1500.0), "$1500");
+		assert_eq!(format.money(-20.4), "-$20");
+		assert_eq!(format.chips(1500.0, true), "$1500");
+		assert_eq!(format.cents(0.5), "$0.50");
+		assert_eq!(format.cents(-12.345), "-$12.35");
+	}
+
+	#[test]
+	fn test_separators_symbol_placement_and_chips() {
+		let format = MoneyFormat {
+			symbol: "€".to_string(),
+			symbol_after: true,
+			thousands: ".".to_string(),
+			chips_in_tournaments: true,
+		};
+		assert_eq!(format.money(1234567.0), "1.234.567 €");
+		assert_eq!(format.money(999.0), "999 €");
+		assert_eq!(format.money(-1000.0), "-1.000 €");
+		assert_eq!(format.chips(15000.0, true), "15.000");
+		assert_eq!(format.chips(15000.0, false), "15.000 €");
+		assert_eq!(format.cents(1234.5), "1.234,50 €");
+
+		let bare = MoneyFormat { symbol: String::new(), symbol_after: true, ..MoneyFormat::default() };
+		assert_eq!(bare.money(40.0), "40");
+	}
+
+	#[test]
+	fn test_big_blinds() {
+		assert_eq!(big_blinds(470.0, 20.0), "23.5 BB");
+		assert_eq!(big_blinds(800.0, 20.0), "40 BB");
+		assert_eq!(big_blinds(5.0, 20.0), "0.2 BB");
+	}
+}
@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use std::io::{self, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::engine::{GameRunner, RunnerConfig};
+use crate::events::redaction::{redact, Audience};
+use crate::events::{GameEvent, Seat, ValidActions};
+use crate::net::protocol::{ArenaMessage, ArenaRequest, ArenaTableInfo};
+use crate::players::{read_bot_line, BotLink, GameSnapshot, PlayerPort, PlayerResponse, MAX_BOT_LINE};
+use crate::table::{load_tables, TableConfig};
+
+/// Per-decision clock for tables that don't set `action_timeout_seconds`.
+const DEFAULT_ARENA_TIMEOUT_SECS: u64 = 5;
+/// Matches end after this many hands unless `with_max_hands` says otherwise.
+pub const DEFAULT_ARENA_HANDS: u32 = 500;
+const MAX_BOT_NAME_LENGTH: usize = 32;
+
+fn lock_tables(tables: &Mutex<HashMap<String, ArenaTable>>) -> MutexGuard<'_, HashMap<String, ArenaTable>> {
+	tables.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+struct WaitingBot {
+	name: String,
+	link: BotLink,
+}
+
+struct ArenaTable {
+	config: TableConfig,
+	order: usize,
+	waiting: Vec<WaitingBot>,
+}
+
+impl ArenaTable {
+	fn to_info(&self) -> ArenaTableInfo {
+		ArenaTableInfo {
+			id: self.config.id.clone(),
+			name: self.config.name.clone(),
+			betting: self.config.betting.to_string(),
+			blinds: self.config.blinds_label(),
+			seats: self.config.max_players,
+			waiting: self.waiting.len(),
+		}
+	}
+}
+
+/// A server where only bots play. Bots connect over TCP, pick a table, and
+/// a match starts as soon as the table's `max_players` seats are taken.
+/// Matches run side by side, so a table keeps taking registrations while
+/// earlier matches are still playing.
+///
+/// The handshake is line-delimited JSON (`ArenaRequest` in,
+/// `ArenaMessage` out). From `game_start` on, the bot gets the seat's
+/// filtered event stream and `action_request` lines exactly like a
+/// `SubprocessPlayer`, and answers each request with a `PlayerAction`.
+/// After `game_over` the server closes the connection.
+pub struct BotArena {
+	tables: Arc<Mutex<HashMap<String, ArenaTable>>>,
+	max_hands: Option<u32>,
+}
+
+impl BotArena {
+	pub fn new(tables: Vec<TableConfig>) -> Self {
+		let tables = tables
+			.into_iter()
+			.enumerate()
+			.map(|(order, config)| {
+				let id = config.id.clone();
+				(id, ArenaTable { config, order, waiting: Vec::new() })
+			})
+			.collect();
+		Self {
+			tables: Arc::new(Mutex::new(tables)),
+			max_hands: Some(DEFAULT_ARENA_HANDS),
+		}
+	}
+
+	/// An arena over the tables in `tables.toml`.
+	pub fn from_config() -> Self {
+		Self::new(load_tables().unwrap_or_default())
+	}
+
+	pub fn with_max_hands(mut self, max: Option<u32>) -> Self {
+		self.max_hands = max;
+		self
+	}
+
+	pub fn run(&self, addr: &str) -> io::Result<()> {
+		let listener = TcpListener::bind(addr)?;
+		println!("Bot arena listening on {}", addr);
+		self.run_with_listener(listener);
+		Ok(())
+	}
+
+	pub fn run_with_listener(&self, listener: TcpListener) {
+		for stream in listener.incoming() {
+			match stream {
+				Ok(stream) => {
+					let tables = Arc::clone(&self.tables);
+					let max_hands = self.max_hands;
+					thread::spawn(move || handle_bot(stream, tables, max_hands));
+				}
+				Err(e) => {
+					eprintln!("Connection failed: {}", e);
+				}
+			}
+		}
+	}
+}
+
+fn send_line(stream: &mut TcpStream, msg: &ArenaMessage) -> io::Result<()> {
+	let json = serde_json::to_string(msg).expect("Failed to serialize message - this is a bug");
+	writeln!(stream, "{}", json)
+}
+
+fn table_list(tables: &HashMap<String, ArenaTable>) -> Vec<ArenaTableInfo> {
+	let mut list: Vec<&ArenaTable> = tables.values().collect();
+	list.sort_by_key(|t| t.order);
+	list.into_iter().map(ArenaTable::to_info).collect()
+}
+
+/// Runs the handshake. Once the bot registers, its link owns the socket
+/// and this thread is done.
+fn handle_bot(stream: TcpStream, tables: Arc<Mutex<HashMap<String, ArenaTable>>>, max_hands: Option<u32>) {
+	let Ok(mut writer) = stream.try_clone() else {
+		return;
+	};
+	let mut reader = BufReader::new(stream);
+	let mut line = String::new();
+
+	loop {
+		line.clear();
+		match read_bot_line(&mut reader, &mut line) {
+			Ok(0) => return,
+			Ok(_) => {}
+			Err(e) => {
+				if e.kind() == io::ErrorKind::InvalidData {
+					let message = format!("Line too long (max {} bytes)", MAX_BOT_LINE);
+					let _ = send_line(&mut writer, &ArenaMessage::Error { message });
+				}
+				return;
+			}
+		}
+		if line.trim().is_empty() {
+			continue;
+		}
+
+		let request = match serde_json::from_str::<ArenaRequest>(line.trim()) {
+			Ok(request) => request,
+			Err(e) => {
+				let message = format!("Invalid request: {}", e);
+				if send_line(&mut writer, &ArenaMessage::Error { message }).is_err() {
+					return;
+				}
+				continue;
+			}
+		};
+
+		match request {
+			ArenaRequest::ListTables => {
+				let tables = table_list(&lock_tables(&tables));
+				if send_line(&mut writer, &ArenaMessage::Tables { tables }).is_err() {
+					return;
+				}
+			}
+			ArenaRequest::Register { name, table } => {
+				let name = name.trim().to_string();
+				let error = if name.is_empty() || name.len() > MAX_BOT_NAME_LENGTH {
+					Some(format!("Name must be 1-{} characters", MAX_BOT_NAME_LENGTH))
+				} else if !lock_tables(&tables).contains_key(&table) {
+					Some(format!("No table '{}'", table))
+				} else {
+					None
+				};
+				if let Some(message) = error {
+					if send_line(&mut writer, &ArenaMessage::Error { message }).is_err() {
+						return;
+					}
+					continue;
+				}
+
+				let link = BotLink::new(format!("Arena:{}", name), reader, ClosingStream(writer));
+				register(&tables, &table, WaitingBot { name, link }, max_hands);
+				return;
+			}
+		}
+	}
+}
+
+fn register(tables: &Mutex<HashMap<String, ArenaTable>>, table_id: &str, bot: WaitingBot, max_hands: Option<u32>) {
+	let mut tables = lock_tables(tables);
+	let Some(table) = tables.get_mut(table_id) else {
+		return;
+	};
+
+	// Bots that hung up while waiting give their seat back
+	table.waiting.retain(|b| b.link.is_alive());
+	table.waiting.push(bot);
+
+	let seats = table.config.max_players;
+	let waiting = table.waiting.len();
+	if let Some(bot) = table.waiting.last() {
+		bot.link.send(&ArenaMessage::Registered {
+			table: table_id.to_string(),
+			waiting,
+			seats,
+		});
+	}
+
+	if waiting >= seats {
+		let bots: Vec<WaitingBot> = table.waiting.drain(..seats).collect();
+		start_match(&table.config, bots, max_hands);
+	}
+}
+
+fn start_match(config: &TableConfig, bots: Vec<WaitingBot>, max_hands: Option<u32>) {
+	let runtime = tokio::runtime::Builder::new_multi_thread()
+		.enable_all()
+		.build()
+		.expect("Failed to create tokio runtime for match");
+
+	let mut runner_config = RunnerConfig::from_table(config);
+	runner_config.max_hands = max_hands;
+	runner_config
+		.action_timeout
+		.get_or_insert(Duration::from_secs(DEFAULT_ARENA_TIMEOUT_SECS));
+
+	let (mut runner, game_handle) = GameRunner::new(runner_config, runtime.handle().clone());
+
+	let names: Vec<String> = bots.iter().map(|b| b.name.clone()).collect();
+	let seated: Vec<Arc<ArenaBot>> = bots
+		.into_iter()
+		.enumerate()
+		.map(|(i, bot)| {
+			Arc::new(ArenaBot {
+				seat: Seat(i),
+				name: bot.name,
+				link: bot.link,
+			})
+		})
+		.collect();
+
+	for bot in &seated {
+		bot.link.send(&ArenaMessage::GameStart {
+			table: config.id.clone(),
+			seat: bot.seat,
+			players: names.clone(),
+		});
+		runner.add_player(Arc::clone(bot) as Arc<dyn PlayerPort>);
+	}
+
+	thread::spawn(move || {
+		let _rt_guard = runtime.enter();
+		runner.run();
+	});
+
+	thread::spawn(move || {
+		while let Ok(event) = game_handle.event_rx.recv() {
+			for bot in &seated {
+				if let Some(seen) = redact(&event, Audience::Player(bot.seat)) {
+					bot.link.notify(&seen);
+				}
+			}
+			if let GameEvent::GameEnded { final_standings, .. } = &event {
+				for bot in &seated {
+					bot.link.send(&ArenaMessage::GameOver {
+						standings: final_standings.clone(),
+					});
+				}
+				break;
+			}
+		}
+	});
+}
+
+/// A registered bot's seat in a match. Events reach it from the match's
+/// event stream rather than `notify`, so it sees everything its seat may.
+struct ArenaBot {
+	seat: Seat,
+	name: String,
+	link: BotLink,
+}
+
+#[async_trait]
+impl PlayerPort for ArenaBot {
+	async fn request_action(
+		&self,
+		seat: Seat,
+		valid_actions: ValidActions,
+		game_state: &GameSnapshot,
+	) -> PlayerResponse {
+		// The table's action timeout bounds the wait
+		self.link.request_action(seat, &valid_actions, game_state, None).await
+	}
+
+	fn notify(&self, _event: &GameEvent) {}
+
+	fn seat(&self) -> Seat {
+		self.seat
+	}
+
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	fn is_human(&self) -> bool {
+		false
+	}
+}
+
+/// The write half of a bot's socket. When the link stops writing, after
+/// `game_over` has gone out, the whole connection is closed.
+struct ClosingStream(TcpStream);
+
+impl Write for ClosingStream {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0.write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.0.flush()
+	}
+}
+
+impl Drop for ClosingStream {
+	fn drop(&mut self) {
+		let _ = self.0.shutdown(Shutdown::Both);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::{BufRead, Read};
+
+	fn heads_up() -> TableConfig {
+		toml::from_str(
+			r#"
+			id = "duel"
+			name = "Duel"
+			format = "sit-n-go"
+			betting = "no-limit"
+			small_blind = 5.0
+			big_blind = 10.0
+			starting_stack = 200.0
+			min_players = 2
+			max_players = 2
+			"#,
+		)
+		.unwrap()
+	}
+
+	fn start_arena() -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = listener.local_addr().unwrap().to_string();
+		let arena = BotArena::new(vec![heads_up()]).with_max_hands(Some(3));
+		thread::spawn(move || arena.run_with_listener(listener));
+		addr
+	}
+
+	/// Registers, answers every request with `action`, and returns the
+	/// message types it saw.
+	fn play_bot(addr: &str, name: &str, action: &str) -> Vec<String> {
+		let mut stream = TcpStream::connect(addr).unwrap();
+		stream.set_read_timeout(Some(Duration::from_secs(20))).unwrap();
+		let register = format!(r#"{{"type":"register","name":"{}","table":"duel"}}"#, name);
+		writeln!(stream, "{}", register).unwrap();
+
+		let mut seen = Vec::new();
+		let mut reader = BufReader::new(stream.try_clone().unwrap());
+		let mut line = String::new();
+		while reader.read_line(&mut line).unwrap_or(0) > 0 {
+			let msg: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+			let kind = msg["type"].as_str().unwrap_or_default().to_string();
+			if kind == "action_request" {
+				writeln!(stream, "{}", action).unwrap();
+			}
+			seen.push(kind);
+			line.clear();
+		}
+		seen
+	}
+
+	#[test]
+	fn test_match_starts_when_table_fills() {
+		let addr = start_arena();
+		let first = {
+			let addr = addr.clone();
+			thread::spawn(move || play_bot(&addr, "Caller", r#"{"Call":{"amount":0}}"#))
+		};
+		let second = play_bot(&addr, "Checker", r#""Check""#);
+		let first = first.join().unwrap();
+
+		for seen in [&first, &second] {
+			assert_eq!(seen.first().map(String::as_str), Some("registered"));
+			assert!(seen.contains(&"game_start".to_string()));
+			assert!(seen.contains(&"action_request".to_string()));
+			assert_eq!(seen.last().map(String::as_str), Some("game_over"));
+		}
+	}
+
+	#[test]
+	fn test_unknown_table_is_an_error() {
+		let addr = start_arena();
+		let mut stream = TcpStream::connect(&addr).unwrap();
+		stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+		writeln!(stream, r#"{{"type":"register","name":"Bot","table":"nope"}}"#).unwrap();
+		writeln!(stream, r#"{{"type":"list_tables"}}"#).unwrap();
+		stream.shutdown(Shutdown::Write).unwrap();
+
+		let mut replies = String::new();
+		stream.read_to_string(&mut replies).unwrap();
+		let kinds: Vec<String> = replies
+			.lines()
+			.map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap()["type"].to_string())
+			.collect();
+		assert_eq!(kinds, vec!["\"error\"", "\"tables\""]);
+	}
+
+	#[test]
+	fn test_endless_line_is_cut_off() {
+		let addr = start_arena();
+		let mut stream = TcpStream::connect(&addr).unwrap();
+		stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+		// No newline, ever: the arena hangs up rather than keep buffering
+		let junk = vec![b'x'; MAX_BOT_LINE + 1];
+		let _ = stream.write_all(&junk);
+
+		let mut replies = String::new();
+		let _ = stream.read_to_string(&mut replies);
+		let reply: serde_json::Value = serde_json::from_str(replies.trim()).unwrap();
+		assert_eq!(reply["type"], "error");
+		assert!(reply["message"].as_str().unwrap().contains("too long"));
+	}
+}
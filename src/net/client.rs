@@ -1,34 +1,204 @@
 use std::io::{Read, Write};
-use std::net::TcpStream;
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::net::protocol::*;
 
+/// Tries at getting a dropped connection back before giving up.
+const RECONNECT_ATTEMPTS: u32 = 8;
+/// The wait before the first try; it doubles each time after.
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(500);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(8);
+/// How often `ping_if_due` measures the round trip.
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+	Connected,
+	/// The connection dropped; this is try `attempt` of `RECONNECT_ATTEMPTS`
+	/// to get it back.
+	Reconnecting { attempt: u32 },
+	/// Every try failed.
+	Lost,
+}
+
+/// Where the server is, kept for reconnecting.
+#[derive(Debug, Clone)]
+enum Endpoint {
+	Direct(String),
+	Relay { relay: String, room: String },
+}
+
+impl Endpoint {
+	fn open(&self) -> std::io::Result<TcpStream> {
+		match self {
+			Endpoint::Direct(addr) => TcpStream::connect(addr),
+			Endpoint::Relay { relay, room } => crate::net::relay::join(relay, room),
+		}
+	}
+}
+
+/// What the server knew about us, to restore after reconnecting: who we
+/// logged in as and the table we're at.
+#[derive(Debug, Clone, Default)]
+struct Session {
+	username: Option<String>,
+	table: Option<String>,
+}
+
+impl Session {
+	fn follow(&mut self, msg: &ServerMessage) {
+		match msg {
+			ServerMessage::Welcome { username, .. } => self.username = Some(username.clone()),
+			ServerMessage::TableJoined { table_id, .. } | ServerMessage::TableChanged { table_id, .. } => {
+				self.table = Some(table_id.clone());
+			}
+			ServerMessage::TableLeft => self.table = None,
+			_ => {}
+		}
+	}
+}
+
+/// Round trips to the server, timed from `Ping` to `Pong`.
+#[derive(Debug, Default)]
+struct Latency {
+	last_id: u64,
+	/// The ping waiting for its pong, and when it went.
+	pending: Option<(u64, Instant)>,
+	last_sent: Option<Instant>,
+	rtt: Option<Duration>,
+}
+
+impl Latency {
+	fn pong(&mut self, id: u64, now: Instant) {
+		let Some((_, sent_at)) = self.pending.filter(|(sent_id, _)| *sent_id == id) else {
+			return;
+		};
+		self.pending = None;
+		let sample = now.duration_since(sent_at);
+		// Smoothed like TCP's estimate, so one slow pong doesn't jump it
+		self.rtt = Some(match self.rtt {
+			Some(rtt) => (rtt * 7 + sample) / 8,
+			None => sample,
+		});
+	}
+}
+
+/// When an answer to an `ActionRequest` that arrived at `received` has to
+/// leave to beat the server's `time_limit`. The server's clock started
+/// half a round trip before the request got here, and the answer takes the
+/// other half to get back, so a whole round trip comes off.
+fn action_deadline(received: Instant, time_limit: u32, rtt: Option<Duration>) -> Instant {
+	let end = received + Duration::from_secs(time_limit.into());
+	end.checked_sub(rtt.unwrap_or_default()).filter(|t| *t > received).unwrap_or(received)
+}
+
+/// Shared between the client and its read loop, which swaps in the new
+/// stream when it reconnects.
+struct Link {
+	stream: Mutex<TcpStream>,
+	state: Mutex<ConnectionState>,
+	session: Mutex<Session>,
+	latency: Mutex<Latency>,
+	/// Set when the client is dropped, so the read loop doesn't reconnect.
+	closed: AtomicBool,
+}
+
+impl Link {
+	fn stream(&self) -> MutexGuard<'_, TcpStream> {
+		self.stream.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn set_state(&self, state: ConnectionState) {
+		*self.state.lock().unwrap_or_else(|e| e.into_inner()) = state;
+	}
+
+	fn session(&self) -> MutexGuard<'_, Session> {
+		self.session.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn latency(&self) -> MutexGuard<'_, Latency> {
+		self.latency.lock().unwrap_or_else(|e| e.into_inner())
+	}
+}
+
 pub struct GameClient {
-	stream: TcpStream,
+	/// Shared with the read loop, which reports protocol errors back and
+	/// reconnects when the connection drops.
+	link: Arc<Link>,
 	rx: Receiver<ServerMessage>,
 }
 
 impl GameClient {
 	pub fn connect(addr: &str) -> std::io::Result<Self> {
-		let stream = TcpStream::connect(addr)?;
+		Self::open(Endpoint::Direct(addr.to_string()))
+	}
+
+	/// Joins a host's room on a relay (see `net::relay`).
+	pub fn connect_relayed(relay_addr: &str, room: &str) -> std::io::Result<Self> {
+		Self::open(Endpoint::Relay { relay: relay_addr.to_string(), room: room.to_string() })
+	}
+
+	fn open(endpoint: Endpoint) -> std::io::Result<Self> {
+		let stream = endpoint.open()?;
 		stream.set_read_timeout(Some(Duration::from_millis(100)))?;
 
 		let reader = stream.try_clone()?;
+		let link = Arc::new(Link {
+			stream: Mutex::new(stream),
+			state: Mutex::new(ConnectionState::Connected),
+			session: Mutex::default(),
+			latency: Mutex::default(),
+			closed: AtomicBool::new(false),
+		});
 		let (tx, rx) = mpsc::channel();
 
+		let loop_link = Arc::clone(&link);
 		thread::spawn(move || {
-			read_loop(reader, tx);
+			read_loop(reader, endpoint, loop_link, tx);
 		});
 
-		Ok(Self { stream, rx })
+		Ok(Self { link, rx })
+	}
+
+	pub fn connection_state(&self) -> ConnectionState {
+		*self.link.state.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	/// The smoothed round trip to the server, once a ping has come back.
+	pub fn latency(&self) -> Option<Duration> {
+		self.link.latency().rtt
+	}
+
+	/// Pings the server if `PING_INTERVAL` has passed since the last ping;
+	/// the read loop times the pong. Call it as often as you like.
+	pub fn ping_if_due(&mut self) -> std::io::Result<()> {
+		let id = {
+			let mut latency = self.link.latency();
+			let now = Instant::now();
+			if latency.last_sent.is_some_and(|sent| now.duration_since(sent) < PING_INTERVAL) {
+				return Ok(());
+			}
+			latency.last_id += 1;
+			latency.pending = Some((latency.last_id, now));
+			latency.last_sent = Some(now);
+			latency.last_id
+		};
+		self.send(&ClientMessage::Ping { id })
+	}
+
+	/// When to have answered an `ActionRequest` with `time_limit` seconds
+	/// that has just arrived, so the answer reaches the server in time.
+	pub fn action_deadline(&self, time_limit: u32) -> Instant {
+		action_deadline(Instant::now(), time_limit, self.latency())
 	}
 
 	pub fn send(&mut self, msg: &ClientMessage) -> std::io::Result<()> {
-		let data = encode_message(msg);
-		self.stream.write_all(&data)
+		send_on(&self.link, msg)
 	}
 
 	pub fn try_recv(&self) -> Option<ServerMessage> {
@@ -85,23 +255,83 @@ impl GameClient {
 		})
 	}
 
+	pub fn fast_forward(&mut self, enabled: bool) -> std::io::Result<()> {
+		self.send(&ClientMessage::FastForward { enabled })
+	}
+
+	pub fn auto_muck(&mut self, enabled: bool) -> std::io::Result<()> {
+		self.send(&ClientMessage::AutoMuck { enabled })
+	}
+
+	pub fn pacing(&mut self, action_delay_ms: Option<u64>, street_delay_ms: Option<u64>) -> std::io::Result<()> {
+		self.send(&ClientMessage::Pacing { action_delay_ms, street_delay_ms })
+	}
+
+	pub fn change_table(&mut self) -> std::io::Result<()> {
+		self.send(&ClientMessage::ChangeTable)
+	}
+
+	pub fn pause(&mut self, paused: bool) -> std::io::Result<()> {
+		self.send(&ClientMessage::Pause { paused })
+	}
+
 	pub fn drain(&self) {
 		while self.rx.try_recv().is_ok() {}
 	}
 }
 
-fn read_loop(mut reader: TcpStream, tx: Sender<ServerMessage>) {
+impl Drop for GameClient {
+	fn drop(&mut self) {
+		self.link.closed.store(true, Ordering::SeqCst);
+		let _ = self.link.stream().shutdown(Shutdown::Both);
+	}
+}
+
+fn send_on(link: &Link, msg: &ClientMessage) -> std::io::Result<()> {
+	let data = encode_message(msg);
+	link.stream().write_all(&data)
+}
+
+/// Reads until the connection drops, then reconnects and carries on, until
+/// the client is dropped or reconnecting fails.
+fn read_loop(mut reader: TcpStream, endpoint: Endpoint, link: Arc<Link>, tx: Sender<ServerMessage>) {
+	loop {
+		if !read_until_closed(&mut reader, &link, &tx) || link.closed.load(Ordering::SeqCst) {
+			return;
+		}
+		crate::logging::log("Client", "WARN", "Connection to the server dropped; reconnecting");
+		match reconnect(&endpoint, &link) {
+			Some(stream) => reader = stream,
+			None => return,
+		}
+	}
+}
+
+/// Passes messages on until the stream closes. False if nobody is
+/// listening any more.
+fn read_until_closed(reader: &mut TcpStream, link: &Link, tx: &Sender<ServerMessage>) -> bool {
 	let mut buf = vec![0u8; 4096];
 	let mut pending = Vec::new();
 
 	loop {
 		match reader.read(&mut buf) {
-			Ok(0) => break,
+			Ok(0) => return true,
 			Ok(n) => {
 				pending.extend_from_slice(&buf[..n]);
-				while let Some(msg) = try_decode_message(&mut pending) {
-					if tx.send(msg).is_err() {
-						return;
+				while let Some(decoded) = try_decode_message(&mut pending) {
+					match decoded {
+						Ok(ServerMessage::Pong { id }) => link.latency().pong(id, Instant::now()),
+						Ok(msg) => {
+							link.session().follow(&msg);
+							if tx.send(msg).is_err() {
+								return false;
+							}
+						}
+						// Tell the server, rather than lose its message quietly
+						Err(reason) => {
+							crate::logging::log("Client", "PROTOCOL", &reason);
+							let _ = send_on(link, &ClientMessage::ProtocolError { reason });
+						}
 					}
 				}
 			}
@@ -111,20 +341,167 @@ fn read_loop(mut reader: TcpStream, tx: Sender<ServerMessage>) {
 			Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
 				continue;
 			}
-			Err(_) => break,
+			Err(_) => return true,
+		}
+	}
+}
+
+/// How long to wait before try `attempt`.
+fn backoff(attempt: u32) -> Duration {
+	RECONNECT_BASE_DELAY.saturating_mul(1 << (attempt - 1).min(16)).min(RECONNECT_MAX_DELAY)
+}
+
+/// Tries to get the connection back, then logs in again and rejoins the
+/// table, where the server hands back a held seat. Returns the new stream
+/// to read from.
+fn reconnect(endpoint: &Endpoint, link: &Link) -> Option<TcpStream> {
+	for attempt in 1..=RECONNECT_ATTEMPTS {
+		link.set_state(ConnectionState::Reconnecting { attempt });
+		thread::sleep(backoff(attempt));
+		if link.closed.load(Ordering::SeqCst) {
+			return None;
+		}
+		let Ok(stream) = endpoint.open() else {
+			continue;
+		};
+		let Ok(reader) = stream.set_read_timeout(Some(Duration::from_millis(100))).and_then(|_| stream.try_clone()) else {
+			continue;
+		};
+		*link.stream() = stream;
+
+		let session = link.session().clone();
+		let resumed = session.username.map_or(Ok(()), |username| {
+			send_on(link, &ClientMessage::Login { username })?;
+			match session.table {
+				Some(table_id) => send_on(link, &ClientMessage::JoinTable { table_id }),
+				None => Ok(()),
+			}
+		});
+		if resumed.is_ok() {
+			crate::logging::log("Client", "INFO", &format!("Reconnected after {} tries", attempt));
+			link.set_state(ConnectionState::Connected);
+			return Some(reader);
 		}
 	}
+	crate::logging::log("Client", "ERROR", "Couldn't reconnect to the server");
+	link.set_state(ConnectionState::Lost);
+	None
 }
 
-fn try_decode_message(buf: &mut Vec<u8>) -> Option<ServerMessage> {
-	if buf.len() < 4 {
-		return None;
+fn try_decode_message(buf: &mut Vec<u8>) -> Option<Result<ServerMessage, String>> {
+	decode_frame(buf, MAX_FRAME_LEN)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::net::TcpListener;
+
+	#[test]
+	fn test_backoff_doubles_up_to_the_cap() {
+		assert_eq!(backoff(1), Duration::from_millis(500));
+		assert_eq!(backoff(2), Duration::from_secs(1));
+		assert_eq!(backoff(4), Duration::from_secs(4));
+		assert_eq!(backoff(RECONNECT_ATTEMPTS), RECONNECT_MAX_DELAY);
 	}
-	let len = decode_length(buf)? as usize;
-	if buf.len() < 4 + len {
-		return None;
+
+	#[test]
+	fn test_action_deadline_allows_for_the_round_trip() {
+		let now = Instant::now();
+		assert_eq!(action_deadline(now, 30, None), now + Duration::from_secs(30));
+		assert_eq!(action_deadline(now, 30, Some(Duration::from_millis(200))), now + Duration::from_millis(29_800));
+		assert_eq!(action_deadline(now, 1, Some(Duration::from_secs(3))), now);
+	}
+
+	#[test]
+	fn test_latency_is_smoothed_and_ignores_stale_pongs() {
+		let start = Instant::now();
+		let mut latency = Latency { pending: Some((1, start)), ..Latency::default() };
+		latency.pong(1, start + Duration::from_millis(80));
+		assert_eq!(latency.rtt, Some(Duration::from_millis(80)));
+
+		latency.pending = Some((2, start));
+		latency.pong(1, start + Duration::from_secs(5));
+		assert_eq!(latency.rtt, Some(Duration::from_millis(80)));
+		latency.pong(2, start + Duration::from_millis(160));
+		assert_eq!(latency.rtt, Some(Duration::from_millis(90)));
+	}
+
+	/// The next message a test server gets from its client. `pending` keeps
+	/// what came in the same read as it for the next call.
+	fn read_client_message(stream: &mut TcpStream, pending: &mut Vec<u8>) -> ClientMessage {
+		let mut buf = [0u8; 1024];
+		loop {
+			if let Some(decoded) = decode_frame(pending, MAX_FRAME_LEN) {
+				return decoded.unwrap();
+			}
+			let n = stream.read(&mut buf).unwrap();
+			assert!(n > 0, "client hung up");
+			pending.extend_from_slice(&buf[..n]);
+		}
+	}
+
+	#[test]
+	fn test_dropped_connection_resumes_the_session() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let mut client = GameClient::connect(&listener.local_addr().unwrap().to_string()).unwrap();
+
+		let (mut first, _) = listener.accept().unwrap();
+		client.login("Ann").unwrap();
+		assert!(matches!(read_client_message(&mut first, &mut Vec::new()), ClientMessage::Login { .. }));
+		first.write_all(&encode_message(&ServerMessage::Welcome {
+			username: "Ann".into(),
+			message: String::new(),
+			bankroll: 100.0,
+		})).unwrap();
+		first.write_all(&encode_message(&ServerMessage::TableJoined {
+			table_id: "home".into(),
+			table_name: "Home".into(),
+			seat: crate::events::Seat(2),
+			players: Vec::new(),
+			min_players: 2,
+			max_players: 6,
+		})).unwrap();
+		assert!(client.recv_timeout(Duration::from_secs(2)).is_some());
+		assert!(client.recv_timeout(Duration::from_secs(2)).is_some());
+		assert_eq!(client.connection_state(), ConnectionState::Connected);
+
+		first.shutdown(Shutdown::Both).unwrap();
+		drop(first);
+		let (mut second, _) = listener.accept().unwrap();
+		let mut pending = Vec::new();
+		assert!(matches!(read_client_message(&mut second, &mut pending), ClientMessage::Login { username } if username == "Ann"));
+		assert!(matches!(read_client_message(&mut second, &mut pending), ClientMessage::JoinTable { table_id } if table_id == "home"));
+		for _ in 0..20 {
+			if client.connection_state() == ConnectionState::Connected {
+				break;
+			}
+			thread::sleep(Duration::from_millis(50));
+		}
+		assert_eq!(client.connection_state(), ConnectionState::Connected);
+	}
+
+	#[test]
+	fn test_pong_measures_latency() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let mut client = GameClient::connect(&listener.local_addr().unwrap().to_string()).unwrap();
+		let (mut server, _) = listener.accept().unwrap();
+		assert_eq!(client.latency(), None);
+
+		client.ping_if_due().unwrap();
+		client.ping_if_due().unwrap();
+		let mut pending = Vec::new();
+		let ClientMessage::Ping { id } = read_client_message(&mut server, &mut pending) else {
+			panic!("expected a ping");
+		};
+		// The second wasn't due yet
+		server.set_read_timeout(Some(Duration::from_millis(100))).unwrap();
+		assert!(pending.is_empty() && server.read(&mut [0u8; 16]).is_err());
+		server.write_all(&encode_message(&ServerMessage::Pong { id })).unwrap();
+		server.write_all(&encode_message(&ServerMessage::TableLeft)).unwrap();
+
+		// The pong is kept by the client; the next message comes through
+		assert!(matches!(client.recv_timeout(Duration::from_secs(2)), Some(ServerMessage::TableLeft)));
+		assert!(client.latency().is_some());
 	}
-	let json = String::from_utf8_lossy(&buf[4..4 + len]).to_string();
-	buf.drain(..4 + len);
-	serde_json::from_str(&json).ok()
 }
@@ -0,0 +1,164 @@
+//! Finding servers on the local network. `poker serve` advertises itself
+//! over mDNS as `_transparent-poker._tcp`, with its name, open tables and
+//! players online in the TXT record; `poker play --discover` browses for
+//! them so nobody has to type an address.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+
+use crate::error::PokerError;
+
+pub const SERVICE_TYPE: &str = "_transparent-poker._tcp.local.";
+
+/// A server that answered a browse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredServer {
+	pub name: String,
+	/// Where to connect, like `192.168.1.20:9999`.
+	pub addr: String,
+	pub tables: usize,
+	pub players: usize,
+}
+
+impl DiscoveredServer {
+	fn from_txt(addr: String, fallback_name: &str, txt: &HashMap<String, String>) -> Self {
+		let count = |key: &str| txt.get(key).and_then(|v| v.parse().ok()).unwrap_or(0);
+		Self {
+			name: txt.get("name").filter(|n| !n.is_empty()).cloned().unwrap_or_else(|| fallback_name.to_string()),
+			addr,
+			tables: count("tables"),
+			players: count("players"),
+		}
+	}
+}
+
+fn txt(name: &str, tables: usize, players: usize) -> [(&'static str, String); 3] {
+	[("name", name.to_string()), ("tables", tables.to_string()), ("players", players.to_string())]
+}
+
+/// A server's mDNS record, kept up for as long as this lives.
+pub struct Advertisement {
+	daemon: ServiceDaemon,
+	name: String,
+	instance: String,
+	port: u16,
+	fullname: String,
+	last: (usize, usize),
+}
+
+impl Advertisement {
+	pub fn start(name: &str, port: u16) -> Result<Self, PokerError> {
+		let daemon = ServiceDaemon::new().map_err(|e| PokerError::Net(format!("Failed to start mDNS: {}", e)))?;
+		// Instance names are unique per network, so the port tells apart
+		// two servers given the same name
+		let instance = format!("{} ({})", name, port);
+		let mut ad = Self { daemon, name: name.to_string(), instance, port, fullname: String::new(), last: (0, 0) };
+		ad.register(0, 0)?;
+		Ok(ad)
+	}
+
+	/// Re-announces the record if the counts changed.
+	pub fn update(&mut self, tables: usize, players: usize) -> Result<(), PokerError> {
+		if self.last == (tables, players) {
+			return Ok(());
+		}
+		self.register(tables, players)
+	}
+
+	fn register(&mut self, tables: usize, players: usize) -> Result<(), PokerError> {
+		let host = format!("{}.local.", host_label(&self.name));
+		let info = ServiceInfo::new(SERVICE_TYPE, &self.instance, &host, "", self.port, &txt(&self.name, tables, players)[..])
+			.map_err(|e| PokerError::Net(format!("Bad mDNS record: {}", e)))?
+			.enable_addr_auto();
+		self.fullname = info.get_fullname().to_string();
+		self.daemon.register(info).map_err(|e| PokerError::Net(format!("Failed to advertise: {}", e)))?;
+		self.last = (tables, players);
+		Ok(())
+	}
+}
+
+impl Drop for Advertisement {
+	fn drop(&mut self) {
+		let _ = self.daemon.unregister(&self.fullname);
+		let _ = self.daemon.shutdown();
+	}
+}
+
+/// A DNS label for the host record: letters, digits and dashes.
+fn host_label(name: &str) -> String {
+	let label = name
+		.chars()
+		.map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+		.collect::<String>();
+	let label = label.trim_matches('-');
+	if label.is_empty() { "poker".to_string() } else { label.to_string() }
+}
+
+/// Browses for `wait` and returns every server that answered, by name.
+pub fn discover(wait: Duration) -> Result<Vec<DiscoveredServer>, PokerError> {
+	let daemon = ServiceDaemon::new().map_err(|e| PokerError::Net(format!("Failed to start mDNS: {}", e)))?;
+	let events = daemon.browse(SERVICE_TYPE).map_err(|e| PokerError::Net(format!("Failed to browse: {}", e)))?;
+	let deadline = Instant::now() + wait;
+	let mut found: HashMap<String, DiscoveredServer> = HashMap::new();
+
+	while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+		match events.recv_timeout(left) {
+			Ok(ServiceEvent::ServiceResolved(info)) => {
+				let Some(ip) = preferred_address(info.get_addresses().iter().copied()) else {
+					continue;
+				};
+				let addr = match ip {
+					IpAddr::V4(v4) => format!("{}:{}", v4, info.get_port()),
+					IpAddr::V6(v6) => format!("[{}]:{}", v6, info.get_port()),
+				};
+				let instance = info.get_fullname().trim_end_matches(SERVICE_TYPE).trim_end_matches('.');
+				let txt = info.get_properties().clone().into_property_map_str();
+				found.insert(info.get_fullname().to_string(), DiscoveredServer::from_txt(addr, instance, &txt));
+			}
+			Ok(ServiceEvent::ServiceRemoved(_, fullname)) => {
+				found.remove(&fullname);
+			}
+			Ok(_) => {}
+			Err(_) => break,
+		}
+	}
+	let _ = daemon.shutdown();
+
+	let mut servers = found.into_values().collect::<Vec<_>>();
+	servers.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.addr.cmp(&b.addr)));
+	Ok(servers)
+}
+
+/// IPv4 over IPv6, and anything over loopback.
+fn preferred_address(addrs: impl Iterator<Item = IpAddr>) -> Option<IpAddr> {
+	addrs.min_by_key(|ip| (ip.is_loopback(), ip.is_ipv6(), *ip))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_txt_record_round_trips() {
+		let txt = txt("Friday Game", 3, 7)
+			.into_iter()
+			.map(|(k, v)| (k.to_string(), v))
+			.collect::<HashMap<_, _>>();
+		let server = DiscoveredServer::from_txt("10.0.0.5:9999".into(), "fallback", &txt);
+		assert_eq!(server, DiscoveredServer { name: "Friday Game".into(), addr: "10.0.0.5:9999".into(), tables: 3, players: 7 });
+
+		let server = DiscoveredServer::from_txt("10.0.0.5:9999".into(), "fallback", &HashMap::new());
+		assert_eq!((server.name.as_str(), server.tables, server.players), ("fallback", 0, 0));
+	}
+
+	#[test]
+	fn test_addresses_and_host_labels() {
+		let addrs: [IpAddr; 3] = ["::1".parse().unwrap(), "fe80::1".parse().unwrap(), "192.168.1.20".parse().unwrap()];
+		assert_eq!(preferred_address(addrs.into_iter()), Some("192.168.1.20".parse().unwrap()));
+		assert_eq!(host_label("Friday Game!"), "friday-game");
+		assert_eq!(host_label("***"), "poker");
+	}
+}
@@ -1,9 +1,21 @@
+pub mod arena;
 pub mod client;
+pub mod discovery;
+pub mod moderation;
 pub mod protocol;
+pub mod proxy;
+pub mod recording;
+pub mod relay;
 pub mod remote_player;
 pub mod server;
+pub mod server_config;
+pub mod soak;
+pub mod stress;
 
-pub use client::GameClient;
-pub use protocol::{ClientMessage, ServerMessage, TableInfo, TableStatus, PlayerInfo};
+pub use arena::BotArena;
+pub use client::{ConnectionState, GameClient};
+pub use protocol::{ArenaMessage, ArenaRequest, ClientMessage, ServerMessage, TableInfo, TableStatus, PlayerInfo};
+pub use relay::Relay;
 pub use remote_player::RemotePlayer;
 pub use server::GameServer;
+pub use server_config::ServerConfig;
@@ -0,0 +1,342 @@
+//! Bans, mutes and strikes, kept in `moderation.toml` beside the other
+//! config files and managed with `poker admin`. The server checks bans at
+//! login and when joining a table, and mutes on chat. It rereads the file
+//! whenever it changes, so `poker admin` works on a running server. The
+//! same goes for tables an admin has paused.
+//!
+//! Strikes are warnings that add up: the `STRIKE_LIMIT`th earns a ban of
+//! `STRIKE_BAN`, and the count starts over.
+
+use std::fmt;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::error::PokerError;
+use crate::logging;
+
+pub const STRIKE_LIMIT: u32 = 3;
+pub const STRIKE_BAN: Duration = Duration::days(7);
+
+/// Who a ban is for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+	Username(String),
+	Ip(IpAddr),
+}
+
+impl Target {
+	/// An IP address if `text` is one, else a username.
+	pub fn parse(text: &str) -> Self {
+		match text.parse() {
+			Ok(ip) => Target::Ip(ip),
+			Err(_) => Target::Username(text.to_lowercase()),
+		}
+	}
+}
+
+impl fmt::Display for Target {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Target::Username(name) => write!(f, "{}", name),
+			Target::Ip(ip) => write!(f, "{}", ip),
+		}
+	}
+}
+
+/// A ban or a mute.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sanction {
+	#[serde(flatten)]
+	pub target: Target,
+	/// Local time, RFC 3339, when it lifts; never if unset.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub until: Option<String>,
+	#[serde(default)]
+	pub reason: String,
+	/// When it was given, RFC 3339.
+	pub at: String,
+}
+
+impl Sanction {
+	fn new(target: Target, duration: Option<Duration>, reason: &str) -> Self {
+		let now = Local::now();
+		Self {
+			target,
+			until: duration.map(|d| (now + d).to_rfc3339()),
+			reason: reason.to_string(),
+			at: now.to_rfc3339(),
+		}
+	}
+
+	fn ends(&self) -> Option<DateTime<Local>> {
+		let until = self.until.as_deref()?;
+		DateTime::parse_from_rfc3339(until).ok().map(|t| t.with_timezone(&Local))
+	}
+
+	pub fn is_active(&self, now: DateTime<Local>) -> bool {
+		self.ends().is_none_or(|end| now < end)
+	}
+
+	/// What the player is told, like "until 2026-10-20 18:00: spamming".
+	pub fn describe(&self) -> String {
+		let when = match self.ends() {
+			Some(end) => format!("until {}", end.format("%Y-%m-%d %H:%M")),
+			None => "for good".to_string(),
+		};
+		if self.reason.is_empty() { when } else { format!("{}: {}", when, self.reason) }
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Strike {
+	pub username: String,
+	pub reason: String,
+	pub at: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModerationFile {
+	pub bans: Vec<Sanction>,
+	pub mutes: Vec<Sanction>,
+	/// Strikes since the player's last strike ban.
+	pub strikes: Vec<Strike>,
+	/// Tables whose games are held until `poker admin resume`.
+	pub paused_tables: Vec<String>,
+}
+
+impl ModerationFile {
+	pub fn ban_for(&self, username: Option<&str>, ip: Option<IpAddr>, now: DateTime<Local>) -> Option<&Sanction> {
+		let username = username.map(str::to_lowercase);
+		self.bans.iter().filter(|b| b.is_active(now)).find(|b| match &b.target {
+			Target::Username(name) => username.as_deref() == Some(name.as_str()),
+			Target::Ip(banned) => ip == Some(*banned),
+		})
+	}
+
+	pub fn mute_for(&self, username: &str, now: DateTime<Local>) -> Option<&Sanction> {
+		let username = Target::Username(username.to_lowercase());
+		self.mutes.iter().find(|m| m.target == username && m.is_active(now))
+	}
+
+	/// Bans `target`, replacing any ban it already has.
+	pub fn ban(&mut self, target: Target, duration: Option<Duration>, reason: &str) {
+		self.bans.retain(|b| b.target != target);
+		self.bans.push(Sanction::new(target, duration, reason));
+	}
+
+	/// Whether there was a ban to lift.
+	pub fn unban(&mut self, target: &Target) -> bool {
+		let before = self.bans.len();
+		self.bans.retain(|b| &b.target != target);
+		self.bans.len() != before
+	}
+
+	pub fn mute(&mut self, username: &str, duration: Option<Duration>, reason: &str) {
+		let target = Target::Username(username.to_lowercase());
+		self.mutes.retain(|m| m.target != target);
+		self.mutes.push(Sanction::new(target, duration, reason));
+	}
+
+	pub fn unmute(&mut self, username: &str) -> bool {
+		let target = Target::Username(username.to_lowercase());
+		let before = self.mutes.len();
+		self.mutes.retain(|m| m.target != target);
+		self.mutes.len() != before
+	}
+
+	/// Adds a strike and returns the player's count. The one that reaches
+	/// `STRIKE_LIMIT` bans them and clears their strikes.
+	pub fn strike(&mut self, username: &str, reason: &str) -> u32 {
+		let username = username.to_lowercase();
+		self.strikes.push(Strike { username: username.clone(), reason: reason.to_string(), at: Local::now().to_rfc3339() });
+		let count = self.strikes.iter().filter(|s| s.username == username).count() as u32;
+		if count >= STRIKE_LIMIT {
+			self.strikes.retain(|s| s.username != username);
+			let reason = format!("{} strikes, the last for {}", count, reason);
+			self.ban(Target::Username(username), Some(STRIKE_BAN), &reason);
+		}
+		count
+	}
+
+	/// Whether the table wasn't already paused.
+	pub fn pause(&mut self, table_id: &str) -> bool {
+		let added = !self.paused_tables.iter().any(|t| t == table_id);
+		if added {
+			self.paused_tables.push(table_id.to_string());
+		}
+		added
+	}
+
+	pub fn resume(&mut self, table_id: &str) -> bool {
+		let before = self.paused_tables.len();
+		self.paused_tables.retain(|t| t != table_id);
+		self.paused_tables.len() != before
+	}
+
+	/// Drops bans and mutes that have run out.
+	pub fn prune(&mut self, now: DateTime<Local>) {
+		self.bans.retain(|b| b.is_active(now));
+		self.mutes.retain(|m| m.is_active(now));
+	}
+}
+
+/// `moderation.toml`, reread when it changes on disk.
+pub struct Moderation {
+	path: PathBuf,
+	file: ModerationFile,
+	modified: Option<SystemTime>,
+}
+
+impl Moderation {
+	/// The file in the config directory.
+	pub fn default_path() -> PathBuf {
+		match dirs::config_dir() {
+			Some(dir) => dir.join("transparent-poker").join("moderation.toml"),
+			None => PathBuf::from("config/moderation.toml"),
+		}
+	}
+
+	/// Reads the file when first asked for the lists.
+	pub fn new(path: impl Into<PathBuf>) -> Self {
+		Self { path: path.into(), file: ModerationFile::default(), modified: None }
+	}
+
+	pub fn open(path: impl Into<PathBuf>) -> Result<Self, PokerError> {
+		let mut moderation = Self::new(path);
+		moderation.reload()?;
+		Ok(moderation)
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	fn reload(&mut self) -> Result<(), PokerError> {
+		let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+		self.file = match modified {
+			Some(_) => {
+				let content = fs::read_to_string(&self.path).map_err(|e| PokerError::io("read", &self.path, e))?;
+				toml::from_str(&content).map_err(|e| PokerError::parse(self.path.display().to_string(), e))?
+			}
+			None => ModerationFile::default(),
+		};
+		self.modified = modified;
+		Ok(())
+	}
+
+	/// The lists as they stand on disk; the ones already loaded if the file
+	/// hasn't changed or can't be read.
+	pub fn current(&mut self) -> &ModerationFile {
+		let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+		if modified != self.modified {
+			if let Err(e) = self.reload() {
+				// Once per change to the file, not on every check
+				logging::log("Server", "ERROR", &e.to_string());
+				self.modified = modified;
+			}
+		}
+		&self.file
+	}
+
+	/// Changes the lists and writes them back.
+	pub fn update<T>(&mut self, change: impl FnOnce(&mut ModerationFile) -> T) -> Result<T, PokerError> {
+		self.reload()?;
+		let result = change(&mut self.file);
+		self.file.prune(Local::now());
+		let content = toml::to_string_pretty(&self.file).map_err(|e| PokerError::parse("moderation lists", e))?;
+		if let Some(dir) = self.path.parent() {
+			fs::create_dir_all(dir).map_err(|e| PokerError::io("create", dir, e))?;
+		}
+		fs::write(&self.path, content).map_err(|e| PokerError::io("write", &self.path, e))?;
+		self.modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+		Ok(result)
+	}
+}
+
+/// A length of time like `30m`, `12h`, `7d` or `2w`.
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+	let text = text.trim();
+	let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+	let (count, unit) = text.split_at(split);
+	let count: i64 = count.parse().map_err(|_| format!("Bad duration '{}' (like 30m, 12h, 7d or 2w)", text))?;
+	match unit {
+		"s" => Ok(Duration::seconds(count)),
+		"m" => Ok(Duration::minutes(count)),
+		"h" => Ok(Duration::hours(count)),
+		"d" => Ok(Duration::days(count)),
+		"w" => Ok(Duration::weeks(count)),
+		_ => Err(format!("Bad duration '{}' (like 30m, 12h, 7d or 2w)", text)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_bans_match_names_and_addresses_until_they_lift() {
+		let mut file = ModerationFile::default();
+		let ip: IpAddr = "10.0.0.9".parse().unwrap();
+		file.ban(Target::parse("Mallory"), Some(Duration::hours(2)), "collusion");
+		file.ban(Target::parse("10.0.0.9"), None, "");
+		let now = Local::now();
+
+		assert!(file.ban_for(Some("MALLORY"), None, now).is_some());
+		assert!(file.ban_for(Some("alice"), Some(ip), now).is_some());
+		assert!(file.ban_for(Some("alice"), None, now).is_none());
+		assert!(file.ban_for(Some("mallory"), None, now + Duration::hours(3)).is_none());
+		assert_eq!(file.ban_for(None, Some(ip), now).unwrap().describe(), "for good");
+
+		assert!(file.unban(&Target::parse("mallory")));
+		assert!(!file.unban(&Target::parse("mallory")));
+
+		file.mute("Bob", Some(Duration::minutes(30)), "spam");
+		assert!(file.mute_for("bob", now).unwrap().describe().ends_with(": spam"));
+		file.prune(now + Duration::hours(1));
+		assert!(file.mutes.is_empty());
+		assert_eq!(file.bans.len(), 1);
+	}
+
+	#[test]
+	fn test_third_strike_bans() {
+		let mut file = ModerationFile::default();
+		assert_eq!(file.strike("eve", "slurs"), 1);
+		assert_eq!(file.strike("Eve", "slurs"), 2);
+		assert!(file.ban_for(Some("eve"), None, Local::now()).is_none());
+		assert_eq!(file.strike("eve", "threats"), 3);
+		let ban = file.ban_for(Some("eve"), None, Local::now()).unwrap();
+		assert!(ban.reason.contains("threats"));
+		assert!(file.strikes.is_empty());
+	}
+
+	#[test]
+	fn test_moderation_file_round_trips() {
+		let path = std::env::temp_dir().join(format!("poker-moderation-{}.toml", std::process::id()));
+		let _ = fs::remove_file(&path);
+		let mut moderation = Moderation::open(&path).unwrap();
+		moderation.update(|file| file.ban(Target::parse("mallory"), None, "cheating")).unwrap();
+		moderation.update(|file| file.mute("bob", Some(Duration::days(1)), "")).unwrap();
+
+		let mut reopened = Moderation::open(&path).unwrap();
+		let file = reopened.current();
+		assert_eq!(file.bans[0].target, Target::Username("mallory".into()));
+		assert!(file.mute_for("bob", Local::now()).is_some());
+		let _ = fs::remove_file(&path);
+	}
+
+	#[test]
+	fn test_durations_parse() {
+		assert_eq!(parse_duration("30m"), Ok(Duration::minutes(30)));
+		assert_eq!(parse_duration("7d"), Ok(Duration::days(7)));
+		assert_eq!(parse_duration("45s"), Ok(Duration::seconds(45)));
+		assert!(parse_duration("7").is_err());
+		assert!(parse_duration("d").is_err());
+	}
+}
@@ -5,7 +5,9 @@ use std::sync::{Arc, Mutex};
 use async_trait::async_trait;
 use tokio::sync::oneshot;
 
-use crate::events::{Card, GameEvent, PlayerAction, Seat, ValidActions};
+use crate::error::PokerError;
+use crate::events::redaction::{redact, Audience};
+use crate::events::{GameEvent, PlayerAction, Seat, ValidActions};
 use crate::net::protocol::{encode_message, ServerMessage};
 use crate::players::{GameSnapshot, PlayerPort, PlayerResponse};
 
@@ -36,25 +38,6 @@ impl NetworkPlayer {
 			let _ = stream.write_all(&data);
 		}
 	}
-
-	fn filter_event(&self, event: &GameEvent) -> Option<GameEvent> {
-		match event {
-			GameEvent::HoleCardsDealt { seat, cards: _ } => {
-				if *seat == self.seat {
-					Some(event.clone())
-				} else {
-					Some(GameEvent::HoleCardsDealt {
-						seat: *seat,
-						cards: [
-							Card { rank: '?', suit: '?' },
-							Card { rank: '?', suit: '?' },
-						],
-					})
-				}
-			}
-			_ => Some(event.clone()),
-		}
-	}
 }
 
 #[async_trait]
@@ -77,17 +60,9 @@ impl PlayerPort for NetworkPlayer {
 			time_limit: Some(60),
 		});
 
-		// Simple blocking wait with timeout using spawn_blocking
-		let result = tokio::task::spawn_blocking(move || {
-			match rx.blocking_recv() {
-				Ok(response) => Some(response),
-				Err(_) => None,
-			}
-		}).await;
-
-		match result {
-			Ok(Some(response)) => response,
-			_ => {
+		match rx.await {
+			Ok(response) => response,
+			Err(_) => {
 				let mut pending = self.pending_action.lock().unwrap();
 				*pending = None;
 				PlayerResponse::Timeout
@@ -96,7 +71,7 @@ impl PlayerPort for NetworkPlayer {
 	}
 
 	fn notify(&self, event: &GameEvent) {
-		if let Some(filtered) = self.filter_event(event) {
+		if let Some(filtered) = redact(event, Audience::Player(self.seat)) {
 			self.send_message(&ServerMessage::GameEvent(filtered));
 		}
 	}
@@ -117,12 +92,12 @@ impl PlayerPort for NetworkPlayer {
 pub fn submit_action(
 	pending: &Arc<Mutex<Option<oneshot::Sender<PlayerResponse>>>>,
 	action: PlayerAction,
-) -> Result<(), String> {
+) -> Result<(), PokerError> {
 	let mut guard = pending.lock().unwrap();
 	if let Some(tx) = guard.take() {
 		tx.send(PlayerResponse::Action(action))
-			.map_err(|_| "Failed to send action".to_string())
+			.map_err(|_| PokerError::Net("Failed to send action".to_string()))
 	} else {
-		Err("No pending action request".to_string())
+		Err(PokerError::Net("No pending action request".to_string()))
 	}
 }
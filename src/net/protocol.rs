@@ -1,8 +1,10 @@
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use crate::events::{GameEvent, PlayerAction, Seat, ValidActions};
+use crate::events::{GameEvent, PlayerAction, Seat, Standing, ValidActions};
 use crate::table::TableConfig;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ClientMessage {
 	Login {
@@ -27,9 +29,40 @@ pub enum ClientMessage {
 	Chat {
 		text: String,
 	},
+	/// Skip AI think time and table pacing while every human at the
+	/// table has it on.
+	FastForward {
+		enabled: bool,
+	},
+	/// Muck losing hands at showdown instead of showing them.
+	AutoMuck {
+		enabled: bool,
+	},
+	/// This player's pauses after each action and new street, in place of
+	/// the table's. With several humans seated the slowest pace wins.
+	Pacing {
+		action_delay_ms: Option<u64>,
+		street_delay_ms: Option<u64>,
+	},
+	/// Move to another running cash table at the same stakes after this
+	/// hand, taking your stack along.
+	ChangeTable,
+	/// Hold the game before its next action, or let it go on. Only the
+	/// host of a `poker play` game may.
+	Pause {
+		paused: bool,
+	},
+	/// Something the server sent was thrown away unread.
+	ProtocolError {
+		reason: String,
+	},
+	/// Measures the round trip; answered with a `Pong` carrying the same id.
+	Ping {
+		id: u64,
+	},
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ServerMessage {
 	Welcome {
@@ -40,6 +73,10 @@ pub enum ServerMessage {
 	Error {
 		message: String,
 	},
+	/// Something the client sent was thrown away unread.
+	ProtocolError {
+		reason: String,
+	},
 	LobbyState {
 		tables: Vec<TableInfo>,
 	},
@@ -66,22 +103,38 @@ pub enum ServerMessage {
 	AIAdded {
 		seat: Seat,
 		name: String,
+		#[serde(default)]
+		bankroll: Option<f32>,
 	},
 	AIRemoved {
 		seat: Seat,
 	},
+	/// A sit & go's registration is open: in `seconds` the empty seats fill
+	/// with AIs and the game starts.
+	RegistrationOpen {
+		seconds: u32,
+	},
 	GameStarting {
 		countdown: u32,
 		table_config: TableConfig,
 	},
+	/// You've been moved to `table_id`; its events follow.
+	TableChanged {
+		table_id: String,
+		seat: Seat,
+		table_config: TableConfig,
+	},
 	GameEvent(GameEvent),
 	ActionRequest {
 		valid_actions: ValidActions,
 		time_limit: Option<u32>,
 	},
+	Pong {
+		id: u64,
+	},
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TableInfo {
 	pub id: String,
 	pub name: String,
@@ -96,7 +149,7 @@ pub struct TableInfo {
 	pub config: TableConfig,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TableStatus {
 	Waiting,
@@ -104,28 +157,159 @@ pub enum TableStatus {
 	Finished,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PlayerInfo {
 	pub seat: Seat,
 	pub username: String,
 	pub ready: bool,
 	#[serde(default)]
 	pub is_ai: bool,
+	/// Roster AIs' bankrolls, so the lobby shows who is flush and who is
+	/// nearly broke.
+	#[serde(default)]
+	pub bankroll: Option<f32>,
+}
+
+/// Bot arena handshake, one JSON object per line. Once registered, a bot
+/// speaks the same lines as a `SubprocessPlayer`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArenaRequest {
+	ListTables,
+	Register {
+		name: String,
+		table: String,
+	},
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArenaMessage {
+	Tables {
+		tables: Vec<ArenaTableInfo>,
+	},
+	Registered {
+		table: String,
+		waiting: usize,
+		seats: usize,
+	},
+	GameStart {
+		table: String,
+		seat: Seat,
+		players: Vec<String>,
+	},
+	GameOver {
+		standings: Vec<Standing>,
+	},
+	Error {
+		message: String,
+	},
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ArenaTableInfo {
+	pub id: String,
+	pub name: String,
+	pub betting: String,
+	pub blinds: String,
+	pub seats: usize,
+	pub waiting: usize,
 }
 
+/// Opens every frame. Neither byte ever occurs in UTF-8, so never inside
+/// a payload, which lets a reader that lost its place find the next frame.
+pub const FRAME_MAGIC: [u8; 2] = [0xFE, 0xFF];
+
+/// Magic, then the payload's length and checksum, each a big-endian u32.
+pub const FRAME_HEADER_LEN: usize = 10;
+
+/// No frame is longer than this; a bigger length is a corrupt header.
+pub const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
 pub fn encode_message<T: Serialize>(msg: &T) -> Vec<u8> {
 	let json = serde_json::to_string(msg).expect("Failed to serialize message - this is a bug");
-	let len = json.len() as u32;
-	let mut buf = len.to_be_bytes().to_vec();
+	let mut buf = Vec::with_capacity(FRAME_HEADER_LEN + json.len());
+	buf.extend(FRAME_MAGIC);
+	buf.extend((json.len() as u32).to_be_bytes());
+	buf.extend(checksum(json.as_bytes()).to_be_bytes());
 	buf.extend(json.as_bytes());
 	buf
 }
 
+/// The payload length from a frame header, once enough of it is in.
 pub fn decode_length(buf: &[u8]) -> Option<u32> {
-	if buf.len() < 4 {
+	let bytes = buf.get(FRAME_MAGIC.len()..FRAME_MAGIC.len() + 4)?;
+	Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+/// FNV-1a over a frame's payload.
+pub fn checksum(payload: &[u8]) -> u32 {
+	payload.iter().fold(0x811c_9dc5, |hash, &b| (hash ^ b as u32).wrapping_mul(0x0100_0193))
+}
+
+/// Takes the next message off the front of `buf`; `None` until a whole
+/// frame is in. `Some(Err)` says what was thrown away instead: bytes
+/// between frames, a frame longer than `max_len`, one whose checksum is
+/// wrong, or one that isn't a message. Only the bad part goes, so the
+/// next call carries on with the frame after it.
+pub fn decode_frame<T: DeserializeOwned>(buf: &mut Vec<u8>, max_len: usize) -> Option<Result<T, String>> {
+	let start = frame_start(buf);
+	if start > 0 {
+		buf.drain(..start);
+		return Some(Err(format!("skipped {} bytes that weren't a frame", start)));
+	}
+	if buf.len() < FRAME_HEADER_LEN {
 		return None;
 	}
-	Some(u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]))
+	let len = decode_length(buf)? as usize;
+	let limit = max_len.min(MAX_FRAME_LEN);
+	// A bad header goes whole, so none of its bytes can pass for the
+	// magic of the next frame
+	if len > limit {
+		buf.drain(..FRAME_HEADER_LEN);
+		return Some(Err(format!("frame of {} bytes is over the {} byte limit", len, limit)));
+	}
+	if buf.len() < FRAME_HEADER_LEN + len {
+		return None;
+	}
+	let sum = u32::from_be_bytes([buf[6], buf[7], buf[8], buf[9]]);
+	let payload = &buf[FRAME_HEADER_LEN..FRAME_HEADER_LEN + len];
+	if checksum(payload) != sum {
+		buf.drain(..FRAME_HEADER_LEN);
+		return Some(Err("frame checksum doesn't match".to_string()));
+	}
+	let msg = decode_payload(payload);
+	buf.drain(..FRAME_HEADER_LEN + len);
+	Some(msg)
+}
+
+/// A frame's payload as a message. A number too big for an `f32` makes it
+/// unreadable: it would come out as infinity, which can't be sent on again.
+pub fn decode_payload<T: DeserializeOwned>(payload: &[u8]) -> Result<T, String> {
+	serde_json::from_slice::<serde_json::Value>(payload)
+		.and_then(|value| match too_big_for_f32(&value) {
+			Some(n) => Err(serde::de::Error::custom(format!("{} is out of range", n))),
+			None => serde_json::from_value(value),
+		})
+		.map_err(|e| format!("unreadable message: {}", e))
+}
+
+/// The first number anywhere in `value` that an `f32` can't hold.
+fn too_big_for_f32(value: &serde_json::Value) -> Option<f64> {
+	match value {
+		serde_json::Value::Number(n) => n.as_f64().filter(|f| f.abs() > f32::MAX as f64),
+		serde_json::Value::Array(items) => items.iter().find_map(too_big_for_f32),
+		serde_json::Value::Object(fields) => fields.values().find_map(too_big_for_f32),
+		_ => None,
+	}
+}
+
+/// Where the first frame in `buf` starts. With no magic in sight, that's
+/// the end, or the last byte if it could be the first half of one.
+fn frame_start(buf: &[u8]) -> usize {
+	buf.windows(2).position(|w| w == FRAME_MAGIC).unwrap_or_else(|| {
+		if buf.last() == Some(&FRAME_MAGIC[0]) { buf.len() - 1 } else { buf.len() }
+	})
 }
 
 #[cfg(test)]
@@ -138,14 +322,112 @@ mod tests {
 		let encoded = encode_message(&msg);
 
 		let len = decode_length(&encoded).expect("Should decode length");
-		assert_eq!(len as usize, encoded.len() - 4);
+		assert_eq!(len as usize, encoded.len() - FRAME_HEADER_LEN);
+	}
+
+	#[test]
+	fn test_decode_frame_waits_for_the_whole_frame() {
+		let encoded = encode_message(&ClientMessage::Ready);
+		let mut buf = encoded[..encoded.len() - 1].to_vec();
+		assert!(decode_frame::<ClientMessage>(&mut buf, MAX_FRAME_LEN).is_none());
+		buf.push(*encoded.last().unwrap());
+		buf.extend(encode_message(&ClientMessage::LeaveTable));
+		assert!(matches!(decode_frame(&mut buf, MAX_FRAME_LEN), Some(Ok(ClientMessage::Ready))));
+		assert!(matches!(decode_frame(&mut buf, MAX_FRAME_LEN), Some(Ok(ClientMessage::LeaveTable))));
+		assert!(buf.is_empty());
+	}
+
+	#[test]
+	fn test_decode_frame_skips_only_the_bad_part() {
+		let mut corrupt = encode_message(&ClientMessage::Ready);
+		*corrupt.last_mut().unwrap() ^= 0x20;
+		let mut oversized = FRAME_MAGIC.to_vec();
+		oversized.extend(1_000_000u32.to_be_bytes());
+		oversized.extend(0u32.to_be_bytes());
+		let mut not_a_message = FRAME_MAGIC.to_vec();
+		not_a_message.extend(2u32.to_be_bytes());
+		not_a_message.extend(checksum(b"{}").to_be_bytes());
+		not_a_message.extend(b"{}");
+
+		let mut buf = b"noise".to_vec();
+		buf.extend(corrupt);
+		buf.extend(oversized);
+		buf.extend(not_a_message);
+		buf.extend(encode_message(&ClientMessage::ListTables));
+
+		let mut errors = Vec::new();
+		let msg = loop {
+			match decode_frame::<ClientMessage>(&mut buf, 1024) {
+				Some(Ok(msg)) => break msg,
+				Some(Err(e)) => errors.push(e),
+				None => panic!("stalled after {:?}", errors),
+			}
+		};
+		assert!(matches!(msg, ClientMessage::ListTables));
+		assert_eq!(errors[0], "skipped 5 bytes that weren't a frame");
+		assert!(errors.iter().any(|e| e.contains("checksum")), "{:?}", errors);
+		assert!(errors.iter().any(|e| e.contains("over the 1024 byte limit")), "{:?}", errors);
+		assert!(errors.last().unwrap().starts_with("unreadable message"), "{:?}", errors);
+		assert!(buf.is_empty());
+	}
+
+	#[test]
+	fn test_decode_frame_skips_magic_inside_a_bad_header() {
+		// Length and checksum bytes that look like the start of a frame
+		let mut oversized = FRAME_MAGIC.to_vec();
+		oversized.extend([0xFE, 0xFF, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+		let mut bad_sum = FRAME_MAGIC.to_vec();
+		bad_sum.extend(2u32.to_be_bytes());
+		bad_sum.extend([0xFE, 0xFF, 0x00, 0x00]);
+		bad_sum.extend(b"{}");
+
+		let mut buf = oversized;
+		buf.extend(bad_sum);
+		buf.extend(encode_message(&ClientMessage::Ready));
+
+		let mut errors = Vec::new();
+		let msg = loop {
+			match decode_frame::<ClientMessage>(&mut buf, usize::MAX) {
+				Some(Ok(msg)) => break msg,
+				Some(Err(e)) => errors.push(e),
+				None => panic!("stalled after {:?}", errors),
+			}
+		};
+		assert!(matches!(msg, ClientMessage::Ready));
+		assert_eq!(
+			errors,
+			vec![
+				format!("frame of {} bytes is over the {} byte limit", 0xFEFF_0000u32, MAX_FRAME_LEN),
+				"frame checksum doesn't match".to_string(),
+				"skipped 2 bytes that weren't a frame".to_string(),
+			]
+		);
+		assert!(buf.is_empty());
+	}
+
+	#[test]
+	fn test_decode_frame_refuses_numbers_past_f32() {
+		let frame = |json: &str| {
+			let mut buf = FRAME_MAGIC.to_vec();
+			buf.extend((json.len() as u32).to_be_bytes());
+			buf.extend(checksum(json.as_bytes()).to_be_bytes());
+			buf.extend(json.as_bytes());
+			buf
+		};
+		let mut buf = frame(r#"{"type":"action","AllIn":{"amount":1e300}}"#);
+		let error = decode_frame::<ClientMessage>(&mut buf, MAX_FRAME_LEN).unwrap().unwrap_err();
+		assert!(error.contains("out of range"), "{}", error);
+		assert!(buf.is_empty());
+
+		let mut buf = frame(r#"{"type":"action","AllIn":{"amount":3e38}}"#);
+		assert!(matches!(decode_frame(&mut buf, MAX_FRAME_LEN), Some(Ok(ClientMessage::Action { .. }))));
 	}
 
 	#[test]
 	fn test_encode_client_message_login() {
 		let msg = ClientMessage::Login { username: "Alice".to_string() };
 		let encoded = encode_message(&msg);
-		let json = std::str::from_utf8(&encoded[4..]).unwrap();
+		let json = std::str::from_utf8(&encoded[FRAME_HEADER_LEN..]).unwrap();
 
 		assert!(json.contains("login"));
 		assert!(json.contains("Alice"));
@@ -157,7 +439,7 @@ mod tests {
 			action: PlayerAction::Raise { amount: 100.0 },
 		};
 		let encoded = encode_message(&msg);
-		let json = std::str::from_utf8(&encoded[4..]).unwrap();
+		let json = std::str::from_utf8(&encoded[FRAME_HEADER_LEN..]).unwrap();
 
 		assert!(json.contains("action"));
 		assert!(json.contains("Raise")); // PlayerAction variants are capitalized
@@ -172,7 +454,7 @@ mod tests {
 			bankroll: 1000.0,
 		};
 		let encoded = encode_message(&msg);
-		let json = std::str::from_utf8(&encoded[4..]).unwrap();
+		let json = std::str::from_utf8(&encoded[FRAME_HEADER_LEN..]).unwrap();
 
 		assert!(json.contains("welcome"));
 		assert!(json.contains("Bob"));
@@ -191,13 +473,14 @@ mod tests {
 					username: "Alice".to_string(),
 					ready: false,
 					is_ai: false,
+					bankroll: None,
 				},
 			],
 			min_players: 2,
 			max_players: 6,
 		};
 		let encoded = encode_message(&msg);
-		let json = std::str::from_utf8(&encoded[4..]).unwrap();
+		let json = std::str::from_utf8(&encoded[FRAME_HEADER_LEN..]).unwrap();
 
 		assert!(json.contains("table_joined"));
 		assert!(json.contains("table1"));
@@ -210,7 +493,7 @@ mod tests {
 			table_id: "test-table".to_string(),
 		};
 		let encoded = encode_message(&original);
-		let json = std::str::from_utf8(&encoded[4..]).unwrap();
+		let json = std::str::from_utf8(&encoded[FRAME_HEADER_LEN..]).unwrap();
 		let decoded: ClientMessage = serde_json::from_str(json).unwrap();
 
 		match decoded {
@@ -250,6 +533,15 @@ mod tests {
 			street_delay_ms: 700,
 			hand_end_delay_ms: 2000,
 			seed: None,
+			mixed_games: None,
+			pit_boss: false,
+			table_talk: false,
+			table_talk_frequency: 1.0,
+			registration_seconds: None,
+			takeover_grace_seconds: None,
+			lineup: None,
+			promotions: None,
+			transparency: false,
 		};
 
 		let original = ServerMessage::GameStarting {
@@ -257,7 +549,7 @@ mod tests {
 			table_config: table_config.clone(),
 		};
 		let encoded = encode_message(&original);
-		let json = std::str::from_utf8(&encoded[4..]).unwrap();
+		let json = std::str::from_utf8(&encoded[FRAME_HEADER_LEN..]).unwrap();
 		let decoded: ServerMessage = serde_json::from_str(json).unwrap();
 
 		match decoded {
@@ -298,6 +590,15 @@ mod tests {
 			street_delay_ms: 700,
 			hand_end_delay_ms: 2000,
 			seed: None,
+			mixed_games: None,
+			pit_boss: false,
+			table_talk: false,
+			table_talk_frequency: 1.0,
+			registration_seconds: None,
+			takeover_grace_seconds: None,
+			lineup: None,
+			promotions: None,
+			transparency: false,
 		};
 
 		let info = TableInfo {
@@ -341,13 +642,14 @@ mod tests {
 			}),
 			can_all_in: true,
 			all_in_amount: 100.0,
+			can_show: false,
 		};
 		let msg = ServerMessage::ActionRequest {
 			valid_actions: valid,
 			time_limit: Some(30),
 		};
 		let encoded = encode_message(&msg);
-		let json = std::str::from_utf8(&encoded[4..]).unwrap();
+		let json = std::str::from_utf8(&encoded[FRAME_HEADER_LEN..]).unwrap();
 		let decoded: ServerMessage = serde_json::from_str(json).unwrap();
 
 		match decoded {
@@ -0,0 +1,397 @@
+//! A recording proxy for chasing network bugs: `poker proxy` sits between
+//! clients and a server, passes every byte through untouched, and logs
+//! each frame it sees as one line of JSON. A recorded client can later be
+//! replayed against a server, with its original timing, to reproduce a
+//! desync that only shows up now and then.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::protocol::{decode_frame, encode_message, MAX_FRAME_LEN};
+
+/// How long a replay waits for the server to go quiet after the last
+/// message.
+const REPLAY_SETTLE: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+	Client,
+	Server,
+}
+
+/// One line of a proxy log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProxyEntry {
+	/// Milliseconds since the proxy started.
+	pub at_ms: u64,
+	pub connection: u64,
+	pub from: Side,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub message: Option<serde_json::Value>,
+	/// Why bytes that came through weren't a readable frame.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// "connected" or "closed", on lines that carry no message.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub note: Option<String>,
+}
+
+/// Where entries go: a file for the proxy, stdout or a file for a replay.
+struct Log {
+	out: Box<dyn Write + Send>,
+	started: Instant,
+}
+
+impl Log {
+	fn new(out: Box<dyn Write + Send>) -> Self {
+		Self { out, started: Instant::now() }
+	}
+
+	fn write(&mut self, connection: u64, from: Side, entry: Entry) {
+		let (message, error, note) = match entry {
+			Entry::Message(message) => (Some(message), None, None),
+			Entry::Error(error) => (None, Some(error), None),
+			Entry::Note(note) => (None, None, Some(note.to_string())),
+		};
+		let line = ProxyEntry {
+			at_ms: self.started.elapsed().as_millis() as u64,
+			connection,
+			from,
+			message,
+			error,
+			note,
+		};
+		if let Ok(json) = serde_json::to_string(&line) {
+			let _ = writeln!(self.out, "{}", json);
+			let _ = self.out.flush();
+		}
+	}
+}
+
+enum Entry {
+	Message(serde_json::Value),
+	Error(String),
+	Note(&'static str),
+}
+
+type SharedLog = Arc<Mutex<Log>>;
+
+fn log_entry(log: &SharedLog, connection: u64, from: Side, entry: Entry) {
+	log.lock().unwrap_or_else(|e| e.into_inner()).write(connection, from, entry);
+}
+
+/// Logs every frame complete in `pending`, leaving any partial one.
+fn log_frames(log: &SharedLog, connection: u64, from: Side, pending: &mut Vec<u8>) {
+	while let Some(decoded) = decode_frame::<serde_json::Value>(pending, MAX_FRAME_LEN) {
+		let entry = match decoded {
+			Ok(message) => Entry::Message(message),
+			Err(e) => Entry::Error(e),
+		};
+		log_entry(log, connection, from, entry);
+	}
+}
+
+/// Copies `reader` into `writer`, logging the frames on the way, until
+/// either side hangs up.
+fn forward(mut reader: TcpStream, mut writer: TcpStream, log: SharedLog, connection: u64, from: Side) {
+	let mut buf = [0u8; 8192];
+	let mut pending = Vec::new();
+	loop {
+		let n = match reader.read(&mut buf) {
+			Ok(0) | Err(_) => break,
+			Ok(n) => n,
+		};
+		if writer.write_all(&buf[..n]).is_err() {
+			break;
+		}
+		pending.extend_from_slice(&buf[..n]);
+		log_frames(&log, connection, from, &mut pending);
+	}
+	if !pending.is_empty() {
+		log_entry(&log, connection, from, Entry::Error(format!("{} bytes left over at close", pending.len())));
+	}
+	let _ = writer.shutdown(Shutdown::Both);
+	let _ = reader.shutdown(Shutdown::Both);
+}
+
+/// The recording proxy: one upstream connection per client.
+pub struct Proxy {
+	upstream: String,
+	log: SharedLog,
+	next_connection: Arc<Mutex<u64>>,
+}
+
+impl Proxy {
+	/// Starts `log` afresh: connections are numbered from 1 each run.
+	pub fn new(upstream: &str, log: &Path) -> io::Result<Self> {
+		let file = File::create(log)?;
+		Ok(Self::with_writer(upstream, Box::new(file)))
+	}
+
+	fn with_writer(upstream: &str, out: Box<dyn Write + Send>) -> Self {
+		Self {
+			upstream: upstream.to_string(),
+			log: Arc::new(Mutex::new(Log::new(out))),
+			next_connection: Arc::new(Mutex::new(0)),
+		}
+	}
+
+	pub fn run(&self, addr: &str) -> io::Result<()> {
+		let listener = TcpListener::bind(addr)?;
+		println!("Proxy listening on {}, forwarding to {}", addr, self.upstream);
+		self.run_with_listener(listener);
+		Ok(())
+	}
+
+	pub fn run_with_listener(&self, listener: TcpListener) {
+		for stream in listener.incoming() {
+			match stream {
+				Ok(client) => {
+					let connection = {
+						let mut next = self.next_connection.lock().unwrap_or_else(|e| e.into_inner());
+						*next += 1;
+						*next
+					};
+					let (upstream, log) = (self.upstream.clone(), Arc::clone(&self.log));
+					thread::spawn(move || {
+						if let Err(e) = proxy_connection(client, &upstream, log, connection) {
+							eprintln!("Connection {} ended: {}", connection, e);
+						}
+					});
+				}
+				Err(e) => eprintln!("Connection failed: {}", e),
+			}
+		}
+	}
+}
+
+fn proxy_connection(client: TcpStream, upstream: &str, log: SharedLog, connection: u64) -> io::Result<()> {
+	let server = TcpStream::connect(upstream)?;
+	let _ = client.set_nodelay(true);
+	let _ = server.set_nodelay(true);
+	log_entry(&log, connection, Side::Client, Entry::Note("connected"));
+
+	let (client_read, server_write) = (client.try_clone()?, server.try_clone()?);
+	let up_log = Arc::clone(&log);
+	let up = thread::spawn(move || forward(client_read, server_write, up_log, connection, Side::Client));
+	forward(server, client, Arc::clone(&log), connection, Side::Server);
+	let _ = up.join();
+	log_entry(&log, connection, Side::Client, Entry::Note("closed"));
+	Ok(())
+}
+
+/// Reads a proxy log, skipping lines that aren't entries.
+pub fn load(path: &Path) -> io::Result<Vec<ProxyEntry>> {
+	let reader = BufReader::new(File::open(path)?);
+	let mut entries = Vec::new();
+	for line in reader.lines() {
+		if let Ok(entry) = serde_json::from_str(&line?) {
+			entries.push(entry);
+		}
+	}
+	Ok(entries)
+}
+
+/// What a client sent, with the time it sent each message: `connection`,
+/// or the first one in the log.
+pub fn client_session(entries: &[ProxyEntry], connection: Option<u64>) -> Option<(u64, Vec<(u64, serde_json::Value)>)> {
+	let connection = connection.or_else(|| entries.iter().find(|e| e.from == Side::Client && e.message.is_some()).map(|e| e.connection))?;
+	let sent = entries
+		.iter()
+		.filter(|e| e.connection == connection && e.from == Side::Client)
+		.filter_map(|e| e.message.clone().map(|m| (e.at_ms, m)))
+		.collect::<Vec<_>>();
+	(!sent.is_empty()).then_some((connection, sent))
+}
+
+pub struct ReplayConfig {
+	/// Send back to back, without the recorded pauses.
+	pub no_wait: bool,
+	pub settle: Duration,
+}
+
+impl Default for ReplayConfig {
+	fn default() -> Self {
+		Self { no_wait: false, settle: REPLAY_SETTLE }
+	}
+}
+
+/// Sends `sent` to the server at `upstream` as the recorded client did,
+/// logging both sides to `out` in the proxy's own format, so the two logs
+/// can be compared line by line. Returns how many messages the server
+/// sent back.
+pub fn replay(
+	sent: &[(u64, serde_json::Value)],
+	upstream: &str,
+	config: &ReplayConfig,
+	out: Box<dyn Write + Send>,
+) -> io::Result<usize> {
+	let mut server = TcpStream::connect(upstream)?;
+	let _ = server.set_nodelay(true);
+	let log = Arc::new(Mutex::new(Log::new(out)));
+
+	let (reader, reader_log) = (server.try_clone()?, Arc::clone(&log));
+	let received = thread::spawn(move || {
+		let mut reader = reader;
+		let mut buf = [0u8; 8192];
+		let mut pending = Vec::new();
+		let mut count = 0;
+		while let Ok(n) = reader.read(&mut buf) {
+			if n == 0 {
+				break;
+			}
+			pending.extend_from_slice(&buf[..n]);
+			while let Some(decoded) = decode_frame::<serde_json::Value>(&mut pending, MAX_FRAME_LEN) {
+				let entry = match decoded {
+					Ok(message) => {
+						count += 1;
+						Entry::Message(message)
+					}
+					Err(e) => Entry::Error(e),
+				};
+				log_entry(&reader_log, 1, Side::Server, entry);
+			}
+		}
+		count
+	});
+
+	let started = Instant::now();
+	let first = sent.first().map_or(0, |(at, _)| *at);
+	for (at_ms, message) in sent {
+		if !config.no_wait {
+			let due = Duration::from_millis(at_ms - first);
+			if let Some(wait) = due.checked_sub(started.elapsed()) {
+				thread::sleep(wait);
+			}
+		}
+		log_entry(&log, 1, Side::Client, Entry::Message(message.clone()));
+		server.write_all(&encode_message(message))?;
+	}
+
+	thread::sleep(config.settle);
+	let _ = server.shutdown(Shutdown::Both);
+	Ok(received.join().unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	/// A stand-in server that answers each frame with `{"type": "pong"}`,
+	/// keeping what it was sent in `seen`.
+	fn pong_server(seen: Arc<Mutex<Vec<serde_json::Value>>>) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = listener.local_addr().unwrap().to_string();
+		thread::spawn(move || {
+			for mut stream in listener.incoming().flatten() {
+				let seen = Arc::clone(&seen);
+				thread::spawn(move || {
+					let mut buf = [0u8; 1024];
+					let mut pending = Vec::new();
+					while let Ok(n) = stream.read(&mut buf) {
+						if n == 0 {
+							break;
+						}
+						pending.extend_from_slice(&buf[..n]);
+						while let Some(Ok(message)) = decode_frame::<serde_json::Value>(&mut pending, MAX_FRAME_LEN) {
+							seen.lock().unwrap().push(message);
+							let _ = stream.write_all(&encode_message(&json!({"type": "pong"})));
+						}
+					}
+				});
+			}
+		});
+		addr
+	}
+
+	/// Collects what a log writes, for reading back.
+	#[derive(Clone, Default)]
+	struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+	impl Write for Buffer {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.0.lock().unwrap().extend_from_slice(buf);
+			Ok(buf.len())
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	impl Buffer {
+		fn entries(&self) -> Vec<ProxyEntry> {
+			let text = String::from_utf8(self.0.lock().unwrap().clone()).unwrap();
+			text.lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+		}
+	}
+
+	#[test]
+	fn test_proxy_passes_frames_through_and_logs_them() {
+		let seen = Arc::new(Mutex::new(Vec::new()));
+		let upstream = pong_server(Arc::clone(&seen));
+		let buffer = Buffer::default();
+		let proxy = Proxy::with_writer(&upstream, Box::new(buffer.clone()));
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = listener.local_addr().unwrap();
+		thread::spawn(move || proxy.run_with_listener(listener));
+
+		let mut client = TcpStream::connect(addr).unwrap();
+		client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+		let login = json!({"type": "login", "username": "alice"});
+		// Split mid-frame, as TCP is free to
+		let frame = encode_message(&login);
+		client.write_all(&frame[..5]).unwrap();
+		client.flush().unwrap();
+		thread::sleep(Duration::from_millis(20));
+		client.write_all(&frame[5..]).unwrap();
+		let reply = encode_message(&json!({"type": "pong"}));
+		let mut echoed = vec![0u8; reply.len()];
+		client.read_exact(&mut echoed).unwrap();
+		assert_eq!(echoed, reply);
+		assert_eq!(*seen.lock().unwrap(), vec![login.clone()]);
+
+		drop(client);
+		thread::sleep(Duration::from_millis(100));
+		let entries = buffer.entries();
+		let messages = entries.iter().filter_map(|e| e.message.clone().map(|m| (e.from, m))).collect::<Vec<_>>();
+		assert_eq!(messages, vec![(Side::Client, login), (Side::Server, json!({"type": "pong"}))]);
+		assert_eq!(entries.first().unwrap().note.as_deref(), Some("connected"));
+		assert!(entries.iter().all(|e| e.connection == 1 && e.error.is_none()));
+	}
+
+	#[test]
+	fn test_replay_resends_one_clients_messages_in_order() {
+		let entry = |at_ms, connection, from, message| ProxyEntry { at_ms, connection, from, message: Some(message), error: None, note: None };
+		let entries = vec![
+			entry(0, 1, Side::Client, json!({"type": "login", "username": "alice"})),
+			entry(5, 2, Side::Client, json!({"type": "login", "username": "bob"})),
+			entry(8, 1, Side::Server, json!({"type": "welcome"})),
+			entry(30, 1, Side::Client, json!({"type": "list_tables"})),
+		];
+		let (connection, sent) = client_session(&entries, None).unwrap();
+		assert_eq!(connection, 1);
+		assert_eq!(sent.iter().map(|(at, _)| *at).collect::<Vec<_>>(), vec![0, 30]);
+		assert!(client_session(&entries, Some(3)).is_none());
+
+		let seen = Arc::new(Mutex::new(Vec::new()));
+		let upstream = pong_server(Arc::clone(&seen));
+		let buffer = Buffer::default();
+		let config = ReplayConfig { no_wait: false, settle: Duration::from_millis(200) };
+		let started = Instant::now();
+		let received = replay(&sent, &upstream, &config, Box::new(buffer.clone())).unwrap();
+		assert!(started.elapsed() >= Duration::from_millis(30));
+		assert_eq!(received, 2);
+		assert_eq!(*seen.lock().unwrap(), sent.iter().map(|(_, m)| m.clone()).collect::<Vec<_>>());
+		assert_eq!(buffer.entries().iter().filter(|e| e.from == Side::Server).count(), 2);
+	}
+}
@@ -0,0 +1,286 @@
+//! The server's own record of every game, for settling disputes: "I
+//! raised, the client showed fold". Each game a server starts gets
+//! `<table>/<game>.jsonl` under the recordings directory, one entry per
+//! line: every event the engine sent, unfiltered and so with everyone's
+//! hole cards, every action the server received from a player, and table
+//! chat. Entries are chained by checksum like the bank's audit log, so an
+//! edited, dropped or reordered line shows. `poker admin export-hand`
+//! pulls out one hand's entries and checks the chain they came from.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::bank::audit::{fnv1a, GENESIS};
+use crate::error::PokerError;
+use crate::events::{GameEvent, GameId, HandId, PlayerAction, Seat};
+
+/// What happened.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recorded {
+	/// An event from the engine or a player's chat, as it was before any
+	/// filtering for the players.
+	Event(GameEvent),
+	/// An action as it arrived from the player at `seat`, before the
+	/// engine checked it.
+	Received { seat: Seat, action: PlayerAction },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordEntry {
+	pub seq: u64,
+	/// Local time, RFC 3339.
+	pub at: String,
+	/// The hand in play; unset between hands.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub hand: Option<HandId>,
+	pub record: Recorded,
+	/// The previous entry's `hash`.
+	pub prev: String,
+	pub hash: String,
+}
+
+impl RecordEntry {
+	fn checksum(&self) -> String {
+		let unsigned = RecordEntry { hash: String::new(), ..self.clone() };
+		let json = serde_json::to_string(&unsigned).unwrap_or_default();
+		format!("{:016x}", fnv1a(json.as_bytes()))
+	}
+}
+
+/// Where recordings are kept: a directory per table.
+#[derive(Debug, Clone)]
+pub struct Recordings {
+	dir: PathBuf,
+}
+
+impl Default for Recordings {
+	/// `recordings/` in the data directory, beside `history/`.
+	fn default() -> Self {
+		match dirs::data_dir() {
+			Some(data_dir) => Self::new(data_dir.join("transparent-poker").join("recordings")),
+			None => Self::new("recordings"),
+		}
+	}
+}
+
+impl Recordings {
+	pub fn new(dir: impl Into<PathBuf>) -> Self {
+		Self { dir: dir.into() }
+	}
+
+	pub fn dir(&self) -> &Path {
+		&self.dir
+	}
+
+	pub fn path(&self, table_id: &str, game_id: GameId) -> PathBuf {
+		self.dir.join(table_id).join(format!("{:016x}.jsonl", game_id.0))
+	}
+
+	/// Starts recording a game.
+	pub fn start(&self, table_id: &str, game_id: GameId) -> Result<GameRecording, PokerError> {
+		let path = self.path(table_id, game_id);
+		if let Some(dir) = path.parent() {
+			fs::create_dir_all(dir).map_err(|e| PokerError::io("create", dir, e))?;
+		}
+		let file = OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(&path)
+			.map_err(|e| PokerError::io("open", &path, e))?;
+		Ok(GameRecording { path, file: BufWriter::new(file), seq: 0, last: GENESIS.to_string(), hand: None })
+	}
+
+	/// Every game's recording, across the tables.
+	fn games(&self) -> Result<Vec<PathBuf>, PokerError> {
+		let tables = fs::read_dir(&self.dir).map_err(|e| PokerError::io("read", &self.dir, e))?;
+		let mut games = Vec::new();
+		for table in tables.flatten() {
+			if let Ok(entries) = fs::read_dir(table.path()) {
+				games.extend(entries.flatten().map(|game| game.path()));
+			}
+		}
+		Ok(games)
+	}
+
+	/// Finds the hand in any table's recordings.
+	pub fn find_hand(&self, hand: HandId) -> Result<HandRecord, PokerError> {
+		let needle = format!("\"hand\":{},", hand.0);
+		for path in self.games()? {
+			let found = fs::read_to_string(&path).is_ok_and(|content| content.contains(&needle));
+			if found {
+				return HandRecord::read(&path, hand);
+			}
+		}
+		Err(PokerError::NotFound(format!("Hand {} isn't in the recordings in {}", hand.0, self.dir.display())))
+	}
+
+	/// Finds a hand by the reference players see, like `56789abc-3`: the
+	/// game whose id ends in the session, and its hand with that number.
+	pub fn find_hand_ref(&self, session: &str, hand_num: u32) -> Result<HandRecord, PokerError> {
+		let suffix = format!("{}.jsonl", session.to_lowercase());
+		for path in self.games()? {
+			let named = path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.ends_with(&suffix));
+			if !named {
+				continue;
+			}
+			let hand = read_entries(&path)?.into_iter().find_map(|entry| match entry.record {
+				Recorded::Event(GameEvent::HandStarted { hand_id, hand_num: n, .. }) if n == hand_num => Some(hand_id),
+				_ => None,
+			});
+			if let Some(hand) = hand {
+				return HandRecord::read(&path, hand);
+			}
+		}
+		Err(PokerError::NotFound(format!("Hand {}-{} isn't in the recordings in {}", session, hand_num, self.dir.display())))
+	}
+}
+
+/// The end of a game's chain, ready for the next entry.
+pub struct GameRecording {
+	path: PathBuf,
+	file: BufWriter<File>,
+	seq: u64,
+	last: String,
+	hand: Option<HandId>,
+}
+
+/// A recording shared by the event forwarder and the connections feeding
+/// the game actions and chat.
+pub type SharedRecording = Arc<Mutex<GameRecording>>;
+
+impl GameRecording {
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	pub fn record(&mut self, record: Recorded) -> Result<(), PokerError> {
+		if let Recorded::Event(GameEvent::HandStarted { hand_id, .. }) = &record {
+			self.hand = Some(*hand_id);
+		}
+		let ends_hand = matches!(record, Recorded::Event(GameEvent::HandEnded { .. }));
+		let mut entry = RecordEntry {
+			seq: self.seq + 1,
+			at: Local::now().to_rfc3339(),
+			hand: self.hand,
+			record,
+			prev: self.last.clone(),
+			hash: String::new(),
+		};
+		entry.hash = entry.checksum();
+		if ends_hand {
+			self.hand = None;
+		}
+		let line = serde_json::to_string(&entry).map_err(|e| PokerError::parse("record entry", e))?;
+		writeln!(self.file, "{}", line)
+			.and_then(|_| self.file.flush())
+			.map_err(|e| PokerError::io("write", &self.path, e))?;
+		self.seq = entry.seq;
+		self.last = entry.hash;
+		Ok(())
+	}
+}
+
+/// One hand pulled out of a recording.
+#[derive(Debug, Clone)]
+pub struct HandRecord {
+	/// The game's recording.
+	pub path: PathBuf,
+	pub entries: Vec<RecordEntry>,
+	/// Breaks anywhere in the game's chain; none means the recording is as
+	/// the server wrote it.
+	pub problems: Vec<String>,
+}
+
+impl HandRecord {
+	pub fn read(path: &Path, hand: HandId) -> Result<Self, PokerError> {
+		let entries = read_entries(path)?;
+		let problems = verify(&entries);
+		let entries = entries.into_iter().filter(|e| e.hand == Some(hand)).collect::<Vec<_>>();
+		if entries.is_empty() {
+			return Err(PokerError::NotFound(format!("Hand {} isn't in {}", hand.0, path.display())));
+		}
+		Ok(Self { path: path.to_path_buf(), entries, problems })
+	}
+}
+
+pub fn read_entries(path: &Path) -> Result<Vec<RecordEntry>, PokerError> {
+	let content = fs::read_to_string(path).map_err(|e| PokerError::io("read", path, e))?;
+	content
+		.lines()
+		.filter(|line| !line.trim().is_empty())
+		.enumerate()
+		.map(|(i, line)| serde_json::from_str(line).map_err(|e| PokerError::parse(format!("{} line {}", path.display(), i + 1), e)))
+		.collect()
+}
+
+/// Walks the chain and says where it breaks.
+pub fn verify(entries: &[RecordEntry]) -> Vec<String> {
+	let mut problems = Vec::new();
+	let mut prev = GENESIS;
+	let mut last_seq = 0;
+	for entry in entries {
+		if entry.seq != last_seq + 1 {
+			problems.push(format!("entry {}: expected entry {} next", entry.seq, last_seq + 1));
+		}
+		if entry.prev != prev {
+			problems.push(format!("entry {}: doesn't follow on from the entry before", entry.seq));
+		}
+		if entry.hash != entry.checksum() {
+			problems.push(format!("entry {}: checksum doesn't match its contents", entry.seq));
+		}
+		prev = &entry.hash;
+		last_seq = entry.seq;
+	}
+	problems
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{Blinds, ChatSender};
+
+	fn hand_started(hand: u64) -> GameEvent {
+		let blinds = Blinds { small: 1.0, big: 2.0, ante: None };
+		GameEvent::HandStarted { hand_id: HandId(hand), hand_num: (hand - 40) as u32, button: Seat(0), blinds, seats: Vec::new(), at: None, game_id: None }
+	}
+
+	#[test]
+	fn test_hand_is_found_and_tampering_shows() {
+		let dir = std::env::temp_dir().join(format!("poker-recordings-{}", std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		let recordings = Recordings::new(&dir);
+		let mut recording = recordings.start("home", GameId(7)).unwrap();
+
+		recording.record(Recorded::Event(hand_started(41))).unwrap();
+		recording.record(Recorded::Event(GameEvent::HandEnded { hand_id: HandId(41), results: Vec::new(), at: None })).unwrap();
+		recording.record(Recorded::Event(hand_started(42))).unwrap();
+		recording.record(Recorded::Received { seat: Seat(1), action: PlayerAction::Fold }).unwrap();
+		let chat = GameEvent::ChatMessage { sender: ChatSender::Player(Seat(1)), text: "I raised!".into() };
+		recording.record(Recorded::Event(chat)).unwrap();
+
+		let record = recordings.find_hand(HandId(42)).unwrap();
+		assert_eq!(record.path, recordings.path("home", GameId(7)));
+		assert_eq!(record.entries.len(), 3);
+		assert_eq!(recordings.find_hand_ref("00000007", 2).unwrap().entries.len(), 3);
+		assert_eq!(recordings.find_hand_ref("00000007", 1).unwrap().entries.len(), 2);
+		assert!(recordings.find_hand_ref("00000008", 2).is_err());
+		assert!(recordings.find_hand_ref("00000007", 3).is_err());
+		assert!(matches!(record.entries[1].record, Recorded::Received { seat: Seat(1), action: PlayerAction::Fold }));
+		assert!(record.problems.is_empty(), "{:?}", record.problems);
+		assert!(recordings.find_hand(HandId(43)).is_err());
+
+		// The fold becomes a raise
+		let path = recording.path().to_path_buf();
+		let content = fs::read_to_string(&path).unwrap().replace("\"Fold\"", "{\"Raise\":{\"amount\":10.0}}");
+		fs::write(&path, content).unwrap();
+		let record = recordings.find_hand(HandId(42)).unwrap();
+		assert_eq!(record.problems, vec!["entry 4: checksum doesn't match its contents".to_string()]);
+		let _ = fs::remove_dir_all(&dir);
+	}
+}
@@ -0,0 +1,316 @@
+//! Relay mode, for home games over the internet without port forwarding.
+//! The relay (`poker relay`) is the only machine that needs a reachable
+//! port: the host's server and its guests all dial out to it.
+//!
+//! Every connection opens with one line of text, then carries the game
+//! protocol untouched:
+//!
+//! - `HOST <room>` from the host claims a room and stays open as its
+//!   control line. For each guest the relay sends `CONNECT <id>` down it;
+//!   the host sends `PING` now and then to keep it alive.
+//! - `JOIN <room>` from a guest waits for the host to pick it up.
+//! - `ACCEPT <room> <id>` from the host opens the data connection for
+//!   guest `id`; the host pipes it into its own server.
+//!
+//! The relay answers `OK` or `ERR <reason>`, and once a guest and its data
+//! connection are paired it just copies bytes between them.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+const MAX_ROOM_LENGTH: usize = 64;
+const MAX_LINE_LENGTH: usize = 128;
+/// How long a new connection has to say what it's for.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often the host pings its control line, so home routers don't
+/// forget an idle connection.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+struct Room {
+	control: TcpStream,
+	/// Guests waiting for the host to accept them, by id.
+	waiting: HashMap<u64, TcpStream>,
+}
+
+type Rooms = Arc<Mutex<HashMap<String, Room>>>;
+
+fn lock_rooms(rooms: &Mutex<HashMap<String, Room>>) -> MutexGuard<'_, HashMap<String, Room>> {
+	rooms.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Hello {
+	Host(String),
+	Join(String),
+	Accept(String, u64),
+}
+
+fn parse_hello(line: &str) -> Result<Hello, String> {
+	let parts = line.split_whitespace().collect::<Vec<_>>();
+	let room = |name: &str| -> Result<String, String> {
+		if name.len() > MAX_ROOM_LENGTH {
+			return Err(format!("Room name too long (max {} characters)", MAX_ROOM_LENGTH));
+		}
+		Ok(name.to_string())
+	};
+	match parts[..] {
+		["HOST", name] => Ok(Hello::Host(room(name)?)),
+		["JOIN", name] => Ok(Hello::Join(room(name)?)),
+		["ACCEPT", name, id] => {
+			let id = id.parse().map_err(|_| format!("Bad guest id '{}'", id))?;
+			Ok(Hello::Accept(room(name)?, id))
+		}
+		_ => Err("Expected HOST, JOIN or ACCEPT".to_string()),
+	}
+}
+
+/// Reads one `\n`-terminated line a byte at a time, so nothing after it
+/// is taken off the stream.
+fn read_line(stream: &mut TcpStream) -> io::Result<String> {
+	let mut line = Vec::new();
+	let mut byte = [0u8; 1];
+	loop {
+		if stream.read(&mut byte)? == 0 {
+			return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Connection closed"));
+		}
+		match byte[0] {
+			b'\n' => break,
+			b => line.push(b),
+		}
+		if line.len() > MAX_LINE_LENGTH {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "Line too long"));
+		}
+	}
+	String::from_utf8(line)
+		.map(|s| s.trim_end_matches('\r').to_string())
+		.map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Line isn't UTF-8"))
+}
+
+fn send_line(stream: &mut TcpStream, line: &str) -> io::Result<()> {
+	stream.write_all(format!("{}\n", line).as_bytes())
+}
+
+/// Sends `hello` and waits for the relay's `OK`.
+fn handshake(stream: &mut TcpStream, hello: &str) -> io::Result<()> {
+	send_line(stream, hello)?;
+	let reply = read_line(stream)?;
+	match reply.strip_prefix("ERR ") {
+		Some(reason) => Err(io::Error::other(format!("Relay refused: {}", reason))),
+		None if reply == "OK" => Ok(()),
+		None => Err(io::Error::other(format!("Unexpected reply from relay: {}", reply))),
+	}
+}
+
+/// Copies bytes both ways until either side hangs up.
+fn pipe(a: TcpStream, b: TcpStream) -> io::Result<()> {
+	let (mut a_read, mut b_write) = (a.try_clone()?, b.try_clone()?);
+	let forward = thread::spawn(move || {
+		let _ = io::copy(&mut a_read, &mut b_write);
+		let _ = b_write.shutdown(Shutdown::Both);
+	});
+	let (mut b_read, mut a_write) = (b, a);
+	let _ = io::copy(&mut b_read, &mut a_write);
+	let _ = a_write.shutdown(Shutdown::Both);
+	let _ = forward.join();
+	Ok(())
+}
+
+/// The relay server: pairs guests with their host's data connections.
+#[derive(Default)]
+pub struct Relay {
+	rooms: Rooms,
+	next_guest: Arc<Mutex<u64>>,
+}
+
+impl Relay {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn run(&self, addr: &str) -> io::Result<()> {
+		let listener = TcpListener::bind(addr)?;
+		println!("Relay listening on {}", addr);
+		self.run_with_listener(listener);
+		Ok(())
+	}
+
+	pub fn run_with_listener(&self, listener: TcpListener) {
+		for stream in listener.incoming() {
+			match stream {
+				Ok(stream) => {
+					let rooms = Arc::clone(&self.rooms);
+					let next_guest = Arc::clone(&self.next_guest);
+					thread::spawn(move || {
+						if let Err(e) = handle_relay_connection(stream, rooms, next_guest) {
+							if e.kind() != io::ErrorKind::UnexpectedEof {
+								eprintln!("Relay connection ended: {}", e);
+							}
+						}
+					});
+				}
+				Err(e) => eprintln!("Connection failed: {}", e),
+			}
+		}
+	}
+}
+
+fn handle_relay_connection(mut stream: TcpStream, rooms: Rooms, next_guest: Arc<Mutex<u64>>) -> io::Result<()> {
+	stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+	let line = read_line(&mut stream)?;
+	stream.set_read_timeout(None)?;
+	let hello = match parse_hello(&line) {
+		Ok(hello) => hello,
+		Err(reason) => return send_line(&mut stream, &format!("ERR {}", reason)),
+	};
+
+	match hello {
+		Hello::Host(name) => {
+			{
+				let mut rooms = lock_rooms(&rooms);
+				if rooms.contains_key(&name) {
+					drop(rooms);
+					return send_line(&mut stream, &format!("ERR Room '{}' is taken", name));
+				}
+				let control = stream.try_clone()?;
+				rooms.insert(name.clone(), Room { control, waiting: HashMap::new() });
+			}
+			send_line(&mut stream, "OK")?;
+			println!("Room '{}' opened", name);
+			// Nothing but pings come up the control line, so reading only
+			// waits for the host to hang up
+			let mut sink = [0u8; 64];
+			while matches!(stream.read(&mut sink), Ok(n) if n > 0) {}
+			lock_rooms(&rooms).remove(&name);
+			println!("Room '{}' closed", name);
+			Ok(())
+		}
+		Hello::Join(name) => {
+			let id = {
+				let mut next = next_guest.lock().unwrap_or_else(|e| e.into_inner());
+				*next += 1;
+				*next
+			};
+			let mut rooms = lock_rooms(&rooms);
+			let Some(room) = rooms.get_mut(&name) else {
+				drop(rooms);
+				return send_line(&mut stream, &format!("ERR No room '{}'", name));
+			};
+			send_line(&mut room.control, &format!("CONNECT {}", id))?;
+			room.waiting.insert(id, stream);
+			Ok(())
+		}
+		Hello::Accept(name, id) => {
+			let guest = lock_rooms(&rooms).get_mut(&name).and_then(|room| room.waiting.remove(&id));
+			let Some(mut guest) = guest else {
+				return send_line(&mut stream, &format!("ERR No guest {} waiting in '{}'", id, name));
+			};
+			send_line(&mut stream, "OK")?;
+			send_line(&mut guest, "OK")?;
+			pipe(guest, stream)
+		}
+	}
+}
+
+/// Opens `room` on the relay and forwards each guest to the server at
+/// `server_addr`. Returns when the relay drops the control line.
+pub fn host(relay_addr: &str, room: &str, server_addr: &str) -> io::Result<()> {
+	let mut control = TcpStream::connect(relay_addr)?;
+	handshake(&mut control, &format!("HOST {}", room))?;
+	println!("Hosting room '{}' on relay {}", room, relay_addr);
+
+	let mut pinger = control.try_clone()?;
+	thread::spawn(move || {
+		while send_line(&mut pinger, "PING").is_ok() {
+			thread::sleep(KEEPALIVE_INTERVAL);
+		}
+	});
+
+	loop {
+		let line = read_line(&mut control)?;
+		let Some(id) = line.strip_prefix("CONNECT ") else {
+			continue;
+		};
+		let (relay_addr, room, server_addr, id) = (relay_addr.to_string(), room.to_string(), server_addr.to_string(), id.to_string());
+		thread::spawn(move || {
+			let forwarded = TcpStream::connect(&relay_addr).and_then(|mut data| {
+				handshake(&mut data, &format!("ACCEPT {} {}", room, id))?;
+				let server = TcpStream::connect(&server_addr)?;
+				pipe(data, server)
+			});
+			if let Err(e) = forwarded {
+				eprintln!("Couldn't forward guest {}: {}", id, e);
+			}
+		});
+	}
+}
+
+/// Joins `room` on the relay. Once this returns, the stream talks to the
+/// host's server as if it had connected directly.
+pub fn join(relay_addr: &str, room: &str) -> io::Result<TcpStream> {
+	let mut stream = TcpStream::connect(relay_addr)?;
+	handshake(&mut stream, &format!("JOIN {}", room))?;
+	Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_hello_lines_parse() {
+		assert_eq!(parse_hello("HOST friday"), Ok(Hello::Host("friday".into())));
+		assert_eq!(parse_hello("JOIN friday"), Ok(Hello::Join("friday".into())));
+		assert_eq!(parse_hello("ACCEPT friday 7"), Ok(Hello::Accept("friday".into(), 7)));
+		assert!(parse_hello("ACCEPT friday seven").is_err());
+		assert!(parse_hello("HOST").is_err());
+		assert!(parse_hello(&format!("JOIN {}", "x".repeat(MAX_ROOM_LENGTH + 1))).is_err());
+	}
+
+	#[test]
+	fn test_guest_reaches_the_hosts_server() {
+		// An echo server stands in for the game server
+		let server = TcpListener::bind("127.0.0.1:0").unwrap();
+		let server_addr = server.local_addr().unwrap().to_string();
+		thread::spawn(move || {
+			for stream in server.incoming().flatten() {
+				thread::spawn(move || {
+					let mut reader = stream.try_clone().unwrap();
+					let mut writer = stream;
+					let _ = io::copy(&mut reader, &mut writer);
+				});
+			}
+		});
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let relay_addr = listener.local_addr().unwrap().to_string();
+		thread::spawn(move || Relay::new().run_with_listener(listener));
+
+		let host_relay = relay_addr.clone();
+		thread::spawn(move || host(&host_relay, "friday", &server_addr));
+
+		// Retry until the host has opened the room
+		let mut guest = (0..50)
+			.find_map(|_| {
+				join(&relay_addr, "friday").ok().or_else(|| {
+					thread::sleep(Duration::from_millis(20));
+					None
+				})
+			})
+			.expect("guest couldn't join");
+		guest.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+		guest.write_all(b"\xfe\xffhello").unwrap();
+		let mut echoed = [0u8; 7];
+		guest.read_exact(&mut echoed).unwrap();
+		assert_eq!(&echoed, b"\xfe\xffhello");
+
+		let err = join(&relay_addr, "saturday").unwrap_err();
+		assert!(err.to_string().contains("No room 'saturday'"), "{}", err);
+		let mut taken = TcpStream::connect(&relay_addr).unwrap();
+		let err = handshake(&mut taken, "HOST friday").unwrap_err();
+		assert!(err.to_string().contains("taken"), "{}", err);
+	}
+}
@@ -1,39 +1,88 @@
-use std::sync::{mpsc, Mutex};
-use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
+use tokio::sync::{mpsc, Mutex};
 
 use crate::events::{GameEvent, PlayerAction, Seat, ValidActions};
-use crate::players::{GameSnapshot, PlayerPort, PlayerResponse};
+use crate::players::{GameSnapshot, PlayerPort, PlayerResponse, RulesPlayer};
+
+/// Seats whose client has dropped, and since when. The server marks seats
+/// away and back; their `RemotePlayer`s read it to decide who's playing.
+pub type AwaySeats = Arc<std::sync::Mutex<HashMap<Seat, Instant>>>;
+
+struct Takeover {
+	stand_in: RulesPlayer,
+	grace: Duration,
+	away: AwaySeats,
+}
+
+impl Takeover {
+	/// How much longer the seat waits for its player, if they're away.
+	fn grace_left(&self, seat: Seat) -> Option<Duration> {
+		let away = self.away.lock().unwrap_or_else(|e| e.into_inner());
+		away.get(&seat).map(|since| self.grace.saturating_sub(since.elapsed()))
+	}
+}
 
 pub struct RemotePlayer {
 	seat: Seat,
 	name: String,
-	action_rx: Mutex<mpsc::Receiver<PlayerAction>>,
+	action_rx: Mutex<mpsc::UnboundedReceiver<PlayerAction>>,
+	auto_muck: Arc<AtomicBool>,
+	takeover: Option<Takeover>,
 }
 
 impl RemotePlayer {
-	pub fn new(seat: Seat, name: String, action_rx: mpsc::Receiver<PlayerAction>) -> Self {
+	pub fn new(seat: Seat, name: String, action_rx: mpsc::UnboundedReceiver<PlayerAction>) -> Self {
 		Self {
 			seat,
 			name,
 			action_rx: Mutex::new(action_rx),
+			auto_muck: Arc::new(AtomicBool::new(false)),
+			takeover: None,
 		}
 	}
+
+	/// Shares the client's auto-muck preference, which can change mid-game.
+	pub fn with_auto_muck(mut self, auto_muck: Arc<AtomicBool>) -> Self {
+		self.auto_muck = auto_muck;
+		self
+	}
+
+	/// Once the seat has been away for `grace`, `stand_in` plays it until
+	/// the player is back.
+	pub fn with_takeover(mut self, stand_in: RulesPlayer, grace: Duration, away: AwaySeats) -> Self {
+		self.takeover = Some(Takeover { stand_in, grace, away });
+		self
+	}
 }
 
 #[async_trait]
 impl PlayerPort for RemotePlayer {
 	async fn request_action(
 		&self,
-		_seat: Seat,
-		_valid_actions: ValidActions,
-		_game_state: &GameSnapshot,
+		seat: Seat,
+		valid_actions: ValidActions,
+		game_state: &GameSnapshot,
 	) -> PlayerResponse {
-		let rx = self.action_rx.lock().unwrap_or_else(|e| e.into_inner());
-		match rx.recv_timeout(Duration::from_secs(120)) {
-			Ok(action) => PlayerResponse::Action(action),
-			Err(_) => PlayerResponse::Timeout,
+		if let Some(takeover) = &self.takeover {
+			if let Some(left) = takeover.grace_left(self.seat) {
+				// Give them what's left of the grace period to come back
+				let waited = tokio::time::timeout(left, self.action_rx.lock().await.recv()).await;
+				return match waited {
+					Ok(Some(action)) => PlayerResponse::Action(action),
+					Ok(None) => PlayerResponse::Timeout,
+					Err(_) => takeover.stand_in.request_action(seat, valid_actions, game_state).await,
+				};
+			}
+		}
+		// The runner's action timeout bounds this wait.
+		match self.action_rx.lock().await.recv().await {
+			Some(action) => PlayerResponse::Action(action),
+			None => PlayerResponse::Timeout,
 		}
 	}
 
@@ -41,6 +90,14 @@ impl PlayerPort for RemotePlayer {
 		// Events forwarded via event_rx stream, not per-player notify
 	}
 
+	fn auto_muck(&self) -> bool {
+		self.auto_muck.load(Ordering::Relaxed)
+	}
+
+	fn asks_to_show(&self) -> bool {
+		true
+	}
+
 	fn seat(&self) -> Seat {
 		self.seat
 	}
@@ -53,3 +110,67 @@ impl PlayerPort for RemotePlayer {
 		true
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{Blinds, Street};
+	use crate::strategy::Strategy;
+
+	fn ask(player: &RemotePlayer) -> PlayerResponse {
+		let valid = ValidActions {
+			can_fold: false,
+			can_check: true,
+			call_amount: None,
+			raise_options: None,
+			can_all_in: true,
+			all_in_amount: 100.0,
+			can_show: false,
+		};
+		let snapshot = GameSnapshot {
+			hand_num: 1,
+			street: Street::Flop,
+			board: vec![],
+			pot: 20.0,
+			pots: vec![],
+			effective_stack: 100.0,
+			seats: vec![],
+			hero_seat: player.seat(),
+			hero_cards: None,
+			button: Seat(1),
+			blinds: Blinds { small: 5.0, big: 10.0, ante: None },
+			current_bet: 0.0,
+			min_raise: 10.0,
+			raises_this_street: 0,
+			max_raises: 4,
+			valid_actions: valid.clone(),
+			action_history: vec![],
+		};
+		tokio::runtime::Builder::new_current_thread()
+			.enable_time()
+			.build()
+			.unwrap()
+			.block_on(player.request_action(player.seat(), valid, &snapshot))
+	}
+
+	#[test]
+	fn test_stand_in_plays_while_away_past_grace() {
+		let (action_tx, action_rx) = mpsc::unbounded_channel();
+		let away: AwaySeats = Arc::default();
+		let stand_in = RulesPlayer::new(Seat(0), "Ann", Strategy::default());
+		let player = RemotePlayer::new(Seat(0), "Ann".into(), action_rx)
+			.with_takeover(stand_in, Duration::ZERO, Arc::clone(&away));
+
+		action_tx.send(PlayerAction::Bet { amount: 10.0 }).unwrap();
+		assert!(matches!(ask(&player), PlayerResponse::Action(PlayerAction::Bet { .. })));
+
+		// Away with no grace left: the stand-in acts instead of waiting
+		away.lock().unwrap().insert(Seat(0), Instant::now());
+		assert!(matches!(ask(&player), PlayerResponse::Action(_)));
+
+		// Back again, so their own action counts
+		away.lock().unwrap().clear();
+		action_tx.send(PlayerAction::Check).unwrap();
+		assert!(matches!(ask(&player), PlayerResponse::Action(PlayerAction::Check)));
+	}
+}
@@ -1,17 +1,39 @@
 use std::collections::HashMap;
 use std::io::{Read, Write};
-use std::net::{TcpListener, TcpStream};
-use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::net::{IpAddr, Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread;
+use std::time::{Duration, Instant};
 
+use chrono::Local;
+use tokio::sync::mpsc;
+
+use crate::ai::think_time;
 use crate::bank::Bank;
+use crate::bank::audit::AuditRef;
+use crate::challenges::HandOutcome;
 use crate::config::{load_players_auto, load_strategies_auto, PlayerConfig};
-use crate::engine::{BettingStructure, GameRunner, RunnerConfig};
-use crate::events::{Card, GameEvent, LeaveReason, PlayerAction, Seat};
+use crate::engine::{Arrivals, EventQueueConfig, GameRunner, Pause, RunnerConfig};
+use crate::events::redaction::{redact, Audience};
+use crate::events::{ChatSender, GameEvent, GameId, HandId, LeaveReason, PlayerAction, Promotion, Seat};
+use crate::feed::ObserverFeed;
+use crate::history::shuffle::ShuffleTally;
+use crate::history::{HandRecorder, HistoryLog};
+use crate::league::Leagues;
+use crate::lineup;
+use crate::logging;
+use crate::money::money;
+use crate::net::discovery::Advertisement;
+use crate::net::moderation::Moderation;
 use crate::net::protocol::*;
-use crate::net::remote_player::RemotePlayer;
-use crate::players::RulesPlayer;
-use crate::table::{load_tables, TableConfig};
+use crate::net::recording::{Recorded, Recordings, SharedRecording};
+use crate::net::server_config::{Limits, ServerConfig};
+use crate::net::remote_player::{AwaySeats, RemotePlayer};
+use crate::pit_boss::{self, PitBoss, PitBossConfig, PitBossHandle};
+use crate::players::{DecisionPool, PooledPlayer, RulesPlayer};
+use crate::promotions::Promotions;
+use crate::strategy::Tempo;
+use crate::table::{load_tables, load_tables_from, Pacing, TableConfig};
 
 type ConnectionId = u64;
 
@@ -24,6 +46,7 @@ type ConnectionId = u64;
 //   3. bank
 //
 // NEVER acquire `connections` before `tables`, or `bank` before either.
+// `moderation` is never held together with any of them.
 // When possible, release earlier locks before acquiring later ones.
 // =============================================================================
 
@@ -39,10 +62,17 @@ fn lock_bank(bank: &Mutex<Bank>) -> MutexGuard<'_, Bank> {
 	bank.lock().unwrap_or_else(|e| e.into_inner())
 }
 
+fn lock_moderation(moderation: &Mutex<Moderation>) -> MutexGuard<'_, Moderation> {
+	moderation.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 struct Connection {
 	username: Option<String>,
 	stream: TcpStream,
 	current_table: Option<String>,
+	/// The peer's address, for IP bans. Relayed guests all show as the
+	/// host's own.
+	ip: Option<IpAddr>,
 }
 
 impl Connection {
@@ -58,12 +88,55 @@ impl Connection {
 
 use std::sync::atomic::{AtomicBool, Ordering};
 
+type SeatStreams = Vec<(Seat, Arc<Mutex<TcpStream>>)>;
+/// Shared with the game's relay thread, which picks up players who join
+/// mid-game.
+type SharedStreams = Arc<Mutex<SeatStreams>>;
+/// What to do with a departing stack instead of cashing it out, by seat.
+type Migrations = Arc<Mutex<HashMap<Seat, Box<dyn FnOnce(f32) + Send>>>>;
+/// Action and street delays by seat; `None` keeps the table's.
+type Paces = Arc<Mutex<HashMap<Seat, (Option<u64>, Option<u64>)>>>;
+
 struct ActiveGame {
-	action_senders: HashMap<Seat, mpsc::Sender<PlayerAction>>,
+	action_senders: HashMap<Seat, mpsc::UnboundedSender<PlayerAction>>,
 	conn_to_seat: HashMap<ConnectionId, Seat>,
 	sitting_out: Arc<Mutex<std::collections::HashSet<Seat>>>,
 	game_finished: Arc<AtomicBool>,
 	quit_signal: Arc<AtomicBool>,
+	/// The game's own event streams, so chat can't interleave with events
+	streams: SharedStreams,
+	/// Bank ids by seat, for cashing out
+	bank_ids: Arc<Mutex<Vec<String>>>,
+	/// Seats to hand a departing stack to instead of the bank, for players
+	/// changing tables
+	migrations: Migrations,
+	leaving: Arc<Mutex<std::collections::HashSet<Seat>>>,
+	arrivals: Arrivals,
+	pit_boss: Option<PitBossHandle>,
+	/// Humans who asked to skip AI pauses
+	fast_forward: Arc<Mutex<std::collections::HashSet<Seat>>>,
+	/// Humans' own action and street delays, where they've set them
+	pacing: Paces,
+	auto_muck: HashMap<Seat, Arc<AtomicBool>>,
+	/// Humans by seat, so a dropped player can find their way back
+	names: HashMap<Seat, String>,
+	/// Disconnected humans whose seats are held for them
+	away: AwaySeats,
+	takeover_grace: Option<Duration>,
+	/// The server's own record of the game, for disputes
+	recording: Option<SharedRecording>,
+	/// The game its buy-ins are held under
+	game_id: Option<GameId>,
+	/// Relays events and settles the bank; gone without the game ending
+	/// means the game died
+	relay: Option<thread::JoinHandle<()>>,
+	/// Held by the host or from `poker admin pause`
+	pause: Pause,
+	/// Every human's seat is held through a dropped connection, for
+	/// `poker daemon`
+	hold_seats: bool,
+	/// Paused because every human was away, to go on when one is back
+	paused_while_away: bool,
 }
 
 impl ActiveGame {
@@ -78,17 +151,104 @@ impl ActiveGame {
 			sitting_out,
 			game_finished,
 			quit_signal,
+			streams: Arc::default(),
+			bank_ids: Arc::default(),
+			migrations: Arc::default(),
+			leaving: Arc::default(),
+			arrivals: Arc::default(),
+			pit_boss: None,
+			fast_forward: Arc::new(Mutex::new(std::collections::HashSet::new())),
+			pacing: Arc::default(),
+			auto_muck: HashMap::new(),
+			names: HashMap::new(),
+			away: Arc::default(),
+			takeover_grace: None,
+			recording: None,
+			game_id: None,
+			relay: None,
+			pause: Pause::default(),
+			hold_seats: false,
+			paused_while_away: false,
 		}
 	}
 
-	fn register_player(&mut self, conn_id: ConnectionId, seat: Seat, action_tx: mpsc::Sender<PlayerAction>) {
+	fn register_player(
+		&mut self,
+		conn_id: ConnectionId,
+		seat: Seat,
+		name: String,
+		action_tx: mpsc::UnboundedSender<PlayerAction>,
+		auto_muck: Arc<AtomicBool>,
+	) {
 		self.action_senders.insert(seat, action_tx);
 		self.conn_to_seat.insert(conn_id, seat);
+		self.names.insert(seat, name);
+		self.auto_muck.insert(seat, auto_muck);
+	}
+
+	/// Holds a dropped player's seat when the table allows a takeover or
+	/// holds every seat, returning the seat; otherwise they're removed as
+	/// usual. A held-seats game with nobody left connected is paused.
+	fn disconnect(&mut self, conn_id: ConnectionId) -> Option<Seat> {
+		if self.takeover_grace.is_none() && !self.hold_seats {
+			return None;
+		}
+		let seat = self.conn_to_seat.remove(&conn_id)?;
+		self.migrations.lock().unwrap_or_else(|e| e.into_inner()).remove(&seat);
+		let since = Instant::now();
+		self.away.lock().unwrap_or_else(|e| e.into_inner()).insert(seat, since);
+		if self.hold_seats && self.conn_to_seat.is_empty() && self.pause.set(true) {
+			self.paused_while_away = true;
+		}
+		let Some(grace) = self.takeover_grace else {
+			return Some(seat);
+		};
+
+		// Tell the table when the stand-in sits down
+		let away = Arc::clone(&self.away);
+		let streams = Arc::clone(&self.streams);
+		let sitting_out = Arc::clone(&self.sitting_out);
+		let name = self.names.get(&seat).cloned().unwrap_or_default();
+		thread::spawn(move || {
+			thread::sleep(grace);
+			let still_away = away.lock().unwrap_or_else(|e| e.into_inner()).get(&seat) == Some(&since);
+			if still_away {
+				let text = format!("An AI is playing for {} until they're back", name);
+				let event = GameEvent::ChatMessage { sender: ChatSender::Dealer, text };
+				send_to_seats(&streams, &sitting_out, &ServerMessage::GameEvent(event));
+			}
+		});
+		Some(seat)
+	}
+
+	/// Hands a held seat back to `username` on their new connection.
+	fn reconnect(&mut self, conn_id: ConnectionId, username: &str, stream: TcpStream) -> Option<Seat> {
+		let mut away = self.away.lock().unwrap_or_else(|e| e.into_inner());
+		let seat = *away.keys().find(|seat| {
+			self.names.get(seat).is_some_and(|name| name.eq_ignore_ascii_case(username))
+		})?;
+		away.remove(&seat);
+		drop(away);
+
+		self.conn_to_seat.insert(conn_id, seat);
+		let streams = self.streams.lock().unwrap_or_else(|e| e.into_inner());
+		if let Some((_, seat_stream)) = streams.iter().find(|(s, _)| *s == seat) {
+			*seat_stream.lock().unwrap_or_else(|e| e.into_inner()) = stream;
+		}
+		drop(streams);
+		let text = format!("{} is back", username);
+		let event = GameEvent::ChatMessage { sender: ChatSender::Dealer, text };
+		send_to_seats(&self.streams, &self.sitting_out, &ServerMessage::GameEvent(event));
+		if std::mem::take(&mut self.paused_while_away) {
+			self.pause.set(false);
+		}
+		Some(seat)
 	}
 
 	fn remove_player(&mut self, conn_id: ConnectionId) -> Option<Seat> {
 		if let Some(seat) = self.conn_to_seat.remove(&conn_id) {
 			self.action_senders.remove(&seat);
+			self.migrations.lock().unwrap_or_else(|e| e.into_inner()).remove(&seat);
 			self.sitting_out.lock().unwrap_or_else(|e| e.into_inner()).insert(seat);
 			Some(seat)
 		} else {
@@ -98,20 +258,70 @@ impl ActiveGame {
 
 	fn submit_action(&self, conn_id: ConnectionId, action: PlayerAction) -> Result<(), String> {
 		let seat = self.conn_to_seat.get(&conn_id).ok_or("Player not in game")?;
+		self.record(Recorded::Received { seat: *seat, action: action.clone() });
 		let tx = self.action_senders.get(seat).ok_or("No action channel for seat")?;
 		tx.send(action).map_err(|_| "Failed to send action".to_string())
 	}
 
+	/// Relays a player's chat line to the table and the pit boss.
+	fn chat(&self, conn_id: ConnectionId, text: String) -> Result<(), String> {
+		let seat = *self.conn_to_seat.get(&conn_id).ok_or("Player not in game")?;
+		if let Some(pit_boss) = &self.pit_boss {
+			pit_boss.chat(seat, &text);
+		}
+		let event = GameEvent::ChatMessage { sender: ChatSender::Player(seat), text };
+		self.record(Recorded::Event(event.clone()));
+		send_to_seats(&self.streams, &self.sitting_out, &ServerMessage::GameEvent(event));
+		Ok(())
+	}
+
+	fn record(&self, record: Recorded) {
+		if let Some(recording) = &self.recording {
+			if let Err(e) = recording.lock().unwrap_or_else(|e| e.into_inner()).record(record) {
+				logging::log("Recording", "ERROR", &e.to_string());
+			}
+		}
+	}
+
+	fn set_fast_forward(&self, conn_id: ConnectionId, enabled: bool) -> Result<(), String> {
+		let seat = *self.conn_to_seat.get(&conn_id).ok_or("Player not in game")?;
+		let mut fast_forward = self.fast_forward.lock().unwrap_or_else(|e| e.into_inner());
+		if enabled {
+			fast_forward.insert(seat);
+		} else {
+			fast_forward.remove(&seat);
+		}
+		Ok(())
+	}
+
+	fn set_pacing(&self, conn_id: ConnectionId, action_delay_ms: Option<u64>, street_delay_ms: Option<u64>) -> Result<(), String> {
+		let seat = *self.conn_to_seat.get(&conn_id).ok_or("Player not in game")?;
+		let mut pacing = self.pacing.lock().unwrap_or_else(|e| e.into_inner());
+		pacing.insert(seat, (action_delay_ms, street_delay_ms));
+		Ok(())
+	}
+
+	fn set_auto_muck(&self, conn_id: ConnectionId, enabled: bool) -> Result<(), String> {
+		let seat = self.conn_to_seat.get(&conn_id).ok_or("Player not in game")?;
+		let flag = self.auto_muck.get(seat).ok_or("No muck setting for seat")?;
+		flag.store(enabled, Ordering::Relaxed);
+		Ok(())
+	}
+
 	fn is_finished(&self) -> bool {
 		self.game_finished.load(Ordering::SeqCst)
+			|| self.relay.as_ref().is_some_and(|relay| relay.is_finished())
 	}
 
 	fn has_humans(&self) -> bool {
 		!self.conn_to_seat.is_empty()
+			|| !self.away.lock().unwrap_or_else(|e| e.into_inner()).is_empty()
 	}
 
+	/// Also lets a paused game go on, so it can see the signal and end.
 	fn signal_quit(&self) {
 		self.quit_signal.store(true, Ordering::SeqCst);
+		self.pause.set(false);
 	}
 }
 
@@ -119,23 +329,36 @@ struct AIPlayer {
 	id: String,
 	name: String,
 	strategy: String,
+	/// Buy-in set by the table's lineup, instead of the usual one.
+	stack: Option<f32>,
+	/// Bankroll when seated, shown in the lobby.
+	bankroll: f32,
 }
 
 struct GameStartInfo {
+	/// Chosen at buy-in, which is held in escrow under it
+	game_id: GameId,
 	config: TableConfig,
 	human_players: Vec<(ConnectionId, Seat, String, TcpStream)>, // conn_id, seat, username, stream
-	ai_players: Vec<(Seat, String, String, String)>, // seat, id, name, strategy
+	ai_players: Vec<(Seat, String, String, String, Option<f32>)>, // seat, id, name, strategy, stack
 	player_bank_ids: Vec<String>, // bank ids for all players in seat order
+	hold_seats: bool,
 }
 
 struct TableRoom {
 	config: TableConfig,
 	order: usize,
+	/// Games here hold their players' seats; see `GameServer::detachable`
+	hold_seats: bool,
 	players: HashMap<Seat, ConnectionId>,
 	ai_players: HashMap<Seat, AIPlayer>,
 	ready: HashMap<Seat, bool>,
 	status: TableStatus,
 	active_game: Option<ActiveGame>,
+	/// When a sit & go's registration clock runs out
+	registration_closes: Option<Instant>,
+	/// When someone last sat, stood or readied up, for the idle timeout
+	last_activity: Instant,
 }
 
 impl TableRoom {
@@ -143,14 +366,28 @@ impl TableRoom {
 		Self {
 			config,
 			order,
+			hold_seats: false,
 			players: HashMap::new(),
 			ai_players: HashMap::new(),
 			ready: HashMap::new(),
 			status: TableStatus::Waiting,
 			active_game: None,
+			registration_closes: None,
+			last_activity: Instant::now(),
 		}
 	}
 
+	/// Empties the table and puts it back to waiting for a new game.
+	fn reset(&mut self) {
+		self.status = TableStatus::Waiting;
+		self.players.clear();
+		self.ai_players.clear();
+		self.ready.clear();
+		self.active_game = None;
+		self.registration_closes = None;
+		self.last_activity = Instant::now();
+	}
+
 	fn player_count(&self) -> usize {
 		self.players.len() + self.ai_players.len()
 	}
@@ -168,6 +405,7 @@ impl TableRoom {
 	fn add_player(&mut self, seat: Seat, conn_id: ConnectionId) {
 		self.players.insert(seat, conn_id);
 		self.ready.insert(seat, false);
+		self.last_activity = Instant::now();
 	}
 
 	fn remove_player(&mut self, conn_id: ConnectionId) -> Option<Seat> {
@@ -177,6 +415,7 @@ impl TableRoom {
 		if let Some(s) = seat {
 			self.players.remove(&s);
 			self.ready.remove(&s);
+			self.last_activity = Instant::now();
 			if let Some(ref mut active_game) = self.active_game {
 				active_game.remove_player(conn_id);
 			}
@@ -184,14 +423,20 @@ impl TableRoom {
 		seat
 	}
 
-	fn add_ai(&mut self, seat: Seat, id: String, name: String, strategy: String) {
-		self.ai_players.insert(seat, AIPlayer { id, name, strategy });
+	fn add_ai(&mut self, seat: Seat, id: String, name: String, strategy: String, bankroll: f32) {
+		// A deep lineup stack is only as deep as the bankroll behind it
+		let stack = self.config.lineup.as_ref()
+			.and_then(|l| l.stack(&self.config, &mut rand::rng()))
+			.map(|stack| stack.min(bankroll).max(self.config.effective_buy_in()));
+		self.ai_players.insert(seat, AIPlayer { id, name, strategy, stack, bankroll });
 		self.ready.insert(seat, true); // AI is always ready
+		self.last_activity = Instant::now();
 	}
 
 	fn remove_ai(&mut self, seat: Seat) -> bool {
 		if self.ai_players.remove(&seat).is_some() {
 			self.ready.remove(&seat);
+			self.last_activity = Instant::now();
 			true
 		} else {
 			false
@@ -200,6 +445,23 @@ impl TableRoom {
 
 	fn set_ready(&mut self, seat: Seat) {
 		self.ready.insert(seat, true);
+		self.last_activity = Instant::now();
+	}
+
+	/// Starts the registration clock if the table has one and it isn't
+	/// already running, returning when it closes.
+	fn start_registration(&mut self) -> Option<Instant> {
+		if self.registration_closes.is_some() {
+			return None;
+		}
+		let closes = Instant::now() + self.config.registration_window()?;
+		self.registration_closes = Some(closes);
+		Some(closes)
+	}
+
+	fn registration_seconds_left(&self) -> Option<u32> {
+		self.registration_closes
+			.map(|closes| closes.saturating_duration_since(Instant::now()).as_secs_f32().ceil() as u32)
 	}
 
 	fn all_ready(&self) -> bool {
@@ -208,10 +470,6 @@ impl TableRoom {
 	}
 
 	fn to_info(&self) -> TableInfo {
-		let blinds = match (self.config.small_blind, self.config.big_blind) {
-			(Some(sb), Some(bb)) => format!("${:.0}/${:.0}", sb, bb),
-			_ => "N/A".to_string(),
-		};
 		let buy_in = self.config.effective_buy_in();
 		let player_count = self.player_count();
 		let is_joinable = self.config.is_joinable(player_count, &self.status);
@@ -220,8 +478,8 @@ impl TableRoom {
 			name: self.config.name.clone(),
 			format: self.config.format.to_string(),
 			betting: self.config.betting.to_string(),
-			blinds,
-			buy_in: format!("${:.0}", buy_in),
+			blinds: self.config.blinds_label(),
+			buy_in: money(buy_in),
 			players: player_count,
 			max_players: self.config.max_players,
 			status: self.status,
@@ -236,7 +494,7 @@ impl TableRoom {
 				.and_then(|c| c.username.clone())
 				.unwrap_or_else(|| "Unknown".to_string());
 			let ready = self.ready.get(&seat).copied().unwrap_or(false);
-			PlayerInfo { seat, username, ready, is_ai: false }
+			PlayerInfo { seat, username, ready, is_ai: false, bankroll: None }
 		}).collect();
 
 		for (&seat, ai) in &self.ai_players {
@@ -246,6 +504,7 @@ impl TableRoom {
 				username: ai.name.clone(),
 				ready,
 				is_ai: true,
+				bankroll: Some(ai.bankroll),
 			});
 		}
 
@@ -279,18 +538,95 @@ pub struct GameServer {
 	next_conn_id: Arc<Mutex<ConnectionId>>,
 	ai_roster: Arc<Vec<PlayerConfig>>,
 	bank: Arc<Mutex<Bank>>,
+	observers: Observers,
+	advertise: Option<String>,
+	limits: Limits,
+	moderation: Arc<Mutex<Moderation>>,
+	/// How long a table can sit with players and no game before it's cleared
+	idle_table: Option<Duration>,
+	/// The one player allowed to pause games, for `poker play`'s own server
+	host: Option<String>,
+}
+
+/// What watches every game the server starts, besides its players, and
+/// what the games share.
+#[derive(Clone, Default)]
+struct Observers {
+	feed: Option<ObserverFeed>,
+	history: Option<HistoryLog>,
+	recordings: Recordings,
+	/// How far a game may run ahead of the relay handing its events out.
+	event_queue: EventQueueConfig,
+	/// Where the AIs make their decisions.
+	decisions: DecisionPool,
+	/// Leagues whose scheduled sit & gos are recorded when they finish.
+	leagues: Leagues,
+}
+
+impl Default for GameServer {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 impl GameServer {
+	/// A server set up by `server.toml`, or the defaults if it can't be read.
 	pub fn new() -> Self {
-		let tables_config = load_tables().unwrap_or_default();
+		let config = ServerConfig::load().unwrap_or_else(|e| {
+			eprintln!("{}; using the defaults", e);
+			ServerConfig::default()
+		});
+		Self::from_config(&config)
+	}
+
+	pub fn from_config(server_config: &ServerConfig) -> Self {
+		logging::set_level(server_config.log_level);
+		if let Some(dir) = server_config.logs_dir() {
+			logging::set_dir(dir);
+		}
+
+		let tables_config = match server_config.tables_path() {
+			Some(path) => load_tables_from(&path),
+			None => load_tables(),
+		};
+		let tables_config = tables_config.unwrap_or_else(|e| {
+			eprintln!("{}", e);
+			Vec::new()
+		});
 		let mut tables = HashMap::new();
-		for (order, config) in tables_config.into_iter().enumerate() {
+		for (order, mut config) in tables_config.into_iter().enumerate() {
+			let timeouts = &server_config.timeouts;
+			config.action_timeout_seconds.get_or_insert(timeouts.action_seconds);
+			if config.takeover_grace_seconds.is_none() {
+				config.takeover_grace_seconds = timeouts.takeover_grace_seconds;
+			}
 			tables.insert(config.id.clone(), TableRoom::new(config, order));
 		}
 
 		let ai_roster = load_players_auto().unwrap_or_default();
-		let bank = Bank::load().expect("Failed to load bank - ensure config directory exists");
+		let bank = match server_config.bank_path() {
+			Some(path) => Bank::load_from(path),
+			None => Bank::load(),
+		};
+		let mut bank = bank.expect("Failed to load bank - ensure config directory exists");
+		// Buy-ins still held from games a crash cut short
+		let refunded = bank.refund_all_escrows();
+		if refunded > 0.0 {
+			logging::log("Server", "INFO", &format!("Refunded {} held from unfinished games", money(refunded)));
+		}
+		if refunded > 0.0 || pay_rakeback(&mut bank) {
+			if let Err(e) = bank.save() {
+				eprintln!("Failed to save bank: {}", e);
+			}
+		}
+
+		let leagues = Leagues::load().unwrap_or_else(|e| {
+			eprintln!("{}", e.user_message());
+			Leagues::default()
+		});
+
+		let moderation_path = server_config.moderation_path().unwrap_or_else(Moderation::default_path);
+		let moderation = Moderation::new(moderation_path);
 
 		Self {
 			connections: Arc::new(Mutex::new(HashMap::new())),
@@ -298,20 +634,119 @@ impl GameServer {
 			next_conn_id: Arc::new(Mutex::new(1)),
 			ai_roster: Arc::new(ai_roster),
 			bank: Arc::new(Mutex::new(bank)),
+			observers: Observers {
+				recordings: server_config.recordings_dir().map(Recordings::new).unwrap_or_default(),
+				event_queue: server_config.event_queue,
+				decisions: DecisionPool::new(server_config.ai.workers),
+				feed: None,
+				history: None,
+				leagues,
+			},
+			advertise: None,
+			limits: server_config.limits,
+			moderation: Arc::new(Mutex::new(moderation)),
+			idle_table: server_config.idle_table_timeout(),
+			host: None,
+		}
+	}
+
+	/// Sends every event of the most recently started game, unfiltered, to
+	/// an observer feed.
+	pub fn with_feed(mut self, feed: ObserverFeed) -> Self {
+		self.observers.feed = Some(feed);
+		self
+	}
+
+	/// Writes down every finished hand, for `poker export`.
+	pub fn with_history(mut self, history: HistoryLog) -> Self {
+		self.observers.history = Some(history);
+		self
+	}
+
+	/// Advertises the server on the local network as `name`, for
+	/// `poker play --discover`.
+	pub fn with_advertisement(mut self, name: &str) -> Self {
+		self.advertise = Some(name.to_string());
+		self
+	}
+
+	/// Lets `username` pause and resume games from their table.
+	pub fn with_host(mut self, username: &str) -> Self {
+		self.host = Some(username.to_string());
+		self
+	}
+
+	/// Keeps a player's seat when their connection drops, at every table,
+	/// and pauses a game once nobody's left connected to it; joining the
+	/// table again picks it back up. For `poker daemon`, whose players
+	/// come and go with their terminals.
+	pub fn detachable(self) -> Self {
+		for table in lock_tables(&self.tables).values_mut() {
+			table.hold_seats = true;
 		}
+		self
 	}
 
 	pub fn run(&self, addr: &str) -> std::io::Result<()> {
 		let listener = TcpListener::bind(addr)?;
 		println!("Poker server listening on {}", addr);
+		if let Some(name) = &self.advertise {
+			match Advertisement::start(name, listener.local_addr()?.port()) {
+				Ok(ad) => {
+					println!("Advertising as \"{}\" on the local network", name);
+					self.keep_advertising(ad);
+				}
+				Err(e) => eprintln!("{}", e),
+			}
+		}
 		self.run_with_listener(listener);
 		Ok(())
 	}
 
+	/// Keeps the advertised table and player counts current.
+	fn keep_advertising(&self, mut ad: Advertisement) {
+		let tables = Arc::clone(&self.tables);
+		let connections = Arc::clone(&self.connections);
+		thread::spawn(move || loop {
+			let table_count = lock_tables(&tables).len();
+			let players = lock_connections(&connections).values().filter(|c| c.username.is_some()).count();
+			if let Err(e) = ad.update(table_count, players) {
+				logging::log("Server", "WARN", &e.to_string());
+			}
+			thread::sleep(ADVERTISE_INTERVAL);
+		});
+	}
+
+	/// Cleans up finished games and idle tables as they happen, rather than
+	/// when someone next lists or joins tables.
+	fn keep_reaping(&self) {
+		let tables = Arc::clone(&self.tables);
+		let connections = Arc::clone(&self.connections);
+		let bank = Arc::clone(&self.bank);
+		let moderation = Arc::clone(&self.moderation);
+		let idle = self.idle_table;
+		thread::spawn(move || {
+			let mut held = std::collections::HashSet::new();
+			loop {
+				thread::sleep(REAP_INTERVAL);
+				reap(&tables, &connections, &bank, idle);
+				let listed = lock_moderation(&moderation).current().paused_tables.clone();
+				hold_paused_tables(&lock_tables(&tables), &listed, &mut held);
+			}
+		});
+	}
+
 	pub fn run_with_listener(&self, listener: TcpListener) {
+		self.keep_reaping();
 		for stream in listener.incoming() {
 			match stream {
-				Ok(stream) => {
+				Ok(mut stream) => {
+					if lock_connections(&self.connections).len() >= self.limits.max_connections {
+						let _ = stream.write_all(&encode_message(&ServerMessage::Error {
+							message: "The server is full, try again later".to_string(),
+						}));
+						continue;
+					}
 					let conn_id = {
 						let mut id = self.next_conn_id.lock().unwrap_or_else(|e| e.into_inner());
 						let current = *id;
@@ -323,9 +758,13 @@ impl GameServer {
 					let tables = Arc::clone(&self.tables);
 					let ai_roster = Arc::clone(&self.ai_roster);
 					let bank = Arc::clone(&self.bank);
+					let observers = self.observers.clone();
+					let limits = self.limits;
+					let moderation = Arc::clone(&self.moderation);
+					let host = self.host.clone();
 
 					thread::spawn(move || {
-						handle_connection(conn_id, stream, connections, tables, ai_roster, bank);
+						handle_connection(conn_id, stream, connections, tables, ai_roster, bank, observers, limits, moderation, host);
 					});
 				}
 				Err(e) => {
@@ -336,6 +775,7 @@ impl GameServer {
 	}
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_connection(
 	conn_id: ConnectionId,
 	stream: TcpStream,
@@ -343,6 +783,10 @@ fn handle_connection(
 	tables: Arc<Mutex<HashMap<String, TableRoom>>>,
 	ai_roster: Arc<Vec<PlayerConfig>>,
 	bank: Arc<Mutex<Bank>>,
+	observers: Observers,
+	limits: Limits,
+	moderation: Arc<Mutex<Moderation>>,
+	host: Option<String>,
 ) {
 	let stream_clone = match stream.try_clone() {
 		Ok(s) => s,
@@ -353,6 +797,7 @@ fn handle_connection(
 	};
 	let conn = Connection {
 		username: None,
+		ip: stream.peer_addr().ok().map(|addr| addr.ip()),
 		stream: stream_clone,
 		current_table: None,
 	};
@@ -369,8 +814,17 @@ fn handle_connection(
 			Ok(0) => break,
 			Ok(n) => {
 				pending.extend_from_slice(&buf[..n]);
-				while let Some(msg) = try_decode_message(&mut pending) {
-					process_message(conn_id, msg, &connections, &tables, &ai_roster, &bank);
+				while let Some(decoded) = try_decode_message(&mut pending, limits.max_message_size) {
+					match decoded {
+						Ok(msg) => process_message(conn_id, msg, &connections, &tables, &ai_roster, &bank, &observers, &limits, &moderation, host.as_deref()),
+						// Tell the client, rather than lose its message quietly
+						Err(reason) => {
+							eprintln!("Protocol error from client {}: {}", conn_id, reason);
+							if let Some(conn) = lock_connections(&connections).get_mut(&conn_id) {
+								conn.send(&ServerMessage::ProtocolError { reason });
+							}
+						}
+					}
 				}
 			}
 			Err(_) => break,
@@ -389,6 +843,10 @@ fn handle_connection(
 		let (removed_seat, has_active_game) = {
 			let mut tables_lock = lock_tables(&tables);
 			if let Some(table) = tables_lock.get_mut(&tid) {
+				// A held seat stays in the game for the player to come back to
+				if let Some(ref mut active_game) = table.active_game {
+					active_game.disconnect(conn_id);
+				}
 				let seat = table.remove_player(conn_id);
 				let no_humans = table.players.is_empty();
 				let has_active = table.active_game.is_some();
@@ -396,6 +854,7 @@ fn handle_connection(
 				if no_humans && table.status == TableStatus::Waiting {
 					table.ai_players.clear();
 					table.ready.clear();
+					table.registration_closes = None;
 				}
 
 				if let Some(ref mut active_game) = table.active_game {
@@ -436,28 +895,26 @@ fn handle_connection(
 	println!("Client {} disconnected", conn_id);
 }
 
-const MAX_MESSAGE_SIZE: usize = 65536;
-const MAX_USERNAME_LENGTH: usize = 32;
-const MAX_TABLE_ID_LENGTH: usize = 64;
-const MAX_CHAT_LENGTH: usize = 500;
-
-fn try_decode_message(buf: &mut Vec<u8>) -> Option<ClientMessage> {
-	if buf.len() < 4 {
-		return None;
-	}
-	let len = decode_length(buf)? as usize;
-	if len > MAX_MESSAGE_SIZE {
-		buf.clear();
-		return None;
-	}
-	if buf.len() < 4 + len {
-		return None;
-	}
-	let json = String::from_utf8_lossy(&buf[4..4 + len]).to_string();
-	buf.drain(..4 + len);
-	serde_json::from_str(&json).ok()
+// Defaults for `[limits]` and `[timeouts]` in server.toml
+pub(crate) const MAX_CONNECTIONS: usize = 256;
+pub(crate) const MAX_MESSAGE_SIZE: usize = 65536;
+pub(crate) const MAX_USERNAME_LENGTH: usize = 32;
+pub(crate) const MAX_TABLE_ID_LENGTH: usize = 64;
+pub(crate) const MAX_CHAT_LENGTH: usize = 500;
+pub(crate) const DEFAULT_ACTION_TIMEOUT_SECS: u32 = 120;
+/// Plays for a disconnected tournament player once their grace runs out.
+const STAND_IN_STRATEGY: &str = "balanced";
+
+/// How often the mDNS record's counts are refreshed.
+const ADVERTISE_INTERVAL: Duration = Duration::from_secs(5);
+/// How often finished games and idle tables are looked for.
+const REAP_INTERVAL: Duration = Duration::from_secs(10);
+
+fn try_decode_message(buf: &mut Vec<u8>, max_len: usize) -> Option<Result<ClientMessage, String>> {
+	decode_frame(buf, max_len)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn process_message(
 	conn_id: ConnectionId,
 	msg: ClientMessage,
@@ -465,23 +922,30 @@ fn process_message(
 	tables: &Arc<Mutex<HashMap<String, TableRoom>>>,
 	ai_roster: &Arc<Vec<PlayerConfig>>,
 	bank: &Arc<Mutex<Bank>>,
+	observers: &Observers,
+	limits: &Limits,
+	moderation: &Arc<Mutex<Moderation>>,
+	host: Option<&str>,
 ) {
 	match msg {
 		ClientMessage::Login { username } => {
-			if username.len() > MAX_USERNAME_LENGTH || username.is_empty() {
-				let mut conns = lock_connections(&connections);
+			if username.len() > limits.max_username_length || username.is_empty() {
+				let mut conns = lock_connections(connections);
 				if let Some(conn) = conns.get_mut(&conn_id) {
 					conn.send(&ServerMessage::Error {
-						message: format!("Username must be 1-{} characters", MAX_USERNAME_LENGTH),
+						message: format!("Username must be 1-{} characters", limits.max_username_length),
 					});
 				}
 				return;
 			}
-			let mut conns = lock_connections(&connections);
+			if refuse_if_banned(conn_id, Some(&username), connections, moderation) {
+				return;
+			}
+			let mut conns = lock_connections(connections);
 			if let Some(conn) = conns.get_mut(&conn_id) {
 				conn.username = Some(username.clone());
 				let bankroll = {
-					let bank_lock = lock_bank(&bank);
+					let bank_lock = lock_bank(bank);
 					bank_lock.get_bankroll(&username.to_lowercase())
 				};
 				conn.send(&ServerMessage::Welcome {
@@ -495,15 +959,15 @@ fn process_message(
 		ClientMessage::ListTables => {
 			// Lock tables first, do cleanup, then get connections
 			let any_cleaned = {
-				let mut tables_lock = lock_tables(&tables);
-				cleanup_finished_games(&mut tables_lock)
+				let mut tables_lock = lock_tables(tables);
+				cleanup_finished_games(&mut tables_lock, bank)
 			};
 
-			let tables_lock = lock_tables(&tables);
+			let tables_lock = lock_tables(tables);
 			let table_list = build_table_list(&tables_lock);
 			drop(tables_lock);
 
-			let mut conns = lock_connections(&connections);
+			let mut conns = lock_connections(connections);
 			if any_cleaned {
 				broadcast_lobby_state(&table_list, &mut conns);
 			} else {
@@ -514,8 +978,8 @@ fn process_message(
 		}
 
 		ClientMessage::JoinTable { table_id } => {
-			if table_id.len() > MAX_TABLE_ID_LENGTH {
-				let mut conns = lock_connections(&connections);
+			if table_id.len() > limits.max_table_id_length {
+				let mut conns = lock_connections(connections);
 				if let Some(conn) = conns.get_mut(&conn_id) {
 					conn.send(&ServerMessage::Error {
 						message: "Invalid table ID".to_string(),
@@ -523,38 +987,55 @@ fn process_message(
 				}
 				return;
 			}
+			// Bans given since the player logged in
+			let username = lock_connections(connections).get(&conn_id).and_then(|c| c.username.clone());
+			if refuse_if_banned(conn_id, username.as_deref(), connections, moderation) {
+				return;
+			}
 			// Lock order: tables first, then connections
-			let mut tables_lock = lock_tables(&tables);
-			let mut conns = lock_connections(&connections);
+			let mut tables_lock = lock_tables(tables);
+			let mut conns = lock_connections(connections);
 
 			let username = conns.get(&conn_id)
 				.and_then(|c| c.username.clone())
 				.unwrap_or_else(|| "Anonymous".to_string());
 
 			// Cleanup finished game if applicable
-			let mut cleaned_up = false;
-			if let Some(table) = tables_lock.get_mut(&table_id) {
-				if table.status == TableStatus::InProgress {
-					if let Some(ref game) = table.active_game {
-						if game.is_finished() {
-							table.status = TableStatus::Waiting;
-							table.players.clear();
-							table.ai_players.clear();
-							table.ready.clear();
-							table.active_game = None;
-							cleaned_up = true;
-						}
-					}
-				}
-			}
-
-			if cleaned_up {
+			if cleanup_finished_games(&mut tables_lock, bank) {
 				// Broadcast updated status to all lobby clients
 				let table_list = build_table_list(&tables_lock);
 				broadcast_lobby_state(&table_list, &mut conns);
 			}
 
 			if let Some(table) = tables_lock.get_mut(&table_id) {
+				let rejoined = match (&mut table.active_game, conns.get(&conn_id)) {
+					(Some(game), Some(conn)) => conn.stream.try_clone().ok()
+						.and_then(|stream| game.reconnect(conn_id, &username, stream)),
+					_ => None,
+				};
+				if let Some(seat) = rejoined {
+					table.players.insert(seat, conn_id);
+					let joined = ServerMessage::TableJoined {
+						table_id: table_id.clone(),
+						table_name: table.config.name.clone(),
+						seat,
+						players: table.player_infos(&conns),
+						min_players: table.config.min_players,
+						max_players: table.config.max_players,
+					};
+					// Straight back into the game; their view catches up at the next hand
+					let starting = ServerMessage::GameStarting {
+						countdown: 0,
+						table_config: table.config.clone(),
+					};
+					if let Some(conn) = conns.get_mut(&conn_id) {
+						conn.current_table = Some(table_id.clone());
+						conn.send(&joined);
+						conn.send(&starting);
+					}
+					return;
+				}
+
 				if table.status != TableStatus::Waiting {
 					if let Some(conn) = conns.get_mut(&conn_id) {
 						conn.send(&ServerMessage::Error {
@@ -575,6 +1056,8 @@ fn process_message(
 
 				if let Some(seat) = table.find_empty_seat() {
 					table.add_player(seat, conn_id);
+					let new_clock = table.start_registration();
+					let seconds_left = table.registration_seconds_left();
 					let player_infos = table.player_infos(&conns);
 					let table_name = table.config.name.clone();
 					let min_players = table.config.min_players;
@@ -598,6 +1081,26 @@ fn process_message(
 					};
 					broadcast_to_table_except(&table_id, conn_id, &join_msg, &mut tables_lock, &mut conns);
 
+					if let Some(seconds) = seconds_left {
+						let open_msg = ServerMessage::RegistrationOpen { seconds };
+						if new_clock.is_some() {
+							broadcast_to_table(&table_id, &open_msg, &mut tables_lock, &mut conns);
+						} else if let Some(conn) = conns.get_mut(&conn_id) {
+							conn.send(&open_msg);
+						}
+					}
+					if let Some(closes) = new_clock {
+						close_registration_at(
+							table_id.clone(),
+							closes,
+							Arc::clone(connections),
+							Arc::clone(tables),
+							Arc::clone(ai_roster),
+							Arc::clone(bank),
+							observers.clone(),
+						);
+					}
+
 					// Broadcast updated lobby state to all clients in table select
 					let table_list = build_table_list(&tables_lock);
 					broadcast_lobby_state(&table_list, &mut conns);
@@ -619,8 +1122,8 @@ fn process_message(
 
 		ClientMessage::LeaveTable => {
 			// Lock order: tables first, then connections
-			let mut tables_lock = lock_tables(&tables);
-			let mut conns = lock_connections(&connections);
+			let mut tables_lock = lock_tables(tables);
+			let mut conns = lock_connections(connections);
 
 			let table_id = conns.get(&conn_id).and_then(|c| c.current_table.clone());
 			if let Some(tid) = table_id {
@@ -636,6 +1139,7 @@ fn process_message(
 							if table.players.is_empty() && table.status == TableStatus::Waiting {
 								table.ai_players.clear();
 								table.ready.clear();
+								table.registration_closes = None;
 							}
 
 							// If game is active, update active_game and signal quit if no humans left
@@ -685,26 +1189,65 @@ fn process_message(
 			}
 		}
 
+		ClientMessage::ChangeTable => {
+			// Lock order: tables first, then connections
+			let tables_lock = lock_tables(tables);
+			let mut conns = lock_connections(connections);
+
+			let from = conns.get(&conn_id).and_then(|c| c.current_table.clone());
+			let planned = from.ok_or("Not at a table".to_string())
+				.and_then(|from| plan_table_change(conn_id, &from, &tables_lock).map(|(seat, to)| (from, seat, to)));
+			match planned {
+				Ok((from, seat, to)) => {
+					let game = tables_lock.get(&from).and_then(|t| t.active_game.as_ref());
+					if let Some(game) = game {
+						let connections = Arc::clone(connections);
+						let tables = Arc::clone(tables);
+						let bank = Arc::clone(bank);
+						let move_player = Box::new(move |stack: f32| {
+							change_table(conn_id, &from, &to, stack, &connections, &tables, &bank);
+						});
+						game.leaving.lock().unwrap_or_else(|e| e.into_inner()).insert(seat);
+						game.migrations.lock().unwrap_or_else(|e| e.into_inner()).insert(seat, move_player);
+					}
+				}
+				Err(message) => {
+					if let Some(conn) = conns.get_mut(&conn_id) {
+						conn.send(&ServerMessage::Error { message });
+					}
+				}
+			}
+		}
+
 		ClientMessage::Ready => {
 			// Lock order: tables first, then connections, then bank
-			let mut tables_lock = lock_tables(&tables);
-			let mut conns = lock_connections(&connections);
+			let mut tables_lock = lock_tables(tables);
+			let mut conns = lock_connections(connections);
 
 			let table_id = conns.get(&conn_id).and_then(|c| c.current_table.clone());
 			if let Some(tid) = table_id {
-				let (ready_seat, all_ready) = {
-					if let Some(table) = tables_lock.get_mut(&tid) {
+				let (ready_seat, all_ready, new_clock) = {
+					// Readying up only means something before the game starts; a
+					// player back in a held seat is already playing
+					let waiting = tables_lock.get_mut(&tid).filter(|t| t.status == TableStatus::Waiting);
+					if let Some(table) = waiting {
 						let seat = table.players.iter()
 							.find(|&(_, &id)| id == conn_id)
 							.map(|(&s, _)| s);
 						if let Some(s) = seat {
 							table.set_ready(s);
-							(Some(s), table.all_ready())
+							// A registration clock starts the game, not readiness; readying
+							// up only restarts a clock that stopped on a failed start
+							if table.config.registration_window().is_some() {
+								(Some(s), false, table.start_registration())
+							} else {
+								(Some(s), table.all_ready(), None)
+							}
 						} else {
-							(None, false)
+							(None, false, None)
 						}
 					} else {
-						(None, false)
+						(None, false, None)
 					}
 				};
 
@@ -713,131 +1256,20 @@ fn process_message(
 					broadcast_to_table(&tid, &msg, &mut tables_lock, &mut conns);
 
 					if all_ready {
-						let mut bank_lock = lock_bank(&bank);
-
-						// Process buy-ins for all players
-						let buy_in_result: Result<(), String> = (|| {
-							let table = tables_lock.get(&tid).ok_or("Table not found")?;
-							let buy_in = table.config.effective_buy_in();
-
-							// Collect all player ids (humans use username lowercase, AI uses id)
-							let mut player_ids: Vec<String> = Vec::new();
-
-							for &cid in table.players.values() {
-								if let Some(conn) = conns.get(&cid) {
-									let username = conn.username.clone().unwrap_or_else(|| "Unknown".to_string());
-									player_ids.push(username.to_lowercase());
-								}
-							}
-
-							for ai in table.ai_players.values() {
-								player_ids.push(ai.id.clone());
-							}
-
-							// Try buy-in for each player
-							for id in &player_ids {
-								bank_lock.buyin(id, buy_in, &table.config.id)
-									.map_err(|e| format!("{}", e))?;
-							}
-
-						Ok(())
-						})();
-
-						if let Err(msg) = buy_in_result {
-							// Reset table status and player ready states
-							if let Some(table) = tables_lock.get_mut(&tid) {
-								table.status = TableStatus::Waiting;
-								for ready in table.ready.values_mut() {
-									*ready = false;
-								}
-								// Re-mark AI as ready
-								for &seat in table.ai_players.keys() {
-									table.ready.insert(seat, true);
-								}
-							}
-
-							let error_msg = ServerMessage::Error { message: msg };
-							broadcast_to_table(&tid, &error_msg, &mut tables_lock, &mut conns);
-
-							// Broadcast updated lobby state
-							let table_list = build_table_list(&tables_lock);
-							broadcast_lobby_state(&table_list, &mut conns);
-							return;
-						}
-
-						// Save bank after successful buy-ins
-						if let Err(e) = bank_lock.save() {
-							eprintln!("Failed to save bank: {}", e);
-						}
-						drop(bank_lock);
-
-						if let Some(table) = tables_lock.get_mut(&tid) {
-							table.status = TableStatus::InProgress;
-						}
-
-						// Send GameStarting with the table config
-						let table_config = tables_lock.get(&tid)
-							.map(|t| t.config.clone())
-							.unwrap_or_else(|| panic!("Table {} not found", tid));
-
-						let starting_msg = ServerMessage::GameStarting {
-							countdown: 3,
-							table_config,
-						};
-						broadcast_to_table(&tid, &starting_msg, &mut tables_lock, &mut conns);
-
-						// Broadcast updated lobby state so table select shows "In Progress"
-						let table_list = build_table_list(&tables_lock);
-						broadcast_lobby_state(&table_list, &mut conns);
-
-						// Collect info for game start
-						let game_info: Option<GameStartInfo> = {
-							if let Some(table) = tables_lock.get(&tid) {
-								let mut human_players = Vec::new();
-								let mut player_bank_ids: Vec<(Seat, String)> = Vec::new();
-
-								for (&seat, &cid) in &table.players {
-									if let Some(conn) = conns.get(&cid) {
-										let username = conn.username.clone().unwrap_or_else(|| "Unknown".to_string());
-										player_bank_ids.push((seat, username.to_lowercase()));
-										if let Ok(stream_clone) = conn.stream.try_clone() {
-											human_players.push((cid, seat, username, stream_clone));
-										}
-									}
-								}
-
-								for (&seat, ai) in &table.ai_players {
-									player_bank_ids.push((seat, ai.id.clone()));
-								}
-
-								// Sort by seat and extract just the ids
-								player_bank_ids.sort_by_key(|(seat, _)| seat.0);
-								let bank_ids: Vec<String> = player_bank_ids.into_iter()
-									.map(|(_, id)| id)
-									.collect();
-
-								let ai_players: Vec<(Seat, String, String, String)> = table.ai_players.iter()
-									.map(|(&seat, ai)| (seat, ai.id.clone(), ai.name.clone(), ai.strategy.clone()))
-									.collect();
-
-								Some(GameStartInfo {
-									config: table.config.clone(),
-									human_players,
-									ai_players,
-									player_bank_ids: bank_ids,
-								})
-							} else {
-								None
-							}
-						};
-
-						// Start game outside of heavy lock usage
-						if let Some(info) = game_info {
-							let active_game = start_game(info, Arc::clone(bank));
-							if let Some(table) = tables_lock.get_mut(&tid) {
-								table.active_game = Some(active_game);
-							}
-						}
+						launch_table(&tid, &mut tables_lock, &mut conns, bank, observers);
+					}
+					if let Some(closes) = new_clock {
+						let seconds = tables_lock.get(&tid).and_then(|t| t.registration_seconds_left()).unwrap_or(0);
+						broadcast_to_table(&tid, &ServerMessage::RegistrationOpen { seconds }, &mut tables_lock, &mut conns);
+						close_registration_at(
+							tid.clone(),
+							closes,
+							Arc::clone(connections),
+							Arc::clone(tables),
+							Arc::clone(ai_roster),
+							Arc::clone(bank),
+							observers.clone(),
+						);
 					}
 				}
 			}
@@ -845,8 +1277,9 @@ fn process_message(
 
 		ClientMessage::AddAI { strategy: _ } => {
 			// Lock order: tables first, then connections, then bank
-			let mut tables_lock = lock_tables(&tables);
-			let mut conns = lock_connections(&connections);
+			let mut tables_lock = lock_tables(tables);
+			let mut conns = lock_connections(connections);
+			let floor = smallest_buy_in(&tables_lock);
 
 			let table_id = conns.get(&conn_id).and_then(|c| c.current_table.clone());
 			if let Some(tid) = table_id {
@@ -861,44 +1294,19 @@ fn process_message(
 					}
 
 					if let Some(seat) = table.find_empty_seat() {
-						// Collect all used IDs (humans + AI) case-insensitively
-						let mut used_ids: Vec<String> = table.ai_players.values()
-							.map(|ai| ai.id.to_lowercase())
-							.collect();
-						for &cid in table.players.values() {
-							if let Some(conn) = conns.get(&cid) {
-								if let Some(username) = &conn.username {
-									used_ids.push(username.to_lowercase());
-								}
-							}
+						let mut bank_lock = lock_bank(bank);
+						let picked = pick_ai(table, &conns, ai_roster, &mut bank_lock, floor);
+						// Saves reloads and paydays even if nobody sat
+						if let Err(e) = bank_lock.save() {
+							eprintln!("Failed to save bank after picking AI: {}", e);
 						}
-
-						let mut available: Vec<_> = ai_roster.iter()
-							.filter(|p| !used_ids.contains(&p.id.to_lowercase()))
-							.collect();
-
-						use rand::seq::SliceRandom;
-						available.shuffle(&mut rand::rng());
-
-						let selected = available.iter()
-							.find(|p| rand::random::<f32>() < p.join_probability)
-							.copied()
-							.or_else(|| available.first().copied());
-
-						if let Some(ai_config) = selected {
-							// Ensure AI player has a bank profile
-							{
-								let mut bank_lock = lock_bank(&bank);
-								bank_lock.ensure_exists(&ai_config.id);
-								if let Err(e) = bank_lock.save() {
-									eprintln!("Failed to save bank after ensuring AI exists: {}", e);
-								}
-							}
-
+						let bankroll = picked.map(|ai| bank_lock.get_bankroll(&ai.id));
+						drop(bank_lock);
+						if let (Some(ai_config), Some(bankroll)) = (picked, bankroll) {
 							let name = ai_config.display_name();
-							table.add_ai(seat, ai_config.id.clone(), name.clone(), ai_config.strategy.clone());
+							table.add_ai(seat, ai_config.id.clone(), name.clone(), ai_config.strategy.clone(), bankroll);
 
-							let msg = ServerMessage::AIAdded { seat, name };
+							let msg = ServerMessage::AIAdded { seat, name, bankroll: Some(bankroll) };
 							broadcast_to_table(&tid, &msg, &mut tables_lock, &mut conns);
 
 							// Broadcast updated lobby state to all clients in table select
@@ -924,8 +1332,8 @@ fn process_message(
 
 		ClientMessage::RemoveAI { seat } => {
 			// Lock order: tables first, then connections
-			let mut tables_lock = lock_tables(&tables);
-			let mut conns = lock_connections(&connections);
+			let mut tables_lock = lock_tables(tables);
+			let mut conns = lock_connections(connections);
 
 			let table_id = conns.get(&conn_id).and_then(|c| c.current_table.clone());
 			if let Some(tid) = table_id {
@@ -959,8 +1367,8 @@ fn process_message(
 
 		ClientMessage::Action { action } => {
 			// Lock order: tables first, then connections
-			let tables_lock = lock_tables(&tables);
-			let conns = lock_connections(&connections);
+			let tables_lock = lock_tables(tables);
+			let conns = lock_connections(connections);
 
 			let table_id = conns.get(&conn_id).and_then(|c| c.current_table.clone());
 			if let Some(tid) = table_id {
@@ -974,66 +1382,652 @@ fn process_message(
 			}
 		}
 
-		ClientMessage::Chat { text } => {
-			if text.len() > MAX_CHAT_LENGTH {
+		ClientMessage::Pause { paused } => {
+			// Lock order: tables first, then connections
+			let tables_lock = lock_tables(tables);
+			let mut conns = lock_connections(connections);
+
+			let Some(conn) = conns.get_mut(&conn_id) else { return };
+			let is_host = host.is_some_and(|host| conn.username.as_deref().is_some_and(|name| name.eq_ignore_ascii_case(host)));
+			if !is_host {
+				conn.send(&ServerMessage::Error { message: "Only the host can pause the game".to_string() });
 				return;
 			}
-			// TODO: Broadcast chat
-			println!("Chat from {}: {}", conn_id, text);
+			let active_game = conn.current_table.as_ref()
+				.and_then(|tid| tables_lock.get(tid))
+				.and_then(|table| table.active_game.as_ref());
+			if let Some(active_game) = active_game {
+				active_game.pause.set(paused);
+			}
 		}
-	}
-}
 
-fn broadcast_to_table(
-	table_id: &str,
-	msg: &ServerMessage,
-	tables: &mut HashMap<String, TableRoom>,
-	conns: &mut HashMap<ConnectionId, Connection>,
-) {
-	if let Some(table) = tables.get(table_id) {
-		for &conn_id in table.players.values() {
-			if let Some(conn) = conns.get_mut(&conn_id) {
-				conn.send(msg);
+		ClientMessage::FastForward { enabled } => {
+			// Lock order: tables first, then connections
+			let tables_lock = lock_tables(tables);
+			let conns = lock_connections(connections);
+
+			let table_id = conns.get(&conn_id).and_then(|c| c.current_table.clone());
+			if let Some(tid) = table_id {
+				if let Some(table) = tables_lock.get(&tid) {
+					if let Some(ref active_game) = table.active_game {
+						if let Err(e) = active_game.set_fast_forward(conn_id, enabled) {
+							eprintln!("Fast-forward error: {}", e);
+						}
+					}
+				}
 			}
 		}
-	}
-}
 
-fn broadcast_to_table_except(
-	table_id: &str,
-	exclude: ConnectionId,
-	msg: &ServerMessage,
-	tables: &mut HashMap<String, TableRoom>,
-	conns: &mut HashMap<ConnectionId, Connection>,
-) {
-	if let Some(table) = tables.get(table_id) {
-		for &conn_id in table.players.values() {
-			if conn_id != exclude {
-				if let Some(conn) = conns.get_mut(&conn_id) {
-					conn.send(msg);
+		ClientMessage::AutoMuck { enabled } => {
+			// Lock order: tables first, then connections
+			let tables_lock = lock_tables(tables);
+			let conns = lock_connections(connections);
+
+			let table_id = conns.get(&conn_id).and_then(|c| c.current_table.clone());
+			if let Some(tid) = table_id {
+				if let Some(table) = tables_lock.get(&tid) {
+					if let Some(ref active_game) = table.active_game {
+						if let Err(e) = active_game.set_auto_muck(conn_id, enabled) {
+							eprintln!("Auto-muck error: {}", e);
+						}
+					}
 				}
 			}
 		}
-	}
-}
 
-fn cleanup_finished_games(tables: &mut HashMap<String, TableRoom>) -> bool {
-	let mut any_cleaned = false;
-	for table in tables.values_mut() {
-		if table.status == TableStatus::InProgress {
-			if let Some(ref game) = table.active_game {
-				if game.is_finished() {
-					table.status = TableStatus::Waiting;
-					table.players.clear();
-					table.ai_players.clear();
-					table.ready.clear();
-					table.active_game = None;
-					any_cleaned = true;
+		ClientMessage::Pacing { action_delay_ms, street_delay_ms } => {
+			// Lock order: tables first, then connections
+			let tables_lock = lock_tables(tables);
+			let conns = lock_connections(connections);
+
+			let table_id = conns.get(&conn_id).and_then(|c| c.current_table.clone());
+			if let Some(tid) = table_id {
+				if let Some(table) = tables_lock.get(&tid) {
+					if let Some(ref active_game) = table.active_game {
+						if let Err(e) = active_game.set_pacing(conn_id, action_delay_ms, street_delay_ms) {
+							eprintln!("Pacing error: {}", e);
+						}
+					}
 				}
 			}
 		}
+
+		ClientMessage::Chat { text } => {
+			if text.len() > limits.max_chat_length || text.trim().is_empty() {
+				return;
+			}
+			let username = lock_connections(connections).get(&conn_id).and_then(|c| c.username.clone());
+			let mute = username.and_then(|name| {
+				lock_moderation(moderation).current().mute_for(&name, Local::now()).map(|m| m.describe())
+			});
+			if let Some(mute) = mute {
+				if let Some(conn) = lock_connections(connections).get_mut(&conn_id) {
+					conn.send(&ServerMessage::Error { message: format!("You are muted {}", mute) });
+				}
+				return;
+			}
+			// Lock order: tables first, then connections
+			let tables_lock = lock_tables(tables);
+			let conns = lock_connections(connections);
+
+			let table_id = conns.get(&conn_id).and_then(|c| c.current_table.clone());
+			if let Some(tid) = table_id {
+				if let Some(table) = tables_lock.get(&tid) {
+					if let Some(ref active_game) = table.active_game {
+						if let Err(e) = active_game.chat(conn_id, text) {
+							eprintln!("Chat error: {}", e);
+						}
+					}
+				}
+			}
+		}
+
+		ClientMessage::ProtocolError { reason } => {
+			eprintln!("Client {} dropped a message from us: {}", conn_id, reason);
+		}
+
+		ClientMessage::Ping { id } => {
+			if let Some(conn) = lock_connections(connections).get_mut(&conn_id) {
+				conn.send(&ServerMessage::Pong { id });
+			}
+		}
+	}
+}
+
+/// Turns away a banned player or address: tells them why and hangs up.
+fn refuse_if_banned(
+	conn_id: ConnectionId,
+	username: Option<&str>,
+	connections: &Arc<Mutex<HashMap<ConnectionId, Connection>>>,
+	moderation: &Arc<Mutex<Moderation>>,
+) -> bool {
+	let ip = lock_connections(connections).get(&conn_id).and_then(|c| c.ip);
+	let ban = lock_moderation(moderation).current().ban_for(username, ip, Local::now()).map(|b| b.describe());
+	let Some(ban) = ban else {
+		return false;
+	};
+	logging::log("Server", "WARN", &format!("Refused client {} ({}), banned {}", conn_id, username.unwrap_or("?"), ban));
+	if let Some(conn) = lock_connections(connections).get_mut(&conn_id) {
+		conn.send(&ServerMessage::Error { message: format!("You are banned {}", ban) });
+		let _ = conn.stream.shutdown(Shutdown::Both);
+	}
+	true
+}
+
+/// The smallest buy-in of any table, the stakes an AI moving down can
+/// drop no lower than.
+fn smallest_buy_in(tables_lock: &HashMap<String, TableRoom>) -> f32 {
+	tables_lock.values()
+		.map(|t| t.config.effective_buy_in())
+		.fold(f32::INFINITY, f32::min)
+}
+
+/// Picks a roster AI who isn't already at the table, is on their
+/// schedule and can cover the buy-in, to suit the table's lineup if it
+/// has one. Broke AIs reload, or not, as their `when_busted` says, and
+/// those bankrolled for the stakes are preferred.
+fn pick_ai<'a>(
+	table: &TableRoom,
+	conns: &HashMap<ConnectionId, Connection>,
+	ai_roster: &'a [PlayerConfig],
+	bank: &mut Bank,
+	floor: f32,
+) -> Option<&'a PlayerConfig> {
+	// Collect all used IDs (humans + AI) case-insensitively
+	let mut used_ids: Vec<String> = table.ai_players.values()
+		.map(|ai| ai.id.to_lowercase())
+		.collect();
+	for &cid in table.players.values() {
+		if let Some(conn) = conns.get(&cid) {
+			if let Some(username) = &conn.username {
+				used_ids.push(username.to_lowercase());
+			}
+		}
+	}
+
+	let mut available: Vec<_> = ai_roster.iter()
+		.filter(|p| !used_ids.contains(&p.id.to_lowercase()))
+		.collect();
+	// Only those whose schedule has them playing now, unless nobody's about
+	let now = Local::now().naive_local();
+	if available.iter().any(|p| p.is_online(now)) {
+		available.retain(|p| p.is_online(now));
+	}
+	let buy_in = table.config.effective_buy_in();
+	let today = now.date();
+	available.retain(|p| bank.ready_ai(&p.id, buy_in, p.when_busted, floor, today));
+	lineup::prefer_stakes(&mut available, |id| bank.get_bankroll(id), buy_in);
+	let seated: Vec<String> = table.ai_players.values()
+		.map(|ai| ai.strategy.clone())
+		.collect();
+
+	lineup::pick(table.config.lineup.as_ref(), &available, &seated, &mut rand::rng())
+}
+
+/// Seats roster AIs in every empty seat at `tid`, or as many as the
+/// roster can spare.
+fn fill_with_ai(
+	tid: &str,
+	tables_lock: &mut HashMap<String, TableRoom>,
+	conns: &mut HashMap<ConnectionId, Connection>,
+	ai_roster: &[PlayerConfig],
+	bank: &Arc<Mutex<Bank>>,
+) {
+	let floor = smallest_buy_in(tables_lock);
+	let mut bank_lock = lock_bank(bank);
+	while let Some(table) = tables_lock.get_mut(tid) {
+		let Some(seat) = table.find_empty_seat() else {
+			break;
+		};
+		let Some(ai_config) = pick_ai(table, conns, ai_roster, &mut bank_lock, floor) else {
+			break;
+		};
+		let bankroll = bank_lock.get_bankroll(&ai_config.id);
+		let name = ai_config.display_name();
+		table.add_ai(seat, ai_config.id.clone(), name.clone(), ai_config.strategy.clone(), bankroll);
+		let msg = ServerMessage::AIAdded { seat, name, bankroll: Some(bankroll) };
+		broadcast_to_table(tid, &msg, tables_lock, conns);
+	}
+	if let Err(e) = bank_lock.save() {
+		eprintln!("Failed to save bank after seating AI: {}", e);
+	}
+}
+
+/// Runs a sit & go's registration clock in the background. When it runs
+/// out with someone still waiting, the empty seats fill with roster AIs
+/// and the game starts.
+fn close_registration_at(
+	tid: String,
+	closes: Instant,
+	connections: Arc<Mutex<HashMap<ConnectionId, Connection>>>,
+	tables: Arc<Mutex<HashMap<String, TableRoom>>>,
+	ai_roster: Arc<Vec<PlayerConfig>>,
+	bank: Arc<Mutex<Bank>>,
+	observers: Observers,
+) {
+	thread::spawn(move || {
+		thread::sleep(closes.saturating_duration_since(Instant::now()));
+
+		// Lock order: tables first, then connections, then bank
+		let mut tables_lock = lock_tables(&tables);
+		let mut conns = lock_connections(&connections);
+		let Some(table) = tables_lock.get_mut(&tid) else {
+			return;
+		};
+		// Everyone left, or the clock was restarted, while this one ran
+		if table.status != TableStatus::Waiting || table.registration_closes != Some(closes) || table.players.is_empty() {
+			return;
+		}
+		table.registration_closes = None;
+
+		fill_with_ai(&tid, &mut tables_lock, &mut conns, &ai_roster, &bank);
+		let short = tables_lock.get(&tid).is_some_and(|t| t.player_count() < t.config.min_players);
+		if short {
+			let msg = ServerMessage::Error { message: "Not enough players to start".to_string() };
+			broadcast_to_table(&tid, &msg, &mut tables_lock, &mut conns);
+			let table_list = build_table_list(&tables_lock);
+			broadcast_lobby_state(&table_list, &mut conns);
+		} else {
+			launch_table(&tid, &mut tables_lock, &mut conns, &bank, &observers);
+		}
+	});
+}
+
+/// Buys everyone in and starts the game at `tid`. Buy-ins are held in
+/// escrow under the new game's id until it ends. A failed buy-in refunds
+/// anyone already bought in and puts the table back to waiting, with the
+/// humans needing to ready up again.
+fn launch_table(
+	tid: &str,
+	tables_lock: &mut HashMap<String, TableRoom>,
+	conns: &mut HashMap<ConnectionId, Connection>,
+	bank: &Arc<Mutex<Bank>>,
+	observers: &Observers,
+) {
+	let mut bank_lock = lock_bank(bank);
+	let game_id = GameId(rand::random());
+
+	// Process buy-ins for all players
+	let buy_in_result: Result<(), String> = (|| {
+		let table = tables_lock.get(tid).ok_or("Table not found")?;
+		let buy_in = table.config.effective_buy_in();
+
+		// Humans' bank ids are their lowercased usernames
+		let mut player_ids: Vec<String> = Vec::new();
+
+		for &cid in table.players.values() {
+			if let Some(conn) = conns.get(&cid) {
+				let username = conn.username.clone().unwrap_or_else(|| "Unknown".to_string());
+				player_ids.push(username.to_lowercase());
+			}
+		}
+
+		// Try buy-in for each player; AIs may bring their lineup stack
+		for id in &player_ids {
+			bank_lock.escrow_buyin(game_id, id, buy_in, &table.config.id)
+				.map_err(|e| format!("{}", e))?;
+		}
+		for ai in table.ai_players.values() {
+			bank_lock.escrow_buyin(game_id, &ai.id, ai.stack.unwrap_or(buy_in), &table.config.id)
+				.map_err(|e| format!("{}", e))?;
+		}
+
+		Ok(())
+	})();
+
+	if let Err(msg) = buy_in_result {
+		bank_lock.refund_escrow(game_id);
+		if let Err(e) = bank_lock.save() {
+			eprintln!("Failed to save bank after refunding buy-ins: {}", e);
+		}
+		drop(bank_lock);
+
+		// Reset table status and player ready states
+		if let Some(table) = tables_lock.get_mut(tid) {
+			table.status = TableStatus::Waiting;
+			for ready in table.ready.values_mut() {
+				*ready = false;
+			}
+			// Re-mark AI as ready
+			for &seat in table.ai_players.keys() {
+				table.ready.insert(seat, true);
+			}
+		}
+
+		let error_msg = ServerMessage::Error { message: msg };
+		broadcast_to_table(tid, &error_msg, tables_lock, conns);
+
+		// Broadcast updated lobby state
+		let table_list = build_table_list(tables_lock);
+		broadcast_lobby_state(&table_list, conns);
+		return;
+	}
+
+	// Save bank after successful buy-ins
+	if let Err(e) = bank_lock.save() {
+		eprintln!("Failed to save bank: {}", e);
+	}
+	drop(bank_lock);
+
+	if let Some(table) = tables_lock.get_mut(tid) {
+		table.status = TableStatus::InProgress;
+	}
+
+	// Send GameStarting with the table config
+	let table_config = tables_lock.get(tid)
+		.map(|t| t.config.clone())
+		.unwrap_or_else(|| panic!("Table {} not found", tid));
+
+	let starting_msg = ServerMessage::GameStarting {
+		countdown: 3,
+		table_config,
+	};
+	broadcast_to_table(tid, &starting_msg, tables_lock, conns);
+
+	// Broadcast updated lobby state so table select shows "In Progress"
+	let table_list = build_table_list(tables_lock);
+	broadcast_lobby_state(&table_list, conns);
+
+	// Collect info for game start
+	let game_info: Option<GameStartInfo> = {
+		if let Some(table) = tables_lock.get(tid) {
+			let mut human_players = Vec::new();
+			let mut player_bank_ids: Vec<(Seat, String)> = Vec::new();
+
+			for (&seat, &cid) in &table.players {
+				if let Some(conn) = conns.get(&cid) {
+					let username = conn.username.clone().unwrap_or_else(|| "Unknown".to_string());
+					player_bank_ids.push((seat, username.to_lowercase()));
+					if let Ok(stream_clone) = conn.stream.try_clone() {
+						human_players.push((cid, seat, username, stream_clone));
+					}
+				}
+			}
+
+			for (&seat, ai) in &table.ai_players {
+				player_bank_ids.push((seat, ai.id.clone()));
+			}
+
+			// Sort by seat and extract just the ids
+			player_bank_ids.sort_by_key(|(seat, _)| seat.0);
+			let bank_ids: Vec<String> = player_bank_ids.into_iter()
+				.map(|(_, id)| id)
+				.collect();
+
+			let ai_players: Vec<(Seat, String, String, String, Option<f32>)> = table.ai_players.iter()
+				.map(|(&seat, ai)| (seat, ai.id.clone(), ai.name.clone(), ai.strategy.clone(), ai.stack))
+				.collect();
+
+			Some(GameStartInfo {
+				game_id,
+				config: table.config.clone(),
+				human_players,
+				ai_players,
+				player_bank_ids: bank_ids,
+				hold_seats: table.hold_seats,
+			})
+		} else {
+			None
+		}
+	};
+
+	// Start game outside of heavy lock usage
+	if let Some(info) = game_info {
+		let active_game = start_game(info, Arc::clone(bank), observers.clone());
+		if let Some(table) = tables_lock.get_mut(tid) {
+			table.active_game = Some(active_game);
+		}
+	}
+}
+
+/// Finds another running cash table at the same stakes with a free seat
+/// for the player at `from`, returning their seat and the new table's id.
+fn plan_table_change(
+	conn_id: ConnectionId,
+	from: &str,
+	tables: &HashMap<String, TableRoom>,
+) -> Result<(Seat, String), String> {
+	use crate::table::GameFormat;
+	let source = tables.get(from).ok_or("Table not found")?;
+	if source.config.format != GameFormat::Cash {
+		return Err("Only cash tables can be changed".to_string());
+	}
+	let seat = source.active_game.as_ref()
+		.and_then(|game| game.conn_to_seat.get(&conn_id).copied())
+		.ok_or("Not playing at this table")?;
+
+	let mut candidates: Vec<&TableRoom> = tables.values()
+		.filter(|room| room.config.id != from && room.config.format == GameFormat::Cash)
+		.filter(|room| room.status == TableStatus::InProgress && room.active_game.is_some())
+		.filter(|room| room.config.betting == source.config.betting)
+		.filter(|room| room.config.current_blinds() == source.config.current_blinds())
+		.filter(|room| room.find_empty_seat().is_some())
+		.collect();
+	candidates.sort_by_key(|room| room.order);
+	let target = candidates.first().ok_or("No other table at these stakes has a free seat")?;
+	Ok((seat, target.config.id.clone()))
+}
+
+/// Moves a player who has just cashed `stack` out of table `from` into a
+/// free seat at `to`, stack and all. The bank isn't touched unless the
+/// new table has filled up or finished in the meantime, in which case the
+/// stack is cashed out as if they'd left.
+fn change_table(
+	conn_id: ConnectionId,
+	from: &str,
+	to: &str,
+	stack: f32,
+	connections: &Arc<Mutex<HashMap<ConnectionId, Connection>>>,
+	tables: &Arc<Mutex<HashMap<String, TableRoom>>>,
+	bank: &Arc<Mutex<Bank>>,
+) {
+	// Lock order: tables first, then connections, then bank
+	let mut tables_lock = lock_tables(tables);
+	let mut conns = lock_connections(connections);
+	let username = conns.get(&conn_id)
+		.and_then(|c| c.username.clone())
+		.unwrap_or_else(|| "Unknown".to_string());
+
+	if let Some(room) = tables_lock.get_mut(from) {
+		let old_seat = room.remove_player(conn_id);
+		if let Some(game) = &room.active_game {
+			if let Some(seat) = old_seat {
+				game.streams.lock().unwrap_or_else(|e| e.into_inner()).retain(|(s, _)| *s != seat);
+			}
+			if !game.has_humans() {
+				game.signal_quit();
+			}
+		}
+		if let Some(seat) = old_seat {
+			let msg = ServerMessage::PlayerLeftTable { seat, username: username.clone() };
+			broadcast_to_table(from, &msg, &mut tables_lock, &mut conns);
+		}
+	}
+
+	let stream = conns.get(&conn_id).and_then(|c| c.stream.try_clone().ok());
+	let target = tables_lock.get_mut(to)
+		.filter(|room| room.active_game.as_ref().is_some_and(|g| !g.is_finished()))
+		.and_then(|room| room.find_empty_seat().map(|seat| (room, seat)));
+	let seated = match (target, stream) {
+		(Some((room, seat)), Some(stream)) if stack > 0.0 => {
+			let game = room.active_game.as_mut().expect("Checked above");
+			let (action_tx, action_rx) = mpsc::unbounded_channel();
+			let auto_muck = Arc::new(AtomicBool::new(false));
+			game.register_player(conn_id, seat, username.clone(), action_tx, Arc::clone(&auto_muck));
+			let player = RemotePlayer::new(seat, username.clone(), action_rx).with_auto_muck(auto_muck);
+
+			game.streams.lock().unwrap_or_else(|e| e.into_inner()).push((seat, Arc::new(Mutex::new(stream))));
+			let mut bank_ids = game.bank_ids.lock().unwrap_or_else(|e| e.into_inner());
+			if bank_ids.len() <= seat.0 {
+				bank_ids.resize(seat.0 + 1, String::new());
+			}
+			bank_ids[seat.0] = username.to_lowercase();
+			drop(bank_ids);
+			game.arrivals.lock().unwrap_or_else(|e| e.into_inner()).push((Arc::new(player), stack));
+
+			room.players.insert(seat, conn_id);
+			Some((seat, room.config.clone()))
+		}
+		_ => None,
+	};
+
+	match seated {
+		Some((seat, table_config)) => {
+			if let Some(conn) = conns.get_mut(&conn_id) {
+				conn.current_table = Some(to.to_string());
+				conn.send(&ServerMessage::TableChanged { table_id: to.to_string(), seat, table_config });
+			}
+			let msg = ServerMessage::PlayerJoinedTable { seat, username };
+			broadcast_to_table_except(to, conn_id, &msg, &mut tables_lock, &mut conns);
+		}
+		None => {
+			let mut bank_lock = lock_bank(bank);
+			bank_lock.cashout(&username.to_lowercase(), stack, &AuditRef::table(from));
+			if let Err(e) = bank_lock.save() {
+				eprintln!("Failed to save bank after table change: {}", e);
+			}
+			drop(bank_lock);
+			if let Some(conn) = conns.get_mut(&conn_id) {
+				conn.current_table = None;
+				conn.send(&ServerMessage::Error { message: format!("Couldn't get a seat at {}", to) });
+				conn.send(&ServerMessage::TableLeft);
+			}
+		}
+	}
+
+	let table_list = build_table_list(&tables_lock);
+	broadcast_lobby_state(&table_list, &mut conns);
+}
+
+fn broadcast_to_table(
+	table_id: &str,
+	msg: &ServerMessage,
+	tables: &mut HashMap<String, TableRoom>,
+	conns: &mut HashMap<ConnectionId, Connection>,
+) {
+	if let Some(table) = tables.get(table_id) {
+		for &conn_id in table.players.values() {
+			if let Some(conn) = conns.get_mut(&conn_id) {
+				conn.send(msg);
+			}
+		}
+	}
+}
+
+fn broadcast_to_table_except(
+	table_id: &str,
+	exclude: ConnectionId,
+	msg: &ServerMessage,
+	tables: &mut HashMap<String, TableRoom>,
+	conns: &mut HashMap<ConnectionId, Connection>,
+) {
+	if let Some(table) = tables.get(table_id) {
+		for &conn_id in table.players.values() {
+			if conn_id != exclude {
+				if let Some(conn) = conns.get_mut(&conn_id) {
+					conn.send(msg);
+				}
+			}
+		}
+	}
+}
+
+/// Puts tables whose game is over back to waiting. A game that died
+/// before settling up still holds buy-ins in escrow; they go back to
+/// their owners. True if any table was cleaned up.
+fn cleanup_finished_games(tables: &mut HashMap<String, TableRoom>, bank: &Arc<Mutex<Bank>>) -> bool {
+	let mut finished = Vec::new();
+	for table in tables.values_mut() {
+		if table.status == TableStatus::InProgress {
+			if let Some(ref game) = table.active_game {
+				if game.is_finished() {
+					finished.push(game.game_id);
+					table.reset();
+				}
+			}
+		}
+	}
+
+	let games = finished.iter().flatten().copied().collect::<Vec<_>>();
+	if !games.is_empty() {
+		let mut bank_lock = lock_bank(bank);
+		let refunded: f32 = games.into_iter().map(|game| bank_lock.refund_escrow(game)).sum();
+		if refunded > 0.0 {
+			logging::log("Server", "WARN", &format!("Refunded {} held by games that died", money(refunded)));
+			if let Err(e) = bank_lock.save() {
+				eprintln!("Failed to save bank after refunding buy-ins: {}", e);
+			}
+		}
+	}
+	!finished.is_empty()
+}
+
+/// Stands everyone up at tables that have had players but no game for
+/// longer than `idle`, leaving them empty for the next lot. True if any
+/// table was cleared.
+fn clear_idle_tables(
+	idle: Duration,
+	tables: &mut HashMap<String, TableRoom>,
+	conns: &mut HashMap<ConnectionId, Connection>,
+) -> bool {
+	let mut any_cleared = false;
+	for table in tables.values_mut() {
+		if table.status != TableStatus::Waiting || table.player_count() == 0 || table.last_activity.elapsed() < idle {
+			continue;
+		}
+		let message = format!("Nothing started at {} for a while, so the table was cleared", table.config.name);
+		for conn_id in table.players.values() {
+			if let Some(conn) = conns.get_mut(conn_id) {
+				conn.current_table = None;
+				conn.send(&ServerMessage::Error { message: message.clone() });
+				conn.send(&ServerMessage::TableLeft);
+			}
+		}
+		table.reset();
+		any_cleared = true;
+	}
+	any_cleared
+}
+
+/// One pass of the reaper: cleans up finished games and, with an idle
+/// timeout, clears tables nobody has started.
+fn reap(
+	tables: &Arc<Mutex<HashMap<String, TableRoom>>>,
+	connections: &Arc<Mutex<HashMap<ConnectionId, Connection>>>,
+	bank: &Arc<Mutex<Bank>>,
+	idle: Option<Duration>,
+) {
+	// Lock order: tables first, then connections, then bank
+	let mut tables_lock = lock_tables(tables);
+	let mut conns = lock_connections(connections);
+	let cleaned = cleanup_finished_games(&mut tables_lock, bank);
+	let cleared = idle.is_some_and(|idle| clear_idle_tables(idle, &mut tables_lock, &mut conns));
+	if cleaned || cleared {
+		let table_list = build_table_list(&tables_lock);
+		broadcast_lobby_state(&table_list, &mut conns);
+	}
+}
+
+/// Holds the games at the tables `poker admin pause` lists, and lets go of
+/// those taken off the list since the last pass; `held` is the games it
+/// paused. A game the host paused is left to them.
+fn hold_paused_tables(
+	tables: &HashMap<String, TableRoom>,
+	listed: &[String],
+	held: &mut std::collections::HashSet<String>,
+) {
+	held.retain(|table_id| tables.get(table_id).is_some_and(|t| t.active_game.is_some()));
+	for (table_id, table) in tables {
+		let Some(ref active_game) = table.active_game else { continue };
+		if listed.contains(table_id) {
+			if active_game.pause.set(true) {
+				logging::log("Server", "INFO", &format!("Paused the game at {}", table_id));
+				held.insert(table_id.clone());
+			}
+		} else if held.remove(table_id) && active_game.pause.set(false) {
+			logging::log("Server", "INFO", &format!("Resumed the game at {}", table_id));
+		}
 	}
-	any_cleaned
 }
 
 fn build_table_list(tables: &HashMap<String, TableRoom>) -> Vec<TableInfo> {
@@ -1053,15 +2047,16 @@ fn broadcast_lobby_state(table_list: &[TableInfo], conns: &mut HashMap<Connectio
 	}
 }
 
-fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>) -> ActiveGame {
+fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>, observers: Observers) -> ActiveGame {
 	let runtime = tokio::runtime::Builder::new_multi_thread()
 		.enable_all()
 		.build()
 		.expect("Failed to create tokio runtime for game");
 	let runtime_handle = runtime.handle().clone();
 
-	let runner_config = build_runner_config(&info.config);
-	let (mut runner, game_handle) = GameRunner::new(runner_config, runtime_handle.clone());
+	let mut runner_config = build_runner_config(&info.config);
+	runner_config.game_id = Some(info.game_id);
+	let (mut runner, game_handle) = GameRunner::with_queue(runner_config, runtime_handle.clone(), observers.event_queue);
 
 	let game_finished = Arc::new(AtomicBool::new(false));
 	let mut active_game = ActiveGame::new(
@@ -1069,27 +2064,39 @@ fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>) -> ActiveGame {
 		Arc::clone(&game_finished),
 		Arc::clone(&game_handle.quit_signal),
 	);
+	active_game.takeover_grace = info.config.takeover_grace();
+	active_game.hold_seats = info.hold_seats;
+	match observers.recordings.start(&info.config.id, info.game_id) {
+		Ok(recording) => active_game.recording = Some(Arc::new(Mutex::new(recording))),
+		Err(e) => logging::log("Recording", "ERROR", &e.to_string()),
+	}
 
 	// Load strategies for AI players
 	let strategies = load_strategies_auto().unwrap_or_default();
-	let big_blind = info.config.big_blind.unwrap_or(2.0);
+	let roster = load_players_auto().unwrap_or_default();
+	let mut ai_tempos: HashMap<Seat, Tempo> = HashMap::new();
 
 	// Capture config for game end processing
 	let game_format = info.config.format;
 	let table_id = info.config.id.clone();
-	let player_bank_ids = info.player_bank_ids.clone();
 	let payouts_config = info.config.payouts.clone();
 	let buy_in = info.config.buy_in;
-
-	// Capture delays from config
-	let action_delay_ms = info.config.action_delay_ms;
-	let street_delay_ms = info.config.street_delay_ms;
-	let hand_end_delay_ms = info.config.hand_end_delay_ms;
+	let Observers { feed, history, leagues, .. } = observers;
+	// A game is the league's game for the day it started
+	let started_on = Local::now().date_naive();
+	let mut recorder = HandRecorder::new(game_handle.game_id, info.config.name.clone())
+		.with_transparency(info.config.transparency);
+	let mut shuffle = ShuffleTally::new(game_handle.game_id, info.config.name.clone(), info.config.seed);
+	let promotions = info.config.promotions.clone().map(Promotions::new);
+	let jackpot = info.config.promotions.as_ref().and_then(|p| p.jackpot.clone());
+	let betting = info.config.betting;
+
+	let table_pacing = Pacing::from_table(&info.config);
 
 	// Combine all players and sort by seat for consistent ordering
 	enum PlayerSlot {
 		Human { conn_id: ConnectionId, name: String, stream: TcpStream },
-		AI { name: String, strategy: String },
+		AI { id: String, name: String, strategy: String, stack: Option<f32> },
 	}
 
 	let mut all_players: Vec<(Seat, PlayerSlot)> = Vec::new();
@@ -1098,14 +2105,14 @@ fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>) -> ActiveGame {
 		all_players.push((seat, PlayerSlot::Human { conn_id, name: username, stream }));
 	}
 
-	for (seat, _id, name, strategy) in info.ai_players {
-		all_players.push((seat, PlayerSlot::AI { name, strategy }));
+	for (seat, id, name, strategy, stack) in info.ai_players {
+		all_players.push((seat, PlayerSlot::AI { id, name, strategy, stack }));
 	}
 
 	all_players.sort_by_key(|(seat, _)| seat.0);
 
 	// Collect streams for event forwarding (human players only)
-	let mut player_streams: Vec<(Seat, Arc<Mutex<TcpStream>>)> = Vec::new();
+	let mut player_streams: SeatStreams = Vec::new();
 
 	for (table_seat, slot) in all_players.into_iter() {
 		match slot {
@@ -1114,20 +2121,61 @@ fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>) -> ActiveGame {
 					player_streams.push((table_seat, Arc::new(Mutex::new(stream_for_events))));
 				}
 
-				let (action_tx, action_rx) = mpsc::channel();
-				active_game.register_player(conn_id, table_seat, action_tx);
+				let (action_tx, action_rx) = mpsc::unbounded_channel();
+				let auto_muck = Arc::new(AtomicBool::new(false));
+				active_game.register_player(conn_id, table_seat, name.clone(), action_tx, Arc::clone(&auto_muck));
 
-				let player = RemotePlayer::new(table_seat, name, action_rx);
+				let mut player = RemotePlayer::new(table_seat, name.clone(), action_rx).with_auto_muck(auto_muck);
+				if let Some(grace) = info.config.takeover_grace() {
+					let stand_in = RulesPlayer::new(table_seat, &name, strategies.get_or_default(STAND_IN_STRATEGY));
+					player = player.with_takeover(stand_in, grace, Arc::clone(&active_game.away));
+				}
 				runner.add_player(Arc::new(player));
 			}
-			PlayerSlot::AI { name, strategy } => {
+			PlayerSlot::AI { id, name, strategy, stack } => {
 				let strat = strategies.get_or_default(&strategy);
-				let player = RulesPlayer::new(table_seat, &name, strat, big_blind);
-				runner.add_player(Arc::new(player));
+				let tempo = roster.iter()
+					.find(|p| p.id == id)
+					.and_then(|p| p.tempo)
+					.unwrap_or(strat.tempo);
+				ai_tempos.insert(table_seat, tempo);
+				let mut player = RulesPlayer::new(table_seat, &name, strat);
+				if info.config.table_talk {
+					player = player.with_table_talk(info.config.table_talk_frequency);
+				}
+				let player = PooledPlayer::new(Arc::new(player), &observers.decisions, info.game_id);
+				match stack {
+					Some(stack) => runner.add_player_with_stack(Arc::new(player), stack),
+					None => runner.add_player(Arc::new(player)),
+				}
 			}
 		}
 	}
 
+	*active_game.streams.lock().unwrap_or_else(|e| e.into_inner()) = player_streams;
+	*active_game.bank_ids.lock().unwrap_or_else(|e| e.into_inner()) = info.player_bank_ids;
+	active_game.leaving = Arc::clone(&game_handle.leaving);
+	active_game.arrivals = Arc::clone(&game_handle.arrivals);
+	active_game.pause = game_handle.pause.clone();
+	if info.config.pit_boss {
+		let model = match pit_boss::configured_model() {
+			Ok(model) => Some(model),
+			Err(e) => {
+				logging::log("PitBoss", "INFO", &format!("Running without a model: {}", e));
+				None
+			}
+		};
+		let streams = Arc::clone(&active_game.streams);
+		let sitting_out = Arc::clone(&game_handle.sitting_out);
+		let boss = PitBoss::new(PitBossConfig::default(), model).spawn(move |text| {
+			let event = GameEvent::ChatMessage { sender: ChatSender::Dealer, text };
+			send_to_seats(&streams, &sitting_out, &ServerMessage::GameEvent(event));
+		});
+		active_game.pit_boss = Some(boss);
+	}
+	let pit_boss = active_game.pit_boss.clone();
+	let recording = active_game.recording.clone();
+
 	thread::spawn(move || {
 		let _rt_guard = runtime.enter();
 		runner.run();
@@ -1136,8 +2184,106 @@ fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>) -> ActiveGame {
 	// Forward events to all players with filtering and pacing
 	let game_finished_clone = Arc::clone(&game_finished);
 	let sitting_out = Arc::clone(&game_handle.sitting_out);
-	thread::spawn(move || {
+	let away = Arc::clone(&active_game.away);
+	let fast_forward = Arc::clone(&active_game.fast_forward);
+	let pacing = Arc::clone(&active_game.pacing);
+	let streams = Arc::clone(&active_game.streams);
+	let bank_ids = Arc::clone(&active_game.bank_ids);
+	let migrations = Arc::clone(&active_game.migrations);
+	let decisions = observers.decisions.clone();
+	let relay = thread::spawn(move || {
+		let game_id = game_handle.game_id;
+		let mut big_blind = 0.0;
+		// Until a hand is dealt, the game ending means it never got going
+		let mut dealt = false;
+		// The hand in play, for the audit trail
+		let mut hand_id: Option<HandId> = None;
+		// Who's dealt in, for sharing out a jackpot
+		let mut dealt_in: Vec<(Seat, String)> = Vec::new();
+		// Hands each human has played in this game, for the daily challenges
+		let mut session_hands: HashMap<Seat, u32> = HashMap::new();
 		while let Ok(event) = game_handle.event_rx.recv() {
+			let player_streams = streams.lock().unwrap_or_else(|e| e.into_inner()).clone();
+			let player_bank_ids = bank_ids.lock().unwrap_or_else(|e| e.into_inner()).clone();
+
+			// Clone the sets so we don't hold the locks during I/O
+			let disconnected = {
+				let mut gone = sitting_out.lock().unwrap_or_else(|e| e.into_inner()).clone();
+				gone.extend(away.lock().unwrap_or_else(|e| e.into_inner()).keys());
+				gone
+			};
+
+			// Everyone still seated wants to skip ahead
+			let skipping = {
+				let wanted = fast_forward.lock().unwrap_or_else(|e| e.into_inner());
+				let mut present = player_streams.iter()
+					.map(|(seat, _)| seat)
+					.filter(|s| !disconnected.contains(s))
+					.peekable();
+				present.peek().is_some() && present.all(|s| wanted.contains(s))
+			};
+
+			// The slowest pace anyone seated asked for
+			let pace = {
+				let paces = pacing.lock().unwrap_or_else(|e| e.into_inner());
+				let present = player_streams.iter()
+					.filter(|(seat, _)| !disconnected.contains(seat))
+					.map(|(seat, _)| paces.get(seat).copied().unwrap_or_default())
+					.collect::<Vec<_>>();
+				Pacing {
+					action_ms: slowest(table_pacing.action_ms, present.iter().map(|p| p.0)),
+					street_ms: slowest(table_pacing.street_ms, present.iter().map(|p| p.1)),
+					..table_pacing
+				}
+			};
+
+			if let GameEvent::HandStarted { blinds, hand_id: id, seats, .. } = &event {
+				big_blind = blinds.big;
+				dealt = true;
+				hand_id = Some(*id);
+				dealt_in = seats.iter().filter(|s| s.is_active).map(|s| (s.seat, s.name.clone())).collect();
+			}
+			// An AI's pause goes before its action, so it reads as thinking
+			if let GameEvent::ActionTaken { seat, action, pot_after, .. } = &event {
+				if let Some(&tempo) = ai_tempos.get(seat) {
+					if !skipping {
+						let base = Duration::from_millis(pace.action_ms);
+						thread::sleep(think_time(tempo, base, action, *pot_after, big_blind, &mut rand::rng()));
+					}
+				}
+			}
+
+			if let Some(feed) = &feed {
+				feed.observe_game(game_handle.game_id, &event);
+			}
+			if let Some(recording) = &recording {
+				if let Some(seen) = redact(&event, Audience::Admin) {
+					if let Err(e) = recording.lock().unwrap_or_else(|e| e.into_inner()).record(Recorded::Event(seen)) {
+						logging::log("Recording", "ERROR", &e.to_string());
+					}
+				}
+			}
+			let finished_hand = recorder.record(&event);
+			if let (Some(history), Some(hand)) = (&history, &finished_hand) {
+				if let Err(e) = history.append(hand) {
+					logging::log("History", "ERROR", &e.to_string());
+				}
+			}
+			shuffle.record(&event);
+			if let (Some(history), GameEvent::GameEnded { .. }) = (&history, &event) {
+				if let Err(e) = history.end_session(game_handle.game_id) {
+					logging::log("History", "ERROR", &e.to_string());
+				}
+				if !shuffle.report().decks.is_empty() {
+					if let Err(e) = history.save_shuffle(shuffle.report()) {
+						logging::log("History", "ERROR", &e.to_string());
+					}
+				}
+			}
+			if let Some(pit_boss) = &pit_boss {
+				pit_boss.event(&event);
+			}
+
 			// Log all events for debugging
 			if matches!(&event, GameEvent::PlayerCashedOut { .. } | GameEvent::GameEnded { .. }) {
 				use std::fs::OpenOptions;
@@ -1147,17 +2293,12 @@ fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>) -> ActiveGame {
 				}
 			}
 
-			// Clone the set so we don't hold the lock during I/O
-			let disconnected = sitting_out.lock()
-				.unwrap_or_else(|e| e.into_inner())
-				.clone();
-
 			for (seat, stream) in &player_streams {
 				// Skip players who have disconnected
 				if disconnected.contains(seat) {
 					continue;
 				}
-				let filtered = filter_event_for_seat(&event, *seat);
+				let Some(filtered) = redact(&event, Audience::Player(*seat)) else { continue };
 				let msg = ServerMessage::GameEvent(filtered);
 				let data = encode_message(&msg);
 				if let Ok(mut s) = stream.lock() {
@@ -1169,11 +2310,11 @@ fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>) -> ActiveGame {
 					}
 
 					// Send ActionRequest message to the acting player
-					if let GameEvent::ActionRequest { seat: action_seat, valid_actions, .. } = &event {
+					if let GameEvent::ActionRequest { seat: action_seat, valid_actions, time_limit, .. } = &event {
 						if action_seat == seat {
 							let action_msg = ServerMessage::ActionRequest {
 								valid_actions: valid_actions.clone(),
-								time_limit: Some(120),
+								time_limit: *time_limit,
 							};
 							let action_data = encode_message(&action_msg);
 							if let Err(e) = s.write_all(&action_data) {
@@ -1186,8 +2327,136 @@ fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>) -> ActiveGame {
 				}
 			}
 
-			// Handle mid-game cashout for players who left
-			if let GameEvent::PlayerCashedOut { seat, name, amount } = &event {
+			// The bad-beat jackpot fills from pots and pays out at once
+			if let Some(jackpot) = &jackpot {
+				match &event {
+					GameEvent::JackpotDrop { amount } => {
+						let mut bank_lock = bank.lock().unwrap_or_else(|e| e.into_inner());
+						bank_lock.add_to_jackpot(&jackpot.pool, *amount);
+						if let Err(e) = bank_lock.save() {
+							eprintln!("Failed to save bank after jackpot drop: {}", e);
+						}
+					}
+					GameEvent::BadBeatJackpot { loser, losing_hand, winners, winning_hand } => {
+						let name = |seat: Seat| {
+							dealt_in.iter().find(|(s, _)| *s == seat).map_or(String::new(), |(_, n)| n.clone())
+						};
+						let mut bank_lock = bank.lock().unwrap_or_else(|e| e.into_inner());
+						let pool = bank_lock.take_jackpot(&jackpot.pool);
+						let seats = dealt_in.iter().map(|(seat, _)| *seat).collect::<Vec<_>>();
+						let at = AuditRef::table(&table_id).game(game_id).hand(hand_id);
+						let mut awarded = vec![GameEvent::ChatMessage {
+							sender: ChatSender::Dealer,
+							text: format!(
+								"Bad beat! {} loses with {} to {}: the {} jackpot pays out",
+								name(*loser), losing_hand, winning_hand, money(pool)
+							),
+						}];
+						let mut paid = 0.0;
+						for (seat, amount) in jackpot.split(pool, *loser, winners, &seats) {
+							if let Some(bank_id) = player_bank_ids.get(seat.0) {
+								bank_lock.award_promotion(bank_id, amount, "bad-beat jackpot", &at);
+								paid += amount;
+								awarded.push(GameEvent::PromotionAwarded { seat, promotion: Promotion::Jackpot, amount });
+							}
+						}
+						// Odd cents and the shares nobody takes seed the next one
+						bank_lock.add_to_jackpot(&jackpot.pool, pool - paid);
+						if let Err(e) = bank_lock.save() {
+							eprintln!("Failed to save bank after jackpot: {}", e);
+						}
+						drop(bank_lock);
+						for event in awarded {
+							send_to_seats(&streams, &sitting_out, &ServerMessage::GameEvent(event));
+						}
+					}
+					_ => {}
+				}
+			}
+
+			// Promotions go out once the hand is over
+			if let (Some(promotions), Some(hand)) = (&promotions, &finished_hand) {
+				let earned = promotions.hand(hand);
+				let mut awarded = Vec::new();
+				if !earned.rakeback.is_empty() || !earned.high_hand.is_empty() {
+					let mut bank_lock = bank.lock().unwrap_or_else(|e| e.into_inner());
+					for (seat, amount) in &earned.rakeback {
+						if let Some(bank_id) = player_bank_ids.get(seat.0) {
+							bank_lock.accrue_rakeback(bank_id, *amount);
+							awarded.push(GameEvent::PromotionAwarded { seat: *seat, promotion: Promotion::Rakeback, amount: *amount });
+						}
+					}
+					for (seat, amount, category) in &earned.high_hand {
+						if let Some(bank_id) = player_bank_ids.get(seat.0) {
+							let at = AuditRef::table(&table_id).game(game_id).hand(Some(hand.hand_id));
+							bank_lock.award_promotion(bank_id, *amount, &format!("high hand, {}", category), &at);
+							awarded.push(GameEvent::PromotionAwarded {
+								seat: *seat,
+								promotion: Promotion::HighHand { hand: category.to_string() },
+								amount: *amount,
+							});
+							let name = hand.players.iter().find(|p| p.seat == *seat).map_or("", |p| p.name.as_str());
+							awarded.push(GameEvent::ChatMessage {
+								sender: ChatSender::Dealer,
+								text: format!("High hand! {} wins {} with {}", name, money(*amount), category),
+							});
+						}
+					}
+					if let Err(e) = bank_lock.save() {
+						eprintln!("Failed to save bank after promotions: {}", e);
+					}
+				}
+				for event in awarded {
+					send_to_seats(&streams, &sitting_out, &ServerMessage::GameEvent(event));
+				}
+			}
+
+			// Daily challenges count each human's finished hands
+			if let Some(hand) = finished_hand.as_ref().filter(|h| h.players.iter().any(|p| p.is_human)) {
+				let today = Local::now().date_naive();
+				let mut awarded = Vec::new();
+				let mut bank_lock = bank.lock().unwrap_or_else(|e| e.into_inner());
+				for player in hand.players.iter().filter(|p| p.is_human) {
+					let Some(bank_id) = player_bank_ids.get(player.seat.0) else { continue };
+					let played = session_hands.entry(player.seat).or_default();
+					*played += 1;
+					let Some(outcome) = HandOutcome::of(hand, player.seat, betting, *played) else { continue };
+					let at = AuditRef::table(&table_id).game(game_id).hand(Some(hand.hand_id));
+					for reward in bank_lock.progress_challenges(bank_id, today, &outcome, &at) {
+						awarded.push(GameEvent::PromotionAwarded {
+							seat: player.seat,
+							promotion: Promotion::Challenge { goal: reward.describe() },
+							amount: reward.chips(),
+						});
+						awarded.push(GameEvent::ChatMessage {
+							sender: ChatSender::Dealer,
+							text: format!("Daily challenge for {}: {} ({})", player.name, reward.describe(), money(reward.chips())),
+						});
+					}
+				}
+				if let Err(e) = bank_lock.save() {
+					eprintln!("Failed to save bank after daily challenges: {}", e);
+				}
+				drop(bank_lock);
+				for event in awarded {
+					send_to_seats(&streams, &sitting_out, &ServerMessage::GameEvent(event));
+				}
+			}
+
+			// A player changing tables takes their stack along instead
+			let migration = match &event {
+				GameEvent::PlayerCashedOut { seat, .. } => {
+					migrations.lock().unwrap_or_else(|e| e.into_inner()).remove(seat)
+				}
+				_ => None,
+			};
+			if let (Some(migrate), GameEvent::PlayerCashedOut { seat, amount, .. }) = (migration, &event) {
+				if let Some(bank_id) = player_bank_ids.get(seat.0) {
+					bank.lock().unwrap_or_else(|e| e.into_inner()).release_escrow(game_id, bank_id);
+				}
+				migrate(*amount);
+			} else if let GameEvent::PlayerCashedOut { seat, name, amount } = &event {
+				// Handle mid-game cashout for players who left
 				use crate::table::GameFormat;
 				use std::fs::OpenOptions;
 				use std::io::Write as IoWrite;
@@ -1201,7 +2470,8 @@ fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>) -> ActiveGame {
 						if let Ok(mut f) = OpenOptions::new().create(true).append(true).open("logs/server-debug.log") {
 							let _ = writeln!(f, "Calling cashout for bank_id={}", bank_id);
 						}
-						bank_lock.cashout(bank_id, *amount, &table_id);
+						bank_lock.cashout(bank_id, *amount, &AuditRef::table(&table_id).game(game_id).hand(hand_id));
+						bank_lock.release_escrow(game_id, bank_id);
 					} else {
 						if let Ok(mut f) = OpenOptions::new().create(true).append(true).open("logs/server-debug.log") {
 							let _ = writeln!(f, "No bank_id at index {}", seat.0);
@@ -1217,14 +2487,32 @@ fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>) -> ActiveGame {
 			if let GameEvent::GameEnded { final_standings, .. } = &event {
 				use crate::table::GameFormat;
 
+				let queue = game_handle.queue.stats();
+				logging::log("Server", "INFO", &format!(
+					"Event queue at {}: peak {} of {}, {} dropped, held {} times",
+					table_id, queue.peak, queue.capacity, queue.dropped, queue.stalls,
+				));
+				if let Some(ai) = decisions.finish(game_id) {
+					logging::log("Server", "INFO", &format!(
+						"AI decisions at {}: {} made, waiting {}ms on average ({}ms at most), thinking {}ms ({}ms at most)",
+						table_id, ai.decisions, ai.mean_wait().as_millis(), ai.longest_wait.as_millis(),
+						ai.mean_think().as_millis(), ai.longest_think.as_millis(),
+					));
+				}
+
 				let mut bank_lock = bank.lock().unwrap_or_else(|e| e.into_inner());
+				let at = AuditRef::table(&table_id).game(game_id).hand(hand_id);
 
 				match game_format {
+					// Over before a card was dealt: everyone gets their buy-in back
+					_ if !dealt => {
+						bank_lock.refund_escrow(game_id);
+					}
 					GameFormat::Cash => {
 						// Cash game: return remaining stacks to players
 						for standing in final_standings {
 							if let Some(bank_id) = player_bank_ids.get(standing.seat.0) {
-								bank_lock.cashout(bank_id, standing.final_stack, &table_id);
+								bank_lock.cashout(bank_id, standing.final_stack, &at);
 							}
 						}
 					}
@@ -1236,7 +2524,7 @@ fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>) -> ActiveGame {
 							for (i, payout) in payouts.iter().enumerate() {
 								if let Some(standing) = final_standings.iter().find(|s| s.finish_position == (i + 1) as u8) {
 									if let Some(bank_id) = player_bank_ids.get(standing.seat.0) {
-										bank_lock.award_prize(bank_id, *payout, i + 1);
+										bank_lock.award_prize(bank_id, *payout, i + 1, &at);
 									}
 								}
 							}
@@ -1244,155 +2532,460 @@ fn start_game(info: GameStartInfo, bank: Arc<Mutex<Bank>>) -> ActiveGame {
 					}
 				}
 
+				bank_lock.settle_escrow(game_id);
+				pay_rakeback(&mut bank_lock);
 				if let Err(e) = bank_lock.save() {
 					eprintln!("Failed to save bank after game end: {}", e);
 				}
 
+				if dealt && game_format == GameFormat::SitNGo {
+					let finished = final_standings.iter()
+						.filter_map(|s| Some((player_bank_ids.get(s.seat.0)?.clone(), s.finish_position)))
+						.collect::<Vec<_>>();
+					match leagues.record(&table_id, started_on, game_id, &finished) {
+						Ok(counted) => for league in counted {
+							logging::log("League", "INFO", &format!("Game {} at {} counted for league {}", game_id.0, table_id, league));
+						},
+						Err(e) => logging::log("League", "ERROR", &e.to_string()),
+					}
+				}
+
 				// Signal that the game has finished
 				game_finished_clone.store(true, Ordering::SeqCst);
 			}
 
-			// Use delays from table config
-			let delay_ms = match &event {
-				GameEvent::ActionTaken { .. } => action_delay_ms,
-				GameEvent::StreetChanged { .. } => street_delay_ms,
-				GameEvent::ShowdownReveal { .. } => 500,
-				GameEvent::HandEnded { .. } => hand_end_delay_ms,
-				GameEvent::PotAwarded { .. } => 1500,
-				_ => 0,
+			// An AI's action already had its think time
+			let delay = match &event {
+				_ if skipping => Duration::ZERO,
+				GameEvent::ActionTaken { seat, .. } if ai_tempos.contains_key(seat) => Duration::ZERO,
+				_ => pace.delay(&event),
 			};
+			if !delay.is_zero() {
+				thread::sleep(delay);
+			}
+		}
 
-			if delay_ms > 0 {
-				thread::sleep(std::time::Duration::from_millis(delay_ms));
+		// The engine went away without ending the game; hand back whatever
+		// is still held rather than lose it
+		let mut bank_lock = bank.lock().unwrap_or_else(|e| e.into_inner());
+		if bank_lock.refund_escrow(game_id) > 0.0 {
+			if let Err(e) = bank_lock.save() {
+				eprintln!("Failed to save bank after refunding buy-ins: {}", e);
 			}
 		}
 	});
+	active_game.game_id = Some(info.game_id);
+	active_game.relay = Some(relay);
 
 	active_game
 }
 
-fn filter_event_for_seat(event: &GameEvent, seat: Seat) -> GameEvent {
-	match event {
-		GameEvent::HoleCardsDealt { seat: dealt_seat, cards: _ } => {
-			if *dealt_seat == seat {
-				event.clone()
-			} else {
-				GameEvent::HoleCardsDealt {
-					seat: *dealt_seat,
-					cards: [
-						Card { rank: '?', suit: '?' },
-						Card { rank: '?', suit: '?' },
-					],
+/// Pays the week's rakeback if it's due; true if anyone was paid.
+fn pay_rakeback(bank: &mut Bank) -> bool {
+	let paid = bank.pay_rakeback(Local::now().date_naive());
+	let total = paid.iter().map(|(_, amount)| amount).sum::<f32>();
+	if total > 0.0 {
+		logging::log("Server", "INFO", &format!("Paid {} rakeback to {} players", money(total), paid.len()));
+	}
+	!paid.is_empty()
+}
+
+/// The longest of the delays players chose, with the table's standing in
+/// for anyone who didn't choose.
+fn slowest(table_ms: u64, chosen: impl Iterator<Item = Option<u64>>) -> u64 {
+	chosen.map(|ms| ms.unwrap_or(table_ms)).max().unwrap_or(table_ms)
+}
+
+/// Sends a message on each seated player's game stream, skipping anyone
+/// who has left.
+fn send_to_seats(streams: &Mutex<SeatStreams>, sitting_out: &Mutex<std::collections::HashSet<Seat>>, msg: &ServerMessage) {
+	let gone = sitting_out.lock().unwrap_or_else(|e| e.into_inner()).clone();
+	let streams = streams.lock().unwrap_or_else(|e| e.into_inner()).clone();
+	let data = encode_message(msg);
+	for (seat, stream) in &streams {
+		if gone.contains(seat) {
+			continue;
+		}
+		if let Ok(mut s) = stream.lock() {
+			if let Err(e) = s.write_all(&data) {
+				if e.kind() != std::io::ErrorKind::BrokenPipe {
+					eprintln!("Failed to send to seat {}: {}", seat.0, e);
 				}
 			}
 		}
-		_ => event.clone(),
 	}
 }
 
 fn build_runner_config(table: &TableConfig) -> RunnerConfig {
-	let (small_blind, big_blind) = table.current_blinds();
-	let starting_stack = table.effective_starting_stack();
-
-	// Cash games use fixed seats, tournaments use compact mode
-	let max_seats = match table.format {
-		crate::table::GameFormat::Cash => Some(table.max_players),
-		crate::table::GameFormat::SitNGo => None,
-	};
-
-	RunnerConfig {
-		small_blind,
-		big_blind,
-		starting_stack,
-		betting_structure: match table.betting {
-			crate::table::BettingStructure::NoLimit => BettingStructure::NoLimit,
-			crate::table::BettingStructure::PotLimit => BettingStructure::PotLimit,
-			crate::table::BettingStructure::FixedLimit => BettingStructure::FixedLimit,
-		},
-		blind_clock: None,
-		max_raises_per_round: table.max_raises_per_round,
-		rake_percent: table.rake_percent,
-		rake_cap: table.rake_cap,
-		no_flop_no_drop: table.no_flop_no_drop,
-		max_hands: None,
-		seed: table.seed,
-		max_seats,
-	}
+	let mut config = RunnerConfig::from_table(table);
+	// Network players always get a clock so a vanished client can't stall the table
+	config
+		.action_timeout
+		.get_or_insert(Duration::from_secs(DEFAULT_ACTION_TIMEOUT_SECS as u64));
+	config
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::net::moderation::Target;
 
 	#[test]
 	fn test_try_decode_message_too_short() {
-		let mut buf = vec![0, 0, 0];
-		assert!(try_decode_message(&mut buf).is_none());
+		let mut buf = vec![0xFE, 0xFF, 0];
+		assert!(try_decode_message(&mut buf, MAX_MESSAGE_SIZE).is_none());
 	}
 
 	#[test]
 	fn test_try_decode_message_oversized() {
-		let mut buf = vec![0xFF, 0xFF, 0xFF, 0xFF];
-		assert!(try_decode_message(&mut buf).is_none());
+		let mut buf = vec![0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0];
+		buf.extend(encode_message(&ClientMessage::Ready));
+		assert!(matches!(try_decode_message(&mut buf, MAX_MESSAGE_SIZE), Some(Err(_))));
+		// Only the bad header goes; the frame after it still arrives
+		assert!(matches!(try_decode_message(&mut buf, MAX_MESSAGE_SIZE), Some(Ok(ClientMessage::Ready))));
 		assert!(buf.is_empty());
 	}
 
 	#[test]
 	fn test_try_decode_message_incomplete() {
-		let mut buf = vec![0, 0, 0, 10, 1, 2, 3];
-		assert!(try_decode_message(&mut buf).is_none());
-		assert_eq!(buf.len(), 7);
+		let mut buf = vec![0xFE, 0xFF, 0, 0, 0, 10, 0, 0, 0, 0, 1, 2, 3];
+		assert!(try_decode_message(&mut buf, MAX_MESSAGE_SIZE).is_none());
+		assert_eq!(buf.len(), 13);
+	}
+
+	#[test]
+	fn test_registration_clock_starts_once() {
+		let sng: TableConfig = toml::from_str(r#"
+			id = "sng"
+			name = "SnG"
+			format = "sit-n-go"
+			betting = "no-limit"
+			registration_seconds = 30
+		"#).unwrap();
+		let mut table = TableRoom::new(sng.clone(), 0);
+		let closes = table.start_registration().expect("clock starts with the first player");
+		assert!(table.start_registration().is_none());
+		assert_eq!(table.registration_closes, Some(closes));
+		assert!(matches!(table.registration_seconds_left(), Some(29..=30)));
+
+		let cash = TableConfig { format: crate::table::GameFormat::Cash, ..sng };
+		assert!(TableRoom::new(cash, 1).start_registration().is_none());
+	}
+
+	#[test]
+	fn test_dropped_player_gets_their_seat_back() {
+		let mut game = ActiveGame::new(Arc::default(), Arc::default(), Arc::default());
+		game.takeover_grace = Some(Duration::from_secs(60));
+		let (action_tx, _action_rx) = mpsc::unbounded_channel();
+		game.register_player(1, Seat(2), "Ann".into(), action_tx, Arc::default());
+
+		assert_eq!(game.disconnect(1), Some(Seat(2)));
+		assert!(game.away.lock().unwrap().contains_key(&Seat(2)));
+		assert!(game.has_humans(), "a held seat keeps the game going");
+		assert!(game.sitting_out.lock().unwrap().is_empty());
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+		assert_eq!(game.reconnect(7, "bob", stream.try_clone().unwrap()), None);
+		assert_eq!(game.reconnect(7, "ANN", stream), Some(Seat(2)));
+		assert!(game.away.lock().unwrap().is_empty());
+		assert!(game.submit_action(7, PlayerAction::Fold).is_ok());
 	}
 
 	#[test]
-	fn test_filter_event_hides_other_hole_cards() {
-		let event = GameEvent::HoleCardsDealt {
-			seat: Seat(0),
-			cards: [
-				Card { rank: 'A', suit: 'h' },
-				Card { rank: 'K', suit: 'h' },
-			],
+	fn test_held_seats_pause_the_game_while_everyone_is_away() {
+		let mut game = ActiveGame::new(Arc::default(), Arc::default(), Arc::default());
+		game.hold_seats = true;
+		for (conn_id, seat, name) in [(1, Seat(0), "Ann"), (2, Seat(3), "Bob")] {
+			let (action_tx, _action_rx) = mpsc::unbounded_channel();
+			game.register_player(conn_id, seat, name.into(), action_tx, Arc::default());
+		}
+
+		assert_eq!(game.disconnect(1), Some(Seat(0)));
+		assert!(!game.pause.is_paused(), "Bob is still playing");
+		assert_eq!(game.disconnect(2), Some(Seat(3)));
+		assert!(game.pause.is_paused());
+		assert!(game.has_humans());
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+		assert_eq!(game.reconnect(7, "bob", stream), Some(Seat(3)));
+		assert!(!game.pause.is_paused());
+
+		// Without held seats or a takeover, a dropped player is just gone
+		let mut plain = ActiveGame::new(Arc::default(), Arc::default(), Arc::default());
+		let (action_tx, _action_rx) = mpsc::unbounded_channel();
+		plain.register_player(1, Seat(0), "Ann".into(), action_tx, Arc::default());
+		assert_eq!(plain.disconnect(1), None);
+	}
+
+	#[test]
+	fn test_ready_is_ignored_once_the_game_is_on() {
+		let config: TableConfig = toml::from_str(r#"
+			id = "sng"
+			name = "SnG"
+			format = "sit-n-go"
+			betting = "no-limit"
+		"#).unwrap();
+		let mut room = TableRoom::new(config, 0);
+		room.add_player(Seat(0), 1);
+		room.status = TableStatus::InProgress;
+		room.active_game = Some(ActiveGame::new(Arc::default(), Arc::default(), Arc::default()));
+		let tables = Arc::new(Mutex::new(HashMap::from([("sng".to_string(), room)])));
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+		let (server_side, _) = listener.accept().unwrap();
+		let conn = Connection { username: Some("ann".into()), stream: server_side, current_table: Some("sng".into()), ip: None };
+		let connections = Arc::new(Mutex::new(HashMap::from([(1, conn)])));
+		let bank = Arc::new(Mutex::new(Bank::new_for_testing(HashMap::new())));
+		let path = std::env::temp_dir().join(format!("poker-server-ready-{}.toml", std::process::id()));
+		let moderation = Arc::new(Mutex::new(Moderation::new(&path)));
+
+		// A player back in a held seat readies up as if joining afresh
+		process_message(1, ClientMessage::Ready, &connections, &tables, &Arc::default(), &bank, &Observers::default(), &Limits::default(), &moderation, None);
+		let tables = lock_tables(&tables);
+		assert!(tables["sng"].ready.values().all(|&ready| !ready), "a running game isn't launched again");
+	}
+
+	#[test]
+	fn test_only_the_host_pauses() {
+		let config: TableConfig = toml::from_str(r#"
+			id = "home"
+			name = "Home"
+			format = "cash"
+			betting = "no-limit"
+		"#).unwrap();
+		let mut room = TableRoom::new(config, 0);
+		room.add_player(Seat(0), 1);
+		room.add_player(Seat(1), 2);
+		room.status = TableStatus::InProgress;
+		room.active_game = Some(ActiveGame::new(Arc::default(), Arc::default(), Arc::default()));
+		let tables = Arc::new(Mutex::new(HashMap::from([("home".to_string(), room)])));
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let mut connections = HashMap::new();
+		let mut clients = Vec::new();
+		for (conn_id, name) in [(1, "ann"), (2, "bob")] {
+			clients.push(TcpStream::connect(listener.local_addr().unwrap()).unwrap());
+			let (stream, _) = listener.accept().unwrap();
+			connections.insert(conn_id, Connection { username: Some(name.into()), stream, current_table: Some("home".into()), ip: None });
+		}
+		let connections = Arc::new(Mutex::new(connections));
+		let bank = Arc::new(Mutex::new(Bank::new_for_testing(HashMap::new())));
+		let path = std::env::temp_dir().join(format!("poker-server-pause-{}.toml", std::process::id()));
+		let moderation = Arc::new(Mutex::new(Moderation::new(&path)));
+		let send = |conn_id, paused| {
+			let msg = ClientMessage::Pause { paused };
+			process_message(conn_id, msg, &connections, &tables, &Arc::default(), &bank, &Observers::default(), &Limits::default(), &moderation, Some("Ann"));
 		};
-		
-		let filtered = filter_event_for_seat(&event, Seat(1));
-		
-		if let GameEvent::HoleCardsDealt { cards, .. } = filtered {
-			assert_eq!(cards[0].rank, '?');
-			assert_eq!(cards[1].rank, '?');
-		} else {
-			panic!("Expected HoleCardsDealt");
+		let paused = || lock_tables(&tables)["home"].active_game.as_ref().unwrap().pause.is_paused();
+
+		send(2, true);
+		assert!(!paused());
+		send(1, true);
+		assert!(paused());
+		send(2, false);
+		assert!(paused());
+		send(1, false);
+		assert!(!paused());
+
+		drop(connections);
+		let mut received = Vec::new();
+		clients[1].read_to_end(&mut received).unwrap();
+		let error = decode_frame::<ServerMessage>(&mut received, MAX_MESSAGE_SIZE).unwrap().unwrap();
+		assert!(matches!(error, ServerMessage::Error { message } if message.contains("host")));
+	}
+
+	#[test]
+	fn test_admin_pauses_hold_until_taken_off_the_list() {
+		let running = |id: &str| {
+			let config: TableConfig = toml::from_str(&format!(r#"
+				id = "{}"
+				name = "{}"
+				format = "cash"
+				betting = "no-limit"
+			"#, id, id)).unwrap();
+			let mut room = TableRoom::new(config, 0);
+			room.status = TableStatus::InProgress;
+			room.active_game = Some(ActiveGame::new(Arc::default(), Arc::default(), Arc::default()));
+			room
+		};
+		let tables = HashMap::from([("main".to_string(), running("main")), ("home".to_string(), running("home"))]);
+		let paused = |id: &str| tables[id].active_game.as_ref().unwrap().pause.is_paused();
+		let mut held = std::collections::HashSet::new();
+
+		// The host's own pause isn't the admin's to lift
+		tables["home"].active_game.as_ref().unwrap().pause.set(true);
+		hold_paused_tables(&tables, &["main".to_string()], &mut held);
+		assert!(paused("main") && paused("home"));
+		hold_paused_tables(&tables, &[], &mut held);
+		assert!(!paused("main") && paused("home"));
+
+		// Nor when the admin lists it too
+		hold_paused_tables(&tables, &["main".to_string(), "home".to_string()], &mut held);
+		assert_eq!(held, ["main".to_string()].into());
+		hold_paused_tables(&tables, &[], &mut held);
+		assert!(!paused("main") && paused("home"));
+		assert!(held.is_empty());
+	}
+
+	#[test]
+	fn test_dead_games_are_cleaned_up_and_refunded() {
+		let config: TableConfig = toml::from_str(r#"
+			id = "cash"
+			name = "Cash"
+			format = "cash"
+			betting = "no-limit"
+		"#).unwrap();
+		let mut room = TableRoom::new(config, 0);
+		room.add_player(Seat(0), 1);
+		room.status = TableStatus::InProgress;
+		let mut game = ActiveGame::new(Arc::default(), Arc::default(), Arc::default());
+		game.game_id = Some(GameId(5));
+		game.relay = Some(thread::spawn(|| {}));
+		while !game.is_finished() {
+			thread::sleep(Duration::from_millis(1));
 		}
+		room.active_game = Some(game);
+		let mut tables = HashMap::from([("cash".to_string(), room)]);
+
+		let mut bank = Bank::new_for_testing(HashMap::new());
+		bank.ensure_exists("ann");
+		let before = bank.get_bankroll("ann");
+		bank.escrow_buyin(GameId(5), "ann", 100.0, "cash").unwrap();
+		let bank = Arc::new(Mutex::new(bank));
+
+		// The relay went without the game ending, so nothing settled up
+		assert!(cleanup_finished_games(&mut tables, &bank));
+		assert_eq!(tables["cash"].status, TableStatus::Waiting);
+		assert!(tables["cash"].players.is_empty() && tables["cash"].active_game.is_none());
+		assert_eq!(lock_bank(&bank).get_bankroll("ann"), before);
+		assert!(!cleanup_finished_games(&mut tables, &bank));
 	}
 
 	#[test]
-	fn test_filter_event_shows_own_hole_cards() {
-		let event = GameEvent::HoleCardsDealt {
-			seat: Seat(0),
-			cards: [
-				Card { rank: 'A', suit: 'h' },
-				Card { rank: 'K', suit: 'h' },
-			],
+	fn test_idle_tables_are_cleared() {
+		let table = |id: &str| {
+			let config: TableConfig = toml::from_str(&format!(r#"
+				id = "{}"
+				name = "{}"
+				format = "cash"
+				betting = "no-limit"
+			"#, id, id)).unwrap();
+			TableRoom::new(config, 0)
 		};
-		
-		let filtered = filter_event_for_seat(&event, Seat(0));
-		
-		if let GameEvent::HoleCardsDealt { cards, .. } = filtered {
-			assert_eq!(cards[0].rank, 'A');
-			assert_eq!(cards[1].rank, 'K');
-		} else {
-			panic!("Expected HoleCardsDealt");
+		let mut stale = table("stale");
+		stale.add_player(Seat(0), 1);
+		stale.add_ai(Seat(1), "bot".into(), "Bot".into(), "balanced".into(), 1000.0);
+		stale.last_activity -= Duration::from_secs(120);
+		let mut fresh = table("fresh");
+		fresh.add_player(Seat(0), 2);
+		let mut tables = HashMap::from([("stale".to_string(), stale), ("fresh".to_string(), fresh), ("empty".to_string(), table("empty"))]);
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let mut conns = HashMap::new();
+		let mut clients = Vec::new();
+		for (conn_id, table_id) in [(1, "stale"), (2, "fresh")] {
+			clients.push(TcpStream::connect(listener.local_addr().unwrap()).unwrap());
+			let (stream, _) = listener.accept().unwrap();
+			conns.insert(conn_id, Connection { username: None, stream, current_table: Some(table_id.into()), ip: None });
 		}
+
+		assert!(clear_idle_tables(Duration::from_secs(60), &mut tables, &mut conns));
+		assert_eq!(tables["stale"].player_count(), 0);
+		assert_eq!(conns[&1].current_table, None);
+		assert_eq!(tables["fresh"].player_count(), 1);
+		assert_eq!(conns[&2].current_table.as_deref(), Some("fresh"));
+		assert!(!clear_idle_tables(Duration::from_secs(60), &mut tables, &mut conns));
+
+		drop(conns);
+		let mut received = Vec::new();
+		clients[0].read_to_end(&mut received).unwrap();
+		let error = decode_frame::<ServerMessage>(&mut received, MAX_MESSAGE_SIZE).unwrap().unwrap();
+		assert!(matches!(error, ServerMessage::Error { .. }));
+		let left = decode_frame::<ServerMessage>(&mut received, MAX_MESSAGE_SIZE).unwrap().unwrap();
+		assert!(matches!(left, ServerMessage::TableLeft));
+	}
+
+	#[test]
+	fn test_change_table_finds_a_seat_at_the_same_stakes() {
+		let running = |id: &str, big_blind: f32, order: usize| {
+			let config: TableConfig = toml::from_str(&format!(r#"
+				id = "{}"
+				name = "{}"
+				format = "cash"
+				betting = "no-limit"
+				small_blind = {}
+				big_blind = {}
+				max_players = 2
+			"#, id, id, big_blind / 2.0, big_blind)).unwrap();
+			let mut room = TableRoom::new(config, order);
+			room.status = TableStatus::InProgress;
+			room.active_game = Some(ActiveGame::new(Arc::default(), Arc::default(), Arc::default()));
+			room
+		};
+		let mut tables = HashMap::new();
+		let mut home = running("home", 2.0, 0);
+		home.add_player(Seat(0), 1);
+		let (action_tx, _action_rx) = mpsc::unbounded_channel();
+		home.active_game.as_mut().unwrap().register_player(1, Seat(0), "Ann".into(), action_tx, Arc::default());
+		tables.insert("home".to_string(), home);
+		tables.insert("bigger".to_string(), running("bigger", 10.0, 1));
+		let mut full = running("full", 2.0, 2);
+		full.add_player(Seat(0), 2);
+		full.add_player(Seat(1), 3);
+		tables.insert("full".to_string(), full);
+
+		let none = plan_table_change(1, "home", &tables).unwrap_err();
+		assert_eq!(none, "No other table at these stakes has a free seat");
+		assert!(plan_table_change(9, "home", &tables).is_err(), "only seated players can move");
+
+		tables.insert("twin".to_string(), running("twin", 2.0, 3));
+		assert_eq!(plan_table_change(1, "home", &tables), Ok((Seat(0), "twin".to_string())));
+	}
+
+	#[test]
+	fn test_banned_players_are_turned_away() {
+		let path = std::env::temp_dir().join(format!("poker-server-bans-{}.toml", std::process::id()));
+		let moderation = Arc::new(Mutex::new(Moderation::new(&path)));
+		lock_moderation(&moderation).update(|file| file.ban(Target::parse("mallory"), None, "collusion")).unwrap();
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+		let (server_side, peer) = listener.accept().unwrap();
+		let connections = Arc::new(Mutex::new(HashMap::new()));
+		let conn = Connection { username: None, stream: server_side, current_table: None, ip: Some(peer.ip()) };
+		lock_connections(&connections).insert(1, conn);
+
+		assert!(!refuse_if_banned(1, Some("alice"), &connections, &moderation));
+		lock_moderation(&moderation).update(|file| file.ban(Target::Ip(peer.ip()), None, "")).unwrap();
+		assert!(refuse_if_banned(1, Some("alice"), &connections, &moderation), "banned by address");
+
+		let mut received = Vec::new();
+		client.read_to_end(&mut received).unwrap();
+		let message = decode_frame::<ServerMessage>(&mut received, MAX_MESSAGE_SIZE).unwrap().unwrap();
+		assert!(matches!(message, ServerMessage::Error { message } if message == "You are banned for good"));
+		let _ = std::fs::remove_file(&path);
 	}
 
 	#[test]
 	fn test_max_message_size_constant() {
-		assert!(MAX_MESSAGE_SIZE > 0);
-		assert!(MAX_MESSAGE_SIZE <= 1024 * 1024);
+		const { assert!(MAX_MESSAGE_SIZE > 0) };
+		const { assert!(MAX_MESSAGE_SIZE <= 1024 * 1024) };
 	}
 
 	#[test]
 	fn test_username_length_limit() {
-		assert!(MAX_USERNAME_LENGTH > 0);
-		assert!(MAX_USERNAME_LENGTH <= 100);
+		const { assert!(MAX_USERNAME_LENGTH > 0) };
+		const { assert!(MAX_USERNAME_LENGTH <= 100) };
+	}
+
+	#[test]
+	fn test_slowest_pace_wins() {
+		assert_eq!(slowest(500, [Some(0), Some(1000)].into_iter()), 1000);
+		assert_eq!(slowest(500, [Some(0), None].into_iter()), 500);
+		assert_eq!(slowest(500, [Some(0)].into_iter()), 0);
+		assert_eq!(slowest(500, std::iter::empty()), 500);
 	}
 }
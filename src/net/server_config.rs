@@ -0,0 +1,295 @@
+//! `server.toml`: where a server listens, where it keeps its data, and the
+//! limits it holds clients to. Every key is optional; a missing file is
+//! the defaults. Relative paths are taken from the file's own directory.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::config::resolve_config;
+use crate::engine::EventQueueConfig;
+use crate::error::PokerError;
+use crate::logging::LogLevel;
+use crate::players::DEFAULT_DECISION_WORKERS;
+use crate::net::protocol::MAX_FRAME_LEN;
+use crate::net::server::{
+	DEFAULT_ACTION_TIMEOUT_SECS, MAX_CHAT_LENGTH, MAX_CONNECTIONS, MAX_MESSAGE_SIZE, MAX_TABLE_ID_LENGTH, MAX_USERNAME_LENGTH,
+};
+
+pub const DEFAULT_BIND: &str = "127.0.0.1:9999";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+	pub bind: String,
+	pub log_level: LogLevel,
+	pub paths: ServerPaths,
+	pub limits: Limits,
+	pub timeouts: Timeouts,
+	/// How far each game may run ahead of the server sending and recording
+	/// its events.
+	pub event_queue: EventQueueConfig,
+	pub ai: AiConfig,
+	/// Where the file was read from, for resolving relative paths.
+	#[serde(skip)]
+	dir: Option<PathBuf>,
+}
+
+impl Default for ServerConfig {
+	fn default() -> Self {
+		Self {
+			bind: DEFAULT_BIND.to_string(),
+			log_level: LogLevel::default(),
+			paths: ServerPaths::default(),
+			limits: Limits::default(),
+			timeouts: Timeouts::default(),
+			event_queue: EventQueueConfig::default(),
+			ai: AiConfig::default(),
+			dir: None,
+		}
+	}
+}
+
+/// Files and directories in place of the usual ones in the config and
+/// data directories.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerPaths {
+	/// The table set, in the format of `tables.toml`.
+	pub tables: Option<PathBuf>,
+	/// The bank's `profiles.toml`; the audit log goes beside it.
+	pub bank: Option<PathBuf>,
+	/// Hand histories, when recorded.
+	pub history: Option<PathBuf>,
+	pub logs: Option<PathBuf>,
+	/// Bans, mutes and strikes, as kept by `poker admin`.
+	pub moderation: Option<PathBuf>,
+	/// Every game's full event record, for `poker admin export-hand`.
+	pub recordings: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+	/// Connections at once; more are turned away.
+	pub max_connections: usize,
+	/// Largest message a client may send, in bytes.
+	pub max_message_size: usize,
+	pub max_username_length: usize,
+	pub max_table_id_length: usize,
+	pub max_chat_length: usize,
+}
+
+impl Default for Limits {
+	fn default() -> Self {
+		Self {
+			max_connections: MAX_CONNECTIONS,
+			max_message_size: MAX_MESSAGE_SIZE,
+			max_username_length: MAX_USERNAME_LENGTH,
+			max_table_id_length: MAX_TABLE_ID_LENGTH,
+			max_chat_length: MAX_CHAT_LENGTH,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Timeouts {
+	/// The action clock at tables that don't set `action_timeout_seconds`.
+	pub action_seconds: u32,
+	/// How long a disconnected sit & go player keeps their seat before a
+	/// stand-in plays for them, at tables that don't set
+	/// `takeover_grace_seconds`; unset means the table waits.
+	pub takeover_grace_seconds: Option<u32>,
+	/// How long players can sit at a table without a game starting before
+	/// they're stood up and the table cleared; unset means as long as they
+	/// like.
+	pub idle_table_seconds: Option<u32>,
+}
+
+impl Default for Timeouts {
+	fn default() -> Self {
+		Self { action_seconds: DEFAULT_ACTION_TIMEOUT_SECS, takeover_grace_seconds: None, idle_table_seconds: None }
+	}
+}
+
+/// How the server's AI players make their decisions.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AiConfig {
+	/// Threads shared by every table's AIs; see `DecisionPool`.
+	pub workers: usize,
+}
+
+impl Default for AiConfig {
+	fn default() -> Self {
+		Self { workers: DEFAULT_DECISION_WORKERS }
+	}
+}
+
+impl ServerConfig {
+	pub fn parse(content: &str) -> Result<Self, PokerError> {
+		let config: Self = toml::from_str(content).map_err(|e| PokerError::parse("server config", e))?;
+		config.validate()?;
+		Ok(config)
+	}
+
+	/// `server.toml` from the config directory, or the defaults if there
+	/// isn't one.
+	pub fn load() -> Result<Self, PokerError> {
+		match resolve_config("server.toml") {
+			Ok(path) => Self::load_from(&path),
+			Err(_) => Ok(Self::default()),
+		}
+	}
+
+	pub fn load_from(path: &Path) -> Result<Self, PokerError> {
+		let content = fs::read_to_string(path).map_err(|e| PokerError::io("read", path, e))?;
+		let mut config = Self::parse(&content).map_err(|e| match e {
+			PokerError::Parse { message, .. } => PokerError::parse(path.display().to_string(), message),
+			PokerError::Config(message) => PokerError::Config(format!("{}: {}", path.display(), message)),
+			other => other,
+		})?;
+		config.dir = path.parent().map(Path::to_path_buf);
+		Ok(config)
+	}
+
+	fn validate(&self) -> Result<(), PokerError> {
+		let invalid = |message: &str| Err(PokerError::Config(message.to_string()));
+		let limits = &self.limits;
+		if limits.max_message_size == 0 || limits.max_message_size > MAX_FRAME_LEN {
+			return Err(PokerError::Config(format!("max_message_size must be 1-{} bytes", MAX_FRAME_LEN)));
+		}
+		if limits.max_connections == 0 || limits.max_username_length == 0 || limits.max_chat_length == 0 {
+			return invalid("Connection, username and chat limits must be above 0");
+		}
+		if self.timeouts.action_seconds == 0 {
+			return invalid("action_seconds must be above 0");
+		}
+		if self.timeouts.idle_table_seconds == Some(0) {
+			return invalid("idle_table_seconds must be above 0");
+		}
+		if self.ai.workers == 0 {
+			return invalid("ai.workers must be above 0");
+		}
+		if self.event_queue.capacity == 0 {
+			return invalid("The event queue's capacity must be above 0");
+		}
+		Ok(())
+	}
+
+	fn resolve(&self, path: &Option<PathBuf>) -> Option<PathBuf> {
+		let path = path.as_ref()?;
+		Some(match &self.dir {
+			Some(dir) if path.is_relative() => dir.join(path),
+			_ => path.clone(),
+		})
+	}
+
+	pub fn tables_path(&self) -> Option<PathBuf> {
+		self.resolve(&self.paths.tables)
+	}
+
+	pub fn bank_path(&self) -> Option<PathBuf> {
+		self.resolve(&self.paths.bank)
+	}
+
+	pub fn history_dir(&self) -> Option<PathBuf> {
+		self.resolve(&self.paths.history)
+	}
+
+	pub fn logs_dir(&self) -> Option<PathBuf> {
+		self.resolve(&self.paths.logs)
+	}
+
+	pub fn moderation_path(&self) -> Option<PathBuf> {
+		self.resolve(&self.paths.moderation)
+	}
+
+	pub fn recordings_dir(&self) -> Option<PathBuf> {
+		self.resolve(&self.paths.recordings)
+	}
+
+	pub fn action_timeout(&self) -> Duration {
+		Duration::from_secs(self.timeouts.action_seconds as u64)
+	}
+
+	pub fn idle_table_timeout(&self) -> Option<Duration> {
+		self.timeouts.idle_table_seconds.map(|seconds| Duration::from_secs(seconds as u64))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::engine::Backpressure;
+
+	#[test]
+	fn test_empty_file_is_the_defaults() {
+		let config = ServerConfig::parse("").unwrap();
+		assert_eq!(config.bind, DEFAULT_BIND);
+		assert_eq!(config.limits, Limits::default());
+		assert_eq!(config.action_timeout(), Duration::from_secs(120));
+		assert!(config.tables_path().is_none());
+
+		// The shipped file is all comments
+		let shipped = ServerConfig::parse(crate::defaults::embedded("server.toml").unwrap()).unwrap();
+		assert_eq!(shipped.bind, DEFAULT_BIND);
+		assert_eq!(shipped.log_level, LogLevel::Debug);
+	}
+
+	#[test]
+	fn test_paths_resolve_against_the_file() {
+		let mut config = ServerConfig::parse(
+			r#"
+			bind = "0.0.0.0:7777"
+			log_level = "warn"
+
+			[paths]
+			tables = "home-game.toml"
+			bank = "/srv/poker/profiles.toml"
+
+			[limits]
+			max_connections = 12
+
+			[timeouts]
+			action_seconds = 45
+			takeover_grace_seconds = 30
+			idle_table_seconds = 600
+
+			[ai]
+			workers = 2
+
+			[event_queue]
+			capacity = 256
+			backpressure = "drop_cosmetic"
+			"#,
+		)
+		.unwrap();
+		config.dir = Some(PathBuf::from("/etc/poker"));
+		assert_eq!(config.bind, "0.0.0.0:7777");
+		assert_eq!(config.log_level, LogLevel::Warn);
+		assert_eq!(config.tables_path(), Some(PathBuf::from("/etc/poker/home-game.toml")));
+		assert_eq!(config.bank_path(), Some(PathBuf::from("/srv/poker/profiles.toml")));
+		assert_eq!(config.limits.max_connections, 12);
+		assert_eq!(config.limits.max_chat_length, 500);
+		assert_eq!(config.timeouts.takeover_grace_seconds, Some(30));
+		assert_eq!(config.idle_table_timeout(), Some(Duration::from_secs(600)));
+		assert_eq!(config.ai.workers, 2);
+		assert_eq!(config.event_queue, EventQueueConfig { capacity: 256, backpressure: Backpressure::DropCosmetic });
+	}
+
+	#[test]
+	fn test_bad_values_are_refused() {
+		assert!(ServerConfig::parse("[limits]\nmax_message_size = 0").is_err());
+		assert!(ServerConfig::parse("[limits]\nmax_message_size = 999999999").is_err());
+		assert!(ServerConfig::parse("[timeouts]\naction_seconds = 0").is_err());
+		assert!(ServerConfig::parse("[timeouts]\nidle_table_seconds = 0").is_err());
+		assert!(ServerConfig::parse("[event_queue]\ncapacity = 0").is_err());
+		assert!(ServerConfig::parse("[ai]\nworkers = 0").is_err());
+		assert!(ServerConfig::parse("log_level = \"loud\"").is_err());
+		assert!(ServerConfig::parse("bnid = \"typo\"").is_err());
+	}
+}
@@ -0,0 +1,707 @@
+//! `poker soak`: a server left to a crowd of scripted clients for hours.
+//! Bots join tables, ready up, play whatever they're offered, leave, drop
+//! their connections and come back for their seats, over and over. A
+//! watcher in the lobby checks that every table in play keeps moving and
+//! that finished games are cleared away, and the server process's memory
+//! and threads are sampled as it goes. Leaks in the lobby's and the
+//! games' lifecycles only show over time: a table that never frees up, or
+//! a thread count that only climbs.
+//!
+//! The server runs in a process of its own (`poker soak` starts `poker
+//! serve`) so its numbers aren't mixed up with the bots'; `prepare` writes
+//! the config it runs on into a scratch directory.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::bank::Bank;
+use crate::error::PokerError;
+use crate::events::{GameEvent, GameId, PlayerAction, RaiseOptions, Seat, ValidActions};
+use crate::net::client::GameClient;
+use crate::net::protocol::{ServerMessage, TableStatus};
+
+/// Every bot starts with this much, enough for hours of buy-ins.
+const BOT_BANKROLL: f32 = 10_000_000.0;
+/// A bot gives up on a table that hasn't started by then.
+const WAITING_LIMIT: Duration = Duration::from_secs(30);
+/// How often the watcher looks over the lobby.
+const LOBBY_POLL: Duration = Duration::from_secs(2);
+/// Threads the server may keep once the bots are gone, over what it
+/// started with, before that counts as a leak.
+const THREAD_SLACK: u64 = 2;
+
+#[derive(Debug, Clone)]
+pub struct SoakConfig {
+	pub bots: usize,
+	/// Cash tables; there's one sit & go besides.
+	pub tables: usize,
+	pub duration: Duration,
+	pub sample_every: Duration,
+	/// A table in play with no events for this long is stuck.
+	pub stuck_after: Duration,
+	pub seed: u64,
+}
+
+impl Default for SoakConfig {
+	fn default() -> Self {
+		Self {
+			bots: 24,
+			tables: 4,
+			duration: Duration::from_secs(60 * 60),
+			sample_every: Duration::from_secs(60),
+			stuck_after: Duration::from_secs(120),
+			seed: 0,
+		}
+	}
+}
+
+/// Writes a `server.toml` for `addr` into `dir`, with its tables, a bank
+/// holding the bots' bankrolls, and logs and recordings beside them.
+/// Returns the config's path.
+pub fn prepare(dir: &Path, config: &SoakConfig, addr: &str) -> Result<PathBuf, PokerError> {
+	fs::create_dir_all(dir).map_err(|e| PokerError::io("create", dir, e))?;
+	let write = |name: &str, content: String| {
+		let path = dir.join(name);
+		fs::write(&path, content).map_err(|e| PokerError::io("write", &path, e))?;
+		Ok::<_, PokerError>(path)
+	};
+
+	let mut tables = String::new();
+	for i in 0..config.tables {
+		tables.push_str(&format!(
+			"[[tables]]\nid = \"soak-{i}\"\nname = \"Soak {i}\"\nformat = \"cash\"\nbetting = \"{}\"\n\
+			small_blind = 1.0\nbig_blind = 2.0\nmin_buy_in = 40.0\nmax_buy_in = 200.0\n\
+			min_players = 2\nmax_players = 6\n{}\n",
+			["no-limit", "pot-limit", "fixed-limit"][i % 3],
+			FAST
+		));
+	}
+	tables.push_str(&format!(
+		"[[tables]]\nid = \"soak-sng\"\nname = \"Soak Sit & Go\"\nformat = \"sit-n-go\"\nbetting = \"no-limit\"\n\
+		small_blind = 10.0\nbig_blind = 20.0\nbuy_in = 50.0\nstarting_stack = 500.0\n\
+		min_players = 3\nmax_players = 6\ntakeover_grace_seconds = 3\n{}\n",
+		FAST
+	));
+	write("tables.toml", tables)?;
+
+	let mut bank = Bank::load_from(dir.join("profiles.toml"))?;
+	for i in 0..config.bots {
+		bank.register(&bot_name(i), BOT_BANKROLL);
+	}
+	bank.save()?;
+
+	write(
+		"server.toml",
+		format!(
+			"bind = \"{}\"\nlog_level = \"warn\"\n\n[paths]\ntables = \"tables.toml\"\nbank = \"profiles.toml\"\n\
+			logs = \"logs\"\nmoderation = \"moderation.toml\"\nrecordings = \"recordings\"\n",
+			addr
+		),
+	)
+}
+
+/// No pauses and a short clock, so hands go by as fast as the bots play.
+const FAST: &str = "action_timeout_seconds = 5\naction_delay_ms = 0\nstreet_delay_ms = 0\nhand_end_delay_ms = 0";
+
+pub(crate) fn bot_name(i: usize) -> String {
+	format!("soak-bot-{:02}", i)
+}
+
+/// The server process's size and threads at one moment, and what the
+/// bots had seen by then.
+#[derive(Debug, Clone, Default)]
+pub struct Sample {
+	pub elapsed: Duration,
+	pub rss_kb: Option<u64>,
+	pub threads: Option<u64>,
+	pub hands: u64,
+	pub games: u64,
+	pub connected: usize,
+	pub reconnects: u64,
+}
+
+impl Sample {
+	pub fn line(&self) -> String {
+		let or_dash = |n: Option<u64>| n.map_or("-".to_string(), |n| n.to_string());
+		format!(
+			"{:>6}s  rss {:>8} kB  threads {:>4}  hands {:>7}  games {:>5}  bots on {:>3}  reconnects {:>5}",
+			self.elapsed.as_secs(),
+			or_dash(self.rss_kb),
+			or_dash(self.threads),
+			self.hands,
+			self.games,
+			self.connected,
+			self.reconnects
+		)
+	}
+}
+
+/// `VmRSS` and `Threads` from a `/proc/<pid>/status`.
+fn parse_status(status: &str) -> (Option<u64>, Option<u64>) {
+	let field = |name: &str| {
+		status
+			.lines()
+			.find_map(|line| line.strip_prefix(name))
+			.and_then(|rest| rest.split_whitespace().next())
+			.and_then(|n| n.parse().ok())
+	};
+	(field("VmRSS:"), field("Threads:"))
+}
+
+/// The process's resident memory in kB and its threads, where the OS has
+/// a `/proc` to ask.
+fn process_stats(pid: u32) -> (Option<u64>, Option<u64>) {
+	match fs::read_to_string(format!("/proc/{}/status", pid)) {
+		Ok(status) => parse_status(&status),
+		Err(_) => (None, None),
+	}
+}
+
+/// Whether `values`, past the first quarter as warm-up, only went up: the
+/// lowest of the second half more than a tenth above the highest of the
+/// first, so the allocator settling by a few pages doesn't count.
+fn climbing(values: &[u64]) -> Option<(u64, u64)> {
+	let settled = &values[values.len() / 4..];
+	if settled.len() < 6 {
+		return None;
+	}
+	let (first, second) = settled.split_at(settled.len() / 2);
+	let (high, low) = (*first.iter().max()?, *second.iter().min()?);
+	(low > high + high / 10).then_some((high, low))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SoakReport {
+	/// Before any bot connected.
+	pub baseline: Sample,
+	pub samples: Vec<Sample>,
+	/// After the bots left and the tables emptied.
+	pub after: Option<Sample>,
+	/// Tables that stopped moving, and when.
+	pub stuck: Vec<String>,
+}
+
+impl SoakReport {
+	/// What looks wrong: stuck tables, and memory or threads that only
+	/// grew, or threads that didn't go once the bots had.
+	pub fn problems(&self) -> Vec<String> {
+		let mut problems = self.stuck.clone();
+		let series = |f: fn(&Sample) -> Option<u64>| self.samples.iter().filter_map(f).collect::<Vec<_>>();
+		if let Some((from, to)) = climbing(&series(|s| s.rss_kb)) {
+			problems.push(format!("Memory kept growing: {} kB to {} kB", from, to));
+		}
+		if let Some((from, to)) = climbing(&series(|s| s.threads)) {
+			problems.push(format!("Threads kept growing: {} to {}", from, to));
+		}
+		let left = self.after.as_ref().and_then(|s| s.threads).zip(self.baseline.threads);
+		if let Some((after, before)) = left.filter(|(after, before)| *after > before + THREAD_SLACK) {
+			problems.push(format!("{} threads left behind: {} at the start, {} after the bots left", after - before, before, after));
+		}
+		problems
+	}
+
+	pub fn lines(&self) -> Vec<String> {
+		let mut lines = vec![format!("start   {}", self.baseline.line())];
+		if let Some(last) = self.samples.last() {
+			lines.push(format!("end     {}", last.line()));
+		}
+		if let Some(after) = &self.after {
+			lines.push(format!("after   {}", after.line()));
+		}
+		lines.push(String::new());
+		let problems = self.problems();
+		if problems.is_empty() {
+			lines.push("No stuck tables or leaks".to_string());
+		}
+		lines.extend(problems);
+		lines
+	}
+}
+
+/// When each table last showed signs of life, and the last hand seen there.
+#[derive(Default)]
+struct Progress {
+	tables: HashMap<String, TableProgress>,
+	hands: u64,
+	games: u64,
+}
+
+struct TableProgress {
+	last_seen: Instant,
+	hand: Option<(GameId, u32)>,
+	/// Already reported stuck, until it moves again.
+	stuck: bool,
+}
+
+impl Progress {
+	fn touch(&mut self, table: &str) -> &mut TableProgress {
+		let progress = self.tables.entry(table.to_string()).or_insert(TableProgress {
+			last_seen: Instant::now(),
+			hand: None,
+			stuck: false,
+		});
+		progress.last_seen = Instant::now();
+		progress.stuck = false;
+		progress
+	}
+
+	/// Counts each hand once, however many bots saw it start.
+	fn hand_started(&mut self, table: &str, game: GameId, hand_num: u32) {
+		let progress = self.touch(table);
+		let (new_game, new_hand) = match progress.hand {
+			Some((seen_game, seen)) if seen_game == game => (false, hand_num > seen),
+			_ => (true, true),
+		};
+		if new_hand {
+			progress.hand = Some((game, hand_num));
+			self.hands += 1;
+		}
+		if new_game {
+			self.games += 1;
+		}
+	}
+}
+
+/// What the bots share with the watcher.
+#[derive(Default)]
+struct Shared {
+	progress: Mutex<Progress>,
+	connected: AtomicUsize,
+	reconnects: AtomicU64,
+	stop: AtomicBool,
+}
+
+impl Shared {
+	fn progress(&self) -> MutexGuard<'_, Progress> {
+		self.progress.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn sample(&self, started: Instant, pid: Option<u32>) -> Sample {
+		let (rss_kb, threads) = pid.map(process_stats).unwrap_or_default();
+		let progress = self.progress();
+		Sample {
+			elapsed: started.elapsed(),
+			rss_kb,
+			threads,
+			hands: progress.hands,
+			games: progress.games,
+			connected: self.connected.load(Ordering::SeqCst),
+			reconnects: self.reconnects.load(Ordering::SeqCst),
+		}
+	}
+}
+
+/// Runs the bots against the server at `addr` for the configured time,
+/// then has them leave and waits for the tables to empty. `server_pid` is
+/// the process to sample; `on_sample` hears each sample as it's taken.
+pub fn run(config: &SoakConfig, addr: &str, server_pid: Option<u32>, mut on_sample: impl FnMut(&Sample)) -> SoakReport {
+	let shared = Arc::new(Shared::default());
+	let started = Instant::now();
+	let mut report = SoakReport { baseline: shared.sample(started, server_pid), ..SoakReport::default() };
+
+	let bots = (0..config.bots)
+		.map(|i| {
+			let mut bot = Bot::new(i, addr, config.seed.wrapping_add(i as u64), Arc::clone(&shared));
+			thread::spawn(move || bot.run())
+		})
+		.collect::<Vec<_>>();
+
+	let mut lobby = Lobby::connect(addr);
+	let deadline = started + config.duration;
+	let mut next_sample = started + config.sample_every;
+	while Instant::now() < deadline {
+		thread::sleep(LOBBY_POLL.min(deadline.saturating_duration_since(Instant::now())));
+		report.stuck.extend(lobby.check(&shared, config.stuck_after, started));
+		if Instant::now() >= next_sample {
+			let sample = shared.sample(started, server_pid);
+			on_sample(&sample);
+			report.samples.push(sample);
+			next_sample += config.sample_every;
+		}
+	}
+
+	shared.stop.store(true, Ordering::SeqCst);
+	for bot in bots {
+		let _ = bot.join();
+	}
+	// With everyone gone, every game should end and its table free up
+	let drained = Instant::now() + config.stuck_after;
+	loop {
+		thread::sleep(LOBBY_POLL);
+		let in_play = lobby.in_play();
+		if in_play.is_empty() {
+			break;
+		}
+		if Instant::now() >= drained {
+			for table in in_play {
+				report.stuck.push(format!("{}: still in play {}s after the bots left", table, config.stuck_after.as_secs()));
+			}
+			break;
+		}
+	}
+	report.after = Some(shared.sample(started, server_pid));
+	report
+}
+
+/// A client sitting in the lobby, asking after the tables.
+struct Lobby {
+	addr: String,
+	client: Option<GameClient>,
+	/// The status of each table at the last look.
+	status: HashMap<String, TableStatus>,
+}
+
+impl Lobby {
+	fn connect(addr: &str) -> Self {
+		let mut lobby = Self { addr: addr.to_string(), client: None, status: HashMap::new() };
+		lobby.refresh();
+		lobby
+	}
+
+	/// Asks for the tables, which also has the server clear away finished
+	/// games, and waits for the answer.
+	fn refresh(&mut self) {
+		if self.client.is_none() {
+			self.client = GameClient::connect(&self.addr).ok().and_then(|mut client| {
+				client.login("soak-watcher").ok()?;
+				Some(client)
+			});
+		}
+		let Some(client) = &mut self.client else {
+			return;
+		};
+		if client.list_tables().is_err() {
+			self.client = None;
+			return;
+		}
+		let until = Instant::now() + Duration::from_secs(5);
+		while let Some(msg) = client.recv_timeout(until.saturating_duration_since(Instant::now())) {
+			if let ServerMessage::LobbyState { tables } = msg {
+				self.status = tables.into_iter().map(|t| (t.id, t.status)).collect();
+				client.drain();
+				return;
+			}
+		}
+	}
+
+	fn in_play(&mut self) -> Vec<String> {
+		self.refresh();
+		let mut tables =
+			self.status.iter().filter(|(_, s)| **s == TableStatus::InProgress).map(|(id, _)| id.clone()).collect::<Vec<_>>();
+		tables.sort();
+		tables
+	}
+
+	/// Tables in play that no bot has heard from in `stuck_after`, each
+	/// reported once until it moves again.
+	fn check(&mut self, shared: &Shared, stuck_after: Duration, started: Instant) -> Vec<String> {
+		self.refresh();
+		let mut progress = shared.progress();
+		let mut stuck = Vec::new();
+		for (id, status) in &self.status {
+			if *status != TableStatus::InProgress {
+				progress.touch(id);
+				continue;
+			}
+			let table = progress.tables.entry(id.clone()).or_insert(TableProgress {
+				last_seen: Instant::now(),
+				hand: None,
+				stuck: false,
+			});
+			let quiet = table.last_seen.elapsed();
+			if quiet >= stuck_after && !table.stuck {
+				table.stuck = true;
+				stuck.push(format!("{}: no events for {}s, {}s in", id, quiet.as_secs(), started.elapsed().as_secs()));
+			}
+		}
+		stuck
+	}
+}
+
+/// Where a bot is.
+#[derive(Debug, Clone, PartialEq)]
+enum Place {
+	Lobby,
+	/// Sat down, waiting for the game to start.
+	Waiting { table: String, since: Instant },
+	Playing { table: String, seat: Seat },
+}
+
+struct Bot {
+	name: String,
+	addr: String,
+	rng: StdRng,
+	shared: Arc<Shared>,
+	client: Option<GameClient>,
+	place: Place,
+	/// The table to go back to after dropping the connection.
+	rejoin: Option<String>,
+	seat: Option<Seat>,
+}
+
+impl Bot {
+	fn new(i: usize, addr: &str, seed: u64, shared: Arc<Shared>) -> Self {
+		Self {
+			name: bot_name(i),
+			addr: addr.to_string(),
+			rng: StdRng::seed_from_u64(seed),
+			shared,
+			client: None,
+			place: Place::Lobby,
+			rejoin: None,
+			seat: None,
+		}
+	}
+
+	fn run(&mut self) {
+		// Staggered, so they don't all arrive at once
+		thread::sleep(Duration::from_millis(self.rng.random_range(0..2000)));
+		while !self.shared.stop.load(Ordering::SeqCst) {
+			if self.client.is_none() && !self.connect() {
+				thread::sleep(Duration::from_secs(1));
+				continue;
+			}
+			let msg = self.client.as_ref().and_then(|c| c.recv_timeout(Duration::from_millis(250)));
+			match msg {
+				Some(msg) => self.handle(msg),
+				None => self.idle(),
+			}
+		}
+		if self.client.take().is_some() {
+			self.shared.connected.fetch_sub(1, Ordering::SeqCst);
+		}
+	}
+
+	fn connect(&mut self) -> bool {
+		let Ok(mut client) = GameClient::connect(&self.addr) else {
+			return false;
+		};
+		if client.login(&self.name).is_err() {
+			return false;
+		}
+		// Straight back to the seat the dropped connection left behind
+		let sent = match self.rejoin.take() {
+			Some(table) => {
+				self.shared.reconnects.fetch_add(1, Ordering::SeqCst);
+				client.join_table(&table)
+			}
+			None => client.list_tables(),
+		};
+		if sent.is_err() {
+			return false;
+		}
+		self.client = Some(client);
+		self.shared.connected.fetch_add(1, Ordering::SeqCst);
+		self.place = Place::Lobby;
+		true
+	}
+
+	fn disconnect(&mut self) {
+		if self.client.take().is_some() {
+			self.shared.connected.fetch_sub(1, Ordering::SeqCst);
+		}
+		self.place = Place::Lobby;
+	}
+
+	fn send(&mut self, send: impl FnOnce(&mut GameClient) -> std::io::Result<()>) {
+		let sent = self.client.as_mut().map(send);
+		if matches!(sent, Some(Err(_))) {
+			self.disconnect();
+		}
+	}
+
+	fn handle(&mut self, msg: ServerMessage) {
+		match msg {
+			ServerMessage::LobbyState { tables } if self.place == Place::Lobby => {
+				let open = tables
+					.iter()
+					.filter(|t| t.status == TableStatus::Waiting && t.players < t.max_players)
+					.collect::<Vec<_>>();
+				if !open.is_empty() {
+					let table = open[self.rng.random_range(0..open.len())].id.clone();
+					self.send(|c| c.join_table(&table));
+				}
+			}
+			ServerMessage::TableJoined { table_id, seat, .. } => {
+				self.seat = Some(seat);
+				self.place = Place::Waiting { table: table_id, since: Instant::now() };
+				self.send(|c| c.ready());
+			}
+			ServerMessage::GameStarting { .. } => {
+				if let (Place::Waiting { table, .. }, Some(seat)) = (&self.place, self.seat) {
+					self.place = Place::Playing { table: table.clone(), seat };
+				}
+			}
+			ServerMessage::TableChanged { table_id, seat, .. } => {
+				self.place = Place::Playing { table: table_id, seat };
+			}
+			ServerMessage::TableLeft => {
+				self.place = Place::Lobby;
+				self.send(|c| c.list_tables());
+			}
+			ServerMessage::Error { .. } => {
+				// A table that filled or started under us; try another
+				if let Place::Lobby = self.place {
+					thread::sleep(Duration::from_millis(200));
+					self.send(|c| c.list_tables());
+				}
+			}
+			ServerMessage::ActionRequest { valid_actions, .. } => {
+				// Now and then, let the clock run out
+				if self.rng.random_bool(0.02) {
+					return;
+				}
+				let action = self.choose(&valid_actions);
+				self.send(|c| c.action(action));
+			}
+			ServerMessage::GameEvent(event) => self.event(event),
+			_ => {}
+		}
+	}
+
+	fn event(&mut self, event: GameEvent) {
+		let Place::Playing { table, seat } = &self.place else {
+			return;
+		};
+		let (table, seat) = (table.clone(), *seat);
+		match &event {
+			GameEvent::HandStarted { game_id, hand_num, .. } => {
+				let game = game_id.unwrap_or(GameId(0));
+				self.shared.progress().hand_started(&table, game, *hand_num);
+			}
+			_ => {
+				self.shared.progress().touch(&table);
+			}
+		}
+		match event {
+			GameEvent::GameEnded { .. } => self.send(|c| c.leave_table()),
+			GameEvent::PlayerLeft { seat: left, .. } if left == seat => self.send(|c| c.leave_table()),
+			GameEvent::HandEnded { .. } => {
+				let roll = self.rng.random_range(0..100);
+				if roll < 3 {
+					self.send(|c| c.leave_table());
+				} else if roll < 5 {
+					// Drop off, and come back a moment later
+					self.rejoin = Some(table);
+					self.disconnect();
+					thread::sleep(Duration::from_millis(self.rng.random_range(500..6000)));
+				} else if roll < 6 {
+					self.send(|c| c.change_table());
+				}
+			}
+			_ => {}
+		}
+	}
+
+	/// Nothing came in for a while.
+	fn idle(&mut self) {
+		match &self.place {
+			Place::Lobby => self.send(|c| c.list_tables()),
+			Place::Waiting { since, .. } if since.elapsed() > WAITING_LIMIT => self.send(|c| c.leave_table()),
+			_ => {}
+		}
+	}
+
+	fn choose(&mut self, valid: &ValidActions) -> PlayerAction {
+		random_action(&mut self.rng, valid)
+	}
+}
+
+/// Something legal, mostly calls and checks with a raise now and then.
+pub(crate) fn random_action(rng: &mut StdRng, valid: &ValidActions) -> PlayerAction {
+	if valid.can_show {
+		return if rng.random_bool(0.5) { PlayerAction::Show } else { PlayerAction::Muck };
+	}
+	let roll = rng.random_range(0..100);
+	if roll < 15 && valid.can_fold && !valid.can_check {
+		return PlayerAction::Fold;
+	}
+	if roll < 30 {
+		let raise_to = match valid.raise_options {
+			Some(RaiseOptions::Fixed { amount }) => Some(amount),
+			Some(RaiseOptions::Variable { min_raise, max_raise }) => {
+				Some(if max_raise > min_raise { rng.random_range(min_raise..=max_raise).floor() } else { min_raise })
+			}
+			None => None,
+		};
+		if let Some(amount) = raise_to {
+			return if valid.can_check { PlayerAction::Bet { amount } } else { PlayerAction::Raise { amount } };
+		}
+	}
+	if roll < 32 && valid.can_all_in {
+		return PlayerAction::AllIn { amount: valid.all_in_amount };
+	}
+	match valid.call_amount {
+		_ if valid.can_check => PlayerAction::Check,
+		Some(amount) => PlayerAction::Call { amount },
+		None if valid.can_all_in => PlayerAction::AllIn { amount: valid.all_in_amount },
+		None => PlayerAction::Fold,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_status_gives_memory_and_threads() {
+		let status = "Name:\tpoker\nVmPeak:\t  20000 kB\nVmRSS:\t   12345 kB\nThreads:\t17\n";
+		assert_eq!(parse_status(status), (Some(12345), Some(17)));
+		assert_eq!(parse_status("Name:\tpoker\n"), (None, None));
+	}
+
+	#[test]
+	fn test_only_steady_growth_counts_as_climbing() {
+		// Warm-up, then up and down around a level
+		assert_eq!(climbing(&[1, 5, 9, 10, 12, 9, 11, 10, 12, 9, 11]), None);
+		// Up every sample once warmed up
+		assert_eq!(climbing(&[1, 5, 10, 12, 14, 16, 18, 20, 22, 24, 26]), Some((16, 18)));
+		// Creeping up by a sliver
+		assert_eq!(climbing(&[900, 950, 1000, 1001, 1002, 1003, 1004, 1005, 1006]), None);
+		assert_eq!(climbing(&[1, 2, 3]), None);
+	}
+
+	#[test]
+	fn test_each_hand_counts_once() {
+		let mut progress = Progress::default();
+		for _ in 0..3 {
+			progress.hand_started("a", GameId(1), 1);
+		}
+		progress.hand_started("a", GameId(1), 2);
+		// A slow bot still seeing the hand before
+		progress.hand_started("a", GameId(1), 1);
+		progress.hand_started("a", GameId(2), 1);
+		progress.hand_started("b", GameId(3), 1);
+		assert_eq!((progress.hands, progress.games), (4, 3));
+	}
+
+	#[test]
+	fn test_bots_only_choose_what_is_offered() {
+		let mut bot = Bot::new(0, "127.0.0.1:0", 7, Arc::default());
+		let facing_bet = ValidActions {
+			can_fold: true,
+			can_check: false,
+			call_amount: Some(4.0),
+			raise_options: Some(RaiseOptions::Variable { min_raise: 8.0, max_raise: 100.0 }),
+			can_all_in: true,
+			all_in_amount: 100.0,
+			can_show: false,
+		};
+		for _ in 0..200 {
+			match bot.choose(&facing_bet) {
+				PlayerAction::Fold | PlayerAction::Call { amount: 4.0 } | PlayerAction::AllIn { amount: 100.0 } => {}
+				PlayerAction::Raise { amount } => assert!((8.0..=100.0).contains(&amount)),
+				other => panic!("{:?} wasn't offered", other),
+			}
+			assert!(matches!(bot.choose(&ValidActions::show_or_muck()), PlayerAction::Show | PlayerAction::Muck));
+		}
+	}
+}
@@ -0,0 +1,401 @@
+//! `poker stress`: a crowd of scripted clients playing as fast as they're
+//! told to, timing how long the server takes to answer. Where `poker
+//! soak` runs for hours looking for leaks, this runs for minutes and asks
+//! how many players a server can take: each client logs in, joins a
+//! table, readies up and plays something legal after its think time,
+//! back to the lobby for another game whenever one ends.
+//!
+//! Latency is timed per kind of request, from sending it to the answer
+//! that shows it was done: `Welcome` for a login, `LobbyState` for the
+//! table list, `TableJoined` for a seat, and for an action, the table's
+//! `ActionTaken` for the client's seat.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::events::{GameEvent, PlayerAction, Seat};
+use crate::net::client::{ConnectionState, GameClient};
+use crate::net::protocol::{ServerMessage, TableStatus};
+use crate::net::soak::{bot_name, random_action};
+
+/// How long a client waits on a table list that's all full or in play.
+const LOBBY_RETRY: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+pub struct StressConfig {
+	pub clients: usize,
+	pub duration: Duration,
+	/// Each client waits half to one and a half times this before acting.
+	pub think: Duration,
+	pub seed: u64,
+}
+
+impl Default for StressConfig {
+	fn default() -> Self {
+		Self {
+			clients: 24,
+			duration: Duration::from_secs(60),
+			think: Duration::from_millis(100),
+			seed: 0,
+		}
+	}
+}
+
+/// What the server was asked to do, timed separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Request {
+	Login,
+	Lobby,
+	Join,
+	Action,
+}
+
+impl Request {
+	const ALL: [Request; 4] = [Request::Login, Request::Lobby, Request::Join, Request::Action];
+
+	fn name(&self) -> &'static str {
+		match self {
+			Request::Login => "login",
+			Request::Lobby => "lobby",
+			Request::Join => "join",
+			Request::Action => "action",
+		}
+	}
+}
+
+/// The `p`th percentile of `sorted`, by nearest rank.
+pub fn percentile(sorted: &[Duration], p: f64) -> Option<Duration> {
+	if sorted.is_empty() {
+		return None;
+	}
+	let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+	Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StressReport {
+	pub clients: usize,
+	pub elapsed: Duration,
+	/// Every answer timed, by request.
+	pub latencies: HashMap<Request, Vec<Duration>>,
+	/// Messages the clients sent.
+	pub sent: u64,
+	/// `Error` answers, like a table that filled first.
+	pub errors: u64,
+	/// Messages either side threw away unread.
+	pub protocol_errors: u64,
+	/// Sends that failed, and connections that dropped.
+	pub dropped: u64,
+	/// Requests never answered before the run ended.
+	pub unanswered: u64,
+	/// The clients' own actions the server timed out.
+	pub timeouts: u64,
+	pub hands: u64,
+}
+
+impl StressReport {
+	fn merge(&mut self, other: StressReport) {
+		for (request, mut times) in other.latencies {
+			self.latencies.entry(request).or_default().append(&mut times);
+		}
+		self.sent += other.sent;
+		self.errors += other.errors;
+		self.protocol_errors += other.protocol_errors;
+		self.dropped += other.dropped;
+		self.unanswered += other.unanswered;
+		self.timeouts += other.timeouts;
+		self.hands += other.hands;
+	}
+
+	/// Everything that went wrong, besides plain `Error`s: those come with
+	/// the lobby's races and say nothing about the server's health.
+	pub fn failures(&self) -> u64 {
+		self.protocol_errors + self.dropped + self.unanswered + self.timeouts
+	}
+
+	/// `failures` per hundred messages sent.
+	pub fn failure_rate(&self) -> f64 {
+		if self.sent == 0 { 0.0 } else { self.failures() as f64 * 100.0 / self.sent as f64 }
+	}
+
+	pub fn lines(&self) -> Vec<String> {
+		let secs = self.elapsed.as_secs_f64().max(0.001);
+		let mut lines = vec![
+			format!(
+				"{} clients for {:.0}s: {} messages sent ({:.0}/s), {} hands dealt to them",
+				self.clients,
+				secs,
+				self.sent,
+				self.sent as f64 / secs,
+				self.hands
+			),
+			String::new(),
+			format!("{:<8} {:>8} {:>8} {:>8} {:>8} {:>8}", "Request", "Count", "p50", "p90", "p99", "Max"),
+			"-".repeat(53),
+		];
+		let ms = |d: Option<Duration>| d.map_or("-".to_string(), |d| format!("{:.1}ms", d.as_secs_f64() * 1000.0));
+		for request in Request::ALL {
+			let mut times = self.latencies.get(&request).cloned().unwrap_or_default();
+			times.sort();
+			lines.push(format!(
+				"{:<8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+				request.name(),
+				times.len(),
+				ms(percentile(&times, 50.0)),
+				ms(percentile(&times, 90.0)),
+				ms(percentile(&times, 99.0)),
+				ms(times.last().copied())
+			));
+		}
+		lines.push(String::new());
+		lines.push(format!(
+			"Failures: {} of {} messages ({:.2}%): {} protocol errors, {} dropped, {} unanswered, {} timed out",
+			self.failures(),
+			self.sent,
+			self.failure_rate(),
+			self.protocol_errors,
+			self.dropped,
+			self.unanswered,
+			self.timeouts
+		));
+		lines.push(format!("Errors from the server: {}, like a table filling first", self.errors));
+		lines
+	}
+}
+
+/// Runs the clients against the server at `addr` for the configured
+/// time. `on_progress` hears the running totals about once a second.
+pub fn run(config: &StressConfig, addr: &str, mut on_progress: impl FnMut(&StressReport)) -> StressReport {
+	let stop = Arc::new(AtomicBool::new(false));
+	let totals = Arc::new(Mutex::new(StressReport { clients: config.clients, ..StressReport::default() }));
+	let started = Instant::now();
+
+	let clients = (0..config.clients)
+		.map(|i| {
+			let mut client = Client::new(i, addr, config, Arc::clone(&stop));
+			let totals = Arc::clone(&totals);
+			thread::spawn(move || {
+				client.run(&totals);
+			})
+		})
+		.collect::<Vec<_>>();
+
+	let deadline = started + config.duration;
+	while Instant::now() < deadline {
+		thread::sleep(Duration::from_secs(1).min(deadline.saturating_duration_since(Instant::now())));
+		let mut totals = totals.lock().unwrap_or_else(|e| e.into_inner());
+		totals.elapsed = started.elapsed();
+		on_progress(&totals);
+	}
+	stop.store(true, Ordering::SeqCst);
+	for client in clients {
+		let _ = client.join();
+	}
+
+	let mut report = std::mem::take(&mut *totals.lock().unwrap_or_else(|e| e.into_inner()));
+	report.elapsed = started.elapsed();
+	report
+}
+
+struct Client {
+	name: String,
+	addr: String,
+	think: Duration,
+	rng: StdRng,
+	stop: Arc<AtomicBool>,
+	client: Option<GameClient>,
+	seat: Option<Seat>,
+	/// Requests sent and not yet answered, and when they went.
+	pending: HashMap<Request, Instant>,
+	/// Counted since the last hand-off to the totals.
+	report: StressReport,
+}
+
+impl Client {
+	fn new(i: usize, addr: &str, config: &StressConfig, stop: Arc<AtomicBool>) -> Self {
+		Self {
+			name: bot_name(i),
+			addr: addr.to_string(),
+			think: config.think,
+			rng: StdRng::seed_from_u64(config.seed.wrapping_add(i as u64)),
+			stop,
+			client: None,
+			seat: None,
+			pending: HashMap::new(),
+			report: StressReport::default(),
+		}
+	}
+
+	fn run(&mut self, totals: &Mutex<StressReport>) {
+		let mut handed_off = Instant::now();
+		while !self.stop.load(Ordering::SeqCst) {
+			if self.client.is_none() && !self.connect() {
+				thread::sleep(Duration::from_secs(1));
+				continue;
+			}
+			let msg = self.client.as_ref().and_then(|c| c.recv_timeout(Duration::from_millis(100)));
+			if let Some(msg) = msg {
+				self.handle(msg);
+			}
+			if self.client.as_ref().is_some_and(|c| c.connection_state() != ConnectionState::Connected) {
+				self.report.dropped += 1;
+				self.client = None;
+				self.pending.clear();
+			}
+			if handed_off.elapsed() >= Duration::from_millis(500) {
+				totals.lock().unwrap_or_else(|e| e.into_inner()).merge(std::mem::take(&mut self.report));
+				handed_off = Instant::now();
+			}
+		}
+		self.report.unanswered += self.pending.len() as u64;
+		totals.lock().unwrap_or_else(|e| e.into_inner()).merge(std::mem::take(&mut self.report));
+	}
+
+	fn connect(&mut self) -> bool {
+		let Ok(client) = GameClient::connect(&self.addr) else {
+			self.report.dropped += 1;
+			return false;
+		};
+		self.client = Some(client);
+		let name = self.name.clone();
+		self.send(Request::Login, |c| c.login(&name));
+		self.client.is_some()
+	}
+
+	/// Sends a request, timing it from now when it has an answer to wait
+	/// for.
+	fn send(&mut self, request: impl Into<Option<Request>>, send: impl FnOnce(&mut GameClient) -> std::io::Result<()>) {
+		let Some(client) = &mut self.client else {
+			return;
+		};
+		self.report.sent += 1;
+		if send(client).is_err() {
+			self.report.dropped += 1;
+			self.client = None;
+			self.pending.clear();
+			return;
+		}
+		if let Some(request) = request.into() {
+			self.pending.insert(request, Instant::now());
+		}
+	}
+
+	fn answered(&mut self, request: Request) {
+		if let Some(sent) = self.pending.remove(&request) {
+			self.report.latencies.entry(request).or_default().push(sent.elapsed());
+		}
+	}
+
+	fn handle(&mut self, msg: ServerMessage) {
+		match msg {
+			ServerMessage::Welcome { .. } => {
+				self.answered(Request::Login);
+				self.send(Request::Lobby, |c| c.list_tables());
+			}
+			ServerMessage::LobbyState { tables } => {
+				self.answered(Request::Lobby);
+				if self.seat.is_some() || self.pending.contains_key(&Request::Join) {
+					return;
+				}
+				let open = tables
+					.iter()
+					.filter(|t| t.status == TableStatus::Waiting && t.players < t.max_players)
+					.collect::<Vec<_>>();
+				if open.is_empty() {
+					thread::sleep(LOBBY_RETRY);
+					self.send(Request::Lobby, |c| c.list_tables());
+					return;
+				}
+				let table = open[self.rng.random_range(0..open.len())].id.clone();
+				self.send(Request::Join, |c| c.join_table(&table));
+			}
+			ServerMessage::TableJoined { seat, .. } => {
+				self.answered(Request::Join);
+				self.seat = Some(seat);
+				self.send(None, |c| c.ready());
+			}
+			ServerMessage::TableChanged { seat, .. } => self.seat = Some(seat),
+			ServerMessage::TableLeft => {
+				self.seat = None;
+				self.send(Request::Lobby, |c| c.list_tables());
+			}
+			ServerMessage::Error { .. } => {
+				self.report.errors += 1;
+				// A table that filled or started under us; try another
+				if self.pending.remove(&Request::Join).is_some() {
+					self.send(Request::Lobby, |c| c.list_tables());
+				}
+			}
+			ServerMessage::ProtocolError { .. } => self.report.protocol_errors += 1,
+			ServerMessage::ActionRequest { valid_actions, .. } => {
+				thread::sleep(self.think.mul_f64(self.rng.random_range(0.5..=1.5)));
+				let action = random_action(&mut self.rng, &valid_actions);
+				self.send(Request::Action, |c| c.action(action));
+			}
+			ServerMessage::GameEvent(event) => self.event(event),
+			_ => {}
+		}
+	}
+
+	fn event(&mut self, event: GameEvent) {
+		match event {
+			GameEvent::HandStarted { .. } => self.report.hands += 1,
+			GameEvent::ActionTaken { seat, action, .. } if Some(seat) == self.seat => {
+				if matches!(action, PlayerAction::Timeout) {
+					self.report.timeouts += 1;
+					self.pending.remove(&Request::Action);
+				} else {
+					self.answered(Request::Action);
+				}
+			}
+			GameEvent::GameEnded { .. } => self.send(None, |c| c.leave_table()),
+			GameEvent::PlayerLeft { seat, .. } if Some(seat) == self.seat => self.send(None, |c| c.leave_table()),
+			_ => {}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_percentiles_by_nearest_rank() {
+		let times = (1..=100).map(Duration::from_millis).collect::<Vec<_>>();
+		assert_eq!(percentile(&times, 50.0), Some(Duration::from_millis(50)));
+		assert_eq!(percentile(&times, 99.0), Some(Duration::from_millis(99)));
+		assert_eq!(percentile(&times, 100.0), Some(Duration::from_millis(100)));
+		assert_eq!(percentile(&times[..1], 90.0), Some(Duration::from_millis(1)));
+		assert_eq!(percentile(&[], 50.0), None);
+	}
+
+	#[test]
+	fn test_report_adds_clients_up_and_counts_failures() {
+		let mut report = StressReport { clients: 2, elapsed: Duration::from_secs(10), ..StressReport::default() };
+		for ms in [4, 8] {
+			report.merge(StressReport {
+				latencies: HashMap::from([(Request::Action, vec![Duration::from_millis(ms)])]),
+				sent: 100,
+				errors: 3,
+				timeouts: 1,
+				..StressReport::default()
+			});
+		}
+		assert_eq!(report.latencies[&Request::Action].len(), 2);
+		assert_eq!(report.failures(), 2);
+		assert_eq!(report.failure_rate(), 1.0);
+
+		let lines = report.lines();
+		assert_eq!(lines[0], "2 clients for 10s: 200 messages sent (20/s), 0 hands dealt to them");
+		assert!(lines[7].starts_with("action") && lines[7].ends_with("8.0ms"), "{:?}", lines);
+		assert!(lines[4].ends_with("-"), "{:?}", lines);
+		assert!(lines[9].starts_with("Failures: 2 of 200 messages (1.00%)"), "{:?}", lines);
+		assert_eq!(lines[10], "Errors from the server: 6, like a table filling first");
+	}
+}
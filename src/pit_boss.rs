@@ -0,0 +1,482 @@
+//! The pit boss: an optional table host that watches a game's events and
+//! posts to table chat. It nudges players who are slow to act, answers
+//! rules questions addressed to it (`@boss can I check-raise?`) and reacts
+//! to big hands. Reminders are canned lines; questions and commentary go
+//! to the `pit_boss` model from `models.toml`. Without a model it only
+//! keeps the game moving.
+//!
+//! It only ever sees public information: hole cards appear in its notes
+//! once they're shown down.
+
+use std::collections::HashMap;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::load_models_auto;
+use crate::error::PokerError;
+use crate::events::{Blinds, BettingStructure, Card, GameEvent, PlayerAction, Seat, Street};
+use crate::llm::{AnthropicModel, LanguageModel, Usage};
+use crate::logging;
+use crate::money::chips;
+
+const LOG_NAME: &str = "PitBoss";
+/// Chat lines addressed to the pit boss start with this.
+pub const ADDRESS: &str = "@boss";
+/// A hand is worth commenting on if someone was all in or the pot reached
+/// this many big blinds.
+const NOTABLE_POT_BB: f32 = 25.0;
+const TICK: Duration = Duration::from_millis(250);
+
+const NARRATION_PROMPT: &str = "You are the pit boss at a friendly home poker game, a veteran \
+casino host with a colorful turn of phrase. React to the hand below in one or two short sentences \
+for the table chat. Only mention cards that were shown. Never give strategy advice.";
+
+const RULES_PROMPT: &str = "You are the pit boss at a friendly home Texas Hold'em game. Answer the \
+player's question about the rules or etiquette in at most two short sentences. If it isn't about \
+poker rules or etiquette, politely decline.";
+
+#[derive(Debug, Clone)]
+pub struct PitBossConfig {
+	/// Quiet reminder once a human has been thinking this long.
+	pub remind_after: Duration,
+	/// Etiquette warning after this long.
+	pub warn_after: Duration,
+	/// Comment on notable hands.
+	pub narrate: bool,
+	/// Stop calling the model once this many dollars are spent.
+	pub budget: Option<f64>,
+}
+
+impl Default for PitBossConfig {
+	fn default() -> Self {
+		Self {
+			remind_after: Duration::from_secs(20),
+			warn_after: Duration::from_secs(45),
+			narrate: true,
+			budget: None,
+		}
+	}
+}
+
+/// The `pit_boss` model from `models.toml`, keyed by `ANTHROPIC_API_KEY`.
+pub fn configured_model() -> Result<Box<dyn LanguageModel>, PokerError> {
+	let models = load_models_auto()?;
+	let config = models.pit_boss_model()?.clone();
+	Ok(Box::new(AnthropicModel::from_env(config)?))
+}
+
+struct Waiting {
+	seat: Seat,
+	since: Instant,
+	reminded: bool,
+	warned: bool,
+}
+
+pub struct PitBoss {
+	config: PitBossConfig,
+	model: Option<Box<dyn LanguageModel>>,
+	usage: Usage,
+	names: HashMap<Seat, String>,
+	humans: HashMap<Seat, bool>,
+	betting: Option<BettingStructure>,
+	blinds: Option<Blinds>,
+	hand_notes: Vec<String>,
+	pot: f32,
+	all_in: bool,
+	waiting: Option<Waiting>,
+}
+
+fn cards_str(cards: &[Card]) -> String {
+	cards
+		.iter()
+		.map(|c| format!("{}{}", c.rank, c.suit))
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+impl PitBoss {
+	pub fn new(config: PitBossConfig, model: Option<Box<dyn LanguageModel>>) -> Self {
+		Self {
+			config,
+			model,
+			usage: Usage::default(),
+			names: HashMap::new(),
+			humans: HashMap::new(),
+			betting: None,
+			blinds: None,
+			hand_notes: Vec::new(),
+			pot: 0.0,
+			all_in: false,
+			waiting: None,
+		}
+	}
+
+	pub fn usage(&self) -> &Usage {
+		&self.usage
+	}
+
+	fn name(&self, seat: Seat) -> String {
+		self.names
+			.get(&seat)
+			.cloned()
+			.unwrap_or_else(|| format!("seat {}", seat.0))
+	}
+
+	/// Asks the model, unless there is none or the budget is spent.
+	fn ask(&mut self, system: &str, prompt: &str) -> Option<String> {
+		let model = self.model.as_ref()?;
+		if let Some(budget) = self.config.budget {
+			if self.usage.cost >= budget {
+				return None;
+			}
+		}
+		logging::ai::prompt(LOG_NAME, prompt);
+		match model.complete(system, prompt) {
+			Ok(completion) => {
+				self.usage.record(LOG_NAME, model.as_ref(), &completion);
+				logging::ai::response(LOG_NAME, &completion.text);
+				Some(completion.text).filter(|t| !t.is_empty())
+			}
+			Err(e) => {
+				logging::ai::error(LOG_NAME, &e.to_string());
+				None
+			}
+		}
+	}
+
+	/// Takes in one game event and returns anything to say about it.
+	pub fn on_event(&mut self, event: &GameEvent, now: Instant) -> Vec<String> {
+		match event {
+			GameEvent::GameCreated { config, .. } => {
+				self.betting = Some(config.betting_structure);
+			}
+			GameEvent::GameStarted { seats } | GameEvent::HandStarted { seats, .. } => {
+				for s in seats.iter().filter(|s| s.is_occupied) {
+					self.names.insert(s.seat, s.name.clone());
+					self.humans.insert(s.seat, s.is_human);
+				}
+				if let GameEvent::HandStarted { hand_num, blinds, .. } = event {
+					self.blinds = Some(*blinds);
+					self.hand_notes = vec![format!("Hand #{}, blinds {}/{}", hand_num, chips(blinds.small), chips(blinds.big))];
+					self.pot = 0.0;
+					self.all_in = false;
+					self.waiting = None;
+				}
+			}
+			GameEvent::GameChanged { betting_structure, .. } => {
+				self.betting = Some(*betting_structure);
+			}
+			GameEvent::BlindPosted { amount, .. } => {
+				self.pot += amount;
+			}
+			GameEvent::StreetChanged { street, board, .. } if *street != Street::Showdown => {
+				self.hand_notes.push(format!("{:?}: {}", street, cards_str(board)));
+			}
+			GameEvent::ActionRequest { seat, .. } => {
+				let is_human = self.humans.get(seat).copied().unwrap_or(false);
+				self.waiting = is_human.then_some(Waiting {
+					seat: *seat,
+					since: now,
+					reminded: false,
+					warned: false,
+				});
+			}
+			GameEvent::ActionTaken { seat, action, pot_after, .. } => {
+				if self.waiting.as_ref().is_some_and(|w| w.seat == *seat) {
+					self.waiting = None;
+				}
+				self.all_in |= matches!(action, PlayerAction::AllIn { .. });
+				self.pot = *pot_after;
+				let line = format!("{} {}", self.name(*seat), action.description());
+				self.hand_notes.push(line);
+			}
+			GameEvent::ShowdownReveal { reveals } => {
+				for (seat, cards) in reveals {
+					let line = format!("{} shows {}", self.name(*seat), cards_str(cards));
+					self.hand_notes.push(line);
+				}
+			}
+			GameEvent::PotAwarded { seat, amount, hand_description, .. } => {
+				let mut line = format!("{} wins {}", self.name(*seat), chips(*amount));
+				if let Some(desc) = hand_description {
+					line.push_str(&format!(" with {}", desc));
+				}
+				self.hand_notes.push(line);
+			}
+			GameEvent::HandEnded { .. } => {
+				self.waiting = None;
+				let big_blind = self.blinds.map(|b| b.big).unwrap_or(1.0);
+				let notable = self.all_in || self.pot >= NOTABLE_POT_BB * big_blind;
+				if self.config.narrate && notable {
+					let prompt = self.hand_notes.join("\n");
+					return self.ask(NARRATION_PROMPT, &prompt).into_iter().collect();
+				}
+			}
+			GameEvent::GameEnded { .. } => {
+				self.waiting = None;
+				if self.usage.calls > 0 {
+					logging::ai::rule(
+						LOG_NAME,
+						&format!(
+							"{} calls, {} in / {} out tokens, ${:.4}",
+							self.usage.calls, self.usage.input_tokens, self.usage.output_tokens, self.usage.cost
+						),
+					);
+				}
+			}
+			_ => {}
+		}
+		Vec::new()
+	}
+
+	/// A chat line from a player. Answers it if it's addressed to the pit
+	/// boss.
+	pub fn on_chat(&mut self, seat: Seat, text: &str) -> Vec<String> {
+		let Some(question) = text.trim().strip_prefix(ADDRESS) else {
+			return Vec::new();
+		};
+		let question = question.trim_start_matches([',', ':']).trim();
+		if question.is_empty() {
+			return Vec::new();
+		}
+
+		let name = self.name(seat);
+		let mut table = String::new();
+		if let Some(betting) = self.betting {
+			table.push_str(&format!("Game: {:?} Texas Hold'em. ", betting));
+		}
+		if let Some(blinds) = self.blinds {
+			table.push_str(&format!("Blinds {}/{}. ", chips(blinds.small), chips(blinds.big)));
+		}
+		let prompt = format!("{}{} asks: {}", table, name, question);
+		match self.ask(RULES_PROMPT, &prompt) {
+			Some(answer) => vec![format!("{}: {}", name, answer)],
+			None => vec![format!("{}: Sorry, I can't take questions right now.", name)],
+		}
+	}
+
+	/// Checks the clock on whoever is acting.
+	pub fn tick(&mut self, now: Instant) -> Vec<String> {
+		let Some(waiting) = self.waiting.as_mut() else {
+			return Vec::new();
+		};
+		let elapsed = now.saturating_duration_since(waiting.since);
+		let seat = waiting.seat;
+		if elapsed >= self.config.warn_after && !waiting.warned {
+			waiting.warned = true;
+			waiting.reminded = true;
+			vec![format!("{}, the whole table is waiting on you. Let's keep it moving, please.", self.name(seat))]
+		} else if elapsed >= self.config.remind_after && !waiting.reminded {
+			waiting.reminded = true;
+			vec![format!("Action's on you, {}.", self.name(seat))]
+		} else {
+			Vec::new()
+		}
+	}
+
+	/// Runs on its own thread, so model calls never hold up the game.
+	/// `say` posts a line to the table.
+	pub fn spawn<F: Fn(String) + Send + 'static>(mut self, say: F) -> PitBossHandle {
+		let (tx, rx) = mpsc::channel();
+		thread::spawn(move || {
+			loop {
+				let lines = match rx.recv_timeout(TICK) {
+					Ok(PitBossInput::Event(event)) => self.on_event(&event, Instant::now()),
+					Ok(PitBossInput::Chat(seat, text)) => self.on_chat(seat, &text),
+					Err(RecvTimeoutError::Timeout) => Vec::new(),
+					Err(RecvTimeoutError::Disconnected) => break,
+				};
+				for line in lines.into_iter().chain(self.tick(Instant::now())) {
+					say(line);
+				}
+			}
+		});
+		PitBossHandle { tx }
+	}
+}
+
+enum PitBossInput {
+	Event(GameEvent),
+	Chat(Seat, String),
+}
+
+/// Feeds a spawned pit boss. It stops once the handle is dropped.
+#[derive(Clone)]
+pub struct PitBossHandle {
+	tx: Sender<PitBossInput>,
+}
+
+impl PitBossHandle {
+	pub fn event(&self, event: &GameEvent) {
+		let _ = self.tx.send(PitBossInput::Event(event.clone()));
+	}
+
+	pub fn chat(&self, seat: Seat, text: &str) {
+		let _ = self.tx.send(PitBossInput::Chat(seat, text.to_string()));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{HandId, HandResult, Position, SeatInfo, ValidActions};
+	use crate::llm::Completion;
+	use std::sync::{Arc, Mutex};
+
+	struct FakeModel {
+		prompts: Arc<Mutex<Vec<String>>>,
+	}
+
+	impl LanguageModel for FakeModel {
+		fn id(&self) -> &str {
+			"fake"
+		}
+
+		fn complete(&self, _system: &str, prompt: &str) -> Result<Completion, PokerError> {
+			self.prompts.lock().unwrap().push(prompt.to_string());
+			Ok(Completion {
+				text: "What a hand!".to_string(),
+				input_tokens: 100,
+				output_tokens: 10,
+			})
+		}
+
+		fn cost(&self, _completion: &Completion) -> f64 {
+			0.01
+		}
+	}
+
+	fn boss(config: PitBossConfig) -> (PitBoss, Arc<Mutex<Vec<String>>>) {
+		let prompts = Arc::new(Mutex::new(Vec::new()));
+		let model = FakeModel { prompts: Arc::clone(&prompts) };
+		(PitBoss::new(config, Some(Box::new(model))), prompts)
+	}
+
+	fn hand_started() -> GameEvent {
+		let seat = |n: usize, name: &str, is_human: bool| SeatInfo {
+			seat: Seat(n),
+			name: name.to_string(),
+			stack: 100.0,
+			position: Position::None,
+			is_active: true,
+			is_human,
+			is_occupied: true,
+		};
+		GameEvent::HandStarted {
+			hand_id: HandId(1),
+			hand_num: 1,
+			button: Seat(0),
+			blinds: Blinds { small: 1.0, big: 2.0, ante: None },
+			seats: vec![seat(0, "Alice", true), seat(1, "Bot", false)],
+			at: None,
+			game_id: None,
+		}
+	}
+
+	fn action_request(seat: usize) -> GameEvent {
+		GameEvent::ActionRequest {
+			seat: Seat(seat),
+			valid_actions: ValidActions {
+				can_fold: true,
+				can_check: false,
+				call_amount: Some(2.0),
+				raise_options: None,
+				can_all_in: true,
+				all_in_amount: 100.0,
+				can_show: false,
+			},
+			time_limit: None,
+			at: None,
+		}
+	}
+
+	fn hand_ended() -> GameEvent {
+		GameEvent::HandEnded {
+			hand_id: HandId(1),
+			results: vec![HandResult {
+				seat: Seat(0),
+				stack_change: 100.0,
+				final_stack: 200.0,
+				showed_cards: None,
+				hand_description: None,
+				tilt: None,
+			}],
+			at: None,
+		}
+	}
+
+	#[test]
+	fn test_reminds_then_warns_slow_humans() {
+		let (mut boss, _) = boss(PitBossConfig::default());
+		let start = Instant::now();
+		boss.on_event(&hand_started(), start);
+		boss.on_event(&action_request(0), start);
+
+		assert!(boss.tick(start + Duration::from_secs(5)).is_empty());
+		assert_eq!(boss.tick(start + Duration::from_secs(21)), vec!["Action's on you, Alice."]);
+		assert!(boss.tick(start + Duration::from_secs(22)).is_empty());
+		let warning = boss.tick(start + Duration::from_secs(46));
+		assert!(warning[0].starts_with("Alice, the whole table"));
+		assert!(boss.tick(start + Duration::from_secs(60)).is_empty());
+	}
+
+	#[test]
+	fn test_bots_are_never_rushed() {
+		let (mut boss, _) = boss(PitBossConfig::default());
+		let start = Instant::now();
+		boss.on_event(&hand_started(), start);
+		boss.on_event(&action_request(1), start);
+		assert!(boss.tick(start + Duration::from_secs(60)).is_empty());
+	}
+
+	#[test]
+	fn test_answers_questions_addressed_to_it() {
+		let (mut boss, prompts) = boss(PitBossConfig::default());
+		boss.on_event(&hand_started(), Instant::now());
+		assert!(boss.on_chat(Seat(0), "nice hand").is_empty());
+		let answer = boss.on_chat(Seat(0), "@boss: can I string bet?");
+		assert_eq!(answer, vec!["Alice: What a hand!"]);
+		assert!(prompts.lock().unwrap()[0].ends_with("Alice asks: can I string bet?"));
+		assert_eq!(boss.usage().calls, 1);
+	}
+
+	#[test]
+	fn test_narrates_all_ins_without_leaking_hole_cards() {
+		let (mut boss, prompts) = boss(PitBossConfig::default());
+		let now = Instant::now();
+		boss.on_event(&hand_started(), now);
+		boss.on_event(&GameEvent::HoleCardsDealt { seat: Seat(1), cards: [Card::new('7', 'c'), Card::new('2', 'd')] }, now);
+		boss.on_event(
+			&GameEvent::ActionTaken {
+				seat: Seat(0),
+				action: PlayerAction::AllIn { amount: 100.0 },
+				stack_after: 0.0,
+				pot_after: 103.0,
+				at: None,
+			},
+			now,
+		);
+		let lines = boss.on_event(&hand_ended(), now);
+		assert_eq!(lines, vec!["What a hand!"]);
+		let prompt = prompts.lock().unwrap()[0].clone();
+		assert!(prompt.contains("Alice all-in $100"));
+		assert!(!prompt.contains("7c"));
+	}
+
+	#[test]
+	fn test_quiet_hands_and_spent_budget_cost_nothing() {
+		let (mut boss, prompts) = boss(PitBossConfig {
+			budget: Some(0.0),
+			..PitBossConfig::default()
+		});
+		let now = Instant::now();
+		boss.on_event(&hand_started(), now);
+		assert!(boss.on_event(&hand_ended(), now).is_empty());
+		assert_eq!(
+			boss.on_chat(Seat(0), "@boss what beats a flush?"),
+			vec!["Alice: Sorry, I can't take questions right now."]
+		);
+		assert!(prompts.lock().unwrap().is_empty());
+	}
+}
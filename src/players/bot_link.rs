@@ -0,0 +1,173 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use crate::events::{GameEvent, PlayerAction, Seat, ValidActions};
+use crate::logging;
+use crate::players::port::{GameSnapshot, PlayerResponse};
+
+/// The longest line taken from a bot. Its answers are a few dozen bytes,
+/// so anything near this is a bot gone wrong or a peer feeding junk.
+pub(crate) const MAX_BOT_LINE: usize = 64 * 1024;
+
+/// Reads one line into `line`, newline included, but no more than
+/// `MAX_BOT_LINE` bytes of it: a longer one is an `InvalidData` error,
+/// so a peer that never sends a newline can't grow it without end.
+pub(crate) fn read_bot_line<R: BufRead>(reader: &mut R, line: &mut String) -> io::Result<usize> {
+	let n = reader.take(MAX_BOT_LINE as u64 + 1).read_line(line)?;
+	if n > MAX_BOT_LINE && !line.ends_with('\n') {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "Line too long"));
+	}
+	Ok(n)
+}
+
+/// One line sent to a bot during a game.
+#[derive(Serialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum BotMessage<'a> {
+	ActionRequest {
+		seat: Seat,
+		valid_actions: &'a ValidActions,
+		state: &'a GameSnapshot,
+	},
+	Event {
+		event: &'a GameEvent,
+	},
+}
+
+/// Newline-delimited JSON to and from a bot over any byte stream: a child
+/// process's pipes for `SubprocessPlayer`, a socket for the bot arena.
+/// Reads and writes happen on their own threads, so a bot that stops
+/// reading can't block the table.
+pub struct BotLink {
+	log_name: String,
+	to_bot: Mutex<mpsc::Sender<String>>,
+	from_bot: tokio::sync::Mutex<UnboundedReceiver<String>>,
+	alive: Arc<AtomicBool>,
+}
+
+impl BotLink {
+	pub fn new<R, W>(log_name: impl Into<String>, reader: R, mut writer: W) -> Self
+	where
+		R: Read + Send + 'static,
+		W: Write + Send + 'static,
+	{
+		let alive = Arc::new(AtomicBool::new(true));
+
+		let (to_bot, lines_out) = mpsc::channel::<String>();
+		let writer_alive = Arc::clone(&alive);
+		thread::spawn(move || {
+			for line in lines_out {
+				if writeln!(writer, "{}", line).and_then(|_| writer.flush()).is_err() {
+					writer_alive.store(false, Ordering::SeqCst);
+					break;
+				}
+			}
+		});
+
+		let (lines_in, from_bot) = unbounded_channel();
+		let reader_alive = Arc::clone(&alive);
+		thread::spawn(move || {
+			let mut reader = BufReader::new(reader);
+			let mut line = String::new();
+			while matches!(read_bot_line(&mut reader, &mut line), Ok(n) if n > 0) {
+				let text = line.trim_end_matches(['\n', '\r']).to_string();
+				line.clear();
+				if lines_in.send(text).is_err() {
+					break;
+				}
+			}
+			reader_alive.store(false, Ordering::SeqCst);
+		});
+
+		Self {
+			log_name: log_name.into(),
+			to_bot: Mutex::new(to_bot),
+			from_bot: tokio::sync::Mutex::new(from_bot),
+			alive,
+		}
+	}
+
+	/// False once the bot has closed its end or can no longer be written to.
+	pub fn is_alive(&self) -> bool {
+		self.alive.load(Ordering::SeqCst)
+	}
+
+	/// Queues one JSON line for the bot.
+	pub fn send<T: Serialize + ?Sized>(&self, message: &T) -> bool {
+		let Ok(line) = serde_json::to_string(message) else {
+			return false;
+		};
+		self.to_bot
+			.lock()
+			.map(|tx| tx.send(line).is_ok())
+			.unwrap_or(false)
+	}
+
+	pub fn notify(&self, event: &GameEvent) {
+		if self.is_alive() {
+			self.send(&BotMessage::Event { event });
+		}
+	}
+
+	/// Sends an `action_request` line and waits for a `PlayerAction` line.
+	/// A dead bot, a late answer or an unparseable one is a timeout.
+	pub async fn request_action(
+		&self,
+		seat: Seat,
+		valid_actions: &ValidActions,
+		game_state: &GameSnapshot,
+		time_limit: Option<Duration>,
+	) -> PlayerResponse {
+		if !self.is_alive() {
+			return PlayerResponse::Timeout;
+		}
+
+		let mut from_bot = self.from_bot.lock().await;
+		// Anything already waiting is a late answer to an earlier request
+		while from_bot.try_recv().is_ok() {}
+
+		let request = BotMessage::ActionRequest {
+			seat,
+			valid_actions,
+			state: game_state,
+		};
+		if !self.send(&request) {
+			self.log("ERROR", "connection closed");
+			return PlayerResponse::Timeout;
+		}
+
+		let reply = match time_limit {
+			Some(limit) => match tokio::time::timeout(limit, from_bot.recv()).await {
+				Ok(reply) => reply,
+				Err(_) => {
+					self.log("TIMEOUT", &format!("no answer within {:?}", limit));
+					return PlayerResponse::Timeout;
+				}
+			},
+			None => from_bot.recv().await,
+		};
+
+		let Some(line) = reply else {
+			self.log("ERROR", "bot went away");
+			return PlayerResponse::Timeout;
+		};
+		match serde_json::from_str::<PlayerAction>(line.trim()) {
+			Ok(action) => PlayerResponse::Action(action),
+			Err(e) => {
+				self.log("ERROR", &format!("unparseable action {:?}: {}", line, e));
+				PlayerResponse::Timeout
+			}
+		}
+	}
+
+	pub fn log(&self, log_type: &str, message: &str) {
+		logging::log(&self.log_name, log_type, message);
+	}
+}
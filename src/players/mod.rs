@@ -1,11 +1,29 @@
+#[cfg(feature = "runtime")]
+mod bot_link;
+#[cfg(feature = "runtime")]
+mod pool;
 mod port;
 mod remote_player;
 mod rules_player;
+#[cfg(feature = "runtime")]
+mod subprocess_player;
+#[cfg(feature = "runtime")]
 mod terminal;
 mod test_player;
 
-pub use port::{ActionRecord, AdminRequest, GameSnapshot, PlayerPort, PlayerResponse, SeatSnapshot};
+#[cfg(feature = "runtime")]
+pub use bot_link::BotLink;
+#[cfg(feature = "net")]
+pub(crate) use bot_link::{read_bot_line, BotMessage, MAX_BOT_LINE};
+#[cfg(feature = "runtime")]
+pub use pool::{DecisionPool, DecisionStats, PooledPlayer, DEFAULT_DECISION_WORKERS};
+pub use port::{
+	ActionRecord, AdminRequest, GameSnapshot, HandRecap, PlayerPort, PlayerResponse, PotSnapshot, SeatSnapshot,
+};
 pub use remote_player::{RemotePlayer, RemotePlayerConfig};
 pub use rules_player::RulesPlayer;
+#[cfg(feature = "runtime")]
+pub use subprocess_player::{SubprocessPlayer, DEFAULT_BOT_TIME_LIMIT};
+#[cfg(feature = "runtime")]
 pub use terminal::{ActionRequest, TerminalPlayer, TerminalPlayerHandle};
 pub use test_player::{CallingPlayer, FoldingPlayer, TestPlayer};
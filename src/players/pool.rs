@@ -0,0 +1,302 @@
+//! A pool of threads for AI decisions, shared by every table a server
+//! runs, so a decision doesn't hold up the game's own runtime and a table
+//! of slow players can't take every thread. Each game queues its
+//! decisions separately, and a free worker takes the next one from the
+//! game that has had the least of the workers' time so far: a table whose
+//! players think for seconds waits behind tables whose players answer at
+//! once. `DecisionStats` reports how long each game's decisions waited
+//! and took.
+
+use std::collections::{HashMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tokio::runtime::Runtime;
+use tokio::sync::oneshot;
+
+use crate::events::{GameEvent, GameId, Seat, ValidActions};
+use crate::players::{GameSnapshot, HandRecap, PlayerPort, PlayerResponse};
+
+/// Workers when a server doesn't say.
+pub const DEFAULT_DECISION_WORKERS: usize = 4;
+
+/// How a game's decisions have gone on the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DecisionStats {
+	pub decisions: u32,
+	/// Time spent queued for a worker, all told.
+	pub waited: Duration,
+	pub longest_wait: Duration,
+	/// Time spent deciding, all told.
+	pub thinking: Duration,
+	pub longest_think: Duration,
+}
+
+impl DecisionStats {
+	pub fn mean_wait(&self) -> Duration {
+		self.waited.checked_div(self.decisions).unwrap_or_default()
+	}
+
+	pub fn mean_think(&self) -> Duration {
+		self.thinking.checked_div(self.decisions).unwrap_or_default()
+	}
+}
+
+type Job = Box<dyn FnOnce(&Runtime) + Send>;
+
+struct Queued {
+	at: Instant,
+	job: Job,
+}
+
+#[derive(Default)]
+struct GameQueue {
+	jobs: VecDeque<Queued>,
+	/// Worker time this game has had; the least goes next.
+	used: Duration,
+	stats: DecisionStats,
+}
+
+struct State {
+	games: HashMap<GameId, GameQueue>,
+	open: bool,
+}
+
+impl State {
+	/// The waiting game that has had the least time.
+	fn next(&mut self) -> Option<(GameId, Queued)> {
+		let (&game, queue) = self.games.iter_mut()
+			.filter(|(_, q)| !q.jobs.is_empty())
+			.min_by_key(|(_, q)| q.used)?;
+		Some((game, queue.jobs.pop_front()?))
+	}
+}
+
+struct Shared {
+	state: Mutex<State>,
+	queued: Condvar,
+}
+
+fn lock(shared: &Shared) -> MutexGuard<'_, State> {
+	shared.state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Closes the pool once the last handle goes.
+struct Owner(Arc<Shared>);
+
+impl Drop for Owner {
+	fn drop(&mut self) {
+		lock(&self.0).open = false;
+		self.0.queued.notify_all();
+	}
+}
+
+/// The pool; clones share its workers.
+#[derive(Clone)]
+pub struct DecisionPool {
+	shared: Arc<Shared>,
+	_owner: Arc<Owner>,
+}
+
+impl Default for DecisionPool {
+	fn default() -> Self {
+		Self::new(DEFAULT_DECISION_WORKERS)
+	}
+}
+
+impl DecisionPool {
+	pub fn new(workers: usize) -> Self {
+		let shared = Arc::new(Shared {
+			state: Mutex::new(State { games: HashMap::new(), open: true }),
+			queued: Condvar::new(),
+		});
+		for i in 0..workers.max(1) {
+			let shared = Arc::clone(&shared);
+			let spawned = thread::Builder::new()
+				.name(format!("decisions-{}", i))
+				.spawn(move || work(&shared));
+			if let Err(e) = spawned {
+				eprintln!("Failed to start a decision worker: {}", e);
+			}
+		}
+		Self { _owner: Arc::new(Owner(Arc::clone(&shared))), shared }
+	}
+
+	fn submit(&self, game: GameId, job: Job) {
+		let mut state = lock(&self.shared);
+		if !state.games.contains_key(&game) {
+			// A game joining late starts level with the rest, not ahead
+			let used = state.games.values().map(|q| q.used).min().unwrap_or_default();
+			state.games.insert(game, GameQueue { used, ..GameQueue::default() });
+		}
+		if let Some(queue) = state.games.get_mut(&game) {
+			queue.jobs.push_back(Queued { at: Instant::now(), job });
+		}
+		self.shared.queued.notify_one();
+	}
+
+	/// How `game`'s decisions have gone so far.
+	pub fn stats(&self, game: GameId) -> Option<DecisionStats> {
+		lock(&self.shared).games.get(&game).map(|q| q.stats)
+	}
+
+	/// Forgets a game that's over, returning its stats.
+	pub fn finish(&self, game: GameId) -> Option<DecisionStats> {
+		lock(&self.shared).games.remove(&game).map(|q| q.stats)
+	}
+}
+
+fn work(shared: &Shared) {
+	let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+		Ok(runtime) => runtime,
+		Err(e) => {
+			eprintln!("Failed to start a decision worker's runtime: {}", e);
+			return;
+		}
+	};
+	let mut state = lock(shared);
+	loop {
+		let Some((game, queued)) = state.next() else {
+			if !state.open {
+				return;
+			}
+			state = shared.queued.wait(state).unwrap_or_else(|e| e.into_inner());
+			continue;
+		};
+		drop(state);
+
+		let started = Instant::now();
+		// A player that panics only loses its answer, which reads as a timeout
+		let _ = panic::catch_unwind(AssertUnwindSafe(|| (queued.job)(&runtime)));
+		let took = started.elapsed();
+		let waited = started - queued.at;
+
+		state = lock(shared);
+		if let Some(queue) = state.games.get_mut(&game) {
+			queue.used += took;
+			let stats = &mut queue.stats;
+			stats.decisions += 1;
+			stats.waited += waited;
+			stats.longest_wait = stats.longest_wait.max(waited);
+			stats.thinking += took;
+			stats.longest_think = stats.longest_think.max(took);
+		}
+	}
+}
+
+/// A player whose decisions are made on a `DecisionPool`. Everything else
+/// goes straight to the player.
+pub struct PooledPlayer {
+	player: Arc<dyn PlayerPort>,
+	pool: DecisionPool,
+	game: GameId,
+}
+
+impl PooledPlayer {
+	pub fn new(player: Arc<dyn PlayerPort>, pool: &DecisionPool, game: GameId) -> Self {
+		Self { player, pool: pool.clone(), game }
+	}
+}
+
+#[async_trait]
+impl PlayerPort for PooledPlayer {
+	async fn request_action(
+		&self,
+		seat: Seat,
+		valid_actions: ValidActions,
+		game_state: &GameSnapshot,
+	) -> PlayerResponse {
+		let (tx, rx) = oneshot::channel();
+		let player = Arc::clone(&self.player);
+		let snapshot = game_state.clone();
+		self.pool.submit(self.game, Box::new(move |runtime| {
+			let response = runtime.block_on(player.request_action(seat, valid_actions, &snapshot));
+			let _ = tx.send(response);
+		}));
+		rx.await.unwrap_or(PlayerResponse::Timeout)
+	}
+
+	fn notify(&self, event: &GameEvent) {
+		self.player.notify(event);
+	}
+
+	fn auto_muck(&self) -> bool {
+		self.player.auto_muck()
+	}
+
+	fn asks_to_show(&self) -> bool {
+		self.player.asks_to_show()
+	}
+
+	fn hand_over(&self, recap: &HandRecap) {
+		self.player.hand_over(recap);
+	}
+
+	fn table_talk(&self, recap: &HandRecap) -> Option<String> {
+		self.player.table_talk(recap)
+	}
+
+	fn tilt(&self) -> Option<f32> {
+		self.player.tilt()
+	}
+
+	fn seat(&self) -> Seat {
+		self.player.seat()
+	}
+
+	fn name(&self) -> &str {
+		self.player.name()
+	}
+
+	fn is_human(&self) -> bool {
+		self.player.is_human()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::mpsc;
+
+	fn job(pool: &DecisionPool, game: u64, think: Duration, done: &mpsc::Sender<u64>) {
+		let done = done.clone();
+		pool.submit(GameId(game), Box::new(move |_| {
+			thread::sleep(think);
+			let _ = done.send(game);
+		}));
+	}
+
+	#[test]
+	fn test_a_slow_game_waits_behind_a_quick_one() {
+		let pool = DecisionPool::new(1);
+		let (done, finished) = mpsc::channel();
+		job(&pool, 2, Duration::ZERO, &done);
+		assert_eq!(finished.recv().unwrap(), 2);
+		// Game 1 has had the worker for longer
+		job(&pool, 1, Duration::from_millis(50), &done);
+		assert_eq!(finished.recv().unwrap(), 1);
+
+		// Hold the worker so both games queue up behind it
+		let (release, hold) = mpsc::channel::<()>();
+		pool.submit(GameId(3), Box::new(move |_| {
+			let _ = hold.recv();
+		}));
+		job(&pool, 1, Duration::ZERO, &done);
+		job(&pool, 2, Duration::ZERO, &done);
+		release.send(()).unwrap();
+
+		assert_eq!(finished.recv().unwrap(), 2);
+		assert_eq!(finished.recv().unwrap(), 1);
+		// The worker counts a decision just after it's answered
+		while pool.stats(GameId(1)).map(|s| s.decisions) != Some(2) {
+			thread::yield_now();
+		}
+		let stats = pool.finish(GameId(1)).unwrap();
+		assert_eq!(stats.decisions, 2);
+		assert!(stats.longest_think >= Duration::from_millis(50));
+		assert!(pool.stats(GameId(1)).is_none());
+	}
+}
@@ -1,4 +1,9 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Serialize;
+
 use crate::events::{GameEvent, PlayerAction, Seat, ValidActions};
 
 #[async_trait]
@@ -10,8 +15,57 @@ pub trait PlayerPort: Send + Sync {
 		game_state: &GameSnapshot,
 	) -> PlayerResponse;
 
+	/// `request_action` with a deadline. A player who hasn't answered when
+	/// `time_limit` runs out gets `PlayerResponse::Timeout`. Without the
+	/// `runtime` feature, or outside a tokio runtime as in a `Session`,
+	/// there is no timer, so the limit is not enforced.
+	async fn request_action_within(
+		&self,
+		seat: Seat,
+		valid_actions: ValidActions,
+		game_state: &GameSnapshot,
+		time_limit: Option<Duration>,
+	) -> PlayerResponse {
+		let request = self.request_action(seat, valid_actions, game_state);
+		#[cfg(feature = "runtime")]
+		if let Some(limit) = time_limit.filter(|_| tokio::runtime::Handle::try_current().is_ok()) {
+			return tokio::time::timeout(limit, request)
+				.await
+				.unwrap_or(PlayerResponse::Timeout);
+		}
+		#[cfg(not(feature = "runtime"))]
+		let _ = time_limit;
+		request.await
+	}
+
 	fn notify(&self, event: &GameEvent);
 
+	/// Muck losing hands at showdown rather than show them.
+	fn auto_muck(&self) -> bool {
+		false
+	}
+
+	/// Offer the choice of showing a hand that won uncalled. Seats that
+	/// aren't asked muck it.
+	fn asks_to_show(&self) -> bool {
+		false
+	}
+
+	/// Told once a hand is over, before `table_talk`.
+	fn hand_over(&self, _recap: &HandRecap) {}
+
+	/// Something to say to the table once a hand is over. Whatever is
+	/// returned goes out as this seat's chat line.
+	fn table_talk(&self, _recap: &HandRecap) -> Option<String> {
+		None
+	}
+
+	/// How tilted the player is, 0.0 to 1.0, for players that can tilt.
+	/// Kept on `HandResult::tilt` for the hands they play.
+	fn tilt(&self) -> Option<f32> {
+		None
+	}
+
 	fn seat(&self) -> Seat;
 
 	fn name(&self) -> &str;
@@ -19,29 +73,103 @@ pub trait PlayerPort: Send + Sync {
 	fn is_human(&self) -> bool;
 }
 
-#[derive(Debug, Clone)]
+/// Everything a seat may know when it has to act: the public table state,
+/// its own hole cards and what it is allowed to do.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct GameSnapshot {
 	pub hand_num: u32,
 	pub street: crate::events::Street,
 	pub board: Vec<crate::events::Card>,
 	pub pot: f32,
+	/// Main pot first, then side pots.
+	pub pots: Vec<PotSnapshot>,
+	/// What the hero can still win or lose: their stack or the biggest
+	/// stack among the others still in, whichever is smaller.
+	pub effective_stack: f32,
 	pub seats: Vec<SeatSnapshot>,
+	pub hero_seat: Seat,
 	pub hero_cards: Option<[crate::events::Card; 2]>,
+	pub button: Seat,
+	pub blinds: crate::events::Blinds,
+	/// The street total everyone still in has to match.
+	pub current_bet: f32,
+	/// Smallest raise increment over `current_bet`.
+	pub min_raise: f32,
+	pub raises_this_street: u32,
+	pub max_raises: u32,
+	pub valid_actions: ValidActions,
+	/// Every action this hand, oldest first.
 	pub action_history: Vec<ActionRecord>,
 }
 
+impl GameSnapshot {
+	pub fn hero(&self) -> Option<&SeatSnapshot> {
+		self.seats.iter().find(|s| s.seat == self.hero_seat)
+	}
+
+	pub fn seat(&self, seat: Seat) -> Option<&SeatSnapshot> {
+		self.seats.iter().find(|s| s.seat == seat)
+	}
+
+	/// Actions on the current street, oldest first.
+	pub fn street_actions(&self) -> impl Iterator<Item = &ActionRecord> {
+		self.action_history.iter().filter(|a| a.street == self.street)
+	}
+
+	/// Seats that were dealt in and haven't folded.
+	pub fn players_in_hand(&self) -> usize {
+		self.seats.iter().filter(|s| s.is_active && !s.is_folded).count()
+	}
+
+	/// The hero's stack-to-pot ratio, as things stand.
+	pub fn spr(&self) -> Option<f32> {
+		crate::events::stack_to_pot(self.effective_stack, self.pot)
+	}
+}
+
+/// The public story of a finished hand.
 #[derive(Debug, Clone)]
+pub struct HandRecap {
+	pub hand_num: u32,
+	pub big_blind: f32,
+	pub board: Vec<crate::events::Card>,
+	/// Chips won or lost by each seat dealt in.
+	pub results: Vec<(Seat, f32)>,
+	/// Two or more hands were shown down.
+	pub showdown: bool,
+	/// A seat that lost the showdown after being a big favourite on the turn.
+	pub bad_beat: Option<Seat>,
+}
+
+impl HandRecap {
+	pub fn result(&self, seat: Seat) -> Option<f32> {
+		self.results.iter().find(|(s, _)| *s == seat).map(|(_, change)| *change)
+	}
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct SeatSnapshot {
 	pub seat: Seat,
 	pub name: String,
 	pub stack: f32,
+	/// Put in on this street.
 	pub current_bet: f32,
+	/// Put in over the whole hand.
+	pub total_bet: f32,
+	/// Dealt into this hand.
+	pub is_active: bool,
 	pub is_folded: bool,
 	pub is_all_in: bool,
 	pub position: crate::events::Position,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PotSnapshot {
+	pub amount: f32,
+	pub eligible: Vec<Seat>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct ActionRecord {
 	pub seat: Seat,
 	pub street: crate::events::Street,
@@ -30,9 +30,11 @@ impl PlayerPort for RemotePlayer {
 	async fn request_action(
 		&self,
 		_seat: Seat,
-		valid_actions: ValidActions,
-		_game_state: &GameSnapshot,
+		_valid_actions: ValidActions,
+		game_state: &GameSnapshot,
 	) -> PlayerResponse {
+		// The snapshot carries the legal actions along with the table state,
+		// so it is the whole request body.
 		// TODO: implement async server call
 		// let response = reqwest::Client::new()
 		//     .post(&format!("{}/v1/action", self.config.server_url))
@@ -40,14 +42,13 @@ impl PlayerPort for RemotePlayer {
 		//     .json(&json!({
 		//         "opponent_id": self.config.opponent_id,
 		//         "game_state": game_state,
-		//         "valid_actions": valid_actions,
 		//     }))
 		//     .send()
 		//     .await?
 		//     .json::<ActionResponse>()
 		//     .await?;
 
-		let action = if valid_actions.can_check {
+		let action = if game_state.valid_actions.can_check {
 			PlayerAction::Check
 		} else {
 			PlayerAction::Fold
@@ -1,31 +1,44 @@
+use std::sync::Mutex;
+
 use async_trait::async_trait;
-use crate::ai::{try_rules, RuleDecision, Situation};
+use crate::ai::{remark, try_rules, MadeHand, Moment, RuleDecision, Situation, Tilt};
+use crate::engine::rank_hand;
 use crate::events::{Card, GameEvent, PlayerAction, RaiseOptions, Seat, Street, ValidActions};
 use crate::logging::ai as log;
-use crate::players::{GameSnapshot, PlayerPort, PlayerResponse};
-use crate::strategy::{char_to_rank, HandGroup, HoleCards, Position, Strategy};
+use crate::players::{GameSnapshot, HandRecap, PlayerPort, PlayerResponse};
+use crate::strategy::{char_to_rank, HandGroup, HoleCards, Position, Strategy, TableSize};
 
 pub struct RulesPlayer {
 	seat: Seat,
 	name: String,
 	strategy: Strategy,
-	big_blind: f32,
-	button: std::sync::RwLock<usize>,
-	num_players: std::sync::RwLock<usize>,
+	/// Scales the strategy persona's chattiness; 0.0 keeps it silent.
+	talk_frequency: f32,
+	/// Only moves if the strategy has a `tilt` config.
+	tilt: Mutex<Tilt>,
 }
 
 impl RulesPlayer {
-	pub fn new(seat: Seat, name: &str, strategy: Strategy, big_blind: f32) -> Self {
+	pub fn new(seat: Seat, name: &str, strategy: Strategy) -> Self {
 		Self {
 			seat,
 			name: name.to_string(),
 			strategy,
-			big_blind,
-			button: std::sync::RwLock::new(0),
-			num_players: std::sync::RwLock::new(2),
+			talk_frequency: 0.0,
+			tilt: Mutex::new(Tilt::Calm),
 		}
 	}
 
+	/// Lets the player chat after memorable hands, in its strategy's persona.
+	pub fn with_table_talk(mut self, frequency: f32) -> Self {
+		self.talk_frequency = frequency;
+		self
+	}
+
+	fn tilt_level(&self) -> f32 {
+		self.tilt.lock().unwrap_or_else(|e| e.into_inner()).level()
+	}
+
 	fn classify_cards(&self, cards: &[Card; 2]) -> Option<HandGroup> {
 		let rank1 = char_to_rank(cards[0].rank)?;
 		let rank2 = char_to_rank(cards[1].rank)?;
@@ -33,45 +46,56 @@ impl RulesPlayer {
 		Some(HoleCards::new(rank1, rank2, suited).classify())
 	}
 
-	fn get_position(&self) -> Position {
-		let button = *self.button.read().unwrap_or_else(|e| e.into_inner());
-		let num_players = *self.num_players.read().unwrap_or_else(|e| e.into_inner());
-		Position::from_seat(self.seat.0, button, num_players)
+	/// Position among the seats dealt in, so empty and busted seats don't
+	/// push everyone later.
+	fn get_position(&self, snapshot: &GameSnapshot) -> Position {
+		let n = snapshot.seats.len().max(1);
+		let from_button = |seat: Seat| (seat.0 + n - snapshot.button.0) % n;
+		let active: Vec<Seat> = snapshot.seats.iter()
+			.filter(|s| s.is_active)
+			.map(|s| s.seat)
+			.collect();
+		let ours = from_button(self.seat);
+		let ahead = active.iter().filter(|&&s| from_button(s) < ours).count();
+		Position::from_seat(ahead, 0, active.len())
 	}
 
 	fn build_situation(&self, cards: &[Card; 2], snapshot: &GameSnapshot, valid: &ValidActions) -> Option<Situation> {
 		let hand_group = self.classify_cards(cards)?;
-		let position = self.get_position();
+		let position = self.get_position(snapshot);
+		let table_size = TableSize::from_players(snapshot.seats.iter().filter(|s| s.is_active).count());
 
 		let to_call = valid.call_amount.unwrap_or(0.0);
-		let current_bet = snapshot.seats.iter()
-			.map(|s| s.current_bet)
-			.fold(0.0_f32, |a, b| a.max(b));
-
-		let num_raises = snapshot.action_history.iter()
-			.filter(|a| a.street == snapshot.street)
-			.filter(|a| matches!(a.action, PlayerAction::Raise { .. } | PlayerAction::Bet { .. }))
-			.count() as u32;
 
 		let we_are_preflop_aggressor = snapshot.action_history.iter()
 			.filter(|a| a.street == Street::Preflop)
 			.filter(|a| a.seat == self.seat)
 			.any(|a| matches!(a.action, PlayerAction::Raise { .. } | PlayerAction::Bet { .. }));
 
+		let checked_this_street = snapshot.action_history.iter()
+			.any(|a| a.street == snapshot.street && a.seat == self.seat && matches!(a.action, PlayerAction::Check));
+
+		let made_hand = if snapshot.board.is_empty() {
+			MadeHand::Nothing
+		} else {
+			MadeHand::from_rank(&rank_hand(cards, &snapshot.board))
+		};
+
 		Some(Situation {
 			hand_group,
 			position,
+			table_size,
 			pot: snapshot.pot,
 			to_call,
-			stack: snapshot.seats.iter()
-				.find(|s| s.seat == self.seat)
-				.map(|s| s.stack)
-				.unwrap_or(0.0),
-			big_blind: self.big_blind,
-			current_bet,
+			stack: snapshot.hero().map(|s| s.stack).unwrap_or(0.0),
+			big_blind: snapshot.blinds.big,
+			current_bet: snapshot.current_bet,
 			is_preflop: snapshot.street == Street::Preflop,
-			num_raises,
-			raise_cap: 4,
+			is_river: snapshot.street == Street::River,
+			made_hand,
+			checked_this_street,
+			num_raises: snapshot.raises_this_street,
+			raise_cap: snapshot.max_raises,
 			we_are_preflop_aggressor,
 		})
 	}
@@ -117,6 +141,9 @@ impl RulesPlayer {
 	}
 
 	fn decide(&self, valid: &ValidActions, snapshot: &GameSnapshot) -> PlayerAction {
+		if valid.can_show {
+			return PlayerAction::Muck;
+		}
 		let cards = match snapshot.hero_cards {
 			Some(ref c) => c,
 			None => {
@@ -125,10 +152,7 @@ impl RulesPlayer {
 			}
 		};
 
-		let stack = snapshot.seats.iter()
-			.find(|s| s.seat == self.seat)
-			.map(|s| s.stack)
-			.unwrap_or(0.0);
+		let stack = snapshot.hero().map(|s| s.stack).unwrap_or(0.0);
 
 		if let Some(situation) = self.build_situation(cards, snapshot, valid) {
 			log::strategy(&self.name, &format!(
@@ -136,7 +160,12 @@ impl RulesPlayer {
 				situation.hand_group, situation.position, snapshot.street
 			));
 
-			if let Some(decision) = try_rules(&self.strategy, &situation) {
+			let level = self.tilt_level();
+			if level > 0.0 {
+				log::strategy(&self.name, &format!("tilted {:.2}", level));
+			}
+			let strategy = self.strategy.tilted(level);
+			if let Some(decision) = try_rules(&strategy, &situation, &mut rand::rng()) {
 				let action = self.rule_to_action(decision, valid, stack);
 				log::decision(&self.name, "RULE", &action.description());
 				return action;
@@ -146,7 +175,7 @@ impl RulesPlayer {
 		let action = if valid.can_check {
 			PlayerAction::Check
 		} else if let Some(amount) = valid.call_amount {
-			if amount <= self.big_blind * 2.0 {
+			if amount <= snapshot.blinds.big * 2.0 {
 				PlayerAction::Call { amount }
 			} else {
 				PlayerAction::Fold
@@ -172,11 +201,25 @@ impl PlayerPort for RulesPlayer {
 		PlayerResponse::Action(action)
 	}
 
-	fn notify(&self, event: &GameEvent) {
-		if let GameEvent::HandStarted { button, seats, .. } = event {
-			*self.button.write().unwrap_or_else(|e| e.into_inner()) = button.0;
-			*self.num_players.write().unwrap_or_else(|e| e.into_inner()) = seats.len();
+	fn notify(&self, _event: &GameEvent) {}
+
+	fn hand_over(&self, recap: &HandRecap) {
+		let (Some(config), Some(net)) = (self.strategy.tilt, recap.result(self.seat)) else { return };
+		let mut tilt = self.tilt.lock().unwrap_or_else(|e| e.into_inner());
+		*tilt = tilt.after_hand(&config, net, recap.big_blind);
+	}
+
+	fn table_talk(&self, recap: &HandRecap) -> Option<String> {
+		if self.talk_frequency <= 0.0 {
+			return None;
 		}
+		let moment = Moment::for_seat(recap, self.seat)?;
+		remark(self.strategy.chat_persona, moment, self.talk_frequency, &mut rand::rng())
+			.map(str::to_string)
+	}
+
+	fn tilt(&self) -> Option<f32> {
+		self.strategy.tilt.map(|_| self.tilt_level())
 	}
 
 	fn seat(&self) -> Seat {
@@ -199,7 +242,52 @@ mod tests {
 	use crate::events::Position as EventPosition;
 
 	fn make_test_player() -> RulesPlayer {
-		RulesPlayer::new(Seat(0), "TestAI", Strategy::default(), 2.0)
+		RulesPlayer::new(Seat(0), "TestAI", Strategy::default())
+	}
+
+	fn snapshot(button: usize, active: &[bool]) -> GameSnapshot {
+		let seats = active
+			.iter()
+			.enumerate()
+			.map(|(i, &is_active)| crate::players::SeatSnapshot {
+				seat: Seat(i),
+				name: format!("P{}", i),
+				stack: if is_active { 100.0 } else { 0.0 },
+				current_bet: 0.0,
+				total_bet: 0.0,
+				is_active,
+				is_folded: false,
+				is_all_in: false,
+				position: EventPosition::None,
+			})
+			.collect();
+		GameSnapshot {
+			hand_num: 1,
+			street: Street::Preflop,
+			board: vec![],
+			pot: 3.0,
+			pots: vec![],
+			effective_stack: 100.0,
+			seats,
+			hero_seat: Seat(0),
+			hero_cards: None,
+			button: Seat(button),
+			blinds: crate::events::Blinds { small: 1.0, big: 2.0, ante: None },
+			current_bet: 2.0,
+			min_raise: 2.0,
+			raises_this_street: 0,
+			max_raises: 4,
+			valid_actions: ValidActions {
+				can_fold: true,
+				can_check: false,
+				call_amount: Some(2.0),
+				raise_options: None,
+				can_all_in: true,
+				all_in_amount: 100.0,
+				can_show: false,
+			},
+			action_history: vec![],
+		}
 	}
 
 	#[test]
@@ -211,37 +299,73 @@ mod tests {
 	}
 
 	#[test]
-	fn test_position_calculation() {
-		let player = RulesPlayer::new(Seat(0), "Test", Strategy::default(), 2.0);
-		
-		player.notify(&GameEvent::HandStarted {
-			hand_id: crate::events::HandId(1),
+	fn test_table_talk_is_opt_in() {
+		let recap = HandRecap {
 			hand_num: 1,
-			button: Seat(1),
-			blinds: crate::events::Blinds { small: 1.0, big: 2.0, ante: None },
-			seats: vec![
-				crate::events::SeatInfo {
-					seat: Seat(0),
-					name: "A".to_string(),
-					stack: 100.0,
-					position: EventPosition::SmallBlind,
-					is_active: true,
-					is_human: false,
-				},
-				crate::events::SeatInfo {
-					seat: Seat(1),
-					name: "B".to_string(),
-					stack: 100.0,
-					position: EventPosition::Button,
-					is_active: true,
-					is_human: false,
-				},
-			],
-		});
-		
-		let position = player.get_position();
+			big_blind: 2.0,
+			board: vec![],
+			results: vec![(Seat(0), -200.0), (Seat(1), 200.0)],
+			showdown: true,
+			bad_beat: Some(Seat(0)),
+		};
+		assert!(make_test_player().table_talk(&recap).is_none());
+
+		// Well past any persona's chattiness, so it always speaks
+		let talker = make_test_player().with_table_talk(100.0);
+		assert!(talker.table_talk(&recap).is_some());
+	}
+
+	#[test]
+	fn test_position_calculation() {
+		let player = make_test_player();
 		// Seat 0 with button at seat 1 in heads-up = big blind
-		assert_eq!(position, Position::Bb);
+		assert_eq!(player.get_position(&snapshot(1, &[true, true])), Position::Bb);
+	}
+
+	#[test]
+	fn test_position_skips_empty_seats() {
+		let player = RulesPlayer::new(Seat(3), "Test", Strategy::default());
+		// Seats 1 and 2 are empty, so seat 3 is next after the button and
+		// posts the small blind rather than sitting under the gun.
+		let state = snapshot(0, &[true, false, false, true, true, true]);
+		assert_eq!(player.get_position(&state), Position::Sb);
+	}
+
+	#[test]
+	fn test_table_size_counts_seats_dealt_in() {
+		let player = make_test_player();
+		let cards = [Card::new('7', 'h'), Card::new('2', 'c')];
+		let valid = snapshot(0, &[]).valid_actions;
+		let heads_up = snapshot(0, &[true, true, false, false, false, false, false, false, false]);
+		let situation = player.build_situation(&cards, &heads_up, &valid).unwrap();
+		assert_eq!(situation.table_size, TableSize::HeadsUp);
+		let full = snapshot(0, &[true; 9]);
+		let situation = player.build_situation(&cards, &full, &valid).unwrap();
+		assert_eq!(situation.table_size, TableSize::FullRing);
+	}
+
+	#[test]
+	fn test_big_loss_tilts_a_player_that_can_tilt() {
+		let recap = |net: f32| HandRecap {
+			hand_num: 1,
+			big_blind: 2.0,
+			board: vec![],
+			results: vec![(Seat(0), net), (Seat(1), -net)],
+			showdown: true,
+			bad_beat: None,
+		};
+		let steady = make_test_player();
+		steady.hand_over(&recap(-200.0));
+		assert_eq!(steady.tilt(), None);
+
+		let mut strategy = Strategy::default();
+		strategy.tilt = Some(crate::strategy::TiltConfig { big_pot: 40.0, decay: 0.5, ..Default::default() });
+		let player = RulesPlayer::new(Seat(0), "Hothead", strategy);
+		assert_eq!(player.tilt(), Some(0.0));
+		player.hand_over(&recap(-100.0));
+		assert_eq!(player.tilt(), Some(1.0));
+		player.hand_over(&recap(4.0));
+		assert_eq!(player.tilt(), Some(0.5));
 	}
 
 	#[test]
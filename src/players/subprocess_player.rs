@@ -0,0 +1,218 @@
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::error::PokerError;
+use crate::events::redaction::{redact, Audience};
+use crate::events::{GameEvent, Seat, ValidActions};
+use crate::logging;
+use crate::players::bot_link::BotLink;
+use crate::players::port::{GameSnapshot, PlayerPort, PlayerResponse};
+
+/// How long a bot gets per decision unless told otherwise.
+pub const DEFAULT_BOT_TIME_LIMIT: Duration = Duration::from_secs(5);
+
+/// A player run by an external program. The program reads newline-delimited
+/// JSON on stdin: an `event` line for everything its seat may see, passed
+/// in through `observe`, and an `action_request` line when it must act, which it answers with one
+/// line holding a `PlayerAction`. A bot that is too slow, answers with
+/// something unparseable, or exits is treated as timing out, which checks
+/// or folds. Once it has exited it is never asked again.
+pub struct SubprocessPlayer {
+	seat: Seat,
+	name: String,
+	child: Mutex<Child>,
+	link: BotLink,
+	time_limit: Option<Duration>,
+}
+
+impl SubprocessPlayer {
+	pub fn spawn(seat: Seat, name: &str, program: &str, args: &[String]) -> Result<Self, PokerError> {
+		let mut child = Command::new(program)
+			.args(args)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.spawn()
+			.map_err(|e| PokerError::io("start", program, e))?;
+
+		let log_name = format!("Bot:{}", name);
+		let stdin = child.stdin.take().expect("piped stdin");
+		let stdout = child.stdout.take().expect("piped stdout");
+		let link = BotLink::new(log_name.clone(), stdout, stdin);
+
+		let stderr = child.stderr.take().expect("piped stderr");
+		thread::spawn(move || {
+			for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+				logging::log(&log_name, "STDERR", &line);
+			}
+		});
+
+		Ok(Self {
+			seat,
+			name: name.to_string(),
+			child: Mutex::new(child),
+			link,
+			time_limit: Some(DEFAULT_BOT_TIME_LIMIT),
+		})
+	}
+
+	/// `None` waits as long as the table's own action timeout allows.
+	pub fn with_time_limit(mut self, limit: Option<Duration>) -> Self {
+		self.time_limit = limit;
+		self
+	}
+
+	pub fn is_alive(&self) -> bool {
+		self.link.is_alive()
+	}
+
+	/// Passes on an event from the game's event stream, as this seat may
+	/// see it: other players' hole cards stay hidden.
+	pub fn observe(&self, event: &GameEvent) {
+		if let Some(seen) = redact(event, Audience::Player(self.seat)) {
+			self.link.notify(&seen);
+		}
+	}
+}
+
+#[async_trait]
+impl PlayerPort for SubprocessPlayer {
+	async fn request_action(
+		&self,
+		seat: Seat,
+		valid_actions: ValidActions,
+		game_state: &GameSnapshot,
+	) -> PlayerResponse {
+		self.link
+			.request_action(seat, &valid_actions, game_state, self.time_limit)
+			.await
+	}
+
+	/// The runner only notifies a few events; the bot gets the whole
+	/// stream through `observe` instead.
+	fn notify(&self, _event: &GameEvent) {}
+
+	fn seat(&self) -> Seat {
+		self.seat
+	}
+
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	fn is_human(&self) -> bool {
+		false
+	}
+}
+
+impl Drop for SubprocessPlayer {
+	fn drop(&mut self) {
+		if let Ok(child) = self.child.get_mut() {
+			let _ = child.kill();
+			let _ = child.wait();
+		}
+	}
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+	use super::*;
+	use crate::events::{Blinds, Card, PlayerAction, Street};
+
+	fn shell(seat: Seat, script: &str) -> SubprocessPlayer {
+		SubprocessPlayer::spawn(seat, "Bot", "sh", &["-c".to_string(), script.to_string()]).unwrap()
+	}
+
+	fn ask(player: &SubprocessPlayer) -> PlayerResponse {
+		let valid = ValidActions {
+			can_fold: true,
+			can_check: false,
+			call_amount: Some(10.0),
+			raise_options: None,
+			can_all_in: true,
+			all_in_amount: 100.0,
+			can_show: false,
+		};
+		let snapshot = GameSnapshot {
+			hand_num: 1,
+			street: Street::Preflop,
+			board: vec![],
+			pot: 15.0,
+			pots: vec![],
+			effective_stack: 100.0,
+			seats: vec![],
+			hero_seat: player.seat(),
+			hero_cards: None,
+			button: Seat(0),
+			blinds: Blinds { small: 5.0, big: 10.0, ante: None },
+			current_bet: 10.0,
+			min_raise: 10.0,
+			raises_this_street: 0,
+			max_raises: 4,
+			valid_actions: valid.clone(),
+			action_history: vec![],
+		};
+		tokio::runtime::Builder::new_current_thread()
+			.enable_time()
+			.build()
+			.unwrap()
+			.block_on(player.request_action(player.seat(), valid, &snapshot))
+	}
+
+	#[test]
+	fn test_reads_action_from_stdout() {
+		let player = shell(
+			Seat(0),
+			r#"while read line; do case "$line" in *action_request*) echo '{"Call":{"amount":10}}';; esac; done"#,
+		);
+		player.observe(&GameEvent::ChatMessage {
+			sender: crate::events::ChatSender::Dealer,
+			text: "Shuffle up and deal".to_string(),
+		});
+		assert!(matches!(ask(&player), PlayerResponse::Action(PlayerAction::Call { .. })));
+	}
+
+	#[test]
+	fn test_observes_only_what_its_seat_may_see() {
+		// Calls only if it saw its own cards and not the other seat's
+		let player = shell(
+			Seat(1),
+			r#"own=; peek=
+			while read line; do
+				case "$line" in *'"rank":"A"'*) peek=1;; *'"rank":"2"'*) own=1;; esac
+				case "$line" in *action_request*)
+					if [ -n "$own" ] && [ -z "$peek" ]; then echo '{"Call":{"amount":10}}'; else echo '"Fold"'; fi;;
+				esac
+			done"#,
+		);
+		let dealt = |seat, cards| GameEvent::HoleCardsDealt { seat: Seat(seat), cards };
+		player.observe(&dealt(0, [Card::new('A', 'h'), Card::new('K', 'h')]));
+		player.observe(&dealt(1, [Card::new('2', 'c'), Card::new('3', 'c')]));
+		assert!(matches!(ask(&player), PlayerResponse::Action(PlayerAction::Call { .. })));
+	}
+
+	#[test]
+	fn test_slow_bot_times_out() {
+		let player = shell(Seat(0), "sleep 5").with_time_limit(Some(Duration::from_millis(100)));
+		assert!(matches!(ask(&player), PlayerResponse::Timeout));
+	}
+
+	#[test]
+	fn test_exited_bot_is_not_asked_again() {
+		let player = shell(Seat(0), "exit 1");
+		assert!(matches!(ask(&player), PlayerResponse::Timeout));
+		assert!(!player.is_alive());
+		assert!(matches!(ask(&player), PlayerResponse::Timeout));
+	}
+
+	#[test]
+	fn test_garbage_reply_times_out() {
+		let player = shell(Seat(0), "read line; echo raise");
+		assert!(matches!(ask(&player), PlayerResponse::Timeout));
+	}
+}
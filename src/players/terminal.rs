@@ -1,6 +1,7 @@
 use std::sync::mpsc::{self, Receiver, Sender};
 
 use async_trait::async_trait;
+use tokio::sync::oneshot;
 use crate::events::{GameEvent, Seat, ValidActions};
 use crate::players::{GameSnapshot, PlayerPort, PlayerResponse};
 
@@ -14,7 +15,7 @@ pub struct ActionRequest {
 	pub seat: Seat,
 	pub valid_actions: ValidActions,
 	pub snapshot: GameSnapshot,
-	pub response_tx: Sender<PlayerResponse>,
+	pub response_tx: oneshot::Sender<PlayerResponse>,
 }
 
 pub struct TerminalPlayerHandle {
@@ -45,7 +46,7 @@ impl PlayerPort for TerminalPlayer {
 		valid_actions: ValidActions,
 		game_state: &GameSnapshot,
 	) -> PlayerResponse {
-		let (response_tx, response_rx) = mpsc::channel();
+		let (response_tx, response_rx) = oneshot::channel();
 
 		let request = ActionRequest {
 			seat,
@@ -56,7 +57,7 @@ impl PlayerPort for TerminalPlayer {
 
 		let _ = self.action_tx.send(request);
 
-		response_rx.recv().unwrap_or(PlayerResponse::Timeout)
+		response_rx.await.unwrap_or(PlayerResponse::Timeout)
 	}
 
 	fn notify(&self, _event: &GameEvent) {}
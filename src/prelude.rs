@@ -0,0 +1,27 @@
+//! Types needed to embed the engine: build a `Game`, seat players, and read
+//! the events it produces. Nothing here pulls in the TUI or networking.
+//!
+//! ```
+//! use transparent_poker::prelude::*;
+//! ```
+
+pub use async_trait::async_trait;
+
+#[cfg(feature = "runtime")]
+pub use crate::game::{Game, GameBuilder, RunningGame};
+
+pub use crate::error::PokerError;
+
+pub use crate::events::{
+	BlindType, Card, GameEndReason, GameEvent, GameId, HandId, HandResult, PlayerAction, Position,
+	PotType, RaiseOptions, Seat, Standing, Street, ValidActions,
+};
+
+pub use crate::players::{
+	ActionRecord, CallingPlayer, FoldingPlayer, GameSnapshot, PlayerPort, PlayerResponse, RulesPlayer,
+	SeatSnapshot, TestPlayer,
+};
+
+pub use crate::strategy::{Strategy, StrategyStore};
+
+pub use crate::table::{BettingStructure, GameFormat, TableConfig};
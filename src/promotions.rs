@@ -0,0 +1,337 @@
+//! House promotions, for modelling what a card room gives back: rakeback,
+//! a share of the rake each player paid, banked hand by hand and paid out
+//! weekly; and a high-hand bonus for a big enough hand at showdown. The
+//! server runs each finished hand through `Promotions::hand` and credits
+//! the bank with what comes out.
+//!
+//! Rake is split among everyone dealt in ("dealt" rakeback), since a hand
+//! history knows what each player lost or won but not what they put in.
+//!
+//! The bad-beat jackpot is different: its drop comes off the pot, so the
+//! engine takes it alongside the rake and spots the bad beat at showdown
+//! (`GameEvent::JackpotDrop`, `GameEvent::BadBeatJackpot`). The pool
+//! lives in the bank; `JackpotConfig::split` says who gets what of it.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use rs_poker::core::Rank;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::rank_hand;
+use crate::events::{PlayerAction, Seat};
+use crate::history::HandHistory;
+
+/// A table's promotions, `[tables.promotions]` in `tables.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PromotionsConfig {
+	/// Share of the rake paid that comes back each week, 0 to 1.
+	#[serde(default)]
+	pub rakeback: f32,
+	#[serde(default)]
+	pub high_hand: Option<HighHandConfig>,
+	#[serde(default)]
+	pub jackpot: Option<JackpotConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct HighHandConfig {
+	/// The least hand that pays.
+	pub at_least: HandCategory,
+	/// Paid to the best qualifying hand, split on a tie.
+	pub bonus: f32,
+}
+
+/// A bad-beat jackpot: a drop from every qualifying pot builds a pool,
+/// paid out when a big enough hand loses at showdown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct JackpotConfig {
+	/// Taken from each qualifying pot.
+	pub drop: f32,
+	/// Called chips a pot needs to qualify; pots never qualify before the
+	/// flop.
+	#[serde(default)]
+	pub min_pot: f32,
+	/// The least losing hand that hits the jackpot.
+	pub at_least: HandCategory,
+	#[serde(default)]
+	pub shares: JackpotShares,
+	/// Tables naming the same pool share it.
+	#[serde(default = "default_pool")]
+	pub pool: String,
+}
+
+fn default_pool() -> String {
+	"main".to_string()
+}
+
+/// How a jackpot splits. Whatever the shares don't add up to stays in
+/// the pool to seed the next one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct JackpotShares {
+	/// The player whose hand lost.
+	pub loser: f32,
+	/// The player who beat it, split if the pot was.
+	pub winner: f32,
+	/// Split among everyone else dealt in.
+	pub table: f32,
+}
+
+impl Default for JackpotShares {
+	fn default() -> Self {
+		Self { loser: 0.5, winner: 0.25, table: 0.25 }
+	}
+}
+
+impl JackpotConfig {
+	/// The drop from a pot with `called` chips in it.
+	pub fn drop_for(&self, called: f32, saw_flop: bool) -> f32 {
+		if !saw_flop || called < self.min_pot.max(self.drop) {
+			return 0.0;
+		}
+		(self.drop * 100.0).round() / 100.0
+	}
+
+	/// Whether losing with `rank` hits the jackpot.
+	pub fn qualifies(&self, rank: &Rank) -> bool {
+		HandCategory::of(rank) >= self.at_least
+	}
+
+	/// Who gets what of a `pool`, floored to the cent. `dealt_in` is
+	/// everyone in the hand, loser and winners included; with nobody else
+	/// there the table share stays in the pool.
+	pub fn split(&self, pool: f32, loser: Seat, winners: &[Seat], dealt_in: &[Seat]) -> Vec<(Seat, f32)> {
+		let cents = |amount: f32| (amount * 100.0).floor() / 100.0;
+		let others = dealt_in
+			.iter()
+			.filter(|s| **s != loser && !winners.contains(s))
+			.copied()
+			.collect::<Vec<_>>();
+		let mut paid = vec![(loser, cents(pool * self.shares.loser))];
+		if !winners.is_empty() {
+			let each = cents(pool * self.shares.winner / winners.len() as f32);
+			paid.extend(winners.iter().map(|&seat| (seat, each)));
+		}
+		if !others.is_empty() {
+			let each = cents(pool * self.shares.table / others.len() as f32);
+			paid.extend(others.into_iter().map(|seat| (seat, each)));
+		}
+		paid.retain(|(_, amount)| *amount > 0.0);
+		paid
+	}
+}
+
+/// Hand categories, weakest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum HandCategory {
+	HighCard,
+	OnePair,
+	TwoPair,
+	ThreeOfAKind,
+	Straight,
+	Flush,
+	FullHouse,
+	FourOfAKind,
+	StraightFlush,
+}
+
+impl HandCategory {
+	pub fn of(rank: &Rank) -> Self {
+		match rank {
+			Rank::HighCard(_) => HandCategory::HighCard,
+			Rank::OnePair(_) => HandCategory::OnePair,
+			Rank::TwoPair(_) => HandCategory::TwoPair,
+			Rank::ThreeOfAKind(_) => HandCategory::ThreeOfAKind,
+			Rank::Straight(_) => HandCategory::Straight,
+			Rank::Flush(_) => HandCategory::Flush,
+			Rank::FullHouse(_) => HandCategory::FullHouse,
+			Rank::FourOfAKind(_) => HandCategory::FourOfAKind,
+			Rank::StraightFlush(_) => HandCategory::StraightFlush,
+		}
+	}
+}
+
+impl fmt::Display for HandCategory {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			HandCategory::HighCard => "high card",
+			HandCategory::OnePair => "one pair",
+			HandCategory::TwoPair => "two pair",
+			HandCategory::ThreeOfAKind => "three of a kind",
+			HandCategory::Straight => "a straight",
+			HandCategory::Flush => "a flush",
+			HandCategory::FullHouse => "a full house",
+			HandCategory::FourOfAKind => "four of a kind",
+			HandCategory::StraightFlush => "a straight flush",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+/// What one hand earned.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HandPromotions {
+	/// Rakeback earned toward the weekly payout, by seat.
+	pub rakeback: Vec<(Seat, f32)>,
+	/// High-hand bonuses to pay now: seat, amount and the hand.
+	pub high_hand: Vec<(Seat, f32, HandCategory)>,
+}
+
+pub struct Promotions {
+	config: PromotionsConfig,
+}
+
+impl Promotions {
+	pub fn new(config: PromotionsConfig) -> Self {
+		Self { config }
+	}
+
+	pub fn hand(&self, hand: &HandHistory) -> HandPromotions {
+		HandPromotions {
+			rakeback: self.rakeback(hand),
+			high_hand: self.high_hand(hand),
+		}
+	}
+
+	/// The hand's rake, whatever went in and didn't come back out, shared
+	/// among everyone dealt in.
+	fn rakeback(&self, hand: &HandHistory) -> Vec<(Seat, f32)> {
+		let rake = -hand.players.iter().map(|p| p.net).sum::<f32>();
+		if self.config.rakeback <= 0.0 || rake < 0.01 || hand.players.is_empty() {
+			return Vec::new();
+		}
+		let share = rake * self.config.rakeback / hand.players.len() as f32;
+		hand.players.iter().map(|p| (p.seat, share)).collect()
+	}
+
+	/// The best hand at a showdown on a full board, if it's good enough.
+	fn high_hand(&self, hand: &HandHistory) -> Vec<(Seat, f32, HandCategory)> {
+		let Some(config) = &self.config.high_hand else {
+			return Vec::new();
+		};
+		let folded = hand
+			.actions
+			.iter()
+			.filter(|a| matches!(a.action, PlayerAction::Fold))
+			.map(|a| a.seat)
+			.collect::<HashSet<_>>();
+		let shown = hand
+			.players
+			.iter()
+			.filter(|p| !folded.contains(&p.seat))
+			.filter_map(|p| Some((p.seat, rank_hand(&p.hole_cards?, &hand.board))))
+			.collect::<Vec<_>>();
+		if hand.board.len() < 5 || shown.len() < 2 {
+			return Vec::new();
+		}
+		let Some(best) = shown.iter().map(|(_, rank)| *rank).max() else {
+			return Vec::new();
+		};
+		let category = HandCategory::of(&best);
+		if category < config.at_least {
+			return Vec::new();
+		}
+		let winners = shown.iter().filter(|(_, rank)| *rank == best).map(|(seat, _)| *seat).collect::<Vec<_>>();
+		let share = ((config.bonus / winners.len() as f32) * 100.0).floor() / 100.0;
+		winners.into_iter().map(|seat| (seat, share, category)).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{cards, Blinds, Street};
+	use crate::history::{test_action, test_hand, test_player, HandPlayer};
+
+	fn player(seat: usize, hole: &str, net: f32) -> HandPlayer {
+		HandPlayer { stack: 200.0, hole_cards: cards(hole).try_into().ok(), net, ..test_player(seat, &format!("P{}", seat)) }
+	}
+
+	/// Quads beat a full house on the river; 5 of the 100 pot went to rake.
+	fn quads() -> HandHistory {
+		HandHistory {
+			blinds: Blinds { small: 1.0, big: 2.0, ante: None },
+			players: vec![player(0, "9s 9h", 45.0), player(1, "Kd Kc", -50.0), player(2, "2c 3d", 0.0)],
+			actions: vec![test_action(Street::Preflop, 2, PlayerAction::Fold)],
+			board: cards("9c 9d Ks 4h 2s"),
+			..test_hand()
+		}
+	}
+
+	#[test]
+	fn test_rakeback_shared_among_those_dealt_in() {
+		let promotions = Promotions::new(PromotionsConfig { rakeback: 0.3, high_hand: None, jackpot: None });
+		let earned = promotions.hand(&quads());
+		assert_eq!(earned.rakeback.len(), 3);
+		assert!(earned.rakeback.iter().all(|(_, amount)| (amount - 0.5).abs() < 1e-4), "{:?}", earned.rakeback);
+		assert!(earned.high_hand.is_empty());
+	}
+
+	#[test]
+	fn test_high_hand_pays_the_best_shown_hand() {
+		let high_hand = |at_least| PromotionsConfig {
+			rakeback: 0.0,
+			high_hand: Some(HighHandConfig { at_least, bonus: 100.0 }),
+			jackpot: None,
+		};
+		let earned = Promotions::new(high_hand(HandCategory::FullHouse)).hand(&quads());
+		assert_eq!(earned.high_hand, [(Seat(0), 100.0, HandCategory::FourOfAKind)]);
+		assert!(earned.rakeback.is_empty());
+
+		let earned = Promotions::new(high_hand(HandCategory::StraightFlush)).hand(&quads());
+		assert!(earned.high_hand.is_empty());
+
+		// No showdown, no bonus
+		let mut folded = quads();
+		folded.actions.push(test_action(Street::River, 1, PlayerAction::Fold));
+		assert!(Promotions::new(high_hand(HandCategory::FullHouse)).hand(&folded).high_hand.is_empty());
+	}
+
+	#[test]
+	fn test_table_config_parses() {
+		let config: PromotionsConfig = toml::from_str(
+			r#"
+			rakeback = 0.25
+			high_hand = { at_least = "four-of-a-kind", bonus = 50.0 }
+			"#,
+		)
+		.unwrap();
+		assert_eq!(config.high_hand.unwrap().at_least, HandCategory::FourOfAKind);
+		assert!(config.jackpot.is_none());
+
+		let config: PromotionsConfig = toml::from_str(
+			r#"
+			jackpot = { drop = 1.0, min_pot = 20.0, at_least = "full-house", shares = { loser = 0.6, winner = 0.4, table = 0.0 } }
+			"#,
+		)
+		.unwrap();
+		let jackpot = config.jackpot.unwrap();
+		assert_eq!(jackpot.pool, "main");
+		assert_eq!(jackpot.shares, JackpotShares { loser: 0.6, winner: 0.4, table: 0.0 });
+	}
+
+	#[test]
+	fn test_jackpot_drop_and_split() {
+		let jackpot = JackpotConfig {
+			drop: 1.0,
+			min_pot: 20.0,
+			at_least: HandCategory::FullHouse,
+			shares: JackpotShares::default(),
+			pool: default_pool(),
+		};
+		assert_eq!(jackpot.drop_for(40.0, true), 1.0);
+		assert_eq!(jackpot.drop_for(40.0, false), 0.0);
+		assert_eq!(jackpot.drop_for(10.0, true), 0.0);
+
+		let dealt_in = [Seat(0), Seat(1), Seat(2), Seat(4)];
+		let paid = jackpot.split(1000.0, Seat(1), &[Seat(4)], &dealt_in);
+		assert_eq!(paid, [(Seat(1), 500.0), (Seat(4), 250.0), (Seat(0), 125.0), (Seat(2), 125.0)]);
+
+		// Heads up, the table's share stays in the pool
+		let paid = jackpot.split(100.0, Seat(0), &[Seat(1)], &[Seat(0), Seat(1)]);
+		assert_eq!(paid.iter().map(|(_, a)| a).sum::<f32>(), 75.0);
+	}
+}
@@ -1,12 +1,14 @@
 use std::fs;
 use std::path::Path;
+use crate::error::PokerError;
 use crate::view::TableView;
 
-pub fn load_scenario<P: AsRef<Path>>(path: P) -> Result<TableView, String> {
+pub fn load_scenario<P: AsRef<Path>>(path: P) -> Result<TableView, PokerError> {
+	let path = path.as_ref();
 	let content = fs::read_to_string(path)
-		.map_err(|e| format!("Failed to read file: {}", e))?;
+		.map_err(|e| PokerError::io("read", path, e))?;
 	toml::from_str(&content)
-		.map_err(|e| format!("Failed to parse TOML: {}", e))
+		.map_err(|e| PokerError::parse(path.display().to_string(), e))
 }
 
 pub fn load_scenarios_from_dir<P: AsRef<Path>>(dir: P) -> Vec<TableView> {
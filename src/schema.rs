@@ -0,0 +1,73 @@
+//! JSON Schemas for everything that crosses a process boundary, generated
+//! from the Rust types. `poker schema` writes them out; the copies in
+//! `docs/schema/` are checked against the types by the tests below, so a
+//! change to the wire format can't go unnoticed.
+
+use std::fs;
+use std::path::Path;
+
+use schemars::{schema_for, Schema};
+
+use crate::events::{GameEvent, PlayerAction};
+use crate::feed::FeedState;
+use crate::history::HandHistory;
+use crate::net::protocol::{ArenaMessage, ArenaRequest, ClientMessage, ServerMessage};
+use crate::players::BotMessage;
+
+/// File name and schema for each message type.
+pub fn schemas() -> Vec<(&'static str, Schema)> {
+	vec![
+		("game_event.schema.json", schema_for!(GameEvent)),
+		("client_message.schema.json", schema_for!(ClientMessage)),
+		("server_message.schema.json", schema_for!(ServerMessage)),
+		("player_action.schema.json", schema_for!(PlayerAction)),
+		("bot_message.schema.json", schema_for!(BotMessage<'static>)),
+		("arena_request.schema.json", schema_for!(ArenaRequest)),
+		("arena_message.schema.json", schema_for!(ArenaMessage)),
+		("observer_feed.schema.json", schema_for!(FeedState)),
+		("hand_history.schema.json", schema_for!(HandHistory)),
+	]
+}
+
+pub fn render(schema: &Schema) -> String {
+	let mut json = serde_json::to_string_pretty(schema).expect("Schemas always serialize");
+	json.push('\n');
+	json
+}
+
+/// Writes every schema into `dir` and returns the paths written.
+pub fn write_all(dir: &Path) -> std::io::Result<Vec<String>> {
+	fs::create_dir_all(dir)?;
+	let mut written = Vec::new();
+	for (name, schema) in schemas() {
+		let path = dir.join(name);
+		fs::write(&path, render(&schema))?;
+		written.push(path.display().to_string());
+	}
+	Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_checked_in_schemas_match_types() {
+		let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("docs/schema");
+		for (name, schema) in schemas() {
+			let on_disk = fs::read_to_string(dir.join(name)).unwrap_or_default();
+			assert!(
+				on_disk == render(&schema),
+				"docs/schema/{} is out of date; run `cargo run --bin poker -- schema`",
+				name
+			);
+		}
+	}
+
+	#[test]
+	fn test_event_schema_names_variants() {
+		let json = render(&schema_for!(GameEvent));
+		assert!(json.contains("\"HoleCardsDealt\""));
+		assert!(json.contains("\"hand_num\""));
+	}
+}
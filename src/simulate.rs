@@ -0,0 +1,215 @@
+//! Strategies played against each other for many hands, to see which
+//! comes out ahead. Every hand starts from fresh stacks.
+//!
+//! In duplicate mode each deck is dealt once per seating, with the
+//! strategies moved one seat round the table each time, so every strategy
+//! plays every seat's cards from the same deck and the luck of the deal
+//! cancels out. Far fewer hands are needed to tell two bots apart.
+
+use std::sync::Arc;
+
+use crate::engine::{Session, SessionConfig};
+use crate::error::PokerError;
+use crate::events::{Card, Seat};
+use crate::players::RulesPlayer;
+use crate::strategy::StrategyStore;
+use crate::table::BettingStructure;
+
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+	/// Strategy ids, one seat each.
+	pub strategies: Vec<String>,
+	/// Decks to deal. In duplicate mode each is played once per strategy.
+	pub deals: u32,
+	pub duplicate: bool,
+	pub seed: u64,
+	pub small_blind: f32,
+	pub big_blind: f32,
+	pub starting_stack: f32,
+	pub betting: BettingStructure,
+}
+
+impl Default for SimulationConfig {
+	fn default() -> Self {
+		Self {
+			strategies: Vec::new(),
+			deals: 1000,
+			duplicate: false,
+			seed: 0,
+			small_blind: 5.0,
+			big_blind: 10.0,
+			starting_stack: 1000.0,
+			betting: BettingStructure::NoLimit,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct StrategyResult {
+	pub name: String,
+	pub hands: u32,
+	/// Chips won or lost over every hand.
+	pub net: f32,
+	pub bb_per_100: f32,
+	/// Standard error of `bb_per_100`, from the spread between deals.
+	pub std_error: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+	pub deals: u32,
+	pub duplicate: bool,
+	/// Best first.
+	pub results: Vec<StrategyResult>,
+}
+
+const MAX_STRATEGIES: usize = 10;
+
+pub fn simulate(config: &SimulationConfig, store: &StrategyStore) -> Result<SimulationReport, PokerError> {
+	let n = config.strategies.len();
+	if !(2..=MAX_STRATEGIES).contains(&n) {
+		return Err(PokerError::Config(format!("Name 2 to {} strategies, got {}", MAX_STRATEGIES, n)));
+	}
+	if config.deals == 0 {
+		return Err(PokerError::Config("Deal at least one hand".to_string()));
+	}
+	let strategies = config
+		.strategies
+		.iter()
+		.map(|id| store.get(id).cloned().ok_or_else(|| PokerError::NotFound(format!("Unknown strategy '{}'", id))))
+		.collect::<Result<Vec<_>, _>>()?;
+	let names = config
+		.strategies
+		.iter()
+		.enumerate()
+		.map(|(i, id)| match config.strategies[..i].iter().filter(|other| *other == id).count() {
+			0 => id.clone(),
+			before => format!("{} {}", id, before + 1),
+		})
+		.collect::<Vec<_>>();
+
+	let seatings = if config.duplicate { n } else { 1 };
+	// Each strategy's result per deal, in big blinds
+	let mut per_deal = vec![Vec::with_capacity(config.deals as usize); n];
+	for deal in 0..config.deals {
+		let mut deck: Option<Vec<Card>> = None;
+		let mut totals = vec![0.0; n];
+		for seating in 0..seatings {
+			// Strategy i sits `shift` seats round from seat i
+			let shift = if config.duplicate { seating } else { deal as usize % n };
+			let strategy_at = |seat: usize| (seat + n - shift) % n;
+
+			let mut session = Session::new(SessionConfig {
+				small_blind: config.small_blind,
+				big_blind: config.big_blind,
+				starting_stack: config.starting_stack,
+				betting: config.betting,
+				max_hands: Some(1),
+				seed: config.seed.wrapping_add(deal as u64),
+				..SessionConfig::default()
+			});
+			for seat in 0..n {
+				let i = strategy_at(seat);
+				session.add_player(Arc::new(RulesPlayer::new(Seat(seat), &names[i], strategies[i].clone())))?;
+			}
+			if let Some(cards) = deck.clone() {
+				session.deal_deck(cards)?;
+			}
+			session.run_until_blocked();
+			deck = session.last_deck().map(<[Card]>::to_vec);
+
+			for (seat, stack) in session.stacks().iter().enumerate() {
+				totals[strategy_at(seat)] += (stack - config.starting_stack) / config.big_blind;
+			}
+		}
+		for (i, total) in totals.into_iter().enumerate() {
+			per_deal[i].push(total);
+		}
+	}
+
+	let hands = config.deals * seatings as u32;
+	let mut results = names
+		.into_iter()
+		.zip(per_deal)
+		.map(|(name, deals)| {
+			let count = deals.len() as f32;
+			let mean = deals.iter().sum::<f32>() / count;
+			let variance = if deals.len() > 1 {
+				deals.iter().map(|d| (d - mean).powi(2)).sum::<f32>() / (count - 1.0)
+			} else {
+				0.0
+			};
+			StrategyResult {
+				name,
+				hands,
+				net: mean * count * config.big_blind,
+				bb_per_100: 100.0 * mean / seatings as f32,
+				std_error: 100.0 * (variance / count).sqrt() / seatings as f32,
+			}
+		})
+		.collect::<Vec<_>>();
+	results.sort_by(|a, b| b.bb_per_100.total_cmp(&a.bb_per_100));
+
+	Ok(SimulationReport { deals: config.deals, duplicate: config.duplicate, results })
+}
+
+impl SimulationReport {
+	pub fn lines(&self) -> Vec<String> {
+		let hands = self.results.first().map(|r| r.hands).unwrap_or(0);
+		let mut lines = vec![if self.duplicate {
+			format!("Duplicate: {} decks, each dealt {} times ({} hands)", self.deals, hands / self.deals.max(1), hands)
+		} else {
+			format!("{} hands", hands)
+		}];
+		lines.push(String::new());
+		lines.push(format!("{:<16} {:>8} {:>10} {:>9} {:>8}", "Strategy", "Hands", "Net", "BB/100", "±"));
+		lines.push("-".repeat(55));
+		for r in &self.results {
+			lines.push(format!(
+				"{:<16} {:>8} {:>+10.0} {:>+9.1} {:>8.1}",
+				r.name, r.hands, r.net, r.bb_per_100, r.std_error
+			));
+		}
+		lines
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn store() -> StrategyStore {
+		StrategyStore::from_toml(include_str!("../config/strategies.toml")).unwrap()
+	}
+
+	#[test]
+	fn test_duplicate_plays_every_deck_from_every_seat() {
+		let config = SimulationConfig {
+			strategies: vec!["rock".to_string(), "maniac".to_string(), "rock".to_string()],
+			deals: 8,
+			duplicate: true,
+			seed: 11,
+			..SimulationConfig::default()
+		};
+		let report = simulate(&config, &store()).unwrap();
+		assert_eq!(report.results.len(), 3);
+		assert!(report.results.iter().all(|r| r.hands == 24));
+		assert!(report.results.iter().any(|r| r.name == "rock 2"));
+		// No rake, so whatever one strategy wins another lost
+		let net: f32 = report.results.iter().map(|r| r.net).sum();
+		assert!(net.abs() < 0.01, "{}", net);
+		assert!(report.lines()[0].starts_with("Duplicate: 8 decks, each dealt 3 times"));
+	}
+
+	#[test]
+	fn test_rejects_unknown_strategies_and_lone_players() {
+		let config = |strategies: &[&str]| SimulationConfig {
+			strategies: strategies.iter().map(|s| s.to_string()).collect(),
+			deals: 1,
+			..SimulationConfig::default()
+		};
+		assert!(simulate(&config(&["rock"]), &store()).is_err());
+		assert!(simulate(&config(&["rock", "nobody"]), &store()).unwrap_err().to_string().contains("nobody"));
+		assert_eq!(simulate(&config(&["rock", "lag"]), &store()).unwrap().results[0].hands, 1);
+	}
+}
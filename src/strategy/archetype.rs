@@ -1,9 +1,11 @@
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use serde::Deserialize;
+use crate::error::PokerError;
 use super::hand_group::HandGroup;
-use super::position::Position;
+use super::position::{Position, TableSize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -26,6 +28,14 @@ impl Aggression {
 			Aggression::Extreme => 0.9,
 		}
 	}
+
+	/// `steps` more aggressive, up to `Extreme`.
+	pub fn stepped(self, steps: u8) -> Self {
+		const ORDER: [Aggression; 5] =
+			[Aggression::Low, Aggression::Medium, Aggression::High, Aggression::VeryHigh, Aggression::Extreme];
+		let at = ORDER.iter().position(|&a| a == self).unwrap_or(0);
+		ORDER[(at + steps as usize).min(ORDER.len() - 1)]
+	}
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
@@ -49,6 +59,19 @@ impl BluffFrequency {
 			BluffFrequency::VeryHigh => 0.6,
 		}
 	}
+
+	/// `steps` more bluffs, up to `VeryHigh`.
+	pub fn stepped(self, steps: u8) -> Self {
+		const ORDER: [BluffFrequency; 5] = [
+			BluffFrequency::None,
+			BluffFrequency::Low,
+			BluffFrequency::Medium,
+			BluffFrequency::High,
+			BluffFrequency::VeryHigh,
+		];
+		let at = ORDER.iter().position(|&b| b == self).unwrap_or(0);
+		ORDER[(at + steps as usize).min(ORDER.len() - 1)]
+	}
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
@@ -74,6 +97,67 @@ impl FoldToAggression {
 	}
 }
 
+/// How an AI talks at the table after a memorable hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatPersona {
+	#[default]
+	Quiet,
+	Friendly,
+	Needler,
+}
+
+impl ChatPersona {
+	/// Chance of saying something when there is something to say.
+	pub fn chattiness(&self) -> f32 {
+		match self {
+			ChatPersona::Quiet => 0.2,
+			ChatPersona::Friendly => 0.5,
+			ChatPersona::Needler => 0.6,
+		}
+	}
+}
+
+/// How long an AI takes over its decisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tempo {
+	Snappy,
+	#[default]
+	Steady,
+	Deliberate,
+}
+
+impl Tempo {
+	/// Scale applied to the table's action delay.
+	pub fn multiplier(&self) -> f32 {
+		match self {
+			Tempo::Snappy => 0.6,
+			Tempo::Steady => 1.0,
+			Tempo::Deliberate => 1.6,
+		}
+	}
+}
+
+/// How a strategy's players tilt, from `[<strategy>.tilt]`. A loss of
+/// `big_pot` big blinds or more sets them off; at full tilt they play
+/// `aggression` and `bluff` steps wilder, and each hand after keeps
+/// `decay` of the tilt.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TiltConfig {
+	pub big_pot: f32,
+	pub aggression: u8,
+	pub bluff: u8,
+	pub decay: f32,
+}
+
+impl Default for TiltConfig {
+	fn default() -> Self {
+		Self { big_pot: 40.0, aggression: 1, bluff: 2, decay: 0.6 }
+	}
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct StrategyConfig {
 	name: String,
@@ -94,6 +178,16 @@ struct StrategyConfig {
 	continuation_bet: f32,
 	#[serde(default)]
 	fold_to_aggression: FoldToAggression,
+	#[serde(default)]
+	check_raise_frequency: f32,
+	#[serde(default)]
+	river_block_size: f32,
+	#[serde(default)]
+	tilt: Option<TiltConfig>,
+	#[serde(default)]
+	chat_persona: ChatPersona,
+	#[serde(default)]
+	tempo: Tempo,
 }
 
 fn default_cbet() -> f32 {
@@ -117,6 +211,14 @@ pub struct Strategy {
 	pub bluff_frequency: BluffFrequency,
 	pub continuation_bet: f32,
 	pub fold_to_aggression: FoldToAggression,
+	/// Chance of check-raising two pair or better when bet into.
+	pub check_raise_frequency: f32,
+	/// River lead with one pair, as a fraction of the pot; 0.0 never leads.
+	pub river_block_size: f32,
+	/// `None` never tilts.
+	pub tilt: Option<TiltConfig>,
+	pub chat_persona: ChatPersona,
+	pub tempo: Tempo,
 }
 
 impl Strategy {
@@ -143,6 +245,11 @@ impl Strategy {
 			bluff_frequency: config.bluff_frequency,
 			continuation_bet: config.continuation_bet,
 			fold_to_aggression: config.fold_to_aggression,
+			check_raise_frequency: config.check_raise_frequency,
+			river_block_size: config.river_block_size,
+			tilt: config.tilt,
+			chat_persona: config.chat_persona,
+			tempo: config.tempo,
 		}
 	}
 
@@ -157,8 +264,25 @@ impl Strategy {
 		}
 	}
 
-	pub fn should_open(&self, hand_group: HandGroup, position: Position) -> bool {
-		self.opens_for_position(position).contains(&hand_group)
+	/// Whether to open `hand_group` from `position`, with the position's
+	/// range widened for the table's size.
+	pub fn should_open(&self, hand_group: HandGroup, position: Position, table: TableSize) -> bool {
+		Self::in_range(self.opens_for_position(position), hand_group, table.widening(position))
+	}
+
+	/// The strategy played at `level` of full tilt: aggression and bluffs
+	/// stepped up by the tilt config's steps, scaled and rounded.
+	pub fn tilted(&self, level: f32) -> Cow<'_, Strategy> {
+		let Some(tilt) = self.tilt else { return Cow::Borrowed(self) };
+		let steps = |full: u8| (level.clamp(0.0, 1.0) * full as f32).round() as u8;
+		let (aggression, bluff) = (steps(tilt.aggression), steps(tilt.bluff));
+		if aggression == 0 && bluff == 0 {
+			return Cow::Borrowed(self);
+		}
+		let mut tilted = self.clone();
+		tilted.aggression = self.aggression.stepped(aggression);
+		tilted.bluff_frequency = self.bluff_frequency.stepped(bluff);
+		Cow::Owned(tilted)
 	}
 
 	pub fn should_three_bet(&self, hand_group: HandGroup) -> bool {
@@ -169,8 +293,15 @@ impl Strategy {
 		self.cold_call.contains(&hand_group)
 	}
 
-	pub fn should_defend_bb(&self, hand_group: HandGroup) -> bool {
-		self.defends_bb.contains(&hand_group)
+	pub fn should_defend_bb(&self, hand_group: HandGroup, table: TableSize) -> bool {
+		Self::in_range(&self.defends_bb, hand_group, table.widening(Position::Bb))
+	}
+
+	/// In `range`, or no more than `widening` groups looser than its
+	/// loosest. An empty range stays empty.
+	fn in_range(range: &HashSet<HandGroup>, hand_group: HandGroup, widening: usize) -> bool {
+		range.contains(&hand_group)
+			|| range.iter().max().is_some_and(|&loosest| hand_group <= loosest.widened(widening))
 	}
 }
 
@@ -192,6 +323,11 @@ impl Default for Strategy {
 			bluff_frequency: BluffFrequency::Low,
 			continuation_bet: 0.65,
 			fold_to_aggression: FoldToAggression::Medium,
+			check_raise_frequency: 0.2,
+			river_block_size: 0.3,
+			tilt: None,
+			chat_persona: ChatPersona::Quiet,
+			tempo: Tempo::Steady,
 		}
 	}
 }
@@ -201,12 +337,15 @@ pub struct StrategyStore {
 }
 
 impl StrategyStore {
-	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PokerError> {
 		let content = fs::read_to_string(&path)
-			.map_err(|e| format!("Failed to read {}: {}", path.as_ref().display(), e))?;
+			.map_err(|e| PokerError::io("read", path.as_ref(), e))?;
+		Self::from_toml(&content)
+	}
 
-		let configs: HashMap<String, StrategyConfig> = toml::from_str(&content)
-			.map_err(|e| format!("Failed to parse strategies: {}", e))?;
+	pub fn from_toml(content: &str) -> Result<Self, PokerError> {
+		let configs: HashMap<String, StrategyConfig> = toml::from_str(content)
+			.map_err(|e| PokerError::parse("strategies", e))?;
 
 		let strategies = configs.into_iter()
 			.map(|(id, config)| (id.clone(), Strategy::from_config(&id, config)))
@@ -279,9 +418,22 @@ mod tests {
 	#[test]
 	fn test_strategy_should_open() {
 		let strategy = Strategy::default();
-		assert!(strategy.should_open(HandGroup::Premium, Position::Utg));
-		assert!(!strategy.should_open(HandGroup::Speculative, Position::Utg));
-		assert!(strategy.should_open(HandGroup::Speculative, Position::Btn));
+		assert!(strategy.should_open(HandGroup::Premium, Position::Utg, TableSize::FullRing));
+		assert!(!strategy.should_open(HandGroup::Speculative, Position::Utg, TableSize::FullRing));
+		assert!(strategy.should_open(HandGroup::Speculative, Position::Btn, TableSize::FullRing));
+	}
+
+	#[test]
+	fn test_ranges_widen_at_smaller_tables() {
+		let strategy = Strategy::default();
+		assert!(!strategy.should_open(HandGroup::Playable, Position::Utg, TableSize::FullRing));
+		assert!(strategy.should_open(HandGroup::Solid, Position::Utg, TableSize::ShortHanded));
+		assert!(!strategy.should_open(HandGroup::Playable, Position::Utg, TableSize::ShortHanded));
+		assert!(!strategy.should_open(HandGroup::Marginal, Position::Btn, TableSize::ShortHanded));
+		assert!(strategy.should_open(HandGroup::Marginal, Position::Btn, TableSize::HeadsUp));
+		assert!(!strategy.should_open(HandGroup::Trash, Position::Btn, TableSize::HeadsUp));
+		assert!(!strategy.should_defend_bb(HandGroup::Speculative, TableSize::FullRing));
+		assert!(strategy.should_defend_bb(HandGroup::Speculative, TableSize::HeadsUp));
 	}
 
 	#[test]
@@ -304,4 +456,53 @@ mod tests {
 		let strategy = store.get_or_default("nonexistent");
 		assert_eq!(strategy.id, "default");
 	}
+
+	#[test]
+	fn test_bundled_strategies_have_personas() {
+		let store = StrategyStore::from_toml(include_str!("../../config/strategies.toml")).unwrap();
+		assert_eq!(store.get("maniac").unwrap().chat_persona, ChatPersona::Needler);
+		assert_eq!(store.get("calling_station").unwrap().chat_persona, ChatPersona::Friendly);
+		assert_eq!(store.get("rock").unwrap().chat_persona, ChatPersona::Quiet);
+		assert_eq!(store.get("nit").unwrap().tempo, Tempo::Deliberate);
+		assert_eq!(store.get("maniac").unwrap().tempo, Tempo::Snappy);
+	}
+
+	#[test]
+	fn test_tilted_steps_up_aggression_and_bluffs() {
+		let mut strategy = Strategy::default();
+		assert_eq!(strategy.tilted(1.0).aggression, Aggression::Medium);
+
+		strategy.tilt = Some(TiltConfig { aggression: 1, bluff: 2, ..TiltConfig::default() });
+		let full = strategy.tilted(1.0);
+		assert_eq!((full.aggression, full.bluff_frequency), (Aggression::High, BluffFrequency::High));
+		let half = strategy.tilted(0.5);
+		assert_eq!((half.aggression, half.bluff_frequency), (Aggression::High, BluffFrequency::Medium));
+		assert!(matches!(strategy.tilted(0.1), Cow::Borrowed(_)));
+		assert_eq!(Aggression::VeryHigh.stepped(3), Aggression::Extreme);
+	}
+
+	#[test]
+	fn test_river_lines_default_off() {
+		let store = StrategyStore::from_toml(r#"
+			[plain]
+			name = "Plain"
+			description = "No river lines"
+			opens_utg = ["Premium"]
+			opens_mp = ["Premium"]
+			opens_co = ["Premium"]
+			opens_btn = ["Premium"]
+			opens_sb = ["Premium"]
+			defends_bb = ["Premium"]
+			three_bet = ["Premium"]
+			cold_call = ["Premium"]
+		"#).unwrap();
+		let plain = store.get("plain").unwrap();
+		assert_eq!((plain.check_raise_frequency, plain.river_block_size), (0.0, 0.0));
+
+		let bundled = StrategyStore::from_toml(include_str!("../../config/strategies.toml")).unwrap();
+		assert!(bundled.get("rock").unwrap().check_raise_frequency > 0.0);
+		assert!(bundled.get("rock").unwrap().river_block_size > 0.0);
+		assert!(bundled.get("rock").unwrap().tilt.is_none());
+		assert_eq!(bundled.get("maniac").unwrap().tilt.map(|t| t.big_pot), Some(50.0));
+	}
 }
@@ -38,6 +38,22 @@ impl HandGroup {
 			_ => None,
 		}
 	}
+
+	/// The group `steps` looser than this one, stopping short of `Trash`.
+	pub fn widened(self, steps: usize) -> HandGroup {
+		const ORDER: [HandGroup; 6] = [
+			HandGroup::Premium,
+			HandGroup::Strong,
+			HandGroup::Solid,
+			HandGroup::Playable,
+			HandGroup::Speculative,
+			HandGroup::Marginal,
+		];
+		match ORDER.iter().position(|&g| g == self) {
+			Some(i) => ORDER[(i + steps).min(ORDER.len() - 1)],
+			None => self,
+		}
+	}
 }
 
 impl fmt::Display for HandGroup {
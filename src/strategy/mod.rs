@@ -2,6 +2,9 @@ mod archetype;
 mod hand_group;
 mod position;
 
-pub use archetype::{Aggression, BluffFrequency, FoldToAggression, Strategy, StrategyStore};
+pub use archetype::{
+	Aggression, BluffFrequency, ChatPersona, FoldToAggression, Strategy, StrategyStore, Tempo,
+	TiltConfig,
+};
 pub use hand_group::{char_to_rank, rank_to_char, HandGroup, HoleCards};
-pub use position::Position;
+pub use position::{Position, TableSize};
@@ -59,6 +59,40 @@ impl Position {
 	}
 }
 
+/// How many are dealt in, as far as preflop ranges go. Strategies write
+/// their ranges for a full ring; with fewer players each position is
+/// nearer the button and the blinds come round sooner, so the ranges
+/// widen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableSize {
+	HeadsUp,
+	/// Three to six players.
+	ShortHanded,
+	FullRing,
+}
+
+impl TableSize {
+	pub fn from_players(num_players: usize) -> Self {
+		match num_players {
+			0..=2 => TableSize::HeadsUp,
+			3..=6 => TableSize::ShortHanded,
+			_ => TableSize::FullRing,
+		}
+	}
+
+	/// Hand groups a full-ring range at `position` widens by.
+	pub fn widening(&self, position: Position) -> usize {
+		match (self, position) {
+			(TableSize::FullRing, _) => 0,
+			(TableSize::ShortHanded, Position::Utg | Position::Mp | Position::Co) => 1,
+			(TableSize::ShortHanded, _) => 0,
+			// The button is the small blind, and acts first preflop
+			(TableSize::HeadsUp, Position::Btn) => 2,
+			(TableSize::HeadsUp, _) => 1,
+		}
+	}
+}
+
 impl std::fmt::Display for Position {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		write!(f, "{}", self.name())
@@ -91,4 +125,15 @@ mod tests {
 		assert_eq!(Position::from_seat(4, 0, 6), Position::Mp);
 		assert_eq!(Position::from_seat(5, 0, 6), Position::Co);
 	}
+
+	#[test]
+	fn test_table_size() {
+		assert_eq!(TableSize::from_players(2), TableSize::HeadsUp);
+		assert_eq!(TableSize::from_players(6), TableSize::ShortHanded);
+		assert_eq!(TableSize::from_players(9), TableSize::FullRing);
+		assert_eq!(TableSize::FullRing.widening(Position::Btn), 0);
+		assert_eq!(TableSize::ShortHanded.widening(Position::Utg), 1);
+		assert_eq!(TableSize::ShortHanded.widening(Position::Btn), 0);
+		assert_eq!(TableSize::HeadsUp.widening(Position::Btn), 2);
+	}
 }
@@ -1,9 +1,16 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::Path;
+use std::time::Duration;
 
+use crate::error::PokerError;
+use crate::lineup::LineupConfig;
+use crate::promotions::PromotionsConfig;
 use crate::logging;
+use crate::money::{self, money};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum GameFormat {
 	Cash,
@@ -19,7 +26,7 @@ impl std::fmt::Display for GameFormat {
 	}
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum BettingStructure {
 	NoLimit,
@@ -37,14 +44,43 @@ impl std::fmt::Display for BettingStructure {
 	}
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum GameVariant {
+	#[default]
+	Holdem,
+}
+
+impl std::fmt::Display for GameVariant {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			GameVariant::Holdem => write!(f, "Hold'em"),
+		}
+	}
+}
+
+/// One entry in a mixed-game rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MixedGame {
+	#[serde(default)]
+	pub variant: GameVariant,
+	pub betting: BettingStructure,
+}
+
+impl std::fmt::Display for MixedGame {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} {}", self.betting, self.variant)
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BlindLevel {
 	pub small: f32,
 	pub big: f32,
 	pub hands: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TableConfig {
 	pub id: String,
 	pub name: String,
@@ -98,6 +134,42 @@ pub struct TableConfig {
 
 	#[serde(default)]
 	pub seed: Option<u64>,
+
+	#[serde(default)]
+	pub mixed_games: Option<Vec<MixedGame>>,
+
+	/// Seat a pit boss to host the table (see `pit_boss`).
+	#[serde(default)]
+	pub pit_boss: bool,
+
+	/// Let AI players chat after big pots and bad beats.
+	#[serde(default)]
+	pub table_talk: bool,
+	/// Scales how often they speak up; 1.0 is each persona's own pace.
+	#[serde(default = "default_table_talk_frequency")]
+	pub table_talk_frequency: f32,
+
+	/// Sit & go only: once the first player sits down, seconds until the
+	/// empty seats fill with roster AIs and the game starts.
+	#[serde(default)]
+	pub registration_seconds: Option<u32>,
+	/// Sit & go only: seconds a disconnected player's seat waits for them
+	/// before an AI plays it. Reconnecting hands the seat back.
+	#[serde(default)]
+	pub takeover_grace_seconds: Option<u32>,
+
+	/// Which roster AIs fill empty seats; see `lineup`.
+	#[serde(default)]
+	pub lineup: Option<LineupConfig>,
+
+	/// Rakeback and high-hand bonuses; see `promotions`.
+	#[serde(default)]
+	pub promotions: Option<PromotionsConfig>,
+
+	/// Keep every hand dealt in the hand history, folded and mucked ones
+	/// too, for review after the game. Nobody sees them during play.
+	#[serde(default)]
+	pub transparency: bool,
 }
 
 fn default_min_players() -> usize {
@@ -124,6 +196,10 @@ fn default_hand_end_delay() -> u64 {
 	2000
 }
 
+fn default_table_talk_frequency() -> f32 {
+	1.0
+}
+
 impl TableConfig {
 	pub fn current_blinds(&self) -> (f32, f32) {
 		match self.format {
@@ -143,6 +219,24 @@ impl TableConfig {
 		}
 	}
 
+	/// How long a sit & go waits for players after the first sits down.
+	/// `None` means the humans start it by all readying up.
+	pub fn registration_window(&self) -> Option<Duration> {
+		match self.format {
+			GameFormat::Cash => None,
+			GameFormat::SitNGo => self.registration_seconds.map(|s| Duration::from_secs(s as u64)),
+		}
+	}
+
+	/// How long a disconnected sit & go player has before an AI takes over
+	/// their seat. `None` means they sit out for good.
+	pub fn takeover_grace(&self) -> Option<Duration> {
+		match self.format {
+			GameFormat::Cash => None,
+			GameFormat::SitNGo => self.takeover_grace_seconds.map(|s| Duration::from_secs(s as u64)),
+		}
+	}
+
 	pub fn effective_buy_in(&self) -> f32 {
 		match self.format {
 			GameFormat::Cash => self.min_buy_in.unwrap_or(40.0),
@@ -157,18 +251,40 @@ impl TableConfig {
 		}
 	}
 
+	/// "$1/$2", or "N/A" for a table on a blind schedule.
+	pub fn blinds_label(&self) -> String {
+		match (self.small_blind, self.big_blind) {
+			(Some(sb), Some(bb)) => format!("{}/{}", self.chips(sb), self.chips(bb)),
+			_ => "N/A".to_string(),
+		}
+	}
+
+	/// An amount in this table's chips.
+	fn chips(&self, amount: f32) -> String {
+		money::chips_at(amount, self.format == GameFormat::SitNGo)
+	}
+
 	pub fn summary(&self) -> String {
+		let game = if self.is_mixed() {
+			"Mixed".to_string()
+		} else {
+			self.betting.to_string()
+		};
 		match self.format {
 			GameFormat::Cash => {
 				let (small, big) = self.current_blinds();
-				format!("${:.0}/${:.0} {}", small, big, self.betting)
+				format!("{}/{} {}", self.chips(small), self.chips(big), game)
 			}
 			GameFormat::SitNGo => {
-				format!("${:.0} {}", self.effective_buy_in(), self.betting)
+				format!("{} {}", money(self.effective_buy_in()), game)
 			}
 		}
 	}
 
+	pub fn is_mixed(&self) -> bool {
+		self.mixed_games.as_ref().is_some_and(|g| g.len() > 1)
+	}
+
 	pub fn player_range(&self) -> String {
 		if self.min_players == self.max_players {
 			format!("{} players", self.min_players)
@@ -177,6 +293,7 @@ impl TableConfig {
 		}
 	}
 
+	#[cfg(feature = "net")]
 	pub fn is_joinable(&self, current_players: usize, status: &crate::net::protocol::TableStatus) -> bool {
 		use crate::net::protocol::TableStatus;
 		match self.format {
@@ -192,22 +309,84 @@ impl TableConfig {
 	}
 }
 
+/// How long a game holds after each event so it can be followed: the
+/// table's action, street and hand-end delays, and set pauses to take in
+/// a showdown and see a pot pushed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pacing {
+	pub action_ms: u64,
+	pub street_ms: u64,
+	pub hand_end_ms: u64,
+}
+
+impl Pacing {
+	pub const SHOWDOWN_MS: u64 = 500;
+	pub const POT_MS: u64 = 1500;
+
+	pub fn from_table(config: &TableConfig) -> Self {
+		Self {
+			action_ms: config.action_delay_ms,
+			street_ms: config.street_delay_ms,
+			hand_end_ms: config.hand_end_delay_ms,
+		}
+	}
+
+	/// Speeds `+` and `-` step through while watching, as multiples of a
+	/// table's pace.
+	pub const SPEEDS: [f32; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+	/// The speed a step up or down from `speed`; stays put at either end.
+	pub fn step_speed(speed: f32, faster: bool) -> f32 {
+		let last = Self::SPEEDS.len() - 1;
+		let i = Self::SPEEDS.iter().position(|&s| s >= speed).unwrap_or(last);
+		let i = if faster { (i + 1).min(last) } else { i.saturating_sub(1) };
+		Self::SPEEDS[i]
+	}
+
+	/// The action, street and hand-end delays at `speed` times the pace.
+	pub fn at_speed(&self, speed: f32) -> Self {
+		let scale = |ms: u64| (ms as f32 / speed).round() as u64;
+		Self {
+			action_ms: scale(self.action_ms),
+			street_ms: scale(self.street_ms),
+			hand_end_ms: scale(self.hand_end_ms),
+		}
+	}
+
+	/// The hold after `event`; nothing for events that come in a run.
+	pub fn delay(&self, event: &crate::events::GameEvent) -> Duration {
+		use crate::events::GameEvent;
+		let ms = match event {
+			GameEvent::ActionTaken { .. } => self.action_ms,
+			GameEvent::StreetChanged { .. } => self.street_ms,
+			GameEvent::ShowdownReveal { .. } => Self::SHOWDOWN_MS,
+			GameEvent::HandEnded { .. } => self.hand_end_ms,
+			GameEvent::PotAwarded { .. } => Self::POT_MS,
+			_ => 0,
+		};
+		Duration::from_millis(ms)
+	}
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct TablesFile {
 	#[serde(default)]
 	tables: Vec<TableConfig>,
 }
 
-pub fn load_tables() -> Result<Vec<TableConfig>, String> {
+pub fn load_tables() -> Result<Vec<TableConfig>, PokerError> {
 	let config_dir = dirs::config_dir()
-		.ok_or_else(|| "Could not determine config directory".to_string())?;
-	let path = config_dir.join("transparent-poker").join("tables.toml");
+		.ok_or_else(|| PokerError::Config("Could not determine config directory".to_string()))?;
+	load_tables_from(&config_dir.join("transparent-poker").join("tables.toml"))
+}
 
+/// The tables in `path`, or the built-in set if there's no such file.
+pub fn load_tables_from(path: &Path) -> Result<Vec<TableConfig>, PokerError> {
 	if path.exists() {
-		let content = fs::read_to_string(&path)
-			.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+		let content = fs::read_to_string(path)
+			.map_err(|e| PokerError::io("read", path, e))?;
 		let file: TablesFile = toml::from_str(&content)
-			.map_err(|e| format!("Failed to parse tables config: {}", e))?;
+			.map_err(|e| PokerError::parse("tables config", e))?;
 		Ok(file.tables)
 	} else {
 		Ok(default_tables())
@@ -241,6 +420,15 @@ fn default_tables() -> Vec<TableConfig> {
 			street_delay_ms: default_street_delay(),
 			hand_end_delay_ms: default_hand_end_delay(),
 			seed: None,
+			mixed_games: None,
+			pit_boss: false,
+			table_talk: false,
+			table_talk_frequency: 1.0,
+			registration_seconds: None,
+			takeover_grace_seconds: None,
+			lineup: None,
+			promotions: None,
+			transparency: false,
 		},
 		TableConfig {
 			id: "home-sng".to_string(),
@@ -273,6 +461,15 @@ fn default_tables() -> Vec<TableConfig> {
 			street_delay_ms: default_street_delay(),
 			hand_end_delay_ms: default_hand_end_delay(),
 			seed: None,
+			mixed_games: None,
+			pit_boss: false,
+			table_talk: false,
+			table_talk_frequency: 1.0,
+			registration_seconds: None,
+			takeover_grace_seconds: None,
+			lineup: None,
+			promotions: None,
+			transparency: false,
 		},
 	]
 }
@@ -327,7 +524,7 @@ impl BlindClock {
 					logging::log(
 						"Engine",
 						"BLINDS",
-						&format!("level {}: ${:.0}/${:.0}", self.current_level + 1, small, big),
+						&format!("level {}: {}/{}", self.current_level + 1, money::chips(small), money::chips(big)),
 					);
 					return true;
 				}
@@ -341,34 +538,89 @@ impl BlindClock {
 	}
 }
 
+/// Cycles through a table's mixed games, moving on after each orbit.
+#[derive(Debug, Clone)]
+pub struct GameRotation {
+	games: Vec<MixedGame>,
+	current: usize,
+	hands_in_game: u32,
+}
+
+impl GameRotation {
+	pub fn new(games: Vec<MixedGame>) -> Self {
+		Self {
+			games,
+			current: 0,
+			hands_in_game: 0,
+		}
+	}
+
+	pub fn from_table(config: &TableConfig) -> Option<Self> {
+		if config.is_mixed() {
+			config.mixed_games.as_ref().map(|games| Self::new(games.clone()))
+		} else {
+			None
+		}
+	}
+
+	pub fn current(&self) -> MixedGame {
+		self.games[self.current % self.games.len()]
+	}
+
+	pub fn games(&self) -> &[MixedGame] {
+		&self.games
+	}
+
+	/// Records a finished hand. An orbit is one hand per player dealt in,
+	/// so `orbit_length` is the number of active players. Returns true when
+	/// the table moves on to the next game.
+	pub fn advance_hand(&mut self, orbit_length: usize) -> bool {
+		self.hands_in_game += 1;
+		if self.hands_in_game as usize >= orbit_length.max(1) {
+			self.current = (self.current + 1) % self.games.len();
+			self.hands_in_game = 0;
+			logging::log("Engine", "GAME", &format!("rotating to {}", self.current()));
+			return true;
+		}
+		false
+	}
+}
+
 pub fn calculate_payouts(buy_in: f32, num_players: usize, payout_percentages: &[f32]) -> Vec<f32> {
 	let prize_pool = buy_in * num_players as f32;
 	payout_percentages.iter().map(|p| prize_pool * p).collect()
 }
 
 pub fn build_info_lines(table: &TableConfig, num_players: usize, seed: Option<u64>) -> Vec<String> {
-	let mut lines = vec![
-		format!("Format: {}", table.format),
-		format!("Betting: {}", table.betting),
-		String::new(),
-	];
+	let mut lines = vec![format!("Format: {}", table.format)];
+
+	match &table.mixed_games {
+		Some(games) if table.is_mixed() => {
+			lines.push("Games (each orbit):".to_string());
+			for (i, game) in games.iter().enumerate() {
+				lines.push(format!("  {}. {}", i + 1, game));
+			}
+		}
+		_ => lines.push(format!("Betting: {}", table.betting)),
+	}
+	lines.push(String::new());
 
 	match table.format {
 		GameFormat::Cash => {
 			if let (Some(sb), Some(bb)) = (table.small_blind, table.big_blind) {
-				lines.push(format!("Blinds: ${:.0}/${:.0}", sb, bb));
+				lines.push(format!("Blinds: {}/{}", money(sb), money(bb)));
 			}
 			if let Some(min) = table.min_buy_in {
-				lines.push(format!("Min Buy-in: ${:.0}", min));
+				lines.push(format!("Min Buy-in: {}", money(min)));
 			}
 			if let Some(max) = table.max_buy_in {
-				lines.push(format!("Max Buy-in: ${:.0}", max));
+				lines.push(format!("Max Buy-in: {}", money(max)));
 			}
 			lines.push(String::new());
 			lines.push(format!("Players: {}", num_players));
 			if table.rake_percent > 0.0 {
 				let rake_str = if let Some(cap) = table.rake_cap {
-					format!("Rake: {:.1}% (${:.0} cap)", table.rake_percent * 100.0, cap)
+					format!("Rake: {:.1}% ({} cap)", table.rake_percent * 100.0, money(cap))
 				} else {
 					format!("Rake: {:.1}%", table.rake_percent * 100.0)
 				};
@@ -377,10 +629,10 @@ pub fn build_info_lines(table: &TableConfig, num_players: usize, seed: Option<u6
 		}
 		GameFormat::SitNGo => {
 			if let Some(buyin) = table.buy_in {
-				lines.push(format!("Buy-in: ${:.0}", buyin));
+				lines.push(format!("Buy-in: {}", money(buyin)));
 			}
 			if let Some(stack) = table.starting_stack {
-				lines.push(format!("Starting Stack: ${:.0}", stack));
+				lines.push(format!("Starting Stack: {}", table.chips(stack)));
 			}
 			lines.push(String::new());
 			lines.push(format!("Players: {}", num_players));
@@ -388,7 +640,7 @@ pub fn build_info_lines(table: &TableConfig, num_players: usize, seed: Option<u6
 				let prize_pool = buyin * num_players as f32;
 				let payout_strs: Vec<String> = payouts
 					.iter()
-					.map(|p| format!("${:.0}", (prize_pool * p).round()))
+					.map(|p| money((prize_pool * p).round()))
 					.collect();
 				lines.push(format!("Payouts: {}", payout_strs.join(", ")));
 			}
@@ -403,10 +655,77 @@ pub fn build_info_lines(table: &TableConfig, num_players: usize, seed: Option<u6
 	lines
 }
 
+/// The table's betting rules in plain words, for the in-game help.
+pub fn build_rules_lines(table: &TableConfig) -> Vec<String> {
+	let mut structures: Vec<BettingStructure> = Vec::new();
+	let rotation = match &table.mixed_games {
+		Some(games) if table.is_mixed() => games.iter().map(|g| g.betting).collect(),
+		_ => vec![table.betting],
+	};
+	for betting in rotation {
+		if !structures.contains(&betting) {
+			structures.push(betting);
+		}
+	}
+
+	let mut lines = Vec::new();
+	for betting in structures {
+		lines.push(format!("{}:", betting));
+		let rule = match betting {
+			BettingStructure::NoLimit => {
+				"  Bet or raise any amount up to your stack. A raise must be at least the last bet or raise.".to_string()
+			}
+			BettingStructure::PotLimit => {
+				"  Bet or raise up to the pot, counting your call. A raise must be at least the last bet or raise.".to_string()
+			}
+			BettingStructure::FixedLimit => match table.format {
+				GameFormat::Cash => {
+					let (_, big) = table.current_blinds();
+					format!("  Bets and raises are {} preflop and on the flop, {} on the turn and river.", money(big), money(big * 2.0))
+				}
+				GameFormat::SitNGo => {
+					"  Bets and raises are one big blind preflop and on the flop, two on the turn and river.".to_string()
+				}
+			},
+		};
+		lines.push(rule);
+	}
+	lines.push(format!("Raise cap: {} bets and raises per street", table.max_raises_per_round));
+	lines
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 
+	#[test]
+	fn test_pacing_holds_after_the_events_worth_watching() {
+		use crate::events::{GameEvent, Seat, Street};
+		let pacing = Pacing { action_ms: 300, street_ms: 700, hand_end_ms: 2000 };
+		let folded = GameEvent::ActionTaken {
+			seat: Seat(0),
+			action: crate::events::PlayerAction::Fold,
+			stack_after: 100.0,
+			pot_after: 3.0,
+			at: None,
+		};
+		assert_eq!(pacing.delay(&folded), Duration::from_millis(300));
+		let flop = GameEvent::StreetChanged { street: Street::Flop, board: Vec::new(), pot: 6.0, effective_stack: 97.0, pots: Vec::new() };
+		assert_eq!(pacing.delay(&flop), Duration::from_millis(700));
+		let chat = GameEvent::ChatMessage { sender: crate::events::ChatSender::Dealer, text: String::new() };
+		assert_eq!(pacing.delay(&chat), Duration::ZERO);
+	}
+
+	#[test]
+	fn test_speed_steps_stop_at_the_ends() {
+		assert_eq!(Pacing::step_speed(1.0, true), 2.0);
+		assert_eq!(Pacing::step_speed(1.0, false), 0.5);
+		assert_eq!(Pacing::step_speed(8.0, true), 8.0);
+		assert_eq!(Pacing::step_speed(0.25, false), 0.25);
+		let pacing = Pacing { action_ms: 300, street_ms: 700, hand_end_ms: 2000 };
+		assert_eq!(pacing.at_speed(2.0), Pacing { action_ms: 150, street_ms: 350, hand_end_ms: 1000 });
+	}
+
 	#[test]
 	fn test_blind_clock() {
 		let levels = vec![
@@ -426,6 +745,83 @@ mod tests {
 		assert_eq!(clock.current_level_num(), 2);
 	}
 
+	#[test]
+	fn test_game_rotation_moves_on_each_orbit() {
+		let mut rotation = GameRotation::new(vec![
+			MixedGame { variant: GameVariant::Holdem, betting: BettingStructure::NoLimit },
+			MixedGame { variant: GameVariant::Holdem, betting: BettingStructure::FixedLimit },
+		]);
+		assert_eq!(rotation.current().betting, BettingStructure::NoLimit);
+
+		assert!(!rotation.advance_hand(3));
+		assert!(!rotation.advance_hand(3));
+		assert!(rotation.advance_hand(3));
+		assert_eq!(rotation.current().betting, BettingStructure::FixedLimit);
+
+		assert!(!rotation.advance_hand(2));
+		assert!(rotation.advance_hand(2));
+		assert_eq!(rotation.current().betting, BettingStructure::NoLimit);
+	}
+
+	#[test]
+	fn test_mixed_game_display() {
+		let game = MixedGame { variant: GameVariant::Holdem, betting: BettingStructure::PotLimit };
+		assert_eq!(game.to_string(), "Pot-Limit Hold'em");
+	}
+
+	#[test]
+	fn test_mixed_games_parse_from_toml() {
+		let toml_str = r#"
+			[[tables]]
+			id = "mixed"
+			name = "Mixed"
+			format = "cash"
+			betting = "no-limit"
+			mixed_games = [
+				{ betting = "no-limit" },
+				{ variant = "holdem", betting = "fixed-limit" },
+			]
+		"#;
+		let file: TablesFile = toml::from_str(toml_str).unwrap();
+		let table = &file.tables[0];
+		assert!(table.is_mixed());
+		let rotation = GameRotation::from_table(table).unwrap();
+		assert_eq!(rotation.games().len(), 2);
+		assert_eq!(rotation.games()[1].betting, BettingStructure::FixedLimit);
+
+		let lines = build_info_lines(table, 4, None);
+		assert!(lines.iter().any(|l| l == "  2. Fixed-Limit Hold'em"));
+	}
+
+	#[test]
+	fn test_rules_lines_describe_betting_and_cap() {
+		let table: TableConfig = toml::from_str(r#"
+			id = "limit"
+			name = "Limit"
+			format = "cash"
+			betting = "fixed-limit"
+			small_blind = 5
+			big_blind = 10
+			max_raises_per_round = 3
+		"#).unwrap();
+		let lines = build_rules_lines(&table);
+		assert_eq!(lines[0], "Fixed-Limit:");
+		assert!(lines[1].contains("$10 preflop") && lines[1].contains("$20 on the turn"));
+		assert_eq!(lines.last().unwrap(), "Raise cap: 3 bets and raises per street");
+
+		let mixed = TableConfig {
+			mixed_games: Some(vec![
+				MixedGame { variant: GameVariant::Holdem, betting: BettingStructure::NoLimit },
+				MixedGame { variant: GameVariant::Holdem, betting: BettingStructure::PotLimit },
+			]),
+			..table
+		};
+		let lines = build_rules_lines(&mixed);
+		assert!(lines.contains(&"No-Limit:".to_string()));
+		assert!(lines.contains(&"Pot-Limit:".to_string()));
+		assert!(!lines.contains(&"Fixed-Limit:".to_string()));
+	}
+
 	#[test]
 	fn test_payouts() {
 		let payouts = calculate_payouts(50.0, 6, &[0.65, 0.35]);
@@ -515,6 +911,15 @@ mod tests {
 			street_delay_ms: 700,
 			hand_end_delay_ms: 2000,
 			seed: None,
+			mixed_games: None,
+			pit_boss: false,
+			table_talk: false,
+			table_talk_frequency: 1.0,
+			registration_seconds: None,
+			takeover_grace_seconds: None,
+			lineup: None,
+			promotions: None,
+			transparency: false,
 		};
 		assert_eq!(config.current_blinds(), (5.0, 10.0));
 	}
@@ -548,6 +953,15 @@ mod tests {
 			street_delay_ms: 700,
 			hand_end_delay_ms: 2000,
 			seed: None,
+			mixed_games: None,
+			pit_boss: false,
+			table_talk: false,
+			table_talk_frequency: 1.0,
+			registration_seconds: None,
+			takeover_grace_seconds: None,
+			lineup: None,
+			promotions: None,
+			transparency: false,
 		};
 		assert_eq!(config.current_blinds(), (15.0, 30.0));
 	}
@@ -579,6 +993,15 @@ mod tests {
 			street_delay_ms: 700,
 			hand_end_delay_ms: 2000,
 			seed: None,
+			mixed_games: None,
+			pit_boss: false,
+			table_talk: false,
+			table_talk_frequency: 1.0,
+			registration_seconds: None,
+			takeover_grace_seconds: None,
+			lineup: None,
+			promotions: None,
+			transparency: false,
 		};
 		assert_eq!(cash.effective_buy_in(), 80.0);
 
@@ -607,6 +1030,15 @@ mod tests {
 			street_delay_ms: 700,
 			hand_end_delay_ms: 2000,
 			seed: None,
+			mixed_games: None,
+			pit_boss: false,
+			table_talk: false,
+			table_talk_frequency: 1.0,
+			registration_seconds: None,
+			takeover_grace_seconds: None,
+			lineup: None,
+			promotions: None,
+			transparency: false,
 		};
 		assert_eq!(sng.effective_buy_in(), 100.0);
 	}
@@ -638,6 +1070,15 @@ mod tests {
 			street_delay_ms: 700,
 			hand_end_delay_ms: 2000,
 			seed: None,
+			mixed_games: None,
+			pit_boss: false,
+			table_talk: false,
+			table_talk_frequency: 1.0,
+			registration_seconds: None,
+			takeover_grace_seconds: None,
+			lineup: None,
+			promotions: None,
+			transparency: false,
 		};
 		assert_eq!(config.player_range(), "2-6 players");
 
@@ -679,4 +1120,13 @@ mod tests {
 			assert!(!table.name.is_empty());
 		}
 	}
+
+	#[test]
+	fn test_bundled_tables_parse() {
+		let file: TablesFile = toml::from_str(include_str!("../config/tables.toml")).unwrap();
+		let vendetta = file.tables.iter().find(|t| t.id == "vendetta").unwrap();
+		let lineup = vendetta.lineup.as_ref().unwrap();
+		assert_eq!(lineup.difficulty, Some(crate::lineup::Difficulty::Tough));
+		assert_eq!(lineup.stack_bb, Some((60.0, 100.0)));
+	}
 }
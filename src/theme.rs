@@ -5,6 +5,9 @@ use ratatui::style::Color;
 use ratatui::widgets::BorderType;
 use serde::{Deserialize, Serialize};
 
+use crate::error::PokerError;
+use crate::tui::layout::{LayoutProfile, SeatArrangement, TableShape};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Theme {
@@ -36,7 +39,14 @@ pub struct Theme {
 	pub red_suit_color: String,
 	pub black_suit_color: String,
 	pub hidden_card_color: String,
-
+	/// Drawn for each face-down card; one column wide.
+	pub card_back: String,
+
+	/// The felt: "minimal" (none), "oval" or "racetrack".
+	pub table_shape: String,
+	/// Where the seats go: "around" the felt, or in "rows" above and
+	/// below it.
+	pub seat_arrangement: String,
 	pub table_border_color: String,
 	pub chat_border_color: String,
 	pub chat_text_color: String,
@@ -60,6 +70,78 @@ pub struct Theme {
 	pub status_quit_border: String,
 	pub status_game_over_color: String,
 	pub status_game_over_border: String,
+
+	/// How many colors the terminal can show; not part of the theme file.
+	#[serde(skip)]
+	pub palette: Palette,
+}
+
+/// The colors a terminal can show. Themes are written in 24-bit color;
+/// anything less gets the nearest match, or no color at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Palette {
+	#[default]
+	TrueColor,
+	/// The 16 standard ANSI colors.
+	Ansi16,
+	/// The terminal's own foreground and background only.
+	Mono,
+}
+
+/// The 16 ANSI colors, with xterm's default values to match against.
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+	(Color::Black, (0, 0, 0)),
+	(Color::Red, (205, 0, 0)),
+	(Color::Green, (0, 205, 0)),
+	(Color::Yellow, (205, 205, 0)),
+	(Color::Blue, (0, 0, 238)),
+	(Color::Magenta, (205, 0, 205)),
+	(Color::Cyan, (0, 205, 205)),
+	(Color::Gray, (229, 229, 229)),
+	(Color::DarkGray, (127, 127, 127)),
+	(Color::LightRed, (255, 0, 0)),
+	(Color::LightGreen, (0, 255, 0)),
+	(Color::LightYellow, (255, 255, 0)),
+	(Color::LightBlue, (92, 92, 255)),
+	(Color::LightMagenta, (255, 0, 255)),
+	(Color::LightCyan, (0, 255, 255)),
+	(Color::White, (255, 255, 255)),
+];
+
+impl Palette {
+	/// From the environment: `NO_COLOR` turns color off, and 24-bit color
+	/// is only used when the terminal says it supports it.
+	pub fn detect() -> Self {
+		let var = |name| std::env::var(name).ok();
+		Self::from_env(var("NO_COLOR"), var("COLORTERM"), var("TERM"), var("WT_SESSION").is_some())
+	}
+
+	fn from_env(no_color: Option<String>, colorterm: Option<String>, term: Option<String>, windows_terminal: bool) -> Self {
+		if no_color.is_some_and(|v| !v.is_empty()) || term.as_deref() == Some("dumb") {
+			return Palette::Mono;
+		}
+		let truecolor = colorterm.is_some_and(|v| v == "truecolor" || v == "24bit");
+		if truecolor || windows_terminal {
+			Palette::TrueColor
+		} else {
+			Palette::Ansi16
+		}
+	}
+
+	pub fn apply(self, color: Color) -> Color {
+		match (self, color) {
+			(Palette::TrueColor, _) => color,
+			(Palette::Mono, _) => Color::Reset,
+			(Palette::Ansi16, Color::Rgb(r, g, b)) => {
+				let distance = |&(_, (ar, ag, ab)): &(Color, (u8, u8, u8))| {
+					let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2);
+					d(r, ar) + d(g, ag) + d(b, ab)
+				};
+				ANSI16.iter().min_by_key(|c| distance(c)).map(|(c, _)| *c).unwrap_or(color)
+			}
+			(Palette::Ansi16, _) => color,
+		}
+	}
 }
 
 impl Default for Theme {
@@ -93,7 +175,10 @@ impl Default for Theme {
 			red_suit_color: "#D70000".to_string(),
 			black_suit_color: "#D7D7D7".to_string(),
 			hidden_card_color: "#0087D7".to_string(),
+			card_back: "▓".to_string(),
 
+			table_shape: "minimal".to_string(),
+			seat_arrangement: "around".to_string(),
 			table_border_color: "#00D700".to_string(),
 			chat_border_color: "#0087D7".to_string(),
 			chat_text_color: "#B0B0B0".to_string(),
@@ -117,6 +202,8 @@ impl Default for Theme {
 			status_quit_border: "#D70000".to_string(),
 			status_game_over_color: "#00D700".to_string(),
 			status_game_over_border: "#00D700".to_string(),
+
+			palette: Palette::default(),
 		}
 	}
 }
@@ -134,9 +221,9 @@ impl Theme {
 		})
 	}
 
-	pub fn load_named(name: &str) -> Result<Self, String> {
+	pub fn load_named(name: &str) -> Result<Self, PokerError> {
 		let config_dir = dirs::config_dir()
-			.ok_or_else(|| "Could not determine config directory".to_string())?;
+			.ok_or_else(|| PokerError::Config("Could not determine config directory".to_string()))?;
 		let path = config_dir
 			.join("transparent-poker")
 			.join("themes")
@@ -145,15 +232,30 @@ impl Theme {
 		if path.exists() {
 			Self::from_file(&path)
 		} else {
-			Err(format!("Theme '{}' not found", name))
+			Err(PokerError::Config(format!("Theme '{}' not found", name)))
 		}
 	}
 
-	fn from_file(path: &PathBuf) -> Result<Self, String> {
+	fn from_file(path: &PathBuf) -> Result<Self, PokerError> {
 		let contents = fs::read_to_string(path)
-			.map_err(|e| format!("Failed to read theme file: {}", e))?;
+			.map_err(|e| PokerError::io("read", path, e))?;
 		toml::from_str(&contents)
-			.map_err(|e| format!("Failed to parse theme: {}", e))
+			.map_err(|e| PokerError::parse("theme", e))
+	}
+
+	pub fn with_palette(mut self, palette: Palette) -> Self {
+		self.palette = palette;
+		self
+	}
+
+	/// A theme color as this terminal can show it.
+	fn paint(&self, color: &str) -> Color {
+		self.adapt(parse_color(color))
+	}
+
+	/// A fixed color as this terminal can show it.
+	pub fn adapt(&self, color: Color) -> Color {
+		self.palette.apply(color)
 	}
 
 	pub fn list_available() -> Vec<String> {
@@ -161,163 +263,171 @@ impl Theme {
 	}
 
 	pub fn background(&self) -> Color {
-		parse_color(&self.background_color)
+		self.paint(&self.background_color)
 	}
 
 	pub fn hero_border_type(&self) -> BorderType {
 		parse_border_type(&self.hero_border_style)
 	}
 
+	/// The table's shape and seating, for `TableLayout`.
+	pub fn layout_profile(&self) -> LayoutProfile {
+		LayoutProfile {
+			shape: parse_table_shape(&self.table_shape),
+			seats: parse_seat_arrangement(&self.seat_arrangement),
+		}
+	}
+
 	pub fn hero_border(&self) -> Color {
-		parse_color(&self.hero_border_color)
+		self.paint(&self.hero_border_color)
 	}
 
 	pub fn actor_border(&self) -> Color {
-		parse_color(&self.actor_border_color)
+		self.paint(&self.actor_border_color)
 	}
 
 	pub fn actor_name(&self) -> Color {
-		parse_color(&self.actor_name_color)
+		self.paint(&self.actor_name_color)
 	}
 
 	pub fn folded_border(&self) -> Color {
-		parse_color(&self.folded_border_color)
+		self.paint(&self.folded_border_color)
 	}
 
 	pub fn folded_text(&self) -> Color {
-		parse_color(&self.folded_text_color)
+		self.paint(&self.folded_text_color)
 	}
 
 	pub fn eliminated_border(&self) -> Color {
-		parse_color(&self.eliminated_border_color)
+		self.paint(&self.eliminated_border_color)
 	}
 
 	pub fn eliminated_text(&self) -> Color {
-		parse_color(&self.eliminated_text_color)
+		self.paint(&self.eliminated_text_color)
 	}
 
 	pub fn all_in_border(&self) -> Color {
-		parse_color(&self.all_in_border_color)
+		self.paint(&self.all_in_border_color)
 	}
 
 	pub fn winner_border(&self) -> Color {
-		parse_color(&self.winner_border_color)
+		self.paint(&self.winner_border_color)
 	}
 
 	pub fn winner_name(&self) -> Color {
-		parse_color(&self.winner_name_color)
+		self.paint(&self.winner_name_color)
 	}
 
 	pub fn default_border(&self) -> Color {
-		parse_color(&self.default_border_color)
+		self.paint(&self.default_border_color)
 	}
 
 	pub fn stack(&self) -> Color {
-		parse_color(&self.stack_color)
+		self.paint(&self.stack_color)
 	}
 
 	pub fn bet(&self) -> Color {
-		parse_color(&self.bet_color)
+		self.paint(&self.bet_color)
 	}
 
 	pub fn pot(&self) -> Color {
-		parse_color(&self.pot_color)
+		self.paint(&self.pot_color)
 	}
 
 	pub fn red_suit(&self) -> Color {
-		parse_color(&self.red_suit_color)
+		self.paint(&self.red_suit_color)
 	}
 
 	pub fn black_suit(&self) -> Color {
-		parse_color(&self.black_suit_color)
+		self.paint(&self.black_suit_color)
 	}
 
 	pub fn hidden_card(&self) -> Color {
-		parse_color(&self.hidden_card_color)
+		self.paint(&self.hidden_card_color)
 	}
 
 	pub fn table_border(&self) -> Color {
-		parse_color(&self.table_border_color)
+		self.paint(&self.table_border_color)
 	}
 
 	pub fn chat_border(&self) -> Color {
-		parse_color(&self.chat_border_color)
+		self.paint(&self.chat_border_color)
 	}
 
 	pub fn chat_text(&self) -> Color {
-		parse_color(&self.chat_text_color)
+		self.paint(&self.chat_text_color)
 	}
 
 	pub fn system_message(&self) -> Color {
-		parse_color(&self.system_message_color)
+		self.paint(&self.system_message_color)
 	}
 
 	pub fn menu_border(&self) -> Color {
-		parse_color(&self.menu_border_color)
+		self.paint(&self.menu_border_color)
 	}
 
 	pub fn menu_title(&self) -> Color {
-		parse_color(&self.menu_title_color)
+		self.paint(&self.menu_title_color)
 	}
 
 	pub fn menu_text(&self) -> Color {
-		parse_color(&self.menu_text_color)
+		self.paint(&self.menu_text_color)
 	}
 
 	pub fn menu_selected(&self) -> Color {
-		parse_color(&self.menu_selected_color)
+		self.paint(&self.menu_selected_color)
 	}
 
 	pub fn menu_selected_bg(&self) -> Color {
-		parse_color(&self.menu_selected_bg)
+		self.paint(&self.menu_selected_bg)
 	}
 
 	pub fn menu_unselected(&self) -> Color {
-		parse_color(&self.menu_unselected_color)
+		self.paint(&self.menu_unselected_color)
 	}
 
 	pub fn menu_host_marker(&self) -> Color {
-		parse_color(&self.menu_host_marker_color)
+		self.paint(&self.menu_host_marker_color)
 	}
 
 	pub fn menu_ai_marker(&self) -> Color {
-		parse_color(&self.menu_ai_marker_color)
+		self.paint(&self.menu_ai_marker_color)
 	}
 
 	pub fn menu_highlight(&self) -> Color {
-		parse_color(&self.menu_highlight_color)
+		self.paint(&self.menu_highlight_color)
 	}
 
 	pub fn status_watching(&self) -> Color {
-		parse_color(&self.status_watching_color)
+		self.paint(&self.status_watching_color)
 	}
 
 	pub fn status_watching_border(&self) -> Color {
-		parse_color(&self.status_watching_border)
+		self.paint(&self.status_watching_border)
 	}
 
 	pub fn status_your_turn(&self) -> Color {
-		parse_color(&self.status_your_turn_color)
+		self.paint(&self.status_your_turn_color)
 	}
 
 	pub fn status_your_turn_border(&self) -> Color {
-		parse_color(&self.status_your_turn_border)
+		self.paint(&self.status_your_turn_border)
 	}
 
 	pub fn status_quit(&self) -> Color {
-		parse_color(&self.status_quit_color)
+		self.paint(&self.status_quit_color)
 	}
 
 	pub fn status_quit_border(&self) -> Color {
-		parse_color(&self.status_quit_border)
+		self.paint(&self.status_quit_border)
 	}
 
 	pub fn status_game_over(&self) -> Color {
-		parse_color(&self.status_game_over_color)
+		self.paint(&self.status_game_over_color)
 	}
 
 	pub fn status_game_over_border(&self) -> Color {
-		parse_color(&self.status_game_over_border)
+		self.paint(&self.status_game_over_border)
 	}
 }
 
@@ -375,6 +485,21 @@ fn parse_border_type(s: &str) -> BorderType {
 	}
 }
 
+fn parse_table_shape(s: &str) -> TableShape {
+	match s.to_lowercase().as_str() {
+		"oval" => TableShape::Oval,
+		"racetrack" => TableShape::Racetrack,
+		_ => TableShape::Minimal,
+	}
+}
+
+fn parse_seat_arrangement(s: &str) -> SeatArrangement {
+	match s.to_lowercase().as_str() {
+		"rows" => SeatArrangement::Rows,
+		_ => SeatArrangement::Around,
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -398,6 +523,32 @@ mod tests {
 		assert_eq!(parse_color("rgb(255, 128, 0)"), Color::Rgb(255, 128, 0));
 	}
 
+	#[test]
+	fn test_palette_detection() {
+		let detect = |no_color: Option<&str>, colorterm: Option<&str>, term: Option<&str>| {
+			Palette::from_env(no_color.map(String::from), colorterm.map(String::from), term.map(String::from), false)
+		};
+		assert_eq!(detect(None, Some("truecolor"), Some("xterm-256color")), Palette::TrueColor);
+		assert_eq!(detect(None, None, Some("xterm-256color")), Palette::Ansi16);
+		assert_eq!(detect(Some("1"), Some("truecolor"), None), Palette::Mono);
+		assert_eq!(detect(Some(""), Some("24bit"), None), Palette::TrueColor);
+		assert_eq!(detect(None, None, Some("dumb")), Palette::Mono);
+		assert_eq!(Palette::from_env(None, None, None, true), Palette::TrueColor);
+	}
+
+	#[test]
+	fn test_palette_maps_theme_colors() {
+		let theme = Theme::default().with_palette(Palette::Ansi16);
+		assert_eq!(theme.red_suit(), Color::Red);
+		assert_eq!(theme.stack(), Color::Green);
+		assert_eq!(theme.folded_text(), Color::DarkGray);
+		assert_eq!(theme.adapt(Color::Yellow), Color::Yellow);
+
+		let mono = Theme::default().with_palette(Palette::Mono);
+		assert_eq!(mono.background(), Color::Reset);
+		assert_eq!(mono.adapt(Color::DarkGray), Color::Reset);
+	}
+
 	#[test]
 	fn test_parse_border_type() {
 		assert_eq!(parse_border_type("double"), BorderType::Double);
@@ -406,4 +557,13 @@ mod tests {
 		assert_eq!(parse_border_type("plain"), BorderType::Plain);
 		assert_eq!(parse_border_type("unknown"), BorderType::Plain);
 	}
+
+	#[test]
+	fn test_layout_profile_from_theme_file() {
+		let theme: Theme = toml::from_str("table_shape = \"Racetrack\"\nseat_arrangement = \"rows\"\ncard_back = \"#\"\n").unwrap();
+		assert_eq!(theme.layout_profile(), LayoutProfile { shape: TableShape::Racetrack, seats: SeatArrangement::Rows });
+		assert_eq!(theme.card_back, "#");
+		assert_eq!(Theme::default().layout_profile(), LayoutProfile::default());
+		assert_eq!(parse_table_shape("hexagon"), TableShape::Minimal);
+	}
 }
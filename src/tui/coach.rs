@@ -0,0 +1,275 @@
+//! Coaching tips: after a hand where the hero folded with plenty of
+//! equity for the price, or called with far too little, a line in the
+//! chat saying so. A decision is judged by the hero's cards against the
+//! hands still in, dealt at random, when it was made; how the hand came
+//! out doesn't count. Only decisions after the flop, and at most one tip
+//! every few hands.
+
+use std::collections::HashSet;
+
+use crate::engine::equity_against_random;
+use crate::events::{Card, GameEvent, PlayerAction, Seat, Street};
+use crate::i18n::{tr, tr_with};
+
+/// Equity, either side of what the price needed, that makes a decision
+/// clearly wrong.
+const CLEAR_MARGIN: f32 = 0.15;
+
+/// Hands from one tip to the next, at least.
+const HANDS_BETWEEN_TIPS: u32 = 5;
+
+/// Follows the hero's decisions over a hand.
+pub struct Coach {
+	hero: Option<Seat>,
+	hand_num: u32,
+	cards: Option<[Card; 2]>,
+	board: Vec<Card>,
+	street: Street,
+	pot: f32,
+	/// Seats dealt in that haven't folded.
+	in_hand: HashSet<Seat>,
+	/// The price of the hero's decision in front of them: to call, and the
+	/// pot.
+	facing: Option<(f32, f32)>,
+	tip: Option<String>,
+	last_tip: Option<u32>,
+}
+
+impl Coach {
+	pub fn new(hero: Option<Seat>) -> Self {
+		Self {
+			hero,
+			hand_num: 0,
+			cards: None,
+			board: Vec::new(),
+			street: Street::Preflop,
+			pot: 0.0,
+			in_hand: HashSet::new(),
+			facing: None,
+			tip: None,
+			last_tip: None,
+		}
+	}
+
+	/// Follows `event`; at the end of a hand, the tip for it, if it has
+	/// one and one hasn't been given too recently.
+	pub fn observe(&mut self, event: &GameEvent) -> Option<String> {
+		let hero = self.hero?;
+		match event {
+			GameEvent::HandStarted { hand_num, seats, .. } => {
+				self.hand_num = *hand_num;
+				self.cards = None;
+				self.board.clear();
+				self.street = Street::Preflop;
+				self.pot = 0.0;
+				self.in_hand = seats.iter().filter(|s| s.is_active).map(|s| s.seat).collect();
+				self.facing = None;
+				self.tip = None;
+			}
+			GameEvent::HoleCardsDealt { seat, cards } if *seat == hero => self.cards = Some(*cards),
+			GameEvent::BlindPosted { amount, .. } => self.pot += amount,
+			GameEvent::StreetChanged { street, board, pot, .. } => {
+				self.street = *street;
+				self.board = board.clone();
+				if *pot > 0.0 {
+					self.pot = *pot;
+				}
+			}
+			GameEvent::ActionRequest { seat, valid_actions, .. } if *seat == hero => {
+				self.facing = valid_actions.call_amount.map(|call| (call, self.pot));
+			}
+			GameEvent::ActionTaken { seat, action, pot_after, .. } => {
+				if *seat == hero {
+					if let Some((call, pot)) = self.facing.take() {
+						if self.tip.is_none() {
+							self.tip = self.judge(action, call, pot);
+						}
+					}
+				}
+				if matches!(action, PlayerAction::Fold) {
+					self.in_hand.remove(seat);
+				}
+				self.pot = *pot_after;
+			}
+			GameEvent::HandEnded { .. } => {
+				let tip = self.tip.take()?;
+				let recent = self.last_tip.is_some_and(|last| self.hand_num < last + HANDS_BETWEEN_TIPS);
+				if recent {
+					return None;
+				}
+				self.last_tip = Some(self.hand_num);
+				return Some(tip);
+			}
+			_ => {}
+		}
+		None
+	}
+
+	/// A tip if folding or calling `call` into `pot` was clearly wrong.
+	fn judge(&self, action: &PlayerAction, call: f32, pot: f32) -> Option<String> {
+		let cards = self.cards?;
+		let folded = match action {
+			PlayerAction::Fold => true,
+			PlayerAction::Call { .. } => false,
+			_ => return None,
+		};
+		if self.board.len() < 3 || call <= 0.0 {
+			return None;
+		}
+		let opponents = self.in_hand.iter().filter(|&&s| Some(s) != self.hero).count();
+		if opponents == 0 {
+			return None;
+		}
+		let needed = call / (pot + call);
+		let equity = equity_against_random(&cards, &self.board, opponents);
+		let percent = |share: f32| format!("{:.0}", share * 100.0);
+		let street = street_name(self.street);
+		let args = [("street", street.as_str()), ("needed", &percent(needed)), ("equity", &percent(equity))];
+		if folded && equity >= needed + CLEAR_MARGIN {
+			match draw(&cards, &self.board) {
+				Some(draw) => {
+					let draw = tr(draw);
+					let mut args = args.to_vec();
+					args.push(("draw", &draw));
+					Some(tr_with("coach.folded_draw", &args))
+				}
+				None => Some(tr_with("coach.folded", &args)),
+			}
+		} else if !folded && equity + CLEAR_MARGIN <= needed {
+			Some(tr_with("coach.called", &args))
+		} else {
+			None
+		}
+	}
+}
+
+fn street_name(street: Street) -> String {
+	tr(match street {
+		Street::Flop => "coach.flop",
+		Street::Turn => "coach.turn",
+		_ => "coach.river",
+	})
+}
+
+fn rank_value(card: &Card) -> Option<u8> {
+	"23456789TJQKA".find(card.rank).map(|i| i as u8 + 2)
+}
+
+/// The draw the hole cards are part of with cards to come, as a catalog
+/// key: four to a flush, or four to a straight.
+fn draw(hole: &[Card; 2], board: &[Card]) -> Option<&'static str> {
+	if board.len() >= 5 {
+		return None;
+	}
+	let all = hole.iter().chain(board).collect::<Vec<_>>();
+	let flush_draw = hole.iter().any(|h| all.iter().filter(|c| c.suit == h.suit).count() == 4);
+	if flush_draw {
+		return Some("coach.flush_draw");
+	}
+	let ranks = |cards: &mut dyn Iterator<Item = &Card>| {
+		cards.filter_map(rank_value).fold(0u16, |bits, r| {
+			let bits = bits | 1 << r;
+			if r == 14 { bits | 1 << 1 } else { bits }
+		})
+	};
+	let all_ranks = ranks(&mut all.iter().copied());
+	let hole_ranks = ranks(&mut hole.iter());
+	// Four of five ranks in a row, one of them ours, and no straight yet
+	let window = |low: u16| 0b11111u16 << low;
+	let made = (1..=10).any(|low| all_ranks & window(low) == window(low));
+	let straight_draw = !made
+		&& (1..=10).any(|low| (all_ranks & window(low)).count_ones() == 4 && hole_ranks & window(low) != 0);
+	straight_draw.then_some("coach.straight_draw")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{cards, Blinds, HandId, Position, SeatInfo, ValidActions};
+
+	fn hole(s: &str) -> [Card; 2] {
+		let c = cards(s);
+		[c[0], c[1]]
+	}
+
+	/// Hand `hand_num`: the hero is bet `bet` into 60 on the flop, and
+	/// does `action` with `hero_cards`.
+	fn hand(hand_num: u32, hero_cards: &str, flop: &str, bet: f32, action: PlayerAction) -> Vec<GameEvent> {
+		let seat = |i: usize| SeatInfo {
+			seat: Seat(i),
+			name: format!("P{}", i),
+			stack: 200.0,
+			position: Position::None,
+			is_active: true,
+			is_human: i == 0,
+			is_occupied: true,
+		};
+		let valid = ValidActions {
+			can_fold: true,
+			can_check: false,
+			call_amount: Some(bet),
+			raise_options: None,
+			can_all_in: true,
+			all_in_amount: 180.0,
+			can_show: false,
+		};
+		vec![
+			GameEvent::HandStarted {
+				hand_id: HandId(hand_num as u64),
+				hand_num,
+				button: Seat(1),
+				blinds: Blinds { small: 5.0, big: 10.0, ante: None },
+				seats: vec![seat(0), seat(1)],
+				game_id: None,
+				at: None,
+			},
+			GameEvent::HoleCardsDealt { seat: Seat(0), cards: hole(hero_cards) },
+			GameEvent::StreetChanged { street: Street::Flop, board: cards(flop), pot: 60.0, effective_stack: 170.0, pots: Vec::new() },
+			GameEvent::ActionTaken { seat: Seat(1), action: PlayerAction::Bet { amount: bet }, stack_after: 170.0 - bet, pot_after: 60.0 + bet, at: None },
+			GameEvent::ActionRequest { seat: Seat(0), valid_actions: valid, time_limit: None, at: None },
+			GameEvent::ActionTaken { seat: Seat(0), action, stack_after: 170.0, pot_after: 60.0 + bet, at: None },
+			GameEvent::HandEnded { hand_id: HandId(hand_num as u64), results: Vec::new(), at: None },
+		]
+	}
+
+	fn tips(coach: &mut Coach, events: &[GameEvent]) -> Vec<String> {
+		events.iter().filter_map(|e| coach.observe(e)).collect()
+	}
+
+	#[test]
+	fn test_folding_a_draw_at_a_good_price() {
+		let mut coach = Coach::new(Some(Seat(0)));
+		let tip = tips(&mut coach, &hand(1, "8h 3h", "2h 7h Kc", 20.0, PlayerAction::Fold));
+		assert_eq!(tip.len(), 1);
+		assert!(tip[0].contains("only 20%") && tip[0].contains("flush draw"), "{}", tip[0]);
+	}
+
+	#[test]
+	fn test_calling_with_nothing() {
+		let mut coach = Coach::new(Some(Seat(0)));
+		// A pot-sized bet needs a third; three-high on a paired board is far short
+		let tip = tips(&mut coach, &hand(1, "3c 2d", "Ks Kd 8h", 60.0, PlayerAction::Call { amount: 60.0 }));
+		assert_eq!(tip.len(), 1);
+		assert!(tip[0].contains("needed 33%"), "{}", tip[0]);
+		let tip = tips(&mut coach, &hand(10, "8h 3h", "2h 7h Kc", 20.0, PlayerAction::Call { amount: 20.0 }));
+		assert!(tip.is_empty(), "a good call gets no tip");
+	}
+
+	#[test]
+	fn test_tips_are_rate_limited() {
+		let mut coach = Coach::new(Some(Seat(0)));
+		let fold = |n| hand(n, "8h 3h", "2h 7h Kc", 20.0, PlayerAction::Fold);
+		assert_eq!(tips(&mut coach, &fold(1)).len(), 1);
+		assert!(tips(&mut coach, &fold(2)).is_empty());
+		assert_eq!(tips(&mut coach, &fold(1 + HANDS_BETWEEN_TIPS)).len(), 1);
+	}
+
+	#[test]
+	fn test_draws() {
+		assert_eq!(draw(&hole("8h 3h"), &cards("2h 7h Kc")), Some("coach.flush_draw"));
+		assert_eq!(draw(&hole("8c 9d"), &cards("Th Jh 2c")), Some("coach.straight_draw"));
+		assert_eq!(draw(&hole("Ac 2d"), &cards("3h 4s Kc")), Some("coach.straight_draw"));
+		assert_eq!(draw(&hole("Kc Kd"), &cards("Th Jh 2c")), None);
+		assert_eq!(draw(&hole("8h 3h"), &cards("2h 7h Kc 4d 9s")), None);
+	}
+}
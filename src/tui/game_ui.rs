@@ -1,23 +1,89 @@
+use std::borrow::Cow;
 use std::time::{Duration, Instant};
 
 use crossterm::event::KeyCode;
 use ratatui::{
 	layout::{Constraint, Direction, Layout, Rect},
 	style::{Modifier, Style},
-	widgets::{Block, Borders, Paragraph},
+	widgets::{Block, Borders, Clear, Paragraph},
 	Frame,
 };
+use serde::Serialize;
 
-use crate::events::{GameEvent, Seat, Standing, ValidActions};
+use crate::config::Preferences;
+use crate::i18n::{tr, tr_with};
+use crate::money::{cents, chips, money};
+use crate::net::ConnectionState;
+use crate::events::{GameEvent, GameId, HandResult, PlayerAction, Promotion, Seat, Standing, ValidActions};
+use crate::history::luck::LuckReport;
+use crate::history::{HandHistory, HandRecorder};
 use crate::players::PlayerResponse;
 use crate::theme::Theme;
-use crate::tui::input::{InputEffect, InputState};
-use crate::tui::widgets::TableWidget;
-use crate::view::TableView;
+use crate::tui::coach::Coach;
+use crate::tui::hints::{decision_hint, misclick_warning};
+use crate::tui::input::{InputEffect, InputState, PreAction, Preference};
+use crate::tui::widgets::{HelpWidget, TableWidget};
+use crate::view::{ChatMessage, PlayerStatus, Street as ViewStreet, TableView};
 use crate::events::ViewUpdater;
 
 const WINNER_HIGHLIGHT_MS: u64 = 5000;
 
+/// The terminal window's title away from the table, and the start of it
+/// at the table.
+pub const WINDOW_TITLE: &str = "transparent-poker";
+
+/// Sparkline width in the game-over luck summary.
+const LUCK_GRAPH_WIDTH: usize = 12;
+
+/// Hole cards going round the table at the start of a hand, one card at
+/// a time from the seat after the button, when `deal_delay_ms` is set.
+/// The events have all arrived; this only paces how they're shown.
+struct Dealing {
+	started: Instant,
+	per_card: Duration,
+	/// Seats dealt in, in the order they get their cards.
+	order: Vec<usize>,
+}
+
+impl Dealing {
+	fn new(button: Seat, seats: &[usize], table_size: usize, per_card: Duration, started: Instant) -> Self {
+		let mut order = seats.to_vec();
+		let size = table_size.max(1);
+		order.sort_by_key(|&seat| (seat + size - button.0 % size - 1) % size);
+		Self { started, per_card, order }
+	}
+
+	fn cards_out(&self, now: Instant) -> usize {
+		let per_card = self.per_card.as_millis().max(1);
+		(now.saturating_duration_since(self.started).as_millis() / per_card) as usize
+	}
+
+	/// How many cards `seat` has so far, or `None` once it has both or
+	/// wasn't dealt in.
+	fn cards(&self, seat: usize, now: Instant) -> Option<u8> {
+		let turn = self.order.iter().position(|&s| s == seat)?;
+		let out = self.cards_out(now);
+		let dealt = (out > turn) as u8 + (out > self.order.len() + turn) as u8;
+		(dealt < 2).then_some(dealt)
+	}
+
+	fn is_done(&self, now: Instant) -> bool {
+		self.cards_out(now) >= 2 * self.order.len()
+	}
+}
+
+/// What `--status-file` holds, as one line of JSON, for a tmux or screen
+/// status line to show.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatusLine {
+	pub table: Option<String>,
+	pub hand: u32,
+	/// The hero's, or none for a viewer.
+	pub stack: Option<f32>,
+	pub pot: f32,
+	pub your_turn: bool,
+}
+
 #[derive(Clone)]
 pub struct WinnerInfo {
 	pub seat: Seat,
@@ -29,6 +95,19 @@ pub struct WinnerInfo {
 pub enum GameUIAction {
 	None,
 	Respond(PlayerResponse),
+	Chat(String),
+	/// A preference was toggled; save it and tell the server if needed.
+	PreferencesChanged,
+	/// Move to another table at the same stakes after this hand.
+	ChangeTable,
+	/// Pause the game, or resume it if it's paused.
+	TogglePause,
+	/// Step the pace of play up or down.
+	ChangeSpeed { faster: bool },
+	/// Close the table without leaving it; `poker play --attach` comes back.
+	Detach,
+	/// Tag the hand in the header with this.
+	TagHand(String),
 	Quit,
 }
 
@@ -39,11 +118,60 @@ pub struct GameUI {
 	pub status_message: Option<String>,
 	pub last_winners: Vec<WinnerInfo>,
 	pub final_standings: Vec<Standing>,
-	_hero_seat: Option<Seat>,
+	hero_seat: Option<Seat>,
 	pub theme: Theme,
 	theme_name: String,
 	pub info_title: String,
 	pub info_lines: Vec<String>,
+	/// Show hands played, pot sizes and so on above the info lines.
+	pub live_info: bool,
+	/// Adds the blind level and players left to the live info.
+	pub tournament: bool,
+	/// The table's betting rules, for the help overlay.
+	pub rules_lines: Vec<String>,
+	/// Help drawn over the table; the game carries on underneath.
+	pub show_help: bool,
+	/// Stacks, bets and the pot in big blinds instead of chips.
+	pub big_blinds: bool,
+	/// Asked the server to skip AI pauses.
+	pub fast_forward: bool,
+	pub prefs: Preferences,
+	/// Folded, busted or sitting out this hand.
+	hero_out: bool,
+	/// Armed while waiting, sent as soon as the hero is asked to act.
+	pub pre_action: Option<PreAction>,
+	/// A misclick warning is up; doing the same again goes through.
+	warned: bool,
+	/// Follows the hero's decisions for `prefs.coaching_tips`.
+	coach: Coach,
+	/// The hands seen so far, for the all-in luck summary at the end.
+	recorder: HandRecorder,
+	hands: Vec<HandHistory>,
+	/// Promotions paid or earned at the table: who, what and how much.
+	promotions: Vec<(String, Promotion, f32)>,
+	/// Shown in the info panel once the game is over.
+	pub session_lines: Vec<String>,
+	/// A banner goes over the table while it's anything but connected.
+	pub connection: ConnectionState,
+	/// Round trip to the server, shown in the corner of the table.
+	pub latency: Option<Duration>,
+	/// When the server's clock on the hero's decision runs out, less the
+	/// time the answer takes to get there.
+	pub action_deadline: Option<Instant>,
+	/// Hot seat, between turns: the hero's cards are face down.
+	pub private: bool,
+	/// Hot seat: the screen is waiting to be handed to this player.
+	pub pass_to: Option<String>,
+	/// Playing at `poker daemon`'s server, which holds the seat while the
+	/// table is closed.
+	pub detachable: bool,
+	/// `poker watch`: every hole card the feed sends is face up.
+	pub show_all_cards: bool,
+	/// `poker watch`: the seat whose view of the table is drawn, their
+	/// cards face up and the others' down until the showdown.
+	pub perspective: Option<Seat>,
+	/// The deal being shown card by card, if it hasn't finished.
+	dealing: Option<Dealing>,
 }
 
 impl GameUI {
@@ -55,12 +183,159 @@ impl GameUI {
 			status_message: None,
 			last_winners: Vec::new(),
 			final_standings: Vec::new(),
-			_hero_seat: hero_seat,
+			hero_seat,
 			theme,
 			theme_name,
 			info_title: String::new(),
 			info_lines: Vec::new(),
+			live_info: true,
+			tournament: false,
+			rules_lines: Vec::new(),
+			show_help: false,
+			big_blinds: false,
+			fast_forward: false,
+			prefs: Preferences::default(),
+			hero_out: false,
+			pre_action: None,
+			warned: false,
+			coach: Coach::new(hero_seat),
+			recorder: HandRecorder::new(GameId(0), ""),
+			hands: Vec::new(),
+			promotions: Vec::new(),
+			session_lines: Vec::new(),
+			connection: ConnectionState::Connected,
+			latency: None,
+			action_deadline: None,
+			private: false,
+			pass_to: None,
+			detachable: false,
+			show_all_cards: false,
+			perspective: None,
+			dealing: None,
+		}
+	}
+
+	/// What the banner says, if the connection is down.
+	pub fn connection_banner(&self) -> Option<String> {
+		match self.connection {
+			ConnectionState::Connected => None,
+			ConnectionState::Reconnecting { attempt } => Some(tr_with("status.reconnecting", &[("attempt", &attempt.to_string())])),
+			ConnectionState::Lost => Some(tr("status.connection_lost")),
+		}
+	}
+
+	/// What goes over the table: a dropped connection, or whose turn it is
+	/// on a shared screen.
+	pub fn banner(&self) -> Option<String> {
+		self.connection_banner()
+			.or_else(|| self.pass_to.as_ref().map(|name| tr_with("status.pass_to", &[("name", name)])))
+	}
+
+	/// The table as drawn, with the hole cards as far round as the deal
+	/// has got.
+	fn shown_view(&self) -> Cow<'_, TableView> {
+		let mut view = self.hidden_view();
+		let now = Instant::now();
+		if let Some(dealing) = self.dealing.as_ref().filter(|d| !d.is_done(now)) {
+			for player in view.to_mut().players.iter_mut() {
+				player.dealing = dealing.cards(player.seat, now);
+			}
 		}
+		view
+	}
+
+	/// On a shared screen between turns, the hero's cards turned down
+	/// until the showdown; for a viewer, the table as the seat they're
+	/// watching sees it.
+	fn hidden_view(&self) -> Cow<'_, TableView> {
+		if let Some(seat) = self.perspective {
+			let mut view = self.table_view.clone();
+			let showdown = view.street == ViewStreet::Showdown;
+			for player in view.players.iter_mut() {
+				player.is_hero = player.seat == seat.0;
+				if !player.is_hero && !(showdown && player.status != PlayerStatus::Folded) {
+					player.hole_cards = None;
+				}
+			}
+			return Cow::Owned(view);
+		}
+		if !(self.private || self.pass_to.is_some()) || self.table_view.street == ViewStreet::Showdown {
+			return Cow::Borrowed(&self.table_view);
+		}
+		let mut view = self.table_view.clone();
+		for player in view.players.iter_mut().filter(|p| p.is_hero) {
+			player.hole_cards = None;
+		}
+		Cow::Owned(view)
+	}
+
+	/// The terminal window's title: whether it's the hero's turn, or which
+	/// hand is on, for someone who's switched to another window.
+	pub fn window_title(&self) -> String {
+		let view = &self.table_view;
+		let status = if self.connection != ConnectionState::Connected {
+			tr("title.reconnecting")
+		} else if self.input_state.is_game_over() {
+			tr("title.game_over")
+		} else if self.is_deciding() {
+			tr_with("title.your_turn", &[("pot", &money(view.pot))])
+		} else if view.paused {
+			tr("title.paused")
+		} else if view.hand_num > 0 {
+			let blinds = format!("{}/{}", chips(view.blinds.0), chips(view.blinds.1));
+			tr_with("title.hand", &[("hand", &view.hand_num.to_string()), ("blinds", &blinds)])
+		} else {
+			return WINDOW_TITLE.to_string();
+		};
+		format!("{} — {}", WINDOW_TITLE, status)
+	}
+
+	/// Where the hero is, for `--status-file`.
+	pub fn status_line(&self) -> StatusLine {
+		let view = &self.table_view;
+		StatusLine {
+			table: view.table_name.clone(),
+			hand: view.hand_num,
+			stack: view.players.iter().find(|p| p.is_hero).map(|p| p.stack),
+			pot: view.pot,
+			your_turn: self.is_deciding(),
+		}
+	}
+
+	/// Watches as the next seat dealt in, and after the last goes back to
+	/// every hand face up.
+	pub fn cycle_perspective(&mut self) {
+		let mut seats: Vec<usize> = self
+			.table_view
+			.players
+			.iter()
+			.filter(|p| !matches!(p.status, PlayerStatus::Empty | PlayerStatus::Eliminated))
+			.map(|p| p.seat)
+			.collect();
+		seats.sort_unstable();
+		let next = match self.perspective {
+			None => seats.first(),
+			Some(seat) => seats.iter().find(|&&s| s > seat.0),
+		};
+		self.perspective = next.map(|&s| Seat(s));
+	}
+
+	/// Whether the hero has a decision to make, even if they're typing a
+	/// chat line or changing preferences first.
+	pub fn is_deciding(&self) -> bool {
+		let mut state = &self.input_state;
+		while let InputState::Chatting { resume, .. } | InputState::Preferences { resume } | InputState::Tagging { resume, .. } = state {
+			state = resume;
+		}
+		matches!(state, InputState::AwaitingAction { .. } | InputState::EnteringRaise { .. })
+	}
+
+	/// Whole seconds left to act, while the hero is being asked.
+	pub fn seconds_to_act(&self, now: Instant) -> Option<u64> {
+		if !matches!(self.input_state, InputState::AwaitingAction { .. } | InputState::EnteringRaise { .. }) {
+			return None;
+		}
+		self.action_deadline.map(|deadline| deadline.saturating_duration_since(now).as_secs_f64().ceil() as u64)
 	}
 
 	pub fn set_table_info(&mut self, title: String, info: String, info_lines: Vec<String>) {
@@ -69,8 +344,70 @@ impl GameUI {
 		self.info_lines = info_lines;
 	}
 
+	/// Whether the server should skip AI pauses right now.
+	pub fn wants_fast_forward(&self) -> bool {
+		self.fast_forward || (self.prefs.fast_forward_folded && self.hero_out)
+	}
+
 	pub fn apply_event(&mut self, event: &GameEvent) {
+		// Before any showdown is skipped, so the luck summary sees every reveal
+		if let Some(hand) = self.recorder.record(event) {
+			self.hands.push(hand);
+		}
+		let tip = if self.prefs.coaching_tips { self.coach.observe(event) } else { None };
+		match event {
+			GameEvent::HandStarted { seats, button, .. } => {
+				self.hero_out = !seats.iter().any(|s| Some(s.seat) == self.hero_seat && s.is_active);
+				self.pre_action = None;
+				self.dealing = self.prefs.deal_delay_ms.filter(|_| !self.wants_fast_forward()).map(|ms| {
+					let dealt_in = seats.iter().filter(|s| s.is_active).map(|s| s.seat.0).collect::<Vec<_>>();
+					Dealing::new(*button, &dealt_in, seats.len(), Duration::from_millis(ms), Instant::now())
+				});
+			}
+			GameEvent::StreetChanged { .. } => {
+				self.pre_action = None;
+			}
+			GameEvent::ActionTaken { seat, action: PlayerAction::Fold, .. } if Some(*seat) == self.hero_seat => {
+				self.hero_out = true;
+				self.pre_action = None;
+			}
+			// A bet means checking is no longer free; the hero decides again
+			GameEvent::ActionTaken {
+				action: PlayerAction::Bet { .. } | PlayerAction::Raise { .. } | PlayerAction::AllIn { .. },
+				..
+			} if self.pre_action == Some(PreAction::Check) => {
+				self.pre_action = None;
+			}
+			// The offer to show lapses once the hand is over
+			GameEvent::HandEnded { .. }
+				if matches!(&self.input_state, InputState::AwaitingAction { valid } if valid.can_show) =>
+			{
+				self.input_state = InputState::Watching;
+				self.apply_effect(InputEffect::ClearPrompt);
+			}
+			_ => {}
+		}
+
+		// Showdowns the hero sat out are settled without turning cards over
+		let skip_reveals = self.prefs.skip_showdowns && self.hero_out;
+		let unrevealed;
+		let event = match event {
+			GameEvent::ShowdownReveal { .. } if skip_reveals => return,
+			GameEvent::HandEnded { hand_id, results, at } if skip_reveals => {
+				let results = results
+					.iter()
+					.map(|r| HandResult { showed_cards: None, ..r.clone() })
+					.collect();
+				unrevealed = GameEvent::HandEnded { hand_id: *hand_id, results, at: *at };
+				&unrevealed
+			}
+			_ => event,
+		};
+
 		self.view_updater.apply(&mut self.table_view, event);
+		if let Some(tip) = tip {
+			self.table_view.chat_messages.push(ChatMessage { sender: tr("coach.name"), text: tip, is_system: false });
+		}
 
 		match event {
 			GameEvent::HandStarted { .. } => {
@@ -88,8 +425,24 @@ impl GameUI {
 					self.table_view.winner_seats.push(seat.0);
 				}
 			}
-			GameEvent::GameEnded { final_standings, .. } => {
+			GameEvent::PromotionAwarded { seat, promotion, amount } => {
+				let name = self.table_view.players
+					.iter()
+					.find(|p| p.seat == seat.0)
+					.map_or_else(|| format!("Seat {}", seat.0 + 1), |p| p.name.clone());
+				self.promotions.push((name, promotion.clone(), *amount));
+			}
+			GameEvent::GameEnded { final_standings, detail, .. } => {
 				self.final_standings = final_standings.clone();
+				self.session_lines = session_summary(&self.final_standings, &self.hands, &self.promotions);
+				if let Some(detail) = detail {
+					let mut lines = vec![format!("The game stopped: {}", detail)];
+					if !self.session_lines.is_empty() {
+						lines.push(String::new());
+					}
+					lines.append(&mut self.session_lines);
+					self.session_lines = lines;
+				}
 				let (state, effect) = InputState::enter_game_over();
 				self.input_state = state;
 				self.apply_effect(effect);
@@ -98,23 +451,64 @@ impl GameUI {
 		}
 	}
 
-	pub fn enter_action_mode(&mut self, valid_actions: ValidActions) {
+	/// Prompts the hero to act, or returns the armed pre-action to send
+	/// straight away if it fits this request.
+	pub fn enter_action_mode(&mut self, valid_actions: ValidActions) -> Option<PlayerAction> {
+		// The hero can't decide on cards still being dealt
+		self.dealing = None;
+		if let Some(action) = self.pre_action.take().and_then(|p| p.resolve(&valid_actions)) {
+			return Some(action);
+		}
+		// Let a half-typed chat line finish; the action prompt comes back after
+		if let InputState::Chatting { resume, .. } | InputState::Preferences { resume } | InputState::Tagging { resume, .. } = &mut self.input_state {
+			**resume = InputState::AwaitingAction { valid: valid_actions };
+			return None;
+		}
 		let (state, effect) = InputState::enter_action_mode(valid_actions);
 		self.input_state = state;
 		self.apply_effect(effect);
+		None
 	}
 
 	pub fn handle_key(&mut self, key: KeyCode) -> GameUIAction {
 		let old_state = std::mem::take(&mut self.input_state);
+		let before = self.prefs.beginner_hints.then(|| old_state.clone());
 		let (new_state, effect) = old_state.handle_key(key);
+
+		// With hints on, a likely misclick needs a second press
+		if let (Some(before), InputEffect::Respond(PlayerResponse::Action(action))) = (before, &effect) {
+			let warning = match &before {
+				InputState::AwaitingAction { valid } | InputState::EnteringRaise { valid, .. } => {
+					misclick_warning(action, valid)
+				}
+				_ => None,
+			};
+			if let Some(warning) = warning.filter(|_| !self.warned) {
+				self.warned = true;
+				self.input_state = before;
+				self.status_message = Some(warning);
+				return GameUIAction::None;
+			}
+		}
+		self.warned = false;
 		self.input_state = new_state;
 		self.process_effect(effect)
 	}
 
+	/// Adds the beginner hint to the action prompt when it's on.
+	fn with_hint(&self, prompt: String) -> String {
+		match &self.input_state {
+			InputState::AwaitingAction { valid } if self.prefs.beginner_hints && !valid.can_show => {
+				format!("{}  · {}", prompt, decision_hint(valid, self.table_view.pot))
+			}
+			_ => prompt,
+		}
+	}
+
 	fn apply_effect(&mut self, effect: InputEffect) {
 		match effect {
 			InputEffect::SetPrompt(prompt) => {
-				self.status_message = Some(prompt);
+				self.status_message = Some(self.with_hint(prompt));
 			}
 			InputEffect::ClearPrompt => {
 				self.status_message = None;
@@ -130,7 +524,7 @@ impl GameUI {
 		match effect {
 			InputEffect::None => GameUIAction::None,
 			InputEffect::SetPrompt(prompt) => {
-				self.status_message = Some(prompt);
+				self.status_message = Some(self.with_hint(prompt));
 				GameUIAction::None
 			}
 			InputEffect::ClearPrompt => {
@@ -141,6 +535,59 @@ impl GameUI {
 				self.status_message = None;
 				GameUIAction::Respond(response)
 			}
+			InputEffect::SendChat(text) => {
+				self.status_message = self.input_state.prompt();
+				GameUIAction::Chat(text)
+			}
+			InputEffect::ArmPreAction(pre_action) => {
+				self.pre_action = (self.pre_action != Some(pre_action)).then_some(pre_action);
+				self.status_message = None;
+				GameUIAction::None
+			}
+			InputEffect::ToggleHelp => {
+				self.show_help = !self.show_help;
+				GameUIAction::None
+			}
+			InputEffect::ToggleBigBlinds => {
+				self.big_blinds = !self.big_blinds;
+				GameUIAction::None
+			}
+			InputEffect::ChangeTable => {
+				self.status_message = Some(tr("status.changing_tables"));
+				GameUIAction::ChangeTable
+			}
+			InputEffect::ShowPreferences => {
+				self.status_message = Some(preferences_prompt(&self.prefs));
+				GameUIAction::None
+			}
+			InputEffect::TogglePreference(preference) => {
+				let setting = match preference {
+					Preference::SkipShowdowns => &mut self.prefs.skip_showdowns,
+					Preference::AutoMuck => &mut self.prefs.auto_muck,
+					Preference::FastForwardFolded => &mut self.prefs.fast_forward_folded,
+					Preference::BeginnerHints => &mut self.prefs.beginner_hints,
+					Preference::CoachingTips => &mut self.prefs.coaching_tips,
+				};
+				*setting = !*setting;
+				self.status_message = Some(preferences_prompt(&self.prefs));
+				GameUIAction::PreferencesChanged
+			}
+			InputEffect::ToggleFastForward => {
+				self.fast_forward = !self.fast_forward;
+				self.status_message = self.input_state.prompt();
+				GameUIAction::None
+			}
+			InputEffect::TogglePause => GameUIAction::TogglePause,
+			InputEffect::ChangeSpeed { faster } => GameUIAction::ChangeSpeed { faster },
+			InputEffect::Detach if self.detachable => GameUIAction::Detach,
+			InputEffect::Detach => {
+				self.status_message = Some(tr("status.cant_detach"));
+				GameUIAction::None
+			}
+			InputEffect::TagHand(tag) => {
+				self.status_message = self.input_state.prompt();
+				GameUIAction::TagHand(tag)
+			}
 			InputEffect::CycleTheme => {
 				self.cycle_theme();
 				GameUIAction::None
@@ -164,14 +611,31 @@ impl GameUI {
 		let next_name = &available[next_idx];
 
 		if let Ok(new_theme) = Theme::load_named(next_name) {
-			self.theme = new_theme;
+			self.theme = new_theme.with_palette(self.theme.palette);
 			self.theme_name = next_name.clone();
 			if !self.input_state.is_awaiting_input() {
-				self.status_message = Some(format!("Theme: {}", next_name));
+				self.status_message = Some(tr_with("status.theme", &[("name", next_name)]));
 			}
 		}
 	}
 
+	/// The info panel: the live numbers once a hand has been dealt, then
+	/// the table's own lines.
+	pub fn panel_lines(&self) -> Vec<String> {
+		let mut lines = self.session_lines.clone();
+		if !lines.is_empty() {
+			lines.push(String::new());
+		}
+		if !self.live_info || self.table_view.info.blind_level == 0 {
+			lines.extend(self.info_lines.iter().cloned());
+			return lines;
+		}
+		lines.extend(self.table_view.info.lines(self.table_view.blinds, self.tournament));
+		lines.push(String::new());
+		lines.extend(self.info_lines.iter().cloned());
+		lines
+	}
+
 	pub fn render(&self, frame: &mut Frame, area: Rect) {
 		let bg = Block::default().style(Style::default().bg(self.theme.background()));
 		frame.render_widget(bg, area);
@@ -189,8 +653,14 @@ impl GameUI {
 		let winner_area = layout[1];
 		let status_area = layout[2];
 
-		let table_widget = TableWidget::new(&self.table_view, &self.theme)
-			.with_info(&self.info_title, &self.info_lines);
+		let info_lines = self.panel_lines();
+		let view = self.shown_view();
+		let mut table_widget = TableWidget::new(&view, &self.theme)
+			.with_info(&self.info_title, &info_lines)
+			.in_big_blinds(self.big_blinds);
+		if self.show_all_cards && self.perspective.is_none() {
+			table_widget = table_widget.show_all_cards(true);
+		}
 		frame.render_widget(table_widget, table_area);
 
 		// Winner display
@@ -205,10 +675,11 @@ impl GameUI {
 						.find(|p| p.seat == w.seat.0)
 						.map(|p| p.name.as_str())
 						.unwrap_or("???");
+					let amount = chips(w.amount);
 					if let Some(desc) = &w.description {
-						format!("{} wins ${:.0} ({})", name, w.amount, desc)
+						tr_with("status.wins_with", &[("name", name), ("amount", &amount), ("hand", desc)])
 					} else {
-						format!("{} wins ${:.0}", name, w.amount)
+						tr_with("status.wins", &[("name", name), ("amount", &amount)])
 					}
 				})
 				.collect::<Vec<_>>()
@@ -227,26 +698,62 @@ impl GameUI {
 
 		let winner = Paragraph::new(winner_text)
 			.style(winner_style)
-			.block(Block::default().borders(Borders::ALL).title("Result"));
+			.block(Block::default().borders(Borders::ALL).title(tr("status.result")));
 		frame.render_widget(winner, winner_area);
 
 		// Status bar
 		let (status_text, status_title, status_style, border_style) = match &self.input_state {
 			InputState::AwaitingAction { .. } | InputState::EnteringRaise { .. } => (
 				self.status_message.clone().unwrap_or_default(),
-				" Your Turn ",
+				match self.seconds_to_act(Instant::now()) {
+					Some(seconds) => tr_with("status.your_turn_timed", &[("seconds", &seconds.to_string())]),
+					None => tr("status.your_turn"),
+				},
 				Style::default().fg(self.theme.status_your_turn()).add_modifier(Modifier::BOLD),
 				Style::default().fg(self.theme.status_your_turn_border()),
 			),
+			InputState::Chatting { .. } => (
+				self.status_message.clone().unwrap_or_default(),
+				tr("status.chat"),
+				Style::default().fg(self.theme.status_watching()),
+				Style::default().fg(self.theme.status_your_turn_border()),
+			),
+			InputState::Tagging { .. } => (
+				self.status_message.clone().unwrap_or_default(),
+				tr("status.tag"),
+				Style::default().fg(self.theme.status_watching()),
+				Style::default().fg(self.theme.status_your_turn_border()),
+			),
+			InputState::Preferences { .. } => (
+				self.status_message.clone().unwrap_or_default(),
+				tr("status.preferences"),
+				Style::default().fg(self.theme.status_watching()),
+				Style::default().fg(self.theme.status_your_turn_border()),
+			),
 			InputState::GameOver => (
-				self.status_message.clone().unwrap_or_else(|| "Game Over!".to_string()),
-				" Game Over ",
+				self.status_message.clone().unwrap_or_else(|| tr("status.game_over")),
+				tr("status.game_over_title"),
 				Style::default().fg(self.theme.status_game_over()).add_modifier(Modifier::BOLD),
 				Style::default().fg(self.theme.status_game_over_border()),
 			),
 			_ => (
-				self.status_message.clone().unwrap_or_else(|| "Watching...".to_string()),
-				" Status ",
+				self.status_message.clone().unwrap_or_else(|| {
+					if self.table_view.paused {
+						tr("status.paused")
+					} else if let Some(pre_action) = self.pre_action {
+						tr_with("status.armed", &[("action", &pre_action.label()), ("key", &pre_action.key().to_string())])
+					} else if self.fast_forward {
+						tr("status.fast_forwarding")
+					} else if let Some(seat) = self.perspective {
+						let name = self.table_view.players.iter().find(|p| p.seat == seat.0).map(|p| p.name.clone());
+						tr_with("status.viewing_as", &[("name", &name.unwrap_or_default())])
+					} else if self.show_all_cards {
+						tr("status.viewing_all")
+					} else {
+						tr("status.watching")
+					}
+				}),
+				tr("status.title"),
 				Style::default().fg(self.theme.status_watching()),
 				Style::default().fg(self.theme.status_watching_border()),
 			),
@@ -261,5 +768,515 @@ impl GameUI {
 					.title(status_title),
 			);
 		frame.render_widget(status, status_area);
+
+		if let Some(latency) = self.latency {
+			let text = tr_with("status.latency", &[("ms", &latency.as_millis().to_string())]);
+			let width = (text.chars().count() as u16).min(table_area.width.saturating_sub(2));
+			let latency_area = Rect {
+				x: table_area.x + table_area.width.saturating_sub(width + 1),
+				y: table_area.y,
+				width,
+				height: 1.min(table_area.height),
+			};
+			let latency = Paragraph::new(text).style(Style::default().fg(self.theme.status_watching()));
+			frame.render_widget(latency, latency_area);
+		}
+
+		if self.show_help {
+			frame.render_widget(HelpWidget::new(&self.rules_lines, &self.theme), table_area);
+		}
+
+		if let Some(text) = self.banner() {
+			let width = (text.chars().count() as u16 + 4).min(table_area.width);
+			let banner_area = Rect {
+				x: table_area.x + (table_area.width - width) / 2,
+				y: table_area.y + table_area.height.saturating_sub(3) / 2,
+				width,
+				height: 3.min(table_area.height),
+			};
+			let banner = Paragraph::new(text)
+				.alignment(ratatui::layout::Alignment::Center)
+				.style(Style::default().fg(self.theme.status_game_over()).add_modifier(Modifier::BOLD))
+				.block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(self.theme.status_game_over_border())));
+			frame.render_widget(Clear, banner_area);
+			frame.render_widget(banner, banner_area);
+		}
+	}
+}
+
+/// The final standings, all-in luck over the game if anyone was all-in
+/// with cards to come, then what the table's promotions paid.
+fn session_summary(standings: &[Standing], hands: &[HandHistory], promotions: &[(String, Promotion, f32)]) -> Vec<String> {
+	let mut lines = Vec::new();
+	if !standings.is_empty() {
+		let mut standings = standings.iter().collect::<Vec<_>>();
+		standings.sort_by_key(|s| s.finish_position);
+		lines.push("Final standings:".to_string());
+		for standing in standings {
+			lines.push(format!("  {}. {} {}", standing.finish_position, standing.name, chips(standing.final_stack)));
+		}
+	}
+	let report = LuckReport::from_hands(hands);
+	if report.all_ins() > 0 {
+		if !lines.is_empty() {
+			lines.push(String::new());
+		}
+		lines.push(format!("All-in luck ({} hands):", hands.len()));
+		lines.extend(report.graph_lines(LUCK_GRAPH_WIDTH));
+	}
+	if promotions.is_empty() {
+		return lines;
+	}
+	if !lines.is_empty() {
+		lines.push(String::new());
+	}
+	lines.push("Promotions:".to_string());
+	for (name, promotion, amount) in promotions {
+		match promotion {
+			Promotion::HighHand { hand } => lines.push(format!("  High hand, {}: {} {}", hand, name, money(*amount))),
+			Promotion::Jackpot => lines.push(format!("  Bad-beat jackpot: {} {}", name, money(*amount))),
+			Promotion::Challenge { goal } => lines.push(format!("  Daily challenge, {}: {} {}", goal, name, money(*amount))),
+			Promotion::Rakeback => {}
+		}
+	}
+	// Rakeback adds up hand by hand, so one line per player
+	let mut rakeback: Vec<(&str, f32)> = Vec::new();
+	for (name, _, amount) in promotions.iter().filter(|(_, p, _)| *p == Promotion::Rakeback) {
+		match rakeback.iter_mut().find(|(n, _)| n == name) {
+			Some((_, total)) => *total += amount,
+			None => rakeback.push((name, *amount)),
+		}
+	}
+	for (name, total) in rakeback {
+		lines.push(format!("  Rakeback, paid weekly: {} {}", name, cents(total)));
+	}
+	lines
+}
+
+fn preferences_prompt(prefs: &Preferences) -> String {
+	let mark = |on: bool| tr(if on { "prompt.on" } else { "prompt.off" });
+	tr_with("prompt.preferences", &[
+		("skip", &mark(prefs.skip_showdowns)),
+		("muck", &mark(prefs.auto_muck)),
+		("fast", &mark(prefs.fast_forward_folded)),
+		("hints", &mark(prefs.beginner_hints)),
+		("coach", &mark(prefs.coaching_tips)),
+	])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{BlindType, Blinds, Card, HandId, Position, SeatInfo, Street};
+
+	fn hand_started() -> GameEvent {
+		let seat = |i: usize| SeatInfo {
+			seat: Seat(i),
+			name: format!("P{}", i),
+			stack: 100.0,
+			position: Position::None,
+			is_active: true,
+			is_human: i == 0,
+			is_occupied: true,
+		};
+		GameEvent::HandStarted {
+			hand_id: HandId(1),
+			hand_num: 1,
+			button: Seat(0),
+			blinds: Blinds { small: 1.0, big: 2.0, ante: None },
+			seats: vec![seat(0), seat(1), seat(2)],
+			at: None,
+			game_id: None,
+		}
+	}
+
+	#[test]
+	fn test_info_panel_keeps_live_numbers() {
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "default".into());
+		ui.set_table_info("Table".into(), "info".into(), vec!["Format: Sit & Go".into()]);
+		ui.tournament = true;
+		assert_eq!(ui.panel_lines(), vec!["Format: Sit & Go"]);
+
+		let award = |amount: f32| GameEvent::PotAwarded {
+			seat: Seat(1),
+			amount,
+			hand_description: None,
+			best_five: Vec::new(),
+			pot_type: crate::events::PotType::Main,
+		};
+		let ended = GameEvent::HandEnded { hand_id: HandId(1), results: Vec::new(), at: None };
+		ui.apply_event(&hand_started());
+		ui.apply_event(&award(30.0));
+		ui.apply_event(&ended);
+		let GameEvent::HandStarted { hand_id, hand_num, button, seats, .. } = hand_started() else {
+			unreachable!()
+		};
+		ui.apply_event(&GameEvent::HandStarted {
+			hand_id,
+			hand_num,
+			button,
+			blinds: Blinds { small: 2.0, big: 4.0, ante: None },
+			seats,
+			at: None,
+			game_id: None,
+		});
+		ui.apply_event(&award(80.0));
+		ui.apply_event(&award(10.0));
+		ui.apply_event(&ended);
+		ui.apply_event(&GameEvent::PlayerLeft { seat: Seat(2), reason: crate::events::LeaveReason::Eliminated });
+
+		assert_eq!(ui.panel_lines(), vec![
+			"Level 2: $2/$4",
+			"Players left: 2",
+			"Hands played: 2",
+			"Average pot: $60",
+			"Biggest pot: $90",
+			"",
+			"Format: Sit & Go",
+		]);
+	}
+
+	#[test]
+	fn test_session_summary_starts_with_the_standings() {
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "default".into());
+		ui.apply_event(&hand_started());
+		let standing = |seat, name: &str, final_stack, finish_position| Standing {
+			seat: Seat(seat),
+			name: name.to_string(),
+			final_stack,
+			finish_position,
+		};
+		ui.apply_event(&GameEvent::GameEnded {
+			reason: crate::events::GameEndReason::Winner,
+			final_standings: vec![standing(1, "P1", 0.0, 2), standing(0, "P0", 300.0, 1)],
+			detail: None,
+		});
+
+		assert_eq!(ui.session_lines, vec!["Final standings:", "  1. P0 $300", "  2. P1 $0"]);
+		assert_eq!(ui.panel_lines()[..3], ui.session_lines[..]);
+	}
+
+	#[test]
+	fn test_session_summary_lists_promotions() {
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "default".into());
+		ui.apply_event(&hand_started());
+		let rakeback = |seat| GameEvent::PromotionAwarded { seat: Seat(seat), promotion: Promotion::Rakeback, amount: 0.5 };
+		ui.apply_event(&rakeback(0));
+		ui.apply_event(&rakeback(1));
+		ui.apply_event(&GameEvent::PromotionAwarded {
+			seat: Seat(1),
+			promotion: Promotion::HighHand { hand: "four of a kind".into() },
+			amount: 50.0,
+		});
+		ui.apply_event(&rakeback(0));
+		ui.apply_event(&GameEvent::PromotionAwarded { seat: Seat(2), promotion: Promotion::Jackpot, amount: 125.0 });
+		ui.apply_event(&GameEvent::GameEnded {
+			reason: crate::events::GameEndReason::AllPlayersLeft,
+			final_standings: Vec::new(),
+			detail: None,
+		});
+
+		let lines = &ui.session_lines;
+		assert_eq!(lines[0], "Promotions:");
+		assert_eq!(lines[1], "  High hand, four of a kind: P1 $50");
+		assert_eq!(lines[2], "  Bad-beat jackpot: P2 $125");
+		assert_eq!(lines[3], "  Rakeback, paid weekly: P0 $1.00");
+		assert_eq!(lines[4], "  Rakeback, paid weekly: P1 $0.50");
+		assert_eq!(lines.len(), 5);
+	}
+
+	#[test]
+	fn test_dollar_key_shows_stacks_in_big_blinds() {
+		let screen = |ui: &GameUI| {
+			let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(120, 40)).unwrap();
+			terminal.draw(|frame| ui.render(frame, frame.area())).unwrap();
+			let buffer = terminal.backend().buffer();
+			buffer.content().iter().map(|cell| cell.symbol()).collect::<String>()
+		};
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "default".into());
+		ui.apply_event(&hand_started());
+		assert!(screen(&ui).contains("$100") && !screen(&ui).contains("50 BB"));
+
+		ui.handle_key(KeyCode::Char('$'));
+		assert!(screen(&ui).contains("50 BB") && !screen(&ui).contains("$100"));
+	}
+
+	#[test]
+	fn test_center_shows_side_pots_and_antes() {
+		let screen = |ui: &GameUI| {
+			let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(120, 40)).unwrap();
+			terminal.draw(|frame| ui.render(frame, frame.area())).unwrap();
+			let buffer = terminal.backend().buffer();
+			buffer.content().iter().map(|cell| cell.symbol()).collect::<String>()
+		};
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "default".into());
+		ui.apply_event(&hand_started());
+		for seat in 0..3 {
+			ui.apply_event(&GameEvent::BlindPosted { seat: Seat(seat), blind_type: BlindType::Ante, amount: 1.0 });
+		}
+		assert_eq!(ui.table_view.players[0].current_bet, 0.0, "an ante isn't a bet");
+		ui.apply_event(&GameEvent::StreetChanged {
+			street: Street::Flop,
+			board: vec![Card::new('K', 'h'), Card::new('8', 'c'), Card::new('3', 's')],
+			pot: 90.0,
+			effective_stack: 40.0,
+			pots: Vec::new(),
+		});
+		assert!(screen(&ui).contains("SPR 0.4"));
+		assert!(screen(&ui).contains("Antes $3"));
+
+		ui.apply_event(&GameEvent::StreetChanged {
+			street: Street::Turn,
+			board: vec![Card::new('K', 'h'), Card::new('8', 'c'), Card::new('3', 's'), Card::new('2', 'd')],
+			pot: 90.0,
+			effective_stack: 0.0,
+			pots: vec![60.0, 30.0],
+		});
+		let shown = screen(&ui);
+		assert!(shown.contains("Main $60  Side $30") && !shown.contains("SPR"), "{}", shown);
+	}
+
+	fn revealed(ui: &GameUI, seat: usize) -> bool {
+		ui.table_view.players.iter().any(|p| p.seat == seat && p.hole_cards.is_some())
+	}
+
+	#[test]
+	fn test_folded_hero_skips_showdown_and_fast_forwards() {
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "default".into());
+		ui.prefs = Preferences { skip_showdowns: true, fast_forward_folded: true, ..Preferences::default() };
+		let reveal = GameEvent::ShowdownReveal {
+			reveals: vec![(Seat(1), [Card::new('A', 's'), Card::new('K', 's')])],
+		};
+
+		ui.apply_event(&hand_started());
+		assert!(!ui.wants_fast_forward());
+		ui.apply_event(&GameEvent::ActionTaken {
+			seat: Seat(0),
+			action: PlayerAction::Fold,
+			stack_after: 100.0,
+			pot_after: 3.0,
+			at: None,
+		});
+		assert!(ui.wants_fast_forward());
+		ui.apply_event(&reveal);
+		assert!(!revealed(&ui, 1));
+
+		// Back in the next hand, so its showdown is shown
+		ui.apply_event(&hand_started());
+		assert!(!ui.wants_fast_forward());
+		ui.apply_event(&reveal);
+		assert!(revealed(&ui, 1));
+	}
+
+	#[test]
+	fn test_pre_action_fires_or_is_cancelled_by_a_bet() {
+		let free = ValidActions {
+			can_fold: false,
+			can_check: true,
+			call_amount: None,
+			raise_options: None,
+			can_all_in: true,
+			all_in_amount: 100.0,
+			can_show: false,
+		};
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "default".into());
+		ui.apply_event(&hand_started());
+
+		ui.process_effect(InputEffect::ArmPreAction(PreAction::Check));
+		assert!(matches!(ui.enter_action_mode(free.clone()), Some(PlayerAction::Check)));
+		assert!(ui.pre_action.is_none());
+
+		// Pressing the same key again disarms it
+		ui.process_effect(InputEffect::ArmPreAction(PreAction::Check));
+		ui.process_effect(InputEffect::ArmPreAction(PreAction::Check));
+		assert!(ui.pre_action.is_none());
+
+		ui.process_effect(InputEffect::ArmPreAction(PreAction::Check));
+		ui.apply_event(&GameEvent::ActionTaken {
+			seat: Seat(1),
+			action: PlayerAction::Bet { amount: 6.0 },
+			stack_after: 94.0,
+			pot_after: 9.0,
+			at: None,
+		});
+		assert!(ui.pre_action.is_none());
+		assert!(ui.enter_action_mode(free).is_none());
+		assert!(matches!(ui.input_state, InputState::AwaitingAction { .. }));
+	}
+
+	#[test]
+	fn test_beginner_hints_explain_and_double_check() {
+		let facing_bet = ValidActions {
+			can_fold: true,
+			can_check: false,
+			call_amount: Some(20.0),
+			raise_options: Some(crate::events::RaiseOptions::Variable { min_raise: 40.0, max_raise: 100.0 }),
+			can_all_in: true,
+			all_in_amount: 100.0,
+			can_show: false,
+		};
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "default".into());
+		ui.prefs.beginner_hints = true;
+		ui.apply_event(&hand_started());
+		ui.table_view.pot = 80.0;
+
+		ui.enter_action_mode(facing_bet.clone());
+		assert!(ui.status_message.as_ref().unwrap().contains("you need 20% equity"));
+
+		ui.input_state = InputState::EnteringRaise { valid: facing_bet, amount: 95.0, min: 40.0, max: 100.0 };
+		assert!(matches!(ui.handle_key(KeyCode::Enter), GameUIAction::None));
+		assert!(ui.status_message.as_ref().unwrap().contains("just $5 behind"));
+		assert!(matches!(
+			ui.handle_key(KeyCode::Enter),
+			GameUIAction::Respond(PlayerResponse::Action(PlayerAction::Raise { amount })) if amount == 95.0
+		));
+	}
+
+	#[test]
+	fn test_hot_seat_hides_the_heros_cards_between_turns() {
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "default".into());
+		ui.apply_event(&hand_started());
+		ui.apply_event(&GameEvent::HoleCardsDealt { seat: Seat(0), cards: [Card::new('A', 's'), Card::new('A', 'h')] });
+		let hero_cards = |ui: &GameUI| ui.shown_view().players[0].hole_cards.is_some();
+		assert!(hero_cards(&ui));
+
+		ui.private = true;
+		assert!(!hero_cards(&ui));
+		assert!(revealed(&ui, 0));
+
+		ui.private = false;
+		ui.pass_to = Some("Alice".into());
+		assert!(!hero_cards(&ui));
+		assert_eq!(ui.banner().unwrap(), "Pass to Alice, press any key to reveal");
+	}
+
+	#[test]
+	fn test_action_clock_counts_down_only_while_asked() {
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "default".into());
+		let now = Instant::now();
+		ui.action_deadline = Some(now + Duration::from_millis(9_500));
+		assert_eq!(ui.seconds_to_act(now), None);
+
+		ui.enter_action_mode(ValidActions {
+			can_fold: false,
+			can_check: true,
+			call_amount: None,
+			raise_options: None,
+			can_all_in: false,
+			all_in_amount: 0.0,
+			can_show: false,
+		});
+		assert_eq!(ui.seconds_to_act(now), Some(10));
+		assert_eq!(ui.seconds_to_act(now + Duration::from_secs(20)), Some(0));
+		assert!(matches!(ui.handle_key(KeyCode::Char('c')), GameUIAction::Respond(_)));
+		assert_eq!(ui.seconds_to_act(now), None);
+	}
+
+	#[test]
+	fn test_viewer_cycles_through_each_players_view() {
+		let mut ui = GameUI::new(None, Theme::default(), "default".into());
+		ui.show_all_cards = true;
+		ui.apply_event(&hand_started());
+		ui.apply_event(&GameEvent::HoleCardsDealt { seat: Seat(0), cards: [Card::new('A', 's'), Card::new('A', 'h')] });
+		ui.apply_event(&GameEvent::HoleCardsDealt { seat: Seat(2), cards: [Card::new('K', 'd'), Card::new('K', 'c')] });
+		let face_up = |ui: &GameUI| {
+			ui.shown_view().players.iter().filter(|p| p.hole_cards.is_some()).map(|p| p.seat).collect::<Vec<_>>()
+		};
+		assert_eq!(face_up(&ui), vec![0, 2]);
+
+		ui.cycle_perspective();
+		assert_eq!(ui.perspective, Some(Seat(0)));
+		assert_eq!(face_up(&ui), vec![0]);
+		assert!(ui.shown_view().players.iter().find(|p| p.seat == 0).unwrap().is_hero);
+
+		ui.cycle_perspective();
+		ui.cycle_perspective();
+		assert_eq!(face_up(&ui), vec![2]);
+		ui.cycle_perspective();
+		assert_eq!(ui.perspective, None);
+		assert_eq!(face_up(&ui), vec![0, 2]);
+	}
+
+	#[test]
+	fn test_deal_goes_round_from_the_seat_after_the_button() {
+		let start = Instant::now();
+		let at = |ms: u64| start + Duration::from_millis(ms);
+		// Button on seat 4 of six; seat 2 sits out
+		let dealing = Dealing::new(Seat(4), &[0, 1, 3, 4, 5], 6, Duration::from_millis(100), start);
+		assert_eq!(dealing.order, vec![5, 0, 1, 3, 4]);
+		let cards = |now: Instant| [5, 0, 1, 3, 4].map(|seat| dealing.cards(seat, now));
+		assert_eq!(cards(at(0)), [Some(0); 5]);
+		assert_eq!(cards(at(250)), [Some(1), Some(1), Some(0), Some(0), Some(0)]);
+		assert_eq!(cards(at(600)), [None, Some(1), Some(1), Some(1), Some(1)]);
+		assert_eq!(dealing.cards(2, at(0)), None);
+		assert!(!dealing.is_done(at(999)));
+		assert!(dealing.is_done(at(1000)));
+	}
+
+	#[test]
+	fn test_deal_pacing_is_a_preference_and_gives_way_to_the_hero() {
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "default".into());
+		let dealt = |ui: &GameUI| ui.shown_view().players.iter().map(|p| p.dealing).collect::<Vec<_>>();
+		ui.apply_event(&hand_started());
+		assert_eq!(dealt(&ui), [None; 3]);
+
+		ui.prefs.deal_delay_ms = Some(60_000);
+		ui.apply_event(&hand_started());
+		ui.apply_event(&GameEvent::HoleCardsDealt { seat: Seat(0), cards: [Card::new('A', 's'), Card::new('A', 'h')] });
+		assert_eq!(dealt(&ui), [Some(0); 3]);
+		assert!(ui.table_view.players[0].hole_cards.is_some());
+
+		ui.enter_action_mode(ValidActions {
+			can_fold: true,
+			can_check: false,
+			call_amount: Some(2.0),
+			raise_options: None,
+			can_all_in: true,
+			all_in_amount: 100.0,
+			can_show: false,
+		});
+		assert_eq!(dealt(&ui), [None; 3]);
+
+		ui.fast_forward = true;
+		ui.apply_event(&hand_started());
+		assert_eq!(dealt(&ui), [None; 3]);
+	}
+
+	#[test]
+	fn test_window_title_says_when_its_the_heros_turn() {
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "default".into());
+		assert_eq!(ui.window_title(), "transparent-poker");
+		ui.apply_event(&hand_started());
+		assert_eq!(ui.window_title(), "transparent-poker — Hand 1, blinds $1/$2");
+
+		ui.table_view.pot = 120.0;
+		ui.enter_action_mode(ValidActions {
+			can_fold: true,
+			can_check: false,
+			call_amount: Some(2.0),
+			raise_options: None,
+			can_all_in: true,
+			all_in_amount: 100.0,
+			can_show: false,
+		});
+		assert_eq!(ui.window_title(), "transparent-poker — Your turn! ($120 pot)");
+
+		ui.connection = ConnectionState::Reconnecting { attempt: 2 };
+		assert_eq!(ui.window_title(), "transparent-poker — Reconnecting...");
+	}
+
+	#[test]
+	fn test_status_line_for_tmux() {
+		let mut ui = GameUI::new(Some(Seat(1)), Theme::default(), "default".into());
+		ui.set_table_info("Home game".into(), String::new(), Vec::new());
+		ui.apply_event(&hand_started());
+		ui.table_view.pot = 3.0;
+		let line = ui.status_line();
+		assert_eq!(line, StatusLine { table: Some("Home game".into()), hand: 1, stack: Some(100.0), pot: 3.0, your_turn: false });
+		assert_eq!(
+			serde_json::to_string(&line).unwrap(),
+			r#"{"table":"Home game","hand":1,"stack":100.0,"pot":3.0,"your_turn":false}"#
+		);
+		assert_eq!(GameUI::new(None, Theme::default(), "default".into()).status_line().stack, None);
 	}
 }
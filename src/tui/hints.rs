@@ -0,0 +1,79 @@
+//! Beginner hints: plain-language notes on the decision in front of the
+//! hero, and a second look at actions that are probably misclicks.
+
+use crate::events::{PlayerAction, RaiseOptions, ValidActions};
+use crate::money::chips;
+
+/// Raising to at least this share of the stack, but not all of it, is
+/// more likely a fumbled all-in than a plan.
+const NEAR_ALL_IN: f32 = 0.9;
+
+/// What calling or checking means here. `pot` is everything in the
+/// middle, bets on this street included.
+pub fn decision_hint(valid: &ValidActions, pot: f32) -> String {
+	match valid.call_amount {
+		Some(cost) => {
+			let equity = cost / (pot + cost) * 100.0;
+			format!(
+				"Calling costs {} into a {} pot: you need {:.0}% equity to break even",
+				chips(cost), chips(pot), equity
+			)
+		}
+		None => "Checking is free: you see what comes next without adding chips".to_string(),
+	}
+}
+
+/// Why `action` looks like a mistake, if it does.
+pub fn misclick_warning(action: &PlayerAction, valid: &ValidActions) -> Option<String> {
+	let amount = match action {
+		PlayerAction::Bet { amount } | PlayerAction::Raise { amount } => *amount,
+		_ => return None,
+	};
+	if !matches!(valid.raise_options, Some(RaiseOptions::Variable { .. })) || !valid.can_all_in {
+		return None;
+	}
+	let behind = valid.all_in_amount - amount;
+	if behind <= 0.0 || amount < valid.all_in_amount * NEAR_ALL_IN {
+		return None;
+	}
+	Some(format!(
+		"That leaves you just {} behind. Enter again to raise to {} anyway, Esc to change it",
+		chips(behind), chips(amount)
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn facing(call_amount: Option<f32>) -> ValidActions {
+		ValidActions {
+			can_fold: call_amount.is_some(),
+			can_check: call_amount.is_none(),
+			call_amount,
+			raise_options: Some(RaiseOptions::Variable { min_raise: 40.0, max_raise: 200.0 }),
+			can_all_in: true,
+			all_in_amount: 200.0,
+			can_show: false,
+		}
+	}
+
+	#[test]
+	fn test_decision_hint_gives_pot_odds() {
+		assert_eq!(
+			decision_hint(&facing(Some(20.0)), 80.0),
+			"Calling costs $20 into a $80 pot: you need 20% equity to break even"
+		);
+		assert!(decision_hint(&facing(None), 30.0).starts_with("Checking is free"));
+	}
+
+	#[test]
+	fn test_misclick_warning_catches_near_all_in_raises() {
+		let valid = facing(Some(20.0));
+		let warning = misclick_warning(&PlayerAction::Raise { amount: 198.0 }, &valid).unwrap();
+		assert!(warning.contains("just $2 behind"));
+		assert!(misclick_warning(&PlayerAction::Raise { amount: 200.0 }, &valid).is_none());
+		assert!(misclick_warning(&PlayerAction::Raise { amount: 120.0 }, &valid).is_none());
+		assert!(misclick_warning(&PlayerAction::Call { amount: 20.0 }, &valid).is_none());
+	}
+}
@@ -1,9 +1,13 @@
 use crossterm::event::KeyCode;
 use crate::events::{PlayerAction, RaiseOptions, ValidActions};
 use crate::players::PlayerResponse;
+use crate::history::tags::REVIEW_LATER;
+use crate::i18n::{tr, tr_with};
+use crate::money::chips;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum InputState {
+	#[default]
 	Watching,
 	AwaitingAction { valid: ValidActions },
 	EnteringRaise {
@@ -12,25 +16,98 @@ pub enum InputState {
 		min: f32,
 		max: f32,
 	},
+	/// Typing a chat line; `resume` is the state to go back to.
+	Chatting { text: String, resume: Box<InputState> },
+	/// Toggling preferences; `resume` is the state to go back to.
+	Preferences { resume: Box<InputState> },
+	/// Typing a tag for the hand; `resume` is the state to go back to.
+	Tagging { text: String, resume: Box<InputState> },
 	GameOver,
 }
 
+/// An action armed before the hero's turn, taken as soon as it comes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreAction {
+	/// Check if free, otherwise fold.
+	CheckFold,
+	/// Check if free; a bet cancels it.
+	Check,
+	/// Call whatever it costs, all-in if need be.
+	CallAny,
+}
+
+impl PreAction {
+	pub fn label(&self) -> String {
+		tr(match self {
+			PreAction::CheckFold => "pre_action.check_fold",
+			PreAction::Check => "pre_action.check",
+			PreAction::CallAny => "pre_action.call_any",
+		})
+	}
+
+	pub fn key(&self) -> char {
+		match self {
+			PreAction::CheckFold => '1',
+			PreAction::Check => '2',
+			PreAction::CallAny => '3',
+		}
+	}
+
+	/// The action to send for this request, or `None` to ask the hero.
+	pub fn resolve(&self, valid: &ValidActions) -> Option<PlayerAction> {
+		match self {
+			_ if valid.can_show => None,
+			_ if valid.can_check => Some(PlayerAction::Check),
+			PreAction::CheckFold if valid.can_fold => Some(PlayerAction::Fold),
+			PreAction::CallAny => match valid.call_amount {
+				Some(amount) => Some(PlayerAction::Call { amount }),
+				None if valid.can_all_in => Some(PlayerAction::AllIn { amount: valid.all_in_amount }),
+				None => None,
+			},
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preference {
+	SkipShowdowns,
+	AutoMuck,
+	FastForwardFolded,
+	BeginnerHints,
+	CoachingTips,
+}
+
+const MAX_CHAT_INPUT: usize = 200;
+const MAX_TAG_INPUT: usize = 40;
+
 #[derive(Debug)]
 pub enum InputEffect {
 	None,
 	SetPrompt(String),
 	ClearPrompt,
 	Respond(PlayerResponse),
+	SendChat(String),
+	ToggleFastForward,
+	/// Play the game faster or slower while watching it.
+	ChangeSpeed { faster: bool },
+	/// Ask the server to hold the game, or let it go on.
+	TogglePause,
+	/// Close the table and leave the seat to the daemon's server.
+	Detach,
+	/// Tag the current hand, or the last one between hands.
+	TagHand(String),
+	ArmPreAction(PreAction),
+	ChangeTable,
+	ToggleHelp,
+	/// Switch stacks, bets and the pot between chips and big blinds.
+	ToggleBigBlinds,
+	ShowPreferences,
+	TogglePreference(Preference),
 	CycleTheme,
 	Quit,
 }
 
-impl Default for InputState {
-	fn default() -> Self {
-		Self::Watching
-	}
-}
-
 impl InputState {
 	pub fn is_awaiting_input(&self) -> bool {
 		matches!(self, Self::AwaitingAction { .. } | Self::EnteringRaise { .. })
@@ -46,10 +123,18 @@ impl InputState {
 		(state, InputEffect::SetPrompt(prompt))
 	}
 
+	/// The prompt this state shows on its own, if any.
+	pub fn prompt(&self) -> Option<String> {
+		match self {
+			Self::AwaitingAction { valid } => Some(build_action_prompt(valid)),
+			_ => None,
+		}
+	}
+
 	pub fn enter_game_over() -> (Self, InputEffect) {
 		(
 			Self::GameOver,
-			InputEffect::SetPrompt("Game Over! Press 'q' to quit.".into()),
+			InputEffect::SetPrompt(tr("prompt.game_over")),
 		)
 	}
 
@@ -60,15 +145,130 @@ impl InputState {
 			Self::EnteringRaise { valid, amount, min, max } => {
 				handle_entering_raise(valid, amount, min, max, key)
 			}
+			Self::Chatting { text, resume } => handle_chatting(text, *resume, key),
+			Self::Preferences { resume } => handle_preferences(*resume, key),
+			Self::Tagging { text, resume } => handle_tagging(text, *resume, key),
 			Self::GameOver => handle_game_over(key),
 		}
 	}
 }
 
+fn start_chat(resume: InputState) -> (InputState, InputEffect) {
+	let prompt = chat_prompt("");
+	(
+		InputState::Chatting { text: String::new(), resume: Box::new(resume) },
+		InputEffect::SetPrompt(prompt),
+	)
+}
+
+fn chat_prompt(text: &str) -> String {
+	tr_with("prompt.chat", &[("text", text)])
+}
+
+fn handle_chatting(mut text: String, resume: InputState, key: KeyCode) -> (InputState, InputEffect) {
+	match key {
+		KeyCode::Enter if !text.trim().is_empty() => (resume, InputEffect::SendChat(text)),
+		KeyCode::Enter | KeyCode::Esc => {
+			let effect = match resume.prompt() {
+				Some(prompt) => InputEffect::SetPrompt(prompt),
+				None => InputEffect::ClearPrompt,
+			};
+			(resume, effect)
+		}
+		KeyCode::Backspace => {
+			text.pop();
+			let prompt = chat_prompt(&text);
+			(InputState::Chatting { text, resume: Box::new(resume) }, InputEffect::SetPrompt(prompt))
+		}
+		KeyCode::Char(c) if text.chars().count() < MAX_CHAT_INPUT => {
+			text.push(c);
+			let prompt = chat_prompt(&text);
+			(InputState::Chatting { text, resume: Box::new(resume) }, InputEffect::SetPrompt(prompt))
+		}
+		_ => (InputState::Chatting { text, resume: Box::new(resume) }, InputEffect::None),
+	}
+}
+
+fn start_tagging(resume: InputState) -> (InputState, InputEffect) {
+	(
+		InputState::Tagging { text: String::new(), resume: Box::new(resume) },
+		InputEffect::SetPrompt(tag_prompt("")),
+	)
+}
+
+fn tag_prompt(text: &str) -> String {
+	tr_with("prompt.tag", &[("text", text)])
+}
+
+/// Like chatting, but Enter on nothing tags the hand to review later.
+fn handle_tagging(mut text: String, resume: InputState, key: KeyCode) -> (InputState, InputEffect) {
+	match key {
+		KeyCode::Enter => {
+			let tag = if text.trim().is_empty() { REVIEW_LATER.to_string() } else { text };
+			(resume, InputEffect::TagHand(tag))
+		}
+		KeyCode::Esc => {
+			let effect = match resume.prompt() {
+				Some(prompt) => InputEffect::SetPrompt(prompt),
+				None => InputEffect::ClearPrompt,
+			};
+			(resume, effect)
+		}
+		KeyCode::Backspace => {
+			text.pop();
+			let prompt = tag_prompt(&text);
+			(InputState::Tagging { text, resume: Box::new(resume) }, InputEffect::SetPrompt(prompt))
+		}
+		KeyCode::Char(c) if text.chars().count() < MAX_TAG_INPUT => {
+			text.push(c);
+			let prompt = tag_prompt(&text);
+			(InputState::Tagging { text, resume: Box::new(resume) }, InputEffect::SetPrompt(prompt))
+		}
+		_ => (InputState::Tagging { text, resume: Box::new(resume) }, InputEffect::None),
+	}
+}
+
+fn start_preferences(resume: InputState) -> (InputState, InputEffect) {
+	(InputState::Preferences { resume: Box::new(resume) }, InputEffect::ShowPreferences)
+}
+
+fn handle_preferences(resume: InputState, key: KeyCode) -> (InputState, InputEffect) {
+	let toggle = match key {
+		KeyCode::Char('s') => Preference::SkipShowdowns,
+		KeyCode::Char('u') => Preference::AutoMuck,
+		KeyCode::Char('f') => Preference::FastForwardFolded,
+		KeyCode::Char('h') => Preference::BeginnerHints,
+		KeyCode::Char('c') => Preference::CoachingTips,
+		KeyCode::Esc | KeyCode::Enter | KeyCode::Char('p') => {
+			let effect = match resume.prompt() {
+				Some(prompt) => InputEffect::SetPrompt(prompt),
+				None => InputEffect::ClearPrompt,
+			};
+			return (resume, effect);
+		}
+		_ => return (InputState::Preferences { resume: Box::new(resume) }, InputEffect::None),
+	};
+	(InputState::Preferences { resume: Box::new(resume) }, InputEffect::TogglePreference(toggle))
+}
+
 fn handle_watching(key: KeyCode) -> (InputState, InputEffect) {
 	match key {
 		KeyCode::Char('q') | KeyCode::Esc => (InputState::Watching, InputEffect::Quit),
 		KeyCode::Char('t') => (InputState::Watching, InputEffect::CycleTheme),
+		KeyCode::Char('m') => start_chat(InputState::Watching),
+		KeyCode::Char('>') => (InputState::Watching, InputEffect::ToggleFastForward),
+		KeyCode::Char('+' | '=') => (InputState::Watching, InputEffect::ChangeSpeed { faster: true }),
+		KeyCode::Char('-') => (InputState::Watching, InputEffect::ChangeSpeed { faster: false }),
+		KeyCode::Char('?') => (InputState::Watching, InputEffect::ToggleHelp),
+		KeyCode::Char('$') => (InputState::Watching, InputEffect::ToggleBigBlinds),
+		KeyCode::Char('p') => start_preferences(InputState::Watching),
+		KeyCode::Char('1') => (InputState::Watching, InputEffect::ArmPreAction(PreAction::CheckFold)),
+		KeyCode::Char('2') => (InputState::Watching, InputEffect::ArmPreAction(PreAction::Check)),
+		KeyCode::Char('3') => (InputState::Watching, InputEffect::ArmPreAction(PreAction::CallAny)),
+		KeyCode::Char('n') => (InputState::Watching, InputEffect::ChangeTable),
+		KeyCode::Char('P') => (InputState::Watching, InputEffect::TogglePause),
+		KeyCode::Char('d') => (InputState::Watching, InputEffect::Detach),
+		KeyCode::Char('#') => start_tagging(InputState::Watching),
 		_ => (InputState::Watching, InputEffect::None),
 	}
 }
@@ -77,11 +277,15 @@ fn handle_game_over(key: KeyCode) -> (InputState, InputEffect) {
 	match key {
 		KeyCode::Char('q') | KeyCode::Esc => (InputState::GameOver, InputEffect::Quit),
 		KeyCode::Char('t') => (InputState::GameOver, InputEffect::CycleTheme),
+		KeyCode::Char('#') => start_tagging(InputState::GameOver),
 		_ => (InputState::GameOver, InputEffect::None),
 	}
 }
 
 fn handle_awaiting_action(valid: ValidActions, key: KeyCode) -> (InputState, InputEffect) {
+	if valid.can_show {
+		return handle_show_or_muck(valid, key);
+	}
 	match key {
 		KeyCode::Char('f') => {
 			if valid.can_fold {
@@ -90,7 +294,7 @@ fn handle_awaiting_action(valid: ValidActions, key: KeyCode) -> (InputState, Inp
 					InputEffect::Respond(PlayerResponse::Action(PlayerAction::Fold)),
 				)
 			} else {
-				let prompt = format!("Can't fold. {}", build_action_prompt(&valid));
+				let prompt = tr_with("prompt.cant_fold", &[("prompt", &build_action_prompt(&valid))]);
 				(InputState::AwaitingAction { valid }, InputEffect::SetPrompt(prompt))
 			}
 		}
@@ -151,7 +355,7 @@ fn handle_awaiting_action(valid: ValidActions, key: KeyCode) -> (InputState, Inp
 					RaiseOptions::Fixed { amount } => (*amount, *amount),
 					RaiseOptions::Variable { min_raise, max_raise } => (*min_raise, *max_raise),
 				};
-				let prompt = format!("Raise: ${:.0} [←/→ adjust] [Enter confirm] [Esc cancel]", min);
+				let prompt = raise_prompt(min);
 				(
 					InputState::EnteringRaise { valid, amount: min, min, max },
 					InputEffect::SetPrompt(prompt),
@@ -182,6 +386,29 @@ fn handle_awaiting_action(valid: ValidActions, key: KeyCode) -> (InputState, Inp
 			(InputState::AwaitingAction { valid }, InputEffect::CycleTheme)
 		}
 
+		KeyCode::Char('m') => start_chat(InputState::AwaitingAction { valid }),
+		KeyCode::Char('>') => (InputState::AwaitingAction { valid }, InputEffect::ToggleFastForward),
+		KeyCode::Char('?') => (InputState::AwaitingAction { valid }, InputEffect::ToggleHelp),
+		KeyCode::Char('$') => (InputState::AwaitingAction { valid }, InputEffect::ToggleBigBlinds),
+		KeyCode::Char('p') => start_preferences(InputState::AwaitingAction { valid }),
+
+		_ => (InputState::AwaitingAction { valid }, InputEffect::None),
+	}
+}
+
+/// Won uncalled: show the hand or muck it. Mucking is the default.
+fn handle_show_or_muck(valid: ValidActions, key: KeyCode) -> (InputState, InputEffect) {
+	match key {
+		KeyCode::Char('s') => (
+			InputState::Watching,
+			InputEffect::Respond(PlayerResponse::Action(PlayerAction::Show)),
+		),
+		KeyCode::Enter => (
+			InputState::Watching,
+			InputEffect::Respond(PlayerResponse::Action(PlayerAction::Muck)),
+		),
+		KeyCode::Char('q') | KeyCode::Esc => (InputState::AwaitingAction { valid }, InputEffect::Quit),
+		KeyCode::Char('m') => start_chat(InputState::AwaitingAction { valid }),
 		_ => (InputState::AwaitingAction { valid }, InputEffect::None),
 	}
 }
@@ -197,10 +424,7 @@ fn handle_entering_raise(
 		KeyCode::Left => {
 			let step = ((max - min) / 10.0).max(1.0);
 			let new_amount = (amount - step).max(min);
-			let prompt = format!(
-				"Raise: ${:.0} [←/→ adjust] [Enter confirm] [Esc cancel]",
-				new_amount
-			);
+			let prompt = raise_prompt(new_amount);
 			(
 				InputState::EnteringRaise { valid, amount: new_amount, min, max },
 				InputEffect::SetPrompt(prompt),
@@ -210,10 +434,7 @@ fn handle_entering_raise(
 		KeyCode::Right => {
 			let step = ((max - min) / 10.0).max(1.0);
 			let new_amount = (amount + step).min(max);
-			let prompt = format!(
-				"Raise: ${:.0} [←/→ adjust] [Enter confirm] [Esc cancel]",
-				new_amount
-			);
+			let prompt = raise_prompt(new_amount);
 			(
 				InputState::EnteringRaise { valid, amount: new_amount, min, max },
 				InputEffect::SetPrompt(prompt),
@@ -238,27 +459,35 @@ fn handle_entering_raise(
 	}
 }
 
+fn raise_prompt(amount: f32) -> String {
+	tr_with("prompt.raise_amount", &[("amount", &chips(amount))])
+}
+
 fn build_action_prompt(valid: &ValidActions) -> String {
+	if valid.can_show {
+		return format!("{}  {}", tr("prompt.show"), tr("prompt.muck"));
+	}
+
 	let mut parts = Vec::new();
 
 	if valid.can_check {
-		parts.push("[Enter] check".to_string());
+		parts.push(tr("prompt.check"));
 		if valid.raise_options.is_some() {
-			parts.push("[b]et".to_string());
+			parts.push(tr("prompt.bet"));
 		}
 	} else if let Some(amt) = valid.call_amount {
-		parts.push(format!("[c]all ${:.0}", amt));
+		parts.push(tr_with("prompt.call", &[("amount", &chips(amt))]));
 		if valid.can_fold {
-			parts.push("[f]old".to_string());
+			parts.push(tr("prompt.fold"));
 		}
 	}
 
 	if valid.raise_options.is_some() && !valid.can_check {
-		parts.push("[r]aise".to_string());
+		parts.push(tr("prompt.raise"));
 	}
 
 	if valid.can_all_in {
-		parts.push("[a]ll-in".to_string());
+		parts.push(tr("prompt.all_in"));
 	}
 
 	parts.join("  ")
@@ -279,6 +508,7 @@ mod tests {
 			}),
 			can_all_in: true,
 			all_in_amount: 100.0,
+			can_show: false,
 		}
 	}
 
@@ -336,6 +566,22 @@ mod tests {
 		));
 	}
 
+	#[test]
+	fn show_or_muck_offers_only_those() {
+		let (state, effect) = InputState::enter_action_mode(ValidActions::show_or_muck());
+		assert!(matches!(effect, InputEffect::SetPrompt(ref p) if p.contains("[s]") && !p.contains("[f]")));
+
+		let (_, effect) = state.clone().handle_key(KeyCode::Char('s'));
+		assert!(matches!(effect, InputEffect::Respond(PlayerResponse::Action(PlayerAction::Show))));
+		let (_, effect) = state.clone().handle_key(KeyCode::Enter);
+		assert!(matches!(effect, InputEffect::Respond(PlayerResponse::Action(PlayerAction::Muck))));
+		let (new_state, effect) = state.handle_key(KeyCode::Char('f'));
+		assert!(matches!(new_state, InputState::AwaitingAction { .. }));
+		assert!(matches!(effect, InputEffect::None));
+
+		assert!(PreAction::CallAny.resolve(&ValidActions::show_or_muck()).is_none());
+	}
+
 	#[test]
 	fn awaiting_action_r_enters_raise_mode() {
 		let valid = make_valid_actions(false, Some(10.0));
@@ -400,6 +646,153 @@ mod tests {
 		assert!(matches!(effect, InputEffect::SetPrompt(_)));
 	}
 
+	#[test]
+	fn chatting_types_and_sends() {
+		let (state, _) = InputState::Watching.handle_key(KeyCode::Char('m'));
+		let (state, _) = state.handle_key(KeyCode::Char('q'));
+		let (state, _) = state.handle_key(KeyCode::Char('x'));
+		let (state, _) = state.handle_key(KeyCode::Backspace);
+		let (state, effect) = state.handle_key(KeyCode::Enter);
+
+		assert!(matches!(state, InputState::Watching));
+		assert!(matches!(effect, InputEffect::SendChat(ref text) if text == "q"));
+	}
+
+	#[test]
+	fn chatting_esc_returns_to_action_prompt() {
+		let valid = make_valid_actions(false, Some(10.0));
+		let (state, _) = InputState::AwaitingAction { valid }.handle_key(KeyCode::Char('m'));
+		assert!(matches!(state, InputState::Chatting { .. }));
+		let (state, effect) = state.handle_key(KeyCode::Esc);
+
+		assert!(matches!(state, InputState::AwaitingAction { .. }));
+		assert!(matches!(effect, InputEffect::SetPrompt(ref p) if p.contains("[c]all")));
+	}
+
+	#[test]
+	fn fast_forward_toggles_without_leaving_action() {
+		let (state, effect) = InputState::Watching.handle_key(KeyCode::Char('>'));
+		assert!(matches!(state, InputState::Watching));
+		assert!(matches!(effect, InputEffect::ToggleFastForward));
+
+		let valid = make_valid_actions(false, Some(10.0));
+		let (state, effect) = InputState::AwaitingAction { valid }.handle_key(KeyCode::Char('>'));
+		assert!(matches!(state, InputState::AwaitingAction { .. }));
+		assert!(matches!(effect, InputEffect::ToggleFastForward));
+	}
+
+	#[test]
+	fn plus_and_minus_change_speed_while_watching() {
+		let (state, effect) = InputState::Watching.handle_key(KeyCode::Char('+'));
+		assert!(matches!(state, InputState::Watching));
+		assert!(matches!(effect, InputEffect::ChangeSpeed { faster: true }));
+		let (_, effect) = InputState::Watching.handle_key(KeyCode::Char('-'));
+		assert!(matches!(effect, InputEffect::ChangeSpeed { faster: false }));
+	}
+
+	#[test]
+	fn shifted_p_pauses_and_lowercase_opens_preferences() {
+		let (_, effect) = InputState::Watching.handle_key(KeyCode::Char('P'));
+		assert!(matches!(effect, InputEffect::TogglePause));
+		let (state, _) = InputState::Watching.handle_key(KeyCode::Char('p'));
+		assert!(matches!(state, InputState::Preferences { .. }));
+	}
+
+	#[test]
+	fn tagging_defaults_to_review_later_and_goes_back() {
+		let (state, _) = InputState::GameOver.handle_key(KeyCode::Char('#'));
+		let (state, effect) = state.handle_key(KeyCode::Enter);
+		assert!(matches!(state, InputState::GameOver));
+		assert!(matches!(effect, InputEffect::TagHand(tag) if tag == REVIEW_LATER));
+
+		let (mut state, _) = InputState::Watching.handle_key(KeyCode::Char('#'));
+		for c in "cooler".chars() {
+			state = state.handle_key(KeyCode::Char(c)).0;
+		}
+		let (state, effect) = state.handle_key(KeyCode::Enter);
+		assert!(matches!(state, InputState::Watching));
+		assert!(matches!(effect, InputEffect::TagHand(tag) if tag == "cooler"));
+	}
+
+	#[test]
+	fn help_toggles_while_watching_or_acting() {
+		let (state, effect) = InputState::Watching.handle_key(KeyCode::Char('?'));
+		assert!(matches!(state, InputState::Watching));
+		assert!(matches!(effect, InputEffect::ToggleHelp));
+
+		let valid = make_valid_actions(false, Some(10.0));
+		let (state, effect) = InputState::AwaitingAction { valid }.handle_key(KeyCode::Char('?'));
+		assert!(matches!(state, InputState::AwaitingAction { .. }));
+		assert!(matches!(effect, InputEffect::ToggleHelp));
+	}
+
+	#[test]
+	fn big_blinds_toggle_while_watching_or_acting() {
+		let (state, effect) = InputState::Watching.handle_key(KeyCode::Char('$'));
+		assert!(matches!(state, InputState::Watching));
+		assert!(matches!(effect, InputEffect::ToggleBigBlinds));
+
+		let valid = make_valid_actions(false, Some(10.0));
+		let (state, effect) = InputState::AwaitingAction { valid }.handle_key(KeyCode::Char('$'));
+		assert!(matches!(state, InputState::AwaitingAction { .. }));
+		assert!(matches!(effect, InputEffect::ToggleBigBlinds));
+	}
+
+	#[test]
+	fn change_table_only_while_watching() {
+		let (state, effect) = InputState::Watching.handle_key(KeyCode::Char('n'));
+		assert!(matches!(state, InputState::Watching));
+		assert!(matches!(effect, InputEffect::ChangeTable));
+
+		let valid = make_valid_actions(false, Some(10.0));
+		let (_, effect) = InputState::AwaitingAction { valid }.handle_key(KeyCode::Char('n'));
+		assert!(!matches!(effect, InputEffect::ChangeTable));
+	}
+
+	#[test]
+	fn preferences_toggle_then_resume_action() {
+		let valid = make_valid_actions(false, Some(10.0));
+		let (state, effect) = InputState::AwaitingAction { valid }.handle_key(KeyCode::Char('p'));
+		assert!(matches!(effect, InputEffect::ShowPreferences));
+
+		// 'f' toggles a preference here rather than folding
+		let (state, effect) = state.handle_key(KeyCode::Char('f'));
+		assert!(matches!(state, InputState::Preferences { .. }));
+		assert!(matches!(effect, InputEffect::TogglePreference(Preference::FastForwardFolded)));
+
+		let (state, effect) = state.handle_key(KeyCode::Esc);
+		assert!(matches!(state, InputState::AwaitingAction { .. }));
+		assert!(matches!(effect, InputEffect::SetPrompt(ref p) if p.contains("[c]all")));
+	}
+
+	#[test]
+	fn pre_actions_resolve_against_the_request() {
+		let free = make_valid_actions(true, None);
+		let facing_bet = make_valid_actions(false, Some(10.0));
+		let all_in_to_call = ValidActions { call_amount: None, ..make_valid_actions(false, None) };
+		let all_in_to_call = ValidActions { can_fold: true, ..all_in_to_call };
+
+		assert!(matches!(PreAction::CheckFold.resolve(&free), Some(PlayerAction::Check)));
+		assert!(matches!(PreAction::CheckFold.resolve(&facing_bet), Some(PlayerAction::Fold)));
+		assert!(matches!(PreAction::Check.resolve(&free), Some(PlayerAction::Check)));
+		assert!(PreAction::Check.resolve(&facing_bet).is_none());
+		assert!(matches!(
+			PreAction::CallAny.resolve(&facing_bet),
+			Some(PlayerAction::Call { amount }) if amount == 10.0
+		));
+		assert!(matches!(
+			PreAction::CallAny.resolve(&all_in_to_call),
+			Some(PlayerAction::AllIn { amount }) if amount == 100.0
+		));
+	}
+
+	#[test]
+	fn watching_number_keys_arm_pre_actions() {
+		let (state, effect) = InputState::Watching.handle_key(KeyCode::Char('3'));
+		assert!(matches!(state, InputState::Watching));
+		assert!(matches!(effect, InputEffect::ArmPreAction(PreAction::CallAny)));
+	}
+
 	#[test]
 	fn game_over_q_quits() {
 		let state = InputState::GameOver;
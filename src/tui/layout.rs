@@ -14,16 +14,47 @@ impl SeatPosition {
 	}
 }
 
+/// How a theme lays the table out: the felt drawn under the seats and
+/// where the seats go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LayoutProfile {
+	pub shape: TableShape,
+	pub seats: SeatArrangement,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableShape {
+	/// No felt, just the seats.
+	#[default]
+	Minimal,
+	/// A dotted oval inside the ring of seats.
+	Oval,
+	/// Long straight sides with rounded ends.
+	Racetrack,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeatArrangement {
+	/// Evenly round an oval, seat 0 at the bottom.
+	#[default]
+	Around,
+	/// Along the top and bottom edges, seat 0 in the middle of the bottom.
+	Rows,
+}
+
 pub struct TableLayout {
 	pub seats: Vec<SeatPosition>,
-	pub board_area: Rect,
-	pub pot_area: Rect,
+	/// Where the felt goes, if the shape has one: inside the seats, around
+	/// the center.
+	pub felt: Option<Rect>,
+	/// The middle of the felt, for `CenterLayout` to share out.
+	pub center_area: Rect,
 	pub chat_area: Rect,
 	pub info_area: Rect,
 }
 
 impl TableLayout {
-	pub fn compute(area: Rect, num_players: usize) -> Self {
+	pub fn compute(area: Rect, num_players: usize, profile: LayoutProfile) -> Self {
 		let seat_width: u16 = 18;
 		let seat_height: u16 = 4;
 
@@ -39,21 +70,19 @@ impl TableLayout {
 		let center_x = table_area.x + table_area.width / 2;
 		let center_y = table_area.y + table_area.height / 2;
 
-		let seats = layout_oval(table_area, num_players, seat_width, seat_height);
-
-		let board_area = Rect::new(
-			center_x.saturating_sub(15),
-			center_y.saturating_sub(1),
-			30,
-			1,
-		);
+		let seats = match profile.seats {
+			SeatArrangement::Around => layout_oval(table_area, num_players, seat_width, seat_height),
+			SeatArrangement::Rows => layout_rows(table_area, num_players, seat_width, seat_height),
+		};
+		let felt = (profile.shape != TableShape::Minimal).then(|| felt_area(table_area, profile.seats, seat_width, seat_height));
 
-		let pot_area = Rect::new(
-			center_x.saturating_sub(10),
-			center_y,
-			20,
-			1,
-		);
+		let center_area = Rect::new(
+			center_x.saturating_sub(CENTER_WIDTH / 2),
+			center_y.saturating_sub(CENTER_ROWS_ABOVE),
+			CENTER_WIDTH,
+			CENTER_ROWS,
+		)
+		.intersection(table_area);
 
 		let bottom_y = area.y + area.height.saturating_sub(chat_height);
 		let bottom_width = area.width.saturating_sub(2);
@@ -75,14 +104,46 @@ impl TableLayout {
 
 		Self {
 			seats,
-			board_area,
-			pot_area,
+			felt,
+			center_area,
 			chat_area,
 			info_area,
 		}
 	}
 }
 
+const CENTER_WIDTH: u16 = 40;
+const CENTER_ROWS: u16 = 5;
+/// Rows of the center above the table's middle line; the first board
+/// sits just above it and the pots on it, however many rows there are.
+const CENTER_ROWS_ABOVE: u16 = 2;
+
+/// What goes in the middle of the table, a row each, top to bottom: the
+/// boards (two when a hand is run twice), the pots, then chips set apart
+/// from them, like antes. New rows go here rather than into the widget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CenterLayout {
+	pub boards: Vec<Rect>,
+	pub pots: Rect,
+	pub chips: Option<Rect>,
+}
+
+impl CenterLayout {
+	/// Shares `area` out, with the pots on its middle line when there's
+	/// room. Rows that don't fit are left empty.
+	pub fn compute(area: Rect, boards: usize, chips: bool) -> Self {
+		let rows = boards as u16 + 1 + chips as u16;
+		let top = (area.y + CENTER_ROWS_ABOVE).saturating_sub(boards as u16).max(area.y);
+		let top = top.min((area.y + area.height).saturating_sub(rows).max(area.y));
+		let row = |i: u16| Rect::new(area.x, top + i, area.width, 1).intersection(area);
+		Self {
+			boards: (0..boards as u16).map(row).collect(),
+			pots: row(boards as u16),
+			chips: chips.then(|| row(boards as u16 + 1)),
+		}
+	}
+}
+
 fn layout_oval(area: Rect, n: usize, w: u16, h: u16) -> Vec<SeatPosition> {
 	let n = n.min(10);
 	let cx = area.x as f32 + area.width as f32 / 2.0;
@@ -109,3 +170,89 @@ fn layout_oval(area: Rect, n: usize, w: u16, h: u16) -> Vec<SeatPosition> {
 
 	seats
 }
+
+/// Seats along the top and bottom rows, clockwise from the middle of the
+/// bottom one like the oval: left along the bottom, right along the top.
+fn layout_rows(area: Rect, n: usize, w: u16, h: u16) -> Vec<SeatPosition> {
+	let n = n.min(10);
+	let bottom = n.div_ceil(2);
+	let top = n - bottom;
+	// The bottom row leaves a line under it for the last action
+	let bottom_y = (area.y + area.height).saturating_sub(h + 1).max(area.y);
+	let spot = |i: usize, count: usize, y: u16| {
+		let middle = area.x as usize + area.width as usize * (2 * i + 1) / (2 * count);
+		SeatPosition {
+			x: middle.saturating_sub(w as usize / 2).max(area.x as usize) as u16,
+			y,
+			width: w,
+			height: h,
+		}
+	};
+	// Round the table from the bottom right, then rotated to start at the
+	// bottom's middle seat
+	let ring = (0..bottom)
+		.rev()
+		.map(|i| spot(i, bottom, bottom_y))
+		.chain((0..top).map(|i| spot(i, top, area.y)))
+		.collect::<Vec<_>>();
+	let start = bottom / 2;
+	(0..n).map(|i| ring[(start + i) % n]).collect()
+}
+
+/// The felt's bounds: clear of the seats, and of the action line under
+/// the top ones.
+fn felt_area(area: Rect, seats: SeatArrangement, w: u16, h: u16) -> Rect {
+	let (margin_x, margin_y) = match seats {
+		SeatArrangement::Around => (w + 2, h + 3),
+		SeatArrangement::Rows => (2, h + 1),
+	};
+	let felt = Rect::new(
+		area.x + margin_x,
+		area.y + margin_y,
+		area.width.saturating_sub(2 * margin_x),
+		area.height.saturating_sub(2 * margin_y),
+	);
+	felt.intersection(area)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_center_keeps_the_pots_on_the_middle_line() {
+		let area = Rect::new(10, 20, 40, 5);
+		let one = CenterLayout::compute(area, 1, false);
+		assert_eq!(one.boards, vec![Rect::new(10, 21, 40, 1)]);
+		assert_eq!(one.pots, Rect::new(10, 22, 40, 1));
+		assert_eq!(one.chips, None);
+
+		let twice = CenterLayout::compute(area, 2, true);
+		assert_eq!(twice.boards.iter().map(|r| r.y).collect::<Vec<_>>(), vec![20, 21]);
+		assert_eq!(twice.pots.y, 22);
+		assert_eq!(twice.chips.map(|r| r.y), Some(23));
+
+		// Squeezed, the rows move up to fit, and past that the last are left out
+		let short = CenterLayout::compute(Rect::new(10, 20, 40, 3), 1, true);
+		assert_eq!((short.boards[0].y, short.pots.y), (20, 21));
+		assert_eq!(short.chips, Some(Rect::new(10, 22, 40, 1)));
+		let shorter = CenterLayout::compute(Rect::new(10, 20, 40, 3), 2, true);
+		assert_eq!(shorter.chips.map(|r| r.height), Some(0));
+	}
+
+	#[test]
+	fn test_rows_seat_clockwise_from_the_bottom_middle() {
+		let profile = LayoutProfile { shape: TableShape::Racetrack, seats: SeatArrangement::Rows };
+		let layout = TableLayout::compute(Rect::new(0, 0, 120, 40), 6, profile);
+		let spots = layout.seats.iter().map(|s| (s.x, s.y)).collect::<Vec<_>>();
+		// Table rows 0..28: the bottom seats at 23, leaving the action line
+		assert_eq!(spots, vec![(51, 23), (11, 23), (11, 0), (51, 0), (91, 0), (91, 23)]);
+		let felt = layout.felt.unwrap();
+		assert_eq!(felt, Rect::new(2, 5, 116, 18));
+		assert!(felt.contains(layout.center_area.as_position()));
+
+		let minimal = TableLayout::compute(Rect::new(0, 0, 120, 40), 6, LayoutProfile::default());
+		assert_eq!(minimal.felt, None);
+		assert_eq!(minimal.seats[0].y, 23);
+	}
+}
@@ -0,0 +1,267 @@
+//! Linear output for screen readers: the game as a stream of plain
+//! sentences, one per line, instead of a redrawn table. `Narrator` turns
+//! events and prompt changes into lines; the caller prints them.
+
+use std::io::{self, Write};
+
+use crate::events::{BlindType, Card, ChatSender, GameEndReason, GameEvent, LeaveReason, Position, Street};
+use crate::money::chips;
+use crate::tui::input::InputState;
+use crate::tui::widgets::{HAND_RANKINGS, HELP_KEYS};
+use crate::tui::GameUI;
+use crate::view::TableView;
+
+fn rank_name(rank: char) -> &'static str {
+	match rank {
+		'A' => "Ace",
+		'K' => "King",
+		'Q' => "Queen",
+		'J' => "Jack",
+		'T' => "Ten",
+		'9' => "Nine",
+		'8' => "Eight",
+		'7' => "Seven",
+		'6' => "Six",
+		'5' => "Five",
+		'4' => "Four",
+		'3' => "Three",
+		'2' => "Two",
+		_ => "Unknown",
+	}
+}
+
+fn suit_name(suit: char) -> &'static str {
+	match suit {
+		's' => "spades",
+		'h' => "hearts",
+		'd' => "diamonds",
+		'c' => "clubs",
+		_ => "unknown",
+	}
+}
+
+/// A card in words, like "Ace of spades", so it reads aloud cleanly.
+pub fn spoken_card(card: &Card) -> String {
+	format!("{} of {}", rank_name(card.rank), suit_name(card.suit))
+}
+
+fn spoken_cards(cards: &[Card]) -> String {
+	cards.iter().map(spoken_card).collect::<Vec<_>>().join(", ")
+}
+
+fn position_name(position: &Position) -> Option<&'static str> {
+	match position {
+		Position::Button => Some("button"),
+		Position::SmallBlind => Some("small blind"),
+		Position::BigBlind => Some("big blind"),
+		Position::None => None,
+	}
+}
+
+/// The help overlay's contents, read out when it's opened.
+pub fn help_lines(rules: &[String]) -> Vec<String> {
+	let mut lines = vec!["Keys:".to_string()];
+	lines.extend(HELP_KEYS.iter().map(|(key, action)| format!("{}: {}", key, action)));
+	lines.push("Hand rankings, best first:".to_string());
+	lines.extend(HAND_RANKINGS.iter().map(|(hand, meaning)| format!("{}: {}", hand, meaning)));
+	lines.push("Betting:".to_string());
+	lines.extend(rules.iter().map(|rule| rule.trim().to_string()));
+	lines.push("Press ? to close help.".to_string());
+	lines
+}
+
+#[derive(Default)]
+pub struct Narrator {
+	last_status: Option<String>,
+}
+
+impl Narrator {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// What to say about `event`. Call after the UI has applied it, so
+	/// `view` has the names of everyone involved.
+	pub fn event(&self, event: &GameEvent, view: &TableView) -> Vec<String> {
+		let name = |seat: usize| {
+			view.players
+				.iter()
+				.find(|p| p.seat == seat)
+				.map(|p| if p.is_hero { "You".to_string() } else { p.name.clone() })
+				.unwrap_or_else(|| format!("Seat {}", seat + 1))
+		};
+		match event {
+			GameEvent::HandStarted { hand_num, blinds, seats, .. } => {
+				let players = seats
+					.iter()
+					.filter(|s| s.is_active)
+					.map(|s| match position_name(&s.position) {
+						Some(position) => format!("{} {} {}", name(s.seat.0), chips(s.stack), position),
+						None => format!("{} {}", name(s.seat.0), chips(s.stack)),
+					})
+					.collect::<Vec<_>>();
+				vec![
+					format!("Hand {}, blinds {}/{}.", hand_num, chips(blinds.small), chips(blinds.big)),
+					format!("Players: {}.", players.join(", ")),
+				]
+			}
+			GameEvent::HoleCardsDealt { seat, cards } if view.players.iter().any(|p| p.is_hero && p.seat == seat.0) => {
+				vec![format!("Your cards: {}.", spoken_cards(cards))]
+			}
+			GameEvent::BlindPosted { seat, blind_type, amount } => {
+				let blind = match blind_type {
+					BlindType::Small => "the small blind",
+					BlindType::Big => "the big blind",
+					BlindType::Ante => "an ante",
+					BlindType::Straddle => "a straddle",
+				};
+				vec![format!("{} posts {}, {}.", name(seat.0), blind, chips(*amount))]
+			}
+			GameEvent::StreetChanged { street, board, .. } => {
+				let street = match street {
+					Street::Flop => "Flop",
+					Street::Turn => "Turn",
+					Street::River => "River",
+					_ => return Vec::new(),
+				};
+				let depth = view.spr.map(|spr| format!(", stack to pot {:.1}", spr)).unwrap_or_default();
+				vec![format!("{}: {}. Pot {}{}.", street, spoken_cards(board), chips(view.pot), depth)]
+			}
+			GameEvent::ActionTaken { seat, action, .. } => {
+				vec![format!("{} {}. Pot {}.", name(seat.0), action.description(), chips(view.pot))]
+			}
+			GameEvent::ShowdownReveal { reveals } => reveals
+				.iter()
+				.map(|(seat, cards)| format!("{} shows {}.", name(seat.0), spoken_cards(cards)))
+				.collect(),
+			GameEvent::PotAwarded { seat, amount, hand_description, .. } => {
+				vec![match hand_description {
+					Some(description) => format!("{} wins {} with {}.", name(seat.0), chips(*amount), description),
+					None => format!("{} wins {}.", name(seat.0), chips(*amount)),
+				}]
+			}
+			GameEvent::ChatMessage { sender, text } => {
+				vec![match sender {
+					ChatSender::System => text.clone(),
+					ChatSender::Dealer => format!("Dealer: {}", text),
+					ChatSender::Player(seat) => format!("{} says: {}", name(seat.0), text),
+					ChatSender::Spectator(spectator) => format!("{} says: {}", spectator, text),
+				}]
+			}
+			GameEvent::GameChanged { name, .. } => vec![format!("Now playing {}.", name)],
+			GameEvent::PlayerLeft { seat, reason } => {
+				let what = match reason {
+					LeaveReason::Eliminated => "is out",
+					LeaveReason::Disconnected => "disconnected",
+					_ => "left the table",
+				};
+				vec![format!("{} {}.", name(seat.0), what)]
+			}
+			GameEvent::GameEnded { reason, final_standings, detail } => {
+				vec![match (reason, final_standings.first(), detail) {
+					(GameEndReason::Winner, Some(winner), _) => format!("Game over. {} wins the game.", winner.name),
+					(GameEndReason::Error, _, Some(detail)) => format!("Game over. {}.", detail),
+					_ => "Game over.".to_string(),
+				}]
+			}
+			_ => Vec::new(),
+		}
+	}
+
+	/// The status line, if it changed since last time: prompts, warnings
+	/// and menus. Said once rather than redrawn.
+	pub fn status(&mut self, ui: &GameUI) -> Option<String> {
+		let status = ui.connection_banner().or_else(|| ui.status_message.clone().map(|message| match &ui.input_state {
+			InputState::AwaitingAction { .. } | InputState::EnteringRaise { .. } => format!("Your turn. {}", message),
+			_ => message,
+		}));
+		if status == self.last_status {
+			return None;
+		}
+		self.last_status = status.clone();
+		status
+	}
+}
+
+/// Prints `lines` one by one. Raw mode needs the explicit carriage return.
+pub fn speak(out: &mut impl Write, lines: &[String]) -> io::Result<()> {
+	for line in lines {
+		write!(out, "{}\r\n", line)?;
+	}
+	out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::{Blinds, HandId, PlayerAction, Seat, SeatInfo, ValidActions};
+	use crate::theme::Theme;
+
+	fn seat(seat: usize, name: &str, position: Position) -> SeatInfo {
+		SeatInfo {
+			seat: Seat(seat),
+			name: name.to_string(),
+			stack: 200.0,
+			position,
+			is_active: true,
+			is_human: seat == 0,
+			is_occupied: true,
+		}
+	}
+
+	#[test]
+	fn test_narrator_reads_the_hand_as_sentences() {
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "classic".to_string());
+		let narrator = Narrator::new();
+		let mut said = Vec::new();
+		let events = [
+			GameEvent::HandStarted {
+				hand_id: HandId(1),
+				hand_num: 3,
+				button: Seat(0),
+				blinds: Blinds { small: 1.0, big: 2.0, ante: None },
+				seats: vec![seat(0, "alice", Position::Button), seat(1, "Bob", Position::BigBlind)],
+				at: None,
+				game_id: None,
+			},
+			GameEvent::HoleCardsDealt { seat: Seat(0), cards: [Card::new('A', 's'), Card::new('T', 'h')] },
+			GameEvent::HoleCardsDealt { seat: Seat(1), cards: [Card::new('2', 'c'), Card::new('2', 'd')] },
+			GameEvent::ActionTaken { seat: Seat(1), action: PlayerAction::Raise { amount: 6.0 }, stack_after: 194.0, pot_after: 9.0, at: None },
+		];
+		for event in &events {
+			ui.apply_event(event);
+			said.extend(narrator.event(event, &ui.table_view));
+		}
+		assert_eq!(said, vec![
+			"Hand 3, blinds $1/$2.",
+			"Players: You $200 button, Bob $200 big blind.",
+			"Your cards: Ace of spades, Ten of hearts.",
+			"Bob raises to $6. Pot $9.",
+		]);
+	}
+
+	#[test]
+	fn test_status_is_said_once_per_change() {
+		let mut ui = GameUI::new(Some(Seat(0)), Theme::default(), "classic".to_string());
+		let mut narrator = Narrator::new();
+		assert_eq!(narrator.status(&ui), None);
+		ui.enter_action_mode(ValidActions {
+			can_fold: false,
+			can_check: true,
+			call_amount: None,
+			raise_options: None,
+			can_all_in: false,
+			all_in_amount: 0.0,
+			can_show: false,
+		});
+		assert!(narrator.status(&ui).unwrap().starts_with("Your turn. "));
+		assert_eq!(narrator.status(&ui), None);
+
+		// A dropped connection is said over everything else, once per try
+		ui.connection = crate::net::ConnectionState::Reconnecting { attempt: 1 };
+		assert!(narrator.status(&ui).unwrap().contains("Reconnecting"));
+		assert_eq!(narrator.status(&ui), None);
+		ui.connection = crate::net::ConnectionState::Connected;
+		assert!(narrator.status(&ui).unwrap().starts_with("Your turn. "));
+	}
+}
@@ -1,9 +1,16 @@
+pub mod coach;
 pub mod game_ui;
+pub mod hints;
 pub mod input;
 pub mod layout;
+pub mod linear;
+pub mod snapshot;
+pub mod terminal;
 pub mod widgets;
 
-pub use game_ui::{GameUI, GameUIAction, WinnerInfo};
-pub use input::{InputEffect, InputState};
+pub use game_ui::{GameUI, GameUIAction, WinnerInfo, WINDOW_TITLE};
+pub use input::{InputEffect, InputState, PreAction, Preference};
 pub use layout::TableLayout;
-pub use widgets::TableWidget;
+pub use linear::Narrator;
+pub use terminal::TuiTerminal;
+pub use widgets::{HelpWidget, TableWidget};
@@ -0,0 +1,94 @@
+//! Screens as plain text, for tests. `render` draws on a ratatui
+//! `TestBackend` and reads the cells back as lines, without colors, so a
+//! screen can be checked against a `.screen` file the way `golden` checks
+//! table views: `check_screen` replays a fixture into a `GameUI` and
+//! compares what it draws with the `.screen` file beside it. The repo's
+//! screens are in `tests/golden/`, the menu's in `tests/golden/menu/`.
+
+use std::path::{Path, PathBuf};
+
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::text::Span;
+use ratatui::{Frame, Terminal};
+
+use crate::error::PokerError;
+use crate::golden::{self, Fixture};
+use crate::theme::Theme;
+use crate::tui::GameUI;
+
+/// The size golden screens are drawn at.
+pub const WIDTH: u16 = 120;
+pub const HEIGHT: u16 = 40;
+
+/// Draws one frame on a `width` by `height` screen and returns it as text.
+pub fn render(width: u16, height: u16, draw: impl FnOnce(&mut Frame)) -> String {
+	let mut terminal = Terminal::new(TestBackend::new(width, height)).expect("a test backend can't fail");
+	terminal.draw(draw).expect("a test backend can't fail");
+	screen_text(terminal.backend().buffer())
+}
+
+/// The screen as lines of plain text, trailing blanks trimmed. A wide
+/// symbol covers the cell after it, so that one is left out.
+pub fn screen_text(buffer: &Buffer) -> String {
+	let width = buffer.area.width as usize;
+	buffer
+		.content()
+		.chunks(width.max(1))
+		.map(|row| {
+			let mut line = String::new();
+			let mut covered = 0;
+			for cell in row {
+				if covered > 0 {
+					covered -= 1;
+					continue;
+				}
+				line.push_str(cell.symbol());
+				covered = Span::raw(cell.symbol()).width().saturating_sub(1);
+			}
+			format!("{}\n", line.trim_end())
+		})
+		.collect()
+}
+
+/// The table as `GameUI` draws it after the fixture, in the default theme.
+pub fn game_screen(fixture: &Fixture) -> String {
+	let mut ui = GameUI::new(fixture.hero, Theme::default(), "default".to_string());
+	for event in &fixture.events {
+		ui.apply_event(event);
+	}
+	render(WIDTH, HEIGHT, |frame| ui.render(frame, frame.area()))
+}
+
+/// `<fixture>.screen`.
+pub fn screen_path(fixture: &Path) -> PathBuf {
+	fixture.with_extension("screen")
+}
+
+/// Replays the fixture into a `GameUI` and compares the screen with its
+/// `.screen` file, or with `UPDATE_GOLDEN` set, writes it.
+pub fn check_screen(fixture: &Path) -> Result<(), PokerError> {
+	let actual = game_screen(&Fixture::load(fixture)?);
+	golden::compare(&screen_path(fixture), &actual)
+}
+
+/// Checks the screen of every `.jsonl` fixture in `dir` and says how
+/// many there were.
+pub fn check_screens(dir: &Path) -> Result<usize, PokerError> {
+	golden::check_all(dir, check_screen)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ratatui::widgets::{Block, Borders, Paragraph};
+
+	#[test]
+	fn test_render_trims_lines_and_skips_covered_cells() {
+		let text = render(12, 3, |frame| {
+			let block = Block::default().borders(Borders::ALL);
+			frame.render_widget(Paragraph::new("💰 ok").block(block), frame.area());
+		});
+		assert_eq!(text, "┌──────────┐\n│💰 ok     │\n└──────────┘\n");
+	}
+}
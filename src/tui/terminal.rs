@@ -0,0 +1,112 @@
+//! The terminal as the TUI takes it over: raw mode, and the alternate
+//! screen unless output is linear. `TuiTerminal` puts it back as it was
+//! when dropped, and the panic hook it installs does the same before the
+//! panic is printed, so a crash leaves a usable shell and a crash report
+//! beside the logs.
+
+use std::io::{self, stdout, Stdout};
+use std::ops::{Deref, DerefMut};
+use std::panic::{self, PanicHookInfo};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once};
+use std::thread::{self, ThreadId};
+
+use crossterm::cursor::Show;
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle};
+use ratatui::backend::CrosstermBackend;
+use ratatui::Terminal;
+
+use crate::logging;
+
+/// What the hook has to undo.
+static RAW: AtomicBool = AtomicBool::new(false);
+static ALTERNATE: AtomicBool = AtomicBool::new(false);
+
+/// The thread drawing the screen. A panic anywhere else leaves the UI
+/// running, so it only gets a crash report.
+static UI_THREAD: Mutex<Option<ThreadId>> = Mutex::new(None);
+
+/// The terminal, taken over until this is dropped.
+pub struct TuiTerminal {
+	terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl TuiTerminal {
+	/// Takes over the terminal. Linear output stays on the normal screen,
+	/// where its scrollback can be read back; otherwise the alternate
+	/// screen is titled `title`.
+	pub fn enter(title: &str, linear: bool) -> io::Result<Self> {
+		install_panic_hook();
+		*UI_THREAD.lock().unwrap_or_else(|e| e.into_inner()) = Some(thread::current().id());
+		enable_raw_mode()?;
+		RAW.store(true, Ordering::SeqCst);
+		let mut stdout = stdout();
+		if !linear {
+			ALTERNATE.store(true, Ordering::SeqCst);
+			execute!(stdout, EnterAlternateScreen, SetTitle(title))?;
+		}
+		Ok(Self { terminal: Terminal::new(CrosstermBackend::new(stdout))? })
+	}
+}
+
+impl Deref for TuiTerminal {
+	type Target = Terminal<CrosstermBackend<Stdout>>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.terminal
+	}
+}
+
+impl DerefMut for TuiTerminal {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.terminal
+	}
+}
+
+impl Drop for TuiTerminal {
+	fn drop(&mut self) {
+		restore();
+	}
+}
+
+/// Leaves the alternate screen and raw mode and shows the cursor, if the
+/// terminal was taken over.
+pub fn restore() {
+	if ALTERNATE.swap(false, Ordering::SeqCst) {
+		let _ = execute!(stdout(), LeaveAlternateScreen);
+	}
+	if RAW.swap(false, Ordering::SeqCst) {
+		let _ = disable_raw_mode();
+		let _ = execute!(stdout(), Show);
+	}
+}
+
+/// Puts the terminal back before a panic on the UI thread is printed.
+/// Every panic gets a crash report; one elsewhere, like an engine thread
+/// the game survives, isn't printed while the screen is up, since it would
+/// only write over it.
+fn install_panic_hook() {
+	static INSTALLED: Once = Once::new();
+	INSTALLED.call_once(|| {
+		let previous = panic::take_hook();
+		panic::set_hook(Box::new(move |info| {
+			let report = crash_report(info);
+			let on_ui = *UI_THREAD.lock().unwrap_or_else(|e| e.into_inner()) == Some(thread::current().id());
+			if on_ui || !RAW.load(Ordering::SeqCst) {
+				restore();
+				previous(info);
+				if let Ok(path) = report {
+					eprintln!("A crash report is in {}", path.display());
+				}
+			}
+		}));
+	});
+}
+
+fn crash_report(info: &PanicHookInfo) -> io::Result<PathBuf> {
+	let location = info.location().map(|l| l.to_string()).unwrap_or_default();
+	let current = thread::current();
+	logging::write_crash_report(&logging::panic_message(info.payload()), &location, current.name().unwrap_or("unnamed"))
+}
@@ -1,14 +1,15 @@
 use ratatui::{
 	buffer::Buffer,
-	layout::Rect,
+	layout::{Alignment, Rect},
 	style::{Color, Modifier, Style},
 	text::{Line, Span},
-	widgets::{Block, BorderType, Borders, Paragraph, Widget},
+	widgets::{Block, BorderType, Borders, Clear, Paragraph, Widget, Wrap},
 };
 
 use crate::view::{Card, ChatMessage, PlayerStatus, PlayerView, Street, TableView};
-use crate::tui::layout::TableLayout;
+use crate::tui::layout::{CenterLayout, TableLayout, TableShape};
 use crate::theme::Theme;
+use crate::money::{big_blinds, chips};
 
 fn card_style(suit: char, theme: &Theme) -> Style {
 	match suit {
@@ -33,7 +34,57 @@ fn render_hole_cards(cards: &[Card; 2], theme: &Theme) -> Line<'static> {
 }
 
 fn render_hidden_cards(theme: &Theme) -> Line<'static> {
-	Line::styled("▓ ▓", Style::default().fg(theme.hidden_card()))
+	Line::styled(format!("{0} {0}", theme.card_back), Style::default().fg(theme.hidden_card()))
+}
+
+/// A hand still being dealt: nothing yet, or the first card alone.
+fn render_dealing(dealt: u8, cards: Option<&[Card; 2]>, theme: &Theme) -> Line<'static> {
+	match (dealt, cards) {
+		(0, _) => Line::raw(""),
+		(_, Some(cards)) => Line::from(render_card(&cards[0], theme)),
+		(_, None) => Line::styled(theme.card_back.clone(), Style::default().fg(theme.hidden_card())),
+	}
+}
+
+/// The felt under the seats, in the table's border color.
+fn render_felt(area: Rect, shape: TableShape, theme: &Theme, buf: &mut Buffer) {
+	if area.width < 3 || area.height < 3 {
+		return;
+	}
+	let style = Style::default().fg(theme.table_border());
+	match shape {
+		TableShape::Minimal => {}
+		TableShape::Racetrack => {
+			Block::default()
+				.borders(Borders::ALL)
+				.border_type(BorderType::Rounded)
+				.border_style(style)
+				.render(area, buf);
+		}
+		TableShape::Oval => {
+			let rx = (area.width - 1) as f32 / 2.0;
+			let ry = (area.height - 1) as f32 / 2.0;
+			let (cx, cy) = (area.x as f32 + rx, area.y as f32 + ry);
+			// Enough points that no cell on the rim is missed
+			let steps = 4 * (area.width + area.height) as usize;
+			for i in 0..steps {
+				let angle = std::f32::consts::TAU * i as f32 / steps as f32;
+				let x = (cx + rx * angle.cos()).round() as u16;
+				let y = (cy + ry * angle.sin()).round() as u16;
+				if let Some(cell) = buf.cell_mut((x, y)) {
+					cell.set_char('·').set_style(style);
+				}
+			}
+		}
+	}
+}
+
+/// An amount in chips, or in big blinds when `big_blind` is given.
+fn amount_text(amount: f32, big_blind: Option<f32>) -> String {
+	match big_blind {
+		Some(big_blind) => big_blinds(amount, big_blind),
+		None => chips(amount),
+	}
 }
 
 pub struct PlayerWidget<'a> {
@@ -41,17 +92,24 @@ pub struct PlayerWidget<'a> {
 	theme: &'a Theme,
 	show_cards: bool,
 	is_winner: bool,
+	big_blind: Option<f32>,
 }
 
 impl<'a> PlayerWidget<'a> {
 	pub fn new(player: &'a PlayerView, theme: &'a Theme, show_cards: bool) -> Self {
-		Self { player, theme, show_cards, is_winner: false }
+		Self { player, theme, show_cards, is_winner: false, big_blind: None }
 	}
 
 	pub fn winner(mut self, is_winner: bool) -> Self {
 		self.is_winner = is_winner;
 		self
 	}
+
+	/// Show the stack and bet as multiples of `big_blind`.
+	pub fn in_big_blinds(mut self, big_blind: Option<f32>) -> Self {
+		self.big_blind = big_blind;
+		self
+	}
 }
 
 impl Widget for PlayerWidget<'_> {
@@ -67,7 +125,7 @@ impl Widget for PlayerWidget<'_> {
 				PlayerStatus::Folded => (self.theme.folded_border(), BorderType::Plain),
 				PlayerStatus::AllIn => (self.theme.all_in_border(), BorderType::Plain),
 				PlayerStatus::Eliminated => (self.theme.eliminated_border(), BorderType::Plain),
-				PlayerStatus::Empty => (Color::DarkGray, BorderType::Plain),
+				PlayerStatus::Empty => (self.theme.adapt(Color::DarkGray), BorderType::Plain),
 				_ => (self.theme.default_border(), BorderType::Plain),
 			}
 		};
@@ -91,7 +149,7 @@ impl Widget for PlayerWidget<'_> {
 		} else if self.player.is_actor {
 			Style::default().fg(self.theme.actor_name()).add_modifier(Modifier::BOLD)
 		} else if self.player.status == PlayerStatus::Empty {
-			Style::default().fg(Color::DarkGray).add_modifier(Modifier::DIM)
+			Style::default().fg(self.theme.adapt(Color::DarkGray)).add_modifier(Modifier::DIM)
 		} else {
 			Style::default()
 		};
@@ -106,7 +164,7 @@ impl Widget for PlayerWidget<'_> {
 			block = block.title_top(
 				Line::from(Span::styled(
 					"💰",
-					Style::default().fg(Color::Yellow),
+					Style::default().fg(self.theme.adapt(Color::Yellow)),
 				))
 				.left_aligned()
 			);
@@ -125,7 +183,7 @@ impl Widget for PlayerWidget<'_> {
 				Line::from(Span::styled(
 					"◉",
 					Style::default()
-						.fg(Color::White)
+						.fg(self.theme.adapt(Color::White))
 						.add_modifier(Modifier::BOLD),
 				))
 				.right_aligned()
@@ -140,11 +198,14 @@ impl Widget for PlayerWidget<'_> {
 		}
 
 		let cards_line = if self.player.status == PlayerStatus::Empty {
-			Line::styled("—", Style::default().fg(Color::DarkGray).add_modifier(Modifier::DIM))
+			Line::styled("—", Style::default().fg(self.theme.adapt(Color::DarkGray)).add_modifier(Modifier::DIM))
 		} else if self.player.status == PlayerStatus::Folded {
 			Line::styled("folded", Style::default().fg(self.theme.folded_text()))
 		} else if self.player.status == PlayerStatus::Eliminated {
 			Line::styled("out", Style::default().fg(self.theme.eliminated_text()))
+		} else if let Some(dealt) = self.player.dealing {
+			let shown = self.player.hole_cards.as_ref().filter(|_| self.show_cards || self.player.is_hero);
+			render_dealing(dealt, shown, self.theme)
 		} else if let Some(ref cards) = self.player.hole_cards {
 			if self.show_cards || self.player.is_hero {
 				render_hole_cards(cards, self.theme)
@@ -158,10 +219,10 @@ impl Widget for PlayerWidget<'_> {
 		let stack_str = if self.player.status == PlayerStatus::Empty {
 			String::new()
 		} else {
-			format!("${:.0}", self.player.stack)
+			amount_text(self.player.stack, self.big_blind)
 		};
 		let bet_str = if self.player.current_bet > 0.0 {
-			format!(" (${:.0})", self.player.current_bet)
+			format!(" ({})", amount_text(self.player.current_bet, self.big_blind))
 		} else {
 			String::new()
 		};
@@ -172,9 +233,9 @@ impl Widget for PlayerWidget<'_> {
 		];
 
 		if self.player.position == crate::view::Position::SmallBlind {
-			stack_spans.push(Span::styled(" SB", Style::default().fg(Color::DarkGray)));
+			stack_spans.push(Span::styled(" SB", Style::default().fg(self.theme.adapt(Color::DarkGray))));
 		} else if self.player.position == crate::view::Position::BigBlind {
-			stack_spans.push(Span::styled(" BB", Style::default().fg(Color::DarkGray)));
+			stack_spans.push(Span::styled(" BB", Style::default().fg(self.theme.adapt(Color::DarkGray))));
 		}
 
 		let stack_line = Line::from(stack_spans);
@@ -199,7 +260,7 @@ impl Widget for BoardWidget<'_> {
 	fn render(self, area: Rect, buf: &mut Buffer) {
 		let mut spans: Vec<Span> = Vec::new();
 
-		spans.push(Span::styled("[ ", Style::default().fg(Color::DarkGray)));
+		spans.push(Span::styled("[ ", Style::default().fg(self.theme.adapt(Color::DarkGray))));
 
 		for i in 0..5 {
 			if i > 0 {
@@ -208,24 +269,90 @@ impl Widget for BoardWidget<'_> {
 			if let Some(card) = self.board.get(i) {
 				spans.push(render_card(card, self.theme));
 			} else {
-				spans.push(Span::styled("--", Style::default().fg(Color::DarkGray)));
+				spans.push(Span::styled("--", Style::default().fg(self.theme.adapt(Color::DarkGray))));
 			}
 		}
 
-		spans.push(Span::styled(" ]", Style::default().fg(Color::DarkGray)));
+		spans.push(Span::styled(" ]", Style::default().fg(self.theme.adapt(Color::DarkGray))));
 
 		let line = Line::from(spans);
-		let paragraph = Paragraph::new(line);
+		let paragraph = Paragraph::new(line).alignment(Alignment::Center);
 		paragraph.render(area, buf);
 	}
 }
 
+/// The middle of the table: the boards, the pots and chips set apart from
+/// them, each on the row `CenterLayout` gives it.
+pub struct CenterWidget<'a> {
+	view: &'a TableView,
+	theme: &'a Theme,
+	big_blind: Option<f32>,
+}
+
+impl<'a> CenterWidget<'a> {
+	pub fn new(view: &'a TableView, theme: &'a Theme) -> Self {
+		Self { view, theme, big_blind: None }
+	}
+
+	pub fn in_big_blinds(mut self, big_blind: Option<f32>) -> Self {
+		self.big_blind = big_blind;
+		self
+	}
+
+	fn boards(&self) -> Vec<&'a [Card]> {
+		vec![&self.view.board]
+	}
+
+	/// `Pot: $120  SPR 3.2`, or `Main $80  Side $40` with side pots.
+	fn pot_text(&self) -> String {
+		let [main, sides @ ..] = self.view.pots.as_slice() else {
+			let mut text = format!("Pot: {}", amount_text(self.view.pot, self.big_blind));
+			if let Some(spr) = self.view.spr {
+				text.push_str(&format!("  SPR {:.1}", spr));
+			}
+			return text;
+		};
+		let mut parts = vec![format!("Main {}", amount_text(*main, self.big_blind))];
+		for (i, side) in sides.iter().enumerate() {
+			let label = if sides.len() == 1 { "Side".to_string() } else { format!("Side {}", i + 1) };
+			parts.push(format!("{} {}", label, amount_text(*side, self.big_blind)));
+		}
+		parts.join("  ")
+	}
+
+	/// Chips in the middle that aren't part of the betting, like antes.
+	fn chips_text(&self) -> Option<String> {
+		(self.view.antes > 0.0).then(|| format!("Antes {}", amount_text(self.view.antes, self.big_blind)))
+	}
+}
+
+impl Widget for CenterWidget<'_> {
+	fn render(self, area: Rect, buf: &mut Buffer) {
+		let boards = self.boards();
+		let chips = self.chips_text();
+		let layout = CenterLayout::compute(area, boards.len(), chips.is_some());
+
+		for (board, rect) in boards.iter().zip(&layout.boards) {
+			BoardWidget::new(board, self.theme, self.view.street).render(*rect, buf);
+		}
+
+		let pot_line = Line::styled(self.pot_text(), Style::default().fg(self.theme.pot()).add_modifier(Modifier::BOLD));
+		Paragraph::new(pot_line).alignment(Alignment::Center).render(layout.pots, buf);
+
+		if let (Some(text), Some(rect)) = (chips, layout.chips) {
+			let chips_line = Line::styled(text, Style::default().fg(self.theme.pot()));
+			Paragraph::new(chips_line).alignment(Alignment::Center).render(rect, buf);
+		}
+	}
+}
+
 pub struct TableWidget<'a> {
 	view: &'a TableView,
 	theme: &'a Theme,
 	show_all_cards: bool,
 	info_lines: Option<&'a [String]>,
 	info_title: Option<&'a str>,
+	big_blind: Option<f32>,
 }
 
 impl<'a> TableWidget<'a> {
@@ -236,6 +363,7 @@ impl<'a> TableWidget<'a> {
 			show_all_cards: view.street == Street::Showdown,
 			info_lines: None,
 			info_title: None,
+			big_blind: None,
 		}
 	}
 
@@ -249,21 +377,28 @@ impl<'a> TableWidget<'a> {
 		self.info_lines = Some(lines);
 		self
 	}
+
+	/// Show stacks, bets and the pot in big blinds, once the view knows
+	/// what the big blind is.
+	pub fn in_big_blinds(mut self, on: bool) -> Self {
+		self.big_blind = (on && self.view.blinds.1 > 0.0).then_some(self.view.blinds.1);
+		self
+	}
 }
 
 impl Widget for TableWidget<'_> {
 	fn render(self, area: Rect, buf: &mut Buffer) {
 		let title = if let Some(ref name) = self.view.table_name {
 			format!(
-				" {} | Hand #{} - {} ",
+				" {} | {} - {} ",
 				name,
-				self.view.hand_num,
+				self.view.hand_label(),
 				self.view.street.name()
 			)
 		} else {
 			format!(
-				" Hand #{} - {} ",
-				self.view.hand_num,
+				" {} - {} ",
+				self.view.hand_label(),
 				self.view.street.name()
 			)
 		};
@@ -272,20 +407,32 @@ impl Widget for TableWidget<'_> {
 			.borders(Borders::ALL)
 			.border_style(Style::default().fg(self.theme.table_border()))
 			.title(title)
-			.title_bottom(format!(
-				" Blinds ${:.0}/${:.0} ",
-				self.view.blinds.0, self.view.blinds.1
-			));
+			.title_bottom(match self.view.current_game {
+				Some(ref game) => format!(
+					" {} - Blinds {}/{} ",
+					game, chips(self.view.blinds.0), chips(self.view.blinds.1)
+				),
+				None => format!(
+					" Blinds {}/{} ",
+					chips(self.view.blinds.0), chips(self.view.blinds.1)
+				),
+			});
 
 		let inner = outer_block.inner(area);
 		outer_block.render(area, buf);
 
-		let layout = TableLayout::compute(inner, self.view.players.len());
+		let profile = self.theme.layout_profile();
+		let layout = TableLayout::compute(inner, self.view.players.len(), profile);
+		if let Some(felt) = layout.felt {
+			render_felt(felt, profile.shape, self.theme, buf);
+		}
 
 		for (i, player) in self.view.players.iter().enumerate() {
 			if let Some(seat_pos) = layout.seats.get(i) {
 				let is_winner = self.view.winner_seats.contains(&player.seat);
-				let widget = PlayerWidget::new(player, self.theme, self.show_all_cards).winner(is_winner);
+				let widget = PlayerWidget::new(player, self.theme, self.show_all_cards)
+					.winner(is_winner)
+					.in_big_blinds(self.big_blind);
 				widget.render(seat_pos.rect(), buf);
 
 				if let Some(action) = &player.last_action {
@@ -297,9 +444,9 @@ impl Widget for TableWidget<'_> {
 					};
 					if action_rect.y < inner.y + inner.height {
 						let style = if player.action_fresh {
-							Style::default().fg(Color::White)
+							Style::default().fg(self.theme.adapt(Color::White))
 						} else {
-							Style::default().fg(Color::DarkGray)
+							Style::default().fg(self.theme.adapt(Color::DarkGray))
 						};
 						let action_text = if action.len() > action_rect.width as usize {
 							format!("{}…", &action[..action_rect.width as usize - 1])
@@ -312,12 +459,9 @@ impl Widget for TableWidget<'_> {
 			}
 		}
 
-		let board_widget = BoardWidget::new(&self.view.board, self.theme, self.view.street);
-		board_widget.render(layout.board_area, buf);
-
-		let pot_str = format!("Pot: ${:.0}", self.view.pot);
-		let pot_line = Line::styled(pot_str, Style::default().fg(self.theme.pot()).add_modifier(Modifier::BOLD));
-		Paragraph::new(pot_line).render(layout.pot_area, buf);
+		CenterWidget::new(self.view, self.theme)
+			.in_big_blinds(self.big_blind)
+			.render(layout.center_area, buf);
 
 		let chat_widget = ChatWidget::new(&self.view.chat_messages, self.theme);
 		chat_widget.render(layout.chat_area, buf);
@@ -409,3 +553,102 @@ impl Widget for InfoWidget<'_> {
 		Paragraph::new(display_lines).render(inner, buf);
 	}
 }
+
+/// In-game keys, as shown in the help overlay.
+pub(crate) const HELP_KEYS: &[(&str, &str)] = &[
+	("f", "Fold"),
+	("c", "Check / Call"),
+	("b", "Bet"),
+	("r", "Raise (←/→ to adjust, Enter to confirm)"),
+	("a", "All-in"),
+	("s", "Show a hand that won uncalled"),
+	("m", "Chat"),
+	("1 2 3", "While waiting: check/fold, check, call any"),
+	("n", "Change tables (cash)"),
+	(">", "Fast-forward AI decisions"),
+	("+ -", "While watching: play faster / slower"),
+	("P", "Pause / resume the game (host)"),
+	("#", "Tag this hand (Enter alone: review later)"),
+	("d", "Detach, leaving the game to `poker daemon`"),
+	("$", "Show amounts in big blinds / chips"),
+	("p", "Preferences"),
+	("t", "Cycle theme"),
+	("?", "Close this help"),
+	("q", "Quit"),
+];
+
+/// Best first.
+pub(crate) const HAND_RANKINGS: &[(&str, &str)] = &[
+	("Straight flush", "Five in a row, one suit (A-K-Q-J-T is a royal flush)"),
+	("Four of a kind", "Four cards of one rank"),
+	("Full house", "Three of a kind and a pair"),
+	("Flush", "Five cards of one suit"),
+	("Straight", "Five in a row; the ace plays high or low"),
+	("Three of a kind", "Three cards of one rank"),
+	("Two pair", "Two different pairs"),
+	("One pair", "Two cards of one rank"),
+	("High card", "None of the above; highest cards win"),
+];
+
+/// Keys, hand rankings and the table's betting rules, drawn over the
+/// middle of `area`.
+pub struct HelpWidget<'a> {
+	rules: &'a [String],
+	theme: &'a Theme,
+}
+
+impl<'a> HelpWidget<'a> {
+	pub fn new(rules: &'a [String], theme: &'a Theme) -> Self {
+		Self { rules, theme }
+	}
+}
+
+impl Widget for HelpWidget<'_> {
+	fn render(self, area: Rect, buf: &mut Buffer) {
+		let heading = Style::default().fg(self.theme.menu_highlight()).add_modifier(Modifier::BOLD);
+		let key_style = Style::default().fg(self.theme.stack());
+		let text = Style::default().fg(self.theme.menu_text());
+
+		let mut lines = vec![Line::styled("Keys", heading)];
+		for (key, action) in HELP_KEYS {
+			lines.push(Line::from(vec![
+				Span::styled(format!("  {:<6}", key), key_style),
+				Span::styled(*action, text),
+			]));
+		}
+		lines.push(Line::raw(""));
+		lines.push(Line::styled("Hand rankings (best first)", heading));
+		for (i, (hand, meaning)) in HAND_RANKINGS.iter().enumerate() {
+			lines.push(Line::from(vec![
+				Span::styled(format!("  {}. {:<16}", i + 1, hand), key_style),
+				Span::styled(*meaning, text),
+			]));
+		}
+		lines.push(Line::raw(""));
+		lines.push(Line::styled("Betting rules", heading));
+		for rule in self.rules {
+			lines.push(Line::styled(format!("  {}", rule), text));
+		}
+
+		let width = area.width.saturating_sub(4).min(76);
+		let height = area.height.saturating_sub(2).min(lines.len() as u16 + 2);
+		let popup = Rect::new(
+			area.x + (area.width - width) / 2,
+			area.y + (area.height - height) / 2,
+			width,
+			height,
+		);
+
+		Clear.render(popup, buf);
+		Paragraph::new(lines)
+			.wrap(Wrap { trim: false })
+			.block(
+				Block::default()
+					.title(" Help ('?' to close) ")
+					.borders(Borders::ALL)
+					.border_style(Style::default().fg(self.theme.menu_highlight()))
+					.style(Style::default().bg(self.theme.background())),
+			)
+			.render(popup, buf);
+	}
+}
@@ -0,0 +1,432 @@
+//! `poker tutorial`: scripted hands that walk a new player through the
+//! game. Each lesson deals known cards (`Session::deal_next`) to opponents
+//! who follow a script, and coaches the hero through each decision,
+//! asking again until they make the move the lesson is teaching.
+
+use std::fs;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::{backend::CrosstermBackend, Terminal};
+use serde::Deserialize;
+
+use crate::config::resolve_config;
+use crate::engine::{Session, SessionConfig, Step};
+use crate::error::PokerError;
+use crate::events::{Card, ChatSender, GameEvent, PlayerAction, RaiseOptions, Seat, ValidActions};
+use crate::players::{PlayerResponse, TestPlayer};
+use crate::theme::Theme;
+use crate::tui::{GameUI, GameUIAction};
+
+const DEFAULT_LESSONS: &str = include_str!("../config/tutorial.toml");
+
+/// Pause after each opponent action and new street, so the hand can be
+/// followed.
+const EVENT_PAUSE: Duration = Duration::from_millis(700);
+
+/// Width the coach's notes are wrapped to for the info panel.
+const COACH_WIDTH: usize = 36;
+
+/// A move the lesson asks for. `Raise` covers bets too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Move {
+	Fold,
+	Check,
+	Call,
+	Raise,
+	AllIn,
+}
+
+impl Move {
+	pub fn of(action: &PlayerAction) -> Option<Self> {
+		match action {
+			PlayerAction::Fold => Some(Move::Fold),
+			PlayerAction::Check => Some(Move::Check),
+			PlayerAction::Call { .. } => Some(Move::Call),
+			PlayerAction::Bet { .. } | PlayerAction::Raise { .. } => Some(Move::Raise),
+			PlayerAction::AllIn { .. } => Some(Move::AllIn),
+			PlayerAction::Timeout | PlayerAction::Show | PlayerAction::Muck => None,
+		}
+	}
+
+	/// The smallest legal action of this kind, if there is one.
+	pub fn action(&self, valid: &ValidActions) -> Option<PlayerAction> {
+		match self {
+			Move::Fold => valid.can_fold.then_some(PlayerAction::Fold),
+			Move::Check => valid.can_check.then_some(PlayerAction::Check),
+			Move::Call => valid.call_amount.map(|amount| PlayerAction::Call { amount }),
+			Move::Raise => valid.raise_options.as_ref().map(|options| {
+				let amount = match options {
+					RaiseOptions::Fixed { amount } => *amount,
+					RaiseOptions::Variable { min_raise, .. } => *min_raise,
+				};
+				PlayerAction::Raise { amount }
+			}),
+			Move::AllIn => valid.can_all_in.then_some(PlayerAction::AllIn { amount: valid.all_in_amount }),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LessonSeat {
+	pub name: String,
+	/// Two cards, like "As Kd".
+	pub cards: String,
+	#[serde(default)]
+	pub hero: bool,
+	/// What this opponent does, in order.
+	#[serde(default)]
+	pub actions: Vec<String>,
+}
+
+/// One of the hero's decisions.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Task {
+	pub prompt: String,
+	/// Any move will do if unset.
+	#[serde(default)]
+	pub expect: Option<Move>,
+	/// Shown when the hero does something else.
+	#[serde(default)]
+	pub wrong: Option<String>,
+	/// Shown once the move is made.
+	#[serde(default)]
+	pub explain: Option<String>,
+}
+
+impl Task {
+	/// Whether `action` is what this task asks for, or the nudge to show if not.
+	pub fn check(&self, action: &PlayerAction) -> Result<(), String> {
+		match self.expect {
+			Some(expected) if Move::of(action) != Some(expected) => Err(self
+				.wrong
+				.clone()
+				.unwrap_or_else(|| "That's not the move this lesson is after. Try again.".to_string())),
+			_ => Ok(()),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Lesson {
+	pub title: String,
+	#[serde(default)]
+	pub intro: Vec<String>,
+	#[serde(default)]
+	pub outro: Vec<String>,
+	/// In seat order; the first seat has the button.
+	pub seats: Vec<LessonSeat>,
+	/// Five cards, like "Kh 9s 4d 8c 3h".
+	pub board: String,
+	#[serde(default)]
+	pub tasks: Vec<Task>,
+}
+
+#[derive(Deserialize)]
+struct LessonsFile {
+	lessons: Vec<Lesson>,
+}
+
+impl Lesson {
+	/// A one-hand session dealt as the lesson says, and the hero's seat.
+	pub fn session(&self) -> Result<(Session, Seat), PokerError> {
+		let bad = |e: String| PokerError::parse(format!("lesson \"{}\"", self.title), e);
+		let mut session = Session::new(SessionConfig { max_hands: Some(1), ..SessionConfig::default() });
+		let mut hero = None;
+		let mut hole_cards = Vec::new();
+		for (i, seat) in self.seats.iter().enumerate() {
+			let cards = parse_cards(&seat.cards).map_err(bad)?;
+			let [first, second] = cards[..] else {
+				return Err(bad(format!("{} needs two cards, got \"{}\"", seat.name, seat.cards)));
+			};
+			hole_cards.push([first, second]);
+
+			if seat.hero {
+				hero = Some(session.add_external_player(&seat.name)?);
+			} else {
+				let actions = seat.actions.iter().map(|a| parse_action(a)).collect::<Result<Vec<_>, _>>().map_err(bad)?;
+				let player = TestPlayer::new(Seat(i), seat.name.clone())
+					.with_actions(actions)
+					.with_default(PlayerAction::Check);
+				session.add_player(Arc::new(player))?;
+			}
+		}
+		let hero = hero.ok_or_else(|| bad("no hero seat".to_string()))?;
+		session.deal_next(hole_cards, parse_cards(&self.board).map_err(bad)?);
+		Ok((session, hero))
+	}
+}
+
+pub fn parse_lessons(content: &str) -> Result<Vec<Lesson>, PokerError> {
+	let file: LessonsFile = toml::from_str(content)
+		.map_err(|e| PokerError::parse("tutorial", e))?;
+	Ok(file.lessons)
+}
+
+/// Lessons from the config directory's `tutorial.toml`, or the built-in ones.
+pub fn load_lessons() -> Result<Vec<Lesson>, PokerError> {
+	match resolve_config("tutorial.toml") {
+		Ok(path) => {
+			let content = fs::read_to_string(&path)
+				.map_err(|e| PokerError::io("read", &path, e))?;
+			parse_lessons(&content)
+		}
+		Err(_) => parse_lessons(DEFAULT_LESSONS),
+	}
+}
+
+fn parse_cards(text: &str) -> Result<Vec<Card>, String> {
+	text.split_whitespace()
+		.map(|card| {
+			let mut chars = card.chars();
+			match (chars.next(), chars.next(), chars.next()) {
+				(Some(rank), Some(suit), None) if "23456789TJQKA".contains(rank) && "shdc".contains(suit) => {
+					Ok(Card::new(rank, suit))
+				}
+				_ => Err(format!("Bad card \"{}\"", card)),
+			}
+		})
+		.collect()
+}
+
+fn parse_action(text: &str) -> Result<PlayerAction, String> {
+	let mut words = text.split_whitespace();
+	let amount = |word: Option<&str>| {
+		word.and_then(|w| w.parse::<f32>().ok())
+			.ok_or_else(|| format!("\"{}\" needs an amount", text))
+	};
+	match words.next() {
+		Some("fold") => Ok(PlayerAction::Fold),
+		Some("check") => Ok(PlayerAction::Check),
+		Some("call") => Ok(PlayerAction::Call { amount: 0.0 }),
+		Some("bet") => Ok(PlayerAction::Bet { amount: amount(words.next())? }),
+		Some("raise") => Ok(PlayerAction::Raise { amount: amount(words.next())? }),
+		Some("all-in") => Ok(PlayerAction::AllIn { amount: 0.0 }),
+		_ => Err(format!("Unknown action \"{}\"", text)),
+	}
+}
+
+/// Greedy word wrap for the coach panel.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+	let mut lines = Vec::new();
+	let mut line = String::new();
+	for word in text.split_whitespace() {
+		if !line.is_empty() && line.len() + 1 + word.len() > width {
+			lines.push(std::mem::take(&mut line));
+		}
+		if !line.is_empty() {
+			line.push(' ');
+		}
+		line.push_str(word);
+	}
+	if !line.is_empty() {
+		lines.push(line);
+	}
+	lines
+}
+
+fn coach(ui: &mut GameUI, paragraphs: &[String]) {
+	ui.info_lines = paragraphs
+		.iter()
+		.flat_map(|p| wrap(p, COACH_WIDTH).into_iter().chain([String::new()]))
+		.collect();
+}
+
+fn tell(ui: &mut GameUI, text: &str) {
+	ui.apply_event(&GameEvent::ChatMessage { sender: ChatSender::Dealer, text: text.to_string() });
+}
+
+type Term = Terminal<CrosstermBackend<io::Stdout>>;
+
+/// Runs every lesson in turn, until they're done or the player quits.
+pub fn run_tutorial(terminal: &mut Term, theme: Theme, theme_name: String) -> io::Result<()> {
+	let lessons = load_lessons().map_err(|e| io::Error::other(e.user_message()))?;
+	for (i, lesson) in lessons.iter().enumerate() {
+		let label = format!("Lesson {} of {}", i + 1, lessons.len());
+		if !play_lesson(terminal, lesson, &label, &theme, &theme_name)? {
+			break;
+		}
+	}
+	Ok(())
+}
+
+/// Plays one lesson; false if the player quit.
+fn play_lesson(terminal: &mut Term, lesson: &Lesson, label: &str, theme: &Theme, theme_name: &str) -> io::Result<bool> {
+	let (mut session, hero) = lesson.session().map_err(|e| io::Error::other(e.user_message()))?;
+	let mut ui = GameUI::new(Some(hero), theme.clone(), theme_name.to_string());
+	ui.live_info = false;
+	ui.set_table_info(lesson.title.clone(), label.to_string(), Vec::new());
+	coach(&mut ui, &lesson.intro);
+	ui.status_message = Some("Press Enter to deal".to_string());
+	if !wait_for_enter(terminal, &mut ui)? {
+		return Ok(false);
+	}
+
+	let mut tasks = lesson.tasks.iter();
+	let mut valid = None;
+	loop {
+		let step = session.step();
+		for event in session.drain_events() {
+			if let GameEvent::ActionRequest { seat, valid_actions, .. } = &event {
+				if *seat == hero {
+					valid = Some(valid_actions.clone());
+				}
+			}
+			let pause = matches!(&event, GameEvent::ActionTaken { seat, .. } if *seat != hero)
+				|| matches!(event, GameEvent::StreetChanged { .. });
+			ui.apply_event(&event);
+			if pause {
+				terminal.draw(|f| ui.render(f, f.area()))?;
+				std::thread::sleep(EVENT_PAUSE);
+			}
+		}
+
+		match step {
+			Step::WaitingForAction(_) => {
+				let task = tasks.next();
+				if let Some(task) = task {
+					coach(&mut ui, std::slice::from_ref(&task.prompt));
+				}
+				let Some(valid) = valid.take() else {
+					return Err(io::Error::other("Asked to act without an action request"));
+				};
+				loop {
+					let action = match ui.enter_action_mode(valid.clone()) {
+						Some(action) => action,
+						None => match wait_for_action(terminal, &mut ui)? {
+							Some(action) => action,
+							None => return Ok(false),
+						},
+					};
+					match task.map_or(Ok(()), |t| t.check(&action)) {
+						Ok(()) => {
+							if let Some(explain) = task.and_then(|t| t.explain.as_ref()) {
+								coach(&mut ui, std::slice::from_ref(explain));
+							}
+							session.submit_action(hero, action).map_err(|e| io::Error::other(e.user_message()))?;
+							break;
+						}
+						Err(nudge) => {
+							tell(&mut ui, "Not quite. See the coach's note.");
+							coach(&mut ui, &[nudge]);
+						}
+					}
+				}
+			}
+			Step::HandComplete => {
+				coach(&mut ui, &lesson.outro);
+				ui.status_message = Some("Lesson complete! Enter to go on, q to stop".to_string());
+				return wait_for_enter(terminal, &mut ui);
+			}
+			Step::GameOver => return Ok(true),
+		}
+	}
+}
+
+/// True on Enter, false on q, Esc or Ctrl-C.
+fn wait_for_enter(terminal: &mut Term, ui: &mut GameUI) -> io::Result<bool> {
+	loop {
+		terminal.draw(|f| ui.render(f, f.area()))?;
+		if event::poll(Duration::from_millis(50))? {
+			if let Event::Key(key) = event::read()? {
+				if key.kind != KeyEventKind::Press {
+					continue;
+				}
+				match key.code {
+					KeyCode::Enter => return Ok(true),
+					KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+					KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(false),
+					KeyCode::Char('?') => {
+						ui.handle_key(key.code);
+					}
+					_ => {}
+				}
+			}
+		}
+	}
+}
+
+/// The hero's action from the keyboard, or `None` if they quit.
+fn wait_for_action(terminal: &mut Term, ui: &mut GameUI) -> io::Result<Option<PlayerAction>> {
+	loop {
+		terminal.draw(|f| ui.render(f, f.area()))?;
+		if event::poll(Duration::from_millis(50))? {
+			if let Event::Key(key) = event::read()? {
+				if key.kind != KeyEventKind::Press {
+					continue;
+				}
+				if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+					return Ok(None);
+				}
+				match ui.handle_key(key.code) {
+					GameUIAction::Respond(PlayerResponse::Action(action)) => return Ok(Some(action)),
+					GameUIAction::Quit => return Ok(None),
+					_ => {}
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_bundled_lessons_play_through_as_scripted() {
+		let lessons = parse_lessons(DEFAULT_LESSONS).unwrap();
+		assert!(lessons.len() >= 3);
+		for lesson in &lessons {
+			let (mut session, hero) = lesson.session().unwrap();
+			let mut tasks = lesson.tasks.iter();
+			let mut valid = None;
+			loop {
+				let step = session.step();
+				for event in session.drain_events() {
+					if let GameEvent::ActionRequest { seat, valid_actions, .. } = event {
+						if seat == hero {
+							valid = Some(valid_actions);
+						}
+					}
+				}
+				match step {
+					Step::WaitingForAction(_) => {
+						let task = tasks.next().unwrap_or_else(|| panic!("{}: hero acts more often than it has tasks", lesson.title));
+						let expected = task.expect.expect("bundled tasks say what to do");
+						let action = expected.action(valid.as_ref().unwrap())
+							.unwrap_or_else(|| panic!("{}: can't {:?} at \"{}\"", lesson.title, expected, task.prompt));
+						assert!(task.check(&action).is_ok());
+						session.submit_action(hero, action).unwrap();
+					}
+					Step::HandComplete | Step::GameOver => break,
+				}
+			}
+			assert!(tasks.next().is_none(), "{}: hand ended with tasks left", lesson.title);
+		}
+	}
+
+	#[test]
+	fn test_task_check_nudges_the_wrong_move() {
+		let task = Task {
+			prompt: "Check".into(),
+			expect: Some(Move::Check),
+			wrong: Some("Press Enter to check.".into()),
+			explain: None,
+		};
+		assert!(task.check(&PlayerAction::Check).is_ok());
+		assert_eq!(task.check(&PlayerAction::Fold).unwrap_err(), "Press Enter to check.");
+		assert!(Task { expect: Some(Move::Raise), ..task }.check(&PlayerAction::Bet { amount: 20.0 }).is_ok());
+	}
+
+	#[test]
+	fn test_parsing_cards_actions_and_wrapping() {
+		assert_eq!(parse_cards("As Td").unwrap(), vec![Card::new('A', 's'), Card::new('T', 'd')]);
+		assert!(parse_cards("Ax").is_err());
+		assert!(matches!(parse_action("raise 60"), Ok(PlayerAction::Raise { amount }) if amount == 60.0));
+		assert!(parse_action("bet").is_err());
+		assert_eq!(wrap("the quick brown fox", 10), vec!["the quick", "brown fox"]);
+	}
+}
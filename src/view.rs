@@ -1,5 +1,7 @@
 use serde::Deserialize;
 
+use crate::money::chips;
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Card {
 	pub rank: char,
@@ -70,6 +72,10 @@ pub struct PlayerView {
 	pub is_actor: bool,
 	pub last_action: Option<String>,
 	pub action_fresh: bool,
+	/// While the deal goes round, how many of their two cards have come;
+	/// `None` once they have both.
+	#[serde(default)]
+	pub dealing: Option<u8>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
@@ -96,11 +102,22 @@ impl Street {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct TableView {
+	/// The game's short id, once a hand has said it.
 	pub game_id: Option<String>,
 	pub hand_num: u32,
 	pub street: Street,
 	pub board: Vec<Card>,
 	pub pot: f32,
+	/// Stack-to-pot ratio as the street started, after the flop.
+	#[serde(default)]
+	pub spr: Option<f32>,
+	/// Main pot first, then side pots, as the street started; empty while
+	/// there's only the one.
+	#[serde(default)]
+	pub pots: Vec<f32>,
+	/// Antes in the pot this hand, kept apart from the bets.
+	#[serde(default)]
+	pub antes: f32,
 	pub players: Vec<PlayerView>,
 	pub blinds: (f32, f32),
 	#[serde(default)]
@@ -112,7 +129,56 @@ pub struct TableView {
 	#[serde(default)]
 	pub table_info: Option<String>,
 	#[serde(default)]
+	pub current_game: Option<String>,
+	#[serde(default)]
 	pub winner_seats: Vec<usize>,
+	#[serde(default)]
+	pub info: InfoPanelState,
+	/// Held between actions until the game resumes.
+	#[serde(default)]
+	pub paused: bool,
+}
+
+/// Running numbers for the info panel, kept up to date from events.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct InfoPanelState {
+	pub hands_played: u32,
+	/// All pots of finished hands, for the average.
+	pub total_pots: f32,
+	pub biggest_pot: f32,
+	/// Players with chips, as of the latest hand.
+	pub players_remaining: usize,
+	/// Counts up each time the blinds change.
+	pub blind_level: u32,
+	/// Awarded so far in the hand being played.
+	pub hand_pot: f32,
+}
+
+impl InfoPanelState {
+	pub fn average_pot(&self) -> f32 {
+		if self.hands_played == 0 {
+			0.0
+		} else {
+			self.total_pots / self.hands_played as f32
+		}
+	}
+
+	/// The panel's live section. Tournaments add the blind level and
+	/// who's left.
+	pub fn lines(&self, blinds: (f32, f32), tournament: bool) -> Vec<String> {
+		let mut lines = Vec::new();
+		if tournament {
+			lines.push(format!("Level {}: {}/{}", self.blind_level, chips(blinds.0), chips(blinds.1)));
+			lines.push(format!("Players left: {}", self.players_remaining));
+		}
+		lines.push(format!("Hands played: {}", self.hands_played));
+		if self.hands_played > 0 {
+			lines.push(format!("Average pot: {}", chips(self.average_pot())));
+			lines.push(format!("Biggest pot: {}", chips(self.biggest_pot)));
+		}
+		lines
+	}
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -140,13 +206,19 @@ impl TableView {
 			street: Street::Preflop,
 			board: Vec::new(),
 			pot: 0.0,
+			spr: None,
+			pots: Vec::new(),
+			antes: 0.0,
 			players: Vec::new(),
 			blinds: (0.0, 0.0),
 			action_prompt: None,
 			chat_messages: Vec::new(),
 			table_name: None,
 			table_info: None,
+			current_game: None,
 			winner_seats: Vec::new(),
+			info: InfoPanelState::default(),
+			paused: false,
 		}
 	}
 
@@ -163,6 +235,21 @@ impl TableView {
 	pub fn hero(&self) -> Option<&PlayerView> {
 		self.players.iter().find(|p| p.is_hero)
 	}
+
+	/// `56789abc-3`, to find the hand in histories by, once the game's id
+	/// is known and a hand has been dealt.
+	pub fn hand_ref(&self) -> Option<String> {
+		let game_id = self.game_id.as_ref().filter(|_| self.hand_num > 0)?;
+		Some(format!("{}-{}", game_id, self.hand_num))
+	}
+
+	/// `Hand 56789abc-3` once the game's id is known, else `Hand #3`.
+	pub fn hand_label(&self) -> String {
+		match &self.game_id {
+			Some(game_id) => format!("Hand {}-{}", game_id, self.hand_num),
+			None => format!("Hand #{}", self.hand_num),
+		}
+	}
 }
 
 impl Default for TableView {
@@ -232,6 +319,7 @@ mod tests {
 			is_actor: false,
 			last_action: None,
 			action_fresh: false,
+			dealing: None,
 		});
 		view.players.push(PlayerView {
 			seat: 1,
@@ -245,6 +333,7 @@ mod tests {
 			is_actor: true,
 			last_action: None,
 			action_fresh: false,
+			dealing: None,
 		});
 		
 		let actor = view.actor().unwrap();
@@ -266,9 +355,34 @@ mod tests {
 			is_actor: false,
 			last_action: None,
 			action_fresh: false,
+			dealing: None,
 		});
 		
 		let hero = view.hero().unwrap();
 		assert_eq!(hero.name, "Human");
 	}
+
+	#[test]
+	fn test_hand_label_gives_the_reference_once_known() {
+		use crate::events::{Blinds, GameEvent, GameId, HandId, Seat, ViewUpdater};
+
+		let mut view = TableView::new();
+		let mut started = GameEvent::HandStarted {
+			hand_id: HandId(1),
+			hand_num: 3,
+			button: Seat(0),
+			blinds: Blinds { small: 1.0, big: 2.0, ante: None },
+			seats: Vec::new(),
+			at: None,
+			game_id: None,
+		};
+		ViewUpdater::new(None).apply(&mut view, &started);
+		assert_eq!(view.hand_label(), "Hand #3");
+
+		if let GameEvent::HandStarted { game_id, .. } = &mut started {
+			*game_id = Some(GameId(0x1234_5678_9abc));
+		}
+		ViewUpdater::new(None).apply(&mut view, &started);
+		assert_eq!(view.hand_label(), "Hand 56789abc-3");
+	}
 }
@@ -0,0 +1,100 @@
+//! JavaScript bindings. Build with
+//! `wasm-pack build --no-default-features --features wasm`.
+//!
+//! Everything crosses the boundary as JSON: the config passed to `new`,
+//! actions passed to `submitAction`, and the events returned by
+//! `takeEvents`.
+
+use std::sync::Arc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::engine::{Session, SessionConfig, Step};
+use crate::events::{PlayerAction, Seat};
+use crate::players::RulesPlayer;
+use crate::strategy::StrategyStore;
+
+const STRATEGIES: &str = include_str!("../config/strategies.toml");
+
+fn js_error(message: impl Into<String>) -> JsValue {
+	JsValue::from_str(&message.into())
+}
+
+#[wasm_bindgen]
+pub struct WasmGame {
+	session: Session,
+	strategies: StrategyStore,
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+	/// `config_json` holds any `SessionConfig` fields; missing ones take
+	/// their defaults.
+	#[wasm_bindgen(constructor)]
+	pub fn new(config_json: &str) -> Result<WasmGame, JsValue> {
+		let config: SessionConfig = if config_json.trim().is_empty() {
+			SessionConfig::default()
+		} else {
+			serde_json::from_str(config_json).map_err(|e| js_error(format!("Invalid config: {}", e)))?
+		};
+		let strategies = StrategyStore::from_toml(STRATEGIES).map_err(js_error)?;
+		Ok(Self {
+			session: Session::new(config),
+			strategies,
+		})
+	}
+
+	/// Seats a rules-based AI and returns its seat number.
+	#[wasm_bindgen(js_name = addAiPlayer)]
+	pub fn add_ai_player(&mut self, name: &str, strategy: &str) -> Result<usize, JsValue> {
+		let seat = self.session.next_seat();
+		let player = RulesPlayer::new(seat, name, self.strategies.get_or_default(strategy));
+		self.session.add_player(Arc::new(player)).map(|s| s.0).map_err(js_error)
+	}
+
+	/// Seats a player whose actions come from `submitAction`.
+	#[wasm_bindgen(js_name = addHumanPlayer)]
+	pub fn add_human_player(&mut self, name: &str) -> Result<usize, JsValue> {
+		self.session.add_external_player(name).map(|s| s.0).map_err(js_error)
+	}
+
+	/// Plays until a human has to act or the game ends. Returns the seat
+	/// that has to act, or `undefined` once the game is over.
+	pub fn advance(&mut self) -> Option<usize> {
+		match self.session.run_until_blocked() {
+			Step::WaitingForAction(seat) => Some(seat.0),
+			Step::HandComplete | Step::GameOver => None,
+		}
+	}
+
+	/// Plays one step: at most one hand, stopping early for a human.
+	/// Returns `"waiting"`, `"hand_complete"` or `"game_over"`.
+	pub fn step(&mut self) -> String {
+		match self.session.step() {
+			Step::WaitingForAction(_) => "waiting",
+			Step::HandComplete => "hand_complete",
+			Step::GameOver => "game_over",
+		}
+		.to_string()
+	}
+
+	/// `action_json` is a serialized `PlayerAction`, e.g. `"Fold"` or
+	/// `{"Raise":{"amount":40}}`.
+	#[wasm_bindgen(js_name = submitAction)]
+	pub fn submit_action(&mut self, seat: usize, action_json: &str) -> Result<(), JsValue> {
+		let action: PlayerAction = serde_json::from_str(action_json)
+			.map_err(|e| js_error(format!("Invalid action: {}", e)))?;
+		self.session.submit_action(Seat(seat), action).map_err(js_error)
+	}
+
+	/// Events since the last call, as a JSON array.
+	#[wasm_bindgen(js_name = takeEvents)]
+	pub fn take_events(&mut self) -> Result<String, JsValue> {
+		serde_json::to_string(&self.session.drain_events()).map_err(|e| js_error(e.to_string()))
+	}
+
+	#[wasm_bindgen(js_name = isOver)]
+	pub fn is_over(&self) -> bool {
+		self.session.is_over()
+	}
+}
@@ -1,9 +1,12 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use tokio::runtime::Runtime;
 use transparent_poker::engine::{BettingStructure, GameRunner, RunnerConfig};
-use transparent_poker::events::{GameEvent, PlayerAction, Seat};
-use transparent_poker::players::TestPlayer;
+use transparent_poker::events::{
+	BlindType, ChatSender, GameEvent, PlayerAction, Seat, Street, ValidActions,
+};
+use transparent_poker::players::{GameSnapshot, HandRecap, PlayerPort, PlayerResponse, TestPlayer};
 
 fn create_runner(config: RunnerConfig) -> (GameRunner, transparent_poker::engine::GameHandle, Runtime) {
 	let runtime = Runtime::new().expect("Failed to create tokio runtime");
@@ -20,12 +23,17 @@ fn test_heads_up_game_completes() {
 		starting_stack: 100.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(50),
 		seed: Some(12345),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -61,12 +69,17 @@ fn test_raise_cap_enforced() {
 		starting_stack: 1000.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 2,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(5),
 		seed: Some(99999),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -127,12 +140,17 @@ fn test_three_player_game_completes() {
 		starting_stack: 30.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(20),
 		seed: Some(42),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -178,12 +196,17 @@ fn test_all_in_scenario() {
 		starting_stack: 50.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(5),
 		seed: Some(77777),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -231,12 +254,17 @@ fn test_blind_posting() {
 		starting_stack: 100.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(3),
 		seed: Some(11111),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -285,12 +313,17 @@ fn test_hand_started_event() {
 		starting_stack: 100.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(3),
 		seed: Some(22222),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -327,12 +360,17 @@ fn test_folding_player_loses_blinds() {
 		starting_stack: 100.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(3),
 		seed: Some(33333),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -369,12 +407,17 @@ fn test_stack_conservation() {
 		starting_stack: 100.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(10),
 		seed: Some(44444),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -412,12 +455,17 @@ fn test_fixed_limit_betting() {
 		starting_stack: 200.0,
 		betting_structure: BettingStructure::FixedLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(5),
 		seed: Some(55555),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -456,12 +504,17 @@ fn test_pot_limit_betting() {
 		starting_stack: 200.0,
 		betting_structure: BettingStructure::PotLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(5),
 		seed: Some(66666),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -499,12 +552,17 @@ fn test_multiway_pot() {
 		starting_stack: 100.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(3),
 		seed: Some(88888),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -556,12 +614,17 @@ fn test_side_pot_creation() {
 		starting_stack: 50.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(1),
 		seed: Some(99991),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -605,12 +668,17 @@ fn test_game_with_rake() {
 		starting_stack: 100.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.05, // 5% rake
 		rake_cap: Some(5.0), // $5 cap
 		no_flop_no_drop: true,
+		jackpot: None,
 		max_hands: Some(5),
 		seed: Some(11112),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -649,12 +717,17 @@ fn test_elimination_order() {
 		starting_stack: 100.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(20),
 		seed: Some(22223),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -699,12 +772,17 @@ fn test_button_rotation() {
 		starting_stack: 500.0,
 		betting_structure: BettingStructure::NoLimit,
 		blind_clock: None,
+		game_rotation: None,
 		max_raises_per_round: 4,
 		rake_percent: 0.0,
 		rake_cap: None,
 		no_flop_no_drop: false,
+		jackpot: None,
 		max_hands: Some(4),
 		seed: Some(33334),
+		game_id: None,
+		max_seats: None,
+		action_timeout: None,
 	};
 
 	let (mut runner, handle, _runtime) = create_runner(config);
@@ -733,3 +811,474 @@ fn test_button_rotation() {
 		assert!(unique.len() > 1, "Button should rotate between players");
 	}
 }
+
+fn heads_up_checkdown(seed: u64, hands: u32) -> Vec<GameEvent> {
+	let config = RunnerConfig {
+		small_blind: 5.0,
+		big_blind: 10.0,
+		starting_stack: 500.0,
+		max_hands: Some(hands),
+		seed: Some(seed),
+		game_id: None,
+		..RunnerConfig::default()
+	};
+
+	let (mut runner, handle, _runtime) = create_runner(config);
+
+	let alice = Arc::new(
+		TestPlayer::new(Seat(0), "Alice")
+			.with_default(PlayerAction::Call { amount: 0.0 })
+	);
+	let bob = Arc::new(
+		TestPlayer::new(Seat(1), "Bob")
+			.with_default(PlayerAction::Call { amount: 0.0 })
+	);
+
+	runner.add_player(alice);
+	runner.add_player(bob);
+	runner.run();
+
+	let mut events = Vec::new();
+	while let Ok(event) = handle.event_rx.try_recv() {
+		events.push(event);
+	}
+	events
+}
+
+#[test]
+fn test_heads_up_button_posts_small_blind() {
+	let events = heads_up_checkdown(70001, 1);
+
+	let button = events
+		.iter()
+		.find_map(|e| match e {
+			GameEvent::HandStarted { button, .. } => Some(*button),
+			_ => None,
+		})
+		.expect("Hand should start");
+
+	let blinds: Vec<(Seat, BlindType)> = events
+		.iter()
+		.filter_map(|e| match e {
+			GameEvent::BlindPosted { seat, blind_type, .. } => Some((*seat, *blind_type)),
+			_ => None,
+		})
+		.collect();
+
+	assert_eq!(blinds.len(), 2, "Exactly two blinds heads-up");
+	assert_eq!(blinds[0], (button, BlindType::Small), "Button posts the small blind first");
+	assert_ne!(blinds[1].0, button, "Other player posts the big blind");
+	assert_eq!(blinds[1].1, BlindType::Big);
+}
+
+#[test]
+fn test_heads_up_button_acts_first_preflop() {
+	let events = heads_up_checkdown(70002, 1);
+
+	let button = events
+		.iter()
+		.find_map(|e| match e {
+			GameEvent::HandStarted { button, .. } => Some(*button),
+			_ => None,
+		})
+		.expect("Hand should start");
+
+	let first_actor = events
+		.iter()
+		.find_map(|e| match e {
+			GameEvent::ActionRequest { seat, .. } => Some(*seat),
+			_ => None,
+		})
+		.expect("Someone should act preflop");
+
+	assert_eq!(first_actor, button, "Button acts first preflop heads-up");
+}
+
+#[test]
+fn test_heads_up_button_acts_last_postflop() {
+	// Enough hands for the button to pass back and forth
+	let events = heads_up_checkdown(70003, 4);
+
+	let mut buttons = Vec::new();
+	let mut street = Street::Preflop;
+	let mut postflop_order: Vec<(usize, Street, Seat)> = Vec::new();
+	for event in &events {
+		match event {
+			GameEvent::HandStarted { button, .. } => {
+				buttons.push(*button);
+				street = Street::Preflop;
+			}
+			GameEvent::StreetChanged { street: s, .. } => street = *s,
+			GameEvent::ActionRequest { seat, .. } if street != Street::Preflop => {
+				postflop_order.push((buttons.len() - 1, street, *seat));
+			}
+			_ => {}
+		}
+	}
+
+	assert_eq!(buttons.len(), 4, "Every hand should start");
+	assert!(buttons.contains(&Seat(0)) && buttons.contains(&Seat(1)), "Both players take the button");
+	for (hand, &button) in buttons.iter().enumerate() {
+		for s in [Street::Flop, Street::Turn, Street::River] {
+			let actors: Vec<Seat> = postflop_order
+				.iter()
+				.filter(|(h, st, _)| *h == hand && *st == s)
+				.map(|(_, _, seat)| *seat)
+				.collect();
+			assert_eq!(actors.len(), 2, "Both players act on {:?} in hand {}", s, hand + 1);
+			assert_ne!(actors[0], button, "Big blind acts first on {:?}", s);
+			assert_eq!(actors[1], button, "Button acts last on {:?}", s);
+		}
+	}
+}
+
+struct StalledPlayer {
+	seat: Seat,
+}
+
+#[async_trait::async_trait]
+impl PlayerPort for StalledPlayer {
+	async fn request_action(
+		&self,
+		_seat: Seat,
+		_valid_actions: ValidActions,
+		_game_state: &GameSnapshot,
+	) -> PlayerResponse {
+		std::future::pending().await
+	}
+
+	fn notify(&self, _event: &GameEvent) {}
+
+	fn seat(&self) -> Seat {
+		self.seat
+	}
+
+	fn name(&self) -> &str {
+		"Stalled"
+	}
+
+	fn is_human(&self) -> bool {
+		true
+	}
+}
+
+#[test]
+fn test_action_timeout_folds_stalled_player() {
+	let config = RunnerConfig {
+		max_hands: Some(1),
+		seed: Some(80001),
+		game_id: None,
+		action_timeout: Some(Duration::from_millis(50)),
+		..RunnerConfig::default()
+	};
+
+	let (mut runner, handle, _runtime) = create_runner(config);
+
+	runner.add_player(Arc::new(StalledPlayer { seat: Seat(0) }));
+	runner.add_player(Arc::new(
+		TestPlayer::new(Seat(1), "Bob")
+			.with_default(PlayerAction::Check)
+	));
+	runner.run();
+
+	let events: Vec<GameEvent> = handle.event_rx.try_iter().collect();
+
+	let time_limit = events
+		.iter()
+		.find_map(|e| match e {
+			GameEvent::ActionRequest { seat: Seat(0), time_limit, .. } => Some(*time_limit),
+			_ => None,
+		})
+		.expect("Stalled player should be asked to act");
+	assert_eq!(time_limit, Some(1), "Time limit is rounded up to whole seconds");
+
+	let stalled_action = events
+		.iter()
+		.find_map(|e| match e {
+			GameEvent::ActionTaken { seat: Seat(0), action, .. } => Some(action.clone()),
+			_ => None,
+		})
+		.expect("Stalled player should still get an action");
+	assert!(matches!(stalled_action, PlayerAction::Fold), "Timed-out player folds");
+	assert!(events.iter().any(|e| matches!(e, GameEvent::GameEnded { .. })));
+}
+
+struct SnapshotRecorder {
+	seat: Seat,
+	snapshots: std::sync::Mutex<Vec<GameSnapshot>>,
+}
+
+#[async_trait::async_trait]
+impl PlayerPort for SnapshotRecorder {
+	async fn request_action(
+		&self,
+		_seat: Seat,
+		_valid_actions: ValidActions,
+		game_state: &GameSnapshot,
+	) -> PlayerResponse {
+		self.snapshots.lock().unwrap().push(game_state.clone());
+		PlayerResponse::Action(PlayerAction::Call { amount: 0.0 })
+	}
+
+	fn notify(&self, _event: &GameEvent) {}
+
+	fn seat(&self) -> Seat {
+		self.seat
+	}
+
+	fn name(&self) -> &str {
+		"Recorder"
+	}
+
+	fn is_human(&self) -> bool {
+		false
+	}
+}
+
+#[test]
+fn test_snapshot_carries_public_state() {
+	let config = RunnerConfig {
+		small_blind: 5.0,
+		big_blind: 10.0,
+		max_hands: Some(1),
+		seed: Some(80002),
+		game_id: None,
+		..RunnerConfig::default()
+	};
+
+	let (mut runner, _handle, _runtime) = create_runner(config);
+
+	let recorder = Arc::new(SnapshotRecorder {
+		seat: Seat(0),
+		snapshots: std::sync::Mutex::new(Vec::new()),
+	});
+	runner.add_player(recorder.clone());
+	runner.add_player(Arc::new(TestPlayer::new(Seat(1), "Bob").with_default(PlayerAction::Check)));
+	runner.add_player(Arc::new(TestPlayer::new(Seat(2), "Carol").with_default(PlayerAction::Check)));
+	runner.run();
+
+	let snapshots = recorder.snapshots.lock().unwrap();
+	let first = snapshots.first().expect("Recorder should be asked to act");
+
+	// Button 0 acts first preflop, facing the big blind
+	assert_eq!(first.street, Street::Preflop);
+	assert_eq!(first.hero_seat, Seat(0));
+	assert_eq!(first.button, Seat(0));
+	assert_eq!(first.blinds.big, 10.0);
+	assert_eq!(first.current_bet, 10.0);
+	assert_eq!(first.valid_actions.call_amount, Some(10.0));
+	assert!(first.hero_cards.is_some());
+
+	let names: Vec<&str> = first.seats.iter().map(|s| s.name.as_str()).collect();
+	assert_eq!(names, vec!["Recorder", "Bob", "Carol"]);
+	let bets: Vec<f32> = first.seats.iter().map(|s| s.total_bet).collect();
+	assert_eq!(bets, vec![0.0, 5.0, 10.0]);
+	assert_eq!(first.pots.iter().map(|p| p.amount).sum::<f32>(), 15.0);
+
+	// Later streets only show that street's actions
+	if let Some(flop) = snapshots.iter().find(|s| s.street == Street::Flop) {
+		assert!(flop.street_actions().all(|a| a.street == Street::Flop));
+		assert_eq!(flop.board.len(), 3);
+	}
+}
+
+/// Calls everything down and sums up every hand for the table.
+struct Commentator;
+
+#[async_trait::async_trait]
+impl PlayerPort for Commentator {
+	async fn request_action(
+		&self,
+		_seat: Seat,
+		valid_actions: ValidActions,
+		_game_state: &GameSnapshot,
+	) -> PlayerResponse {
+		let action = match valid_actions.call_amount {
+			Some(amount) => PlayerAction::Call { amount },
+			None => PlayerAction::Check,
+		};
+		PlayerResponse::Action(action)
+	}
+
+	fn notify(&self, _event: &GameEvent) {}
+
+	fn table_talk(&self, recap: &HandRecap) -> Option<String> {
+		let change = recap.result(Seat(1))?;
+		Some(format!("Hand {}: {:+}", recap.hand_num, change))
+	}
+
+	fn seat(&self) -> Seat {
+		Seat(1)
+	}
+
+	fn name(&self) -> &str {
+		"Commentator"
+	}
+
+	fn is_human(&self) -> bool {
+		false
+	}
+}
+
+#[test]
+fn test_table_talk_follows_hand_end() {
+	let config = RunnerConfig {
+		small_blind: 5.0,
+		big_blind: 10.0,
+		max_hands: Some(3),
+		seed: Some(4242),
+		game_id: None,
+		..RunnerConfig::default()
+	};
+
+	let (mut runner, handle, _runtime) = create_runner(config);
+	runner.add_player(Arc::new(TestPlayer::new(Seat(0), "Alice").with_default(PlayerAction::Check)));
+	runner.add_player(Arc::new(Commentator));
+	runner.run();
+
+	let events: Vec<GameEvent> = handle.event_rx.try_iter().collect();
+	let mut hands = 0;
+	for (i, event) in events.iter().enumerate() {
+		if let GameEvent::HandEnded { results, .. } = event {
+			hands += 1;
+			let change = results.iter().find(|r| r.seat == Seat(1)).unwrap().stack_change;
+			match &events[i + 1] {
+				GameEvent::ChatMessage { sender: ChatSender::Player(seat), text } => {
+					assert_eq!(*seat, Seat(1));
+					assert_eq!(text, &format!("Hand {}: {:+}", hands, change));
+				}
+				other => panic!("Expected table talk after HandEnded, got {:?}", other),
+			}
+		}
+	}
+	assert_eq!(hands, 3);
+}
+
+#[test]
+fn test_golden_views_match_their_snapshots() {
+	let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+	match transparent_poker::golden::check_dir(&dir) {
+		Ok(count) => assert_eq!(count, 3),
+		Err(e) => panic!("{}", e),
+	}
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn test_golden_screens_match() {
+	let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+	match transparent_poker::tui::snapshot::check_screens(&dir) {
+		Ok(count) => assert_eq!(count, 3),
+		Err(e) => panic!("{}", e),
+	}
+}
+
+/// A lobby that answers with whatever it's been handed.
+#[cfg(feature = "tui")]
+struct ScriptedLobby {
+	tables: Vec<transparent_poker::table::TableConfig>,
+	events: std::collections::VecDeque<transparent_poker::lobby::LobbyEvent>,
+}
+
+#[cfg(feature = "tui")]
+impl transparent_poker::lobby::LobbyBackend for ScriptedLobby {
+	fn send(&mut self, _cmd: transparent_poker::lobby::LobbyCommand) {}
+
+	fn poll(&mut self) -> Option<transparent_poker::lobby::LobbyEvent> {
+		self.events.pop_front()
+	}
+
+	fn table_config(&self, table_id: &str) -> Option<transparent_poker::table::TableConfig> {
+		self.tables.iter().find(|t| t.id == table_id).cloned()
+	}
+
+	fn get_bankroll(&self, _player_id: &str) -> f32 {
+		1000.0
+	}
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn test_golden_menu_screens_match() {
+	use crossterm::event::KeyCode;
+	use transparent_poker::config::Preferences;
+	use transparent_poker::achievements::Achievements;
+	use transparent_poker::golden;
+	use transparent_poker::league::Leagues;
+	use transparent_poker::lobby::{LobbyEvent, LobbyPlayer, TableSummary};
+	use transparent_poker::menu::Menu;
+	use transparent_poker::theme::Theme;
+	use transparent_poker::tui::snapshot::{HEIGHT, WIDTH};
+
+	let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+	let tables = transparent_poker::table::load_tables_from(&root.join("config").join("tables.toml")).unwrap();
+	let listed = LobbyEvent::TablesListed(tables.iter().take(6).map(TableSummary::from).collect());
+	let lobby = ScriptedLobby { tables: tables.clone(), events: [listed].into() };
+	let mut menu = Menu::new(lobby, "alice".to_string(), Theme::default(), "default".to_string())
+		.with_preferences(Preferences::default());
+
+	let dir = root.join("tests").join("golden").join("menu");
+	let check = |name: &str, menu: &mut Menu<ScriptedLobby>| {
+		golden::compare(&dir.join(format!("{}.screen", name)), &menu.screen(WIDTH, HEIGHT))
+	};
+	let mut failures = Vec::new();
+	failures.extend(check("tables", &mut menu).err());
+
+	menu.handle_key(KeyCode::Down);
+	menu.handle_key(KeyCode::Char('i'));
+	failures.extend(check("table_info", &mut menu).err());
+	menu.handle_key(KeyCode::Esc);
+
+	menu.handle_key(KeyCode::Char('s'));
+	failures.extend(check("settings", &mut menu).err());
+
+	let mut menu = menu.into_backend();
+	let player = |seat: usize, name: &str, is_human: bool| LobbyPlayer {
+		seat: Some(Seat(seat)),
+		id: name.to_lowercase(),
+		name: name.to_string(),
+		is_host: seat == 0,
+		is_human,
+		is_ready: false,
+		strategy: (!is_human).then(|| "lag".to_string()),
+		bankroll: Some(1000.0),
+	};
+	menu.events.push_back(LobbyEvent::TableJoined {
+		table_id: tables[1].id.clone(),
+		table_name: tables[1].name.clone(),
+		seat: Seat(0),
+		players: vec![player(0, "Alice", true), player(1, "Lisa", false), player(2, "Lonny", false)],
+		min_players: tables[1].min_players,
+		max_players: tables[1].max_players,
+	});
+	let mut menu = Menu::new(menu, "alice".to_string(), Theme::default(), "default".to_string())
+		.with_preferences(Preferences::default());
+	failures.extend(check("lobby", &mut menu).err());
+
+	let results = std::env::temp_dir().join(format!("poker-golden-league-{}", std::process::id()));
+	let _ = std::fs::remove_dir_all(&results);
+	let config = Leagues::parse(
+		"[[leagues]]\nid = \"friday\"\nname = \"Friday Night\"\nmembers = [\"alice\", \"bob\", \"carol\"]\npoints = [10, 6, 3]\n\
+		schedule = [{ table = \"olive-field\", date = \"2099-01-02\" }, { table = \"olive-field\", date = \"2099-01-09\" }]\n",
+	)
+	.unwrap();
+	let leagues = Leagues::new(config, &results);
+	let finished = [("bob".to_string(), 1), ("lisa".to_string(), 2), ("alice".to_string(), 3)];
+	let date = chrono::NaiveDate::from_ymd_opt(2099, 1, 2).unwrap();
+	leagues.record("olive-field", date, transparent_poker::events::GameId(1), &finished).unwrap();
+	let mut menu = Menu::new(menu.into_backend(), "alice".to_string(), Theme::default(), "default".to_string())
+		.with_preferences(Preferences::default())
+		.with_leagues(leagues);
+	menu.handle_key(KeyCode::Char('l'));
+	failures.extend(check("league", &mut menu).err());
+	menu.handle_key(KeyCode::Esc);
+
+	let saved = results.join("achievements.toml");
+	std::fs::write(&saved, "hands_won = 37\n\n[unlocked]\nroyal-flush = \"2026-10-02\"\n").unwrap();
+	let mut menu = menu.with_achievements(Achievements::load_from(&saved).unwrap());
+	menu.handle_key(KeyCode::Char('p'));
+	failures.extend(check("profile", &mut menu).err());
+	let _ = std::fs::remove_dir_all(&results);
+
+	assert!(failures.is_empty(), "{}", failures.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n\n"));
+}
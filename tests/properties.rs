@@ -0,0 +1,396 @@
+//! Engine invariants over random tables and random players: whatever the
+//! seats try, chips are only ever lost to the rake, stacks never go
+//! negative, the betting structure's caps hold, each hand has one button
+//! and every hand ends. Games run on a seeded `Session`, so a failing case
+//! replays exactly from what proptest prints.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use proptest::prelude::*;
+use proptest::test_runner::FileFailurePersistence;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use transparent_poker::engine::{Session, SessionConfig, Step};
+use transparent_poker::events::{
+	BlindType, GameEndReason, GameEvent, PlayerAction, Position, RaiseOptions, Seat, Street, ValidActions,
+};
+use transparent_poker::players::{GameSnapshot, PlayerPort, PlayerResponse};
+use transparent_poker::table::BettingStructure;
+
+const EPSILON: f32 = 0.01;
+
+/// How a seat picks its actions: a weight for each kind of choice.
+#[derive(Debug, Clone)]
+struct Policy {
+	fold: u32,
+	passive: u32,
+	min_raise: u32,
+	any_raise: u32,
+	all_in: u32,
+	/// Anything at all, legal or not, for the validator to sort out.
+	nonsense: u32,
+	asks_to_show: bool,
+	auto_muck: bool,
+}
+
+struct RandomPlayer {
+	seat: Seat,
+	name: String,
+	policy: Policy,
+	rng: Mutex<StdRng>,
+}
+
+impl RandomPlayer {
+	fn choose(&self, valid: &ValidActions) -> PlayerAction {
+		let mut rng = self.rng.lock().unwrap();
+		if valid.can_show {
+			return if rng.random_bool(0.5) { PlayerAction::Show } else { PlayerAction::Muck };
+		}
+		let p = &self.policy;
+		let weights = [p.fold, p.passive, p.min_raise, p.any_raise, p.all_in, p.nonsense];
+		let total = weights.iter().sum::<u32>().max(1);
+		let mut pick = rng.random_range(0..total);
+		let kind = weights.iter().position(|w| {
+			if pick < *w {
+				return true;
+			}
+			pick -= w;
+			false
+		});
+		let (min, max) = match valid.raise_options {
+			Some(RaiseOptions::Fixed { amount }) => (amount, amount),
+			Some(RaiseOptions::Variable { min_raise, max_raise }) => (min_raise, max_raise),
+			None => (valid.all_in_amount, valid.all_in_amount),
+		};
+		match kind {
+			Some(0) => PlayerAction::Fold,
+			Some(2) => PlayerAction::Raise { amount: min },
+			Some(3) => PlayerAction::Raise { amount: rng.random_range(min..=max).round() },
+			Some(4) => PlayerAction::AllIn { amount: valid.all_in_amount },
+			Some(5) => {
+				let amount = rng.random_range(-50.0..2.0 * max + 50.0);
+				match rng.random_range(0..9) {
+					0 => PlayerAction::Fold,
+					1 => PlayerAction::Check,
+					2 => PlayerAction::Call { amount },
+					3 => PlayerAction::Bet { amount },
+					4 => PlayerAction::Raise { amount },
+					5 => PlayerAction::AllIn { amount },
+					6 => PlayerAction::Timeout,
+					7 => PlayerAction::Show,
+					_ => PlayerAction::Muck,
+				}
+			}
+			_ => match valid.call_amount {
+				Some(amount) => PlayerAction::Call { amount },
+				None => PlayerAction::Check,
+			},
+		}
+	}
+}
+
+#[async_trait]
+impl PlayerPort for RandomPlayer {
+	async fn request_action(&self, _seat: Seat, valid_actions: ValidActions, _game_state: &GameSnapshot) -> PlayerResponse {
+		PlayerResponse::Action(self.choose(&valid_actions))
+	}
+
+	fn notify(&self, _event: &GameEvent) {}
+
+	fn auto_muck(&self) -> bool {
+		self.policy.auto_muck
+	}
+
+	fn asks_to_show(&self) -> bool {
+		self.policy.asks_to_show
+	}
+
+	fn seat(&self) -> Seat {
+		self.seat
+	}
+
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	fn is_human(&self) -> bool {
+		false
+	}
+}
+
+fn policy() -> impl Strategy<Value = Policy> {
+	(0..10u32, 0..10u32, 0..5u32, 0..5u32, 0..3u32, 0..3u32, any::<bool>(), any::<bool>()).prop_map(
+		|(fold, passive, min_raise, any_raise, all_in, nonsense, asks_to_show, auto_muck)| Policy {
+			fold,
+			passive,
+			min_raise,
+			any_raise,
+			all_in,
+			nonsense,
+			asks_to_show,
+			auto_muck,
+		},
+	)
+}
+
+fn betting() -> impl Strategy<Value = BettingStructure> {
+	prop_oneof![
+		Just(BettingStructure::NoLimit),
+		Just(BettingStructure::PotLimit),
+		Just(BettingStructure::FixedLimit),
+	]
+}
+
+fn table() -> impl Strategy<Value = SessionConfig> {
+	(
+		1..=25u32,
+		10..=200u32,
+		betting(),
+		1..=5u32,
+		prop_oneof![Just(0.0f32), 0.01f32..0.1],
+		proptest::option::of(1..=20u32),
+		any::<bool>(),
+		1..=30u32,
+		any::<u64>(),
+	)
+		.prop_map(|(small, stack_bb, betting, max_raises, rake, cap, no_flop_no_drop, hands, seed)| SessionConfig {
+			small_blind: small as f32,
+			big_blind: 2.0 * small as f32,
+			starting_stack: (2 * small * stack_bb) as f32,
+			betting,
+			max_raises_per_round: max_raises,
+			rake_percent: rake,
+			rake_cap: cap.map(|c| c as f32),
+			no_flop_no_drop,
+			max_hands: Some(hands),
+			seed,
+		})
+}
+
+/// Plays the game out, returning every event and the step that ended it.
+fn play(config: &SessionConfig, policies: &[Policy], seed: u64) -> (Vec<GameEvent>, Vec<f32>, Step) {
+	let mut session = Session::new(config.clone());
+	for (i, policy) in policies.iter().enumerate() {
+		let player = RandomPlayer {
+			seat: Seat(i),
+			name: format!("P{}", i),
+			policy: policy.clone(),
+			rng: Mutex::new(StdRng::seed_from_u64(seed.wrapping_add(i as u64))),
+		};
+		session.add_player(Arc::new(player)).unwrap();
+	}
+	let mut events = Vec::new();
+	let max_hands = config.max_hands.unwrap_or(0);
+	let mut last = Step::GameOver;
+	for _ in 0..=max_hands + 1 {
+		last = session.step();
+		events.extend(session.drain_events());
+		if last != Step::HandComplete {
+			break;
+		}
+	}
+	(events, session.stacks().to_vec(), last)
+}
+
+/// A hand's events between its `HandStarted` and `HandEnded`.
+fn hands(events: &[GameEvent]) -> Vec<&[GameEvent]> {
+	let mut hands = Vec::new();
+	let mut start = None;
+	for (i, event) in events.iter().enumerate() {
+		match event {
+			GameEvent::HandStarted { .. } => start = Some(i),
+			GameEvent::HandEnded { .. } => {
+				if let Some(s) = start.take() {
+					hands.push(&events[s..=i]);
+				}
+			}
+			_ => {}
+		}
+	}
+	hands
+}
+
+fn is_legal(action: &PlayerAction, valid: &ValidActions) -> bool {
+	let close = |a: f32, b: f32| (a - b).abs() < EPSILON;
+	let raise_reaches = |amount: f32| match valid.raise_options {
+		Some(RaiseOptions::Fixed { amount: to }) => close(amount, to),
+		Some(RaiseOptions::Variable { min_raise, max_raise }) => {
+			amount >= min_raise - EPSILON && amount <= max_raise + EPSILON
+		}
+		None => false,
+	};
+	match action {
+		PlayerAction::Fold => valid.can_fold,
+		PlayerAction::Check => valid.can_check,
+		PlayerAction::Call { amount } => valid.call_amount.is_some_and(|call| close(call, *amount)),
+		PlayerAction::Bet { amount } | PlayerAction::Raise { amount } => {
+			raise_reaches(*amount) && *amount < valid.all_in_amount - EPSILON
+		}
+		PlayerAction::AllIn { amount } => {
+			close(*amount, valid.all_in_amount) && (valid.can_all_in || raise_reaches(*amount))
+		}
+		PlayerAction::Timeout | PlayerAction::Show | PlayerAction::Muck => false,
+	}
+}
+
+/// Checks one hand's betting against its requests and the structure's
+/// caps, returning the chips the hand lost to the rake.
+fn check_hand(hand: &[GameEvent], config: &SessionConfig) -> Result<f32, TestCaseError> {
+	let GameEvent::HandStarted { seats, button, .. } = &hand[0] else {
+		unreachable!()
+	};
+	// Under the dead-button rule the button can sit on a seat that just
+	// busted, and then no one plays from it
+	let buttons = seats.iter().filter(|s| s.position == Position::Button).map(|s| s.seat).collect::<Vec<_>>();
+	let live = seats.get(button.0).is_some_and(|s| s.is_active);
+	prop_assert!(button.0 < seats.len(), "button on seat {}", button.0);
+	prop_assert_eq!(buttons, if live { vec![*button] } else { vec![] }, "one button, on seat {}", button.0);
+	let big_blinds = seats.iter().filter(|s| s.position == Position::BigBlind).count();
+	prop_assert_eq!(big_blinds, 1, "one big blind");
+
+	let before = seats.iter().map(|s| s.stack).sum::<f32>();
+	let mut requests: HashMap<Seat, ValidActions> = HashMap::new();
+	let mut street = Street::Preflop;
+	let mut street_bets: HashMap<Seat, f32> = HashMap::new();
+	let mut current_bet = 0.0f32;
+	let mut pot = 0.0f32;
+	let mut raises = 0;
+	let mut actions = 0;
+	let mut awarded = 0.0f32;
+	let mut saw_flop = false;
+
+	for event in hand {
+		match event {
+			GameEvent::BlindPosted { seat, blind_type, amount } => {
+				prop_assert!(*amount >= 0.0);
+				*street_bets.entry(*seat).or_default() += amount;
+				current_bet = current_bet.max(street_bets[seat]);
+				// A short big blind still makes the others call a whole one
+				if *blind_type == BlindType::Big {
+					current_bet = current_bet.max(config.big_blind);
+				}
+				pot += amount;
+			}
+			GameEvent::StreetChanged { street: next, .. } => {
+				street = *next;
+				saw_flop |= street != Street::Preflop;
+				street_bets.clear();
+				current_bet = 0.0;
+				raises = 0;
+			}
+			GameEvent::ActionRequest { seat, valid_actions, .. } => {
+				requests.insert(*seat, valid_actions.clone());
+			}
+			GameEvent::ActionTaken { seat, action, stack_after, pot_after, .. } => {
+				actions += 1;
+				prop_assert!(*stack_after >= -EPSILON, "seat {} went to {}", seat.0, stack_after);
+				let Some(valid) = requests.remove(seat) else {
+					return Err(TestCaseError::fail(format!("seat {} acted unasked: {:?}", seat.0, action)));
+				};
+				prop_assert!(is_legal(action, &valid), "seat {} took {:?} from {:?}", seat.0, action, valid);
+
+				let bet = street_bets.get(seat).copied().unwrap_or(0.0);
+				match action {
+					PlayerAction::Bet { amount } | PlayerAction::Raise { amount } => {
+						raises += 1;
+						prop_assert!(raises <= config.max_raises_per_round, "raise {} on {:?}", raises, street);
+						match config.betting {
+							BettingStructure::FixedLimit => {
+								let size = match street {
+									Street::Preflop | Street::Flop => config.big_blind,
+									_ => 2.0 * config.big_blind,
+								};
+								prop_assert!((amount - current_bet - size).abs() < EPSILON, "limit raise to {}", amount);
+							}
+							BettingStructure::PotLimit => {
+								let limit = current_bet + pot + (current_bet - bet);
+								prop_assert!(*amount <= limit + EPSILON, "pot-limit raise to {} over {}", amount, limit);
+							}
+							BettingStructure::NoLimit => {}
+						}
+					}
+					PlayerAction::AllIn { amount } if config.betting == BettingStructure::PotLimit => {
+						let limit = current_bet + pot + (current_bet - bet);
+						prop_assert!(*amount <= limit + EPSILON, "pot-limit all-in to {} over {}", amount, limit);
+					}
+					_ => {}
+				}
+				let total = match action {
+					PlayerAction::Call { amount } => bet + amount,
+					PlayerAction::Bet { amount } | PlayerAction::Raise { amount } | PlayerAction::AllIn { amount } => *amount,
+					_ => bet,
+				};
+				street_bets.insert(*seat, total);
+				current_bet = current_bet.max(total);
+				prop_assert!((pot + total - bet - pot_after).abs() < EPSILON, "pot {} after {:?}", pot_after, action);
+				pot = *pot_after;
+			}
+			GameEvent::PotAwarded { amount, .. } => {
+				prop_assert!(*amount >= 0.0);
+				awarded += amount;
+			}
+			_ => {}
+		}
+	}
+
+	let players = seats.iter().filter(|s| s.is_active).count() as u32;
+	let most = 4 * players * (config.max_raises_per_round + 2);
+	prop_assert!(actions <= most, "{} actions in a hand", actions);
+
+	let GameEvent::HandEnded { results, .. } = &hand[hand.len() - 1] else {
+		unreachable!()
+	};
+	prop_assert!(results.iter().all(|r| r.final_stack >= -EPSILON), "{:?}", results);
+	let after = results.iter().map(|r| r.final_stack).sum::<f32>();
+	let rake = before - after;
+	prop_assert!(rake >= -EPSILON, "chips appeared: {} before, {} after", before, after);
+	let most_rake = config.rake_percent * (awarded + rake) + 0.005;
+	prop_assert!(rake <= most_rake + EPSILON, "rake {} over {}", rake, most_rake);
+	if let Some(cap) = config.rake_cap {
+		prop_assert!(rake <= cap + EPSILON, "rake {} over the cap {}", rake, cap);
+	}
+	if config.no_flop_no_drop && !saw_flop {
+		prop_assert!(rake.abs() < EPSILON, "rake {} with no flop", rake);
+	}
+	Ok(rake)
+}
+
+proptest! {
+	#![proptest_config(ProptestConfig {
+		cases: 200,
+		failure_persistence: Some(Box::new(FileFailurePersistence::WithSource("regressions"))),
+		..ProptestConfig::default()
+	})]
+
+	#[test]
+	fn test_random_games_keep_the_invariants(
+		config in table(),
+		policies in proptest::collection::vec(policy(), 2..=6),
+		seed in any::<u64>(),
+	) {
+		let (events, stacks, last) = play(&config, &policies, seed);
+		prop_assert_eq!(last, Step::GameOver, "the game ends within its hands");
+		let ended = events.iter().find_map(|e| match e {
+			GameEvent::GameEnded { reason, .. } => Some(*reason),
+			_ => None,
+		});
+		prop_assert_ne!(ended, Some(GameEndReason::Error));
+		prop_assert!(ended.is_some(), "no GameEnded");
+
+		let started = events.iter().filter(|e| matches!(e, GameEvent::HandStarted { .. })).count();
+		let hands = hands(&events);
+		prop_assert_eq!(hands.len(), started, "every hand ends");
+		prop_assert!(hands.len() as u32 <= config.max_hands.unwrap_or(u32::MAX));
+
+		let mut rake = 0.0;
+		for hand in &hands {
+			rake += check_hand(hand, &config)?;
+		}
+		prop_assert!(stacks.iter().all(|s| *s >= 0.0), "{:?}", stacks);
+		let start = config.starting_stack * policies.len() as f32;
+		let total = stacks.iter().sum::<f32>();
+		prop_assert!((start - rake - total).abs() < EPSILON * (1 + hands.len()) as f32, "{} dealt, {} raked, {} left", start, rake, total);
+	}
+}